@@ -26,6 +26,11 @@ impl Actor for SingleCopyActor {
             Get(req_id) => {
                 o.send(src, GetOk(req_id, **state));
             }
+            Cas(req_id, expected, new) => {
+                let swapped = **state == expected;
+                if swapped { *state.to_mut() = new; }
+                o.send(src, CasOk(req_id, swapped));
+            }
             _ => {}
         }
     }
@@ -34,11 +39,13 @@ impl Actor for SingleCopyActor {
 #[cfg(test)]
 #[test]
 fn can_model_single_copy_register() {
-    use stateright::actor::DuplicatingNetwork;
+    use stateright::actor::{DuplicatingNetwork, LossyNetwork};
     use stateright::actor::SystemAction::Deliver;
+    use stateright::semantics::SequentialConsistencyTester;
+    use stateright::semantics::register::Register;
 
     // Linearizable if only one server. DFS for this one.
-    let checker = RegisterTestSystem {
+    let checker = RegisterTestSystem::<_, _> {
         servers: vec![SingleCopyActor],
         client_count: 2,
         duplicating_network: DuplicatingNetwork::No,
@@ -52,8 +59,41 @@ fn can_model_single_copy_register() {
     ]);
     assert_eq!(checker.generated_count(), 180);
 
+    // Memoizing the history does not change the outcome.
+    let checker = RegisterTestSystem::<_, _> {
+        servers: vec![SingleCopyActor],
+        client_count: 2,
+        duplicating_network: DuplicatingNetwork::No,
+        memoize_history: true,
+        .. Default::default()
+    }.into_model().checker().spawn_dfs().join();
+    checker.assert_properties();
+    assert_eq!(checker.generated_count(), 180);
+
+    // Still linearizable if clients give up on lost requests and move on.
+    let checker = RegisterTestSystem::<_, _> {
+        servers: vec![SingleCopyActor],
+        client_count: 2,
+        lossy_network: LossyNetwork::Yes,
+        duplicating_network: DuplicatingNetwork::No,
+        client_timeouts: true,
+        .. Default::default()
+    }.into_model().checker().spawn_dfs().join();
+    checker.assert_properties();
+
+    // Also linearizable if clients compare-and-swap.
+    let checker = RegisterTestSystem::<_, _> {
+        servers: vec![SingleCopyActor],
+        client_count: 2,
+        duplicating_network: DuplicatingNetwork::No,
+        client_cas: true,
+        .. Default::default()
+    }.into_model().checker().spawn_dfs().join();
+    checker.assert_properties();
+    checker.assert_any_discovery("value chosen");
+
     // Otherwise (if more than one server) then not linearizabile. BFS this time.
-    let checker = RegisterTestSystem {
+    let checker = RegisterTestSystem::<_, _> {
         servers: vec![SingleCopyActor, SingleCopyActor],
         client_count: 2,
         duplicating_network: DuplicatingNetwork::No,
@@ -71,7 +111,16 @@ fn can_model_single_copy_register() {
         Deliver { src: Id::from(2), dst: Id::from(0), msg: Put(2, 'A') },
         Deliver { src: Id::from(3), dst: Id::from(0), msg: Get(6) },
     ]);
-    assert_eq!(checker.generated_count(), 20);
+    assert_eq!(checker.generated_count(), 24);
+
+    // Nor sequentially consistent, as a client can miss its own write.
+    let checker = RegisterTestSystem::<_, _, SequentialConsistencyTester<Id, Register<TestValue>>> {
+        servers: vec![SingleCopyActor, SingleCopyActor],
+        client_count: 2,
+        duplicating_network: DuplicatingNetwork::No,
+        .. Default::default()
+    }.into_model().checker().spawn_bfs().join();
+    checker.assert_any_discovery("sequentially consistent");
 }
 
 fn main() {
@@ -108,7 +157,7 @@ fn main() {
                 26, value_t!(args, "client_count", u8).expect("client count missing"));
             println!("Model checking a single-copy register with {} clients.",
                      client_count);
-            RegisterTestSystem {
+            RegisterTestSystem::<_, _> {
                 servers: vec![SingleCopyActor],
                 client_count,
                 duplicating_network: DuplicatingNetwork::No,
@@ -124,7 +173,7 @@ fn main() {
             println!(
                 "Exploring state space for single-copy register with {} clients on {}.",
                 client_count, address);
-            RegisterTestSystem {
+            RegisterTestSystem::<_, _> {
                 servers: vec![SingleCopyActor],
                 client_count,
                 duplicating_network: DuplicatingNetwork::No,
@@ -1,6 +1,8 @@
 //! This module provides an [Actor] trait, which can be model checked by implementing
 //! [System] and calling [System::into_model()]. You can also [`spawn()`] the actor
-//! in which case it will communicate over a UDP socket.
+//! in which case it will communicate over a UDP socket, or run it on an async executor via
+//! [`spawn_tokio()`]. A [`SimRuntime`] instead runs actors deterministically in memory, and
+//! events recorded via [`SpawnOptions::record`] can be [`replay()`]ed.
 //!
 //! ## Example
 //!
@@ -14,7 +16,6 @@
 //! use stateright::actor::*;
 //! use std::borrow::Cow;
 //! use std::iter::FromIterator;
-//! use std::sync::Arc;
 //!
 //! /// The actor needs to know whether it should "bootstrap" by sending the first
 //! /// message. If so, it needs to know to which peer the message should be sent.
@@ -85,16 +86,26 @@
 //!     SystemAction::Deliver { src: Id::from(1), dst: Id::from(0), msg: MsgWithTimestamp(1) },
 //!     SystemAction::Deliver { src: Id::from(0), dst: Id::from(1), msg: MsgWithTimestamp(2) },
 //! ]);
-//! assert_eq!(
-//!     checker.discovery("less than max").unwrap().last_state().actor_states,
-//!     vec![Arc::new(Timestamp(2)), Arc::new(Timestamp(3))]);
+//! assert_actor_state!(checker, "less than max", actor 1, matches Timestamp(3));
+//! assert_actor_state!(checker, "less than max", actor 0, matches Timestamp(t) if *t < 3);
 //! ```
 //!
 //! [Additional examples](https://github.com/stateright/stateright/tree/master/examples)
 //! are available in the repository.
 
+mod interceptor;
+mod metadata;
+mod nested;
+mod network;
+mod quorum;
+mod record;
+mod registry;
+mod scenario;
+mod sequence_diagram;
+mod sim;
 mod system;
 mod spawn;
+mod spawn_tokio;
 use std::borrow::Cow;
 use std::hash::Hash;
 use std::fmt::{Debug, Display, Formatter};
@@ -104,9 +115,23 @@ use std::ops::Range;
 
 #[cfg(test)]
 pub mod actor_test_util;
+pub mod causal_delivery;
+#[cfg(feature = "consensus")]
+pub mod consensus;
+pub mod env;
+pub mod kv;
 pub mod ordered_reliable_link;
 pub mod register;
+pub use interceptor::*;
+pub use metadata::*;
+pub use network::*;
+pub use quorum::*;
+pub use record::*;
+pub use registry::*;
+pub use scenario::*;
+pub use sim::*;
 pub use spawn::*;
+pub use spawn_tokio::*;
 pub use system::*;
 
 /// Uniquely identifies an [`Actor`]. Encodes the socket address for spawned
@@ -139,6 +164,15 @@ pub enum Command<Msg> {
     SetTimer(Range<Duration>),
     /// Send a message to a destination.
     Send(Id, Msg),
+    /// Send a message to multiple destinations, either atomically or one destination at a time
+    /// per [`System::atomic_broadcast`]. Runtimes send the message to each destination in turn.
+    Broadcast(Vec<Id>, Msg),
+    /// Spawn a new actor as described by a specification. See [`Actor::spawned_actor`].
+    Spawn(Msg),
+    /// Request notification when a peer crashes or stops. See [`Actor::on_peer_down`].
+    Monitor(Id),
+    /// Choose a value from a range at random. See [`Actor::on_random`].
+    ChooseRandom(Range<u64>),
 }
 
 /// Holds [`Command`]s output by an actor.
@@ -157,6 +191,29 @@ impl<A: Actor> Out<A> {
         self.0.append(&mut other.0)
     }
 
+    /// Runs a handler of a nested actor whose messages are embedded in this actor's messages
+    /// (such as via [`nested_msg!`]), then records the [`Command`]s output by the nested actor,
+    /// converting their messages. Returns the result of the handler.
+    ///
+    /// [`nested_msg!`]: crate::nested_msg
+    pub fn nested<B, R>(&mut self, handler: impl FnOnce(&mut Out<B>) -> R) -> R
+    where B: Actor,
+          B::Msg: Into<A::Msg>,
+    {
+        let mut nested_out = Out::new();
+        let result = handler(&mut nested_out);
+        self.0.extend(nested_out.0.into_iter().map(|command| match command {
+            Command::CancelTimer => Command::CancelTimer,
+            Command::SetTimer(duration) => Command::SetTimer(duration),
+            Command::Send(dst, msg) => Command::Send(dst, msg.into()),
+            Command::Broadcast(dsts, msg) => Command::Broadcast(dsts, msg.into()),
+            Command::Spawn(spec) => Command::Spawn(spec.into()),
+            Command::Monitor(peer) => Command::Monitor(peer),
+            Command::ChooseRandom(range) => Command::ChooseRandom(range),
+        }));
+        result
+    }
+
     /// Records the need to set the timer. See [`Actor::on_timeout`].
     pub fn set_timer(&mut self, duration: Range<Duration>) {
         self.0.push(Command::SetTimer(duration));
@@ -172,13 +229,39 @@ impl<A: Actor> Out<A> {
         self.0.push(Command::Send(recipient, msg));
     }
 
-    /// Records the need to send a message to multiple recipients. See [`Actor::on_msg`].
+    /// Records the need to send a message to the actor with the specified name. See
+    /// [`Registry`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name is not in the registry.
+    pub fn send_named(&mut self, registry: &Registry, name: &str, msg: A::Msg) {
+        let recipient = registry.id(name)
+            .unwrap_or_else(|| panic!("Unknown actor name. name={}", name));
+        self.send(recipient, msg);
+    }
+
+    /// Records the need to send a message to multiple recipients. See [`Actor::on_msg`] and
+    /// [`System::atomic_broadcast`].
     pub fn broadcast(&mut self, recipients: &[Id], msg: &A::Msg)
     where A::Msg: Clone
     {
-        for recipient in recipients {
-            self.send(*recipient, msg.clone());
-        }
+        self.0.push(Command::Broadcast(recipients.to_vec(), msg.clone()));
+    }
+
+    /// Records the need to spawn a new actor. See [`Actor::spawned_actor`].
+    pub fn spawn(&mut self, spec: A::Msg) {
+        self.0.push(Command::Spawn(spec));
+    }
+
+    /// Records the need to monitor a peer. See [`Actor::on_peer_down`].
+    pub fn monitor(&mut self, peer: Id) {
+        self.0.push(Command::Monitor(peer));
+    }
+
+    /// Records the need to choose a value from a range at random. See [`Actor::on_random`].
+    pub fn choose_random(&mut self, range: Range<u64>) {
+        self.0.push(Command::ChooseRandom(range));
     }
 }
 
@@ -252,6 +335,48 @@ pub trait Actor: Sized {
     fn on_timeout(&self, _id: Id, _state: &mut Cow<Self::State>, _o: &mut Out<Self>) {
         // no-op by default
     }
+
+    /// Indicates the next state and commands when a configuration update (such as a new peer set
+    /// or tuning parameter) is received. Unlike [`Actor::on_msg`], the update originates outside
+    /// the system: from a [`ConfigUpdater`] for spawned actors, or from
+    /// [`System::config_updates`] when model checking.
+    fn on_config_update(&self, _id: Id, _state: &mut Cow<Self::State>, _config: Self::Msg, _o: &mut Out<Self>) {
+        // no-op by default
+    }
+
+    /// Indicates the next state and commands when a monitored peer is down, which happens at most
+    /// once per [`Out::monitor`] call. A peer is down once it crashes when model checking (see
+    /// [`System::max_crashes`]) or once its thread stops when spawned, such as due to a panic.
+    /// Monitoring a peer that is already down results in an immediate notification when model
+    /// checking.
+    fn on_peer_down(&self, _id: Id, _state: &mut Cow<Self::State>, _peer: Id, _o: &mut Out<Self>) {
+        // no-op by default
+    }
+
+    /// Indicates the next state and commands when a peer restarted, in which case messages
+    /// exchanged with the peer's previous incarnation may have been lost. Only called by links
+    /// that track sessions, such as [`ordered_reliable_link`].
+    fn on_peer_reset(&self, _id: Id, _state: &mut Cow<Self::State>, _peer: Id, _o: &mut Out<Self>) {
+        // no-op by default
+    }
+
+    /// Indicates the next state and commands once a value is chosen at random per
+    /// [`Out::choose_random`], which lets randomized protocols (such as Ben-Or consensus or
+    /// randomized backoff) be both checked and run. The model checker explores every value in
+    /// the range, whereas runtimes choose one uniformly at random. Values are chosen after the
+    /// current handler returns, in the order requested, and requests with empty ranges are
+    /// ignored.
+    fn on_random(&self, _id: Id, _state: &mut Cow<Self::State>, _value: u64, _o: &mut Out<Self>) {
+        // no-op by default
+    }
+
+    /// Indicates the actor to create when this actor outputs a [`Command::Spawn`] with a
+    /// specified message. The new actor is assigned the next available [`Id`] and then started
+    /// as usual. Returns `None` by default, in which case the command is ignored. See
+    /// [`Out::spawn`].
+    fn spawned_actor(&self, _spec: &Self::Msg) -> Option<Self> {
+        None
+    }
 }
 
 /// Implemented only for rustdoc tests. Do not take a dependency on this. It will likely be removed
@@ -263,7 +388,27 @@ impl Actor for () {
     fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
 }
 
-/// Indicates the number of nodes that constitute a majority for a particular cluster size.
+/// Panics unless a [`Checker`](crate::Checker) has a discovery for the named property and the
+/// state of a particular actor at the end of the discovery matches a pattern (with an optional
+/// guard), which avoids comparing every actor's state when only one is relevant. See the
+/// [module example](crate::actor).
+#[macro_export]
+macro_rules! assert_actor_state {
+    ($checker:expr, $name:expr, actor $index:expr, matches $pattern:pat $(if $guard:expr)?) => {{
+        let discovery = $crate::Checker::assert_any_discovery(&$checker, $name);
+        let index: usize = $index;
+        match discovery.last_state().actor_states.get(index).map(|s| &**s) {
+            Some($pattern) $(if $guard)? => {}
+            Some(state) => panic!(
+                "Actor {} state for discovery '{}' does not match `{}`. state={:?}",
+                index, $name, stringify!($pattern $(if $guard)?), state),
+            None => panic!("Actor {} not found for discovery '{}'.", index, $name),
+        }
+    }};
+}
+
+/// Indicates the number of nodes that constitute a majority for a particular cluster size. See
+/// [`QuorumSet`] for other kinds of quorums.
 pub fn majority(cluster_size: usize) -> usize {
     cluster_size / 2 + 1
 }
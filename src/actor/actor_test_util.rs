@@ -13,6 +13,7 @@ pub mod ping_pong {
     pub struct PingPongCount(pub u32);
 
     #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    #[derive(serde::Serialize, serde::Deserialize)]
     pub enum PingPongMsg { Ping(u32), Pong(u32) }
 
     impl Actor for PingPongActor {
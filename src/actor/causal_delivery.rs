@@ -0,0 +1,353 @@
+//! A causal delivery layer based on the vector clock algorithm of Birman, Schiper, and Stephenson
+//! (as described in "Introduction to Reliable and Secure Distributed Programming" by Cachin,
+//! Guerraoui, and Rodrigues).
+//!
+//! A message is only delivered to the wrapped actor once every message that causally precedes it
+//! has been delivered, where a message sent after delivering (or sending) another message causally
+//! follows that message. The layer does not resend lost messages, so it should be paired with a
+//! network that does not lose messages (or with an [`ordered_reliable_link`]).
+//!
+//! [`ordered_reliable_link`]: crate::actor::ordered_reliable_link
+
+use crate::actor::*;
+use crate::util::VectorClock;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Wraps an actor with logic to deliver messages in causal order among a group of `peers`.
+///
+/// Each message is tagged with the sender's [`VectorClock`], which counts the messages sent by
+/// each group member that the sender had delivered (or sent) beforehand. As in causal broadcast,
+/// every message is announced to the whole group: group members other than the recipients are
+/// sent the clock without the message, which lets them deliver later messages that depend on it.
+#[derive(Clone)]
+pub struct CausalDeliveryActor<A: Actor> {
+    pub peers: Vec<Id>,
+    pub wrapped_actor: A,
+}
+
+/// An envelope for causal delivery messages, which carries the wrapped message unless the
+/// recipient is only being informed that the sender sent a message to other recipients.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CausalMsg<Msg> {
+    pub clock: VectorClock<Id>,
+    pub msg: Option<Msg>,
+}
+
+/// Maintains state for causal delivery.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CausalState<Msg, State> {
+    /// Counts the messages of each group member that have been delivered, or in the case of this
+    /// actor, sent.
+    clock: VectorClock<Id>,
+    /// Messages that arrived before their causal predecessors, keyed by sender and sequence.
+    msgs_pending_delivery: BTreeMap<(Id, u64), CausalMsg<Msg>>,
+
+    wrapped_state: State,
+}
+
+impl<Msg, State> CausalState<Msg, State> {
+    /// The state of the wrapped actor.
+    pub fn wrapped_state(&self) -> &State {
+        &self.wrapped_state
+    }
+}
+
+impl<A: Actor> CausalDeliveryActor<A> {
+    pub fn new(peers: Vec<Id>, wrapped_actor: A) -> Self {
+        Self { peers, wrapped_actor }
+    }
+
+    /// Tags messages output by the wrapped actor with the next clock value.
+    fn process_output(&self, id: Id, state: &mut CausalState<A::Msg, A::State>, wrapped_out: Out<A>, o: &mut Out<Self>) {
+        for command in wrapped_out {
+            let (dsts, msg) = match command {
+                Command::Send(dst, msg) => (vec![dst], msg),
+                Command::Broadcast(dsts, msg) => (dsts, msg),
+                Command::CancelTimer => {
+                    o.cancel_timer();
+                    continue;
+                },
+                Command::SetTimer(range) => {
+                    o.set_timer(range);
+                    continue;
+                },
+                Command::Spawn(spec) => {
+                    // The specification is never delivered, so it needs no clock.
+                    o.spawn(CausalMsg { clock: VectorClock::new(), msg: Some(spec) });
+                    continue;
+                },
+                Command::Monitor(peer) => {
+                    o.monitor(peer);
+                    continue;
+                },
+                Command::ChooseRandom(range) => {
+                    o.choose_random(range);
+                    continue;
+                },
+            };
+            state.clock.increment(id);
+            let clock = state.clock.clone();
+            let msg = CausalMsg { clock: clock.clone(), msg: Some(msg) };
+            o.broadcast(&dsts, &msg);
+            let others: Vec<Id> = self.peers.iter()
+                .filter(|peer| **peer != id && !dsts.contains(peer))
+                .copied()
+                .collect();
+            if !others.is_empty() {
+                o.broadcast(&others, &CausalMsg { clock, msg: None });
+            }
+        }
+    }
+}
+
+impl<A: Actor> Actor for CausalDeliveryActor<A> {
+    type Msg = CausalMsg<A::Msg>;
+    type State = CausalState<A::Msg, A::State>;
+
+    fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+        let mut wrapped_out = Out::new();
+        let mut state = CausalState {
+            clock: VectorClock::new(),
+            msgs_pending_delivery: BTreeMap::new(),
+            wrapped_state: self.wrapped_actor.on_start(id, &mut wrapped_out),
+        };
+        self.process_output(id, &mut state, wrapped_out, o);
+        state
+    }
+
+    fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        // Messages to self are already causally ordered, as the clock reflects them when sent.
+        if src == id {
+            let wrapped_msg = match msg.msg {
+                None => return,
+                Some(wrapped_msg) => wrapped_msg,
+            };
+            let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+            let mut wrapped_out = Out::new();
+            self.wrapped_actor.on_msg(id, &mut wrapped_state, src, wrapped_msg, &mut wrapped_out);
+            if let Cow::Owned(wrapped_state) = wrapped_state {
+                state.to_mut().wrapped_state = wrapped_state;
+            }
+            self.process_output(id, state.to_mut(), wrapped_out, o);
+            return;
+        }
+
+        // Ignore messages that were already delivered or are already pending.
+        let seq = msg.clock.get(&src);
+        if seq <= state.clock.get(&src) || state.msgs_pending_delivery.contains_key(&(src, seq)) {
+            return;
+        }
+        let state = state.to_mut();
+        state.msgs_pending_delivery.insert((src, seq), msg);
+
+        // Deliver pending messages until none are next in causal order.
+        while let Some(key) = state.msgs_pending_delivery.iter()
+            .find(|((src, _), msg)| state.clock.is_next_from(src, &msg.clock))
+            .map(|(key, _)| *key)
+        {
+            let (src, _) = key;
+            let msg = state.msgs_pending_delivery.remove(&key).unwrap();
+            state.clock.increment(src);
+            let wrapped_msg = match msg.msg {
+                None => continue,
+                Some(wrapped_msg) => wrapped_msg,
+            };
+            let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+            let mut wrapped_out = Out::new();
+            self.wrapped_actor.on_msg(id, &mut wrapped_state, src, wrapped_msg, &mut wrapped_out);
+            if let Cow::Owned(wrapped_state) = wrapped_state {
+                state.wrapped_state = wrapped_state;
+            }
+            self.process_output(id, state, wrapped_out, o);
+        }
+    }
+
+    fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_timeout(id, &mut wrapped_state, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(id, state.to_mut(), wrapped_out, o);
+    }
+
+    /// Delivers the configuration carried by the update to the wrapped actor. The clock is
+    /// ignored, as configuration updates do not arrive from group members.
+    fn on_config_update(&self, id: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+        let config = match config.msg {
+            None => return,
+            Some(config) => config,
+        };
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_config_update(id, &mut wrapped_state, config, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(id, state.to_mut(), wrapped_out, o);
+    }
+
+    fn on_peer_down(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_peer_down(id, &mut wrapped_state, peer, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(id, state.to_mut(), wrapped_out, o);
+    }
+
+    fn on_random(&self, id: Id, state: &mut Cow<Self::State>, value: u64, o: &mut Out<Self>) {
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_random(id, &mut wrapped_state, value, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(id, state.to_mut(), wrapped_out, o);
+    }
+
+    /// Wraps the actor spawned by the wrapped actor, which joins the same group.
+    fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+        let wrapped_actor = self.wrapped_actor.spawned_actor(spec.msg.as_ref()?)?;
+        Some(CausalDeliveryActor::new(self.peers.clone(), wrapped_actor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model, Property};
+
+    /// Actor 0 posts a question, actor 1 answers it upon delivery, and actor 2 records what it
+    /// receives. Actor 2 is only sent the question if `ask_observer` is set.
+    #[derive(Clone)]
+    struct TestActor { ask_observer: bool }
+
+    impl Actor for TestActor {
+        type Msg = char;
+        type State = Vec<char>;
+
+        fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+            if id == Id::from(0) {
+                if self.ask_observer {
+                    o.broadcast(&[Id::from(1), Id::from(2)], &'Q');
+                } else {
+                    o.send(Id::from(1), 'Q');
+                }
+            }
+            Vec::new()
+        }
+
+        fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, _src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+            if id == Id::from(1) && msg == 'Q' { o.send(Id::from(2), 'A'); }
+            state.to_mut().push(msg);
+        }
+    }
+
+    struct TestSystem { ask_observer: bool }
+
+    impl System for TestSystem {
+        type Actor = CausalDeliveryActor<TestActor>;
+        type History = ();
+
+        fn actors(&self) -> Vec<Self::Actor> {
+            let peers = vec![Id::from(0), Id::from(1), Id::from(2)];
+            vec![CausalDeliveryActor::new(peers, TestActor { ask_observer: self.ask_observer }); 3]
+        }
+
+        fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+
+        fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+            vec![
+                Property::<SystemModel<Self>>::always("answer follows question", |model, state| {
+                    !model.actors[2].wrapped_actor.ask_observer
+                        || state.actor_states[2].wrapped_state() != &vec!['A']
+                }),
+                Property::<SystemModel<Self>>::sometimes("answer delivered", |_, state| {
+                    state.actor_states[2].wrapped_state().contains(&'A')
+                }),
+            ]
+        }
+    }
+
+    #[test]
+    fn delivers_in_causal_order() {
+        TestSystem { ask_observer: true }.into_model().checker().spawn_bfs().join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn forwards_spawned_actors() {
+        #[derive(Clone)]
+        struct Spawner;
+        impl Actor for Spawner {
+            type Msg = char;
+            type State = ();
+            fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State { o.spawn('S'); }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+                if *spec == 'S' { Some(Spawner) } else { None }
+            }
+        }
+
+        let actor = CausalDeliveryActor::new(vec![Id::from(0)], Spawner);
+        let mut o = Out::new();
+        actor.on_start(Id::from(0), &mut o);
+        let specs: Vec<_> = o.into_iter()
+            .filter_map(|c| if let Command::Spawn(spec) = c { Some(spec) } else { None })
+            .collect();
+        assert_eq!(specs.len(), 1);
+        let spawned = actor.spawned_actor(&specs[0]).unwrap();
+        assert_eq!(spawned.peers, vec![Id::from(0)]);
+        assert!(actor.spawned_actor(&CausalMsg { clock: VectorClock::new(), msg: None }).is_none());
+    }
+
+    #[test]
+    fn forwards_config_updates() {
+        struct Configured;
+        impl Actor for Configured {
+            type Msg = char;
+            type State = Vec<char>;
+            fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State { Vec::new() }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_config_update(&self, _: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+                state.to_mut().push(config);
+                o.send(Id::from(1), config);
+            }
+        }
+
+        let actor = CausalDeliveryActor::new(vec![Id::from(0), Id::from(1)], Configured);
+        let mut o = Out::new();
+        let mut state = Cow::Owned(actor.on_start(Id::from(0), &mut o));
+        let mut o = Out::new();
+        let config = CausalMsg { clock: VectorClock::new(), msg: Some('C') };
+        actor.on_config_update(Id::from(0), &mut state, config, &mut o);
+        assert_eq!(state.wrapped_state(), &vec!['C']);
+
+        // Messages sent in response are tagged like any other.
+        let mut clock = VectorClock::new();
+        clock.increment(Id::from(0));
+        let sent: Vec<_> = o.into_iter()
+            .filter_map(|c| if let Command::Broadcast(dsts, msg) = c { Some((dsts, msg)) } else { None })
+            .collect();
+        assert_eq!(sent, vec![(vec![Id::from(1)], CausalMsg { clock, msg: Some('C') })]);
+    }
+
+    #[test]
+    fn delivers_messages_that_follow_messages_to_other_peers() {
+        // Actor 2 never receives the question, so it relies on the announcement of the question
+        // to deliver the answer.
+        TestSystem { ask_observer: false }.into_model().checker().spawn_bfs().join()
+            .assert_properties();
+    }
+}
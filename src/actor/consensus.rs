@@ -0,0 +1,18 @@
+//! Reference implementations of consensus protocols, available via the `consensus` feature.
+//!
+//! The actors implement the [`RegisterMsg`] interface, so they can be checked with a
+//! [`RegisterTestSystem`] or composed into larger systems. They are generic over the request ID
+//! type and the value type, the latter of which serves as the command being agreed upon.
+//!
+//! - [`PaxosActor`] implements Single Decree Paxos, deciding the first value written.
+//! - [`RaftActor`] implements Raft, replicating a log of register commands. Log replication can
+//!   be batched via [`RaftActor::max_batch_size`], and the log can be compacted into a snapshot
+//!   via [`RaftActor::snapshot_threshold`].
+//!
+//! [`RegisterMsg`]: crate::actor::register::RegisterMsg
+//! [`RegisterTestSystem`]: crate::actor::register::RegisterTestSystem
+
+mod paxos;
+mod raft;
+pub use paxos::*;
+pub use raft::*;
@@ -0,0 +1,213 @@
+//! Private module for selective re-export.
+
+use crate::actor::{majority, Actor, Id, Out};
+use crate::actor::register::RegisterMsg::{self, *};
+use crate::util::{HashableHashMap, HashableHashSet};
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+/// A Paxos ballot, which is a round number paired with the ID of the proposing actor so that
+/// ballots are unique.
+pub type Ballot = (u32, Id);
+
+/// A proposed value along with the request that proposed it and the requester's ID.
+pub type Proposal<RequestId, Value> = (RequestId, Id, Value);
+
+/// The last proposal accepted by an acceptor, if any, along with the ballot it was accepted in.
+pub type LastAccepted<RequestId, Value> = Option<(Ballot, Proposal<RequestId, Value>)>;
+
+/// Messages for the internal protocol of a [`PaxosActor`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum PaxosMsg<RequestId, Value> {
+    Prepare { ballot: Ballot },
+    Prepared { ballot: Ballot, last_accepted: LastAccepted<RequestId, Value> },
+
+    Accept { ballot: Ballot, proposal: Proposal<RequestId, Value> },
+    Accepted { ballot: Ballot },
+
+    Decided { ballot: Ballot, proposal: Proposal<RequestId, Value> },
+}
+use PaxosMsg::*;
+
+/// The state of a [`PaxosActor`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct PaxosState<RequestId, Value> {
+    // shared state
+    ballot: Ballot,
+
+    // leader state
+    proposal: Option<Proposal<RequestId, Value>>,
+    prepares: HashableHashMap<Id, LastAccepted<RequestId, Value>>,
+    accepts: HashableHashSet<Id>,
+
+    // acceptor state
+    accepted: LastAccepted<RequestId, Value>,
+    is_decided: bool,
+}
+
+impl<RequestId, Value> PaxosState<RequestId, Value> {
+    /// The highest ballot that the actor has participated in.
+    pub fn ballot(&self) -> Ballot {
+        self.ballot
+    }
+
+    /// The decided value, if the actor has learned it.
+    pub fn decided(&self) -> Option<&Value> {
+        match &self.accepted {
+            Some((_ballot, (_request_id, _requester_id, value))) if self.is_decided => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// An actor that implements Single Decree Paxos. The first [`RegisterMsg::Put`] received by an
+/// actor starts a ballot proposing the value, and [`RegisterMsg::Get`]s are answered once a value
+/// is decided.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaxosActor<RequestId, Value> {
+    pub peer_ids: Vec<Id>,
+    _marker: PhantomData<(RequestId, Value)>,
+}
+
+impl<RequestId, Value> PaxosActor<RequestId, Value> {
+    pub fn new(peer_ids: Vec<Id>) -> Self {
+        Self { peer_ids, _marker: PhantomData }
+    }
+}
+
+impl<RequestId, Value> Actor for PaxosActor<RequestId, Value>
+where
+    RequestId: Clone + Debug + Eq + Hash,
+    Value: Clone + Debug + Eq + Hash,
+{
+    type Msg = RegisterMsg<RequestId, Value, PaxosMsg<RequestId, Value>>;
+    type State = PaxosState<RequestId, Value>;
+
+    fn on_start(&self, id: Id, _o: &mut Out<Self>) -> Self::State {
+        PaxosState {
+            // shared state
+            ballot: (0, id),
+
+            // leader state
+            proposal: None,
+            prepares: Default::default(),
+            accepts: Default::default(),
+
+            // acceptor state
+            accepted: None,
+            is_decided: false,
+        }
+    }
+
+    fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        let quorum = majority(self.peer_ids.len() + 1);
+        match msg {
+            Put(request_id, value) if !state.is_decided && state.proposal.is_none() => {
+                let state = state.to_mut();
+                state.ballot = (state.ballot.0 + 1, id);
+                state.proposal = Some((request_id, src, value));
+                state.prepares = Default::default();
+                state.accepts = Default::default();
+                // The proposer is also an acceptor, so it counts toward each quorum.
+                state.prepares.insert(id, state.accepted.clone());
+                o.broadcast(
+                    &self.peer_ids,
+                    &Internal(Prepare { ballot: state.ballot }));
+            }
+            Get(request_id) if state.is_decided => {
+                if let Some(value) = state.decided() {
+                    o.send(src, GetOk(request_id, value.clone()));
+                }
+                // An undecided actor does not reply, as a value may have been decided elsewhere.
+            }
+            Internal(Prepare { ballot }) if state.ballot < ballot => {
+                state.to_mut().ballot = ballot;
+                o.send(src, Internal(Prepared {
+                    ballot,
+                    last_accepted: state.accepted.clone(),
+                }));
+            }
+            Internal(Prepared { ballot, last_accepted })
+            if ballot == state.ballot && !state.is_decided => {
+                let state = state.to_mut();
+                state.prepares.insert(src, last_accepted);
+                if state.prepares.len() == quorum {
+                    // Adopt the proposal of the highest accepted ballot, if any.
+                    let proposal = state.prepares.values()
+                        .flatten()
+                        .max_by_key(|(ballot, _)| *ballot)
+                        .map(|(_, proposal)| proposal.clone())
+                        .unwrap_or_else(|| state.proposal.clone().expect("proposal expected"));
+                    state.proposal = Some(proposal.clone());
+                    state.accepted = Some((ballot, proposal.clone()));
+                    state.accepts.insert(id);
+                    o.broadcast(&self.peer_ids, &Internal(Accept { ballot, proposal }));
+                }
+            }
+            Internal(Accept { ballot, proposal })
+            if state.ballot <= ballot && !state.is_decided => {
+                let state = state.to_mut();
+                state.ballot = ballot;
+                state.accepted = Some((ballot, proposal));
+                o.send(src, Internal(Accepted { ballot }));
+            }
+            Internal(Accepted { ballot }) if ballot == state.ballot && !state.is_decided => {
+                let state = state.to_mut();
+                state.accepts.insert(src);
+                if state.accepts.len() == quorum {
+                    state.is_decided = true;
+                    let proposal = state.proposal.clone().expect("proposal expected");
+                    o.broadcast(&self.peer_ids, &Internal(Decided {
+                        ballot,
+                        proposal: proposal.clone(),
+                    }));
+                    let (request_id, requester_id, _) = proposal;
+                    o.send(requester_id, PutOk(request_id));
+                }
+            }
+            Internal(Decided { ballot, proposal }) => {
+                let state = state.to_mut();
+                state.ballot = ballot;
+                state.accepted = Some((ballot, proposal));
+                state.is_decided = true;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model};
+    use crate::actor::{model_peers, DuplicatingNetwork, System, SystemState};
+    use crate::actor::register::{RegisterActorState, RegisterTestSystem, TestRequestId, TestValue};
+
+    type TestSystem = RegisterTestSystem<PaxosActor<TestRequestId, TestValue>, PaxosMsg<TestRequestId, TestValue>>;
+
+    fn within_boundary(state: &SystemState<TestSystem>) -> bool {
+        state.actor_states.iter().all(|s| match &**s {
+            RegisterActorState::Server(s) => s.ballot().0 < 4,
+            _ => true,
+        })
+    }
+
+    #[test]
+    fn decides_a_single_value() {
+        let checker = TestSystem {
+            servers: (0..3).map(|i| PaxosActor::new(model_peers(i, 3))).collect(),
+            client_count: 2,
+            within_boundary,
+            duplicating_network: DuplicatingNetwork::No,
+            .. Default::default()
+        }.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        let discovery = checker.discovery("value chosen").unwrap();
+        assert!(discovery.last_state().actor_states.iter().any(|s| {
+            matches!(&**s, RegisterActorState::Server(s) if s.decided().is_some())
+        }));
+    }
+}
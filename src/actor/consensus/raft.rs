@@ -0,0 +1,554 @@
+//! Private module for selective re-export.
+
+use crate::actor::{majority, Actor, Id, Out};
+use crate::actor::register::RegisterMsg::{self, *};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::time::Duration;
+
+/// A Raft term.
+pub type Term = u64;
+
+/// The position of an entry in a Raft log, starting from 1. Index 0 precedes the first entry.
+pub type LogIndex = usize;
+
+/// A register command replicated via a [`RaftActor`]'s log.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum RaftCommand<RequestId, Value> {
+    Put(RequestId, Value),
+    Get(RequestId),
+    Cas(RequestId, Value, Value),
+}
+
+/// An entry in a [`RaftActor`]'s log, which records the client that requested the command so
+/// that the leader can reply once the entry is committed.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct LogEntry<RequestId, Value> {
+    pub term: Term,
+    pub client: Id,
+    pub command: RaftCommand<RequestId, Value>,
+}
+
+/// Messages for the internal protocol of a [`RaftActor`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum RaftMsg<RequestId, Value> {
+    /// A client request forwarded by a follower to the leader.
+    Forward { client: Id, command: RaftCommand<RequestId, Value> },
+
+    RequestVote { term: Term, last_log_index: LogIndex, last_log_term: Term },
+    Vote { term: Term, granted: bool },
+
+    AppendEntries {
+        term: Term,
+        prev_log_index: LogIndex,
+        prev_log_term: Term,
+        entries: Vec<LogEntry<RequestId, Value>>,
+        leader_commit: LogIndex,
+    },
+    /// Replaces the log prefix of a follower that is missing entries the leader has compacted.
+    InstallSnapshot { term: Term, last_index: LogIndex, last_term: Term, value: Value },
+    /// Responds to [`RaftMsg::AppendEntries`] and [`RaftMsg::InstallSnapshot`]. Upon success,
+    /// `last_index` is the last index known to match the leader's log. Otherwise it is the last
+    /// index of the follower's log, which helps the leader find where the logs diverge.
+    Appended { term: Term, success: bool, last_index: LogIndex },
+}
+use RaftMsg::*;
+
+/// The role of a [`RaftActor`] within its term.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RaftRole {
+    Follower,
+    Candidate { votes: BTreeSet<Id> },
+    Leader { next_index: BTreeMap<Id, LogIndex>, match_index: BTreeMap<Id, LogIndex> },
+}
+
+/// The state of a [`RaftActor`]. Committed entries are applied to the register immediately.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RaftState<RequestId, Value> {
+    term: Term,
+    voted_for: Option<Id>,
+    leader: Option<Id>,
+    role: RaftRole,
+
+    /// Entries after the snapshot.
+    log: Vec<LogEntry<RequestId, Value>>,
+    snapshot_index: LogIndex,
+    snapshot_term: Term,
+    snapshot_value: Value,
+
+    commit_index: LogIndex,
+    value: Value,
+}
+
+impl<RequestId, Value> RaftState<RequestId, Value> {
+    /// The current term.
+    pub fn term(&self) -> Term {
+        self.term
+    }
+
+    /// The actor that this actor voted for in the current term, if any.
+    pub fn voted_for(&self) -> Option<Id> {
+        self.voted_for
+    }
+
+    /// Indicates whether the actor is the leader for the current term.
+    pub fn is_leader(&self) -> bool {
+        matches!(self.role, RaftRole::Leader { .. })
+    }
+
+    /// The index of the last committed entry.
+    pub fn commit_index(&self) -> LogIndex {
+        self.commit_index
+    }
+
+    /// The index of the last entry, including entries compacted into the snapshot.
+    pub fn last_index(&self) -> LogIndex {
+        self.snapshot_index + self.log.len()
+    }
+
+    /// The index of the last entry compacted into the snapshot.
+    pub fn snapshot_index(&self) -> LogIndex {
+        self.snapshot_index
+    }
+
+    /// The register value after applying the committed entries.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// The term of the entry at an index, or `None` if the entry is missing or compacted.
+    fn term_at(&self, index: LogIndex) -> Option<Term> {
+        if index == self.snapshot_index {
+            Some(self.snapshot_term)
+        } else if self.snapshot_index < index && index <= self.last_index() {
+            Some(self.log[index - self.snapshot_index - 1].term)
+        } else {
+            None
+        }
+    }
+
+    fn entry(&self, index: LogIndex) -> &LogEntry<RequestId, Value> {
+        &self.log[index - self.snapshot_index - 1]
+    }
+}
+
+/// An actor that implements Raft, replicating a log of register commands. Clients may send
+/// requests to any actor, as followers forward requests to the leader if one is known. The leader
+/// replies once the corresponding entry is committed, so reads are linearizable.
+///
+/// The log is not persisted, so the actor does not tolerate restarts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RaftActor<RequestId, Value> {
+    pub peer_ids: Vec<Id>,
+    /// How long a follower waits to hear from a leader before starting an election.
+    pub election_timeout: Range<Duration>,
+    /// How often a leader sends entries (or heartbeats) to its followers.
+    pub heartbeat_interval: Range<Duration>,
+    /// The most entries sent in a single [`RaftMsg::AppendEntries`].
+    pub max_batch_size: usize,
+    /// How many committed entries accumulate in the log before they are compacted into a
+    /// snapshot, if ever.
+    pub snapshot_threshold: Option<usize>,
+    _marker: PhantomData<(RequestId, Value)>,
+}
+
+impl<RequestId, Value> RaftActor<RequestId, Value> {
+    pub fn new(peer_ids: Vec<Id>) -> Self {
+        Self {
+            peer_ids,
+            election_timeout: Duration::from_millis(150)..Duration::from_millis(300),
+            heartbeat_interval: Duration::from_millis(50)..Duration::from_millis(50),
+            max_batch_size: usize::MAX,
+            snapshot_threshold: None,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn max_batch_size(self, max_batch_size: usize) -> Self {
+        assert!(max_batch_size > 0);
+        Self { max_batch_size, .. self }
+    }
+
+    pub fn snapshot_threshold(self, snapshot_threshold: usize) -> Self {
+        Self { snapshot_threshold: Some(snapshot_threshold), .. self }
+    }
+}
+
+impl<RequestId, Value> RaftActor<RequestId, Value>
+where
+    RequestId: Clone + Debug + Eq + Hash,
+    Value: Clone + Debug + Default + Eq + Hash,
+{
+    /// Adopts a newer term as a follower.
+    fn observe_term(&self, state: &mut RaftState<RequestId, Value>, term: Term, o: &mut Out<Self>) {
+        if term <= state.term { return }
+        if state.is_leader() { o.set_timer(self.election_timeout.clone()); }
+        state.term = term;
+        state.voted_for = None;
+        state.leader = None;
+        state.role = RaftRole::Follower;
+    }
+
+    /// Assumes leadership for the current term upon winning an election.
+    fn become_leader(&self, id: Id, state: &mut RaftState<RequestId, Value>, o: &mut Out<Self>) {
+        let last_index = state.last_index();
+        state.role = RaftRole::Leader {
+            next_index: self.peer_ids.iter().map(|&peer| (peer, last_index + 1)).collect(),
+            match_index: self.peer_ids.iter().map(|&peer| (peer, 0)).collect(),
+        };
+        state.leader = Some(id);
+        o.set_timer(self.heartbeat_interval.clone());
+        for &peer in &self.peer_ids {
+            self.replicate(state, peer, o);
+        }
+    }
+
+    /// Appends a client command if leading, or forwards it to the leader if one is known.
+    fn on_request(&self, id: Id, state: &mut Cow<RaftState<RequestId, Value>>,
+                  client: Id, command: RaftCommand<RequestId, Value>, o: &mut Out<Self>)
+    {
+        if state.is_leader() {
+            let state = state.to_mut();
+            state.log.push(LogEntry { term: state.term, client, command });
+            self.advance_commit(id, state, o);
+            for &peer in &self.peer_ids {
+                self.replicate(state, peer, o);
+            }
+        } else if let Some(leader) = state.leader {
+            o.send(leader, Internal(Forward { client, command }));
+        }
+    }
+
+    /// Sends the entries (or snapshot) that a follower is missing.
+    fn replicate(&self, state: &RaftState<RequestId, Value>, peer: Id, o: &mut Out<Self>) {
+        let next_index = match &state.role {
+            RaftRole::Leader { next_index, .. } => next_index[&peer],
+            _ => return,
+        };
+        if next_index <= state.snapshot_index {
+            o.send(peer, Internal(InstallSnapshot {
+                term: state.term,
+                last_index: state.snapshot_index,
+                last_term: state.snapshot_term,
+                value: state.snapshot_value.clone(),
+            }));
+            return;
+        }
+        let prev_log_index = next_index - 1;
+        let entries = state.log[prev_log_index - state.snapshot_index..].iter()
+            .take(self.max_batch_size)
+            .cloned()
+            .collect();
+        o.send(peer, Internal(AppendEntries {
+            term: state.term,
+            prev_log_index,
+            prev_log_term: state.term_at(prev_log_index).expect("entry expected"),
+            entries,
+            leader_commit: state.commit_index,
+        }));
+    }
+
+    /// Commits the latest entry of the current term that a quorum has replicated.
+    fn advance_commit(&self, id: Id, state: &mut RaftState<RequestId, Value>, o: &mut Out<Self>) {
+        let match_index = match &state.role {
+            RaftRole::Leader { match_index, .. } => match_index,
+            _ => return,
+        };
+        let quorum = majority(self.peer_ids.len() + 1);
+        let commit_index = (state.commit_index + 1..=state.last_index()).rev()
+            .find(|&index| {
+                state.term_at(index) == Some(state.term)
+                    && 1 + match_index.values().filter(|&&m| m >= index).count() >= quorum
+            });
+        if let Some(commit_index) = commit_index {
+            self.apply(id, state, commit_index, o);
+        }
+    }
+
+    /// Applies entries through `commit_index`, replying to clients if leading.
+    fn apply(&self, id: Id, state: &mut RaftState<RequestId, Value>, commit_index: LogIndex, o: &mut Out<Self>) {
+        while state.commit_index < commit_index {
+            state.commit_index += 1;
+            let entry = state.entry(state.commit_index).clone();
+            let reply = match entry.command {
+                RaftCommand::Put(request_id, value) => {
+                    state.value = value;
+                    PutOk(request_id)
+                }
+                RaftCommand::Get(request_id) => GetOk(request_id, state.value.clone()),
+                RaftCommand::Cas(request_id, expected, new) => {
+                    let swapped = state.value == expected;
+                    if swapped { state.value = new; }
+                    CasOk(request_id, swapped)
+                }
+            };
+            if state.leader == Some(id) { o.send(entry.client, reply); }
+        }
+        if let Some(threshold) = self.snapshot_threshold {
+            if state.commit_index - state.snapshot_index >= threshold {
+                state.snapshot_term = state.term_at(state.commit_index).expect("entry expected");
+                state.log.drain(..state.commit_index - state.snapshot_index);
+                state.snapshot_index = state.commit_index;
+                state.snapshot_value = state.value.clone();
+            }
+        }
+    }
+}
+
+impl<RequestId, Value> Actor for RaftActor<RequestId, Value>
+where
+    RequestId: Clone + Debug + Eq + Hash,
+    Value: Clone + Debug + Default + Eq + Hash,
+{
+    type Msg = RegisterMsg<RequestId, Value, RaftMsg<RequestId, Value>>;
+    type State = RaftState<RequestId, Value>;
+
+    fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
+        o.set_timer(self.election_timeout.clone());
+        RaftState {
+            term: 0,
+            voted_for: None,
+            leader: None,
+            role: RaftRole::Follower,
+
+            log: Vec::new(),
+            snapshot_index: 0,
+            snapshot_term: 0,
+            snapshot_value: Value::default(),
+
+            commit_index: 0,
+            value: Value::default(),
+        }
+    }
+
+    fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        match msg {
+            Put(request_id, value) => {
+                self.on_request(id, state, src, RaftCommand::Put(request_id, value), o);
+            }
+            Get(request_id) => {
+                self.on_request(id, state, src, RaftCommand::Get(request_id), o);
+            }
+            Cas(request_id, expected, new) => {
+                self.on_request(id, state, src, RaftCommand::Cas(request_id, expected, new), o);
+            }
+            Internal(Forward { client, command }) => {
+                self.on_request(id, state, client, command, o);
+            }
+            Internal(RequestVote { term, last_log_index, last_log_term }) => {
+                let state = state.to_mut();
+                self.observe_term(state, term, o);
+                let is_log_current = (last_log_term, last_log_index)
+                    >= (state.term_at(state.last_index()).unwrap(), state.last_index());
+                let granted = term == state.term
+                    && (state.voted_for.is_none() || state.voted_for == Some(src))
+                    && is_log_current;
+                if granted {
+                    state.voted_for = Some(src);
+                    o.set_timer(self.election_timeout.clone());
+                }
+                o.send(src, Internal(Vote { term: state.term, granted }));
+            }
+            Internal(Vote { term, granted }) => {
+                let state = state.to_mut();
+                self.observe_term(state, term, o);
+                if term != state.term || !granted { return }
+                let quorum = majority(self.peer_ids.len() + 1);
+                if let RaftRole::Candidate { votes } = &mut state.role {
+                    votes.insert(src);
+                    if votes.len() == quorum { self.become_leader(id, state, o); }
+                }
+            }
+            Internal(AppendEntries { term, prev_log_index, prev_log_term, entries, leader_commit }) => {
+                let state = state.to_mut();
+                self.observe_term(state, term, o);
+                if term < state.term {
+                    o.send(src, Internal(Appended { term: state.term, success: false, last_index: state.last_index() }));
+                    return;
+                }
+                state.role = RaftRole::Follower;
+                state.leader = Some(src);
+                o.set_timer(self.election_timeout.clone());
+
+                // Entries through the snapshot are committed, so they already match.
+                let (prev_log_index, prev_log_term, entries) = if prev_log_index < state.snapshot_index {
+                    let skipped = state.snapshot_index - prev_log_index;
+                    let entries = entries.into_iter().skip(skipped).collect::<Vec<_>>();
+                    (state.snapshot_index, state.snapshot_term, entries)
+                } else {
+                    (prev_log_index, prev_log_term, entries)
+                };
+                if state.term_at(prev_log_index) != Some(prev_log_term) {
+                    o.send(src, Internal(Appended { term, success: false, last_index: state.last_index() }));
+                    return;
+                }
+                let last_index = prev_log_index + entries.len();
+                for (index, entry) in (prev_log_index + 1..).zip(entries) {
+                    match state.term_at(index) {
+                        Some(term) if term == entry.term => continue,
+                        Some(_) => state.log.truncate(index - state.snapshot_index - 1),
+                        None => {}
+                    }
+                    state.log.push(entry);
+                }
+                let commit_index = leader_commit.min(last_index);
+                if commit_index > state.commit_index {
+                    self.apply(id, state, commit_index, o);
+                }
+                o.send(src, Internal(Appended { term, success: true, last_index }));
+            }
+            Internal(InstallSnapshot { term, last_index, last_term, value }) => {
+                let state = state.to_mut();
+                self.observe_term(state, term, o);
+                if term < state.term {
+                    o.send(src, Internal(Appended { term: state.term, success: false, last_index: state.last_index() }));
+                    return;
+                }
+                state.role = RaftRole::Follower;
+                state.leader = Some(src);
+                o.set_timer(self.election_timeout.clone());
+                if last_index > state.commit_index {
+                    // Retain entries that follow the snapshot if the logs agree at that point.
+                    if state.term_at(last_index) == Some(last_term) {
+                        state.log.drain(..last_index - state.snapshot_index);
+                    } else {
+                        state.log.clear();
+                    }
+                    state.snapshot_index = last_index;
+                    state.snapshot_term = last_term;
+                    state.snapshot_value = value.clone();
+                    state.commit_index = last_index;
+                    state.value = value;
+                }
+                o.send(src, Internal(Appended { term, success: true, last_index }));
+            }
+            Internal(Appended { term, success, last_index }) => {
+                let state = state.to_mut();
+                self.observe_term(state, term, o);
+                if term != state.term { return }
+                if let RaftRole::Leader { next_index, match_index } = &mut state.role {
+                    let next = next_index.get_mut(&src).expect("peer expected");
+                    if success {
+                        let matched = match_index.get_mut(&src).expect("peer expected");
+                        *matched = (*matched).max(last_index);
+                        *next = (*next).max(last_index + 1);
+                    } else {
+                        *next = (*next - 1).min(last_index + 1).max(1);
+                    }
+                    let is_behind = *next <= state.snapshot_index + state.log.len();
+                    if success { self.advance_commit(id, state, o); }
+                    if !success || is_behind { self.replicate(state, src, o); }
+                }
+            }
+            PutOk(_) | GetOk(..) | CasOk(..) => {}
+        }
+    }
+
+    fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+        if state.is_leader() {
+            o.set_timer(self.heartbeat_interval.clone());
+            for &peer in &self.peer_ids {
+                self.replicate(state, peer, o);
+            }
+            return;
+        }
+
+        // Start an election.
+        let state = state.to_mut();
+        state.term += 1;
+        state.voted_for = Some(id);
+        state.leader = None;
+        state.role = RaftRole::Candidate { votes: std::iter::once(id).collect() };
+        o.set_timer(self.election_timeout.clone());
+        o.broadcast(&self.peer_ids, &Internal(RequestVote {
+            term: state.term,
+            last_log_index: state.last_index(),
+            last_log_term: state.term_at(state.last_index()).unwrap(),
+        }));
+        if majority(self.peer_ids.len() + 1) == 1 { self.become_leader(id, state, o); }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model};
+    use crate::actor::{model_peers, Command, DuplicatingNetwork, System, SystemState};
+    use crate::actor::register::{RegisterActorState, RegisterTestSystem, TestRequestId, TestValue};
+
+    type TestSystem = RegisterTestSystem<RaftActor<TestRequestId, TestValue>, RaftMsg<TestRequestId, TestValue>>;
+    type TestMsg = RegisterMsg<TestRequestId, TestValue, RaftMsg<TestRequestId, TestValue>>;
+
+    fn within_boundary(state: &SystemState<TestSystem>) -> bool {
+        state.actor_states.iter().all(|s| match &**s {
+            RegisterActorState::Server(s) => s.term() < 2,
+            _ => true,
+        })
+    }
+
+    fn check(servers: Vec<RaftActor<TestRequestId, TestValue>>) {
+        TestSystem {
+            servers,
+            client_count: 1,
+            within_boundary,
+            duplicating_network: DuplicatingNetwork::No,
+            .. Default::default()
+        }.into_model().checker().spawn_bfs().join().assert_properties();
+    }
+
+    fn sends(o: Out<RaftActor<TestRequestId, TestValue>>) -> Vec<(Id, TestMsg)> {
+        o.into_iter().filter_map(|c| match c {
+            Command::Send(dst, msg) => Some((dst, msg)),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn is_linearizable() {
+        check(vec![RaftActor::new(model_peers(0, 1))]);
+        check((0..2).map(|i| RaftActor::new(model_peers(i, 2))).collect());
+    }
+
+    #[test]
+    fn is_linearizable_with_batching_and_snapshots() {
+        check((0..2).map(|i| {
+            RaftActor::new(model_peers(i, 2)).max_batch_size(1).snapshot_threshold(1)
+        }).collect());
+    }
+
+    #[test]
+    fn installs_snapshot_on_lagging_follower() {
+        let actor = |i| RaftActor::new(model_peers(i, 3)).snapshot_threshold(1);
+        let (leader, follower, client) = (actor(0), actor(2), Id::from(3));
+
+        // Win an election and commit a write with the first follower.
+        let mut state = Cow::Owned(leader.on_start(Id::from(0), &mut Out::new()));
+        leader.on_timeout(Id::from(0), &mut state, &mut Out::new());
+        leader.on_msg(Id::from(0), &mut state, Id::from(1), Internal(Vote { term: 1, granted: true }), &mut Out::new());
+        assert!(state.is_leader());
+        leader.on_msg(Id::from(0), &mut state, client, Put(1, 'A'), &mut Out::new());
+        let mut o = Out::new();
+        leader.on_msg(Id::from(0), &mut state, Id::from(1), Internal(Appended { term: 1, success: true, last_index: 1 }), &mut o);
+        assert_eq!(sends(o), vec![(client, PutOk(1))]);
+        assert_eq!(state.snapshot_index(), 1);
+
+        // The second follower is sent the snapshot, as the entry it lacks was compacted.
+        let mut o = Out::new();
+        leader.on_timeout(Id::from(0), &mut state, &mut o);
+        let install = Internal(InstallSnapshot { term: 1, last_index: 1, last_term: 1, value: 'A' });
+        assert!(sends(o).contains(&(Id::from(2), install.clone())));
+
+        let mut state = Cow::Owned(follower.on_start(Id::from(2), &mut Out::new()));
+        let mut o = Out::new();
+        follower.on_msg(Id::from(2), &mut state, Id::from(0), install, &mut o);
+        assert_eq!(sends(o), vec![(Id::from(0), Internal(Appended { term: 1, success: true, last_index: 1 }))]);
+        assert_eq!((state.commit_index(), state.value()), (1, &'A'));
+    }
+}
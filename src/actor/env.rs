@@ -0,0 +1,407 @@
+//! Provides an [`EnvironmentActor`] that plays the role of a client, issuing requests to the
+//! system under test per a [`Workload`], along with an [`EnvironmentHistory`] for recording those
+//! requests in a [`ConsistencyTester`] via the [`System`] history hooks.
+//!
+//! A workload is flattened into alternative scripts (one per combination of [`Workload::Choice`]
+//! options). The actor follows the first script, and the remaining scripts are made available to
+//! the model checker via [`EnvironmentActor::script_perturbations`], which is intended to be
+//! returned from [`System::init_state_perturbations`].
+
+use crate::actor::*;
+use crate::semantics::ConsistencyTester;
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Describes the requests that an [`EnvironmentActor`] issues.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Workload<Msg> {
+    /// Sends a request to an actor and awaits a response.
+    Request(Id, Msg),
+    /// Pauses for a "think time" (modeled by a timer) before proceeding.
+    Think,
+    /// Performs each workload in order.
+    Seq(Vec<Workload<Msg>>),
+    /// Performs any one of the workloads.
+    Choice(Vec<Workload<Msg>>),
+}
+
+/// A single step of a flattened [`Workload`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum WorkloadStep<Msg> {
+    /// Sends a request to an actor and awaits a response.
+    Request(Id, Msg),
+    /// Pauses before proceeding.
+    Think,
+}
+
+impl<Msg: Clone> Workload<Msg> {
+    /// Flattens the workload into the sequences of steps that it allows, in order of the
+    /// [`Workload::Choice`] options.
+    pub fn scripts(&self) -> Vec<Vec<WorkloadStep<Msg>>> {
+        match self {
+            Workload::Request(dst, msg) => vec![vec![WorkloadStep::Request(*dst, msg.clone())]],
+            Workload::Think => vec![vec![WorkloadStep::Think]],
+            Workload::Seq(workloads) => {
+                let mut scripts = vec![Vec::new()];
+                for workload in workloads {
+                    let suffixes = workload.scripts();
+                    scripts = scripts.iter()
+                        .flat_map(|prefix| suffixes.iter().map(move |suffix| {
+                            let mut script = prefix.clone();
+                            script.extend(suffix.iter().cloned());
+                            script
+                        }))
+                        .collect();
+                }
+                scripts
+            }
+            Workload::Choice(workloads) => {
+                workloads.iter().flat_map(|w| w.scripts()).collect()
+            }
+        }
+    }
+}
+
+/// An actor that issues requests per a [`Workload`], awaiting a response to each request before
+/// proceeding. If [`EnvironmentActor::retries`] are configured, then a request times out, in
+/// which case it is resent (with the same message, and therefore the same request ID) up to the
+/// specified number of times before the actor gives up and moves on.
+///
+/// If the workload has choices, then the actor pauses before its first step so that
+/// [`EnvironmentActor::script_perturbations`] can substitute a different script.
+///
+/// The actor typically is one variant of an enum that also includes the actors under test, for
+/// which it forwards the [`Actor`] methods.
+#[derive(Clone)]
+pub struct EnvironmentActor<Msg> {
+    scripts: Vec<Vec<WorkloadStep<Msg>>>,
+    is_response: fn(request: &Msg, response: &Msg) -> bool,
+    retries: Option<u8>,
+}
+
+/// The state of an [`EnvironmentActor`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize)]
+pub struct EnvironmentState {
+    /// The index of the script being followed.
+    pub script: usize,
+    /// The index of the current step within the script, which equals the script length once the
+    /// script is complete.
+    pub position: usize,
+    /// The number of times the current request has been resent.
+    pub attempts: u8,
+}
+
+impl<Msg: Clone> EnvironmentActor<Msg> {
+    /// Constructs an actor that follows the `workload`, where `is_response` indicates whether a
+    /// message is the response to a request.
+    pub fn new(workload: Workload<Msg>, is_response: fn(&Msg, &Msg) -> bool) -> Self {
+        let mut scripts = workload.scripts();
+        if scripts.len() > 1 {
+            for script in &mut scripts {
+                script.insert(0, WorkloadStep::Think);
+            }
+        }
+        EnvironmentActor { scripts, is_response, retries: None }
+    }
+
+    /// Times out requests, resending each up to `retries` times before giving up on it.
+    pub fn retries(mut self, retries: u8) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// Indicates the alternative initial states, one for each script other than the first.
+    pub fn script_perturbations(&self, state: &EnvironmentState) -> Vec<EnvironmentState> {
+        (1..self.scripts.len())
+            .map(|script| EnvironmentState { script, ..state.clone() })
+            .collect()
+    }
+
+    /// The step at which the actor is waiting, if any.
+    pub fn current_step(&self, state: &EnvironmentState) -> Option<&WorkloadStep<Msg>> {
+        self.scripts.get(state.script)?.get(state.position)
+    }
+
+    /// Moves past the current step and performs subsequent steps until one needs to wait.
+    fn advance<A>(&self, state: &mut EnvironmentState, o: &mut Out<A>)
+    where A: Actor<Msg = Msg>,
+    {
+        state.position += 1;
+        state.attempts = 0;
+        self.perform(state, o);
+    }
+
+    /// Performs the current step.
+    fn perform<A>(&self, state: &EnvironmentState, o: &mut Out<A>)
+    where A: Actor<Msg = Msg>,
+    {
+        match self.current_step(state) {
+            None => {}
+            Some(WorkloadStep::Think) => o.set_timer(model_timeout()),
+            Some(WorkloadStep::Request(dst, msg)) => {
+                o.send(*dst, msg.clone());
+                if self.retries.is_some() { o.set_timer(model_timeout()); }
+            }
+        }
+    }
+}
+
+impl<Msg> Actor for EnvironmentActor<Msg>
+where Msg: Clone + Debug + Eq + Hash,
+{
+    type Msg = Msg;
+    type State = EnvironmentState;
+
+    fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
+        let state = EnvironmentState { script: 0, position: 0, attempts: 0 };
+        self.perform(&state, o);
+        state
+    }
+
+    fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, _src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        if let Some(WorkloadStep::Request(_, request)) = self.current_step(state) {
+            if (self.is_response)(request, &msg) {
+                if self.retries.is_some() { o.cancel_timer(); }
+                self.advance(state.to_mut(), o);
+            }
+        }
+    }
+
+    fn on_timeout(&self, _id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+        match (self.current_step(state), self.retries) {
+            (Some(WorkloadStep::Request(_, _)), Some(retries)) if state.attempts < retries => {
+                let state = state.to_mut();
+                state.attempts += 1;
+                self.perform(state, o);
+            }
+            (Some(_), _) => self.advance(state.to_mut(), o),
+            (None, _) => {}
+        }
+    }
+}
+
+/// Wraps a [`ConsistencyTester`] to account for the retries and abandoned requests of
+/// [`EnvironmentActor`]s. A resent request is not a new operation, and a request that is
+/// followed by a different request was abandoned, so its operation is recorded as timed out.
+/// Intended to be updated via [`System::record_msg_out`] and [`System::record_msg_in`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct EnvironmentHistory<Msg, Tester> {
+    /// The underlying tester.
+    pub tester: Tester,
+    outstanding: Vec<(Id, Msg)>,
+}
+
+// Manual implementation to avoid `Msg: Default` constraint that `#derive(Default)` would
+// introduce on `EnvironmentHistory<Msg, Tester>`.
+impl<Msg, Tester: Default> Default for EnvironmentHistory<Msg, Tester> {
+    fn default() -> Self {
+        EnvironmentHistory { tester: Tester::default(), outstanding: Vec::new() }
+    }
+}
+
+impl<Msg, Tester> EnvironmentHistory<Msg, Tester>
+where Msg: Clone + PartialEq,
+      Tester: Clone,
+{
+    /// Records that an environment actor sent a request for an operation. Returns `None` if the
+    /// request was resent, as the history is then unchanged.
+    pub fn on_request<Op, Ret>(&self, thread_id: Id, request: &Msg, op: Op) -> Option<Self>
+    where Tester: ConsistencyTester<Id, Op, Ret>,
+    {
+        let position = self.outstanding.iter().position(|(id, _)| *id == thread_id);
+        if let Some(position) = position {
+            if self.outstanding[position].1 == *request { return None }
+        }
+        let mut history = self.clone();
+        if let Some(position) = position {
+            history.outstanding.remove(position);
+            let _ = history.tester.on_timeout(thread_id);
+        }
+        history.outstanding.push((thread_id, request.clone()));
+        let _ = history.tester.on_invoke(thread_id, op);
+        Some(history)
+    }
+
+    /// Records that an environment actor received the response to its request. Returns `None` if
+    /// the actor has no request outstanding, as for a duplicate response.
+    pub fn on_response<Op, Ret>(&self, thread_id: Id, ret: Ret) -> Option<Self>
+    where Tester: ConsistencyTester<Id, Op, Ret>,
+    {
+        let position = self.outstanding.iter().position(|(id, _)| *id == thread_id)?;
+        let mut history = self.clone();
+        history.outstanding.remove(position);
+        let _ = history.tester.on_return(thread_id, ret);
+        Some(history)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model, Property};
+    use crate::actor::register::{RegisterMsg, RegisterMsg::*, TestRequestId, TestValue};
+    use crate::semantics::LinearizabilityTester;
+    use crate::semantics::register::{Register, RegisterOp, RegisterRet};
+
+    type Msg = RegisterMsg<TestRequestId, TestValue, ()>;
+
+    #[derive(Clone)]
+    enum TestActor {
+        Client(EnvironmentActor<Msg>),
+        Server,
+    }
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    enum TestState {
+        Client(EnvironmentState),
+        Server(TestValue),
+    }
+
+    impl Actor for TestActor {
+        type Msg = Msg;
+        type State = TestState;
+
+        fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+            match self {
+                TestActor::Client(client) => {
+                    let mut client_out = Out::new();
+                    let state = client.on_start(id, &mut client_out);
+                    o.append(&mut client_out);
+                    TestState::Client(state)
+                }
+                TestActor::Server => TestState::Server(TestValue::default()),
+            }
+        }
+
+        fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+            match (self, &**state) {
+                (TestActor::Client(client), TestState::Client(client_state)) => {
+                    let mut client_state = Cow::Borrowed(client_state);
+                    let mut client_out = Out::new();
+                    client.on_msg(id, &mut client_state, src, msg, &mut client_out);
+                    if let Cow::Owned(client_state) = client_state {
+                        *state = Cow::Owned(TestState::Client(client_state));
+                    }
+                    o.append(&mut client_out);
+                }
+                (TestActor::Server, TestState::Server(value)) => match msg {
+                    Put(req_id, new_value) => {
+                        *state = Cow::Owned(TestState::Server(new_value));
+                        o.send(src, PutOk(req_id));
+                    }
+                    Get(req_id) => o.send(src, GetOk(req_id, *value)),
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+            if let (TestActor::Client(client), TestState::Client(client_state)) = (self, &**state) {
+                let mut client_state = Cow::Borrowed(client_state);
+                let mut client_out = Out::new();
+                client.on_timeout(id, &mut client_state, &mut client_out);
+                if let Cow::Owned(client_state) = client_state {
+                    *state = Cow::Owned(TestState::Client(client_state));
+                }
+                o.append(&mut client_out);
+            }
+        }
+    }
+
+    struct TestSystem;
+
+    impl System for TestSystem {
+        type Actor = TestActor;
+        type History = EnvironmentHistory<Msg, LinearizabilityTester<Id, Register<TestValue>>>;
+
+        fn actors(&self) -> Vec<Self::Actor> {
+            let is_response = |request: &Msg, response: &Msg| match (request, response) {
+                (Put(req_id, _), PutOk(resp_id)) | (Get(req_id), GetOk(resp_id, _)) => req_id == resp_id,
+                _ => false,
+            };
+            let client = EnvironmentActor::new(
+                Workload::Seq(vec![
+                    Workload::Choice(vec![
+                        Workload::Request(Id::from(0), Put(1, 'A')),
+                        Workload::Request(Id::from(0), Put(1, 'B')),
+                    ]),
+                    Workload::Think,
+                    Workload::Request(Id::from(0), Get(2)),
+                ]),
+                is_response,
+            ).retries(1);
+            vec![TestActor::Server, TestActor::Client(client)]
+        }
+
+        fn lossy_network(&self) -> LossyNetwork { LossyNetwork::Yes }
+
+        fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+
+        fn init_state_perturbations(&self, id: Id, state: &TestState) -> Vec<TestState> {
+            match (&self.actors()[usize::from(id)], state) {
+                (TestActor::Client(client), TestState::Client(state)) => {
+                    client.script_perturbations(state).into_iter().map(TestState::Client).collect()
+                }
+                _ => Vec::new(),
+            }
+        }
+
+        fn record_msg_out(&self, history: &Self::History, src: Id, _dst: Id, msg: &Msg) -> Option<Self::History> {
+            match msg {
+                Put(_, value) => history.on_request(src, msg, RegisterOp::Write(*value)),
+                Get(_) => history.on_request(src, msg, RegisterOp::Read),
+                _ => None,
+            }
+        }
+
+        fn record_msg_in(&self, history: &Self::History, _src: Id, dst: Id, msg: &Msg) -> Option<Self::History> {
+            match msg {
+                PutOk(_) => history.on_response(dst, RegisterRet::WriteOk),
+                GetOk(_, value) => history.on_response(dst, RegisterRet::ReadOk(*value)),
+                _ => None,
+            }
+        }
+
+        fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+            vec![
+                Property::<SystemModel<Self>>::always("linearizable", |_, state| {
+                    state.history.tester.is_consistent()
+                }),
+                Property::<SystemModel<Self>>::sometimes("read B", |_, state| {
+                    matches!(&*state.actor_states[1],
+                             TestState::Client(s) if s.position == 4)
+                        && matches!(&*state.actor_states[0], TestState::Server('B'))
+                }),
+                Property::<SystemModel<Self>>::sometimes("gave up", |_, state| {
+                    state.history.tester.len() == 2
+                        && *state.actor_states[0] == TestState::Server(TestValue::default())
+                }),
+            ]
+        }
+    }
+
+    #[test]
+    fn flattens_workload_into_scripts() {
+        let workload = Workload::Seq(vec![
+            Workload::Choice(vec![Workload::Request(Id::from(0), 'a'), Workload::Think]),
+            Workload::Choice(vec![Workload::Request(Id::from(0), 'b'), Workload::Request(Id::from(1), 'c')]),
+        ]);
+        assert_eq!(workload.scripts(), vec![
+            vec![WorkloadStep::Request(Id::from(0), 'a'), WorkloadStep::Request(Id::from(0), 'b')],
+            vec![WorkloadStep::Request(Id::from(0), 'a'), WorkloadStep::Request(Id::from(1), 'c')],
+            vec![WorkloadStep::Think, WorkloadStep::Request(Id::from(0), 'b')],
+            vec![WorkloadStep::Think, WorkloadStep::Request(Id::from(1), 'c')],
+        ]);
+    }
+
+    #[test]
+    fn can_drive_system_under_test() {
+        let model = TestSystem.into_model();
+        assert_eq!(model.init_states().len(), 2);
+        let checker = model.checker().spawn_bfs().join();
+        checker.assert_properties();
+    }
+}
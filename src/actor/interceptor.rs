@@ -0,0 +1,132 @@
+//! Private module for selective re-export.
+
+use crate::actor::Id;
+use parking_lot::Mutex;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Decides the fate of each message sent by actors started via [`SpawnOptions::intercept`],
+/// which enables running the fault scenarios explored by the model checker against real actors.
+///
+/// See [`ChaosNetwork`] for an implementation that mirrors the model's network modes.
+///
+/// [`SpawnOptions::intercept`]: crate::actor::SpawnOptions::intercept
+pub trait NetworkInterceptor: Send + Sync {
+    /// Indicates when to send each copy of a serialized message from `src` to `dst`, as delays
+    /// relative to when the actor sent it. An empty list drops the message, multiple delays
+    /// duplicate it, and differing delays reorder it relative to other messages.
+    fn on_send(&self, src: Id, dst: Id, bytes: &[u8]) -> Vec<Duration>;
+}
+
+/// A [`NetworkInterceptor`] that randomly drops, duplicates, and delays messages, mirroring
+/// [`LossyNetwork`], [`DuplicatingNetwork`], and [`System::reordering_window`] respectively.
+/// Faults are chosen by a random number generator seeded by the caller, although the resulting
+/// schedule also depends on thread timing.
+///
+/// ```
+/// use stateright::actor::{ChaosNetwork, Id, NetworkInterceptor};
+/// use std::time::Duration;
+///
+/// let network = ChaosNetwork::new(7).lossy(1.0);
+/// assert_eq!(network.on_send(Id::from(0), Id::from(1), b"msg"), vec![]);
+///
+/// let network = ChaosNetwork::new(7)
+///     .duplicating(1.0)
+///     .reordering(Duration::from_millis(1)..Duration::from_millis(10));
+/// let delays = network.on_send(Id::from(0), Id::from(1), b"msg");
+/// assert_eq!(delays.len(), 2);
+/// assert!(delays.iter().all(|d| *d >= Duration::from_millis(1)));
+/// ```
+///
+/// [`LossyNetwork`]: crate::actor::LossyNetwork
+/// [`DuplicatingNetwork`]: crate::actor::DuplicatingNetwork
+/// [`System::reordering_window`]: crate::actor::System::reordering_window
+pub struct ChaosNetwork {
+    rng: Mutex<StdRng>,
+    drop_probability: f64,
+    duplicate_probability: f64,
+    delay: Range<Duration>,
+}
+
+impl ChaosNetwork {
+    /// A network that delivers every message once without delay until configured otherwise.
+    pub fn new(seed: u64) -> Self {
+        ChaosNetwork {
+            rng: Mutex::new(StdRng::seed_from_u64(seed)),
+            drop_probability: 0.0,
+            duplicate_probability: 0.0,
+            delay: Duration::from_secs(0)..Duration::from_secs(0),
+        }
+    }
+
+    /// Drops each message with the specified probability.
+    pub fn lossy(mut self, drop_probability: f64) -> Self {
+        self.drop_probability = drop_probability;
+        self
+    }
+
+    /// Sends a second copy of each (undropped) message with the specified probability.
+    pub fn duplicating(mut self, duplicate_probability: f64) -> Self {
+        self.duplicate_probability = duplicate_probability;
+        self
+    }
+
+    /// Delays each copy of a message by a duration chosen from a range, so messages can arrive
+    /// out of order.
+    pub fn reordering(mut self, delay: Range<Duration>) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+impl NetworkInterceptor for ChaosNetwork {
+    fn on_send(&self, _src: Id, _dst: Id, _bytes: &[u8]) -> Vec<Duration> {
+        let mut rng = self.rng.lock();
+        if rng.gen_bool(self.drop_probability.clamp(0.0, 1.0)) {
+            return Vec::new();
+        }
+        let copies = if rng.gen_bool(self.duplicate_probability.clamp(0.0, 1.0)) { 2 } else { 1 };
+        (0..copies)
+            .map(|_| {
+                if self.delay.start < self.delay.end {
+                    rng.gen_range(self.delay.start, self.delay.end)
+                } else {
+                    self.delay.start
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fates(network: &ChaosNetwork) -> Vec<Vec<Duration>> {
+        (0..100).map(|_| network.on_send(Id::from(0), Id::from(1), &[])).collect()
+    }
+
+    #[test]
+    fn delivers_once_by_default() {
+        assert!(fates(&ChaosNetwork::new(0)).iter().all(|delays| {
+            delays == &vec![Duration::from_secs(0)]
+        }));
+    }
+
+    #[test]
+    fn is_deterministic_per_seed() {
+        let network = || ChaosNetwork::new(3)
+            .lossy(0.2)
+            .duplicating(0.2)
+            .reordering(Duration::from_millis(1)..Duration::from_millis(100));
+        let first = fates(&network());
+        assert_eq!(first, fates(&network()));
+        assert!(first.iter().any(|delays| delays.is_empty()));
+        assert!(first.iter().any(|delays| delays.len() == 2));
+        let mut delays: Vec<_> = first.iter().flatten().collect();
+        delays.dedup();
+        assert!(delays.len() > 1);
+    }
+}
@@ -0,0 +1,420 @@
+//! Defines an interface for key-value store actors (via [`KvMsg`]) and also provides
+//! [`KvTestSystem`] for model checking.
+
+use crate::Property;
+use crate::actor::{Actor, Id, Out};
+use crate::actor::register::{TestRequestId, TestValue};
+use crate::actor::system::{DuplicatingNetwork, LossyNetwork, System, SystemModel, SystemState};
+use crate::semantics::map::{MapOp, MapRet};
+use crate::semantics::register::{Register, RegisterOp, RegisterRet};
+use crate::semantics::{LinearizabilityTester, SequentialConsistencyTester};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Defines an interface for a key-value store actor.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum KvMsg<RequestId, Key, Value, InternalMsg> {
+    /// A message specific to the key-value store's internal protocol.
+    Internal(InternalMsg),
+
+    /// Indicates that a value should be written for a key.
+    Put(RequestId, Key, Value),
+    /// Indicates that the value for a key should be retrieved.
+    Get(RequestId, Key),
+
+    /// Indicates a successful `Put`. Analogous to an HTTP 2XX.
+    PutOk(RequestId),
+    /// Indicates a successful `Get`, with `None` if the key has no value. Analogous to an HTTP
+    /// 2XX.
+    GetOk(RequestId, Option<Value>),
+}
+use KvMsg::*;
+
+/// A system for testing an actor service with key-value store semantics.
+#[derive(Clone)]
+pub struct KvTestSystem<ServerActor, InternalMsg>
+where
+    ServerActor: Actor<Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>> + Clone,
+    InternalMsg: Clone + Debug + Eq + Hash,
+{
+    pub servers: Vec<ServerActor>,
+    pub client_count: u8,
+    /// The number of distinct keys that clients access.
+    pub key_count: u8,
+    pub within_boundary: fn(state: &SystemState<Self>) -> bool,
+    pub lossy_network: LossyNetwork,
+    pub duplicating_network: DuplicatingNetwork,
+    /// Whether to also check that some serial order of all operations (across keys, and without
+    /// regard to real time) explains every response. See [`KvTestHistory::all_keys`].
+    pub check_serializability: bool,
+}
+
+impl<ServerActor, InternalMsg> Default for KvTestSystem<ServerActor, InternalMsg>
+    where
+    ServerActor: Actor<Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>> + Clone,
+    InternalMsg: Clone + Debug + Eq + Hash,
+{
+    fn default() -> Self {
+        Self {
+            servers: Vec::new(),
+            client_count: 2,
+            key_count: 2,
+            within_boundary: |_| true,
+            lossy_network: LossyNetwork::No,
+            duplicating_network: DuplicatingNetwork::Yes,
+            check_serializability: false,
+        }
+    }
+}
+
+/// The history of a [`KvTestSystem`].
+#[derive(Clone, Debug, Default, Hash, PartialEq)]
+#[derive(serde::Serialize)]
+pub struct KvTestHistory {
+    /// Operations grouped by key. Linearizability is a local property, so the system is
+    /// linearizable if the history of each key is linearizable, and checking keys independently
+    /// is considerably cheaper than checking them together.
+    pub by_key: BTreeMap<TestKey, LinearizabilityTester<Id, Register<Option<TestValue>>>>,
+    /// Operations across all keys, each treated as a single-operation transaction, if
+    /// [`KvTestSystem::check_serializability`]. Every operation is recorded under a distinct
+    /// thread ID, so the operations can be serialized in any order.
+    pub all_keys: Option<SequentialConsistencyTester<(Id, TestRequestId), BTreeMap<TestKey, TestValue>>>,
+}
+
+impl<ServerActor, InternalMsg> System for KvTestSystem<ServerActor, InternalMsg>
+    where
+        ServerActor: Actor<Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>> + Clone,
+        InternalMsg: Clone + Debug + Eq + Hash,
+{
+    type Actor = KvActor<ServerActor>;
+    type History = KvTestHistory;
+
+    fn actors(&self) -> Vec<Self::Actor> {
+        let mut actors: Vec<Self::Actor> = self.servers.iter().map(|s| {
+            KvActor::Server(s.clone())
+        }).collect();
+        for _ in 0..self.client_count {
+            actors.push(KvActor::Client {
+                server_count: self.servers.len() as u64,
+                key_count: self.key_count,
+            });
+        }
+        actors
+    }
+
+    fn lossy_network(&self) -> LossyNetwork {
+        self.lossy_network
+    }
+
+    fn duplicating_network(&self) -> DuplicatingNetwork {
+        self.duplicating_network
+    }
+
+    fn record_msg_out(&self, history: &Self::History, src: Id, _dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
+        // An invalid history poisons the tester, which records the offending operation (see
+        // `ConsistencyTester::invalid_history`) and fails the consistency property.
+        let (req_id, key, op, map_op) = match msg {
+            Get(req_id, key) => (req_id, key, RegisterOp::Read, MapOp::Get(*key)),
+            Put(req_id, key, value) => (
+                req_id, key, RegisterOp::Write(Some(*value)), MapOp::Put(*key, *value)),
+            _ => return None,
+        };
+        let mut history = history.clone();
+        let _ = history.by_key.entry(*key).or_default().on_invoke(src, op);
+        if self.check_serializability {
+            let _ = history.all_keys.get_or_insert_with(Default::default)
+                .on_invoke((src, *req_id), map_op);
+        }
+        Some(history)
+    }
+
+    fn record_msg_in(&self, history: &Self::History, _src: Id, dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
+        // See `record_msg_out` regarding invalid histories.
+        let (req_id, ret, map_ret) = match msg {
+            GetOk(req_id, v) => (req_id, RegisterRet::ReadOk(*v), MapRet::GetOk(*v)),
+            PutOk(req_id) => (req_id, RegisterRet::WriteOk, MapRet::PutOk),
+            _ => return None,
+        };
+        let mut history = history.clone();
+        // Clients have at most one operation in flight, which identifies the key.
+        let tester = history.by_key.values_mut().find(|t| t.is_in_flight(dst))?;
+        let _ = tester.on_return(dst, ret);
+        if let Some(all_keys) = &mut history.all_keys {
+            let _ = all_keys.on_return((dst, *req_id), map_ret);
+        }
+        Some(history)
+    }
+
+    fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+        let mut properties = vec![
+            Property::<SystemModel<Self>>::always("linearizable", |_, state| {
+                state.history.by_key.values().all(|t| t.serialized_history().is_some())
+            }),
+            Property::<SystemModel<Self>>::sometimes("value chosen",  |_, state| {
+                for env in &state.network {
+                    if let KvMsg::GetOk(_req_id, Some(_)) = env.msg {
+                        return true;
+                    }
+                }
+                false
+            }),
+        ];
+        if self.check_serializability {
+            properties.push(Property::<SystemModel<Self>>::always("serializable", |_, state| {
+                state.history.all_keys.as_ref()
+                    .map(|t| t.serialized_history().is_some())
+                    .unwrap_or(true)
+            }));
+        }
+        properties
+    }
+
+    fn within_boundary(&self, state: &SystemState<Self>) -> bool {
+        (self.within_boundary)(state)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum KvActor<ServerActor> {
+    /// A client that [`KvMsg::Put`]s a value for one key and then [`KvMsg::Get`]s every key,
+    /// starting with the one it wrote, awaiting each response before sending the next request.
+    Client {
+        server_count: u64,
+        key_count: u8,
+    },
+    /// A server actor being validated.
+    Server(ServerActor),
+}
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize)]
+pub enum KvActorState<ServerState> {
+    /// A client that sends a [`KvMsg::Put`] followed by a [`KvMsg::Get`] per key.
+    Client {
+        awaiting: Option<TestRequestId>,
+        op_count: u64,
+    },
+    /// Wraps the state of a server actor.
+    Server(ServerState),
+}
+
+// This implementation assumes the servers are at the beginning of the list of
+// actors in the system under test so that an arbitrary server destination ID
+// can be derived from `(client_id.0 + k) % server_count` for any `k`.
+impl<ServerActor, InternalMsg> Actor for KvActor<ServerActor>
+where
+    ServerActor: Actor<Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>>,
+    InternalMsg: Clone + Debug + Eq + Hash,
+{
+    type Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>;
+    type State = KvActorState<ServerActor::State>;
+
+    fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+        match self {
+            KvActor::Client { server_count, key_count } => {
+                next_client_op(id, *server_count, *key_count, 0, o)
+            }
+            KvActor::Server(server_actor) => {
+                let mut server_out = Out::new();
+                let state = KvActorState::Server(server_actor.on_start(id, &mut server_out));
+                o.append(&mut server_out);
+                state
+            }
+        }
+    }
+
+    fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        use KvActor as A;
+        use KvActorState as S;
+
+        match (self, &**state) {
+            (A::Client { server_count, key_count }, S::Client {
+                                             awaiting: Some(awaiting),
+                                             op_count
+                                         }) => {
+                match msg {
+                    KvMsg::PutOk(request_id) | KvMsg::GetOk(request_id, _)
+                        if &request_id == awaiting =>
+                    {
+                        *state = Cow::Owned(
+                            next_client_op(id, *server_count, *key_count, *op_count, o));
+                    }
+                    _ => {}
+                }
+            }
+            (A::Server(server_actor), S::Server(server_state)) => {
+                let mut server_state = Cow::Borrowed(server_state);
+                let mut server_out = Out::new();
+                server_actor.on_msg(id, &mut server_state, src, msg, &mut server_out);
+                if let Cow::Owned(server_state) = server_state {
+                    *state = Cow::Owned(KvActorState::Server(server_state))
+                }
+                o.append(&mut server_out);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+        if let (KvActor::Server(server_actor), KvActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_timeout(id, &mut server_state, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(KvActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_config_update(&self, id: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+        if let (KvActor::Server(server_actor), KvActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_config_update(id, &mut server_state, config, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(KvActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_peer_down(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        if let (KvActor::Server(server_actor), KvActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_peer_down(id, &mut server_state, peer, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(KvActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_peer_reset(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        if let (KvActor::Server(server_actor), KvActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_peer_reset(id, &mut server_state, peer, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(KvActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_random(&self, id: Id, state: &mut Cow<Self::State>, value: u64, o: &mut Out<Self>) {
+        if let (KvActor::Server(server_actor), KvActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_random(id, &mut server_state, value, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(KvActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+        match self {
+            KvActor::Server(server_actor) => server_actor.spawned_actor(spec).map(KvActor::Server),
+            _ => None,
+        }
+    }
+}
+
+/// Sends the client's next operation after `op_count` operations have finished: a `Put` for the
+/// client's own key, then a `Get` for each key.
+fn next_client_op<ServerActor, InternalMsg>(
+    id: Id, server_count: u64, key_count: u8, op_count: u64, o: &mut Out<KvActor<ServerActor>>)
+    -> KvActorState<ServerActor::State>
+where
+    ServerActor: Actor<Msg = KvMsg<TestRequestId, TestKey, TestValue, InternalMsg>>,
+    InternalMsg: Clone + Debug + Eq + Hash,
+{
+    let index = id.0;
+    if op_count > key_count as u64 {
+        return KvActorState::Client { awaiting: None, op_count };
+    }
+    let unique_request_id = ((op_count + 1) * index) as TestRequestId;
+    let dst = Id((index + op_count) % server_count);
+    let client_index = index - server_count;
+    if op_count == 0 {
+        let key = (client_index % key_count as u64) as TestKey;
+        let value = (b'A' + client_index as u8) as char;
+        o.send(dst, Put(unique_request_id, key, value));
+    } else {
+        let key = ((client_index + op_count - 1) % key_count as u64) as TestKey;
+        o.send(dst, Get(unique_request_id, key));
+    }
+    KvActorState::Client {
+        awaiting: Some(unique_request_id),
+        op_count: op_count + 1,
+    }
+}
+
+/// A simple key type for tests.
+pub type TestKey = u8;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model};
+
+    /// Stores values by key, unless `ignores_keys`, in which case every key shares one value.
+    #[derive(Clone)]
+    struct SingleCopyKvActor { ignores_keys: bool }
+
+    impl Actor for SingleCopyKvActor {
+        type Msg = KvMsg<TestRequestId, TestKey, TestValue, ()>;
+        type State = BTreeMap<TestKey, TestValue>;
+
+        fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State {
+            BTreeMap::new()
+        }
+
+        fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+            match msg {
+                Put(req_id, key, value) => {
+                    let key = if self.ignores_keys { 0 } else { key };
+                    state.to_mut().insert(key, value);
+                    o.send(src, PutOk(req_id));
+                }
+                Get(req_id, key) => {
+                    let key = if self.ignores_keys { 0 } else { key };
+                    o.send(src, GetOk(req_id, state.get(&key).copied()));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn can_model_single_copy_kv_store() {
+        let checker = KvTestSystem {
+            servers: vec![SingleCopyKvActor { ignores_keys: false }],
+            duplicating_network: DuplicatingNetwork::No,
+            check_serializability: true,
+            .. Default::default()
+        }.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+    }
+
+    #[test]
+    fn detects_cross_key_bugs() {
+        let checker = KvTestSystem {
+            servers: vec![SingleCopyKvActor { ignores_keys: true }],
+            duplicating_network: DuplicatingNetwork::No,
+            check_serializability: true,
+            .. Default::default()
+        }.into_model().checker().spawn_bfs().join();
+        let path = checker.discovery("linearizable").unwrap();
+        assert!(checker.discovery("serializable").is_some());
+
+        // The reads observe a value that was written for a different key.
+        let last_state = path.last_state();
+        assert!(last_state.history.by_key.values().any(|t| t.serialized_history().is_none()));
+    }
+}
@@ -0,0 +1,201 @@
+//! Private module for selective re-export.
+
+use crate::{Expectation, Model};
+use crate::actor::{Actor, AtomicBroadcast, DuplicatingNetwork, LossyNetwork, System, SystemModel, TimeoutOrdering};
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use std::fmt::{Debug, Display, Formatter};
+
+/// A machine-readable description of a [`SystemModel`], for tooling that needs to describe a model
+/// without checking it. Obtained via [`SystemModel::metadata`] and typically exported via
+/// [`SystemMetadata::to_json`].
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize)]
+pub struct SystemMetadata {
+    /// The name of the actor type.
+    pub actor_type: &'static str,
+    /// The actors, in [`Id`](crate::actor::Id) order.
+    pub actors: Vec<ActorMetadata>,
+    /// The name of the message type.
+    pub msg_type: &'static str,
+    /// The variant names if the message type is an enum.
+    pub msg_variants: Vec<&'static str>,
+    /// The properties to be checked.
+    pub properties: Vec<PropertyMetadata>,
+    /// Messages on the network before any actor starts, rendered via `Debug`.
+    pub init_network: Vec<String>,
+    /// Configuration updates as `(actor index, config)` pairs, rendered via `Debug`.
+    pub config_updates: Vec<(usize, String)>,
+    /// Whether the network loses messages.
+    pub lossy_network: bool,
+    /// Whether the network duplicates messages.
+    pub duplicating_network: bool,
+    /// Whether timeouts only fire once no messages for the actor are deliverable.
+    pub timeouts_after_deliveries: bool,
+    /// How far messages on a channel may be reordered, if bounded.
+    pub reordering_window: Option<usize>,
+    /// How many later messages on a channel may overtake any one message, if bounded.
+    pub max_overtaking: Option<usize>,
+    /// How many distinct messages the network can hold, if bounded.
+    pub max_in_flight: Option<usize>,
+    /// Whether broadcasts send to every recipient in a single step.
+    pub atomic_broadcast: bool,
+    /// How many actors may crash.
+    pub max_crashes: usize,
+}
+
+/// Describes an actor within [`SystemMetadata`].
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize)]
+pub struct ActorMetadata {
+    /// The actor's index, which is also its [`Id`](crate::actor::Id).
+    pub index: usize,
+    /// The state returned by [`Actor::on_start`], rendered via `Debug`.
+    pub init_state: String,
+}
+
+/// Describes a [`Property`](crate::Property) within [`SystemMetadata`].
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize)]
+pub struct PropertyMetadata {
+    /// The property name.
+    pub name: &'static str,
+    /// Whether the property is always, eventually, or sometimes true.
+    pub expectation: Expectation,
+}
+
+impl SystemMetadata {
+    /// Renders the metadata as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("metadata is always serializable")
+    }
+}
+
+impl<S: System> SystemModel<S> {
+    /// Describes the system without checking it. Message variant names are derived from the
+    /// message type's `serde::Deserialize` implementation.
+    pub fn metadata(&self) -> SystemMetadata
+    where <S::Actor as Actor>::Msg: DeserializeOwned,
+    {
+        SystemMetadata {
+            actor_type: std::any::type_name::<S::Actor>(),
+            actors: self.unperturbed_init_state().actor_states.into_iter()
+                .enumerate()
+                .map(|(index, state)| ActorMetadata {
+                    index,
+                    init_state: format!("{:?}", state),
+                })
+                .collect(),
+            msg_type: std::any::type_name::<<S::Actor as Actor>::Msg>(),
+            msg_variants: variants::<<S::Actor as Actor>::Msg>().to_vec(),
+            properties: self.properties().into_iter()
+                .map(|p| PropertyMetadata { name: p.name, expectation: p.expectation })
+                .collect(),
+            init_network: self.init_network.iter()
+                .map(|env| format!("{:?}", env))
+                .collect(),
+            config_updates: self.config_updates.iter()
+                .map(|(id, config)| (usize::from(*id), format!("{:?}", config)))
+                .collect(),
+            lossy_network: self.lossy_network == LossyNetwork::Yes,
+            duplicating_network: self.duplicating_network != DuplicatingNetwork::No,
+            timeouts_after_deliveries: self.timeout_ordering == TimeoutOrdering::AfterDeliveries,
+            reordering_window: self.reordering_window,
+            max_overtaking: self.max_overtaking,
+            max_in_flight: self.max_in_flight,
+            atomic_broadcast: self.atomic_broadcast == AtomicBroadcast::Yes,
+            max_crashes: self.max_crashes,
+        }
+    }
+}
+
+/// Returns the variant names of an enum by asking its `Deserialize` implementation to read from a
+/// deserializer that records the variants and then aborts. Returns an empty list for other types.
+fn variants<T: DeserializeOwned>() -> &'static [&'static str] {
+    let mut variants: &'static [&'static str] = &[];
+    let _ = T::deserialize(VariantRecorder { variants: &mut variants });
+    variants
+}
+
+struct VariantRecorder<'a> {
+    variants: &'a mut &'static [&'static str],
+}
+
+#[derive(Debug)]
+struct Abort;
+
+impl Display for Abort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "aborted")
+    }
+}
+
+impl std::error::Error for Abort {}
+
+impl de::Error for Abort {
+    fn custom<T: Display>(_msg: T) -> Self { Abort }
+}
+
+impl<'de, 'a> Deserializer<'de> for VariantRecorder<'a> {
+    type Error = Abort;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Abort> {
+        Err(Abort)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self, _name: &'static str, variants: &'static [&'static str], _visitor: V)
+        -> Result<V::Value, Abort>
+    {
+        *self.variants = variants;
+        Err(Abort)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actor::actor_test_util::ping_pong::PingPongSystem;
+    use crate::actor::register::{RegisterMsg, TestRequestId, TestValue};
+
+    #[test]
+    fn records_enum_variants() {
+        assert_eq!(
+            variants::<RegisterMsg<TestRequestId, TestValue, ()>>(),
+            &["Internal", "Put", "Get", "Cas", "PutOk", "GetOk", "CasOk"]);
+        assert!(variants::<u64>().is_empty());
+    }
+
+    #[test]
+    fn describes_system() {
+        let metadata = PingPongSystem {
+            max_nat: 1,
+            lossy: LossyNetwork::Yes,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        }.into_model().metadata();
+        assert_eq!(metadata.actors.len(), 2);
+        assert_eq!(metadata.actors[0].init_state, "PingPongCount(0)");
+        assert_eq!(metadata.msg_variants, vec!["Ping", "Pong"]);
+        assert!(metadata.lossy_network);
+        assert!(!metadata.duplicating_network);
+        assert!(!metadata.timeouts_after_deliveries);
+        assert_eq!(metadata.reordering_window, None);
+        assert_eq!(metadata.max_overtaking, None);
+        assert_eq!(metadata.max_in_flight, None);
+        assert!(metadata.atomic_broadcast);
+        assert_eq!(metadata.max_crashes, 0);
+        assert_eq!(
+            metadata.properties.iter().map(|p| p.name).collect::<Vec<_>>(),
+            vec!["delta within 1", "can reach max", "must reach max", "must exceed max",
+                 "#in <= #out", "#out <= #in + 1"]);
+
+        let json = metadata.to_json();
+        assert!(json.contains(r#""expectation": "Always""#));
+        assert!(json.contains(r#""msg_variants": ["#));
+    }
+}
@@ -0,0 +1,166 @@
+//! Private module for selective re-export.
+
+/// Declares a message type for an actor composed of nested actors, with a variant embedding the
+/// messages of each nested actor. Also implements `From` to embed each nested message and
+/// `TryFrom` to extract it (returning the original message otherwise), which pairs with
+/// [`Out::nested`] to delegate to nested actors.
+///
+/// Each variant must embed a distinct type. Generic message types are not supported, but a
+/// variant can embed a particular instantiation of a generic type.
+///
+/// The macro only declares the message type. It does not generate the composed actor's
+/// `on_msg` (or other callbacks), because how nested actors share the composed actor's state
+/// varies; write that delegation by hand with [`Out::nested`], as in the example below.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Checker, Model, Property, nested_msg};
+/// use stateright::actor::{Actor, Id, Out, System, SystemModel};
+/// use std::borrow::Cow;
+/// use std::convert::TryFrom;
+///
+/// /// Counts pings up to 3.
+/// #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// struct Ping(u32);
+///
+/// struct Pinger;
+///
+/// impl Actor for Pinger {
+///     type Msg = Ping;
+///     type State = u32;
+///
+///     fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State { 0 }
+///
+///     fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+///         if msg.0 < 3 { o.send(src, Ping(msg.0 + 1)); }
+///         *state.to_mut() = msg.0;
+///     }
+/// }
+///
+/// #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+/// struct Hello;
+///
+/// nested_msg! {
+///     /// A node handles `Hello` itself and delegates `Ping` to a nested `Pinger`.
+///     #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+///     enum NodeMsg {
+///         Hello(Hello),
+///         Ping(Ping),
+///     }
+/// }
+///
+/// struct Node { peer: Option<Id>, pinger: Pinger }
+///
+/// impl Actor for Node {
+///     type Msg = NodeMsg;
+///     type State = u32; // the pinger's state
+///
+///     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+///         if let Some(peer) = self.peer { o.send(peer, Hello.into()); }
+///         o.nested(|o| self.pinger.on_start(id, o))
+///     }
+///
+///     fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+///         match Ping::try_from(msg) {
+///             Ok(ping) => o.nested(|o| self.pinger.on_msg(id, state, src, ping, o)),
+///             Err(NodeMsg::Hello(Hello)) => o.send(src, Ping(0).into()),
+///             Err(_) => {}
+///         }
+///     }
+/// }
+///
+/// struct NodeSystem;
+///
+/// impl System for NodeSystem {
+///     type Actor = Node;
+///     type History = ();
+///
+///     fn actors(&self) -> Vec<Self::Actor> {
+///         vec![
+///             Node { peer: None, pinger: Pinger },
+///             Node { peer: Some(Id::from(0)), pinger: Pinger },
+///         ]
+///     }
+///
+///     fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+///         vec![Property::<SystemModel<Self>>::sometimes("pinged", |_, state| {
+///             *state.actor_states[0] == 3
+///         })]
+///     }
+/// }
+///
+/// NodeSystem.into_model().checker().spawn_bfs().join().assert_properties();
+/// ```
+///
+/// [`Out::nested`]: crate::actor::Out::nested
+#[macro_export]
+macro_rules! nested_msg {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident($nested:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$variant_meta])* $variant($nested)),+
+        }
+
+        $(
+            impl ::std::convert::From<$nested> for $name {
+                fn from(msg: $nested) -> Self {
+                    $name::$variant(msg)
+                }
+            }
+
+            impl ::std::convert::TryFrom<$name> for $nested {
+                type Error = $name;
+
+                #[allow(unreachable_patterns)]
+                fn try_from(msg: $name) -> ::std::result::Result<Self, Self::Error> {
+                    match msg {
+                        $name::$variant(msg) => ::std::result::Result::Ok(msg),
+                        msg => ::std::result::Result::Err(msg),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::actor::register::RegisterMsg;
+    use std::convert::TryFrom;
+
+    type Register = RegisterMsg<u64, char, ()>;
+
+    nested_msg! {
+        #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+        pub enum ComposedMsg {
+            Register(Register),
+            /// Variants can be documented.
+            Count(u32),
+        }
+    }
+
+    nested_msg! {
+        #[derive(Debug, PartialEq)]
+        enum SingleMsg { Only(String) }
+    }
+
+    #[test]
+    fn embeds_and_extracts() {
+        let msg = ComposedMsg::from(RegisterMsg::Get(1));
+        assert_eq!(msg, ComposedMsg::Register(RegisterMsg::Get(1)));
+        assert_eq!(Register::try_from(msg.clone()), Ok(RegisterMsg::Get(1)));
+        assert_eq!(u32::try_from(msg.clone()), Err(msg));
+
+        let msg: ComposedMsg = 7.into();
+        assert_eq!(u32::try_from(msg), Ok(7));
+
+        let msg: SingleMsg = String::from("x").into();
+        assert_eq!(String::try_from(msg), Ok(String::from("x")));
+    }
+}
@@ -0,0 +1,148 @@
+//! Private module for selective re-export.
+
+use crate::actor::Envelope;
+use std::fmt::{self, Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::iter::FromIterator;
+
+/// Represents a network of messages.
+///
+/// Backed by a persistent hash set, so cloning a network (as happens for every successor of a
+/// [`SystemState`]) is constant time, and each successor only copies the part of the set that
+/// its own sends and deliveries modify. Iteration order is consistent across runs and platforms,
+/// and hashing is independent of iteration order, so fingerprints and discovery paths can be
+/// reproduced on other machines.
+///
+/// [`SystemState`]: crate::actor::SystemState
+pub struct Network<Msg>(im::HashSet<Envelope<Msg>, crate::stable::BuildStableHasher>);
+
+impl<Msg> Network<Msg> {
+    /// Constructs an empty network.
+    pub fn new() -> Self {
+        Network(im::HashSet::with_hasher(crate::stable::BuildStableHasher)) // for consistent discoveries
+    }
+
+    /// Iterates over the messages in the network.
+    pub fn iter(&self) -> im::hashset::Iter<'_, Envelope<Msg>> {
+        self.0.iter()
+    }
+
+    /// Returns the number of messages in the network.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether the network is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<Msg: Clone + Eq + Hash> Network<Msg> {
+    /// Indicates whether the network contains a message.
+    pub fn contains(&self, env: &Envelope<Msg>) -> bool {
+        self.0.contains(env)
+    }
+
+    /// Adds a message to the network, returning `true` if it was absent.
+    pub fn insert(&mut self, env: Envelope<Msg>) -> bool {
+        self.0.insert(env).is_none()
+    }
+
+    /// Removes a message from the network, returning `true` if it was present.
+    pub fn remove(&mut self, env: &Envelope<Msg>) -> bool {
+        self.0.remove(env).is_some()
+    }
+}
+
+impl<Msg: Clone> Clone for Network<Msg> {
+    fn clone(&self) -> Self {
+        Network(self.0.clone())
+    }
+}
+
+impl<Msg: Debug> Debug for Network<Msg> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.0.iter()).finish()
+    }
+}
+
+impl<Msg> Default for Network<Msg> {
+    fn default() -> Self {
+        Network::new()
+    }
+}
+
+impl<Msg: Eq + Hash> Eq for Network<Msg> {}
+
+impl<Msg: Clone + Eq + Hash> FromIterator<Envelope<Msg>> for Network<Msg> {
+    fn from_iter<T: IntoIterator<Item = Envelope<Msg>>>(iter: T) -> Self {
+        let mut network = Network::new();
+        for env in iter {
+            network.insert(env);
+        }
+        network
+    }
+}
+
+impl<Msg: Hash> Hash for Network<Msg> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        crate::util::hash_unordered(self.0.iter(), hasher);
+    }
+}
+
+impl<'a, Msg> IntoIterator for &'a Network<Msg> {
+    type Item = &'a Envelope<Msg>;
+    type IntoIter = im::hashset::Iter<'a, Envelope<Msg>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<Msg: Eq + Hash> PartialEq for Network<Msg> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().all(|env| other.0.contains(env))
+    }
+}
+
+impl<Msg: serde::Serialize> serde::Serialize for Network<Msg> {
+    fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
+        ser.collect_seq(self.0.iter())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::actor::{Envelope, Id, Network};
+    use crate::fingerprint;
+
+    fn env(src: usize, msg: char) -> Envelope<char> {
+        Envelope { src: Id::from(src), dst: Id::from(0), msg }
+    }
+
+    #[test]
+    fn shares_structure_without_aliasing() {
+        let original: Network<char> = vec![env(1, 'a'), env(2, 'b')].into_iter().collect();
+        let mut modified = original.clone();
+        assert!(modified.remove(&env(1, 'a')));
+        assert!(modified.insert(env(3, 'c')));
+        assert!(!modified.insert(env(3, 'c')));
+
+        assert_eq!(original.len(), 2);
+        assert!(original.contains(&env(1, 'a')));
+        assert!(!original.contains(&env(3, 'c')));
+        assert_eq!(modified.len(), 2);
+        assert!(!modified.contains(&env(1, 'a')));
+        assert_ne!(original, modified);
+    }
+
+    #[test]
+    fn hash_is_independent_of_insertion_order() {
+        let forward: Network<char> = (0..100).map(|i| env(i, 'x')).collect();
+        let backward: Network<char> = (0..100).rev().map(|i| env(i, 'x')).collect();
+        assert_eq!(forward, backward);
+        assert_eq!(fingerprint(&forward), fingerprint(&backward));
+        assert_ne!(fingerprint(&forward), fingerprint(&Network::<char>::new()));
+    }
+}
@@ -8,6 +8,7 @@
 use crate::actor::*;
 use crate::util::HashableHashMap;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::time::Duration;
 use std::ops::Range;
@@ -17,9 +18,26 @@ use std::hash::Hash;
 /// 1. Maintain message order.
 /// 2. Resend lost messages.
 /// 3. Avoid message redelivery.
+/// 4. Reset sessions with peers that restart.
+///
+/// Messages to each destination are numbered, and at most `window_size` consecutively numbered
+/// messages starting from the earliest unacknowledged message are in flight at a time (i.e.
+/// "selective repeat"). Each message is acknowledged individually, so only unacknowledged
+/// messages in the window are resent, and the recipient buffers messages that arrive early until
+/// the preceding messages arrive.
+///
+/// Each incarnation of an actor is identified by an `epoch`, which must increase whenever the
+/// actor restarts (for example by deriving it from the system clock or a persisted counter).
+/// Messages indicate the [`Session`] to which they belong, so that messages from or to a previous
+/// incarnation are ignored. Once an actor learns that a peer restarted, it renumbers the messages
+/// that the peer has yet to acknowledge, resends them to the new incarnation, and calls
+/// [`Actor::on_peer_reset`] for the wrapped actor. The session with a peer is torn down if the
+/// peer is down per [`Actor::on_peer_down`], discarding messages in flight.
 #[derive(Clone)]
 pub struct ActorWrapper<A: Actor> {
     pub resend_interval: Range<Duration>,
+    pub window_size: usize,
+    pub epoch: Epoch,
     pub wrapped_actor: A,
 }
 
@@ -27,22 +45,43 @@ pub struct ActorWrapper<A: Actor> {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[derive(serde::Serialize, serde::Deserialize)]
 pub enum MsgWrapper<Msg> {
-    Deliver(Sequencer, Msg),
-    Ack(Sequencer),
+    Deliver(Session, Sequencer, Msg),
+    Ack(Session, Sequencer),
+    /// Indicates that a message was addressed to a previous incarnation of the sender, whose
+    /// current epoch is included.
+    Reset(Epoch),
 }
 
-/// Message sequencer.
+/// Message sequencer, which is specific to a source/destination pair.
 pub type Sequencer = u64;
 
+/// Identifies an incarnation of an actor.
+pub type Epoch = u64;
+
+/// Identifies the incarnations between which a message is delivered (or, for an ack, between
+/// which the acknowledged message was delivered).
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Session {
+    /// The epoch of the actor sending the delivered messages.
+    pub src_epoch: Epoch,
+    /// The epoch of the actor receiving the delivered messages, or `None` if the sender has yet
+    /// to learn it.
+    pub dst_epoch: Option<Epoch>,
+}
+
 /// Maintains state for the ORL.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct StateWrapper<Msg, State> {
+    peer_epochs: HashableHashMap<Id, Epoch>,
+
     // send side
-    next_send_seq: Sequencer,
-    msgs_pending_ack: HashableHashMap<Sequencer, (Id, Msg)>,
+    next_send_seqs: HashableHashMap<Id, Sequencer>,
+    msgs_pending_ack: BTreeMap<(Id, Sequencer), Msg>,
 
     // receive (ack'ing) side
     last_delivered_seqs: HashableHashMap<Id, Sequencer>,
+    msgs_pending_delivery: BTreeMap<(Id, Sequencer), Msg>,
 
     wrapped_state: State,
 }
@@ -51,9 +90,56 @@ impl<A: Actor> ActorWrapper<A> {
     pub fn with_default_timeout(wrapped_actor: A) -> Self {
         Self {
             resend_interval: Duration::from_secs(1)..Duration::from_secs(2),
+            window_size: 16,
+            epoch: 0,
             wrapped_actor,
         }
     }
+
+    /// Sets the maximum number of messages in flight to each destination, which must be at least
+    /// one. Actors that communicate should use the same window size, as a recipient ignores
+    /// messages beyond its own window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_size` is zero.
+    pub fn window_size(self, window_size: usize) -> Self {
+        assert!(window_size >= 1, "Window size must be at least one.");
+        Self { window_size, ..self }
+    }
+
+    /// Sets the epoch that identifies this incarnation of the actor.
+    pub fn epoch(self, epoch: Epoch) -> Self {
+        Self { epoch, ..self }
+    }
+
+    /// The session for messages sent to a peer.
+    fn session<Msg>(&self, state: &StateWrapper<Msg, A::State>, dst: Id) -> Session {
+        Session {
+            src_epoch: self.epoch,
+            dst_epoch: state.peer_epochs.get(&dst).copied(),
+        }
+    }
+
+    /// The sequencer after the last sequencer in the send window for a destination.
+    fn window_end<Msg>(&self, state: &StateWrapper<Msg, A::State>, dst: Id) -> Sequencer {
+        let window_start = state.msgs_pending_ack.range((dst, 0)..=(dst, Sequencer::MAX))
+            .next()
+            .map(|((_, seq), _)| *seq)
+            .unwrap_or_else(|| *state.next_send_seqs.get(&dst).unwrap_or(&1));
+        window_start.saturating_add(self.window_size as Sequencer)
+    }
+
+    /// Forgets messages exchanged with a peer, returning those that the peer did not acknowledge.
+    fn clear_session<Msg>(state: &mut StateWrapper<Msg, A::State>, peer: Id) -> Vec<Msg> {
+        state.last_delivered_seqs.remove(&peer);
+        state.msgs_pending_delivery.retain(|(src, _), _| *src != peer);
+        state.next_send_seqs.remove(&peer);
+        let seqs: Vec<_> = state.msgs_pending_ack.range((peer, 0)..=(peer, Sequencer::MAX))
+            .map(|(key, _)| *key)
+            .collect();
+        seqs.into_iter().filter_map(|key| state.msgs_pending_ack.remove(&key)).collect()
+    }
 }
 
 impl<A: Actor> Actor for ActorWrapper<A>
@@ -67,74 +153,227 @@ impl<A: Actor> Actor for ActorWrapper<A>
 
         let mut wrapped_out = Out::new();
         let mut state = StateWrapper {
-            next_send_seq: 1,
+            peer_epochs: Default::default(),
+            next_send_seqs: Default::default(),
             msgs_pending_ack: Default::default(),
             last_delivered_seqs: Default::default(),
+            msgs_pending_delivery: Default::default(),
             wrapped_state: self.wrapped_actor.on_start(id, &mut wrapped_out),
         };
-        process_output(&mut state, wrapped_out, o);
+        self.process_output(&mut state, wrapped_out, o);
         state
     }
 
     fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+        // Learn the sender's epoch, ignoring messages from (or acks to) a previous incarnation.
+        let src_epoch = match &msg {
+            MsgWrapper::Deliver(session, _, _) => session.src_epoch,
+            MsgWrapper::Ack(session, _) => match session.dst_epoch {
+                Some(dst_epoch) if session.src_epoch == self.epoch => dst_epoch,
+                _ => return,
+            },
+            MsgWrapper::Reset(epoch) => *epoch,
+        };
+        match state.peer_epochs.get(&src) {
+            Some(&known_epoch) if src_epoch < known_epoch => return,
+            Some(&known_epoch) if src_epoch > known_epoch => {
+                self.reset_session(id, state.to_mut(), src, src_epoch, o);
+            },
+            Some(_) => {},
+            None => {
+                state.to_mut().peer_epochs.insert(src, src_epoch);
+            },
+        }
+
         match msg {
-            MsgWrapper::Deliver(seq, wrapped_msg) => {
-                // Always ack the message to prevent re-sends, and early exit if already delivered.
-                o.send(src, MsgWrapper::Ack(seq));
-                if seq <= *state.last_delivered_seqs.get(&src).unwrap_or(&0) { return }
-
-                // Process the message, and early exit if ignored.
-                let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
-                let mut wrapped_out = Out::new();
-                self.wrapped_actor.on_msg(
-                    id, &mut wrapped_state, src, wrapped_msg, &mut wrapped_out);
-                if is_no_op(&wrapped_state, &wrapped_out) { return }
-
-                // Never delivered, and not ignored by actor, so update the sequencer and process the original output.
-                if let Cow::Owned(wrapped_state) = wrapped_state {
-                    // Avoid unnecessarily cloning wrapped_state by not calling to_mut() in this
-                    // case.
-                    *state = Cow::Owned(StateWrapper {
-                        next_send_seq: state.next_send_seq,
-                        msgs_pending_ack: state.msgs_pending_ack.clone(),
-                        last_delivered_seqs: state.last_delivered_seqs.clone(),
-                        wrapped_state,
-                    });
+            MsgWrapper::Deliver(session, seq, wrapped_msg) => {
+                // Ask the sender to reset the session if addressed to a previous incarnation.
+                if matches!(session.dst_epoch, Some(dst_epoch) if dst_epoch != self.epoch) {
+                    o.send(src, MsgWrapper::Reset(self.epoch));
+                    return;
+                }
+
+                // Ignore messages beyond the window (which are resent later). Otherwise always
+                // ack the message to prevent re-sends, and early exit if already received.
+                let last_delivered_seq = *state.last_delivered_seqs.get(&src).unwrap_or(&0);
+                if seq > last_delivered_seq.saturating_add(self.window_size as Sequencer) { return }
+                let ack_session = Session { src_epoch: session.src_epoch, dst_epoch: Some(self.epoch) };
+                o.send(src, MsgWrapper::Ack(ack_session, seq));
+                if seq <= last_delivered_seq
+                    || state.msgs_pending_delivery.contains_key(&(src, seq)) { return }
+
+                // Deliver messages that are next in order to the wrapped actor.
+                let state = state.to_mut();
+                state.msgs_pending_delivery.insert((src, seq), wrapped_msg);
+                let mut next_seq = last_delivered_seq + 1;
+                while let Some(wrapped_msg) = state.msgs_pending_delivery.remove(&(src, next_seq)) {
+                    let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+                    let mut wrapped_out = Out::new();
+                    self.wrapped_actor.on_msg(
+                        id, &mut wrapped_state, src, wrapped_msg, &mut wrapped_out);
+                    if let Cow::Owned(wrapped_state) = wrapped_state {
+                        state.wrapped_state = wrapped_state;
+                    }
+                    state.last_delivered_seqs.insert(src, next_seq);
+                    self.process_output(state, wrapped_out, o);
+                    next_seq += 1;
                 }
-                state.to_mut().last_delivered_seqs.insert(src, seq);
-                process_output(state.to_mut(), wrapped_out, o);
             },
-            MsgWrapper::Ack(seq) => {
-                state.to_mut().msgs_pending_ack.remove(&seq);
+            MsgWrapper::Ack(_, seq) => {
+                if !state.msgs_pending_ack.contains_key(&(src, seq)) { return }
+
+                // Send messages that enter the window as it slides.
+                let prev_window_end = self.window_end(state, src);
+                let state = state.to_mut();
+                state.msgs_pending_ack.remove(&(src, seq));
+                let window_end = self.window_end(state, src);
+                let session = self.session(state, src);
+                for ((_, seq), msg) in state.msgs_pending_ack.range((src, prev_window_end)..(src, window_end)) {
+                    o.send(src, MsgWrapper::Deliver(session, *seq, msg.clone()));
+                }
+            },
+            MsgWrapper::Reset(_) => {
+                // Handled above upon learning the sender's epoch.
             },
         }
     }
 
     fn on_timeout(&self, _id: Id, state: &mut std::borrow::Cow<Self::State>, o: &mut Out<Self>) {
         o.set_timer(self.resend_interval.clone());
-        for (seq, (dst, msg)) in &state.msgs_pending_ack {
-            o.send(*dst, MsgWrapper::Deliver(*seq, msg.clone()));
+        for (&(dst, seq), msg) in &state.msgs_pending_ack {
+            if seq < self.window_end(state, dst) {
+                o.send(dst, MsgWrapper::Deliver(self.session(state, dst), seq, msg.clone()));
+            }
         }
     }
+
+    fn on_peer_down(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        // Tear down the session, but remember the peer's epoch to ignore stragglers.
+        let is_in_session = state.next_send_seqs.contains_key(&peer)
+            || state.last_delivered_seqs.contains_key(&peer)
+            || state.msgs_pending_delivery.range((peer, 0)..=(peer, Sequencer::MAX)).next().is_some();
+        if is_in_session {
+            Self::clear_session(state.to_mut(), peer);
+        }
+
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_peer_down(id, &mut wrapped_state, peer, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(state.to_mut(), wrapped_out, o);
+    }
+
+    /// Delivers the configuration carried by a [`MsgWrapper::Deliver`] update to the wrapped
+    /// actor. The session and sequencer are ignored, as configuration updates do not arrive via
+    /// the link.
+    fn on_config_update(&self, id: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+        let config = match config {
+            MsgWrapper::Deliver(_, _, config) => config,
+            _ => return,
+        };
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_config_update(id, &mut wrapped_state, config, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(state.to_mut(), wrapped_out, o);
+    }
+
+    fn on_random(&self, id: Id, state: &mut Cow<Self::State>, value: u64, o: &mut Out<Self>) {
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_random(id, &mut wrapped_state, value, &mut wrapped_out);
+        if is_no_op(&wrapped_state, &wrapped_out) { return }
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.to_mut().wrapped_state = wrapped_state;
+        }
+        self.process_output(state.to_mut(), wrapped_out, o);
+    }
+
+    /// Wraps the actor spawned by the wrapped actor with the same link configuration.
+    fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+        let spec = match spec {
+            MsgWrapper::Deliver(_, _, spec) => spec,
+            _ => return None,
+        };
+        Some(ActorWrapper {
+            resend_interval: self.resend_interval.clone(),
+            window_size: self.window_size,
+            epoch: self.epoch,
+            wrapped_actor: self.wrapped_actor.spawned_actor(spec)?,
+        })
+    }
 }
 
-fn process_output<A: Actor>(state: &mut StateWrapper<A::Msg, A::State>, wrapped_out: Out<A>, o: &mut Out<ActorWrapper<A>>)
+impl<A: Actor> ActorWrapper<A>
 where A::Msg: Hash
 {
-    for command in wrapped_out {
-        match command {
-            Command::CancelTimer => {
-                todo!("CancelTimer is not supported at this time");
-            },
-            Command::SetTimer(_) => {
-                todo!("SetTimer is not supported at this time");
-            },
-            Command::Send(dst, inner_msg) => {
-                o.send(dst, MsgWrapper::Deliver(state.next_send_seq, inner_msg.clone()));
-                state.msgs_pending_ack.insert(state.next_send_seq, (dst, inner_msg));
-                state.next_send_seq += 1;
-            },
+    fn process_output(&self, state: &mut StateWrapper<A::Msg, A::State>, wrapped_out: Out<A>, o: &mut Out<Self>) {
+        for command in wrapped_out {
+            match command {
+                Command::CancelTimer => {
+                    todo!("CancelTimer is not supported at this time");
+                },
+                Command::SetTimer(_) => {
+                    todo!("SetTimer is not supported at this time");
+                },
+                Command::Send(dst, inner_msg) => {
+                    self.send_wrapped(state, dst, inner_msg, o);
+                },
+                Command::Broadcast(dsts, inner_msg) => {
+                    // Each recipient has its own sequence, so recipients are sent to in turn.
+                    for dst in dsts {
+                        self.send_wrapped(state, dst, inner_msg.clone(), o);
+                    }
+                },
+                Command::Spawn(spec) => {
+                    // The specification is carried by an envelope that is never delivered.
+                    let session = Session { src_epoch: self.epoch, dst_epoch: None };
+                    o.spawn(MsgWrapper::Deliver(session, 0, spec));
+                },
+                Command::Monitor(peer) => {
+                    o.monitor(peer);
+                },
+                Command::ChooseRandom(range) => {
+                    o.choose_random(range);
+                },
+            }
+        }
+    }
+
+    /// Assigns the next sequence number for a recipient to a message output by the wrapped actor.
+    fn send_wrapped(&self, state: &mut StateWrapper<A::Msg, A::State>, dst: Id, inner_msg: A::Msg, o: &mut Out<Self>) {
+        // Messages beyond the window are sent once the window slides.
+        let seq = *state.next_send_seqs.get(&dst).unwrap_or(&1);
+        if seq < self.window_end(state, dst) {
+            o.send(dst, MsgWrapper::Deliver(self.session(state, dst), seq, inner_msg.clone()));
+        }
+        state.msgs_pending_ack.insert((dst, seq), inner_msg);
+        state.next_send_seqs.insert(dst, seq + 1);
+    }
+
+    /// Starts a new session with a peer that restarted, resending messages that its previous
+    /// incarnation did not acknowledge, and notifies the wrapped actor.
+    fn reset_session(&self, id: Id, state: &mut StateWrapper<A::Msg, A::State>, peer: Id, epoch: Epoch, o: &mut Out<Self>) {
+        state.peer_epochs.insert(peer, epoch);
+        let mut resend_out = Out::new();
+        for msg in Self::clear_session(state, peer) {
+            resend_out.send(peer, msg);
+        }
+        self.process_output(state, resend_out, o);
+
+        let mut wrapped_state = Cow::Borrowed(&state.wrapped_state);
+        let mut wrapped_out = Out::new();
+        self.wrapped_actor.on_peer_reset(id, &mut wrapped_state, peer, &mut wrapped_out);
+        if let Cow::Owned(wrapped_state) = wrapped_state {
+            state.wrapped_state = wrapped_state;
         }
+        self.process_output(state, wrapped_out, o);
     }
 }
 
@@ -142,8 +381,8 @@ where A::Msg: Hash
 mod test {
     use std::borrow::Cow;
     use crate::{Checker, Property, Model};
-    use crate::actor::{Actor, Id, Out};
-    use crate::actor::ordered_reliable_link::{ActorWrapper, MsgWrapper};
+    use crate::actor::{Actor, Command, Id, Out};
+    use crate::actor::ordered_reliable_link::{ActorWrapper, MsgWrapper, Session};
     use crate::actor::system::{SystemModel, System, LossyNetwork, DuplicatingNetwork, SystemState};
     use crate::actor::system::SystemAction;
 
@@ -164,6 +403,7 @@ mod test {
             if let TestActor::Sender { receiver_id } = self {
                 o.send(*receiver_id, TestMsg(42));
                 o.send(*receiver_id, TestMsg(43));
+                o.send(*receiver_id, TestMsg(44));
             }
             Received(Vec::new())
         }
@@ -171,9 +411,17 @@ mod test {
         fn on_msg(&self, _id: Id, received: &mut Cow<Self::State>, src: Id, msg: Self::Msg, _o: &mut Out<Self>) {
             received.to_mut().0.push((src, msg));
         }
+
+        fn on_peer_reset(&self, _id: Id, received: &mut Cow<Self::State>, peer: Id, _o: &mut Out<Self>) {
+            // Resets are recorded as a zero message.
+            received.to_mut().0.push((peer, TestMsg(0)));
+        }
     }
 
-    struct TestSystem;
+    const NEW_SESSION: Session = Session { src_epoch: 0, dst_epoch: None };
+    const SESSION: Session = Session { src_epoch: 0, dst_epoch: Some(0) };
+
+    struct TestSystem { window_size: usize }
     impl System for TestSystem {
         type Actor = ActorWrapper<TestActor>;
         type History = ();
@@ -181,9 +429,11 @@ mod test {
         fn actors(&self) -> Vec<Self::Actor> {
             vec![
                 ActorWrapper::with_default_timeout(
-                    TestActor::Sender { receiver_id: Id::from(1) }),
+                    TestActor::Sender { receiver_id: Id::from(1) })
+                    .window_size(self.window_size),
                 ActorWrapper::with_default_timeout(
-                    TestActor::Receiver),
+                    TestActor::Receiver)
+                    .window_size(self.window_size),
             ]
         }
 
@@ -201,6 +451,7 @@ mod test {
                     let received = &state.actor_states[1].wrapped_state.0;
                     received.iter().filter(|(_, TestMsg(v))| *v == 42).count() < 2
                         && received.iter().filter(|(_, TestMsg(v))| *v == 43).count() < 2
+                        && received.iter().filter(|(_, TestMsg(v))| *v == 44).count() < 2
                 }),
                 Property::<SystemModel<TestSystem>>::always("ordered", |_, state| {
                     state.actor_states[1].wrapped_state.0.iter()
@@ -208,11 +459,18 @@ mod test {
                         .fold((true, 0), |(acc, last), next| (acc && last <= next, next))
                         .0
                 }),
+                Property::<SystemModel<TestSystem>>::always("exactly once in order", |_, state| {
+                    // i.e. received messages are a prefix of those sent
+                    state.actor_states[1].wrapped_state.0.iter()
+                        .zip(42..)
+                        .all(|((src, TestMsg(v)), expected)| *src == Id::from(0) && *v == expected)
+                }),
                 // FIXME: convert to an eventually property once the liveness checker is complete
                 Property::<SystemModel<TestSystem>>::sometimes("delivered", |_, state| {
                     state.actor_states[1].wrapped_state.0 == vec![
                         (Id::from(0), TestMsg(42)),
                         (Id::from(0), TestMsg(43)),
+                        (Id::from(0), TestMsg(44)),
                     ]
                 }),
             ]
@@ -225,22 +483,218 @@ mod test {
 
     #[test]
     fn messages_are_not_delivered_twice() {
-        TestSystem.into_model().checker().spawn_bfs().join()
-            .assert_no_discovery("no redelivery");
+        for window_size in 1..=3 {
+            TestSystem { window_size }.into_model().checker().spawn_bfs().join()
+                .assert_no_discovery("no redelivery");
+        }
     }
 
     #[test]
     fn messages_are_delivered_in_order() {
-        TestSystem.into_model().checker().spawn_bfs().join()
-            .assert_no_discovery("ordered");
+        for window_size in 1..=3 {
+            TestSystem { window_size }.into_model().checker().spawn_bfs().join()
+                .assert_no_discovery("ordered");
+        }
+    }
+
+    #[test]
+    fn messages_are_delivered_exactly_once_in_order() {
+        for window_size in 1..=3 {
+            TestSystem { window_size }.into_model().checker().spawn_bfs().join()
+                .assert_no_discovery("exactly once in order");
+        }
     }
 
     #[test]
     fn messages_are_eventually_delivered() {
-        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        let checker = TestSystem { window_size: 3 }.into_model().checker().spawn_bfs().join();
         checker.assert_discovery("delivered", vec![
-            SystemAction::Deliver { src: Id(0), dst: Id(1), msg: MsgWrapper::Deliver(1, TestMsg(42)) },
-            SystemAction::Deliver { src: Id(0), dst: Id(1), msg: MsgWrapper::Deliver(2, TestMsg(43)) },
+            SystemAction::Deliver { src: Id(0), dst: Id(1), msg: MsgWrapper::Deliver(NEW_SESSION, 1, TestMsg(42)) },
+            SystemAction::Deliver { src: Id(0), dst: Id(1), msg: MsgWrapper::Deliver(NEW_SESSION, 2, TestMsg(43)) },
+            SystemAction::Deliver { src: Id(0), dst: Id(1), msg: MsgWrapper::Deliver(NEW_SESSION, 3, TestMsg(44)) },
+        ]);
+    }
+
+    fn sends(o: Out<ActorWrapper<TestActor>>) -> Vec<(Id, MsgWrapper<TestMsg>)> {
+        o.into_iter()
+            .filter_map(|c| if let Command::Send(dst, msg) = c { Some((dst, msg)) } else { None })
+            .collect()
+    }
+
+    #[test]
+    fn forwards_spawned_actors() {
+        #[derive(Clone)]
+        struct Spawner;
+        impl Actor for Spawner {
+            type Msg = TestMsg;
+            type State = ();
+            fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State { o.spawn(TestMsg(1)); }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+                if *spec == TestMsg(1) { Some(Spawner) } else { None }
+            }
+        }
+
+        let actor = ActorWrapper::with_default_timeout(Spawner).window_size(2);
+        let mut o = Out::new();
+        actor.on_start(Id::from(0), &mut o);
+        let specs: Vec<_> = o.into_iter()
+            .filter_map(|c| if let Command::Spawn(spec) = c { Some(spec) } else { None })
+            .collect();
+        assert_eq!(specs.len(), 1);
+        assert_eq!(actor.spawned_actor(&specs[0]).unwrap().window_size, 2);
+        assert!(actor.spawned_actor(&MsgWrapper::Ack(NEW_SESSION, 1)).is_none());
+    }
+
+    #[test]
+    fn forwards_config_updates() {
+        struct Configured;
+        impl Actor for Configured {
+            type Msg = TestMsg;
+            type State = Vec<TestMsg>;
+            fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State { Vec::new() }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_config_update(&self, _: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+                state.to_mut().push(config.clone());
+                o.send(Id::from(1), config);
+            }
+        }
+
+        let actor = ActorWrapper::with_default_timeout(Configured);
+        let mut o = Out::new();
+        let mut state = Cow::Owned(actor.on_start(Id::from(0), &mut o));
+        let mut o = Out::new();
+        let config = MsgWrapper::Deliver(NEW_SESSION, 0, TestMsg(7));
+        actor.on_config_update(Id::from(0), &mut state, config, &mut o);
+        assert_eq!(state.wrapped_state, vec![TestMsg(7)]);
+
+        // Messages sent in response are sequenced like any other.
+        let sent: Vec<_> = o.into_iter()
+            .filter_map(|c| if let Command::Send(dst, msg) = c { Some((dst, msg)) } else { None })
+            .collect();
+        assert_eq!(sent, vec![(Id::from(1), MsgWrapper::Deliver(NEW_SESSION, 1, TestMsg(7)))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Window size must be at least one.")]
+    fn rejects_empty_window() {
+        let _ = ActorWrapper::with_default_timeout(
+            TestActor::Sender { receiver_id: Id::from(1) }).window_size(0);
+    }
+
+    #[test]
+    fn only_resends_unacked_messages_in_window() {
+        let sender = ActorWrapper::with_default_timeout(
+            TestActor::Sender { receiver_id: Id::from(1) }).window_size(2);
+        let deliver = |session, seq, v| (Id::from(1), MsgWrapper::Deliver(session, seq, TestMsg(v)));
+
+        // The third message waits for the window to slide.
+        let mut o = Out::new();
+        let mut state = Cow::Owned(sender.on_start(Id::from(0), &mut o));
+        assert_eq!(sends(o), vec![deliver(NEW_SESSION, 1, 42), deliver(NEW_SESSION, 2, 43)]);
+
+        // A selective ack does not slide the window, and the acked message is not resent.
+        let mut o = Out::new();
+        sender.on_msg(Id::from(0), &mut state, Id::from(1), MsgWrapper::Ack(SESSION, 2), &mut o);
+        assert_eq!(sends(o), vec![]);
+        let mut o = Out::new();
+        sender.on_timeout(Id::from(0), &mut state, &mut o);
+        assert_eq!(sends(o), vec![deliver(SESSION, 1, 42)]);
+
+        // Acking the earliest message slides the window.
+        let mut o = Out::new();
+        sender.on_msg(Id::from(0), &mut state, Id::from(1), MsgWrapper::Ack(SESSION, 1), &mut o);
+        assert_eq!(sends(o), vec![deliver(SESSION, 3, 44)]);
+    }
+
+    #[test]
+    fn buffers_messages_that_arrive_early() {
+        let receiver = ActorWrapper::with_default_timeout(TestActor::Receiver).window_size(2);
+        let mut o = Out::new();
+        let mut state = Cow::Owned(receiver.on_start(Id::from(1), &mut o));
+
+        // Beyond the window, so ignored without an ack.
+        let mut o = Out::new();
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0), MsgWrapper::Deliver(NEW_SESSION, 3, TestMsg(44)), &mut o);
+        assert_eq!(o.len(), 0);
+
+        let mut o = Out::new();
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0), MsgWrapper::Deliver(NEW_SESSION, 2, TestMsg(43)), &mut o);
+        assert_eq!(state.wrapped_state, Received(vec![]));
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0), MsgWrapper::Deliver(NEW_SESSION, 1, TestMsg(42)), &mut o);
+        assert_eq!(state.wrapped_state, Received(vec![
+            (Id::from(0), TestMsg(42)),
+            (Id::from(0), TestMsg(43)),
+        ]));
+    }
+
+    #[test]
+    fn resets_session_when_sender_restarts() {
+        let receiver = ActorWrapper::with_default_timeout(TestActor::Receiver);
+        let mut o = Out::new();
+        let mut state = Cow::Owned(receiver.on_start(Id::from(1), &mut o));
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0),
+                        MsgWrapper::Deliver(NEW_SESSION, 1, TestMsg(42)), &mut o);
+
+        // The restarted sender numbers messages from the start.
+        let restarted = Session { src_epoch: 1, dst_epoch: Some(0) };
+        let mut o = Out::new();
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0),
+                        MsgWrapper::Deliver(restarted, 1, TestMsg(50)), &mut o);
+        assert_eq!(sends(o), vec![
+            (Id::from(0), MsgWrapper::Ack(Session { src_epoch: 1, dst_epoch: Some(0) }, 1)),
+        ]);
+        assert_eq!(state.wrapped_state, Received(vec![
+            (Id::from(0), TestMsg(42)),
+            (Id::from(0), TestMsg(0)), // reset
+            (Id::from(0), TestMsg(50)),
+        ]));
+
+        // Messages from the previous incarnation are ignored.
+        let mut o = Out::new();
+        receiver.on_msg(Id::from(1), &mut state, Id::from(0),
+                        MsgWrapper::Deliver(SESSION, 2, TestMsg(43)), &mut o);
+        assert_eq!(o.len(), 0);
+        assert_eq!(state.wrapped_state.0.len(), 3);
+    }
+
+    #[test]
+    fn resets_session_when_recipient_restarts() {
+        let sender = ActorWrapper::with_default_timeout(
+            TestActor::Sender { receiver_id: Id::from(1) }).window_size(2);
+        let mut o = Out::new();
+        let mut state = Cow::Owned(sender.on_start(Id::from(0), &mut o));
+        sender.on_msg(Id::from(0), &mut state, Id::from(1), MsgWrapper::Ack(SESSION, 1), &mut o);
+
+        // The restarted recipient asks the sender to reset the session.
+        let receiver = ActorWrapper::with_default_timeout(TestActor::Receiver).window_size(2).epoch(1);
+        let mut o = Out::new();
+        let mut receiver_state = Cow::Owned(receiver.on_start(Id::from(1), &mut o));
+        receiver.on_msg(Id::from(1), &mut receiver_state, Id::from(0),
+                        MsgWrapper::Deliver(SESSION, 3, TestMsg(44)), &mut o);
+        assert_eq!(sends(o), vec![(Id::from(0), MsgWrapper::Reset(1))]);
+
+        // Unacknowledged messages are renumbered for the new session.
+        let restarted = Session { src_epoch: 0, dst_epoch: Some(1) };
+        let mut o = Out::new();
+        sender.on_msg(Id::from(0), &mut state, Id::from(1), MsgWrapper::Reset(1), &mut o);
+        assert_eq!(sends(o), vec![
+            (Id::from(1), MsgWrapper::Deliver(restarted, 1, TestMsg(43))),
+            (Id::from(1), MsgWrapper::Deliver(restarted, 2, TestMsg(44))),
         ]);
+        assert_eq!(state.wrapped_state, Received(vec![(Id::from(1), TestMsg(0))]));
+    }
+
+    #[test]
+    fn tears_down_session_when_peer_is_down() {
+        let sender = ActorWrapper::with_default_timeout(
+            TestActor::Sender { receiver_id: Id::from(1) });
+        let mut o = Out::new();
+        let mut state = Cow::Owned(sender.on_start(Id::from(0), &mut o));
+        sender.on_peer_down(Id::from(0), &mut state, Id::from(1), &mut o);
+
+        let mut o = Out::new();
+        sender.on_timeout(Id::from(0), &mut state, &mut o);
+        assert_eq!(sends(o), vec![]);
     }
 }
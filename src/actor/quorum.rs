@@ -0,0 +1,171 @@
+//! Private module for selective re-export.
+
+use crate::actor::{majority, Id};
+use std::collections::BTreeSet;
+
+/// Describes which sets of actors constitute a quorum. Quorums are monotonic: any superset of a
+/// quorum is also a quorum.
+///
+/// Protocols such as Flexible Paxos use different quorum systems for different phases, with the
+/// requirement that each quorum of one intersects each quorum of the other, which can be verified
+/// via [`QuorumSet::intersects`].
+///
+/// ```
+/// use stateright::actor::{Id, QuorumSet};
+/// let ids: Vec<Id> = (0..5).map(Id::from).collect();
+///
+/// let (phase1, phase2) = QuorumSet::flexible_paxos(ids.clone(), 2);
+/// assert!(phase1.is_quorum(&ids[..4]));
+/// assert!(phase2.is_quorum(&ids[3..]));
+/// assert!(phase1.intersects(&phase2));
+/// assert!(!phase2.intersects(&phase2));
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum QuorumSet {
+    /// Any `threshold` of the members.
+    Threshold { members: Vec<Id>, threshold: usize },
+    /// Any members whose weights sum to at least `threshold`.
+    Weighted { weights: Vec<(Id, u64)>, threshold: u64 },
+    /// Any members that include a full row of the grid.
+    GridRow(Vec<Vec<Id>>),
+    /// Any members that include a full column of the grid (one member from each row).
+    GridColumn(Vec<Vec<Id>>),
+}
+
+impl QuorumSet {
+    /// Any majority of the members.
+    pub fn majority(members: Vec<Id>) -> Self {
+        let threshold = majority(members.len());
+        QuorumSet::Threshold { members, threshold }
+    }
+
+    /// Any `threshold` of the members.
+    pub fn threshold(members: Vec<Id>, threshold: usize) -> Self {
+        QuorumSet::Threshold { members, threshold }
+    }
+
+    /// Any members whose weights sum to at least `threshold`.
+    pub fn weighted(weights: Vec<(Id, u64)>, threshold: u64) -> Self {
+        QuorumSet::Weighted { weights, threshold }
+    }
+
+    /// The smallest phase 1 and phase 2 quorums for Flexible Paxos given the size of phase 2
+    /// quorums. Phase 1 quorums are sized to intersect every phase 2 quorum.
+    pub fn flexible_paxos(members: Vec<Id>, phase2_size: usize) -> (Self, Self) {
+        let phase1_size = members.len() + 1 - phase2_size.min(members.len());
+        (
+            QuorumSet::threshold(members.clone(), phase1_size),
+            QuorumSet::threshold(members, phase2_size),
+        )
+    }
+
+    /// The actors that can participate in a quorum.
+    pub fn members(&self) -> BTreeSet<Id> {
+        match self {
+            QuorumSet::Threshold { members, .. } => members.iter().copied().collect(),
+            QuorumSet::Weighted { weights, .. } => weights.iter().map(|(id, _)| *id).collect(),
+            QuorumSet::GridRow(grid) | QuorumSet::GridColumn(grid) => {
+                grid.iter().flatten().copied().collect()
+            }
+        }
+    }
+
+    /// Indicates whether the specified actors constitute a quorum. Duplicates and non-members are
+    /// ignored.
+    pub fn is_quorum<'a>(&self, ids: impl IntoIterator<Item = &'a Id>) -> bool {
+        let ids: BTreeSet<Id> = ids.into_iter().copied().collect();
+        match self {
+            QuorumSet::Threshold { members, threshold } => {
+                let members: BTreeSet<_> = members.iter().collect();
+                members.into_iter().filter(|id| ids.contains(id)).count() >= *threshold
+            }
+            QuorumSet::Weighted { weights, threshold } => {
+                let mut counted = BTreeSet::new();
+                let weight: u64 = weights.iter()
+                    .filter(|(id, _)| ids.contains(id) && counted.insert(*id))
+                    .map(|(_, weight)| weight)
+                    .sum();
+                weight >= *threshold
+            }
+            QuorumSet::GridRow(grid) => {
+                grid.iter().any(|row| row.iter().all(|id| ids.contains(id)))
+            }
+            QuorumSet::GridColumn(grid) => {
+                let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+                (0..width).any(|c| {
+                    grid.iter().all(|row| row.get(c).into_iter().all(|id| ids.contains(id)))
+                })
+            }
+        }
+    }
+
+    /// Indicates whether every quorum of this set shares a member with every quorum of `other`.
+    /// Checks every subset of the members, so this is intended for the small clusters that are
+    /// practical to model check.
+    pub fn intersects(&self, other: &QuorumSet) -> bool {
+        let members: Vec<Id> = self.members().union(&other.members()).copied().collect();
+        assert!(members.len() < 32, "Too many members to check intersection: {}", members.len());
+        (0..1_u32 << members.len()).all(|subset| {
+            let (inside, outside): (Vec<_>, Vec<_>) = members.iter().enumerate()
+                .partition(|(i, _)| subset & (1 << i) != 0);
+            let inside = inside.into_iter().map(|(_, id)| id);
+            let outside = outside.into_iter().map(|(_, id)| id);
+            !(self.is_quorum(inside) && other.is_quorum(outside))
+        })
+    }
+
+    /// Indicates whether any two quorums of this set share a member, which is required if the
+    /// same quorums are used for every phase of a protocol.
+    pub fn is_intersecting(&self) -> bool {
+        self.intersects(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ids(range: std::ops::Range<usize>) -> Vec<Id> {
+        range.map(Id::from).collect()
+    }
+
+    #[test]
+    fn can_identify_quorums() {
+        let majority = QuorumSet::majority(ids(0..4));
+        assert!(!majority.is_quorum(&ids(0..2)));
+        assert!(!majority.is_quorum(&[Id::from(0), Id::from(0), Id::from(9)]));
+        assert!(majority.is_quorum(&ids(1..4)));
+
+        let weighted = QuorumSet::weighted(vec![(Id::from(0), 3), (Id::from(1), 1), (Id::from(2), 1)], 3);
+        assert!(weighted.is_quorum(&[Id::from(0)]));
+        assert!(!weighted.is_quorum(&[Id::from(1), Id::from(2)]));
+
+        let grid = vec![ids(0..3), ids(3..6)];
+        assert!(QuorumSet::GridRow(grid.clone()).is_quorum(&ids(3..6)));
+        assert!(!QuorumSet::GridRow(grid.clone()).is_quorum(&ids(2..5)));
+        assert!(QuorumSet::GridColumn(grid.clone()).is_quorum(&[Id::from(1), Id::from(4)]));
+        assert!(!QuorumSet::GridColumn(grid).is_quorum(&[Id::from(1), Id::from(5)]));
+    }
+
+    #[test]
+    fn can_check_intersection() {
+        assert!(QuorumSet::majority(ids(0..4)).is_intersecting());
+        assert!(QuorumSet::majority(ids(0..5)).is_intersecting());
+        assert!(!QuorumSet::threshold(ids(0..4), 2).is_intersecting());
+        assert!(!QuorumSet::weighted(vec![(Id::from(0), 1), (Id::from(1), 1)], 1).is_intersecting());
+
+        for phase2_size in 1..=5 {
+            let (phase1, phase2) = QuorumSet::flexible_paxos(ids(0..5), phase2_size);
+            assert!(phase1.intersects(&phase2));
+            assert!(phase2.intersects(&phase1));
+            let smaller_phase1 = QuorumSet::threshold(ids(0..5), 5 - phase2_size);
+            assert!(!smaller_phase1.intersects(&phase2));
+        }
+
+        let grid = vec![ids(0..3), ids(3..6)];
+        assert!(QuorumSet::GridRow(grid.clone()).intersects(&QuorumSet::GridColumn(grid.clone())));
+        assert!(!QuorumSet::GridRow(grid.clone()).is_intersecting());
+        assert!(!QuorumSet::GridColumn(grid).is_intersecting());
+    }
+}
@@ -0,0 +1,340 @@
+//! Private module for selective re-export.
+
+use crate::actor::*;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::BufRead;
+
+/// An event recorded per [`SpawnOptions::record`], which writes one event per line as JSON in the
+/// order that actors processed them. Messages and configuration updates are recorded in their
+/// serialized form. See [`replay`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub enum RecordedEvent {
+    /// An actor started, either because it was passed to [`spawn_with`] or because the `parent`
+    /// actor spawned it.
+    Start { id: Id, parent: Option<Id> },
+    /// An actor received a message.
+    Deliver { src: Id, dst: Id, bytes: Vec<u8> },
+    /// An actor's timer fired.
+    Timeout { id: Id },
+    /// An actor received a configuration update.
+    UpdateConfig { id: Id, bytes: Vec<u8> },
+    /// An actor was notified that a monitored peer is down.
+    PeerDown { id: Id, peer: Id },
+    /// An actor received a value chosen at random.
+    ChooseRandom { id: Id, value: u64 },
+}
+
+/// Indicates why a log could not be replayed. Each variant other than `Read` indicates the
+/// (1-based) `line` of the offending event.
+#[derive(Debug)]
+pub enum ReplayError<E> {
+    /// Unable to read the log.
+    Read { source: std::io::Error },
+    /// Unable to parse an event.
+    Parse { line: usize, source: serde_json::Error },
+    /// Unable to deserialize a recorded message or configuration update.
+    Deserialize { line: usize, bytes: Vec<u8>, err: E },
+    /// The event is for an actor that has not started.
+    UnknownActor { line: usize, id: Id },
+}
+
+impl<E: Debug> Display for ReplayError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Read { source } =>
+                write!(f, "Unable to read log. err={}", source),
+            ReplayError::Parse { line, source } =>
+                write!(f, "Unable to parse event. line={}, err={}", line, source),
+            ReplayError::Deserialize { line, bytes, err } =>
+                write!(f, "Unable to parse message. line={}, buf={:?}, err={:?}", line, bytes, err),
+            ReplayError::UnknownActor { line, id } =>
+                write!(f, "Actor has not started. line={}, id={}", line, id),
+        }
+    }
+}
+
+impl<E: Debug> std::error::Error for ReplayError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReplayError::Read { source } => Some(source),
+            ReplayError::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The result of [`replay`]ing a log: the resulting actor states, and the equivalent sequence of
+/// [`SystemAction`]s for model checking.
+pub struct Replay<A: Actor> {
+    ids: Vec<Id>,
+    actors: Vec<A>,
+    states: Vec<Option<A::State>>,
+    /// Actors spawned by each actor that have yet to start, in the order spawned.
+    pending_spawns: Vec<VecDeque<A>>,
+    crashed: BTreeSet<Id>,
+    actions: Vec<SystemAction<A::Msg>>,
+}
+
+impl<A: Actor> Replay<A> {
+    /// The state of an actor, or `None` if the actor is unknown or did not start.
+    pub fn state(&self, id: Id) -> Option<&A::State> {
+        self.index(id).and_then(|index| self.states[index].as_ref())
+    }
+
+    /// The replayed events as actions of a [`SystemModel`] whose actors are those passed to
+    /// [`replay`] (in the same order), so that the events can be passed to
+    /// [`SystemModel::check_conformance`] or [`Path::from_actions`]. Actors are identified by index as when model checking, with spawned
+    /// actors numbered in the order they started. A peer that is down is indicated by a
+    /// [`SystemAction::Crash`], and starting an actor does not correspond with an action.
+    ///
+    /// [`Path::from_actions`]: crate::Path::from_actions
+    pub fn actions(&self) -> &[SystemAction<A::Msg>] {
+        &self.actions
+    }
+
+    fn index(&self, id: Id) -> Option<usize> {
+        self.ids.iter().position(|candidate| *candidate == id)
+    }
+
+    /// Maps a recorded ID to a model ID, leaving IDs of actors that were not replayed unchanged.
+    fn model_id(&self, id: Id) -> Id {
+        self.index(id).map(Id::from).unwrap_or(id)
+    }
+
+    /// Applies one event, calling the corresponding method of the actor that handled it.
+    fn apply<E>(&mut self, line: usize, event: RecordedEvent,
+                deserialize: fn(&[u8]) -> Result<A::Msg, E>) -> Result<(), ReplayError<E>>
+    {
+        let started = |replay: &Self, id| match replay.index(id) {
+            Some(index) if replay.states[index].is_some() => Ok(index),
+            _ => Err(ReplayError::UnknownActor { line, id }),
+        };
+        let (index, out) = match event {
+            RecordedEvent::Start { id, parent } => {
+                let index = match (self.index(id), parent) {
+                    (Some(index), None) if self.states[index].is_none() => index,
+                    (None, Some(parent)) => {
+                        let parent_index = started(self, parent)?;
+                        let child = self.pending_spawns[parent_index].pop_front()
+                            .ok_or(ReplayError::UnknownActor { line, id })?;
+                        self.ids.push(id);
+                        self.actors.push(child);
+                        self.states.push(None);
+                        self.pending_spawns.push(VecDeque::new());
+                        self.ids.len() - 1
+                    },
+                    _ => return Err(ReplayError::UnknownActor { line, id }),
+                };
+                let mut out = Out::new();
+                self.states[index] = Some(self.actors[index].on_start(id, &mut out));
+                (index, out)
+            },
+            RecordedEvent::Deliver { src, dst, bytes } => {
+                let index = started(self, dst)?;
+                let msg = deserialize(&bytes)
+                    .map_err(|err| ReplayError::Deserialize { line, bytes, err })?;
+                self.actions.push(SystemAction::Deliver {
+                    src: self.model_id(src),
+                    dst: self.model_id(dst),
+                    msg: msg.clone(),
+                });
+                let mut out = Out::new();
+                let mut state = Cow::Borrowed(self.states[index].as_ref().unwrap());
+                self.actors[index].on_msg(dst, &mut state, src, msg, &mut out);
+                if let Cow::Owned(next_state) = state {
+                    self.states[index] = Some(next_state);
+                }
+                (index, out)
+            },
+            RecordedEvent::Timeout { id } => {
+                let index = started(self, id)?;
+                self.actions.push(SystemAction::Timeout(Id::from(index)));
+                let mut out = Out::new();
+                let mut state = Cow::Borrowed(self.states[index].as_ref().unwrap());
+                self.actors[index].on_timeout(id, &mut state, &mut out);
+                if let Cow::Owned(next_state) = state {
+                    self.states[index] = Some(next_state);
+                }
+                (index, out)
+            },
+            RecordedEvent::UpdateConfig { id, bytes } => {
+                let index = started(self, id)?;
+                let config = deserialize(&bytes)
+                    .map_err(|err| ReplayError::Deserialize { line, bytes, err })?;
+                self.actions.push(SystemAction::UpdateConfig {
+                    dst: Id::from(index),
+                    config: config.clone(),
+                });
+                let mut out = Out::new();
+                let mut state = Cow::Borrowed(self.states[index].as_ref().unwrap());
+                self.actors[index].on_config_update(id, &mut state, config, &mut out);
+                if let Cow::Owned(next_state) = state {
+                    self.states[index] = Some(next_state);
+                }
+                (index, out)
+            },
+            RecordedEvent::PeerDown { id, peer } => {
+                let index = started(self, id)?;
+                if self.crashed.insert(peer) {
+                    self.actions.push(SystemAction::Crash(self.model_id(peer)));
+                }
+                let mut out = Out::new();
+                let mut state = Cow::Borrowed(self.states[index].as_ref().unwrap());
+                self.actors[index].on_peer_down(id, &mut state, peer, &mut out);
+                if let Cow::Owned(next_state) = state {
+                    self.states[index] = Some(next_state);
+                }
+                (index, out)
+            },
+            RecordedEvent::ChooseRandom { id, value } => {
+                let index = started(self, id)?;
+                self.actions.push(SystemAction::ChooseRandom { id: Id::from(index), value });
+                let mut out = Out::new();
+                let mut state = Cow::Borrowed(self.states[index].as_ref().unwrap());
+                self.actors[index].on_random(id, &mut state, value, &mut out);
+                if let Cow::Owned(next_state) = state {
+                    self.states[index] = Some(next_state);
+                }
+                (index, out)
+            },
+        };
+
+        // Only spawns affect replay, as other effects are reflected by later events.
+        for command in out {
+            if let Command::Spawn(spec) = command {
+                if let Some(child) = self.actors[index].spawned_actor(&spec) {
+                    self.pending_spawns[index].push_back(child);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Re-drives actors through the events in a log written per [`SpawnOptions::record`], calling the
+/// same [`Actor`] methods in the same order as when the log was recorded, so that a misbehaving
+/// deployment can be reproduced (for instance under a debugger). `actors` must match those that
+/// were passed to [`spawn_with`].
+///
+/// Replay is deterministic as long as the actors are, and messages sent while replaying are
+/// discarded, as their receipt is already recorded. See [`Replay::actions`] for converting the
+/// log into a model checking [`Path`](crate::Path).
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, replay};
+/// use std::fs::File;
+/// use std::io::BufReader;
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// # mod serde_json {
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor1 = ();
+/// let id1 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let log = BufReader::new(File::open("actors.log").unwrap());
+/// let replay = replay(|bytes| serde_json::from_slice(bytes), vec![(id1, actor1)], log).unwrap();
+/// println!("{:?}", replay.state(id1));
+/// ```
+pub fn replay<A: Actor, E>(
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>,
+    log: impl BufRead) -> Result<Replay<A>, ReplayError<E>>
+{
+    let mut replay = Replay {
+        ids: Vec::with_capacity(actors.len()),
+        actors: Vec::with_capacity(actors.len()),
+        states: Vec::with_capacity(actors.len()),
+        pending_spawns: Vec::with_capacity(actors.len()),
+        crashed: BTreeSet::new(),
+        actions: Vec::new(),
+    };
+    for (id, actor) in actors {
+        replay.ids.push(id.into());
+        replay.actors.push(actor);
+        replay.states.push(None);
+        replay.pending_spawns.push(VecDeque::new());
+    }
+    for (index, line) in log.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.map_err(|source| ReplayError::Read { source })?;
+        if line.trim().is_empty() { continue }
+        let event = serde_json::from_str(&line)
+            .map_err(|source| ReplayError::Parse { line: line_number, source })?;
+        replay.apply(line_number, event, deserialize)?;
+    }
+    Ok(replay)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Model, Path};
+    use crate::actor::actor_test_util::ping_pong::*;
+    use crate::actor::actor_test_util::ping_pong::PingPongMsg::*;
+    use std::net::{Ipv4Addr, SocketAddrV4};
+
+    fn log(events: Vec<RecordedEvent>) -> String {
+        events.iter().map(|e| serde_json::to_string(e).unwrap() + "\n").collect()
+    }
+
+    fn bytes(msg: PingPongMsg) -> Vec<u8> {
+        serde_json::to_vec(&msg).unwrap()
+    }
+
+    #[test]
+    fn can_replay_log() {
+        let ping_id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+        let pong_id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+        let log = log(vec![
+            RecordedEvent::Start { id: pong_id, parent: None },
+            RecordedEvent::Start { id: ping_id, parent: None },
+            RecordedEvent::Deliver { src: ping_id, dst: pong_id, bytes: bytes(Ping(0)) },
+            RecordedEvent::Deliver { src: pong_id, dst: ping_id, bytes: bytes(Pong(0)) },
+            RecordedEvent::Deliver { src: ping_id, dst: pong_id, bytes: bytes(Ping(1)) },
+        ]);
+        let replay = replay(
+            |bytes| serde_json::from_slice::<PingPongMsg>(bytes),
+            vec![
+                (ping_id, PingPongActor::PingActor { pong_id }),
+                (pong_id, PingPongActor::PongActor),
+            ],
+            log.as_bytes()).unwrap();
+        assert_eq!(replay.state(ping_id), Some(&PingPongCount(1)));
+        assert_eq!(replay.state(pong_id), Some(&PingPongCount(2)));
+        assert_eq!(replay.actions(), &[
+            SystemAction::Deliver { src: Id::from(0), dst: Id::from(1), msg: Ping(0) },
+            SystemAction::Deliver { src: Id::from(1), dst: Id::from(0), msg: Pong(0) },
+            SystemAction::Deliver { src: Id::from(0), dst: Id::from(1), msg: Ping(1) },
+        ]);
+
+        // The log is also a valid path of the corresponding model.
+        let model = PingPongSystem {
+            max_nat: 5,
+            lossy: LossyNetwork::No,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        }.into_model();
+        let init_state = model.init_states().remove(0);
+        assert!(Path::from_actions(&model, init_state, replay.actions()).is_some());
+    }
+
+    #[test]
+    fn rejects_invalid_log() {
+        let id = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+        let deserialize = |bytes: &[u8]| serde_json::from_slice::<PingPongMsg>(bytes);
+        let actors = || vec![(id, PingPongActor::PongActor)];
+
+        let log1 = log(vec![RecordedEvent::Timeout { id }]);
+        assert!(matches!(
+            replay(deserialize, actors(), log1.as_bytes()),
+            Err(ReplayError::UnknownActor { line: 1, .. })));
+
+        let log2 = log(vec![RecordedEvent::Start { id, parent: None }]) + "oops\n";
+        assert!(matches!(
+            replay(deserialize, actors(), log2.as_bytes()),
+            Err(ReplayError::Parse { line: 2, .. })));
+    }
+}
@@ -3,9 +3,9 @@
 
 use crate::Property;
 use crate::actor::{Actor, Id, Out};
-use crate::actor::system::{DuplicatingNetwork, LossyNetwork, System, SystemModel, SystemState};
+use crate::actor::system::{DuplicatingNetwork, LossyNetwork, System, SystemModel, SystemState, model_timeout};
 use crate::semantics::register::{Register, RegisterOp, RegisterRet};
-use crate::semantics::LinearizabilityTester;
+use crate::semantics::{ConsistencyTester, LinearizabilityTester};
 use std::borrow::Cow;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -21,32 +21,67 @@ pub enum RegisterMsg<RequestId, Value, InternalMsg> {
     Put(RequestId, Value),
     /// Indicates that a value should be retrieved.
     Get(RequestId),
+    /// Indicates that the second value should be written if the register currently holds the
+    /// first value (compare-and-swap).
+    Cas(RequestId, Value, Value),
 
     /// Indicates a successful `Put`. Analogous to an HTTP 2XX.
     PutOk(RequestId),
     /// Indicates a successful `Get`. Analogous to an HTTP 2XX.
     GetOk(RequestId, Value),
+    /// Indicates a completed `Cas`, and whether the value was swapped.
+    CasOk(RequestId, bool),
 }
 use RegisterMsg::*;
 
-/// A system for testing an actor service with register semantics.
+/// A system for testing an actor service with register semantics. The history is validated by
+/// a [`ConsistencyTester`], which defaults to a [`LinearizabilityTester`]. For example, a
+/// `RegisterTestSystem<ServerActor, InternalMsg, SequentialConsistencyTester<Id,
+/// Register<TestValue>>>` checks for sequential consistency instead.
+///
+/// [`SequentialConsistencyTester`]: crate::semantics::SequentialConsistencyTester
 #[derive(Clone)]
-pub struct RegisterTestSystem<ServerActor, InternalMsg>
+pub struct RegisterTestSystem<ServerActor, InternalMsg, History = DefaultHistory>
 where
     ServerActor: Actor<Msg = RegisterMsg<TestRequestId, TestValue, InternalMsg>> + Clone,
     InternalMsg: Clone + Debug + Eq + Hash,
+    History: RegisterHistory,
 {
     pub servers: Vec<ServerActor>,
     pub client_count: u8,
     pub within_boundary: fn(state: &SystemState<Self>) -> bool,
     pub lossy_network: LossyNetwork,
     pub duplicating_network: DuplicatingNetwork,
+    /// Whether clients give up on an operation after a (modeled) timeout, in which case the
+    /// operation is recorded as indeterminate. See [`ConsistencyTester::on_timeout`].
+    pub client_timeouts: bool,
+    /// Whether clients follow their `Put`s with a [`RegisterMsg::Cas`] that expects the value
+    /// they last wrote.
+    pub client_cas: bool,
+    /// How many times clients resend a request (with the same request ID) whose response does not
+    /// arrive before a (modeled) timeout. A resent request may take effect more than once, so each
+    /// timeout renders the pending operation indeterminate. See [`ConsistencyTester::on_timeout`].
+    pub client_retries: u8,
+    /// Whether consistency checks are memoized across states, which avoids re-checking histories
+    /// shared by many states at the cost of memory. See [`ConsistencyTester::memoized`].
+    pub memoize_history: bool,
 }
 
-impl<ServerActor, InternalMsg> Default for RegisterTestSystem<ServerActor, InternalMsg>
+/// The [`ConsistencyTester`] used by a [`RegisterTestSystem`] unless otherwise specified.
+pub type DefaultHistory = LinearizabilityTester<Id, Register<TestValue>>;
+
+/// A [`ConsistencyTester`] suitable for a [`RegisterTestSystem`].
+pub trait RegisterHistory: ConsistencyTester<Id, RegisterOp<TestValue>, RegisterRet<TestValue>>
+    + Clone + Debug + Default + Hash {}
+impl<H> RegisterHistory for H
+where H: ConsistencyTester<Id, RegisterOp<TestValue>, RegisterRet<TestValue>>
+    + Clone + Debug + Default + Hash {}
+
+impl<ServerActor, InternalMsg, History> Default for RegisterTestSystem<ServerActor, InternalMsg, History>
     where
     ServerActor: Actor<Msg = RegisterMsg<TestRequestId, TestValue, InternalMsg>> + Clone,
     InternalMsg: Clone + Debug + Eq + Hash,
+    History: RegisterHistory,
 {
     fn default() -> Self {
         Self {
@@ -55,28 +90,46 @@ impl<ServerActor, InternalMsg> Default for RegisterTestSystem<ServerActor, Inter
             within_boundary: |_| true,
             lossy_network: LossyNetwork::No,
             duplicating_network: DuplicatingNetwork::Yes,
+            client_timeouts: false,
+            client_cas: false,
+            client_retries: 0,
+            memoize_history: false,
         }
     }
 }
 
-impl<ServerActor, InternalMsg> System for RegisterTestSystem<ServerActor, InternalMsg>
+impl<ServerActor, InternalMsg, History> System for RegisterTestSystem<ServerActor, InternalMsg, History>
     where
         ServerActor: Actor<Msg = RegisterMsg<TestRequestId, TestValue, InternalMsg>> + Clone,
         InternalMsg: Clone + Debug + Eq + Hash,
+        History: RegisterHistory,
 {
     type Actor = RegisterActor<ServerActor>;
-    type History = LinearizabilityTester<Id, Register<TestValue>>;
+    type History = History;
 
     fn actors(&self) -> Vec<Self::Actor> {
         let mut actors: Vec<Self::Actor> = self.servers.iter().map(|s| {
             RegisterActor::Server(s.clone())
         }).collect();
         for _ in 0..self.client_count {
-            actors.push(RegisterActor::Client { server_count: self.servers.len() as u64 });
+            actors.push(RegisterActor::Client {
+                server_count: self.servers.len() as u64,
+                times_out: self.client_timeouts,
+                performs_cas: self.client_cas,
+                retries: self.client_retries,
+            });
         }
         actors
     }
 
+    fn init_history(&self) -> Self::History {
+        if self.memoize_history {
+            History::default().memoized()
+        } else {
+            History::default()
+        }
+    }
+
     fn lossy_network(&self) -> LossyNetwork {
         self.lossy_network
     }
@@ -86,24 +139,36 @@ impl<ServerActor, InternalMsg> System for RegisterTestSystem<ServerActor, Intern
     }
 
     fn record_msg_out(&self, history: &Self::History, src: Id, _dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
-        // FIXME: Currently throws away useful information about invalid histories. Ideally
-        //        checking would continue, but the property would be labeled with an error.
-        if let Get(_) = msg {
-            let mut history = history.clone();
-            let _ = history.on_invoke(src, RegisterOp::Read);
-            Some(history)
-        } else if let Put(_req_id, value) = msg {
-            let mut history = history.clone();
-            let _ = history.on_invoke(src, RegisterOp::Write(*value));
-            Some(history)
-        } else {
-            None
+        // An invalid history poisons the tester, which records the offending operation (see
+        // `ConsistencyTester::invalid_history`) and fails the consistency property.
+        let op = match msg {
+            Get(_) => RegisterOp::Read,
+            Put(_req_id, value) => RegisterOp::Write(*value),
+            Cas(_req_id, expected, new) => RegisterOp::Cas(*expected, *new),
+            _ => return None,
+        };
+        let mut history = history.clone();
+        if self.client_timeouts && history.is_in_flight(src) {
+            // The client only moves on after giving up on the previous operation.
+            let _ = history.on_timeout(src);
         }
+        let _ = history.on_invoke(src, op);
+        Some(history)
+    }
+
+    fn record_timeout(&self, history: &Self::History, id: Id) -> Option<Self::History> {
+        // A client that times out with retries enabled either resends its request or gives up on
+        // it. Either way, the pending operation may or may not take effect.
+        if self.client_retries == 0
+            || usize::from(id) < self.servers.len()
+            || !history.is_in_flight(id) { return None }
+        let mut history = history.clone();
+        let _ = history.on_timeout(id);
+        Some(history)
     }
 
     fn record_msg_in(&self, history: &Self::History, _src: Id, dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
-        // FIXME: Currently throws away useful information about invalid histories. Ideally
-        //        checking would continue, but the property would be labeled with an error.
+        // See `record_msg_out` regarding invalid histories.
         match msg {
             GetOk(_, v) => {
                 let mut history = history.clone();
@@ -115,14 +180,19 @@ impl<ServerActor, InternalMsg> System for RegisterTestSystem<ServerActor, Intern
                 let _ = history.on_return(dst, RegisterRet::WriteOk);
                 Some(history)
             }
+            CasOk(_, swapped) => {
+                let mut history = history.clone();
+                let _ = history.on_return(dst, RegisterRet::CasOk(*swapped));
+                Some(history)
+            }
             _ => None
         }
     }
 
     fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
         vec![
-            Property::<SystemModel<Self>>::always("linearizable", |_, state| {
-                state.history.serialized_history().is_some()
+            Property::<SystemModel<Self>>::always(History::consistency_model(), |_, state| {
+                state.history.is_consistent()
             }),
             Property::<SystemModel<Self>>::sometimes("value chosen",  |_, state| {
                 for env in &state.network {
@@ -144,9 +214,15 @@ impl<ServerActor, InternalMsg> System for RegisterTestSystem<ServerActor, Intern
 pub enum RegisterActor<ServerActor> {
     /// A client that [`RegisterMsg::Put`]s a message and upon receving a
     /// corresponding [`RegisterMsg::PutOk`] follows up with a
-    /// [`RegisterMsg::Get`].
+    /// [`RegisterMsg::Get`]. If `performs_cas`, then the `Get` is preceded by
+    /// a [`RegisterMsg::Cas`]. If a response does not arrive before a
+    /// timeout, then the client resends the request up to `retries` times,
+    /// after which it moves on if `times_out`.
     Client {
         server_count: u64,
+        times_out: bool,
+        performs_cas: bool,
+        retries: u8,
     },
     /// A server actor being validated.
     Server(ServerActor),
@@ -154,11 +230,13 @@ pub enum RegisterActor<ServerActor> {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 #[derive(serde::Serialize)]
 pub enum RegisterActorState<ServerState> {
-    /// A client that sends a sequence of [`RegisterMsg::Put`] messages before sending a
-    /// [`RegisterMsg::Get`].
+    /// A client that sends a sequence of [`RegisterMsg::Put`] messages (optionally followed by a
+    /// [`RegisterMsg::Cas`]) before sending a [`RegisterMsg::Get`].
     Client {
         awaiting: Option<TestRequestId>,
         op_count: u64,
+        /// The number of times the awaited request has been resent.
+        attempts: u8,
     },
     /// Wraps the state of a server actor.
     Server(ServerState),
@@ -178,16 +256,16 @@ where
     #[allow(clippy::identity_op)]
     fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
         match self {
-            RegisterActor::Client { server_count } => {
+            RegisterActor::Client { server_count, times_out, performs_cas, retries } => {
                 let index = id.0;
                 let unique_request_id = 1 * index as TestRequestId; // next will be 2 * index
-                let value = (b'A' + (index - server_count) as u8) as char;
-                o.send(
-                    Id((index + 0) % server_count),
-                    Put(unique_request_id, value));
+                let (dst, request) = client_request(id, *server_count, 0, *performs_cas);
+                o.send(dst, request);
+                if *times_out || *retries > 0 { o.set_timer(model_timeout()); }
                 RegisterActorState::Client {
                     awaiting: Some(unique_request_id),
                     op_count: 1,
+                    attempts: 0,
                 }
             }
             RegisterActor::Server(server_actor) => {
@@ -204,37 +282,24 @@ where
         use RegisterActorState as S;
 
         match (self, &**state) {
-            (A::Client { server_count }, S::Client {
+            (A::Client { server_count, times_out, performs_cas, retries }, S::Client {
                                              awaiting: Some(awaiting),
-                                             op_count
+                                             op_count,
+                                             ..
                                          }) => {
                 match msg {
-                    RegisterMsg::PutOk(request_id) if &request_id == awaiting => {
-                        // Clients send a sequence of `Put`s followed by a `Get`. As a simple
-                        // heuristic to cover a wider range of behaviors: the first client's `Put`
-                        // sequence is of length 2, while the others are of length 1.
-                        let index = id.0;
-                        let unique_request_id = ((op_count + 1) * index) as TestRequestId;
-                        let max_put_count = if index == *server_count { 2 } else { 1 };
-                        if *op_count < max_put_count {
-                            let value = (b'Z' - (index - server_count) as u8) as char;
-                            o.send(
-                                Id((index + op_count) % server_count),
-                                Put(unique_request_id, value));
-                        } else {
-                            o.send(
-                                Id((index + op_count) % server_count),
-                                Get(unique_request_id));
-                        }
-                        *state = Cow::Owned(RegisterActorState::Client {
-                            awaiting: Some(unique_request_id),
-                            op_count: op_count + 1,
-                        });
+                    RegisterMsg::PutOk(request_id) | RegisterMsg::CasOk(request_id, _)
+                        if &request_id == awaiting =>
+                    {
+                        *state = Cow::Owned(next_client_op(
+                            id, *server_count, *op_count, *times_out || *retries > 0, *performs_cas, o));
                     }
                     RegisterMsg::GetOk(request_id, _value) if &request_id == awaiting => {
+                        if *times_out || *retries > 0 { o.cancel_timer(); }
                         *state = Cow::Owned(RegisterActorState::Client {
                             awaiting: None,
                             op_count: op_count + 1,
+                            attempts: 0,
                         });
                     }
                     _ => {}
@@ -252,6 +317,165 @@ where
             _ => {}
         }
     }
+
+    fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+        use RegisterActor as A;
+        use RegisterActorState as S;
+
+        match (self, &**state) {
+            (A::Client { server_count, times_out, performs_cas, retries }, S::Client {
+                                                              awaiting: Some(awaiting),
+                                                              op_count,
+                                                              attempts,
+                                                          }) if attempts < retries => {
+                // Resend the pending request, only awaiting a further timeout if the client will
+                // act upon it.
+                let (dst, request) = client_request(id, *server_count, op_count - 1, *performs_cas);
+                o.send(dst, request);
+                if attempts + 1 < *retries || *times_out { o.set_timer(model_timeout()); }
+                *state = Cow::Owned(RegisterActorState::Client {
+                    awaiting: Some(*awaiting),
+                    op_count: *op_count,
+                    attempts: attempts + 1,
+                });
+            }
+            (A::Client { server_count, times_out: true, performs_cas, .. }, S::Client {
+                                                              awaiting: Some(_),
+                                                              op_count,
+                                                              ..
+                                                          }) => {
+                // Give up on the pending operation, which is recorded as indeterminate.
+                let last_op_count = max_put_count(id, *server_count) + *performs_cas as u64;
+                *state = Cow::Owned(
+                    if *op_count <= last_op_count {
+                        next_client_op(id, *server_count, *op_count, true, *performs_cas, o)
+                    } else {
+                        RegisterActorState::Client { awaiting: None, op_count: op_count + 1, attempts: 0 }
+                    });
+            }
+            (A::Server(server_actor), S::Server(server_state)) => {
+                let mut server_state = Cow::Borrowed(server_state);
+                let mut server_out = Out::new();
+                server_actor.on_timeout(id, &mut server_state, &mut server_out);
+                if let Cow::Owned(server_state) = server_state {
+                    *state = Cow::Owned(RegisterActorState::Server(server_state))
+                }
+                o.append(&mut server_out);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_config_update(&self, id: Id, state: &mut Cow<Self::State>, config: Self::Msg, o: &mut Out<Self>) {
+        if let (RegisterActor::Server(server_actor), RegisterActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_config_update(id, &mut server_state, config, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(RegisterActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_peer_down(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        if let (RegisterActor::Server(server_actor), RegisterActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_peer_down(id, &mut server_state, peer, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(RegisterActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_peer_reset(&self, id: Id, state: &mut Cow<Self::State>, peer: Id, o: &mut Out<Self>) {
+        if let (RegisterActor::Server(server_actor), RegisterActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_peer_reset(id, &mut server_state, peer, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(RegisterActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn on_random(&self, id: Id, state: &mut Cow<Self::State>, value: u64, o: &mut Out<Self>) {
+        if let (RegisterActor::Server(server_actor), RegisterActorState::Server(server_state)) = (self, &**state) {
+            let mut server_state = Cow::Borrowed(server_state);
+            let mut server_out = Out::new();
+            server_actor.on_random(id, &mut server_state, value, &mut server_out);
+            if let Cow::Owned(server_state) = server_state {
+                *state = Cow::Owned(RegisterActorState::Server(server_state))
+            }
+            o.append(&mut server_out);
+        }
+    }
+
+    fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+        match self {
+            RegisterActor::Server(server_actor) => server_actor.spawned_actor(spec).map(RegisterActor::Server),
+            _ => None,
+        }
+    }
+}
+
+
+/// Clients send a sequence of `Put`s followed by an optional `Cas` and then a `Get`. As a simple
+/// heuristic to cover a wider range of behaviors: the first client's `Put` sequence is of length
+/// 2, while the others are of length 1.
+fn max_put_count(id: Id, server_count: u64) -> u64 {
+    if id.0 == server_count { 2 } else { 1 }
+}
+
+/// Sends the client's next operation after `op_count` operations have finished (or timed out).
+fn next_client_op<ServerActor, InternalMsg>(
+    id: Id, server_count: u64, op_count: u64, sets_timer: bool, performs_cas: bool,
+    o: &mut Out<RegisterActor<ServerActor>>)
+    -> RegisterActorState<ServerActor::State>
+where
+    ServerActor: Actor<Msg = RegisterMsg<TestRequestId, TestValue, InternalMsg>>,
+    InternalMsg: Clone + Debug + Eq + Hash,
+{
+    let unique_request_id = ((op_count + 1) * id.0) as TestRequestId;
+    let (dst, request) = client_request(id, server_count, op_count, performs_cas);
+    o.send(dst, request);
+    if sets_timer { o.set_timer(model_timeout()); }
+    RegisterActorState::Client {
+        awaiting: Some(unique_request_id),
+        op_count: op_count + 1,
+        attempts: 0,
+    }
+}
+
+/// The destination and request for the client's operation after `op_count` operations have
+/// finished (or timed out).
+fn client_request<InternalMsg>(id: Id, server_count: u64, op_count: u64, performs_cas: bool)
+    -> (Id, RegisterMsg<TestRequestId, TestValue, InternalMsg>)
+{
+    let index = id.0;
+    let unique_request_id = ((op_count + 1) * index) as TestRequestId;
+    let dst = Id((index + op_count) % server_count);
+    let put_count = max_put_count(id, server_count);
+    if op_count == 0 {
+        let value = (b'A' + (index - server_count) as u8) as char;
+        (dst, Put(unique_request_id, value))
+    } else if op_count < put_count {
+        let value = (b'Z' - (index - server_count) as u8) as char;
+        (dst, Put(unique_request_id, value))
+    } else if performs_cas && op_count == put_count {
+        // Expects the value that this client last wrote.
+        let expected = if put_count == 1 {
+            (b'A' + (index - server_count) as u8) as char
+        } else {
+            (b'Z' - (index - server_count) as u8) as char
+        };
+        (dst, Cas(unique_request_id, expected, expected.to_ascii_lowercase()))
+    } else {
+        (dst, Get(unique_request_id))
+    }
 }
 
 
@@ -260,3 +484,84 @@ pub type TestRequestId = u64;
 
 /// A simple value type for tests.
 pub type TestValue = char;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model};
+    use crate::actor::{Envelope, SystemAction};
+
+    /// Stores a single value, and responds to every request.
+    #[derive(Clone)]
+    struct SingleCopyRegisterActor;
+
+    impl Actor for SingleCopyRegisterActor {
+        type Msg = RegisterMsg<TestRequestId, TestValue, ()>;
+        type State = TestValue;
+
+        fn on_start(&self, _id: Id, _o: &mut Out<Self>) -> Self::State {
+            TestValue::default()
+        }
+
+        fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+            match msg {
+                Put(req_id, value) => {
+                    *state.to_mut() = value;
+                    o.send(src, PutOk(req_id));
+                }
+                Get(req_id) => o.send(src, GetOk(req_id, **state)),
+                Cas(req_id, expected, new) => {
+                    let swapped = **state == expected;
+                    if swapped { *state.to_mut() = new; }
+                    o.send(src, CasOk(req_id, swapped));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn system(client_timeouts: bool) -> RegisterTestSystem<SingleCopyRegisterActor, ()> {
+        RegisterTestSystem {
+            servers: vec![SingleCopyRegisterActor],
+            client_count: 1,
+            lossy_network: LossyNetwork::Yes,
+            duplicating_network: DuplicatingNetwork::No,
+            client_timeouts,
+            client_cas: true,
+            client_retries: 1,
+            .. Default::default()
+        }
+    }
+
+    #[test]
+    fn clients_resend_requests_on_timeout() {
+        let model = system(false).into_model();
+        let put = Envelope { src: Id(1), dst: Id(0), msg: Put(1, 'A') };
+        let state = model.init_states().remove(0);
+        let state = model.next_state(&state, SystemAction::Drop(put.clone())).unwrap();
+        assert!(state.network.is_empty());
+
+        // The request is resent once, after which the client awaits the response indefinitely.
+        let state = model.next_state(&state, SystemAction::Timeout(Id(1))).unwrap();
+        assert!(state.network.contains(&put));
+        assert_eq!(state.is_timer_set, vec![false, false]);
+        let state = model.next_state(&state, SystemAction::Deliver {
+            src: Id(1), dst: Id(0), msg: Put(1, 'A'),
+        }).unwrap();
+        let state = model.next_state(&state, SystemAction::Deliver {
+            src: Id(0), dst: Id(1), msg: PutOk(1),
+        }).unwrap();
+        assert_eq!(*state.actor_states[1], RegisterActorState::Client {
+            awaiting: Some(2), op_count: 2, attempts: 0,
+        });
+        assert!(state.history.is_consistent());
+    }
+
+    #[test]
+    fn retries_preserve_linearizability() {
+        for client_timeouts in [false, true] {
+            let checker = system(client_timeouts).into_model().checker().spawn_bfs().join();
+            checker.assert_properties();
+        }
+    }
+}
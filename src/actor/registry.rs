@@ -0,0 +1,190 @@
+//! Private module for selective re-export.
+
+use crate::actor::Id;
+use std::collections::BTreeMap;
+
+/// Maps human-readable names such as `"node-3"` or `"client-a"` to actor [`Id`]s, so actors can
+/// address peers by name (see [`Out::send_named`]) rather than by hand-computed indices.
+///
+/// The same names can map to indices when model checking (see [`Registry::indexed`]) and to
+/// socket addresses when spawning (see [`Registry::with`]), so actor logic does not depend on
+/// which runtime is in use.
+///
+/// ```
+/// use stateright::actor::{Id, Registry};
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+///
+/// // When model checking, each name corresponds with the actor at the same index.
+/// let registry = Registry::indexed(vec!["node-0", "node-1", "client-a"]);
+/// assert_eq!(registry.id("client-a"), Some(Id::from(2)));
+/// assert_eq!(registry.name(Id::from(1)), Some("node-1"));
+/// assert_eq!(registry.id("client-b"), None);
+///
+/// // When spawning, each name corresponds with a socket address.
+/// let registry = Registry::new()
+///     .with("node-0", SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3000))
+///     .with("node-1", SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// assert_eq!(
+///     registry.id("node-1"),
+///     Some(Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001))));
+/// ```
+///
+/// [`Out::send_named`]: crate::actor::Out::send_named
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Registry {
+    ids: BTreeMap<String, Id>,
+}
+
+impl Registry {
+    /// Constructs an empty registry.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Constructs a registry that maps each name to the index at which it appears, matching the
+    /// order of [`System::actors`].
+    ///
+    /// [`System::actors`]: crate::actor::System::actors
+    pub fn indexed<N: Into<String>>(names: impl IntoIterator<Item = N>) -> Self {
+        let mut registry = Registry::new();
+        for (index, name) in names.into_iter().enumerate() {
+            registry.register(name, Id::from(index));
+        }
+        registry
+    }
+
+    /// Maps a name to an ID, replacing any previous mapping for the name.
+    pub fn with(mut self, name: impl Into<String>, id: impl Into<Id>) -> Self {
+        self.register(name, id);
+        self
+    }
+
+    /// Maps a name to an ID, returning the ID to which the name previously mapped, if any.
+    pub fn register(&mut self, name: impl Into<String>, id: impl Into<Id>) -> Option<Id> {
+        self.ids.insert(name.into(), id.into())
+    }
+
+    /// Returns the ID to which a name maps.
+    pub fn id(&self, name: &str) -> Option<Id> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns a name that maps to an ID.
+    pub fn name(&self, id: Id) -> Option<&str> {
+        self.ids.iter()
+            .find(|(_, candidate)| **candidate == id)
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the IDs of every name with the specified prefix, such as `"node-"`, ordered by
+    /// name. Useful for broadcasting to a group of peers.
+    pub fn ids_with_prefix(&self, prefix: &str) -> Vec<Id> {
+        self.ids.iter()
+            .filter(|(name, _)| name.starts_with(prefix))
+            .map(|(_, id)| *id)
+            .collect()
+    }
+
+    /// Iterates over the names and corresponding IDs, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Id)> {
+        self.ids.iter().map(|(name, id)| (name.as_str(), *id))
+    }
+
+    /// Returns the number of names in the registry.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Indicates whether the registry is empty.
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Checker, Model, Property};
+    use crate::actor::*;
+    use std::borrow::Cow;
+
+    /// Each client greets a server by name, and the server echoes back.
+    #[derive(Clone)]
+    struct Greeter { registry: Registry, server: Option<&'static str> }
+
+    impl Actor for Greeter {
+        type Msg = &'static str;
+        type State = Option<String>;
+
+        fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
+            if let Some(server) = self.server {
+                o.send_named(&self.registry, server, "hello");
+            }
+            None
+        }
+
+        fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+            if self.server.is_none() {
+                o.send(src, msg);
+            }
+            *state.to_mut() = self.registry.name(src).map(String::from);
+        }
+    }
+
+    struct GreeterSystem;
+
+    impl System for GreeterSystem {
+        type Actor = Greeter;
+        type History = ();
+
+        fn actors(&self) -> Vec<Self::Actor> {
+            let registry = Registry::indexed(vec!["server-a", "server-b", "client"]);
+            vec![
+                Greeter { registry: registry.clone(), server: None },
+                Greeter { registry: registry.clone(), server: None },
+                Greeter { registry, server: Some("server-b") },
+            ]
+        }
+
+        fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+            vec![
+                Property::<SystemModel<Self>>::sometimes("echoed", |_, state| {
+                    state.actor_states[2].as_deref() == Some("server-b")
+                }),
+                Property::<SystemModel<Self>>::always("not greeted", |_, state| {
+                    state.actor_states[0].is_none()
+                }),
+            ]
+        }
+    }
+
+    #[test]
+    fn sends_by_name() {
+        let checker = GreeterSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        checker.assert_discovery("echoed", vec![
+            SystemAction::Deliver { src: Id::from(2), dst: Id::from(1), msg: "hello" },
+            SystemAction::Deliver { src: Id::from(1), dst: Id::from(2), msg: "hello" },
+        ]);
+    }
+
+    #[test]
+    fn looks_up_groups() {
+        let mut registry = Registry::indexed(vec!["node-0", "node-1", "client-a"]);
+        assert_eq!(registry.ids_with_prefix("node-"), vec![Id::from(0), Id::from(1)]);
+        assert_eq!(registry.register("node-1", Id::from(7)), Some(Id::from(1)));
+        assert_eq!(registry.ids_with_prefix("node-"), vec![Id::from(0), Id::from(7)]);
+        assert_eq!(registry.name(Id::from(1)), None);
+        assert_eq!(registry.len(), 3);
+        assert_eq!(
+            registry.iter().collect::<Vec<_>>(),
+            vec![("client-a", Id::from(2)), ("node-0", Id::from(0)), ("node-1", Id::from(7))]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown actor name. name=missing")]
+    fn panics_for_unknown_names() {
+        let mut o = Out::<Greeter>::new();
+        o.send_named(&Registry::new(), "missing", "hello");
+    }
+}
@@ -0,0 +1,185 @@
+//! Private module for selective re-export.
+
+use crate::Path;
+use crate::actor::*;
+use std::fmt::{Debug, Display, Formatter};
+
+/// A sequence of [`SystemAction`]s along with the actor states that they lead to, such as a
+/// discovery of the model checker. A scenario can be saved (e.g. as JSON via `serde_json`) and
+/// later [`replay`]ed against the actors running in a [`SimRuntime`], which turns a checker
+/// discovery into a regression test for the executable system.
+///
+/// Actors are identified by index, as when model checking. The simulated network does not
+/// duplicate messages, so a scenario that delivers a message more than once cannot be replayed.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Checker, Model, Property};
+/// use stateright::actor::{Actor, Id, Out, Scenario, System, SystemModel};
+/// use std::borrow::Cow;
+///
+/// /// Replies to each number with the next number, up to 3.
+/// struct Counter { peer: Option<Id> }
+///
+/// impl Actor for Counter {
+///     type Msg = u32;
+///     type State = u32;
+///
+///     fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
+///         if let Some(peer) = self.peer { o.send(peer, 1); }
+///         0
+///     }
+///
+///     fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+///         if msg < 3 { o.send(src, msg + 1); }
+///         *state.to_mut() = msg;
+///     }
+/// }
+///
+/// struct CounterSystem;
+///
+/// impl System for CounterSystem {
+///     type Actor = Counter;
+///     type History = ();
+///
+///     fn actors(&self) -> Vec<Self::Actor> {
+///         vec![Counter { peer: Some(Id::from(1)) }, Counter { peer: None }]
+///     }
+///
+///     fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+///         vec![Property::<SystemModel<Self>>::sometimes("counted", |_, state| {
+///             *state.actor_states[1] == 3
+///         })]
+///     }
+/// }
+///
+/// let checker = CounterSystem.into_model().checker().spawn_bfs().join();
+/// let scenario = Scenario::from_path(&checker.discovery("counted").unwrap());
+///
+/// // The scenario can be saved and later replayed against the actors.
+/// let json = serde_json::to_string(&scenario).unwrap();
+/// let scenario: Scenario<u32, u32> = serde_json::from_str(&json).unwrap();
+/// let sim = scenario.replay(CounterSystem.actors()).unwrap();
+/// assert_eq!(sim.state(Id::from(1)), Some(&3));
+/// ```
+///
+/// [`replay`]: Scenario::replay
+#[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Scenario<Msg, State> {
+    /// The actions to take, in order.
+    pub actions: Vec<SystemAction<Msg>>,
+    /// The expected state of each actor after the actions, indexed by actor ID.
+    pub actor_states: Vec<State>,
+}
+
+impl<Msg: Clone, State: Clone> Scenario<Msg, State> {
+    /// Captures the actions of a path, such as a [`Checker::discovery`], and the actor states
+    /// that they lead to.
+    ///
+    /// [`Checker::discovery`]: crate::Checker::discovery
+    pub fn from_path<S>(path: &Path<SystemState<S>, SystemAction<Msg>>) -> Self
+    where S: System,
+          S::Actor: Actor<Msg = Msg, State = State>,
+    {
+        Scenario {
+            actions: path.actions().cloned().collect(),
+            actor_states: path.last_state().actor_states.iter()
+                .map(|state| (**state).clone())
+                .collect(),
+        }
+    }
+}
+
+impl<Msg, State> Scenario<Msg, State>
+where Msg: Clone + Debug + Eq + std::hash::Hash,
+      State: Clone + Debug + PartialEq + std::hash::Hash,
+{
+    /// Runs the actors (identified by index) in a [`SimRuntime`], applies each action in turn,
+    /// then checks that each actor reached its expected state. Returns the simulation for further
+    /// inspection, or the first divergence from the scenario.
+    pub fn replay<A>(&self, actors: Vec<A>) -> Result<SimRuntime<A>, ScenarioError<Msg, State>>
+    where A: Actor<Msg = Msg, State = State>,
+    {
+        let mut sim = SimRuntime::new(0, actors.into_iter()
+            .enumerate()
+            .map(|(index, actor)| (Id::from(index), actor))
+            .collect());
+        for (index, action) in self.actions.iter().enumerate() {
+            if !sim.apply(action.clone()) {
+                return Err(ScenarioError::Inapplicable { index, action: action.clone() });
+            }
+        }
+        for (index, expected) in self.actor_states.iter().enumerate() {
+            let id = Id::from(index);
+            let actual = sim.state(id);
+            if actual != Some(expected) {
+                return Err(ScenarioError::Mismatch {
+                    id,
+                    expected: expected.clone(),
+                    actual: actual.cloned(),
+                });
+            }
+        }
+        Ok(sim)
+    }
+}
+
+/// Indicates how a [`Scenario`] diverged when replayed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScenarioError<Msg, State> {
+    /// The action (at the specified position in the scenario) was not possible, such as when
+    /// delivering a message that was never sent.
+    Inapplicable { index: usize, action: SystemAction<Msg> },
+    /// An actor did not reach the expected state. `actual` is `None` if the actor did not start.
+    Mismatch { id: Id, expected: State, actual: Option<State> },
+}
+
+impl<Msg: Debug, State: Debug> Display for ScenarioError<Msg, State> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScenarioError::Inapplicable { index, action } =>
+                write!(f, "Unable to apply action. index={}, action={:?}", index, action),
+            ScenarioError::Mismatch { id, expected, actual } =>
+                write!(f, "Unexpected actor state. id={}, expected={:?}, actual={:?}", id, expected, actual),
+        }
+    }
+}
+
+impl<Msg: Debug, State: Debug> std::error::Error for ScenarioError<Msg, State> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Model};
+    use crate::actor::actor_test_util::ping_pong::{PingPongCount, PingPongMsg::*, PingPongSystem};
+
+    #[test]
+    fn replays_discoveries() {
+        let system = PingPongSystem {
+            max_nat: 3,
+            lossy: LossyNetwork::Yes,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        };
+        let checker = system.clone().into_model().checker().spawn_bfs().join();
+        let scenario = Scenario::from_path(&checker.discovery("can reach max").unwrap());
+        let sim = scenario.replay(system.actors()).unwrap();
+        assert!(sim.state(Id::from(0)) == Some(&PingPongCount(3))
+            || sim.state(Id::from(1)) == Some(&PingPongCount(3)));
+
+        // Divergence from the scenario is reported.
+        let mut diverged = scenario.clone();
+        diverged.actions.pop();
+        assert!(matches!(
+            diverged.replay(system.actors()),
+            Err(ScenarioError::Mismatch { .. })));
+        let mut diverged = scenario;
+        let never_sent = SystemAction::Deliver { src: Id::from(1), dst: Id::from(0), msg: Pong(7) };
+        diverged.actions.insert(0, never_sent.clone());
+        assert_eq!(
+            diverged.replay(system.actors()).err(),
+            Some(ScenarioError::Inapplicable { index: 0, action: never_sent }));
+    }
+}
@@ -0,0 +1,133 @@
+//! Private module for selective re-export.
+
+use crate::actor::*;
+use crate::Path;
+use std::fmt::Write;
+
+/// The parts of a sequence diagram that differ between text formats.
+struct Syntax {
+    header: &'static str,
+    footer: &'static str,
+    participant: fn(&mut String, usize),
+    message: fn(&mut String, usize, usize, &str),
+    lost_message: fn(&mut String, usize, usize, &str),
+    note: fn(&mut String, usize, &str),
+    escape: fn(&str) -> String,
+}
+
+const PLANTUML: Syntax = Syntax {
+    header: "@startuml\n",
+    footer: "@enduml\n",
+    participant: |out, i| writeln!(out, "participant \"{}\" as A{}", i, i).unwrap(),
+    message: |out, src, dst, label| writeln!(out, "A{} -> A{}: {}", src, dst, label).unwrap(),
+    lost_message: |out, src, dst, label| writeln!(out, "A{} ->x A{}: {}", src, dst, label).unwrap(),
+    note: |out, i, label| writeln!(out, "note over A{}: {}", i, label).unwrap(),
+    escape: |label| label.replace('\n', "\\n"),
+};
+
+const MERMAID: Syntax = Syntax {
+    header: "sequenceDiagram\n",
+    footer: "",
+    participant: |out, i| writeln!(out, "    participant A{} as {}", i, i).unwrap(),
+    message: |out, src, dst, label| writeln!(out, "    A{}->>A{}: {}", src, dst, label).unwrap(),
+    lost_message: |out, src, dst, label| writeln!(out, "    A{}-xA{}: {}", src, dst, label).unwrap(),
+    note: |out, i, label| writeln!(out, "    Note over A{}: {}", i, label).unwrap(),
+    escape: |label| label.replace('#', "#35;").replace(';', "#59;").replace('\n', "<br/>"),
+};
+
+impl<S: System> Path<SystemState<S>, SystemAction<<S::Actor as Actor>::Msg>> {
+    /// Renders the path as a [PlantUML](https://plantuml.com/sequence-diagram) sequence diagram,
+    /// which unlike [`SystemModel::as_svg`](crate::Model::as_svg) can be edited and pasted into
+    /// design documents.
+    pub fn as_plantuml(&self) -> String {
+        self.as_sequence_diagram(&PLANTUML)
+    }
+
+    /// Renders the path as a [Mermaid](https://mermaid.js.org/syntax/sequenceDiagram.html)
+    /// sequence diagram, which GitHub renders within Markdown code blocks tagged `mermaid`.
+    pub fn as_mermaid(&self) -> String {
+        self.as_sequence_diagram(&MERMAID)
+    }
+
+    fn as_sequence_diagram(&self, syntax: &Syntax) -> String {
+        let mut out = syntax.header.to_string();
+        for i in 0..self.last_state().actor_states.len() {
+            (syntax.participant)(&mut out, i);
+        }
+        for action in self.actions() {
+            match action {
+                SystemAction::Deliver { src, dst, msg } => {
+                    let label = (syntax.escape)(&format!("{:?}", msg));
+                    (syntax.message)(&mut out, usize::from(*src), usize::from(*dst), &label);
+                }
+                SystemAction::Drop(Envelope { src, dst, msg }) => {
+                    let label = (syntax.escape)(&format!("{:?}", msg));
+                    (syntax.lost_message)(&mut out, usize::from(*src), usize::from(*dst), &label);
+                }
+                SystemAction::Transmit(Envelope { src, dst, msg }) => {
+                    let label = (syntax.escape)(&format!("Send {:?} to {:?}", msg, dst));
+                    (syntax.note)(&mut out, usize::from(*src), &label);
+                }
+                SystemAction::Timeout(id) => {
+                    (syntax.note)(&mut out, usize::from(*id), "Timeout");
+                }
+                SystemAction::UpdateConfig { dst, config } => {
+                    let label = (syntax.escape)(&format!("Config: {:?}", config));
+                    (syntax.note)(&mut out, usize::from(*dst), &label);
+                }
+                SystemAction::Crash(id) => {
+                    (syntax.note)(&mut out, usize::from(*id), "Crash");
+                }
+                SystemAction::ChooseRandom { id, value } => {
+                    (syntax.note)(&mut out, usize::from(*id), &format!("Random {}", value));
+                }
+            }
+        }
+        out.push_str(syntax.footer);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::actor::*;
+    use crate::actor::actor_test_util::ping_pong::{PingPongMsg::*, PingPongSystem};
+
+    fn path() -> Path<
+        SystemState<PingPongSystem>,
+        SystemAction<<<PingPongSystem as System>::Actor as Actor>::Msg>>
+    {
+        let model = PingPongSystem {
+            max_nat: 2,
+            lossy: LossyNetwork::Yes,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        }.into_model();
+        Path::from_actions(&model, model.init_states().remove(0), &[
+            SystemAction::Deliver { src: Id::from(0), dst: Id::from(1), msg: Ping(0) },
+            SystemAction::Drop(Envelope { src: Id::from(1), dst: Id::from(0), msg: Pong(0) }),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn can_render_plantuml() {
+        assert_eq!(path().as_plantuml(), "\
+            @startuml\n\
+            participant \"0\" as A0\n\
+            participant \"1\" as A1\n\
+            A0 -> A1: Ping(0)\n\
+            A1 ->x A0: Pong(0)\n\
+            @enduml\n");
+    }
+
+    #[test]
+    fn can_render_mermaid() {
+        assert_eq!(path().as_mermaid(), "\
+            sequenceDiagram\n    \
+                participant A0 as 0\n    \
+                participant A1 as 1\n    \
+                A0->>A1: Ping(0)\n    \
+                A1-xA0: Pong(0)\n");
+    }
+}
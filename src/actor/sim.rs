@@ -0,0 +1,515 @@
+//! Private module for selective re-export.
+
+use crate::actor::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use std::time::Duration;
+
+/// Runs actors in memory on the current thread with a virtual clock, in the style of
+/// [FoundationDB's deterministic simulation testing]. A random number generator seeded by the
+/// caller chooses the latency of each message (and therefore the order in which messages are
+/// delivered) and the duration of each timer, so rerunning a simulation with the same seed
+/// reproduces the same sequence of events, as reported by [`SimRuntime::step`].
+///
+/// Whereas a model checker explores every ordering of events within a bounded state space and
+/// [`spawn`] runs actors over UDP, a simulation follows one ordering per seed, which scales to
+/// larger systems and longer runs while still reusing the same [`Actor`] implementations.
+///
+/// # Example
+///
+/// ```
+/// use stateright::actor::{Actor, Id, Out, SimRuntime};
+/// use std::borrow::Cow;
+/// use std::time::Duration;
+///
+/// /// Replies to each number with the next number, up to 10.
+/// struct Counter { peer: Option<Id> }
+///
+/// impl Actor for Counter {
+///     type Msg = u32;
+///     type State = u32;
+///
+///     fn on_start(&self, _id: Id, o: &mut Out<Self>) -> Self::State {
+///         if let Some(peer) = self.peer { o.send(peer, 1); }
+///         0
+///     }
+///
+///     fn on_msg(&self, _id: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+///         if msg < 10 { o.send(src, msg + 1); }
+///         *state.to_mut() = msg;
+///     }
+/// }
+///
+/// for seed in 0..10 {
+///     let mut sim = SimRuntime::new(seed, vec![
+///         (Id::from(0), Counter { peer: Some(Id::from(1)) }),
+///         (Id::from(1), Counter { peer: None }),
+///     ]);
+///     sim.run_for(Duration::from_secs(1));
+///     assert_eq!(sim.state(Id::from(0)), Some(&10), "seed={}", seed);
+/// }
+/// ```
+///
+/// [FoundationDB's deterministic simulation testing]: https://apple.github.io/foundationdb/testing.html
+pub struct SimRuntime<A: Actor> {
+    seed: u64,
+    rng: StdRng,
+    now: Duration,
+    latency: Range<Duration>,
+    is_started: bool,
+    ids: Vec<Id>,
+    actors: Vec<A>,
+    states: Vec<A::State>,
+    /// The deadline and sequencer of each actor's timer, if set.
+    timers: Vec<Option<(Duration, u64)>>,
+    crashed: Vec<bool>,
+    /// `(watcher, peer)` pairs recorded via [`Out::monitor`].
+    monitors: Vec<(Id, Id)>,
+    /// Undelivered messages keyed by delivery time and sequencer, which breaks ties in send order.
+    network: BTreeMap<(Duration, u64), Envelope<A::Msg>>,
+    next_seq: u64,
+    /// Values chosen for [`Out::choose_random`] that have yet to be passed to the requesting
+    /// actor, in the order requested.
+    random_values: VecDeque<(usize, u64)>,
+}
+
+impl<A: Actor> SimRuntime<A> {
+    /// Instantiates a simulation of the specified actors, whose random choices are determined by
+    /// `seed`. Actors start upon the first step.
+    pub fn new(seed: u64, actors: Vec<(impl Into<Id>, A)>) -> Self {
+        let mut sim = SimRuntime {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            now: Duration::from_secs(0),
+            latency: Duration::from_millis(1)..Duration::from_millis(10),
+            is_started: false,
+            ids: Vec::with_capacity(actors.len()),
+            actors: Vec::with_capacity(actors.len()),
+            states: Vec::with_capacity(actors.len()),
+            timers: Vec::with_capacity(actors.len()),
+            crashed: Vec::with_capacity(actors.len()),
+            monitors: Vec::new(),
+            network: BTreeMap::new(),
+            next_seq: 0,
+            random_values: VecDeque::new(),
+        };
+        for (id, actor) in actors {
+            sim.ids.push(id.into());
+            sim.actors.push(actor);
+            sim.timers.push(None);
+            sim.crashed.push(false);
+        }
+        sim
+    }
+
+    /// Sets the range from which each message's latency is chosen. Defaults to 1-10ms.
+    pub fn latency(mut self, latency: Range<Duration>) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// The seed that determines the simulation's random choices.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The current virtual time, which starts at zero.
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// The state of an actor, or `None` if the actor is unknown or has not started.
+    pub fn state(&self, id: Id) -> Option<&A::State> {
+        self.index(id).and_then(|index| self.states.get(index))
+    }
+
+    /// Indicates whether an actor has crashed. See [`SimRuntime::crash`].
+    pub fn is_crashed(&self, id: Id) -> bool {
+        matches!(self.index(id), Some(index) if self.crashed[index])
+    }
+
+    /// Processes the next event, advancing the virtual clock to the time of the event. Returns
+    /// the event, or `None` if no messages or timers are pending. A message for an unknown or
+    /// crashed actor is returned as a [`SystemAction::Drop`]. Values chosen at random are passed
+    /// to actors without advancing the clock.
+    pub fn step(&mut self) -> Option<SystemAction<A::Msg>> {
+        self.start();
+        if let Some((index, value)) = self.random_values.pop_front() {
+            self.act(index, |actor, id, state, o| actor.on_random(id, state, value, o));
+            return Some(SystemAction::ChooseRandom { id: self.ids[index], value });
+        }
+        let (time, seq, timer_index) = self.next_event()?;
+        self.now = time;
+        if let Some(index) = timer_index {
+            self.timers[index] = None; // timer is no longer valid
+            self.act(index, |actor, id, state, o| actor.on_timeout(id, state, o));
+            return Some(SystemAction::Timeout(self.ids[index]));
+        }
+
+        let env = self.network.remove(&(time, seq)).expect("message is pending");
+        Some(self.deliver(env))
+    }
+
+    /// Processes a specific event rather than the next event chosen by the simulation, such as
+    /// an action from a model checker [`Path`], without advancing the virtual clock. Returns
+    /// `false` if the event is not possible, such as when delivering a message that is not
+    /// pending or firing a timer that is not set.
+    ///
+    /// [`SystemAction::ChooseRandom`] overrides the value chosen for the actor's earliest pending
+    /// random choice, and [`SystemAction::Transmit`] has no effect as broadcasts are sent
+    /// atomically.
+    ///
+    /// [`Path`]: crate::Path
+    pub fn apply(&mut self, action: SystemAction<A::Msg>) -> bool {
+        self.start();
+        match action {
+            SystemAction::Deliver { src, dst, msg } => {
+                let env = Envelope { src, dst, msg };
+                match self.pending_key(&env) {
+                    Some(key) if !self.is_crashed(dst) && self.index(dst).is_some() => {
+                        let env = self.network.remove(&key).expect("message is pending");
+                        self.deliver(env);
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            SystemAction::Drop(env) => {
+                match self.pending_key(&env) {
+                    Some(key) => self.network.remove(&key).is_some(),
+                    None => false,
+                }
+            },
+            SystemAction::Transmit(env) => self.pending_key(&env).is_some(),
+            SystemAction::Timeout(id) => {
+                match self.index(id) {
+                    Some(index) if self.timers[index].is_some() && !self.crashed[index] => {
+                        self.timers[index] = None; // timer is no longer valid
+                        self.act(index, |actor, id, state, o| actor.on_timeout(id, state, o));
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            SystemAction::UpdateConfig { dst, config } => {
+                match self.index(dst) {
+                    Some(index) if !self.crashed[index] => {
+                        self.act(index, |actor, id, state, o| actor.on_config_update(id, state, config, o));
+                        true
+                    },
+                    _ => false,
+                }
+            },
+            SystemAction::Crash(id) => {
+                if self.index(id).is_none() || self.is_crashed(id) { return false }
+                self.crash(id);
+                true
+            },
+            SystemAction::ChooseRandom { id, value } => {
+                let index = match self.index(id) {
+                    Some(index) => index,
+                    None => return false,
+                };
+                match self.random_values.iter().position(|(chooser, _)| *chooser == index) {
+                    Some(position) => {
+                        self.random_values.remove(position);
+                        self.act(index, |actor, id, state, o| actor.on_random(id, state, value, o));
+                        true
+                    },
+                    None => false,
+                }
+            },
+        }
+    }
+
+    /// Processes events until the virtual clock advances by `duration` (or no events are
+    /// pending). Returns the number of events processed.
+    pub fn run_for(&mut self, duration: Duration) -> usize {
+        self.start();
+        let deadline = self.now + duration;
+        let mut count = 0;
+        while !self.random_values.is_empty()
+            || matches!(self.next_event(), Some((time, _, _)) if time <= deadline)
+        {
+            self.step();
+            count += 1;
+        }
+        self.now = deadline;
+        count
+    }
+
+    /// Crashes an actor, which then takes no further steps. Pending messages for the actor are
+    /// dropped upon delivery, and actors monitoring it are notified via [`Actor::on_peer_down`].
+    pub fn crash(&mut self, id: Id) {
+        self.start();
+        let index = match self.index(id) {
+            Some(index) if !self.crashed[index] => index,
+            _ => return,
+        };
+        self.crashed[index] = true;
+        self.timers[index] = None;
+        self.random_values.retain(|(chooser, _)| *chooser != index);
+        let watchers: Vec<Id> = self.monitors.iter()
+            .filter(|(watcher, peer)| *peer == id && *watcher != id)
+            .map(|(watcher, _)| *watcher)
+            .collect();
+        self.monitors.retain(|(watcher, peer)| *watcher != id && *peer != id);
+        for watcher in watchers {
+            self.notify_peer_down(watcher, id);
+        }
+    }
+
+    /// Starts every actor if not already started.
+    fn start(&mut self) {
+        if self.is_started { return }
+        self.is_started = true;
+
+        // Start each actor, then process commands, as spawned actors are assigned later IDs.
+        let mut outs = Vec::with_capacity(self.actors.len());
+        for (id, actor) in self.ids.iter().zip(&self.actors) {
+            let mut out = Out::new();
+            self.states.push(actor.on_start(*id, &mut out));
+            outs.push(out);
+        }
+        for (index, out) in outs.into_iter().enumerate() {
+            self.process_commands(index, out);
+        }
+    }
+
+    /// Indicates the time and sequencer of the next event, along with the index of the actor
+    /// whose timer fires or `None` if a message is delivered.
+    fn next_event(&self) -> Option<(Duration, u64, Option<usize>)> {
+        let next_timer = self.timers.iter().enumerate()
+            .filter_map(|(index, timer)| timer.map(|(time, seq)| (time, seq, Some(index))))
+            .min();
+        let next_msg = self.network.keys().next().map(|(time, seq)| (*time, *seq, None));
+        match (next_timer, next_msg) {
+            (Some(timer), Some(msg)) => Some(if (timer.0, timer.1) < (msg.0, msg.1) { timer } else { msg }),
+            (timer, msg) => timer.or(msg),
+        }
+    }
+
+    fn index(&self, id: Id) -> Option<usize> {
+        self.ids.iter().position(|candidate| *candidate == id)
+    }
+
+    /// The key of the earliest pending message matching an envelope.
+    fn pending_key(&self, env: &Envelope<A::Msg>) -> Option<(Duration, u64)> {
+        self.network.iter().find(|(_, pending)| *pending == env).map(|(key, _)| *key)
+    }
+
+    /// Delivers a message that was removed from the network, or drops it if the recipient is
+    /// unknown or crashed.
+    fn deliver(&mut self, env: Envelope<A::Msg>) -> SystemAction<A::Msg> {
+        let index = match self.index(env.dst) {
+            Some(index) if !self.crashed[index] => index,
+            _ => return SystemAction::Drop(env),
+        };
+        let Envelope { src, dst, msg } = env;
+        let delivered = msg.clone();
+        self.act(index, |actor, id, state, o| actor.on_msg(id, state, src, delivered, o));
+        SystemAction::Deliver { src, dst, msg }
+    }
+
+    /// Invokes one of an actor's handlers, then updates its state and processes its commands.
+    fn act(&mut self, index: usize, handler: impl FnOnce(&A, Id, &mut Cow<A::State>, &mut Out<A>)) {
+        let id = self.ids[index];
+        let mut state = Cow::Borrowed(&self.states[index]);
+        let mut out = Out::new();
+        handler(&self.actors[index], id, &mut state, &mut out);
+        if let Cow::Owned(next_state) = state {
+            self.states[index] = next_state;
+        }
+        self.process_commands(index, out);
+    }
+
+    fn next_seq(&mut self) -> u64 {
+        self.next_seq += 1;
+        self.next_seq
+    }
+
+    /// Chooses a duration from a range.
+    fn choose(&mut self, range: Range<Duration>) -> Duration {
+        if range.start < range.end {
+            self.rng.gen_range(range.start, range.end)
+        } else {
+            range.start
+        }
+    }
+
+    /// Notifies an actor that a monitored peer is down per [`Actor::on_peer_down`].
+    fn notify_peer_down(&mut self, id: Id, peer: Id) {
+        let index = match self.index(id) {
+            Some(index) if !self.crashed[index] => index,
+            _ => return,
+        };
+        self.act(index, |actor, id, state, o| actor.on_peer_down(id, state, peer, o));
+    }
+
+    /// Updates the network and timers, spawns actors, and records monitors.
+    fn process_commands(&mut self, index: usize, out: Out<A>) {
+        let id = self.ids[index];
+        for command in out {
+            match command {
+                Command::Send(dst, msg) => {
+                    let time = self.now + self.choose(self.latency.clone());
+                    let seq = self.next_seq();
+                    self.network.insert((time, seq), Envelope { src: id, dst, msg });
+                },
+                Command::Broadcast(dsts, msg) => {
+                    for dst in dsts {
+                        let time = self.now + self.choose(self.latency.clone());
+                        let seq = self.next_seq();
+                        self.network.insert((time, seq), Envelope { src: id, dst, msg: msg.clone() });
+                    }
+                },
+                Command::SetTimer(range) => {
+                    let time = self.now + self.choose(range);
+                    let seq = self.next_seq();
+                    self.timers[index] = Some((time, seq));
+                },
+                Command::CancelTimer => {
+                    self.timers[index] = None;
+                },
+                Command::Spawn(spec) => {
+                    let child = match self.actors[index].spawned_actor(&spec) {
+                        None => continue,
+                        Some(child) => child,
+                    };
+                    let mut child_index = self.ids.len();
+                    while self.index(Id::from(child_index)).is_some() { child_index += 1 }
+                    let child_id = Id::from(child_index);
+                    let mut out = Out::new();
+                    let child_state = child.on_start(child_id, &mut out);
+                    self.ids.push(child_id);
+                    self.actors.push(child);
+                    self.states.push(child_state);
+                    self.timers.push(None);
+                    self.crashed.push(false);
+                    self.process_commands(self.ids.len() - 1, out);
+                },
+                Command::ChooseRandom(range) => {
+                    if range.start < range.end {
+                        let value = self.rng.gen_range(range.start, range.end);
+                        self.random_values.push_back((index, value));
+                    }
+                },
+                Command::Monitor(peer) => {
+                    if self.is_crashed(peer) {
+                        self.notify_peer_down(id, peer);
+                    } else if !self.monitors.contains(&(id, peer)) {
+                        self.monitors.push((id, peer));
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actor::actor_test_util::ping_pong::{PingPongActor, PingPongCount, PingPongMsg::*};
+
+    /// Clients each send their ID to a server, which records the order of arrival.
+    struct RaceActor;
+    impl Actor for RaceActor {
+        type Msg = Id;
+        type State = Vec<Id>;
+        fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+            if id != Id::from(0) { o.send(Id::from(0), id); }
+            Vec::new()
+        }
+        fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+            state.to_mut().push(msg);
+        }
+    }
+
+    fn race(seed: u64) -> (Vec<SystemAction<Id>>, Vec<Id>) {
+        let mut sim = SimRuntime::new(seed, (0..4).map(|i| (Id::from(i), RaceActor)).collect());
+        let mut trace = Vec::new();
+        while let Some(action) = sim.step() {
+            trace.push(action);
+        }
+        (trace, sim.state(Id::from(0)).unwrap().clone())
+    }
+
+    #[test]
+    fn is_deterministic_per_seed() {
+        assert_eq!(race(7), race(7));
+
+        let mut orders: Vec<_> = (0..20).map(|seed| race(seed).1).collect();
+        orders.sort();
+        orders.dedup();
+        assert!(orders.len() > 1);
+    }
+
+    #[test]
+    fn advances_virtual_clock() {
+        let mut sim = SimRuntime::new(0, vec![
+            (Id::from(0), PingPongActor::PingActor { pong_id: Id::from(1) }),
+            (Id::from(1), PingPongActor::PongActor),
+        ]).latency(Duration::from_millis(10)..Duration::from_millis(10));
+        assert_eq!(sim.run_for(Duration::from_millis(35)), 3);
+        assert_eq!(sim.now(), Duration::from_millis(35));
+        assert_eq!(sim.state(Id::from(0)), Some(&PingPongCount(1)));
+        assert_eq!(sim.state(Id::from(1)), Some(&PingPongCount(2)));
+        assert_eq!(sim.step(), Some(SystemAction::Deliver {
+            src: Id::from(1), dst: Id::from(0), msg: Pong(1),
+        }));
+        assert_eq!(sim.now(), Duration::from_millis(40));
+    }
+
+    #[test]
+    fn chooses_random_values_per_seed() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = ();
+            type State = Vec<u64>;
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.choose_random(0..100);
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_random(&self, _: Id, state: &mut Cow<Self::State>, value: u64, o: &mut Out<Self>) {
+                if state.len() < 2 { o.choose_random(0..100); }
+                state.to_mut().push(value);
+            }
+        }
+
+        let chosen = |seed| {
+            let mut sim = SimRuntime::new(seed, vec![(Id::from(0), TestActor)]);
+            assert_eq!(sim.run_for(Duration::from_secs(1)), 3);
+            sim.state(Id::from(0)).unwrap().clone()
+        };
+        assert_eq!(chosen(3), chosen(3));
+        assert!(chosen(3).iter().all(|value| *value < 100));
+        assert_ne!(chosen(3), chosen(4));
+    }
+
+    #[test]
+    fn notifies_monitors_of_crashes() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type Msg = ();
+            type State = Vec<Id>;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) { o.monitor(Id::from(1)); }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_peer_down(&self, _: Id, state: &mut Cow<Self::State>, peer: Id, _: &mut Out<Self>) {
+                state.to_mut().push(peer);
+            }
+        }
+
+        let mut sim = SimRuntime::new(0, vec![(Id::from(0), TestActor), (Id::from(1), TestActor)]);
+        sim.crash(Id::from(1));
+        assert!(sim.is_crashed(Id::from(1)));
+        assert_eq!(sim.state(Id::from(0)), Some(&vec![Id::from(1)]));
+        sim.crash(Id::from(1)); // no effect
+        assert_eq!(sim.state(Id::from(0)), Some(&vec![Id::from(1)]));
+    }
+}
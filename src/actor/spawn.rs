@@ -1,8 +1,13 @@
 //! Private module for selective re-export.
 
 use crate::actor::*;
-use std::fmt::Debug;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Debug, Display, Formatter};
+use std::io::Write;
 use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::ops::Range;
+use std::sync::{Arc, mpsc};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -37,7 +42,92 @@ fn practically_never() -> Instant {
     Instant::now() + Duration::from_secs(3600 * 24 * 365 * 500)
 }
 
-/// Runs an actor, sending messages over UDP.
+/// Indicates a fault encountered while running spawned actors. Each variant indicates the `id`
+/// of the actor that encountered the fault.
+#[derive(Debug)]
+pub enum SpawnError<Msg, E> {
+    /// Unable to bind the actor's socket.
+    Bind { id: Id, source: std::io::Error },
+    /// Unable to read from the actor's socket.
+    Recv { id: Id, source: std::io::Error },
+    /// Received a datagram from an address that cannot be represented as an [`Id`].
+    UnsupportedSource { id: Id, src: SocketAddr },
+    /// Received a datagram that is not a valid batch of messages. See [`SpawnOptions::batch`].
+    Frame { id: Id, src: SocketAddr, bytes: Vec<u8> },
+    /// Unable to deserialize a received datagram (or configuration update if `src` is `None`).
+    Deserialize { id: Id, src: Option<SocketAddr>, bytes: Vec<u8>, err: E },
+    /// Unable to serialize an outgoing message (or configuration update if `dst` is `id`).
+    Serialize { id: Id, dst: Id, msg: Msg, err: E },
+    /// Unable to send a datagram.
+    Send { id: Id, dst: Id, source: std::io::Error },
+    /// Unable to send a serialized message of `len` bytes because it exceeds the `max` that can be
+    /// sent. See [`SpawnConfig::max_datagram_len`].
+    TooLarge { id: Id, dst: Id, len: usize, max: usize },
+    /// Unable to deliver a configuration update because the actor is no longer running.
+    Stopped { id: Id },
+    /// Unable to record an event. See [`SpawnOptions::record`].
+    Record { id: Id, source: std::io::Error },
+}
+
+impl<Msg: Debug, E: Debug> Display for SpawnError<Msg, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::Bind { id, source } =>
+                write!(f, "Unable to bind socket. id={}, err={}", id, source),
+            SpawnError::Recv { id, source } =>
+                write!(f, "Unable to read socket. id={}, err={}", id, source),
+            SpawnError::UnsupportedSource { id, src } =>
+                write!(f, "Received non-IPv4 message. id={}, src={}", id, src),
+            SpawnError::Frame { id, src, bytes } =>
+                write!(f, "Unable to parse batch. id={}, src={}, buf={:?}", id, src, bytes),
+            SpawnError::Deserialize { id, src: Some(src), bytes, err } =>
+                write!(f, "Unable to parse message. id={}, src={}, buf={:?}, err={:?}", id, src, bytes, err),
+            SpawnError::Deserialize { id, src: None, bytes, err } =>
+                write!(f, "Unable to parse config. id={}, buf={:?}, err={:?}", id, bytes, err),
+            SpawnError::Serialize { id, dst, msg, err } =>
+                write!(f, "Unable to serialize. src={}, dst={}, msg={:?}, err={:?}", id, dst, msg, err),
+            SpawnError::Send { id, dst, source } =>
+                write!(f, "Unable to send. src={}, dst={}, err={}", id, dst, source),
+            SpawnError::TooLarge { id, dst, len, max } =>
+                write!(f, "Message is too large to send. src={}, dst={}, len={}, max={}", id, dst, len, max),
+            SpawnError::Stopped { id } =>
+                write!(f, "Actor is no longer running. id={}", id),
+            SpawnError::Record { id, source } =>
+                write!(f, "Unable to record event. id={}, err={}", id, source),
+        }
+    }
+}
+
+impl<Msg: Debug, E: Debug> std::error::Error for SpawnError<Msg, E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SpawnError::Bind { source, .. }
+            | SpawnError::Recv { source, .. }
+            | SpawnError::Send { source, .. }
+            | SpawnError::Record { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// The default error callback, which logs and otherwise ignores errors.
+pub(crate) fn log_error<Msg: Debug, E: Debug>(error: SpawnError<Msg, E>) {
+    match error {
+        SpawnError::UnsupportedSource { .. } | SpawnError::Frame { .. } | SpawnError::Deserialize { .. } =>
+            log::debug!("{}. Ignoring.", error),
+        _ =>
+            log::warn!("{}. Ignoring.", error),
+    }
+}
+
+/// Runs an actor, sending messages over UDP. Panics if unable to bind any actor's socket; use
+/// [`try_spawn`] to handle that error instead. Errors encountered while running are logged; use
+/// [`spawn_with`] and [`SpawnOptions::on_error`] to handle them instead.
+///
+/// With the `tracing` feature enabled, each actor's thread runs within an `actor` span whose `id`
+/// field is the actor's address. Each handler invocation is a `handler` span, which records the
+/// handler's latency, and receiving a message, sending a message, and firing a timer emit
+/// events at the debug level.
 ///
 /// # Example
 ///
@@ -69,62 +159,481 @@ where
     A::Msg: Debug,
     A::State: Debug,
 {
-    let mut handles = Vec::with_capacity(actors.len());
+    try_spawn(serialize, deserialize, actors).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Runs an actor like [`spawn`], but returns an error rather than panicking if unable to bind
+/// any actor's socket, in which case no actors are started.
+pub fn try_spawn<A, E: Debug + 'static>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>) -> Result<Vec<thread::JoinHandle<()>>, SpawnError<A::Msg, E>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug,
+    A::State: Debug,
+{
+    spawn_with(serialize, deserialize, SpawnOptions::default(), actors)
+        .map(|(handles, _updaters)| handles)
+}
+
+/// Delivers configuration updates to an actor started by [`spawn_with`], which in turn
+/// calls [`Actor::on_config_update`]. See also [`System::config_updates`], which mirrors this
+/// facility when model checking.
+pub struct ConfigUpdater<Msg, E> {
+    id: Id,
+    sender: mpsc::Sender<Signal>,
+    serialize: fn(&Msg) -> Result<Vec<u8>, E>,
+}
+
+impl<Msg: Clone, E: Debug> ConfigUpdater<Msg, E> {
+    /// The ID of the actor to which this updater delivers configuration.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Delivers a configuration update to the actor. Updates are applied in the order sent.
+    pub fn update(&self, config: &Msg) -> Result<(), SpawnError<Msg, E>> {
+        let id = self.id;
+        let bytes = (self.serialize)(config).map_err(|err| {
+            SpawnError::Serialize { id, dst: id, msg: config.clone(), err }
+        })?;
+        self.sender.send(Signal::Config(bytes)).map_err(|_| SpawnError::Stopped { id })
+    }
+}
+
+/// Input for a spawned actor that does not arrive via its socket. Actors poll for signals at
+/// least every [`SIGNAL_POLL_INTERVAL`].
+enum Signal {
+    /// A serialized configuration update from a [`ConfigUpdater`].
+    Config(Vec<u8>),
+    /// A monitored peer is down. See [`Actor::on_peer_down`].
+    PeerDown(Id),
+}
+
+/// State shared by actors started together, including the actors they spawn.
+struct Runtime<Msg, E> {
+    serialize: fn(&Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<Msg, E>,
+    on_error: Box<dyn Fn(SpawnError<Msg, E>) + Send + Sync>,
+    /// Delivers [`Signal`]s to each running actor.
+    signals: Mutex<HashMap<Id, mpsc::Sender<Signal>>>,
+    monitors: Mutex<Monitors>,
+    /// Receives [`RecordedEvent`]s. See [`SpawnOptions::record`].
+    recorder: Option<Mutex<Box<dyn Write + Send>>>,
+    /// Indicates how to batch messages. See [`SpawnOptions::batch`].
+    batching: Option<SpawnConfig>,
+    /// Decides the fate of each message. See [`SpawnOptions::intercept`].
+    interceptor: Option<Box<dyn NetworkInterceptor>>,
+}
+
+/// Tracks the watchers of each monitored actor, and which actors have stopped.
+#[derive(Default)]
+struct Monitors {
+    watchers: HashMap<Id, Vec<Id>>,
+    stopped: HashSet<Id>,
+}
+
+impl<Msg, E> Runtime<Msg, E> {
+    /// Records an event processed by an actor, if recording.
+    fn record(&self, id: Id, event: RecordedEvent) {
+        if let Some(recorder) = &self.recorder {
+            let mut recorder = recorder.lock();
+            let result = serde_json::to_writer(&mut *recorder, &event)
+                .map_err(std::io::Error::from)
+                .and_then(|()| recorder.write_all(b"\n"))
+                .and_then(|()| recorder.flush()); // so that the log survives a crash
+            if let Err(source) = result {
+                drop(recorder);
+                (self.on_error)(SpawnError::Record { id, source });
+            }
+        }
+    }
+
+    /// Delivers a signal to a running actor. Signals for stopped actors are dropped.
+    fn signal(&self, dst: Id, signal: Signal) {
+        if let Some(sender) = self.signals.lock().get(&dst) {
+            let _ = sender.send(signal);
+        }
+    }
+
+    /// Records that a watcher monitors a peer, notifying the watcher immediately if the peer
+    /// already stopped.
+    fn monitor(&self, watcher: Id, peer: Id) {
+        let mut monitors = self.monitors.lock();
+        if monitors.stopped.contains(&peer) {
+            drop(monitors);
+            self.signal(watcher, Signal::PeerDown(peer));
+        } else {
+            monitors.watchers.entry(peer).or_default().push(watcher);
+        }
+    }
+
+    /// Notifies the watchers of an actor that stopped.
+    fn on_stop(&self, id: Id) {
+        log::info!("Actor stopped. id={}", id);
+        self.signals.lock().remove(&id);
+        let watchers = {
+            let mut monitors = self.monitors.lock();
+            monitors.stopped.insert(id);
+            monitors.watchers.remove(&id).unwrap_or_default()
+        };
+        for watcher in watchers {
+            self.signal(watcher, Signal::PeerDown(id));
+        }
+    }
+}
+
+/// Calls [`Runtime::on_stop`] when an actor's thread stops, including due to a panic.
+struct StopGuard<Msg, E> {
+    id: Id,
+    runtime: Arc<Runtime<Msg, E>>,
+}
+
+impl<Msg, E> Drop for StopGuard<Msg, E> {
+    fn drop(&mut self) {
+        self.runtime.on_stop(self.id);
+    }
+}
+
+/// Configures optional behavior of actors started via [`spawn_with`]. Options can be combined,
+/// for instance to record the events of actors whose messages are batched.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{ChaosNetwork, SpawnConfig, SpawnOptions};
+/// use std::fs::File;
+/// # let _: SpawnOptions<(), ()> =
+/// SpawnOptions::default()
+///     .on_error(|err| eprintln!("{}", err))
+///     .record(File::create("actors.log").unwrap())
+///     .batch(SpawnConfig::default())
+///     .intercept(ChaosNetwork::new(42).lossy(0.1));
+/// ```
+pub struct SpawnOptions<Msg, E> {
+    on_error: Box<dyn Fn(SpawnError<Msg, E>) + Send + Sync>,
+    recorder: Option<Box<dyn Write + Send>>,
+    batching: Option<SpawnConfig>,
+    interceptor: Option<Box<dyn NetworkInterceptor>>,
+}
+
+impl<Msg: Debug + 'static, E: Debug + 'static> Default for SpawnOptions<Msg, E> {
+    fn default() -> Self {
+        SpawnOptions {
+            on_error: Box::new(log_error),
+            recorder: None,
+            batching: None,
+            interceptor: None,
+        }
+    }
+}
+
+impl<Msg, E> SpawnOptions<Msg, E> {
+    /// Passes errors encountered while running to `on_error`, rather than logging them.
+    pub fn on_error(mut self, on_error: impl Fn(SpawnError<Msg, E>) + Send + Sync + 'static) -> Self {
+        self.on_error = Box::new(on_error);
+        self
+    }
+
+    /// Writes each event that an actor processes to `log` as a line of JSON (see
+    /// [`RecordedEvent`]), so that the events can later be [`replay`]ed. Events are written in the
+    /// order processed, and the log is flushed after each event.
+    pub fn record(mut self, log: impl Write + Send + 'static) -> Self {
+        self.recorder = Some(Box::new(log));
+        self
+    }
+
+    /// Coalesces messages to the same peer into a single datagram per [`SpawnConfig`], which
+    /// reduces the number of syscalls under load. Each message in a datagram is preceded by its
+    /// length as a 4 byte big-endian integer, so actors started this way can only exchange
+    /// messages with other actors that batch.
+    ///
+    /// Whereas actors that do not batch report messages that do not fit in a datagram as a
+    /// [`SpawnError::TooLarge`], batching actors fragment them per
+    /// [`SpawnConfig::max_datagram_len`].
+    pub fn batch(mut self, config: SpawnConfig) -> Self {
+        self.batching = Some(config);
+        self
+    }
+
+    /// Subjects each message that actors send to a [`NetworkInterceptor`], which can drop,
+    /// duplicate, delay, or reorder messages. This allows checking that a deployed system
+    /// tolerates the same network faults as its model.
+    pub fn intercept(mut self, interceptor: impl NetworkInterceptor + 'static) -> Self {
+        self.interceptor = Some(Box::new(interceptor));
+        self
+    }
+}
+
+/// Configures how actors started with [`SpawnOptions::batch`] coalesce and fragment outgoing
+/// messages.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SpawnConfig {
+    /// The maximum number of messages sent to a peer in one datagram.
+    pub max_batch: usize,
+    /// The maximum time that a message waits for more messages to the same peer. Messages sent by
+    /// one handler invocation are coalesced even if this is zero.
+    pub max_delay: Duration,
+    /// The largest datagram to send. Larger messages are split into fragments that the recipient
+    /// reassembles, in which case losing any fragment loses the message. Defaults to the largest
+    /// UDP payload over IPv4, although a smaller size such as 1472 bytes avoids IP fragmentation
+    /// on typical Ethernet networks.
+    pub max_datagram_len: usize,
+}
 
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        SpawnConfig {
+            max_batch: 64,
+            max_delay: Duration::from_secs(0),
+            max_datagram_len: MAX_DATAGRAM_LEN,
+        }
+    }
+}
+
+/// Runs actors like [`spawn`] per [`SpawnOptions`], additionally returning a [`ConfigUpdater`]
+/// per actor (in the same order as the actors) for delivering configuration at runtime. Returns
+/// an error if unable to bind any actor's socket, in which case no actors are started.
+///
+/// Actors started together (including the actors they spawn) can monitor each other via
+/// [`Out::monitor`], in which case they are notified via [`Actor::on_peer_down`] once a monitored
+/// actor's thread stops, such as due to a panic. Monitoring any other actor has no effect.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, SpawnConfig, SpawnOptions, spawn_with};
+/// use std::fs::File;
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// use std::time::Duration;
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor1 = ();
+/// # let actor2 = ();
+/// # let new_config = ();
+/// let id1 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let id2 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+/// let (handles, updaters) = spawn_with(
+///     serde_json::to_vec,
+///     |bytes| serde_json::from_slice(bytes),
+///     SpawnOptions::default()
+///         .on_error(|err| eprintln!("{}", err))
+///         .record(File::create("actors.log").unwrap())
+///         .batch(SpawnConfig { max_batch: 32, max_delay: Duration::from_millis(1), ..Default::default() }),
+///     vec![
+///         (id1, actor1),
+///         (id2, actor2),
+///     ]).unwrap();
+/// updaters[0].update(&new_config).unwrap();
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn spawn_with<A, E: Debug + 'static>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    options: SpawnOptions<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>)
+    -> Result<(Vec<thread::JoinHandle<()>>, Vec<ConfigUpdater<A::Msg, E>>), SpawnError<A::Msg, E>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug,
+    A::State: Debug,
+{
+    // Bind every socket before starting any actor so that failures are reported to the caller.
+    let mut bound = Vec::with_capacity(actors.len());
     for (id, actor) in actors {
-    let id = id.into();
+        let id = id.into();
+        let socket = UdpSocket::bind(SocketAddrV4::from(id))
+            .map_err(|source| SpawnError::Bind { id, source })?;
+        bound.push((id, actor, socket));
+    }
+
+    let runtime = Arc::new(Runtime {
+        serialize,
+        deserialize,
+        on_error: options.on_error,
+        signals: Mutex::new(HashMap::with_capacity(bound.len())),
+        monitors: Mutex::new(Monitors::default()),
+        recorder: options.recorder.map(Mutex::new),
+        batching: options.batching,
+        interceptor: options.interceptor,
+    });
+    let mut handles = Vec::with_capacity(bound.len());
+    let mut updaters = Vec::with_capacity(bound.len());
+    for (id, actor, socket) in bound {
+        let (sender, signals) = mpsc::channel();
+        runtime.signals.lock().insert(id, sender.clone());
+        updaters.push(ConfigUpdater { id, sender, serialize });
+        handles.push(run(id, None, actor, socket, signals, Arc::clone(&runtime)));
+    }
+
+    Ok((handles, updaters))
+}
+
+/// Runs an actor on a new thread using a bound socket. `parent` indicates the actor that spawned
+/// it, if any.
+fn run<A, E: Debug + 'static>(
+    id: Id,
+    parent: Option<Id>,
+    actor: A,
+    socket: UdpSocket,
+    signals: mpsc::Receiver<Signal>,
+    runtime: Arc<Runtime<A::Msg, E>>) -> thread::JoinHandle<()>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug,
+    A::State: Debug,
+{
     let addr = SocketAddrV4::from(id);
 
     // note that panics are returned as `Err` when `join`ing
-    handles.push(thread::spawn(move || {
-        let socket = UdpSocket::bind(addr).unwrap(); // panic if unable to bind
+    thread::spawn(move || {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("actor", id = %addr).entered();
+        let _guard = StopGuard { id, runtime: Arc::clone(&runtime) };
+        let on_error = &runtime.on_error;
         let mut in_buf = [0; 65_535];
         let mut next_interrupt = practically_never();
+        let mut random_values = VecDeque::new();
+        let mut outbox = Outbox::new(&socket, runtime.batching, runtime.interceptor.as_deref());
+        let mut reassembler = Reassembler::default();
 
         let mut out = Out::new();
-        let mut state = Cow::Owned(actor.on_start(id, &mut out));
+        runtime.record(id, RecordedEvent::Start { id, parent });
+        let mut state = Cow::Owned(handle("on_start", || actor.on_start(id, &mut out)));
         log::info!("Actor started. id={}, state={:?}, out={:?}", addr, state, out);
         for c in out {
-            on_command::<A, E>(addr, c, serialize, &socket, &mut next_interrupt);
+            on_command(id, &actor, c, &mut outbox, &mut next_interrupt, &mut random_values, &runtime);
         }
 
         loop {
-            // Apply an interrupt if present, otherwise wait for a message.
+            // Send batches and delayed messages that are due, so that they cannot delay the
+            // interrupt.
+            let now = Instant::now();
+            outbox.flush_due(id, now, on_error);
+
+            // Apply a random value or interrupt if present, otherwise wait for a message.
             let mut out = Out::new();
-            if let Some(max_wait) = next_interrupt.checked_duration_since(Instant::now()) {
-                socket.set_read_timeout(Some(max_wait)).expect("set_read_timeout failed");
+            if let Some(value) = random_values.pop_front() {
+                log::info!("Chose random value. id={}, value={}", addr, value);
+                runtime.record(id, RecordedEvent::ChooseRandom { id, value });
+                handle("on_random", || actor.on_random(id, &mut state, value, &mut out));
+                if !is_no_op(&state, &out) {
+                    log::debug!("Acted. id={}, state={:?}, out={:?}",
+                                addr, state, out);
+                }
+                for c in out {
+                    on_command(id, &actor, c, &mut outbox, &mut next_interrupt, &mut random_values, &runtime);
+                }
+                continue;
+            }
+            // Apply pending signals before waiting, as they do not arrive via the socket.
+            let pending: Vec<Signal> = signals.try_iter().collect();
+            let wake_at = outbox.next_deadline().map_or(next_interrupt, |d| d.min(next_interrupt));
+            let max_wait = wake_at.checked_duration_since(now)
+                .filter(|max_wait| *max_wait > Duration::from_secs(0)); // zero is an invalid timeout
+            if !pending.is_empty() {
+                for signal in pending {
+                    match signal {
+                        Signal::Config(bytes) => match (runtime.deserialize)(&bytes) {
+                            Ok(config) => {
+                                log::info!("Received config. id={}, config={:?}", addr, config);
+                                runtime.record(id, RecordedEvent::UpdateConfig { id, bytes });
+                                handle("on_config_update", || {
+                                    actor.on_config_update(id, &mut state, config, &mut out)
+                                });
+                            },
+                            Err(err) => {
+                                on_error(SpawnError::Deserialize { id, src: None, bytes, err });
+                            }
+                        },
+                        Signal::PeerDown(peer) => {
+                            log::info!("Peer down. id={}, peer={}", addr, peer);
+                            runtime.record(id, RecordedEvent::PeerDown { id, peer });
+                            handle("on_peer_down", || {
+                                actor.on_peer_down(id, &mut state, peer, &mut out)
+                            });
+                        },
+                    }
+                }
+            } else if let Some(max_wait) = max_wait {
+                // Bound the wait so that signals sent meanwhile are applied promptly.
+                let max_wait = max_wait.min(SIGNAL_POLL_INTERVAL);
+                if let Err(source) = socket.set_read_timeout(Some(max_wait)) {
+                    // Back off rather than retrying immediately, as the error likely persists.
+                    on_error(SpawnError::Recv { id, source });
+                    thread::sleep(max_wait.min(RECV_RETRY_DELAY));
+                    continue;
+                }
                 match socket.recv_from(&mut in_buf) {
-                    Err(e) => {
-                        // Timeout (`WouldBlock`) ignored since next iteration will apply interrupt.
-                        if e.kind() != std::io::ErrorKind::WouldBlock {
-                            log::warn!("Unable to read socket. Ignoring. id={}, err={:?}", addr, e);
+                    Err(source) => {
+                        // Timeout (`WouldBlock`) ignored since next iteration will apply interrupt
+                        // or signals.
+                        if source.kind() != std::io::ErrorKind::WouldBlock {
+                            on_error(SpawnError::Recv { id, source });
                         }
                         continue;
                     },
                     Ok((count, src_addr)) => {
-                        match deserialize(&in_buf[..count]) {
-                            Ok(msg) => {
-                                if let SocketAddr::V4(src_addr) = src_addr {
-                                    log::info!("Received message. id={}, src={}, msg={}",
-                                                addr, src_addr, format!("{:?}", msg));
-                                    actor.on_msg(id, &mut state, Id::from(src_addr), msg, &mut out);
-                                } else {
-                                    log::debug!("Received non-IPv4 message. Ignoring. id={}, src={}, msg={}",
-                                               addr, src_addr, format!("{:?}", msg));
+                        let datagram = &in_buf[..count];
+                        let entries = match runtime.batching {
+                            None => vec![BatchEntry::Message(datagram)],
+                            Some(_) => match decode_batch(datagram) {
+                                Some(entries) => entries,
+                                None => {
+                                    let bytes = datagram.to_vec();
+                                    on_error(SpawnError::Frame { id, src: src_addr, bytes });
                                     continue;
-                                }
+                                },
                             },
-                            Err(e) => {
-                                log::debug!("Unable to parse message. Ignoring. id={}, src={}, buf={:?}, err={:?}",
-                                           addr, src_addr, &in_buf[..count], e);
-                                continue;
+                        };
+                        for entry in entries {
+                            let bytes = match entry {
+                                BatchEntry::Message(bytes) => Cow::Borrowed(bytes),
+                                BatchEntry::Fragment { seq, index, count, chunk } => {
+                                    match reassembler.add(src_addr, seq, index, count, chunk) {
+                                        Some(bytes) => Cow::Owned(bytes),
+                                        None => continue, // awaiting other fragments
+                                    }
+                                },
+                            };
+                            let bytes = &*bytes;
+                            match (runtime.deserialize)(bytes) {
+                                Ok(msg) => {
+                                    if let SocketAddr::V4(src_addr) = src_addr {
+                                        log::info!("Received message. id={}, src={}, msg={:?}",
+                                                    addr, src_addr, msg);
+                                        #[cfg(feature = "tracing")]
+                                        tracing::debug!(src = %src_addr, len = bytes.len(), msg = ?msg,
+                                                        "received message");
+                                        runtime.record(id, RecordedEvent::Deliver {
+                                            src: Id::from(src_addr),
+                                            dst: id,
+                                            bytes: bytes.to_vec(),
+                                        });
+                                        handle("on_msg", || {
+                                            actor.on_msg(id, &mut state, Id::from(src_addr), msg, &mut out)
+                                        });
+                                    } else {
+                                        on_error(SpawnError::UnsupportedSource { id, src: src_addr });
+                                    }
+                                },
+                                Err(err) => {
+                                    let bytes = bytes.to_vec();
+                                    on_error(SpawnError::Deserialize { id, src: Some(src_addr), bytes, err });
+                                }
                             }
                         }
                     },
                 }
             } else {
                 next_interrupt = practically_never(); // timer is no longer valid
-                actor.on_timeout(id, &mut state, &mut out);
+                runtime.record(id, RecordedEvent::Timeout { id });
+                #[cfg(feature = "tracing")]
+                tracing::debug!("timer fired");
+                handle("on_timeout", || actor.on_timeout(id, &mut state, &mut out));
             };
 
             // Handle commands and update state.
@@ -132,61 +641,381 @@ where
                 log::debug!("Acted. id={}, state={:?}, out={:?}",
                             addr, state, out);
             }
-            for c in out { on_command::<A, E>(addr, c, serialize, &socket, &mut next_interrupt); }
+            for c in out {
+                on_command(id, &actor, c, &mut outbox, &mut next_interrupt, &mut random_values, &runtime);
+            }
         }
-    }));
-    }
-
-    handles
+    })
 }
 
 /// The effect to perform in response to spawned actor outputs.
 fn on_command<A, E>(
-    addr: SocketAddrV4,
+    id: Id,
+    actor: &A,
     command: Command<A::Msg>,
-    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
-    socket: &UdpSocket,
-    next_interrupt: &mut Instant)
-where A: Actor,
+    outbox: &mut Outbox,
+    next_interrupt: &mut Instant,
+    random_values: &mut VecDeque<u64>,
+    runtime: &Arc<Runtime<A::Msg, E>>)
+where A: 'static + Send + Actor,
       A::Msg: Debug,
-      E: Debug,
+      A::State: Debug,
+      E: Debug + 'static,
 {
+    let on_error = &runtime.on_error;
     match command {
         Command::Send(dst, msg) => {
-            let dst_addr = SocketAddrV4::from(dst);
-            match serialize(&msg) {
-                Err(e) => {
-                    log::warn!("Unable to serialize. Ignoring. src={}, dst={}, msg={:?}, err={:?}",
-                             addr, dst_addr, msg, e);
+            match (runtime.serialize)(&msg) {
+                Err(err) => {
+                    on_error(SpawnError::Serialize { id, dst, msg, err });
                 },
                 Ok(out_buf) => {
-                    if let Err(e) = socket.send_to(&out_buf, dst_addr) {
-                        log::warn!("Unable to send. Ignoring. src={}, dst={}, msg={:?}, err={:?}",
-                                 addr, dst_addr, msg, e);
-                    }
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(dst = %SocketAddrV4::from(dst), len = out_buf.len(), msg = ?msg,
+                                    "sent message");
+                    outbox.send(id, dst, &out_buf, on_error);
                 },
             }
         },
+        Command::Broadcast(dsts, msg) => {
+            for dst in dsts {
+                on_command(id, actor, Command::Send(dst, msg.clone()), outbox, next_interrupt, random_values, runtime);
+            }
+        },
         Command::SetTimer(range) => {
-            let duration =
-                if range.start < range.end {
-                    use rand::Rng;
-                    rand::thread_rng().gen_range(range.start, range.end)
-                } else {
-                    range.start
-                };
-            *next_interrupt = Instant::now() + duration;
+            *next_interrupt = Instant::now() + timer_duration(range);
         },
         Command::CancelTimer => {
             *next_interrupt = practically_never();
         },
+        Command::Spawn(spec) => {
+            let child = match actor.spawned_actor(&spec) {
+                None => return,
+                Some(child) => child,
+            };
+
+            // The child listens on an ephemeral port at the parent's IP, which determines its ID.
+            let ip = *SocketAddrV4::from(id).ip();
+            let bound = UdpSocket::bind(SocketAddrV4::new(ip, 0))
+                .and_then(|socket| Ok((socket.local_addr()?, socket)));
+            match bound {
+                Ok((SocketAddr::V4(child_addr), child_socket)) => {
+                    log::info!("Spawning actor. id={}, parent={}, spec={:?}", child_addr, id, spec);
+
+                    // The child has no `ConfigUpdater`, and its thread is detached.
+                    let child_id = Id::from(child_addr);
+                    let (sender, signals) = mpsc::channel();
+                    runtime.signals.lock().insert(child_id, sender);
+                    run(child_id, Some(id), child, child_socket, signals, Arc::clone(runtime));
+                },
+                Ok((child_addr, _)) => {
+                    on_error(SpawnError::UnsupportedSource { id, src: child_addr });
+                },
+                Err(source) => {
+                    on_error(SpawnError::Bind { id, source });
+                },
+            }
+        },
+        Command::ChooseRandom(range) => {
+            if range.start < range.end {
+                use rand::Rng;
+                random_values.push_back(rand::thread_rng().gen_range(range.start, range.end));
+            }
+        },
+        Command::Monitor(peer) => {
+            runtime.monitor(id, peer);
+        },
+    }
+}
+
+/// The largest UDP payload over IPv4.
+pub(crate) const MAX_DATAGRAM_LEN: usize = 65_507;
+
+/// The longest that an actor waits on its socket before checking for [`Signal`]s.
+const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long an actor waits before retrying after failing to configure its socket for a read.
+const RECV_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+/// The length of the header preceding each entry in a batch.
+const BATCH_HEADER_LEN: usize = 4;
+
+/// Set in an entry's header if the entry is a fragment of a message rather than a whole message.
+const FRAGMENT_FLAG: u32 = 1 << 31;
+
+/// The length of the header that follows the batch header of a fragment: the sender's sequence
+/// number for the fragmented message, and the fragment's index and count.
+const FRAGMENT_HEADER_LEN: usize = 8;
+
+/// The maximum number of partially received messages retained per actor.
+const MAX_INCOMPLETE: usize = 64;
+
+/// Sends an actor's messages, coalescing them into batches per [`SpawnOptions::batch`] and
+/// subjecting them to a [`NetworkInterceptor`] per [`SpawnOptions::intercept`].
+struct Outbox<'a> {
+    socket: &'a UdpSocket,
+    config: Option<SpawnConfig>,
+    batches: HashMap<Id, Batch>,
+    interceptor: Option<&'a dyn NetworkInterceptor>,
+    /// Messages held back by the interceptor, along with when to send them.
+    delayed: Vec<(Instant, Id, Vec<u8>)>,
+    /// The sequence number of the next fragmented message.
+    next_seq: u32,
+}
+
+/// Messages awaiting a send to one peer.
+struct Batch {
+    deadline: Instant,
+    count: usize,
+    datagram: Vec<u8>,
+}
+
+impl<'a> Outbox<'a> {
+    fn new(
+        socket: &'a UdpSocket,
+        config: Option<SpawnConfig>,
+        interceptor: Option<&'a dyn NetworkInterceptor>) -> Self
+    {
+        Outbox {
+            socket,
+            config,
+            batches: HashMap::new(),
+            interceptor,
+            delayed: Vec::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Sends a serialized message, unless the interceptor drops or delays it.
+    fn send<Msg, E>(&mut self, id: Id, dst: Id, bytes: &[u8], on_error: &dyn Fn(SpawnError<Msg, E>)) {
+        let interceptor = match self.interceptor {
+            None => return self.enqueue(id, dst, bytes, on_error),
+            Some(interceptor) => interceptor,
+        };
+        let delays = interceptor.on_send(id, dst, bytes);
+        if delays.is_empty() {
+            log::debug!("Dropped message. src={}, dst={}", id, dst);
+        }
+        for delay in delays {
+            if delay == Duration::from_secs(0) {
+                self.enqueue(id, dst, bytes, on_error);
+            } else {
+                self.delayed.push((Instant::now() + delay, dst, bytes.to_vec()));
+            }
+        }
+    }
+
+    /// Sends a serialized message, or adds it to the peer's batch if batching. The batch is sent
+    /// once full.
+    fn enqueue<Msg, E>(&mut self, id: Id, dst: Id, bytes: &[u8], on_error: &dyn Fn(SpawnError<Msg, E>)) {
+        let config = match self.config {
+            None if MAX_DATAGRAM_LEN < bytes.len() => {
+                let (len, max) = (bytes.len(), MAX_DATAGRAM_LEN);
+                return on_error(SpawnError::TooLarge { id, dst, len, max });
+            },
+            None => return self.send_datagram(id, dst, bytes, on_error),
+            Some(config) => config,
+        };
+        if config.max_datagram_len < BATCH_HEADER_LEN + bytes.len() {
+            // Send earlier messages first, then the fragments.
+            if let Some(batch) = self.batches.remove(&dst) {
+                self.send_datagram(id, dst, &batch.datagram, on_error);
+            }
+            return self.send_fragments(id, dst, bytes, config.max_datagram_len, on_error);
+        }
+        let is_too_large = matches!(self.batches.get(&dst), Some(batch)
+            if config.max_datagram_len < batch.datagram.len() + BATCH_HEADER_LEN + bytes.len());
+        if is_too_large {
+            let batch = self.batches.remove(&dst).expect("batch is pending");
+            self.send_datagram(id, dst, &batch.datagram, on_error);
+        }
+        let batch = self.batches.entry(dst).or_insert_with(|| Batch {
+            deadline: Instant::now() + config.max_delay,
+            count: 0,
+            datagram: Vec::new(),
+        });
+        encode_batched(&mut batch.datagram, bytes);
+        batch.count += 1;
+        if config.max_batch <= batch.count {
+            let batch = self.batches.remove(&dst).expect("batch is pending");
+            self.send_datagram(id, dst, &batch.datagram, on_error);
+        }
+    }
+
+    /// Sends the delayed messages and batches whose deadline has passed.
+    fn flush_due<Msg, E>(&mut self, id: Id, now: Instant, on_error: &dyn Fn(SpawnError<Msg, E>)) {
+        if self.delayed.iter().any(|(deadline, _, _)| *deadline <= now) {
+            let (due, delayed) = std::mem::take(&mut self.delayed).into_iter()
+                .partition::<Vec<_>, _>(|(deadline, _, _)| *deadline <= now);
+            self.delayed = delayed;
+            for (_, dst, bytes) in due {
+                self.enqueue(id, dst, &bytes, on_error);
+            }
+        }
+        let due: Vec<Id> = self.batches.iter()
+            .filter(|(_, batch)| batch.deadline <= now)
+            .map(|(dst, _)| *dst)
+            .collect();
+        for dst in due {
+            let batch = self.batches.remove(&dst).expect("batch is pending");
+            self.send_datagram(id, dst, &batch.datagram, on_error);
+        }
+    }
+
+    /// The earliest deadline of a pending batch or delayed message, if any.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.batches.values().map(|batch| batch.deadline)
+            .chain(self.delayed.iter().map(|(deadline, _, _)| *deadline))
+            .min()
+    }
+
+    /// Splits a message into fragments that each fit in a datagram, and sends them.
+    fn send_fragments<Msg, E>(
+        &mut self,
+        id: Id,
+        dst: Id,
+        bytes: &[u8],
+        max_datagram_len: usize,
+        on_error: &dyn Fn(SpawnError<Msg, E>))
+    {
+        let chunk_len = max_datagram_len.saturating_sub(BATCH_HEADER_LEN + FRAGMENT_HEADER_LEN).max(1);
+        let count = bytes.chunks(chunk_len).len();
+        if usize::from(u16::MAX) < count {
+            let (len, max) = (bytes.len(), chunk_len * usize::from(u16::MAX));
+            return on_error(SpawnError::TooLarge { id, dst, len, max });
+        }
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        for (index, chunk) in bytes.chunks(chunk_len).enumerate() {
+            let mut datagram = Vec::with_capacity(BATCH_HEADER_LEN + FRAGMENT_HEADER_LEN + chunk.len());
+            encode_fragment(&mut datagram, seq, index as u16, count as u16, chunk);
+            self.send_datagram(id, dst, &datagram, on_error);
+        }
+    }
+
+    fn send_datagram<Msg, E>(&self, id: Id, dst: Id, bytes: &[u8], on_error: &dyn Fn(SpawnError<Msg, E>)) {
+        if let Err(source) = self.socket.send_to(bytes, SocketAddrV4::from(dst)) {
+            on_error(SpawnError::Send { id, dst, source });
+        }
+    }
+}
+
+/// An entry of a batch.
+#[derive(Debug, PartialEq)]
+enum BatchEntry<'a> {
+    /// A whole message.
+    Message(&'a [u8]),
+    /// Part of a message that did not fit in a datagram.
+    Fragment { seq: u32, index: u16, count: u16, chunk: &'a [u8] },
+}
+
+/// Appends a message to a batch, preceded by its length.
+fn encode_batched(datagram: &mut Vec<u8>, bytes: &[u8]) {
+    datagram.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    datagram.extend_from_slice(bytes);
+}
+
+/// Appends a fragment of a message to a batch, preceded by its length (with [`FRAGMENT_FLAG`]
+/// set) and the fragment header.
+fn encode_fragment(datagram: &mut Vec<u8>, seq: u32, index: u16, count: u16, chunk: &[u8]) {
+    let len = (FRAGMENT_HEADER_LEN + chunk.len()) as u32;
+    datagram.extend_from_slice(&(len | FRAGMENT_FLAG).to_be_bytes());
+    datagram.extend_from_slice(&seq.to_be_bytes());
+    datagram.extend_from_slice(&index.to_be_bytes());
+    datagram.extend_from_slice(&count.to_be_bytes());
+    datagram.extend_from_slice(chunk);
+}
+
+/// Splits a batch into its entries, or returns `None` if the batch is malformed.
+fn decode_batch(mut datagram: &[u8]) -> Option<Vec<BatchEntry<'_>>> {
+    let mut entries = Vec::new();
+    while !datagram.is_empty() {
+        if datagram.len() < BATCH_HEADER_LEN { return None }
+        let (header, rest) = datagram.split_at(BATCH_HEADER_LEN);
+        let header = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+        let len = (header & !FRAGMENT_FLAG) as usize;
+        if rest.len() < len { return None }
+        let (entry, rest) = rest.split_at(len);
+        if header & FRAGMENT_FLAG == 0 {
+            entries.push(BatchEntry::Message(entry));
+        } else {
+            if entry.len() < FRAGMENT_HEADER_LEN { return None }
+            let (header, chunk) = entry.split_at(FRAGMENT_HEADER_LEN);
+            let seq = u32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+            let index = u16::from_be_bytes([header[4], header[5]]);
+            let count = u16::from_be_bytes([header[6], header[7]]);
+            if count <= index { return None }
+            entries.push(BatchEntry::Fragment { seq, index, count, chunk });
+        }
+        datagram = rest;
+    }
+    Some(entries)
+}
+
+/// Reassembles fragmented messages. Incomplete messages are discarded once too many accumulate,
+/// oldest first, as their remaining fragments may have been lost.
+#[derive(Default)]
+struct Reassembler {
+    /// The fragments received so far for each message, keyed by sender and sequence number.
+    incomplete: HashMap<(SocketAddr, u32), Vec<Option<Vec<u8>>>>,
+    /// The keys of incomplete messages in the order that their first fragment arrived.
+    order: VecDeque<(SocketAddr, u32)>,
+}
+
+impl Reassembler {
+    /// Records a fragment, returning the message if the fragment completes it.
+    fn add(&mut self, src: SocketAddr, seq: u32, index: u16, count: u16, chunk: &[u8]) -> Option<Vec<u8>> {
+        let key = (src, seq);
+        if !self.incomplete.contains_key(&key) {
+            if self.order.len() == MAX_INCOMPLETE {
+                let oldest = self.order.pop_front().expect("message is incomplete");
+                self.incomplete.remove(&oldest);
+            }
+            self.order.push_back(key);
+            self.incomplete.insert(key, vec![None; usize::from(count)]);
+        }
+        let fragments = self.incomplete.get_mut(&key).expect("message is incomplete");
+        if fragments.len() != usize::from(count) { return None } // inconsistent with earlier fragments
+        fragments[usize::from(index)] = Some(chunk.to_vec());
+        if fragments.iter().any(Option::is_none) { return None }
+
+        let fragments = self.incomplete.remove(&key).expect("message is incomplete");
+        self.order.retain(|incomplete| *incomplete != key);
+        Some(fragments.into_iter().flatten().flatten().collect())
+    }
+}
+
+/// Invokes an actor's handler within a `handler` span that records the handler's latency.
+#[cfg(feature = "tracing")]
+fn handle<R>(name: &'static str, handler: impl FnOnce() -> R) -> R {
+    let span = tracing::debug_span!("handler", name, latency_us = tracing::field::Empty);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let result = handler();
+    span.record("latency_us", start.elapsed().as_micros() as u64);
+    result
+}
+
+/// Invokes an actor's handler. Instrumented if the `tracing` feature is enabled.
+#[cfg(not(feature = "tracing"))]
+fn handle<R>(_name: &'static str, handler: impl FnOnce() -> R) -> R {
+    handler()
+}
+
+/// Chooses a duration for a [`Command::SetTimer`].
+pub(crate) fn timer_duration(range: Range<Duration>) -> Duration {
+    if range.start < range.end {
+        use rand::Rng;
+        rand::thread_rng().gen_range(range.start, range.end)
+    } else {
+        range.start
     }
 }
 
 #[cfg(test)]
 mod test {
     use crate::actor::*;
-    use std::net::{SocketAddrV4, Ipv4Addr};
+    use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
 
     #[test]
     fn can_encode_id() {
@@ -203,4 +1032,150 @@ mod test {
             SocketAddrV4::from(Id::from(addr)),
             addr);
     }
+
+    #[test]
+    fn reports_unbindable_sockets() {
+        use crate::actor::actor_test_util::ping_pong::PingPongActor;
+        let taken = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let addr = match taken.local_addr().unwrap() {
+            SocketAddr::V4(addr) => addr,
+            SocketAddr::V6(_) => unreachable!(),
+        };
+        let result = try_spawn(
+            serde_json::to_vec,
+            |bytes| serde_json::from_slice(bytes),
+            vec![(addr, PingPongActor::PongActor)]);
+        assert!(matches!(result, Err(SpawnError::Bind { id, .. }) if id == Id::from(addr)));
+    }
+
+    #[test]
+    fn delivers_empty_messages() {
+        struct EchoActor;
+        impl Actor for EchoActor {
+            type Msg = ();
+            type State = ();
+            fn on_start(&self, _id: Id, _o: &mut Out<Self>) {}
+            fn on_msg(&self, _id: Id, _state: &mut Cow<()>, src: Id, msg: (), o: &mut Out<Self>) {
+                o.send(src, msg);
+            }
+        }
+
+        let addr = {
+            let socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            match socket.local_addr().unwrap() {
+                SocketAddr::V4(addr) => addr,
+                SocketAddr::V6(_) => unreachable!(),
+            }
+        };
+        try_spawn(|_| Ok::<_, ()>(Vec::new()), |_| Ok(()), vec![(addr, EchoActor)]).unwrap();
+
+        // The message serializes to an empty datagram, which is still delivered to `on_msg`.
+        let client = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        client.set_read_timeout(Some(std::time::Duration::from_secs(5))).unwrap();
+        client.send_to(&[], addr).unwrap();
+        let mut buf = [0; 16];
+        let (len, src) = client.recv_from(&mut buf).unwrap();
+        assert_eq!((len, src), (0, SocketAddr::V4(addr)));
+    }
+
+    #[test]
+    fn combines_options() {
+        use crate::actor::actor_test_util::ping_pong::{PingPongActor, PingPongMsg};
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        #[derive(Clone, Default)]
+        struct SharedLog(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for SharedLog {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+        }
+        fn unused_addr() -> SocketAddrV4 {
+            let socket = std::net::UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+            match socket.local_addr().unwrap() {
+                SocketAddr::V4(addr) => addr,
+                SocketAddr::V6(_) => unreachable!(),
+            }
+        }
+
+        let (ping_id, pong_id) = (Id::from(unused_addr()), Id::from(unused_addr()));
+        let log = SharedLog::default();
+        let errors = Arc::new(AtomicUsize::new(0));
+        let options = SpawnOptions::default()
+            .on_error({
+                let errors = Arc::clone(&errors);
+                move |_: SpawnError<PingPongMsg, serde_json::Error>| {
+                    errors.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .record(log.clone())
+            .batch(SpawnConfig::default());
+        spawn_with(
+            serde_json::to_vec,
+            |bytes| serde_json::from_slice(bytes),
+            options,
+            vec![
+                (ping_id, PingPongActor::PingActor { pong_id }),
+                (pong_id, PingPongActor::PongActor),
+            ]).unwrap();
+
+        // Batched messages are exchanged in both directions and recorded.
+        let delivered_to = |dst: Id| {
+            log.0.lock().split(|b| *b == b'\n')
+                .filter_map(|line| serde_json::from_slice::<RecordedEvent>(line).ok())
+                .any(|event| matches!(event, RecordedEvent::Deliver { dst: d, .. } if d == dst))
+        };
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !(delivered_to(ping_id) && delivered_to(pong_id)) {
+            assert!(std::time::Instant::now() < deadline, "Messages were not recorded.");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(errors.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn can_batch_messages() {
+        use super::{decode_batch, encode_batched, encode_fragment, BatchEntry::*};
+        let mut datagram = Vec::new();
+        encode_batched(&mut datagram, b"first");
+        encode_batched(&mut datagram, b"");
+        encode_fragment(&mut datagram, 7, 1, 2, b"third");
+        assert_eq!(
+            decode_batch(&datagram),
+            Some(vec![
+                Message(&b"first"[..]),
+                Message(&b""[..]),
+                Fragment { seq: 7, index: 1, count: 2, chunk: &b"third"[..] },
+            ]));
+        assert_eq!(decode_batch(&datagram[..datagram.len() - 1]), None);
+        assert_eq!(decode_batch(&datagram[..2]), None);
+
+        let mut datagram = Vec::new();
+        encode_fragment(&mut datagram, 7, 2, 2, b"out of range");
+        assert_eq!(decode_batch(&datagram), None);
+    }
+
+    #[test]
+    fn can_reassemble_fragments() {
+        use super::{Reassembler, MAX_INCOMPLETE};
+        let src1 = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 1));
+        let src2 = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 2));
+        let mut reassembler = Reassembler::default();
+        assert_eq!(reassembler.add(src1, 0, 2, 3, b"c"), None);
+        assert_eq!(reassembler.add(src2, 0, 0, 2, b"x"), None);
+        assert_eq!(reassembler.add(src1, 0, 0, 3, b"a"), None);
+        assert_eq!(reassembler.add(src1, 0, 1, 3, b"b"), Some(b"abc".to_vec()));
+        assert_eq!(reassembler.add(src2, 0, 1, 2, b"y"), Some(b"xy".to_vec()));
+
+        // The oldest incomplete message is discarded once the limit is reached.
+        for seq in 0..=MAX_INCOMPLETE as u32 {
+            assert_eq!(reassembler.add(src1, seq, 0, 2, b"a"), None);
+        }
+        assert_eq!(reassembler.add(src1, 1, 1, 2, b"b"), Some(b"ab".to_vec()));
+        assert_eq!(reassembler.add(src1, 0, 1, 2, b"b"), None);
+    }
 }
@@ -0,0 +1,309 @@
+//! Private module for selective re-export.
+
+use crate::actor::*;
+use crate::actor::spawn::{log_error, timer_duration, MAX_DATAGRAM_LEN};
+use futures::{Async, Future, Poll};
+use futures::sync::oneshot::{self, SpawnHandle};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::net::{SocketAddr, SocketAddrV4};
+use std::time::Instant;
+use tokio_executor::DefaultExecutor;
+use tokio_timer::Delay;
+use tokio_udp::UdpSocket;
+
+/// Runs actors like [`spawn`], but as tasks on the current [tokio] executor rather than on a
+/// thread per actor, so that many actors can be embedded in an existing async service. Sockets
+/// are non-blocking, and timers are driven by the runtime's timer. Returns an error if unable to
+/// bind any actor's socket, in which case no actors are started. Errors encountered while running
+/// are logged.
+///
+/// Dropping a returned handle stops the corresponding actor, so call [`SpawnHandle::forget`] to
+/// run an actor for the lifetime of the runtime. Actors started via [`Command::Spawn`] run until
+/// the runtime shuts down. [`Command::Monitor`] is not supported by this runtime and is ignored.
+///
+/// # Panics
+///
+/// Panics if not called from the context of a tokio 0.1 runtime, such as one started by
+/// `actix_rt::System`.
+///
+/// # Example
+///
+/// ```no_run
+/// use stateright::actor::{Id, spawn_tokio};
+/// use std::net::{Ipv4Addr, SocketAddrV4};
+/// # mod serde_json {
+/// #     pub fn to_vec(_: &()) -> Result<Vec<u8>, ()> { Ok(vec![]) }
+/// #     pub fn from_slice(_: &[u8]) -> Result<(), ()> { Ok(()) }
+/// # }
+/// # let actor1 = ();
+/// # let actor2 = ();
+/// let id1 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3001));
+/// let id2 = Id::from(SocketAddrV4::new(Ipv4Addr::LOCALHOST, 3002));
+/// actix_rt::System::new("actors").block_on(futures::future::lazy(|| {
+///     let handles = spawn_tokio(
+///         serde_json::to_vec,
+///         |bytes| serde_json::from_slice(bytes),
+///         vec![
+///             (id1, actor1),
+///             (id2, actor2),
+///         ]).unwrap();
+///     futures::future::join_all(handles)
+/// })).unwrap();
+/// ```
+///
+/// [tokio]: https://tokio.rs
+#[allow(clippy::type_complexity)]
+pub fn spawn_tokio<A, E>(
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+    actors: Vec<(impl Into<Id>, A)>) -> Result<Vec<SpawnHandle<(), ()>>, SpawnError<A::Msg, E>>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    E: Debug + Send + 'static,
+{
+    // Bind every socket before starting any actor so that failures are reported to the caller.
+    let mut tasks = Vec::with_capacity(actors.len());
+    for (id, actor) in actors {
+        let id = id.into();
+        let socket = UdpSocket::bind(&SocketAddr::V4(SocketAddrV4::from(id)))
+            .map_err(|source| SpawnError::Bind { id, source })?;
+        tasks.push(ActorTask::new(id, actor, socket, serialize, deserialize));
+    }
+
+    let executor = DefaultExecutor::current();
+    Ok(tasks.into_iter().map(|task| oneshot::spawn(task, &executor)).collect())
+}
+
+/// A task that drives an actor in response to datagrams and timeouts.
+struct ActorTask<A: Actor, E> {
+    id: Id,
+    actor: A,
+    state: Option<A::State>, // `None` until the first poll
+    socket: UdpSocket,
+    timer: Option<Delay>,
+    in_buf: Vec<u8>,
+    out_queue: VecDeque<(Id, Vec<u8>)>,
+    random_values: VecDeque<u64>,
+    serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+    deserialize: fn(&[u8]) -> Result<A::Msg, E>,
+}
+
+impl<A, E> ActorTask<A, E>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    E: Debug + Send + 'static,
+{
+    fn new(
+        id: Id,
+        actor: A,
+        socket: UdpSocket,
+        serialize: fn(&A::Msg) -> Result<Vec<u8>, E>,
+        deserialize: fn(&[u8]) -> Result<A::Msg, E>) -> Self
+    {
+        ActorTask {
+            id,
+            actor,
+            state: None,
+            socket,
+            timer: None,
+            in_buf: vec![0; 65_535],
+            out_queue: VecDeque::new(),
+            random_values: VecDeque::new(),
+            serialize,
+            deserialize,
+        }
+    }
+
+    /// The effect to perform in response to actor outputs. Messages are queued until the socket
+    /// is ready.
+    fn on_commands(&mut self, out: Out<A>) {
+        let id = self.id;
+        for command in out {
+            match command {
+                Command::Send(dst, msg) => {
+                    match (self.serialize)(&msg) {
+                        Err(err) => {
+                            log_error(SpawnError::Serialize { id, dst, msg, err });
+                        },
+                        Ok(out_buf) => self.enqueue(dst, out_buf),
+                    }
+                },
+                Command::Broadcast(dsts, msg) => {
+                    for dst in dsts {
+                        match (self.serialize)(&msg) {
+                            Err(err) => {
+                                log_error(SpawnError::Serialize { id, dst, msg: msg.clone(), err });
+                            },
+                            Ok(out_buf) => self.enqueue(dst, out_buf),
+                        }
+                    }
+                },
+                Command::SetTimer(range) => {
+                    let deadline = Instant::now() + timer_duration(range);
+                    match &mut self.timer {
+                        Some(timer) => timer.reset(deadline),
+                        None => self.timer = Some(Delay::new(deadline)),
+                    }
+                },
+                Command::CancelTimer => {
+                    self.timer = None;
+                },
+                Command::Spawn(spec) => {
+                    let child = match self.actor.spawned_actor(&spec) {
+                        None => continue,
+                        Some(child) => child,
+                    };
+
+                    // The child listens on an ephemeral port at the parent's IP, which determines
+                    // its ID.
+                    let ip = *SocketAddrV4::from(id).ip();
+                    let bound = UdpSocket::bind(&SocketAddr::V4(SocketAddrV4::new(ip, 0)))
+                        .and_then(|socket| Ok((socket.local_addr()?, socket)));
+                    match bound {
+                        Ok((SocketAddr::V4(child_addr), child_socket)) => {
+                            log::info!("Spawning actor. id={}, parent={}, spec={:?}",
+                                       child_addr, id, spec);
+                            tokio_executor::spawn(ActorTask::new(
+                                Id::from(child_addr), child, child_socket,
+                                self.serialize, self.deserialize));
+                        },
+                        Ok((child_addr, _)) => {
+                            log_error::<A::Msg, E>(
+                                SpawnError::UnsupportedSource { id, src: child_addr });
+                        },
+                        Err(source) => {
+                            log_error::<A::Msg, E>(SpawnError::Bind { id, source });
+                        },
+                    }
+                },
+                Command::ChooseRandom(range) => {
+                    if range.start < range.end {
+                        use rand::Rng;
+                        self.random_values.push_back(
+                            rand::thread_rng().gen_range(range.start, range.end));
+                    }
+                },
+                Command::Monitor(peer) => {
+                    log::warn!("Monitoring is not supported by this runtime. Ignoring. id={}, peer={}",
+                               id, peer);
+                },
+            }
+        }
+    }
+
+    /// Queues a serialized message unless it exceeds the datagram limit.
+    fn enqueue(&mut self, dst: Id, out_buf: Vec<u8>) {
+        if MAX_DATAGRAM_LEN < out_buf.len() {
+            let (id, len, max) = (self.id, out_buf.len(), MAX_DATAGRAM_LEN);
+            log_error::<A::Msg, E>(SpawnError::TooLarge { id, dst, len, max });
+        } else {
+            self.out_queue.push_back((dst, out_buf));
+        }
+    }
+
+    /// Sends queued messages until the socket is not ready.
+    fn flush(&mut self) {
+        while let Some((dst, out_buf)) = self.out_queue.front() {
+            let dst = *dst;
+            match self.socket.poll_send_to(out_buf, &SocketAddr::V4(SocketAddrV4::from(dst))) {
+                Ok(Async::NotReady) => return,
+                Ok(Async::Ready(_)) => {},
+                Err(source) => {
+                    log_error::<A::Msg, E>(SpawnError::Send { id: self.id, dst, source });
+                },
+            }
+            self.out_queue.pop_front();
+        }
+    }
+}
+
+impl<A, E> Future for ActorTask<A, E>
+where
+    A: 'static + Send + Actor,
+    A::Msg: Debug + Send,
+    A::State: Debug + Send,
+    E: Debug + Send + 'static,
+{
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let id = self.id;
+        let addr = SocketAddrV4::from(id);
+        if self.state.is_none() {
+            let mut out = Out::new();
+            let state = self.actor.on_start(id, &mut out);
+            log::info!("Actor started. id={}, state={:?}, out={:?}", addr, state, out);
+            self.state = Some(state);
+            self.on_commands(out);
+        }
+
+        loop {
+            self.flush();
+
+            // Apply a random value if chosen or a timeout if due, otherwise check for a message.
+            let random_value = self.random_values.pop_front();
+            let is_timed_out = random_value.is_none() && match self.timer.as_mut().map(Future::poll) {
+                Some(Ok(Async::Ready(()))) => true,
+                Some(Err(err)) => {
+                    log::warn!("Unable to run timer. Ignoring. id={}, err={}", addr, err);
+                    self.timer = None;
+                    continue;
+                },
+                _ => false,
+            };
+            let mut out = Out::new();
+            let mut state = Cow::Borrowed(self.state.as_ref().expect("actor is started"));
+            if let Some(value) = random_value {
+                log::info!("Chose random value. id={}, value={}", addr, value);
+                self.actor.on_random(id, &mut state, value, &mut out);
+            } else if is_timed_out {
+                self.timer = None; // timer is no longer valid
+                self.actor.on_timeout(id, &mut state, &mut out);
+            } else {
+                match self.socket.poll_recv_from(&mut self.in_buf) {
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(source) => {
+                        log_error::<A::Msg, E>(SpawnError::Recv { id, source });
+                        continue;
+                    },
+                    Ok(Async::Ready((count, src_addr))) => {
+                        match (self.deserialize)(&self.in_buf[..count]) {
+                            Ok(msg) => {
+                                if let SocketAddr::V4(src_addr) = src_addr {
+                                    log::info!("Received message. id={}, src={}, msg={:?}",
+                                               addr, src_addr, msg);
+                                    self.actor.on_msg(id, &mut state, Id::from(src_addr), msg, &mut out);
+                                } else {
+                                    log_error::<A::Msg, E>(
+                                        SpawnError::UnsupportedSource { id, src: src_addr });
+                                    continue;
+                                }
+                            },
+                            Err(err) => {
+                                let bytes = self.in_buf[..count].to_vec();
+                                log_error::<A::Msg, E>(
+                                    SpawnError::Deserialize { id, src: Some(src_addr), bytes, err });
+                                continue;
+                            },
+                        }
+                    },
+                }
+            }
+
+            // Handle commands and update state.
+            if !is_no_op(&state, &out) {
+                log::debug!("Acted. id={}, state={:?}, out={:?}", addr, state, out);
+            }
+            if let Cow::Owned(next_state) = state {
+                self.state = Some(next_state);
+            }
+            self.on_commands(out);
+        }
+    }
+}
@@ -2,24 +2,178 @@
 
 use crate::*;
 use crate::actor::*;
-use crate::util::HashableHashSet;
+use crate::util::{BuildStableHasher, HashableHashMap};
+use dashmap::{DashMap, DashSet};
+use nohash_hasher::NoHashHasher;
+use std::collections::BTreeSet;
+use std::hash::BuildHasherDefault;
 use std::ops::Range;
 use std::sync::Arc;
 use std::time::Duration;
 
-/// Represents a network of messages.
-pub type Network<Msg> = HashableHashSet<Envelope<Msg>>;
-
 /// Indicates whether the network loses messages. Note that as long as invariants do not check
 /// the network state, losing a message is indistinguishable from an unlimited delay, so in
 /// many cases you can improve model checking performance by not modeling message loss.
-#[derive(Copy, Clone, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum LossyNetwork { Yes, No }
 
 /// Indicates whether the network duplicates messages. If duplication is disabled, messages
 /// are forgotten once delivered, which can improve model checking performance.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum DuplicatingNetwork {
+    Yes,
+    No,
+    /// Each message is delivered at most `k + 1` times (i.e. duplicated at most `k` times) and
+    /// then forgotten, as tracked by [`SystemState::delivery_counts`]. Unbounded duplication
+    /// keeps every delivered message on the network, whereas a bound of 1 or 2 typically
+    /// catches the same bugs while still letting the network drain.
+    UpTo(usize),
+}
+
+/// Describes the channel from one actor to another, such as a reliable bus between co-located
+/// actors versus an unreliable network between sites. See [`System::link_quality`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct LinkQuality {
+    /// Whether the channel loses messages.
+    pub lossy: LossyNetwork,
+    /// Whether the channel duplicates messages.
+    pub duplicating: DuplicatingNetwork,
+    /// Whether the channel delivers messages in the order they were sent, as if
+    /// [`System::reordering_window`] were `Some(0)` for the channel.
+    pub ordered: bool,
+}
+
+/// Indicates whether a [`Command::Broadcast`] sends to every recipient in a single step, or
+/// instead sends to one recipient at a time such that other events (including a crash of the
+/// sender) can interleave. See [`System::atomic_broadcast`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AtomicBroadcast { Yes, No }
+
+/// Indicates whether the model checker applies partial-order reduction, which avoids exploring
+/// every interleaving of actions by actors that cannot influence one another. See
+/// [`System::partial_order_reduction`].
+#[derive(Copy, Clone, PartialEq)]
+pub enum PartialOrderReduction { Yes, No }
+
+/// Indicates when an actor's pending timeout may fire relative to deliveries of messages to that
+/// actor or to the timeouts of other actors. See [`System::timeout_ordering`].
 #[derive(Copy, Clone, PartialEq)]
-pub enum DuplicatingNetwork { Yes, No }
+pub enum TimeoutOrdering {
+    /// A timeout may fire even while messages for the actor are deliverable, as in a fully
+    /// asynchronous system.
+    Asynchronous,
+    /// A timeout only fires once no messages for the actor are on the network, which assumes that
+    /// messages are delivered more eagerly than timeouts elapse.
+    AfterDeliveries,
+    /// A timeout may fire while messages are deliverable, but not before a timer that was set
+    /// earlier and must have elapsed first, given that the rates of any two actors' clocks differ
+    /// by at most the specified ratio (which is at least `1.0`). That is, a timer with duration
+    /// `start..end` cannot fire before an earlier timer with duration `s..e` if `e * ratio <=
+    /// start`. Timers are ordered symbolically, so no durations elapse in the model, but
+    /// protocols that rely on a hierarchy of timeouts (such as an election timeout that exceeds
+    /// the heartbeat interval) are spared spurious counterexamples.
+    BoundedDrift(f64),
+}
+
+/// Remembers message deliveries that were no-ops, keyed by the fingerprint of the recipient's
+/// state and the delivery, so that the model checker can skip them when the same delivery is
+/// reconsidered while the recipient is in the same state, as happens repeatedly for duplicated
+/// messages. See [`System::cache_no_op_deliveries`].
+///
+/// The cache is emptied whenever it reaches its [capacity](NoOpCache::capacity), which bounds its
+/// memory use. Clones share the same underlying cache.
+#[derive(Clone)]
+pub struct NoOpCache {
+    entries: Arc<DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>>,
+    capacity: usize,
+}
+
+impl NoOpCache {
+    /// The number of no-op deliveries that [`NoOpCache::default`] retains.
+    pub const DEFAULT_CAPACITY: usize = 1 << 20;
+
+    /// Instantiates a cache that retains at most `capacity` no-op deliveries.
+    pub fn with_capacity(capacity: usize) -> Self {
+        NoOpCache { entries: Arc::default(), capacity }
+    }
+
+    /// The maximum number of no-op deliveries retained.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Indicates how many no-op deliveries have been recorded.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Indicates whether no no-op deliveries have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    fn contains(&self, key: &Fingerprint) -> bool {
+        self.entries.contains(key)
+    }
+
+    fn insert(&self, key: Fingerprint) {
+        if self.capacity <= self.entries.len() {
+            self.entries.clear();
+        }
+        self.entries.insert(key);
+    }
+}
+
+impl Default for NoOpCache {
+    fn default() -> Self {
+        NoOpCache::with_capacity(NoOpCache::DEFAULT_CAPACITY)
+    }
+}
+
+/// Stores each distinct actor state once so that system states with identical actor states
+/// share them, which reduces memory when few actor states recur across many system states (for
+/// instance when network contents vary far more than actor states do). Fingerprints are still
+/// derived from the actor states themselves, so they do not depend on the order in which states
+/// were interned. See [`System::intern_actor_states`].
+///
+/// Distinct actor states are retained until the pool is dropped. Clones share the same pool.
+pub struct ActorStatePool<State>(Arc<DashMap<Fingerprint, Arc<State>, BuildHasherDefault<NoHashHasher<u64>>>>);
+
+impl<State> ActorStatePool<State> {
+    /// Indicates how many distinct actor states have been interned.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Indicates whether no actor states have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl<State: Hash + PartialEq> ActorStatePool<State> {
+    /// Returns the shared copy of an actor state, adding it if absent.
+    fn intern(&self, state: State) -> Arc<State> {
+        let fp = fingerprint(&state);
+        if let Some(shared) = self.0.get(&fp) {
+            // Fingerprint collisions are unlikely but simply forgo sharing.
+            return if **shared == state { Arc::clone(&shared) } else { Arc::new(state) };
+        }
+        Arc::clone(&self.0.entry(fp).or_insert_with(|| Arc::new(state)))
+    }
+}
+
+impl<State> Clone for ActorStatePool<State> {
+    fn clone(&self) -> Self {
+        ActorStatePool(Arc::clone(&self.0))
+    }
+}
+
+impl<State> Default for ActorStatePool<State> {
+    fn default() -> Self {
+        ActorStatePool(Default::default())
+    }
+}
 
 /// Represents a system of actors that communicate over a network.
 /// Usage: `let checker = my_system.into_model().checker()`.
@@ -36,6 +190,11 @@ pub trait System: Sized {
     /// Defines the actors.
     fn actors(&self) -> Vec<Self::Actor>;
 
+    /// Defines the initial history. Defaults to [`Default::default`].
+    fn init_history(&self) -> Self::History {
+        Self::History::default()
+    }
+
     /// Defines the initial network.
     fn init_network(&self) -> Vec<Envelope<<Self::Actor as Actor>::Msg>> {
         Vec::with_capacity(20)
@@ -51,6 +210,81 @@ pub trait System: Sized {
         DuplicatingNetwork::Yes
     }
 
+    /// Defines the quality of the channel from one actor to another, overriding
+    /// [`System::lossy_network`] and [`System::duplicating_network`] for that channel. Defaults to
+    /// those network-wide settings, without ordering beyond [`System::reordering_window`].
+    fn link_quality(&self, src: Id, dst: Id) -> LinkQuality {
+        let _ = src;
+        let _ = dst;
+        LinkQuality {
+            lossy: self.lossy_network(),
+            duplicating: self.duplicating_network(),
+            ordered: false,
+        }
+    }
+
+    /// Defines how far messages on a channel (i.e. from one actor to another) may be reordered.
+    /// With a window of `k`, a message can only be delivered if fewer than `k + 1` messages sent
+    /// earlier on the same channel remain undelivered, so `Some(0)` means that every channel is
+    /// FIFO. Duplicates of previously delivered messages are unconstrained. Real transports
+    /// typically exhibit bounded reordering, so a window both shrinks the state space and rules
+    /// out implausible counterexamples. Defaults to `None`, which allows unbounded reordering.
+    fn reordering_window(&self) -> Option<usize> {
+        None
+    }
+
+    /// Defines how many later messages on a channel may overtake any one message. With a limit of
+    /// `k`, once a message has been overtaken by `k` later messages, no further message sent
+    /// after it on the same channel can be delivered until it is delivered or dropped. This
+    /// complements [`System::reordering_window`], which instead bounds how many earlier messages
+    /// a message may overtake. For example, given messages 1, 2, and 3 sent in that order, a
+    /// window of 1 allows delivering 2, 3, then 1, whereas a limit of 1 does not (as 1 would be
+    /// overtaken twice). Both may be defined. Defaults to `None`, which allows unbounded
+    /// overtaking.
+    fn max_overtaking(&self) -> Option<usize> {
+        None
+    }
+
+    /// Defines how many distinct messages the network can hold. A message sent while the network
+    /// is at capacity is blocked, meaning that it is discarded as if the network lost it and
+    /// [`System::record_send_blocked`] is notified. Messages retained by a [`DuplicatingNetwork`]
+    /// count toward the capacity until forgotten. This bounds the state space without tracking
+    /// message counts in [`System::within_boundary`]. Defaults to `None`, which allows an
+    /// unbounded number of messages.
+    fn max_in_flight(&self) -> Option<usize> {
+        None
+    }
+
+    /// Defines whether a [`Command::Broadcast`] (e.g. via [`Out::broadcast`]) is atomic. A
+    /// non-atomic broadcast instead queues a message per recipient in
+    /// [`SystemState::pending_sends`], and the model checker sends each via a separate
+    /// [`SystemAction::Transmit`] step, in recipient order. Messages not yet sent are discarded if
+    /// the sender crashes, which models a sender that fails partway through a broadcast. Defaults
+    /// to [`AtomicBroadcast::Yes`], which matches a sequence of [`Out::send`] calls.
+    fn atomic_broadcast(&self) -> AtomicBroadcast {
+        AtomicBroadcast::Yes
+    }
+
+    /// Defines how many actors may crash, per the crash fault model. A crashed actor takes no
+    /// further steps: messages are never delivered to it, and its timer and configuration updates
+    /// are ignored. Actors monitoring it via [`Out::monitor`] are notified via
+    /// [`Actor::on_peer_down`]. Defaults to `0`.
+    fn max_crashes(&self) -> usize {
+        0
+    }
+
+    /// Defines whether a pending timeout may fire while messages for the same actor are
+    /// deliverable. [`TimeoutOrdering::AfterDeliveries`] shrinks the state space and rules out
+    /// liveness counterexamples where a timeout perpetually preempts deliveries, but it is only
+    /// realistic for deployments where message latency is well below timeout durations. Note
+    /// that a duplicating network never forgets a delivered message, so timeouts will not fire
+    /// for actors that have received a message unless the network also loses messages.
+    /// [`TimeoutOrdering::BoundedDrift`] instead orders timeouts relative to one another based on
+    /// the durations passed to [`Out::set_timer`]. Defaults to [`TimeoutOrdering::Asynchronous`].
+    fn timeout_ordering(&self) -> TimeoutOrdering {
+        TimeoutOrdering::Asynchronous
+    }
+
     /// Defines whether/how an incoming message contributes to relevant history. Returning
     /// `Some(new_history)` updates the relevant history, while `None` does not.
     fn record_msg_in(&self, history: &Self::History, src: Id, dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
@@ -71,6 +305,128 @@ pub trait System: Sized {
         None
     }
 
+    /// Defines whether/how a timeout contributes to relevant history. Returning
+    /// `Some(new_history)` updates the relevant history, while `None` does not.
+    fn record_timeout(&self, history: &Self::History, id: Id) -> Option<Self::History> {
+        let _ = history;
+        let _ = id;
+        None
+    }
+
+    /// Defines whether/how a message dropped by a [`LossyNetwork`] contributes to relevant
+    /// history. Returning `Some(new_history)` updates the relevant history, while `None` does
+    /// not.
+    fn record_drop(&self, history: &Self::History, src: Id, dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
+        let _ = history;
+        let _ = src;
+        let _ = dst;
+        let _ = msg;
+        None
+    }
+
+    /// Defines whether/how a message blocked by [`System::max_in_flight`] contributes to
+    /// relevant history. Returning `Some(new_history)` updates the relevant history, while `None`
+    /// does not.
+    fn record_send_blocked(&self, history: &Self::History, src: Id, dst: Id, msg: &<Self::Actor as Actor>::Msg) -> Option<Self::History> {
+        let _ = history;
+        let _ = src;
+        let _ = dst;
+        let _ = msg;
+        None
+    }
+
+    /// Names the protocol phase (e.g. "view change" or "steady state") that the system enters upon
+    /// reaching `state`. Returning `Some(phase)` updates [`SystemState::phase`], while `None`
+    /// leaves it unchanged. Properties can then scope conditions to phases via
+    /// [`SystemState::is_in_phase`], and sequence diagrams mark phase boundaries.
+    fn record_phase(&self, state: &SystemState<Self>) -> Option<&'static str> {
+        let _ = state;
+        None
+    }
+
+    /// Summarizes the details of `state` that distinguish it from other states, such that the
+    /// checker treats a state as already visited if another state had the same projection. For
+    /// example, the projection can omit request IDs or message payloads that do not affect
+    /// behavior. Properties are still evaluated against full states. Returning `None` (the
+    /// default) distinguishes states by all of their contents.
+    ///
+    /// This is a manual abstraction for taming state explosion: successors of a state that is
+    /// skipped because of its projection are not checked either, so a projection that omits
+    /// relevant details can hide bugs.
+    fn fingerprint_projection(&self, state: &SystemState<Self>) -> Option<impl Hash> {
+        let _ = state;
+        None::<()>
+    }
+
+    /// Defines whether the model checker applies partial-order reduction. When enabled, the checker
+    /// picks an actor whose pending actions cannot be influenced by any other active actor (per
+    /// [`System::may_send`]) and only explores that actor's actions from the current state,
+    /// deferring other actors' actions to subsequent states. Disabled by default.
+    ///
+    /// The reduction skips intermediate states, so it should only be enabled when:
+    ///
+    /// 1. Properties are insensitive to the order in which independent actors act, such as
+    ///    properties that relate states at quiescence or that only relate an actor to itself.
+    /// 2. [`System::History`] is insensitive to that order as well.
+    /// 3. The state space is acyclic, as an actor that can act forever may otherwise indefinitely
+    ///    defer other actors.
+    ///
+    /// No reduction is applied if [`System::max_in_flight`] is set or timeouts are ordered by
+    /// [`TimeoutOrdering::BoundedDrift`], as actors then influence one another through the shared
+    /// network capacity or timer order regardless of [`System::may_send`].
+    fn partial_order_reduction(&self) -> PartialOrderReduction {
+        PartialOrderReduction::No
+    }
+
+    /// Defines whether the actor identified by `src` may ever directly send a message to the actor
+    /// identified by `dst`. Partial-order reduction relies on this to determine which actors are
+    /// independent, so a more precise definition enables more reduction. Defaults to `true`.
+    fn may_send(&self, src: Id, dst: Id) -> bool {
+        let _ = src;
+        let _ = dst;
+        true
+    }
+
+    /// Defines whether the model checker remembers message deliveries that were no-ops (keyed by
+    /// the recipient's state and the delivery) to avoid re-evaluating them, which helps
+    /// duplicating networks, where inert duplicates would otherwise be re-evaluated every time
+    /// they are considered. See [`NoOpCache`]. This relies on actors being deterministic.
+    /// Defaults to `false`.
+    ///
+    /// Hashing the recipient's state and the message on every delivery has a cost of its own, so
+    /// the cache only saves time when message handlers are expensive relative to that hashing.
+    fn cache_no_op_deliveries(&self) -> bool {
+        false
+    }
+
+    /// Defines whether identical actor states are stored once and shared by every system state
+    /// in which they appear. See [`ActorStatePool`]. Defaults to `false`.
+    fn intern_actor_states(&self) -> bool {
+        false
+    }
+
+    /// Defines configuration updates that the environment may deliver to actors via
+    /// [`Actor::on_config_update`], mirroring a [`ConfigUpdater`] for spawned actors. Updates are
+    /// delivered at most once and in order per actor, but they can interleave arbitrarily with
+    /// other events.
+    fn config_updates(&self) -> Vec<(Id, <Self::Actor as Actor>::Msg)> {
+        Vec::new()
+    }
+
+    /// Defines alternative initial states for the actor identified by `id`, given the `state`
+    /// returned by its [`Actor::on_start`]. The model checker starts from every combination of
+    /// unperturbed and perturbed actor states, which can model recovery from arbitrary but
+    /// reachable crash states (e.g. stale persisted terms or partially applied logs) when paired
+    /// with properties indicating that the system converges. Commands output by
+    /// [`Actor::on_start`] apply regardless. Defaults to no perturbations.
+    fn init_state_perturbations(&self, id: Id, state: &<Self::Actor as Actor>::State)
+        -> Vec<<Self::Actor as Actor>::State>
+    {
+        let _ = id;
+        let _ = state;
+        Vec::new()
+    }
+
     /// Generates the expected properties for this model.
     fn properties(&self) -> Vec<Property<SystemModel<Self>>>;
 
@@ -79,13 +435,44 @@ pub trait System: Sized {
         true
     }
 
+    /// Indicates whether a message in the network should be considered for delivery, which
+    /// enables targeted pruning of deliveries that are irrelevant to the properties being checked
+    /// (such as messages from a stale term). Unlike [`System::within_boundary`], which excludes
+    /// states, this excludes individual actions, so the message remains in the network (where a
+    /// lossy network can still drop it). Defaults to `true`.
+    fn should_deliver(&self, _state: &SystemState<Self>, _src: Id, _dst: Id, _msg: &<Self::Actor as Actor>::Msg) -> bool {
+        true
+    }
+
+    /// Indicates whether a state is an intended terminal state, such as one in which every client
+    /// has received its responses, as opposed to a deadlock. See [`Checker::deadlocks`].
+    fn is_terminal(&self, _state: &SystemState<Self>) -> bool {
+        false
+    }
+
+    /// Whether to check the built-in [`Property::deadlock_free`] property in addition to
+    /// [`System::properties`]. Defaults to `false`.
+    fn deadlock_freedom(&self) -> bool {
+        false
+    }
+
     /// Converts this system into a model that can be checked.
     fn into_model(self) -> SystemModel<Self> {
         SystemModel {
             actors: self.actors(),
             init_network: self.init_network(),
+            config_updates: self.config_updates(),
             lossy_network: self.lossy_network(),
             duplicating_network: self.duplicating_network(),
+            timeout_ordering: self.timeout_ordering(),
+            reordering_window: self.reordering_window(),
+            max_overtaking: self.max_overtaking(),
+            max_in_flight: self.max_in_flight(),
+            atomic_broadcast: self.atomic_broadcast(),
+            max_crashes: self.max_crashes(),
+            partial_order_reduction: self.partial_order_reduction(),
+            no_op_cache: if self.cache_no_op_deliveries() { Some(NoOpCache::default()) } else { None },
+            actor_state_pool: if self.intern_actor_states() { Some(ActorStatePool::default()) } else { None },
             system: self,
         }
     }
@@ -96,8 +483,18 @@ pub trait System: Sized {
 pub struct SystemModel<S: System> {
     pub actors: Vec<S::Actor>,
     pub init_network: Vec<Envelope<<S::Actor as Actor>::Msg>>,
+    pub config_updates: Vec<(Id, <S::Actor as Actor>::Msg)>,
     pub lossy_network: LossyNetwork,
     pub duplicating_network: DuplicatingNetwork,
+    pub timeout_ordering: TimeoutOrdering,
+    pub reordering_window: Option<usize>,
+    pub max_overtaking: Option<usize>,
+    pub max_in_flight: Option<usize>,
+    pub atomic_broadcast: AtomicBroadcast,
+    pub max_crashes: usize,
+    pub partial_order_reduction: PartialOrderReduction,
+    pub no_op_cache: Option<NoOpCache>,
+    pub actor_state_pool: Option<ActorStatePool<<S::Actor as Actor>::State>>,
     pub system: S,
 }
 
@@ -106,48 +503,33 @@ impl<S: System> Model for SystemModel<S> {
     type Action = SystemAction<<S::Actor as Actor>::Msg>;
 
     fn init_states(&self) -> Vec<Self::State> {
-        let mut init_sys_state = SystemState {
-            actor_states: Vec::with_capacity(self.actors.len()),
-            network: Network::with_hasher(stable::build_hasher()), // for consistent discoveries
-            is_timer_set: Vec::new(),
-            history: S::History::default(),
-        };
-
-        // init the network
-        for e in self.init_network.clone() {
-            init_sys_state.network.insert(e);
+        // perturb actor states, combining with the alternatives for preceding actors
+        let mut init_sys_states = vec![self.unperturbed_init_state()];
+        for index in 0..self.actors.len() {
+            let perturbations = self.system.init_state_perturbations(
+                Id::from(index), &init_sys_states[0].actor_states[index]);
+            if perturbations.is_empty() { continue }
+            let mut perturbed_sys_states = Vec::with_capacity(
+                init_sys_states.len() * perturbations.len());
+            for sys_state in &init_sys_states {
+                for state in &perturbations {
+                    let mut perturbed_sys_state = sys_state.clone();
+                    perturbed_sys_state.actor_states[index] = self.share_actor_state(state.clone());
+                    perturbed_sys_states.push(perturbed_sys_state);
+                }
+            }
+            init_sys_states.append(&mut perturbed_sys_states);
         }
-
-        // init each actor
-        for (index, actor) in self.actors.iter().enumerate() {
-            let id = Id::from(index);
-            let mut out = Out::new();
-            let state = actor.on_start(id, &mut out);
-            init_sys_state.actor_states.push(Arc::new(state));
-            self.process_commands(id, out, &mut init_sys_state);
+        for init_sys_state in &mut init_sys_states {
+            self.record_phase(init_sys_state);
         }
-
-        vec![init_sys_state]
+        init_sys_states
     }
 
     fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
-        for env in &state.network {
-            // option 1: message is lost
-            if self.lossy_network == LossyNetwork::Yes {
-                actions.push(SystemAction::Drop(env.clone()));
-            }
-
-            // option 2: message is delivered
-            if usize::from(env.dst) < self.actors.len() {
-                actions.push(SystemAction::Deliver { src: env.src, dst: env.dst, msg: env.msg.clone() });
-            }
-        }
-
-        // option 3: actor timeout
-        for (index, &is_scheduled) in state.is_timer_set.iter().enumerate() {
-            if is_scheduled {
-                actions.push(SystemAction::Timeout(Id::from(index)));
-            }
+        self.enabled_actions(state, actions);
+        if self.partial_order_reduction == PartialOrderReduction::Yes {
+            self.reduce(actions);
         }
     }
 
@@ -155,9 +537,23 @@ impl<S: System> Model for SystemModel<S> {
         match action {
             SystemAction::Drop(env) => {
                 let mut next_state = last_sys_state.clone();
+                Self::remove_from_send_order(&mut next_state, &env, false);
+                if let Some(history) = self.system.record_drop(&next_state.history, env.src, env.dst, &env.msg) {
+                    next_state.history = history;
+                }
                 next_state.network.remove(&env);
+                next_state.delivery_counts.remove(&env);
+                self.record_phase(&mut next_state);
                 Some(next_state)
             },
+            SystemAction::Transmit(env) => {
+                let mut next_sys_state = last_sys_state.clone();
+                let position = next_sys_state.pending_sends.iter().position(|e| *e == env)?;
+                next_sys_state.pending_sends.remove(position);
+                self.transmit(env, &mut next_sys_state);
+                self.record_phase(&mut next_sys_state);
+                Some(next_sys_state)
+            },
             SystemAction::Deliver { src, dst: id, msg } => {
                 let index = usize::from(id);
                 let last_actor_state = &last_sys_state.actor_states.get(index);
@@ -167,33 +563,67 @@ impl<S: System> Model for SystemModel<S> {
                 let last_actor_state = &**last_actor_state.unwrap();
                 let mut state = Cow::Borrowed(last_actor_state);
 
-                // Some operations are no-ops, so ignore those as well.
+                // Some operations are no-ops, so ignore those as well. The first delivery of a
+                // message that bounds reordering is never a no-op though, as it unblocks the
+                // channel.
+                let env = Envelope { src, dst: id, msg };
+                let is_ordered = last_sys_state.send_order.contains(&env);
+                let Envelope { msg, .. } = env;
+                // The outcome only depends on the recipient's state, so the rest of the system
+                // state need not be hashed.
+                let no_op_key = self.no_op_cache.as_ref().filter(|_| !is_ordered).map(|cache| {
+                    (cache, fingerprint(&(id, last_actor_state, src, &msg)))
+                });
+                if let Some((cache, key)) = &no_op_key {
+                    if cache.contains(key) { return None; }
+                }
+                let actor = self.actor(last_sys_state, index)?;
                 let mut out = Out::new();
-                self.actors[index].on_msg(id, &mut state, src, msg.clone(), &mut out);
-                if is_no_op(&state, &out) { return None; }
+                actor.on_msg(id, &mut state, src, msg.clone(), &mut out);
+                if is_no_op(&state, &out) && !is_ordered {
+                    if let Some((cache, key)) = no_op_key {
+                        cache.insert(key);
+                    }
+                    return None;
+                }
                 let history = self.system.record_msg_in(&last_sys_state.history, src, id, &msg);
 
                 // Update the state as necessary:
-                // - Drop delivered message if not a duplicating network.
+                // - Drop delivered message if the channel does not duplicate messages.
                 // - Swap out revised actor state.
                 // - Track message input history.
                 // - Handle effect of commands on timers, network, and message output history.
                 let mut next_sys_state = last_sys_state.clone();
-                if self.duplicating_network == DuplicatingNetwork::No {
-                    // Strictly speaking, this state should be updated regardless of whether the
-                    // actor and history updates are a no-op. The current implementation is only
-                    // safe if invariants do not relate to the existence of envelopes on the
-                    // network.
-                    let env = Envelope { src, dst: id, msg };
-                    next_sys_state.network.remove(&env);
+                let env = Envelope { src, dst: id, msg };
+                if is_ordered {
+                    Self::remove_from_send_order(&mut next_sys_state, &env, true);
+                }
+                // Strictly speaking, the network should be updated regardless of whether the actor
+                // and history updates are a no-op. The current implementation is only safe if
+                // invariants do not relate to the existence of envelopes on the network.
+                match self.system.link_quality(src, id).duplicating {
+                    DuplicatingNetwork::Yes => {}
+                    DuplicatingNetwork::No => {
+                        next_sys_state.network.remove(&env);
+                    }
+                    DuplicatingNetwork::UpTo(max_duplicates) => {
+                        let count = next_sys_state.delivery_counts.get(&env).copied().unwrap_or(0);
+                        if count < max_duplicates {
+                            next_sys_state.delivery_counts.insert(env.clone(), count + 1);
+                        } else {
+                            next_sys_state.delivery_counts.remove(&env);
+                            next_sys_state.network.remove(&env);
+                        }
+                    }
                 }
                 if let Cow::Owned(next_actor_state) = state {
-                    next_sys_state.actor_states[index] = Arc::new(next_actor_state);
+                    next_sys_state.actor_states[index] = self.share_actor_state(next_actor_state);
                 }
                 if let Some(history) = history {
                     next_sys_state.history = history;
                 }
                 self.process_commands(id, out, &mut next_sys_state);
+                self.record_phase(&mut next_sys_state);
                 Some(next_sys_state)
             },
             SystemAction::Timeout(id) => {
@@ -201,18 +631,81 @@ impl<S: System> Model for SystemModel<S> {
                 let index = usize::from(id);
                 let mut state = Cow::Borrowed(&*last_sys_state.actor_states[index]);
                 let mut out = Out::new();
-                self.actors[index].on_timeout(id, &mut state, &mut out);
+                self.actor(last_sys_state, index)?.on_timeout(id, &mut state, &mut out);
                 let keep_timer = out.iter().any(|c| matches!(c, Command::SetTimer(_)));
                 if is_no_op(&state, &out) && keep_timer { return None }
                 let mut next_sys_state = last_sys_state.clone();
 
                 // Timer is no longer valid.
                 next_sys_state.is_timer_set[index] = false;
+                next_sys_state.timer_order.retain(|(timer_id, _)| *timer_id != id);
+                if let Some(history) = self.system.record_timeout(&next_sys_state.history, id) {
+                    next_sys_state.history = history;
+                }
 
                 if let Cow::Owned(next_actor_state) = state {
-                    next_sys_state.actor_states[index] = Arc::new(next_actor_state);
+                    next_sys_state.actor_states[index] = self.share_actor_state(next_actor_state);
+                }
+                self.process_commands(id, out, &mut next_sys_state);
+                self.record_phase(&mut next_sys_state);
+                Some(next_sys_state)
+            },
+            SystemAction::ChooseRandom { id, value } => {
+                // Always results in a new state, as the choice is consumed.
+                let position = last_sys_state.random_choices.iter()
+                    .position(|(chooser, range)| *chooser == id && range.contains(&value))?;
+                let index = usize::from(id);
+                let mut state = Cow::Borrowed(&*last_sys_state.actor_states[index]);
+                let mut out = Out::new();
+                self.actor(last_sys_state, index)?.on_random(id, &mut state, value, &mut out);
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.random_choices.remove(position);
+                if let Cow::Owned(next_actor_state) = state {
+                    next_sys_state.actor_states[index] = self.share_actor_state(next_actor_state);
+                }
+                self.process_commands(id, out, &mut next_sys_state);
+                self.record_phase(&mut next_sys_state);
+                Some(next_sys_state)
+            },
+            SystemAction::UpdateConfig { dst: id, config } => {
+                // Always results in a new state, as the update is consumed even if ignored.
+                let index = usize::from(id);
+                let mut state = Cow::Borrowed(&*last_sys_state.actor_states[index]);
+                let mut out = Out::new();
+                self.actor(last_sys_state, index)?.on_config_update(id, &mut state, config, &mut out);
+                let mut next_sys_state = last_sys_state.clone();
+                next_sys_state.config_update_counts[index] += 1;
+                if let Cow::Owned(next_actor_state) = state {
+                    next_sys_state.actor_states[index] = self.share_actor_state(next_actor_state);
                 }
                 self.process_commands(id, out, &mut next_sys_state);
+                self.record_phase(&mut next_sys_state);
+                Some(next_sys_state)
+            },
+            SystemAction::Crash(id) => {
+                let index = usize::from(id);
+                let mut next_sys_state = last_sys_state.clone();
+                if next_sys_state.crashed.len() <= index {
+                    next_sys_state.crashed.resize(index + 1, false);
+                }
+                next_sys_state.crashed[index] = true;
+                if let Some(is_timer_set) = next_sys_state.is_timer_set.get_mut(index) {
+                    *is_timer_set = false;
+                }
+                next_sys_state.timer_order.retain(|(timer_id, _)| *timer_id != id);
+                next_sys_state.pending_sends.retain(|env| env.src != id);
+                next_sys_state.random_choices.retain(|(chooser, _)| *chooser != id);
+
+                // Monitors involving the crashed actor are consumed, and watchers are notified.
+                let watchers: Vec<Id> = next_sys_state.monitors.iter()
+                    .filter(|(watcher, peer)| *peer == id && *watcher != id)
+                    .map(|(watcher, _)| *watcher)
+                    .collect();
+                next_sys_state.monitors.retain(|(watcher, peer)| *watcher != id && *peer != id);
+                for watcher in watchers {
+                    self.notify_peer_down(&mut next_sys_state, watcher, id);
+                }
+                self.record_phase(&mut next_sys_state);
                 Some(next_sys_state)
             },
         }
@@ -244,6 +737,9 @@ impl<S: System> Model for SystemModel<S> {
             SystemAction::Drop(env) => {
                 Some(format!("DROP: {:?}", env))
             },
+            SystemAction::Transmit(env) => {
+                Some(format!("TRANSMIT: {:?}", env))
+            },
             SystemAction::Deliver { src, dst: id, msg } => {
                 let index = usize::from(id);
                 let last_actor_state = match last_state.actor_states.get(index) {
@@ -252,7 +748,7 @@ impl<S: System> Model for SystemModel<S> {
                 };
                 let mut actor_state = Cow::Borrowed(last_actor_state);
                 let mut out = Out::new();
-                self.actors[index].on_msg(id, &mut actor_state, src, msg, &mut out);
+                self.actor(last_state, index)?.on_msg(id, &mut actor_state, src, msg, &mut out);
                 Some(format!("{}", ActorStep {
                     last_state: last_actor_state,
                     next_state: match actor_state {
@@ -270,7 +766,46 @@ impl<S: System> Model for SystemModel<S> {
                 };
                 let mut actor_state = Cow::Borrowed(last_actor_state);
                 let mut out = Out::new();
-                self.actors[index].on_timeout(id, &mut actor_state, &mut out);
+                self.actor(last_state, index)?.on_timeout(id, &mut actor_state, &mut out);
+                Some(format!("{}", ActorStep {
+                    last_state: last_actor_state,
+                    next_state: match actor_state {
+                        Cow::Borrowed(_) => None,
+                        Cow::Owned(next_actor_state) => Some(next_actor_state),
+                    },
+                    out,
+                }))
+            },
+            SystemAction::UpdateConfig { dst: id, config } => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let mut actor_state = Cow::Borrowed(last_actor_state);
+                let mut out = Out::new();
+                self.actor(last_state, index)?.on_config_update(id, &mut actor_state, config, &mut out);
+                Some(format!("{}", ActorStep {
+                    last_state: last_actor_state,
+                    next_state: match actor_state {
+                        Cow::Borrowed(_) => None,
+                        Cow::Owned(next_actor_state) => Some(next_actor_state),
+                    },
+                    out,
+                }))
+            },
+            SystemAction::Crash(id) => {
+                Some(format!("CRASH: {:?}", id))
+            },
+            SystemAction::ChooseRandom { id, value } => {
+                let index = usize::from(id);
+                let last_actor_state = match last_state.actor_states.get(index) {
+                    None => return None,
+                    Some(last_actor_state) => &**last_actor_state,
+                };
+                let mut actor_state = Cow::Borrowed(last_actor_state);
+                let mut out = Out::new();
+                self.actor(last_state, index)?.on_random(id, &mut actor_state, value, &mut out);
                 Some(format!("{}", ActorStep {
                     last_state: last_actor_state,
                     next_state: match actor_state {
@@ -283,17 +818,164 @@ impl<S: System> Model for SystemModel<S> {
         }
     }
 
-    /// Draws a sequence diagram for the actor system.
+    /// Lists only the fields that changed: a diff of each changed actor state, the envelopes
+    /// added to or removed from the network, and a diff of any other changed field.
+    fn display_diff(&self, last_state: &Self::State, next_state: &Self::State) -> Option<String>
+    where Self::State: Debug
+    {
+        fn push_field(diff: &mut String, name: &str, field_diff: &str) {
+            diff.push_str(&format!("  {}:\n", name));
+            for line in field_diff.lines() {
+                let (marker, line) = line.split_at(2);
+                diff.push_str(&format!("{}    {}\n", marker, line));
+            }
+        }
+        fn push_field_if_changed(diff: &mut String, name: &str, last: &impl Debug, next: &impl Debug) {
+            let (last, next) = (format!("{:#?}", last), format!("{:#?}", next));
+            if last != next {
+                push_field(diff, name, &crate::util::diff_lines(&last, &next));
+            }
+        }
+
+        let mut diff = String::new();
+        let actor_count = last_state.actor_states.len().max(next_state.actor_states.len());
+        for index in 0..actor_count {
+            let last = last_state.actor_states.get(index).map(|s| format!("{:#?}", s));
+            let next = next_state.actor_states.get(index).map(|s| format!("{:#?}", s));
+            if last != next {
+                push_field(
+                    &mut diff,
+                    &format!("actor_states[{}]", index),
+                    &crate::util::diff_lines(
+                        last.as_deref().unwrap_or(""),
+                        next.as_deref().unwrap_or("")));
+            }
+        }
+        push_field_if_changed(&mut diff, "history", &last_state.history, &next_state.history);
+        push_field_if_changed(&mut diff, "phase", &last_state.phase, &next_state.phase);
+        push_field_if_changed(&mut diff, "is_timer_set", &last_state.is_timer_set, &next_state.is_timer_set);
+        push_field_if_changed(
+            &mut diff, "config_update_counts",
+            &last_state.config_update_counts, &next_state.config_update_counts);
+        push_field_if_changed(&mut diff, "send_order", &last_state.send_order, &next_state.send_order);
+        push_field_if_changed(
+            &mut diff, "overtaken_counts",
+            &last_state.overtaken_counts, &next_state.overtaken_counts);
+        push_field_if_changed(&mut diff, "spawned", &last_state.spawned, &next_state.spawned);
+        push_field_if_changed(&mut diff, "crashed", &last_state.crashed, &next_state.crashed);
+        push_field_if_changed(&mut diff, "monitors", &last_state.monitors, &next_state.monitors);
+        push_field_if_changed(
+            &mut diff, "delivery_counts", &last_state.delivery_counts, &next_state.delivery_counts);
+        push_field_if_changed(
+            &mut diff, "pending_sends", &last_state.pending_sends, &next_state.pending_sends);
+
+        // The network is unordered, so envelopes are compared as sets rather than line by line.
+        let mut network_diff = String::new();
+        let mut removed: Vec<_> = last_state.network.iter()
+            .filter(|env| !next_state.network.contains(env))
+            .map(|env| format!("- {:?}\n", env))
+            .collect();
+        let mut added: Vec<_> = next_state.network.iter()
+            .filter(|env| !last_state.network.contains(env))
+            .map(|env| format!("+ {:?}\n", env))
+            .collect();
+        removed.sort();
+        added.sort();
+        for line in removed.into_iter().chain(added) { network_diff.push_str(&line); }
+        if !network_diff.is_empty() {
+            push_field(&mut diff, "network", &network_diff);
+        }
+
+        Some(diff)
+    }
+
+    /// The recipient of a delivery, timeout, or configuration update, or the sender of a
+    /// transmission. Dropped messages are attributed to the environment.
+    fn action_context(&self, action: &Self::Action) -> Option<usize> {
+        match action {
+            SystemAction::Transmit(env) => Some(usize::from(env.src)),
+            SystemAction::Deliver { dst, .. } => Some(usize::from(*dst)),
+            SystemAction::Timeout(id) => Some(usize::from(*id)),
+            SystemAction::UpdateConfig { dst, .. } => Some(usize::from(*dst)),
+            SystemAction::Crash(id) => Some(usize::from(*id)),
+            SystemAction::ChooseRandom { id, .. } => Some(usize::from(*id)),
+            SystemAction::Drop(_) => None,
+        }
+    }
+
+    /// The kind of action along with the recipient, so that coverage is tracked per actor.
+    fn action_kind(&self, action: &Self::Action) -> String
+    where Self::Action: Debug,
+    {
+        match action {
+            SystemAction::Deliver { dst, .. } => format!("Deliver to {:?}", dst),
+            SystemAction::Drop(env) => format!("Drop to {:?}", env.dst),
+            SystemAction::Transmit(env) => format!("Transmit from {:?}", env.src),
+            SystemAction::Timeout(id) => format!("Timeout at {:?}", id),
+            SystemAction::UpdateConfig { dst, .. } => format!("UpdateConfig to {:?}", dst),
+            SystemAction::Crash(id) => format!("Crash at {:?}", id),
+            SystemAction::ChooseRandom { id, .. } => format!("ChooseRandom at {:?}", id),
+        }
+    }
+
+    /// Draws a sequence diagram for the actor system, including dropped messages, redelivered
+    /// duplicate messages, and timers. Below the diagram, each message that can be dropped,
+    /// timeout that can fire, or actor that can crash in the last state is drawn as a link to the
+    /// resulting step in the explorer.
     fn as_svg(&self, path: Path<Self::State, Self::Action>) -> Option<String> {
-        use std::collections::HashMap;
+        use std::collections::{HashMap, HashSet};
         use std::fmt::Write;
 
         let plot = |x, y| (x as u64 * 100, y as u64 * 30);
+        // Wraps long labels across lines so that they do not obscure one another.
+        fn write_label(svg: &mut String, x: u64, y: u64, label: &str) {
+            const MAX_LINE_LEN: usize = 40;
+            let chars: Vec<char> = label.chars().collect();
+            if chars.len() <= MAX_LINE_LEN {
+                writeln!(svg, "<text x='{}' y='{}' class='svg-event-label'>{}</text>", x, y, label).unwrap();
+                return;
+            }
+            write!(svg, "<text x='{}' y='{}' class='svg-event-label'>", x, y).unwrap();
+            for (i, line) in chars.chunks(MAX_LINE_LEN).enumerate() {
+                write!(svg, "<tspan x='{}' dy='{}'>{}</tspan>",
+                       x, if i == 0 { "0" } else { "1.2em" }, line.iter().collect::<String>()).unwrap();
+            }
+            writeln!(svg, "</text>").unwrap();
+        }
         let actor_count = path.last_state().actor_states.len();
         let path = path.into_vec();
 
+        // Controls for injecting a fault into the last state, each linking to the resulting step
+        // in the explorer.
+        let mut fault_controls = Vec::new();
+        {
+            let mut url = "#/steps".to_string();
+            for (state, _) in &path {
+                write!(&mut url, "/{}", fingerprint(state)).unwrap();
+            }
+            let last_state = &path.last().unwrap().0;
+            let mut actions = Vec::new();
+            self.actions(last_state, &mut actions);
+            for action in actions {
+                let (index, label) = match &action {
+                    SystemAction::Drop(env) => (usize::from(env.dst), format!("Drop {:?}", env.msg)),
+                    SystemAction::Timeout(id) => (usize::from(*id), "Fire timeout".to_string()),
+                    SystemAction::Crash(id) => (usize::from(*id), "Crash".to_string()),
+                    _ => continue,
+                };
+                if actor_count <= index { continue }
+                if let Some(next_state) = self.next_state(last_state, action) {
+                    let href = format!("{}/{}", url, fingerprint(&next_state));
+                    fault_controls.push((index, label, href));
+                }
+            }
+        }
+        let control_rows = (0..actor_count)
+            .map(|index| fault_controls.iter().filter(|(i, _, _)| *i == index).count())
+            .max().unwrap_or(0);
+
         // SVG wrapper.
-        let (mut svg_w, svg_h) = plot(actor_count, path.len());
+        let (mut svg_w, svg_h) = plot(actor_count, path.len() + control_rows);
         svg_w += 300; // KLUDGE: extra width for event labels
         let mut svg = format!("<svg version='1.1' baseProfile='full' \
                                     width='{}' height='{}' viewbox='-20 -20 {} {}' \
@@ -318,100 +1000,632 @@ impl<S: System> Model for SystemModel<S> {
                    x1, y1, actor_index).unwrap();
         }
 
-        // Arrow for each delivery. Circle for other events.
+        // Arrow for each delivery (doubled for redelivery of a duplicated message), crossed-out
+        // dangling arrow for each dropped message, loop from when a timer is set to when it
+        // fires, and circle for other events.
         let mut send_time  = HashMap::new();
+        let mut timer_set_time = HashMap::new();
+        let mut delivered = HashSet::new();
         for (time, (state, action)) in path.clone().into_iter().enumerate() {
             let time = time + 1; // action is for the next step
+            let mut out = Out::new();
+            let mut actor_id = None;
             match action {
                 Some(SystemAction::Deliver { src, dst: id, msg }) => {
                     let src_time = *send_time.get(&(src, id, msg.clone())).unwrap_or(&0);
                     let (x1, y1) = plot(src.into(), src_time);
                     let (x2, y2) = plot(id.into(),  time);
-                    writeln!(&mut svg, "<line x1='{}' x2='{}' y1='{}' y2='{}' marker-end='url(#arrow)' class='svg-event-shape' />",
-                           x1, x2, y1, y2).unwrap();
+                    if delivered.insert((src, id, msg.clone())) {
+                        writeln!(&mut svg, "<line x1='{}' x2='{}' y1='{}' y2='{}' marker-end='url(#arrow)' class='svg-event-shape' />",
+                               x1, x2, y1, y2).unwrap();
+                    } else {
+                        for (y1, y2) in [(y1, y2), (y1 + 4, y2 + 4)] {
+                            writeln!(&mut svg, "<line x1='{}' x2='{}' y1='{}' y2='{}' marker-end='url(#arrow)' class='svg-event-shape svg-duplicate-shape' />",
+                                   x1, x2, y1, y2).unwrap();
+                        }
+                    }
 
-                    // Track sends to facilitate building arrows.
                     let index = usize::from(id);
-                    if let Some(actor_state) = state.actor_states.get(index) {
+                    if let (Some(actor_state), Some(actor)) = (state.actor_states.get(index), self.actor(&state, index)) {
                         let mut actor_state = Cow::Borrowed(&**actor_state);
-                        let mut out = Out::new();
-                        self.actors[index].on_msg(id, &mut actor_state, src, msg, &mut out);
-                        for command in out {
-                            if let Command::Send(dst, msg) = command {
-                                send_time.insert((id, dst, msg), time);
-                            }
-                        }
+                        actor.on_msg(id, &mut actor_state, src, msg, &mut out);
+                        actor_id = Some(id);
                     }
                 }
-                Some(SystemAction::Timeout(actor_id)) => {
-                    writeln!(&mut svg, "<circle cx='{}' cy='{}' r='5' class='svg-event-shape' />",
-                           actor_id, time).unwrap();
+                Some(SystemAction::Drop(Envelope { src, dst, msg })) => {
+                    let src_time = *send_time.get(&(src, dst, msg)).unwrap_or(&0);
+                    let (x1, y1) = plot(src.into(), src_time);
+                    let (x2, y2) = plot(dst.into(), time);
+                    let x2 = (x1 + x2) / 2; // dangles halfway to the recipient
+                    writeln!(&mut svg, "<line x1='{}' x2='{}' y1='{}' y2='{}' class='svg-event-shape svg-dropped-shape' />",
+                           x1, x2, y1, y2).unwrap();
+                    writeln!(&mut svg, "<path d='M {} {} l 10 10 m 0 -10 l -10 10' class='svg-event-shape' />",
+                           x2 - 5, y2 - 5).unwrap();
                 }
-                _ => {}
-            }
+                Some(SystemAction::Timeout(id)) => {
+                    let set_time = *timer_set_time.get(&id).unwrap_or(&0);
+                    let (x, y1) = plot(id.into(), set_time);
+                    let (_, y2) = plot(id.into(), time);
+                    writeln!(&mut svg, "<path d='M {} {} C {} {}, {} {}, {} {}' marker-end='url(#arrow)' class='svg-timer-shape' />",
+                           x, y1, x + 30, y1, x + 30, y2, x, y2).unwrap();
+
+                    let index = usize::from(id);
+                    if let (Some(actor_state), Some(actor)) = (state.actor_states.get(index), self.actor(&state, index)) {
+                        let mut actor_state = Cow::Borrowed(&**actor_state);
+                        actor.on_timeout(id, &mut actor_state, &mut out);
+                        actor_id = Some(id);
+                    }
+                }
+                Some(SystemAction::Transmit(Envelope { src, dst, msg })) => {
+                    send_time.insert((src, dst, msg), time);
+                    let (x, y) = plot(src.into(), time);
+                    writeln!(&mut svg, "<circle cx='{}' cy='{}' r='5' class='svg-event-shape' />",
+                           x, y).unwrap();
+                }
+                Some(SystemAction::UpdateConfig { dst: actor_id, .. })
+                | Some(SystemAction::Crash(actor_id)) => {
+                    let (x, y) = plot(actor_id.into(), time);
+                    writeln!(&mut svg, "<circle cx='{}' cy='{}' r='5' class='svg-event-shape' />",
+                           x, y).unwrap();
+                }
+                Some(SystemAction::ChooseRandom { id, value }) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(&mut svg, "<circle cx='{}' cy='{}' r='5' class='svg-event-shape' />",
+                           x, y).unwrap();
+
+                    let index = usize::from(id);
+                    if let (Some(actor_state), Some(actor)) = (state.actor_states.get(index), self.actor(&state, index)) {
+                        let mut actor_state = Cow::Borrowed(&**actor_state);
+                        actor.on_random(id, &mut actor_state, value, &mut out);
+                        actor_id = Some(id);
+                    }
+                }
+                None => {}
+            }
+
+            // Track sends and timers to facilitate building arrows and loops.
+            if let Some(id) = actor_id {
+                for command in out {
+                    match command {
+                        Command::Send(dst, msg) => { send_time.insert((id, dst, msg), time); }
+                        Command::Broadcast(dsts, msg) => {
+                            for dst in dsts { send_time.insert((id, dst, msg.clone()), time); }
+                        }
+                        Command::SetTimer(_) => { timer_set_time.insert(id, time); }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Horizontal line for each phase boundary.
+        let phase_boundaries: Vec<_> = path.windows(2).enumerate()
+            .filter(|(_, w)| w[0].0.phase != w[1].0.phase)
+            .map(|(time, w)| (time + 1, w[1].0.phase.unwrap_or("")))
+            .collect();
+        for (time, _) in &phase_boundaries {
+            let (x1, y) = plot(0, *time);
+            let (x2, _) = plot(actor_count, *time);
+            writeln!(&mut svg, "<line x1='{}' y1='{}' x2='{}' y2='{}' class='svg-phase-boundary' />",
+                   x1, y, x2, y).unwrap();
         }
 
         // Handle event labels last to ensure they are drawn over shapes.
+        for (time, phase) in phase_boundaries {
+            let (x, y) = plot(actor_count, time);
+            writeln!(&mut svg, "<text x='{}' y='{}' class='svg-phase-label'>{}</text>",
+                   x, y, phase).unwrap();
+        }
+        let path_len = path.len();
         for (time, (_state, action)) in path.into_iter().enumerate() {
             let time = time + 1; // action is for the next step
             match action {
                 Some(SystemAction::Deliver { dst: id, msg, .. }) => {
                     let (x, y) = plot(id.into(), time);
-                    writeln!(&mut svg, "<text x='{}' y='{}' class='svg-event-label'>{:?}</text>",
-                           x, y, msg).unwrap();
+                    write_label(&mut svg, x, y, &format!("{:?}", msg));
+                }
+                Some(SystemAction::Drop(Envelope { src, dst, msg })) => {
+                    let (x1, y) = plot(src.into(), time);
+                    let (x2, _) = plot(dst.into(), time);
+                    write_label(&mut svg, (x1 + x2) / 2, y, &format!("Dropped {:?}", msg));
                 }
                 Some(SystemAction::Timeout(id)) => {
                     let (x, y) = plot(id.into(), time);
                     writeln!(&mut svg, "<text x='{}' y='{}' class='svg-event-label'>Timeout</text>",
                            x, y).unwrap();
                 }
+                Some(SystemAction::UpdateConfig { dst: id, config }) => {
+                    let (x, y) = plot(id.into(), time);
+                    write_label(&mut svg, x, y, &format!("Config: {:?}", config));
+                }
+                Some(SystemAction::Crash(id)) => {
+                    let (x, y) = plot(id.into(), time);
+                    writeln!(&mut svg, "<text x='{}' y='{}' class='svg-event-label'>Crash</text>",
+                           x, y).unwrap();
+                }
+                Some(SystemAction::ChooseRandom { id, value }) => {
+                    let (x, y) = plot(id.into(), time);
+                    write_label(&mut svg, x, y, &format!("Random: {}", value));
+                }
                 _ => {}
             }
         }
+        let mut rows = vec![path_len; actor_count];
+        for (index, label, href) in fault_controls {
+            rows[index] += 1;
+            let (x, y) = plot(index, rows[index]);
+            writeln!(&mut svg, "<a href='{}' class='svg-fault-control'><text x='{}' y='{}'>{}</text></a>",
+                   href, x, y, label).unwrap();
+        }
 
         writeln!(&mut svg, "</svg>").unwrap();
         Some(svg)
     }
 
     fn properties(&self) -> Vec<Property<Self>> {
-        self.system.properties()
+        let mut properties = self.system.properties();
+        if self.system.deadlock_freedom() {
+            properties.push(Property::deadlock_free());
+        }
+        properties
     }
 
     fn within_boundary(&self, state: &Self::State) -> bool {
         self.system.within_boundary(state)
     }
+
+    fn is_terminal(&self, state: &Self::State) -> bool {
+        self.system.is_terminal(state)
+    }
 }
 
 impl<S: System> SystemModel<S> {
+    /// Checks that actions observed while running the system's actors (for instance via
+    /// [`Replay::actions`]) are a valid path of this model, i.e. that each action is enabled in
+    /// the state reached by the preceding actions. Such a check catches divergence between the
+    /// model and the implementation, for example when a message is delivered twice although the
+    /// model assumes that the network does not duplicate messages.
+    ///
+    /// Observed actions that have no effect (which the model omits) leave the state unchanged
+    /// and are omitted from the resulting path. If the model has multiple initial states, then
+    /// the first one from which every action is valid is chosen.
+    #[allow(clippy::type_complexity)]
+    pub fn check_conformance(&self, actions: impl IntoIterator<Item = SystemAction<<S::Actor as Actor>::Msg>>)
+        -> Result<Path<SystemState<S>, SystemAction<<S::Actor as Actor>::Msg>>,
+                  Nonconformance<SystemState<S>, SystemAction<<S::Actor as Actor>::Msg>>>
+    {
+        let actions: Vec<_> = actions.into_iter().collect();
+        let mut furthest: Option<Nonconformance<_, _>> = None;
+        for init_state in self.init_states() {
+            let mut steps = Vec::new();
+            let mut last_state = init_state;
+            let mut divergence = None;
+            for (index, action) in actions.iter().enumerate() {
+                let mut enabled = Vec::new();
+                self.enabled_actions(&last_state, &mut enabled);
+                if !enabled.contains(action) {
+                    divergence = Some(index);
+                    break;
+                }
+                if let Some(next_state) = self.next_state(&last_state, action.clone()) {
+                    steps.push((last_state, Some(action.clone())));
+                    last_state = next_state;
+                }
+            }
+            steps.push((last_state, None));
+            let index = match divergence {
+                None => return Ok(Path::from_vec(steps)),
+                Some(index) => index,
+            };
+            if !matches!(&furthest, Some(f) if f.index >= index) {
+                furthest = Some(Nonconformance {
+                    index,
+                    action: actions[index].clone(),
+                    path: Path::from_vec(steps),
+                });
+            }
+        }
+        Err(furthest.expect("model has an initial state"))
+    }
+
+    /// Indicates every action that the system can take from a state, whereas [`Model::actions`]
+    /// may omit some per [`System::partial_order_reduction`].
+    fn enabled_actions(&self, state: &SystemState<S>, actions: &mut Vec<SystemAction<<S::Actor as Actor>::Msg>>) {
+        for env in &state.network {
+            // option 1: message is lost
+            if self.system.link_quality(env.src, env.dst).lossy == LossyNetwork::Yes {
+                actions.push(SystemAction::Drop(env.clone()));
+            }
+
+            // option 2: message is delivered
+            if usize::from(env.dst) < state.actor_states.len()
+                    && !state.is_crashed(env.dst)
+                    && self.is_within_reordering_window(state, env)
+                    && self.is_within_overtaking_limit(state, env)
+                    && self.system.should_deliver(state, env.src, env.dst, &env.msg) {
+                actions.push(SystemAction::Deliver { src: env.src, dst: env.dst, msg: env.msg.clone() });
+            }
+        }
+
+        // option 3: actor timeout
+        for (index, &is_scheduled) in state.is_timer_set.iter().enumerate() {
+            if !is_scheduled { continue }
+            let id = Id::from(index);
+            let is_enabled = match self.timeout_ordering {
+                TimeoutOrdering::Asynchronous => true,
+                TimeoutOrdering::AfterDeliveries => {
+                    !state.network.iter().any(|env| env.dst == id)
+                }
+                TimeoutOrdering::BoundedDrift(ratio) => {
+                    let mut earlier = state.timer_order.iter()
+                        .take_while(|(timer_id, _)| *timer_id != id);
+                    match state.timer_order.iter().find(|(timer_id, _)| *timer_id == id) {
+                        None => true,
+                        Some((_, duration)) => !earlier.any(|(_, earlier_duration)| {
+                            earlier_duration.end.mul_f64(ratio) <= duration.start
+                        }),
+                    }
+                }
+            };
+            if is_enabled {
+                actions.push(SystemAction::Timeout(id));
+            }
+        }
+
+        // option 4: configuration update
+        for (index, &count) in state.config_update_counts.iter().enumerate() {
+            if state.is_crashed(Id::from(index)) { continue }
+            if let Some(config) = self.config_update(Id::from(index), count) {
+                actions.push(SystemAction::UpdateConfig { dst: Id::from(index), config: config.clone() });
+            }
+        }
+
+        // option 5: next message of a non-atomic broadcast
+        for (position, env) in state.pending_sends.iter().enumerate() {
+            if !state.is_crashed(env.src)
+                    && !state.pending_sends[..position].iter().any(|e| e.src == env.src) {
+                actions.push(SystemAction::Transmit(env.clone()));
+            }
+        }
+
+        // option 6: actor crash
+        if state.crashed.iter().filter(|&&is_crashed| is_crashed).count() < self.max_crashes {
+            for index in 0..state.actor_states.len() {
+                if !state.is_crashed(Id::from(index)) {
+                    actions.push(SystemAction::Crash(Id::from(index)));
+                }
+            }
+        }
+
+        // option 7: each value of an actor's earliest random choice
+        for (position, (id, range)) in state.random_choices.iter().enumerate() {
+            if !state.random_choices[..position].iter().any(|(chooser, _)| chooser == id) {
+                for value in range.clone() {
+                    actions.push(SystemAction::ChooseRandom { id: *id, value });
+                }
+            }
+        }
+    }
+
+    /// Looks up the actor at an index, which may have been spawned at runtime, in which case it is
+    /// recreated from its parent per [`Actor::spawned_actor`].
+    fn actor(&self, state: &SystemState<S>, index: usize) -> Option<ActorRef<'_, S::Actor>> {
+        if let Some(actor) = self.actors.get(index) {
+            return Some(ActorRef::Initial(actor));
+        }
+        let (parent, spec) = state.spawned.get(index - self.actors.len())?;
+        let parent = self.actor(state, usize::from(*parent))?;
+        parent.spawned_actor(spec).map(ActorRef::Spawned)
+    }
+
+    /// The initial state before any [`System::init_state_perturbations`] apply.
+    pub(crate) fn unperturbed_init_state(&self) -> SystemState<S> {
+        let mut init_sys_state = SystemState {
+            actor_states: Vec::with_capacity(self.actors.len()),
+            network: Network::new(),
+            is_timer_set: Vec::new(),
+            timer_order: Vec::new(),
+            config_update_counts: Vec::new(),
+            send_order: Vec::new(),
+            overtaken_counts: Vec::new(),
+            spawned: Vec::new(),
+            crashed: Vec::new(),
+            monitors: BTreeSet::new(),
+            delivery_counts: HashableHashMap::with_hasher(BuildStableHasher),
+            pending_sends: Vec::new(),
+            random_choices: Vec::new(),
+            history: self.system.init_history(),
+            phase: None,
+            projected_fingerprint: None,
+        };
+        if !self.config_updates.is_empty() {
+            init_sys_state.config_update_counts.resize(self.actors.len(), 0);
+        }
+
+        // init the network
+        for e in self.init_network.clone() {
+            self.send(e, &mut init_sys_state);
+        }
+
+        // init each actor, then process commands, as spawned actors are assigned later IDs
+        let mut outs = Vec::with_capacity(self.actors.len());
+        for (index, actor) in self.actors.iter().enumerate() {
+            let mut out = Out::new();
+            let state = actor.on_start(Id::from(index), &mut out);
+            init_sys_state.actor_states.push(self.share_actor_state(state));
+            outs.push(out);
+        }
+        for (index, out) in outs.into_iter().enumerate() {
+            self.process_commands(Id::from(index), out, &mut init_sys_state);
+        }
+        init_sys_state
+    }
+
+    /// Wraps an actor state for a [`SystemState`], sharing an identical copy if interning.
+    fn share_actor_state(&self, state: <S::Actor as Actor>::State) -> Arc<<S::Actor as Actor>::State> {
+        match &self.actor_state_pool {
+            None => Arc::new(state),
+            Some(pool) => pool.intern(state),
+        }
+    }
+
+    /// Notifies an actor that a monitored peer is down per [`Actor::on_peer_down`].
+    fn notify_peer_down(&self, state: &mut SystemState<S>, id: Id, peer: Id) {
+        let index = usize::from(id);
+        let actor = match self.actor(state, index) {
+            None => return,
+            Some(actor) => actor,
+        };
+        let mut actor_state = Cow::Borrowed(&*state.actor_states[index]);
+        let mut out = Out::new();
+        actor.on_peer_down(id, &mut actor_state, peer, &mut out);
+        if let Cow::Owned(next_actor_state) = actor_state {
+            state.actor_states[index] = self.share_actor_state(next_actor_state);
+        }
+        self.process_commands(id, out, state);
+    }
+
+    /// Looks up the configuration update at a particular position in an actor's sequence.
+    fn config_update(&self, id: Id, position: usize) -> Option<&<S::Actor as Actor>::Msg> {
+        self.config_updates.iter()
+            .filter(|(dst, _)| *dst == id)
+            .nth(position)
+            .map(|(_, config)| config)
+    }
+
+    /// Restricts `actions` to those of a single actor that no other active actor (i.e. an actor
+    /// with pending actions) can influence, either directly or via intermediaries. Such an actor's
+    /// actions are independent of every action that other actors can take before it acts, so
+    /// deferring those actions still reaches the same states once the actor is done (a
+    /// "persistent set").
+    fn reduce(&self, actions: &mut Vec<SystemAction<<S::Actor as Actor>::Msg>>) {
+        let actor_count = self.actors.len();
+        let owner = |action: &SystemAction<_>| usize::from(match action {
+            SystemAction::Deliver { dst, .. } => *dst,
+            SystemAction::Drop(env) => env.dst,
+            SystemAction::Transmit(env) => env.src,
+            SystemAction::Timeout(id) => *id,
+            SystemAction::UpdateConfig { dst, .. } => *dst,
+            SystemAction::Crash(id) => *id,
+            SystemAction::ChooseRandom { id, .. } => *id,
+        });
+        let mut action_counts = vec![0; actor_count];
+        for action in actions.iter() {
+            if let Some(count) = action_counts.get_mut(owner(action)) { *count += 1; }
+        }
+        if actions.iter().any(|a| owner(a) >= actor_count) { return }
+
+        // Actors can block one another via shared network capacity or timer order, which
+        // `may_send` does not capture either.
+        if self.max_in_flight.is_some() { return }
+        if let TimeoutOrdering::BoundedDrift(_) = self.timeout_ordering { return }
+
+        // A crash notifies monitoring actors, which `may_send` does not capture.
+        if actions.iter().any(|a| matches!(a, SystemAction::Crash(_))) { return }
+
+        let is_active = |index: usize| action_counts[index] > 0;
+        let can_influence = |src: usize, dst: usize| {
+            // Depth-first search of the `may_send` graph.
+            let mut visited = vec![false; actor_count];
+            let mut pending = vec![src];
+            while let Some(index) = pending.pop() {
+                for (next, is_visited) in visited.iter_mut().enumerate() {
+                    if *is_visited || !self.system.may_send(Id::from(index), Id::from(next)) {
+                        continue;
+                    }
+                    if next == dst { return true }
+                    *is_visited = true;
+                    pending.push(next);
+                }
+            }
+            false
+        };
+        let chosen = (0..actor_count)
+            .filter(|&index| is_active(index))
+            .filter(|&index| {
+                (0..actor_count).all(|other| {
+                    other == index || !is_active(other) || !can_influence(other, index)
+                })
+            })
+            .min_by_key(|&index| action_counts[index]);
+        if let Some(chosen) = chosen {
+            actions.retain(|action| owner(action) == chosen);
+        }
+    }
+
+    /// Updates the phase of a state per [`System::record_phase`] and its projected fingerprint
+    /// per [`System::fingerprint_projection`].
+    fn record_phase(&self, state: &mut SystemState<S>) {
+        if let Some(phase) = self.system.record_phase(state) {
+            state.phase = Some(phase);
+        }
+        state.projected_fingerprint = None; // in case the projection hashes the whole state
+        state.projected_fingerprint = self.system.fingerprint_projection(state)
+            .map(|projection| fingerprint(&projection).get());
+    }
+
+    /// Adds a message to the network, also tracking its order if reordering is bounded.
+    fn send(&self, env: Envelope<<S::Actor as Actor>::Msg>, state: &mut SystemState<S>) {
+        let is_ordered = self.reordering_window.is_some() || self.max_overtaking.is_some()
+            || self.system.link_quality(env.src, env.dst).ordered;
+        if is_ordered && !state.network.contains(&env) {
+            state.send_order.push(env.clone());
+            state.overtaken_counts.push(0);
+        }
+        state.network.insert(env);
+    }
+
+    /// Sends a message output by an actor, recording it in the history unless the network is
+    /// full.
+    fn transmit(&self, env: Envelope<<S::Actor as Actor>::Msg>, state: &mut SystemState<S>) {
+        if let Some(history) = self.system.record_msg_out(&state.history, env.src, env.dst, &env.msg) {
+            state.history = history;
+        }
+        if self.is_network_full(state, &env) {
+            if let Some(history) = self.system.record_send_blocked(&state.history, env.src, env.dst, &env.msg) {
+                state.history = history;
+            }
+            return;
+        }
+        self.send(env, state);
+    }
+
+    /// Indicates whether sending a message would exceed the [`System::max_in_flight`]. Resending a
+    /// message that is already on the network never does.
+    fn is_network_full(&self, state: &SystemState<S>, env: &Envelope<<S::Actor as Actor>::Msg>) -> bool {
+        match self.max_in_flight {
+            None => false,
+            Some(max) => state.network.len() >= max && !state.network.contains(env),
+        }
+    }
+
+    /// Removes a message from [`SystemState::send_order`]. A delivered message overtakes every
+    /// earlier message on the same channel.
+    fn remove_from_send_order(state: &mut SystemState<S>, env: &Envelope<<S::Actor as Actor>::Msg>, is_delivered: bool) {
+        let position = match state.send_order.iter().position(|e| e == env) {
+            None => return,
+            Some(position) => position,
+        };
+        state.send_order.remove(position);
+        state.overtaken_counts.remove(position);
+        if !is_delivered { return }
+        for (e, count) in state.send_order.iter().zip(&mut state.overtaken_counts).take(position) {
+            if e.src == env.src && e.dst == env.dst { *count += 1 }
+        }
+    }
+
+    /// Indicates whether a message can be delivered given the [`System::max_overtaking`].
+    fn is_within_overtaking_limit(&self, state: &SystemState<S>, env: &Envelope<<S::Actor as Actor>::Msg>) -> bool {
+        let limit = match self.max_overtaking {
+            None => return true,
+            Some(limit) => limit,
+        };
+        for (e, count) in state.send_order.iter().zip(&state.overtaken_counts) {
+            if e == env { return true }
+            if e.src == env.src && e.dst == env.dst && *count >= limit { return false }
+        }
+        true // already delivered, so this is a duplicate
+    }
+
+    /// Indicates whether a message can be delivered given the [`System::reordering_window`] or an
+    /// ordered [`System::link_quality`].
+    fn is_within_reordering_window(&self, state: &SystemState<S>, env: &Envelope<<S::Actor as Actor>::Msg>) -> bool {
+        let window = if self.system.link_quality(env.src, env.dst).ordered {
+            0
+        } else {
+            match self.reordering_window {
+                None => return true,
+                Some(window) => window,
+            }
+        };
+        let mut earlier_count = 0;
+        for e in &state.send_order {
+            if e == env { return earlier_count <= window }
+            if e.src == env.src && e.dst == env.dst { earlier_count += 1 }
+        }
+        true // already delivered, so this is a duplicate
+    }
+
     /// Updates the actor state, sends messages, and configures the timer.
     fn process_commands(&self, id: Id, commands: Out<S::Actor>, state: &mut SystemState<S>) {
         let index = usize::from(id);
         for c in commands {
             match c {
                 Command::Send(dst, msg) => {
-                    if let Some(history) = self.system.record_msg_out(&state.history, id, dst, &msg) {
-                        state.history = history;
+                    self.transmit(Envelope { src: id, dst, msg }, state);
+                },
+                Command::Broadcast(dsts, msg) => {
+                    let envs = dsts.into_iter().map(|dst| Envelope { src: id, dst, msg: msg.clone() });
+                    if self.atomic_broadcast == AtomicBroadcast::Yes {
+                        for env in envs { self.transmit(env, state); }
+                    } else {
+                        state.pending_sends.extend(envs);
                     }
-                    state.network.insert(Envelope { src: id, dst, msg });
                 },
-                Command::SetTimer(_) => {
+                Command::SetTimer(duration) => {
                     // must use the index to infer how large as actor state may not be initialized yet
                     if state.is_timer_set.len() <= index {
                         state.is_timer_set.resize(index + 1, false);
                     }
                     state.is_timer_set[index] = true;
+                    if let TimeoutOrdering::BoundedDrift(_) = self.timeout_ordering {
+                        // Resetting a timer moves it after the timers that were set earlier.
+                        state.timer_order.retain(|(timer_id, _)| *timer_id != id);
+                        state.timer_order.push((id, duration));
+                    }
                 },
                 Command::CancelTimer => {
                     state.is_timer_set[index] = false;
+                    state.timer_order.retain(|(timer_id, _)| *timer_id != id);
+                },
+                Command::Spawn(spec) => {
+                    let child = match self.actor(state, index).and_then(|a| a.spawned_actor(&spec)) {
+                        None => continue,
+                        Some(child) => child,
+                    };
+                    let child_id = Id::from(state.actor_states.len());
+                    state.spawned.push((id, spec));
+                    let mut out = Out::new();
+                    let child_state = child.on_start(child_id, &mut out);
+                    state.actor_states.push(self.share_actor_state(child_state));
+                    self.process_commands(child_id, out, state);
+                },
+                Command::ChooseRandom(range) => {
+                    if !range.is_empty() {
+                        state.random_choices.push((id, range));
+                    }
+                },
+                Command::Monitor(peer) => {
+                    if state.is_crashed(peer) {
+                        self.notify_peer_down(state, id, peer);
+                    } else if self.max_crashes > 0 {
+                        state.monitors.insert((id, peer));
+                    }
                 },
             }
         }
     }
 }
 
+/// An actor defined by [`System::actors`] or one spawned at runtime.
+enum ActorRef<'a, A> {
+    Initial(&'a A),
+    Spawned(A),
+}
+
+impl<'a, A> std::ops::Deref for ActorRef<'a, A> {
+    type Target = A;
+    fn deref(&self) -> &A {
+        match self {
+            ActorRef::Initial(actor) => actor,
+            ActorRef::Spawned(actor) => actor,
+        }
+    }
+}
+
 /// Indicates the source and destination for a message.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub struct Envelope<Msg> { pub src: Id, pub dst: Id, pub msg: Msg }
 
 /// Represents a snapshot in time for the entire actor system.
@@ -419,7 +1633,55 @@ pub struct SystemState<S: System> {
     pub actor_states: Vec<Arc<<S::Actor as Actor>::State>>,
     pub network: Network<<S::Actor as Actor>::Msg>,
     pub is_timer_set: Vec<bool>,
+    /// Pending timers in the order they were set, along with their durations. Empty unless the
+    /// system uses [`TimeoutOrdering::BoundedDrift`].
+    pub timer_order: Vec<(Id, Range<Duration>)>,
+    /// The number of [`System::config_updates`] applied by each actor. Empty if the system
+    /// defines no configuration updates.
+    pub config_update_counts: Vec<usize>,
+    /// Undelivered messages in the order they were sent. Empty if the system does not define a
+    /// [`System::reordering_window`] or [`System::max_overtaking`].
+    pub send_order: Vec<Envelope<<S::Actor as Actor>::Msg>>,
+    /// The number of later messages that have overtaken each message in `send_order`.
+    pub overtaken_counts: Vec<usize>,
+    /// The parent ID and specification of each actor spawned at runtime via
+    /// [`Command::Spawn`]. These actors follow the [`System::actors`], in order.
+    pub spawned: Vec<(Id, <S::Actor as Actor>::Msg)>,
+    /// Whether each actor has crashed. Empty if no actor has crashed. See
+    /// [`System::max_crashes`].
+    pub crashed: Vec<bool>,
+    /// `(watcher, peer)` pairs recorded via [`Out::monitor`] for peers that have not crashed.
+    /// Empty if the system defines no [`System::max_crashes`].
+    pub monitors: BTreeSet<(Id, Id)>,
+    /// The number of times each message on the network has been delivered, for channels that
+    /// only duplicate messages [`DuplicatingNetwork::UpTo`] a bound. Messages that have not been
+    /// delivered are omitted.
+    pub delivery_counts: HashableHashMap<Envelope<<S::Actor as Actor>::Msg>, usize, BuildStableHasher>,
+    /// Messages of non-atomic broadcasts that have yet to be sent, in the order they will be sent
+    /// by each actor. See [`System::atomic_broadcast`].
+    pub pending_sends: Vec<Envelope<<S::Actor as Actor>::Msg>>,
+    /// Ranges from which actors requested a value chosen at random via [`Out::choose_random`],
+    /// in the order requested.
+    pub random_choices: Vec<(Id, Range<u64>)>,
     pub history: S::History,
+    /// The most recent phase named by [`System::record_phase`], if any.
+    pub phase: Option<&'static str>,
+    /// The fingerprint of [`System::fingerprint_projection`], if any, which then identifies the
+    /// state in place of the other fields.
+    pub projected_fingerprint: Option<u64>,
+}
+
+impl<S: System> SystemState<S> {
+    /// Indicates whether the system is in the specified phase per [`System::record_phase`].
+    /// Useful for scoping properties to phases.
+    pub fn is_in_phase(&self, phase: &str) -> bool {
+        self.phase == Some(phase)
+    }
+
+    /// Indicates whether an actor has crashed. See [`System::max_crashes`].
+    pub fn is_crashed(&self, id: Id) -> bool {
+        matches!(self.crashed.get(usize::from(id)), Some(true))
+    }
 }
 
 impl<S> serde::Serialize for SystemState<S>
@@ -430,11 +1692,26 @@ where S: System,
 {
     fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
         use serde::ser::SerializeStruct;
-        let mut out = ser.serialize_struct("SystemState", 4)?;
+        let mut out = ser.serialize_struct("SystemState", 13)?;
         out.serialize_field("actor_states", &self.actor_states)?;
         out.serialize_field("network", &self.network)?;
         out.serialize_field("is_timer_set", &self.is_timer_set)?;
+        if !self.timer_order.is_empty() {
+            out.serialize_field("timer_order", &self.timer_order)?;
+        }
+        out.serialize_field("config_update_counts", &self.config_update_counts)?;
+        out.serialize_field("send_order", &self.send_order)?;
+        out.serialize_field("overtaken_counts", &self.overtaken_counts)?;
+        out.serialize_field("spawned", &self.spawned)?;
+        out.serialize_field("crashed", &self.crashed)?;
+        out.serialize_field("monitors", &self.monitors)?;
+        out.serialize_field("delivery_counts", &self.delivery_counts)?;
+        out.serialize_field("pending_sends", &self.pending_sends)?;
+        if !self.random_choices.is_empty() {
+            out.serialize_field("random_choices", &self.random_choices)?;
+        }
         out.serialize_field("history", &self.history)?;
+        out.serialize_field("phase", &self.phase)?;
         out.end()
     }
 }
@@ -447,7 +1724,19 @@ impl<S: System> Clone for SystemState<S> {
             actor_states: self.actor_states.clone(),
             network: self.network.clone(),
             is_timer_set: self.is_timer_set.clone(),
+            timer_order: self.timer_order.clone(),
+            config_update_counts: self.config_update_counts.clone(),
+            send_order: self.send_order.clone(),
+            overtaken_counts: self.overtaken_counts.clone(),
+            spawned: self.spawned.clone(),
+            crashed: self.crashed.clone(),
+            monitors: self.monitors.clone(),
+            delivery_counts: self.delivery_counts.clone(),
+            pending_sends: self.pending_sends.clone(),
+            random_choices: self.random_choices.clone(),
             history: self.history.clone(),
+            phase: self.phase,
+            projected_fingerprint: self.projected_fingerprint,
         }
     }
 }
@@ -459,7 +1748,30 @@ impl<S: System> Debug for SystemState<S> {
         let mut builder = f.debug_struct("SystemState");
         builder.field("actor_states", &self.actor_states);
         builder.field("history", &self.history);
+        builder.field("phase", &self.phase);
         builder.field("is_timer_set", &self.is_timer_set);
+        if !self.timer_order.is_empty() {
+            // Omitted otherwise, as most systems do not bound clock drift.
+            builder.field("timer_order", &self.timer_order);
+        }
+        builder.field("config_update_counts", &self.config_update_counts);
+        builder.field("send_order", &self.send_order);
+        builder.field("overtaken_counts", &self.overtaken_counts);
+        builder.field("spawned", &self.spawned);
+        builder.field("crashed", &self.crashed);
+        builder.field("monitors", &self.monitors);
+        if !self.delivery_counts.is_empty() {
+            // Omitted otherwise, as most networks do not bound duplication.
+            builder.field("delivery_counts", &self.delivery_counts);
+        }
+        if !self.pending_sends.is_empty() {
+            // Likewise omitted otherwise, as most broadcasts are atomic.
+            builder.field("pending_sends", &self.pending_sends);
+        }
+        if !self.random_choices.is_empty() {
+            // Likewise omitted otherwise, as most actors are deterministic.
+            builder.field("random_choices", &self.random_choices);
+        }
         builder.field("network", &self.network);
         builder.finish()
     }
@@ -474,9 +1786,54 @@ where <S::Actor as Actor>::State: Eq, S::History: Eq {}
 // `SystemState<S>`.
 impl<S: System> Hash for SystemState<S> {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        if let Some(projected_fingerprint) = self.projected_fingerprint {
+            projected_fingerprint.hash(state);
+            return
+        }
         self.actor_states.hash(state);
         self.history.hash(state);
+        if self.phase.is_some() {
+            // Skipped otherwise, so that fingerprints are unaffected unless phases are recorded.
+            self.phase.hash(state);
+        }
         self.is_timer_set.hash(state);
+        if !self.timer_order.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless clock drift is bounded.
+            self.timer_order.hash(state);
+        }
+        if !self.config_update_counts.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless updates are modeled.
+            self.config_update_counts.hash(state);
+        }
+        if !self.send_order.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless reordering is bounded.
+            self.send_order.hash(state);
+        }
+        if !self.overtaken_counts.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless reordering is bounded.
+            self.overtaken_counts.hash(state);
+        }
+        if !self.spawned.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless actors are spawned.
+            self.spawned.hash(state);
+        }
+        if !self.crashed.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless an actor crashes.
+            self.crashed.hash(state);
+        }
+        if !self.monitors.is_empty() {
+            self.monitors.hash(state);
+        }
+        if !self.delivery_counts.is_empty() {
+            // Skipped otherwise, so that fingerprints are unaffected unless duplication is bounded.
+            self.delivery_counts.hash(state);
+        }
+        if !self.pending_sends.is_empty() {
+            self.pending_sends.hash(state);
+        }
+        if !self.random_choices.is_empty() {
+            self.random_choices.hash(state);
+        }
         self.network.hash(state);
     }
 }
@@ -488,20 +1845,58 @@ where <S::Actor as Actor>::State: PartialEq, S::History: PartialEq {
     fn eq(&self, other: &Self) -> bool {
         self.actor_states.eq(&other.actor_states)
             && self.history.eq(&other.history)
+            && self.phase.eq(&other.phase)
             && self.is_timer_set.eq(&other.is_timer_set)
+            && self.timer_order.eq(&other.timer_order)
+            && self.config_update_counts.eq(&other.config_update_counts)
+            && self.send_order.eq(&other.send_order)
+            && self.overtaken_counts.eq(&other.overtaken_counts)
+            && self.spawned.eq(&other.spawned)
+            && self.crashed.eq(&other.crashed)
+            && self.monitors.eq(&other.monitors)
+            && self.delivery_counts.eq(&other.delivery_counts)
+            && self.pending_sends.eq(&other.pending_sends)
+            && self.random_choices.eq(&other.random_choices)
             && self.network.eq(&other.network)
     }
 }
 
+/// Indicates where observed actions diverge from a model. See [`SystemModel::check_conformance`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Nonconformance<State, Action> {
+    /// The position of the first observed action that the model does not allow.
+    pub index: usize,
+    /// The observed action that the model does not allow.
+    pub action: Action,
+    /// The path through the model taken by the preceding actions.
+    pub path: Path<State, Action>,
+}
+
+impl<State, Action: Debug> Display for Nonconformance<State, Action> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Observed action is not enabled by the model. index={}, action={:?}",
+               self.index, self.action)
+    }
+}
+
 /// Indicates possible steps that an actor system can take as it evolves.
 #[derive(Clone, Debug, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
 pub enum SystemAction<Msg> {
     /// A message can be delivered to an actor.
     Deliver { src: Id, dst: Id, msg: Msg },
     /// A message can be dropped if the network is lossy.
     Drop(Envelope<Msg>),
+    /// The next message of a non-atomic broadcast can be sent. See [`System::atomic_broadcast`].
+    Transmit(Envelope<Msg>),
     /// An actor can by notified after a timeout.
     Timeout(Id),
+    /// An actor can receive a configuration update. See [`System::config_updates`].
+    UpdateConfig { dst: Id, config: Msg },
+    /// An actor can crash. See [`System::max_crashes`].
+    Crash(Id),
+    /// An actor can receive a value chosen at random. See [`Actor::on_random`].
+    ChooseRandom { id: Id, value: u64 },
 }
 
 impl From<Id> for usize {
@@ -551,7 +1946,19 @@ mod test {
                 actor_states: states.into_iter().map(|s| Arc::new(s)).collect::<Vec<_>>(),
                 network: Network::from_iter(envelopes),
                 is_timer_set: Vec::new(),
+                timer_order: Vec::new(),
+                config_update_counts: Vec::new(),
+                send_order: Vec::new(),
+                overtaken_counts: Vec::new(),
+                spawned: Vec::new(),
+                crashed: Vec::new(),
+                monitors: BTreeSet::new(),
+                delivery_counts: HashableHashMap::with_hasher(BuildStableHasher),
+                pending_sends: Vec::new(),
+                random_choices: Vec::new(),
                 history: (0_u32, 0_u32), // constant as `maintains_history: false`
+                phase: None,
+                projected_fingerprint: None,
             }
         };
 
@@ -635,6 +2042,61 @@ mod test {
         ]));
     }
 
+    #[test]
+    fn caches_no_op_deliveries_without_changing_state_space() {
+        let system = PingPongSystem {
+            max_nat: 2,
+            lossy: LossyNetwork::No,
+            duplicating: DuplicatingNetwork::Yes,
+            maintains_history: false,
+        };
+        let mut model = system.clone().into_model();
+        let no_op_cache = NoOpCache::default();
+        model.no_op_cache = Some(no_op_cache.clone());
+        let cached = model.checker().spawn_bfs().join();
+        assert!(!no_op_cache.is_empty());
+
+        let model = system.into_model();
+        assert!(model.no_op_cache.is_none()); // disabled by default
+        let uncached = model.checker().spawn_bfs().join();
+        assert_eq!(cached.generated_count(), uncached.generated_count());
+        assert_eq!(cached.discoveries().len(), uncached.discoveries().len());
+    }
+
+    #[test]
+    fn no_op_cache_is_bounded() {
+        let cache = NoOpCache::with_capacity(2);
+        cache.insert(fingerprint(&1));
+        cache.insert(fingerprint(&2));
+        assert_eq!(cache.len(), 2);
+        cache.insert(fingerprint(&3));
+        assert_eq!(cache.len(), 1);
+        assert!(cache.contains(&fingerprint(&3)));
+        assert!(!cache.contains(&fingerprint(&1)));
+    }
+
+    #[test]
+    fn interns_actor_states_without_changing_state_space() {
+        let system = PingPongSystem {
+            max_nat: 2,
+            lossy: LossyNetwork::Yes,
+            duplicating: DuplicatingNetwork::Yes,
+            maintains_history: false,
+        };
+        let mut model = system.clone().into_model();
+        assert!(model.actor_state_pool.is_none()); // disabled by default
+        let pool = ActorStatePool::default();
+        model.actor_state_pool = Some(pool.clone());
+        let init_states = model.init_states();
+        assert!(Arc::ptr_eq(&init_states[0].actor_states[0], &init_states[0].actor_states[1]));
+        let interned = model.checker().spawn_bfs().join();
+        assert_eq!(pool.len(), 4); // PingPongCount(0..=3)
+
+        let uninterned = system.into_model().checker().spawn_bfs().join();
+        assert_eq!(interned.generated_count(), uninterned.generated_count());
+        assert_eq!(interned.discoveries().len(), uninterned.discoveries().len());
+    }
+
     #[test]
     fn maintains_fixed_delta_despite_lossy_duplicating_network() {
         let checker = PingPongSystem {
@@ -689,6 +2151,30 @@ mod test {
             vec![Arc::new(PingPongCount(4)), Arc::new(PingPongCount(5))]);
     }
 
+    #[test]
+    fn matches_actor_state() {
+        let checker = PingPongSystem {
+            max_nat: 5,
+            lossy: LossyNetwork::No,
+            duplicating: DuplicatingNetwork::Yes,
+            maintains_history: false,
+        }.into_model().checker().spawn_bfs().join();
+        assert_actor_state!(checker, "can reach max", actor 0, matches PingPongCount(4));
+        assert_actor_state!(checker, "can reach max", actor 1, matches PingPongCount(n) if *n == 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "Actor 1 state for discovery 'can reach max' does not match `PingPongCount(n) if *n > 5`. state=PingPongCount(5)")]
+    fn panics_if_actor_state_does_not_match() {
+        let checker = PingPongSystem {
+            max_nat: 5,
+            lossy: LossyNetwork::No,
+            duplicating: DuplicatingNetwork::Yes,
+            maintains_history: false,
+        }.into_model().checker().spawn_bfs().join();
+        assert_actor_state!(checker, "can reach max", actor 1, matches PingPongCount(n) if *n > 5);
+    }
+
     #[test]
     fn might_never_reach_beyond_max() {
         // ^ and in fact will never. This is a subtle distinction: we're exercising a
@@ -758,4 +2244,1173 @@ mod test {
         // Init state with timer, followed by next state without timer.
         assert_eq!(2, TestSystem.into_model().checker().spawn_bfs().join().generated_count());
     }
+
+    #[test]
+    fn distinguishes_deadlocks_from_terminal_states() {
+        // Actor 0 awaits a reply that actor 1 only sends if it is configured to.
+        struct TestActor { replies: bool }
+        impl Actor for TestActor {
+            type State = bool;
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) { o.send(Id::from(1), ()); }
+                false
+            }
+            fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, src: Id, _: Self::Msg, o: &mut Out<Self>) {
+                if id == Id::from(1) && self.replies { o.send(src, ()); }
+                *state.to_mut() = true;
+            }
+        }
+        struct TestSystem { replies: bool }
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> {
+                vec![TestActor { replies: self.replies }, TestActor { replies: self.replies }]
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> { Vec::new() }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn is_terminal(&self, state: &SystemState<Self>) -> bool { *state.actor_states[0] }
+            fn deadlock_freedom(&self) -> bool { true }
+        }
+        let checker = TestSystem { replies: true }.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.deadlocks().len(), 0);
+        let checker = TestSystem { replies: false }.into_model().checker().spawn_bfs().join();
+        let deadlock = checker.discovery("deadlock free").unwrap().last_state().clone();
+        assert_eq!(*deadlock.actor_states[0], false);
+        assert_eq!(*deadlock.actor_states[1], true);
+    }
+
+    #[test]
+    fn applies_config_updates_in_order() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, _: Id, _o: &mut Out<Self>) -> Self::State { Vec::new() }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_config_update(&self, _: Id, state: &mut Cow<Self::State>, config: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(config);
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn config_updates(&self) -> Vec<(Id, u8)> {
+                vec![(Id::from(0), 1), (Id::from(1), 2), (Id::from(0), 3)]
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("in order", |_, state| {
+                        state.actor_states[0].windows(2).all(|w| w[0] < w[1])
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("all applied", |_, state| {
+                        *state.actor_states[0] == vec![1, 3] && *state.actor_states[1] == vec![2]
+                    }),
+                ]
+            }
+        }
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 6); // 3 positions for actor 0 * 2 for actor 1
+        checker.assert_discovery("all applied", vec![
+            UpdateConfig { dst: Id::from(1), config: 2 },
+            UpdateConfig { dst: Id::from(0), config: 1 },
+            UpdateConfig { dst: Id::from(0), config: 3 },
+        ]);
+    }
+
+    #[test]
+    fn bounds_reordering_within_window() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    for msg in 1..=3 { o.send(Id::from(1), msg); }
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(msg);
+            }
+        }
+        struct TestSystem(DuplicatingNetwork, Option<usize>);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { self.0 }
+            fn reordering_window(&self) -> Option<usize> { self.1 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("third first", |_, state| {
+                        state.actor_states[1].first() == Some(&3)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("second first", |_, state| {
+                        state.actor_states[1].first() == Some(&2)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("all received twice", |_, state| {
+                        state.actor_states[1].len() == 6
+                    }),
+                ]
+            }
+            fn within_boundary(&self, state: &SystemState<Self>) -> bool {
+                state.actor_states[1].len() <= 6
+            }
+        }
+
+        // Unbounded reordering.
+        let checker = TestSystem(DuplicatingNetwork::No, None)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_any_discovery("third first");
+        checker.assert_no_discovery("all received twice");
+        assert_eq!(checker.generated_count(), 16); // every subset and order of 3 messages
+
+        // A message can overtake at most one earlier message.
+        let checker = TestSystem(DuplicatingNetwork::No, Some(1))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("third first");
+        checker.assert_discovery("second first", vec![
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 2 },
+        ]);
+
+        // FIFO.
+        let checker = TestSystem(DuplicatingNetwork::No, Some(0))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("second first");
+        assert_eq!(checker.generated_count(), 4);
+
+        // Duplicates are unconstrained once delivered.
+        let checker = TestSystem(DuplicatingNetwork::Yes, Some(0))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("second first");
+        checker.assert_discovery("all received twice", vec![
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 1 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 2 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 3 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 1 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 1 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 1 },
+        ]);
+    }
+
+    #[test]
+    fn bounds_overtaking() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    for msg in 1..=3 { o.send(Id::from(1), msg); }
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(msg);
+            }
+        }
+        struct TestSystem(LossyNetwork, Option<usize>, Option<usize>);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn lossy_network(&self) -> LossyNetwork { self.0 }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn reordering_window(&self) -> Option<usize> { self.1 }
+            fn max_overtaking(&self) -> Option<usize> { self.2 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("first overtaken twice", |_, state| {
+                        state.actor_states[1][..] == [2, 3, 1]
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("third first", |_, state| {
+                        state.actor_states[1].first() == Some(&3)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("second and third", |_, state| {
+                        state.actor_states[1][..] == [2, 3]
+                    }),
+                ]
+            }
+        }
+
+        // A reordering window of 1 allows a message to be overtaken twice...
+        let checker = TestSystem(LossyNetwork::No, Some(1), None)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_any_discovery("first overtaken twice");
+        checker.assert_no_discovery("third first");
+
+        // ... whereas an overtaking limit of 1 allows a message to overtake two others.
+        let checker = TestSystem(LossyNetwork::No, None, Some(1))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("first overtaken twice");
+        checker.assert_any_discovery("third first");
+        assert_eq!(checker.generated_count(), 12);
+
+        // Both can be bounded.
+        let checker = TestSystem(LossyNetwork::No, Some(1), Some(1))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("first overtaken twice");
+        checker.assert_no_discovery("third first");
+
+        // Dropping an overtaken message unblocks the channel.
+        let checker = TestSystem(LossyNetwork::Yes, None, Some(1))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("first overtaken twice");
+        checker.assert_any_discovery("second and third");
+    }
+
+    #[test]
+    fn can_spawn_actors() {
+        // The parent spawns a child that spawns a grandchild. Each child reports to the parent,
+        // which acknowledges the grandchild and spawns another child upon hearing from the first.
+        enum TestActor { Parent, Child(u8) }
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                match self {
+                    TestActor::Parent => o.spawn(2),
+                    TestActor::Child(n) => {
+                        o.send(Id::from(0), *n);
+                        if *n > 1 { o.spawn(n - 1); }
+                    }
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, src: Id, msg: Self::Msg, o: &mut Out<Self>) {
+                if let TestActor::Parent = self {
+                    if msg == 1 { o.send(src, 0); }
+                    if msg == 2 { o.spawn(0); }
+                }
+                state.to_mut().push(msg);
+            }
+            fn spawned_actor(&self, spec: &Self::Msg) -> Option<Self> {
+                Some(TestActor::Child(*spec))
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor::Parent] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("spawned actors follow initial actors", |_, state| {
+                        state.actor_states.len() == 1 + state.spawned.len()
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("grandchild acknowledged", |_, state| {
+                        state.actor_states.get(2).map(|s| &s[..]) == Some(&[0])
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("all reported", |_, state| {
+                        state.actor_states.len() == 4 && state.actor_states[0].len() == 3
+                    }),
+                ]
+            }
+        }
+
+        let model = TestSystem.into_model();
+        let init_states = model.init_states();
+        assert_eq!(init_states[0].actor_states.len(), 3);
+        assert_eq!(init_states[0].spawned, vec![(Id::from(0), 2), (Id::from(1), 1)]);
+
+        let checker = model.checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 15);
+    }
+
+    #[test]
+    fn notifies_monitors_of_crashes() {
+        // Actors 0 and 1 monitor each other from the start, whereas actor 2 monitors actor 1
+        // upon a timeout, which may happen after actor 1 crashes.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<Id>;
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                match usize::from(id) {
+                    0 => o.monitor(Id::from(1)),
+                    1 => o.monitor(Id::from(0)),
+                    _ => o.set_timer(model_timeout()),
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_timeout(&self, _: Id, _: &mut Cow<Self::State>, o: &mut Out<Self>) {
+                o.monitor(Id::from(1));
+            }
+            fn on_peer_down(&self, _: Id, state: &mut Cow<Self::State>, peer: Id, _: &mut Out<Self>) {
+                state.to_mut().push(peer);
+            }
+        }
+        struct TestSystem(usize);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor, TestActor] }
+            fn max_crashes(&self) -> usize { self.0 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("only crashed peers reported", |_, state| {
+                        state.actor_states.iter().all(|peers| peers.iter().all(|p| state.is_crashed(*p)))
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("watcher notified", |_, state| {
+                        state.actor_states[0][..] == [Id::from(1)]
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("late watcher notified", |_, state| {
+                        state.actor_states[2][..] == [Id::from(1)]
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("two crashed", |_, state| {
+                        state.crashed.iter().filter(|c| **c).count() == 2
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem(0).into_model().checker().spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 2); // only the timeout
+        checker.assert_no_discovery("watcher notified");
+
+        let checker = TestSystem(1).into_model().checker().spawn_bfs().join();
+        checker.assert_discovery("watcher notified", vec![Crash(Id::from(1))]);
+        checker.assert_discovery("late watcher notified", vec![Crash(Id::from(1)), Timeout(Id::from(2))]);
+        checker.assert_no_discovery("two crashed");
+        checker.assert_no_discovery("only crashed peers reported");
+        assert_eq!(checker.generated_count(), 7);
+
+        let checker = TestSystem(2).into_model().checker().spawn_bfs().join();
+        checker.assert_any_discovery("two crashed");
+        checker.assert_no_discovery("only crashed peers reported");
+    }
+
+    #[test]
+    fn can_prune_deliveries() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    for msg in 1..=3 { o.send(Id::from(1), msg); }
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(msg);
+            }
+        }
+        struct TestSystem(LossyNetwork);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn lossy_network(&self) -> LossyNetwork { self.0 }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("received 2", |_, state| {
+                        state.actor_states[1].contains(&2)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("received 1 and 3", |_, state| {
+                        state.actor_states[1].len() == 2
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("network empty", |_, state| {
+                        state.network.is_empty()
+                    }),
+                ]
+            }
+            fn should_deliver(&self, _: &SystemState<Self>, _: Id, _: Id, msg: &u8) -> bool {
+                *msg != 2
+            }
+        }
+
+        let checker = TestSystem(LossyNetwork::No)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("received 2");
+        checker.assert_any_discovery("received 1 and 3");
+        checker.assert_no_discovery("network empty"); // the pruned message is never delivered
+        assert_eq!(checker.generated_count(), 5); // every subset and order of 2 messages
+
+        // A lossy network can still drop the message.
+        let checker = TestSystem(LossyNetwork::Yes)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("received 2");
+        checker.assert_any_discovery("network empty");
+    }
+
+    #[test]
+    fn checks_conformance_of_observed_actions() {
+        let model = |duplicating| PingPongSystem {
+            max_nat: 5,
+            lossy: LossyNetwork::No,
+            duplicating,
+            maintains_history: false,
+        }.into_model();
+        let deliver = |src: usize, dst: usize, msg| Deliver { src: Id::from(src), dst: Id::from(dst), msg };
+        let observed = vec![
+            deliver(0, 1, Ping(0)),
+            deliver(1, 0, Pong(0)),
+            deliver(0, 1, Ping(0)), // duplicate
+            deliver(0, 1, Ping(1)),
+        ];
+
+        // A duplicating network permits the duplicate, which is omitted as it has no effect.
+        let path = model(DuplicatingNetwork::Yes).check_conformance(observed.clone()).unwrap();
+        assert_eq!(path.depth(), 3);
+        assert_eq!(**path.last_state().actor_states.last().unwrap(), PingPongCount(2));
+
+        // Otherwise the duplicate indicates that the model diverges from the implementation.
+        let nonconformance = model(DuplicatingNetwork::No).check_conformance(observed).unwrap_err();
+        assert_eq!(nonconformance.index, 2);
+        assert_eq!(nonconformance.action, deliver(0, 1, Ping(0)));
+        assert_eq!(nonconformance.path.depth(), 2);
+    }
+
+    #[test]
+    fn can_defer_timeouts_until_after_deliveries() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<&'static str>;
+            type Msg = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(model_timeout());
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push("msg");
+            }
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, _: &mut Out<Self>) {
+                state.to_mut().push("timeout");
+            }
+        }
+        struct TestSystem(TimeoutOrdering);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor] }
+            fn init_network(&self) -> Vec<Envelope<()>> {
+                vec![Envelope { src: Id::from(1), dst: Id::from(0), msg: () }]
+            }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn timeout_ordering(&self) -> TimeoutOrdering { self.0 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![Property::<SystemModel<Self>>::sometimes("timeout first", |_, state| {
+                    state.actor_states[0].first() == Some(&"timeout")
+                })]
+            }
+        }
+
+        let checker = TestSystem(TimeoutOrdering::Asynchronous)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_discovery("timeout first", vec![Timeout(Id::from(0))]);
+        assert_eq!(checker.generated_count(), 4);
+
+        let checker = TestSystem(TimeoutOrdering::AfterDeliveries)
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("timeout first");
+        assert_eq!(checker.generated_count(), 3);
+    }
+
+    #[test]
+    fn can_bound_clock_drift_between_timeouts() {
+        // Actor 0 sends heartbeats every 1-2 seconds, and actor 1 times out after 3-6 seconds.
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8; // timeout count
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.set_timer(Duration::from_secs(1)..Duration::from_secs(2));
+                } else {
+                    o.set_timer(Duration::from_secs(3)..Duration::from_secs(6));
+                }
+                0
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_timeout(&self, id: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+                if id == Id::from(0) && **state < 2 {
+                    o.set_timer(Duration::from_secs(1)..Duration::from_secs(2));
+                }
+                *state.to_mut() += 1;
+            }
+        }
+        struct TestSystem(TimeoutOrdering);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn timeout_ordering(&self) -> TimeoutOrdering { self.0 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("timeout before heartbeat", |_, state| {
+                        *state.actor_states[0] == 0 && *state.actor_states[1] == 1
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("timeout after heartbeat", |_, state| {
+                        *state.actor_states[0] == 1 && *state.actor_states[1] == 1
+                    }),
+                ]
+            }
+        }
+
+        TestSystem(TimeoutOrdering::Asynchronous).into_model().checker().spawn_bfs().join()
+            .assert_properties();
+        TestSystem(TimeoutOrdering::BoundedDrift(2.0)).into_model().checker().spawn_bfs().join()
+            .assert_properties();
+
+        // The heartbeat must elapse first, until it is reset after the timeout was set.
+        let checker = TestSystem(TimeoutOrdering::BoundedDrift(1.5))
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("timeout before heartbeat");
+        checker.assert_discovery("timeout after heartbeat", vec![Timeout(Id::from(0)), Timeout(Id::from(1))]);
+        let init_state = &checker.model().init_states()[0];
+        assert_eq!(init_state.timer_order.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+                   vec![Id::from(0), Id::from(1)]);
+    }
+
+    #[test]
+    fn explores_each_random_choice() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u64>; // chosen values
+            type Msg = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.choose_random(0..3);
+                o.choose_random(5..5); // ignored
+                o.choose_random(10..12);
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_random(&self, _: Id, state: &mut Cow<Self::State>, value: u64, _: &mut Out<Self>) {
+                state.to_mut().push(value);
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor] }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("in order", |_, state| {
+                        state.actor_states[0].iter().zip([0..3, 10..12].iter())
+                            .all(|(value, range)| range.contains(value))
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("chose 2 then 11", |_, state| {
+                        *state.actor_states[0] == vec![2, 11]
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        checker.assert_discovery("chose 2 then 11", vec![
+            ChooseRandom { id: Id::from(0), value: 2 },
+            ChooseRandom { id: Id::from(0), value: 11 },
+        ]);
+        assert_eq!(checker.generated_count(), 1 + 3 + 3 * 2);
+    }
+
+    #[test]
+    fn scopes_properties_to_phases() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(model_timeout());
+                0
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+                if **state < 3 {
+                    *state.to_mut() += 1;
+                    o.set_timer(model_timeout());
+                }
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor] }
+            fn record_phase(&self, state: &SystemState<Self>) -> Option<&'static str> {
+                match *state.actor_states[0] {
+                    1 => Some("view change"),
+                    3 => Some("steady state"),
+                    _ => None,
+                }
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("view change is brief", |_, state| {
+                        !state.is_in_phase("view change") || *state.actor_states[0] < 3
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("steady", |_, state| {
+                        state.is_in_phase("steady state")
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("view change is brief");
+        checker.assert_discovery("steady", vec![Timeout(Id::from(0)); 3]);
+        let path = checker.discovery("steady").unwrap();
+        assert_eq!(
+            path.clone().into_states().iter().map(|s| s.phase).collect::<Vec<_>>(),
+            vec![None, Some("view change"), Some("view change"), Some("steady state")]);
+
+        let svg = TestSystem.into_model().as_svg(path).unwrap();
+        assert!(svg.contains("<line x1='0' y1='30' x2='100' y2='30' class='svg-phase-boundary' />"));
+        assert!(svg.contains("<text x='100' y='30' class='svg-phase-label'>view change</text>"));
+        assert!(svg.contains("<text x='100' y='90' class='svg-phase-label'>steady state</text>"));
+        assert!(!svg.contains("y='60' class='svg-phase-label'"));
+    }
+
+    #[test]
+    fn configures_link_quality_per_channel() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.send(Id::from(1), 1);
+                    o.send(Id::from(1), 2);
+                    o.send(Id::from(2), 3);
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+                if state.len() < 2 { state.to_mut().push(msg); }
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor, TestActor] }
+            fn link_quality(&self, _: Id, dst: Id) -> LinkQuality {
+                if dst == Id::from(1) {
+                    LinkQuality { lossy: LossyNetwork::No, duplicating: DuplicatingNetwork::No, ordered: true }
+                } else {
+                    LinkQuality { lossy: LossyNetwork::Yes, duplicating: DuplicatingNetwork::Yes, ordered: false }
+                }
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("bus is reliable and ordered", |_, state| {
+                        let received = &*state.actor_states[1];
+                        let in_flight = state.network.iter().filter(|env| env.dst == Id::from(1)).count();
+                        [1, 2].starts_with(received) && received.len() + in_flight == 2
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("wan duplicates", |_, state| {
+                        state.actor_states[2].len() > 1
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("wan loses", |_, state| {
+                        state.network.is_empty() && state.actor_states[2].is_empty()
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        checker.assert_discovery("wan loses", vec![
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 1 },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: 2 },
+            Drop(Envelope { src: Id::from(0), dst: Id::from(2), msg: 3 }),
+        ]);
+    }
+
+    #[test]
+    fn can_bound_message_duplication() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) { o.send(Id::from(1), ()); }
+                0
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                *state.to_mut() += 1;
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::UpTo(1) }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("delivered at most twice", |_, state| {
+                        *state.actor_states[1] <= 2
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("duplicated then forgotten", |_, state| {
+                        *state.actor_states[1] == 2 && state.network.is_empty()
+                            && state.delivery_counts.is_empty()
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        checker.assert_discovery("duplicated then forgotten", vec![
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: () },
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: () },
+        ]);
+        assert_eq!(checker.generated_count(), 3);
+    }
+
+    #[test]
+    fn can_bound_messages_in_flight() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.send(Id::from(1), 1);
+                    o.send(Id::from(1), 2);
+                    o.send(Id::from(1), 3);
+                }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(msg);
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = usize;
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn max_in_flight(&self) -> Option<usize> { Some(2) }
+            fn record_send_blocked(&self, history: &Self::History, _: Id, _: Id, _: &u8) -> Option<Self::History> {
+                Some(history + 1)
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::always("within capacity", |_, state| {
+                        state.network.len() <= 2
+                    }),
+                    Property::<SystemModel<Self>>::always("last send blocked", |_, state| {
+                        state.history == 1 && !state.actor_states[1].contains(&3)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("others delivered", |_, state| {
+                        state.actor_states[1].len() == 2
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 5);
+    }
+
+    #[test]
+    fn can_broadcast_non_atomically() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = bool;
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) { o.broadcast(&[Id::from(1), Id::from(2)], &()); }
+                false
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                *state.to_mut() = true;
+            }
+        }
+        struct TestSystem(AtomicBroadcast);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor, TestActor] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn max_crashes(&self) -> usize { 1 }
+            fn atomic_broadcast(&self) -> AtomicBroadcast { self.0 }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("partially delivered", |_, state| {
+                        state.network.is_empty() && state.pending_sends.is_empty()
+                            && *state.actor_states[1] && !*state.actor_states[2]
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem(AtomicBroadcast::Yes).into_model().checker().spawn_bfs().join();
+        checker.assert_no_discovery("partially delivered");
+
+        let checker = TestSystem(AtomicBroadcast::No).into_model().checker().spawn_bfs().join();
+        checker.assert_discovery("partially delivered", vec![
+            Transmit(Envelope { src: Id::from(0), dst: Id::from(1), msg: () }),
+            Crash(Id::from(0)),
+            Deliver { src: Id::from(0), dst: Id::from(1), msg: () },
+        ]);
+    }
+
+    #[test]
+    fn can_record_timeouts_and_drops_in_history() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.send(Id::from(1), ());
+                    o.set_timer(model_timeout());
+                }
+                0
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                *state.to_mut() += 1;
+            }
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, _: &mut Out<Self>) {
+                *state.to_mut() += 1;
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = (u8, u8); // (drops, timeouts)
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn lossy_network(&self) -> LossyNetwork { LossyNetwork::Yes }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn record_timeout(&self, history: &Self::History, _: Id) -> Option<Self::History> {
+                Some((history.0, history.1 + 1))
+            }
+            fn record_drop(&self, history: &Self::History, _: Id, _: Id, _: &()) -> Option<Self::History> {
+                Some((history.0 + 1, history.1))
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("dropped and timed out", |_, state| {
+                        state.history == (1, 1)
+                    }),
+                    Property::<SystemModel<Self>>::always("delivered unless dropped", |_, state| {
+                        state.history.0 == 1 || !state.network.is_empty() || *state.actor_states[1] == 1
+                    }),
+                ]
+            }
+        }
+
+        let checker = TestSystem.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        checker.assert_discovery("dropped and timed out", vec![
+            Drop(Envelope { src: Id::from(0), dst: Id::from(1), msg: () }),
+            Timeout(Id::from(0)),
+        ]);
+    }
+
+    #[test]
+    fn links_fault_injection_controls_from_svg() {
+        let model = PingPongSystem {
+            max_nat: 1,
+            lossy: LossyNetwork::Yes,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        }.into_model();
+        let init_state = model.init_states().remove(0);
+        let dropped_state = model.next_state(&init_state, Drop(Envelope {
+            src: Id::from(0),
+            dst: Id::from(1),
+            msg: Ping(0),
+        })).unwrap();
+        let path = Path::from_fingerprints(&model, vec![fingerprint(&init_state)].into());
+        let svg = model.as_svg(path).unwrap();
+        assert!(svg.contains(&format!(
+            "<a href='#/steps/{}/{}' class='svg-fault-control'><text x='100' y='60'>Drop Ping(0)</text></a>",
+            fingerprint(&init_state), fingerprint(&dropped_state))),
+            "svg={}", svg);
+        assert!(svg.contains("height='60'"), "svg={}", svg);
+    }
+
+    #[test]
+    fn draws_drops_duplicates_and_timers_in_svg() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = String;
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.send(Id::from(1), "x".repeat(45));
+                    o.set_timer(model_timeout());
+                }
+                0
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                *state.to_mut() += 1;
+            }
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, _: &mut Out<Self>) {
+                *state.to_mut() += 1;
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn lossy_network(&self) -> LossyNetwork { LossyNetwork::Yes }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> { Vec::new() }
+        }
+
+        let model = TestSystem.into_model();
+        let env = Envelope { src: Id::from(0), dst: Id::from(1), msg: "x".repeat(45) };
+        let deliver = Deliver { src: env.src, dst: env.dst, msg: env.msg.clone() };
+        let path = Path::from_actions(
+            &model,
+            model.init_states().remove(0),
+            &[deliver.clone(), deliver, Timeout(Id::from(0)), Drop(env)]).unwrap();
+        let svg = model.as_svg(path).unwrap();
+
+        // The first delivery is a single arrow, and the redelivery is doubled.
+        assert!(svg.contains(
+            "<line x1='0' x2='100' y1='0' y2='30' marker-end='url(#arrow)' class='svg-event-shape' />"),
+            "svg={}", svg);
+        assert!(svg.contains(
+            "<line x1='0' x2='100' y1='0' y2='60' marker-end='url(#arrow)' class='svg-event-shape svg-duplicate-shape' />\n\
+             <line x1='0' x2='100' y1='4' y2='64' marker-end='url(#arrow)' class='svg-event-shape svg-duplicate-shape' />"),
+            "svg={}", svg);
+
+        // The timer loops from when it was set until it fires.
+        assert!(svg.contains(
+            "<path d='M 0 0 C 30 0, 30 90, 0 90' marker-end='url(#arrow)' class='svg-timer-shape' />"),
+            "svg={}", svg);
+
+        // The dropped message dangles halfway to its recipient.
+        assert!(svg.contains(
+            "<line x1='0' x2='50' y1='0' y2='120' class='svg-event-shape svg-dropped-shape' />"),
+            "svg={}", svg);
+
+        // Long labels are wrapped.
+        assert!(svg.contains(&format!(
+            "<text x='100' y='30' class='svg-event-label'>\
+               <tspan x='100' dy='0'>\"{}</tspan>\
+               <tspan x='100' dy='1.2em'>{}\"</tspan>\
+             </text>",
+            "x".repeat(39), "x".repeat(6))),
+            "svg={}", svg);
+    }
+
+    #[test]
+    fn deduplicates_states_by_projection() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<Id>; // senders, in order of delivery
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id != Id::from(2) { o.send(Id::from(2), ()); }
+                Vec::new()
+            }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, src: Id, _: Self::Msg, _: &mut Out<Self>) {
+                state.to_mut().push(src);
+            }
+        }
+        struct TestSystem { ignores_order: bool }
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor, TestActor] }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn fingerprint_projection(&self, state: &SystemState<Self>) -> Option<impl Hash> {
+                if !self.ignores_order { return None }
+                let mut senders = (*state.actor_states[2]).clone();
+                senders.sort();
+                Some((senders, &state.network))
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![Property::<SystemModel<Self>>::always("delivered at most once", |_, state| {
+                    state.actor_states[2].len() <= 2
+                })]
+            }
+        }
+
+        // The two delivery orders otherwise result in distinct states.
+        let checker = TestSystem { ignores_order: false }.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 5);
+        let checker = TestSystem { ignores_order: true }.into_model().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 4);
+    }
+
+    #[test]
+    fn checks_from_perturbed_init_states() {
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(model_timeout());
+                0
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+                if **state < 3 {
+                    *state.to_mut() += 1;
+                    o.set_timer(model_timeout());
+                }
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn init_state_perturbations(&self, id: Id, state: &u8) -> Vec<u8> {
+                assert_eq!(*state, 0);
+                if id == Id::from(0) { vec![2] } else { vec![1, 3] }
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::eventually("converges", |_, state| {
+                        state.actor_states.iter().all(|s| **s == 3)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("perturbed", |_, state| {
+                        *state.actor_states[0] == 2 && *state.actor_states[1] == 1
+                    }),
+                ]
+            }
+        }
+        let model = TestSystem.into_model();
+        let init_states = model.init_states();
+        assert_eq!(
+            init_states.iter()
+                .map(|s| (*s.actor_states[0], *s.actor_states[1]))
+                .collect::<Vec<_>>(),
+            vec![(0, 0), (2, 0), (0, 1), (0, 3), (2, 1), (2, 3)]);
+        assert!(init_states.iter().all(|s| s.is_timer_set == vec![true, true]));
+        let metadata = model.metadata();
+        assert_eq!(
+            metadata.actors.iter().map(|a| a.init_state.as_str()).collect::<Vec<_>>(),
+            vec!["0", "0"]); // excludes perturbations
+
+        let checker = model.checker().spawn_bfs().join();
+        checker.assert_no_discovery("converges");
+        checker.assert_discovery("perturbed", Vec::new());
+        assert_eq!(checker.generated_count(), 25); // 5 combinations of count and timer per actor
+    }
+
+    #[test]
+    fn reduces_interleavings_of_independent_actors() {
+        #[derive(Clone)]
+        struct TestActor { forward_to: Option<Id> }
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = u8;
+            fn on_start(&self, _: Id, _o: &mut Out<Self>) -> Self::State { Vec::new() }
+            fn on_msg(&self, _: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, o: &mut Out<Self>) {
+                state.to_mut().push(msg);
+                if let Some(dst) = self.forward_to { o.send(dst, msg + 10); }
+            }
+        }
+        #[derive(Clone)]
+        struct TestSystem { actors: Vec<TestActor>, por: PartialOrderReduction }
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { self.actors.clone() }
+            fn init_network(&self) -> Vec<Envelope<u8>> {
+                (0..self.actors.len())
+                    .map(|i| Envelope { src: Id::from(99), dst: Id::from(i), msg: i as u8 })
+                    .collect()
+            }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn partial_order_reduction(&self) -> PartialOrderReduction { self.por }
+            fn may_send(&self, src: Id, dst: Id) -> bool {
+                self.actors[usize::from(src)].forward_to == Some(dst)
+            }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![
+                    Property::<SystemModel<Self>>::sometimes("forwarded first", |_, state| {
+                        state.actor_states.get(1).map(|s| **s == vec![10, 1]) == Some(true)
+                    }),
+                    Property::<SystemModel<Self>>::sometimes("forwarded last", |_, state| {
+                        state.actor_states.get(1).map(|s| **s == vec![1, 10]) == Some(true)
+                    }),
+                ]
+            }
+        }
+
+        // Independent actors: 2^3 states without reduction, but only 3 + 1 with.
+        let sinks = vec![TestActor { forward_to: None }; 3];
+        let checker = TestSystem { actors: sinks.clone(), por: PartialOrderReduction::No }
+            .into_model().checker().spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 8);
+        let checker = TestSystem { actors: sinks, por: PartialOrderReduction::Yes }
+            .into_model().checker().spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 4);
+
+        // Both orders remain reachable for an actor that another actor can influence.
+        let actors = vec![
+            TestActor { forward_to: Some(Id::from(1)) },
+            TestActor { forward_to: None },
+        ];
+        let checker = TestSystem { actors, por: PartialOrderReduction::Yes }
+            .into_model().checker().spawn_bfs().join();
+        checker.assert_any_discovery("forwarded first");
+        checker.assert_any_discovery("forwarded last");
+    }
+
+    #[test]
+    fn does_not_reduce_when_actors_share_network_capacity() {
+        #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+        enum TestMsg { Go, X(u8) }
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = Vec<u8>;
+            type Msg = TestMsg;
+            fn on_start(&self, _: Id, _o: &mut Out<Self>) -> Self::State { Vec::new() }
+            fn on_msg(&self, id: Id, state: &mut Cow<Self::State>, _: Id, msg: Self::Msg, o: &mut Out<Self>) {
+                match msg {
+                    TestMsg::Go if id == Id::from(0) => {
+                        o.send(id, TestMsg::X(1));
+                        o.send(id, TestMsg::X(2));
+                    }
+                    TestMsg::Go => state.to_mut().push(0),
+                    TestMsg::X(x) => state.to_mut().push(x),
+                }
+            }
+        }
+        struct TestSystem(PartialOrderReduction);
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn init_network(&self) -> Vec<Envelope<TestMsg>> {
+                (0..2).map(|i| Envelope { src: Id::from(i), dst: Id::from(i), msg: TestMsg::Go }).collect()
+            }
+            fn duplicating_network(&self) -> DuplicatingNetwork { DuplicatingNetwork::No }
+            fn max_in_flight(&self) -> Option<usize> { Some(2) }
+            fn partial_order_reduction(&self) -> PartialOrderReduction { self.0 }
+            fn may_send(&self, src: Id, dst: Id) -> bool { src == dst }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![Property::<SystemModel<Self>>::sometimes("received second", |_, state| {
+                    state.actor_states[0].contains(&2)
+                })]
+            }
+        }
+
+        // Actor 0 can only send both messages once actor 1 consumes its message, so deferring
+        // actor 1 would hide the example.
+        let unreduced = TestSystem(PartialOrderReduction::No).into_model().checker().spawn_bfs().join();
+        unreduced.assert_any_discovery("received second");
+        let reduced = TestSystem(PartialOrderReduction::Yes).into_model().checker().spawn_bfs().join();
+        reduced.assert_any_discovery("received second");
+        assert_eq!(reduced.generated_count(), unreduced.generated_count());
+    }
 }
@@ -1,18 +1,46 @@
 //! Private module for selective re-export.
 
 mod bfs;
-use crate::{Expectation, Model};
+use crate::{fingerprint, Expectation, Model, Property};
+mod bounded;
+mod checkpoint;
+mod collision;
+mod compress;
+mod coverage;
+mod diff;
+mod digest;
 mod dfs;
+mod distributed;
 mod explorer;
+mod frontier;
+mod graph_sink;
+mod guided;
+mod iddfs;
+mod merge;
 mod path;
+mod session;
+mod stats;
+mod status;
+mod store;
 mod visitor;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::num::NonZeroUsize;
 use std::time::Instant;
 
+pub use collision::FingerprintMode;
+pub use coverage::Coverage;
+pub use diff::StateSpaceDiff;
+pub use digest::StateSpaceDigest;
+pub use graph_sink::{SqlGraphSink, StateGraphSink};
+pub use merge::{MergedReport, PropertyVerdict};
 pub use path::*;
+pub use session::{CheckerSession, CheckOutcome, SessionReport};
+pub use stats::CheckerStats;
+pub use status::{CancellationToken, CheckerStatus, StopReason};
+use status::StopConditions;
+pub use store::StateStore;
 pub use visitor::*;
 
 /// A [`Model`] [`Checker`] builder. Instantiable via the [`Model::checker`] method.
@@ -23,21 +51,59 @@ pub use visitor::*;
 /// # use stateright::*; let model = ();
 /// model.checker().threads(4).spawn_dfs().join().assert_properties();
 /// ```
+///
+/// Not every checker supports every option. For example, only [`CheckerBuilder::spawn_bfs`] can
+/// [`CheckerBuilder::resume_from`] a checkpoint. Spawning a checker with an option that it does
+/// not support panics rather than ignoring the option.
 #[must_use = "This code constructs a builder, not a checker. \
               Consider calling spawn_bfs() or spawn_dfs()."]
 pub struct CheckerBuilder<M: Model> {
     model: M,
     target_generated_count: Option<NonZeroUsize>,
+    stop_conditions: StopConditions,
     thread_count: usize,
     visitor: Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+    state_store: StateStore,
+    frontier_priority: Option<FrontierPriority<M>>,
+    checkpoint: Option<checkpoint::Checkpoint<M::State>>,
+    record_predecessors: bool,
+    limits: ExplorationLimits,
+    action_kind: Option<coverage::ActionKind<M>>,
+    graph_exporter: Option<graph_sink::GraphExporter<M>>,
+    named_predicates: Vec<NamedPredicate<M>>,
+    target_properties: Option<Vec<&'static str>>,
+    stop_on_first_discovery: bool,
+    collision_detector: Option<collision::CollisionDetector<M::State>>,
+    state_compressor: Option<compress::StateCompressor<M::State>>,
+    state_heap_size: Option<fn(&M::State) -> usize>,
 }
+
+/// Scores a pending state. See [`CheckerBuilder::frontier_priority`].
+type FrontierPriority<M> = Box<dyn Fn(&M, &<M as Model>::State) -> i64 + Send + Sync>;
+
+/// A predicate to search for in the explorer. See [`CheckerBuilder::named_predicate`].
+pub(crate) type NamedPredicate<M> = (&'static str, fn(&M, &<M as Model>::State) -> bool);
 impl<M: Model> CheckerBuilder<M> {
     pub(crate) fn new(model: M) -> Self {
         Self {
             model,
             target_generated_count: None,
+            stop_conditions: StopConditions::default(),
             thread_count: 1,
             visitor: None,
+            state_store: StateStore::InMemory,
+            frontier_priority: None,
+            checkpoint: None,
+            record_predecessors: false,
+            limits: ExplorationLimits::default(),
+            action_kind: None,
+            graph_exporter: None,
+            named_predicates: Vec::new(),
+            target_properties: None,
+            stop_on_first_discovery: false,
+            collision_detector: None,
+            state_compressor: None,
+            state_heap_size: None,
         }
     }
 
@@ -88,13 +154,44 @@ impl<M: Model> CheckerBuilder<M> {
     ///
     /// # API
     ///
+    /// The JSON endpoints below are stable, so external UIs and scripts can drive exploration.
+    /// Paths are identified by the fingerprints of their states, delimited by forward slashes
+    /// (as produced by [`Path::encode`]). Each state view is an object with `"state"` and
+    /// `"fingerprint"` fields and, where applicable, `"action"`, `"outcome"` (see
+    /// [`Model::display_outcome`]), `"diff"` (see [`Model::display_diff`]), and `"svg"` (see
+    /// [`Model::as_svg`]) fields, where states and actions are rendered via [`Debug`].
+    ///
     /// - `GET /` returns a web browser UI as HTML.
     /// - `GET /.status` returns information about the model checker status.
+    /// - `GET /.properties` returns an array with an object for each checked property, having
+    ///   `"name"`, `"expectation"` (`"Always"`, `"Eventually"`, or `"Sometimes"`), `"discovery"`
+    ///   (the encoded path or `null`), and `"satisfied"` fields, where `"satisfied"` is `null`
+    ///   until the property has a discovery or checking is done.
     /// - `GET /.states` returns available initial states and fingerprints.
     /// - `GET /.states/{fingerprint1}/{fingerprint2}/...` follows the specified
-    ///    path of fingerprints and returns available actions with resulting
-    ///    states and fingerprints.
+    ///   path of fingerprints and returns available actions with resulting
+    ///   states, fingerprints, and diffs (see [`Model::display_diff`]).
     /// - `GET /.states/.../{invalid-fingerprint}` returns 404.
+    /// - `GET /.path/{fingerprint1}/{fingerprint2}/...` returns the states along the specified
+    ///   path, each with the action that led to it and its diff from the previous state, or 404
+    ///   if the path is invalid.
+    /// - `GET /.live` upgrades to a WebSocket that pushes a JSON object each second with
+    ///   `"done"`, `"generated"`, `"pending"`, `"max_depth"`, and `"elapsed_secs"` fields, along
+    ///   with `"new_discoveries"`, which maps each property discovered since the previous message
+    ///   (named as in `/.status`) to its encoded path. The server closes the WebSocket after
+    ///   sending the message indicating that checking is done. `"pending"` counts the states
+    ///   left unchecked, so it can be nonzero once checking is done if every property had a
+    ///   discovery before all states were checked.
+    /// - `GET /.snippet/{fingerprint1}/{fingerprint2}/...` returns the actions along the specified
+    ///   path as a [`Checker::assert_discovery`] snippet for use in tests, naming the first
+    ///   property for which the path is a discovery.
+    /// - `GET /.search/{fingerprint1}/{fingerprint2}/...?predicate={name}` breadth-first searches
+    ///   from the last state of the specified path (or from the initial states) for a state
+    ///   satisfying a [`CheckerBuilder::named_predicate`], returning the fingerprints of the path
+    ///   to that state, or 404 if no reachable state satisfies the predicate.
+    ///
+    /// See also [`Checker::serve`], which attaches the Explorer to a checker that is already
+    /// running.
     pub fn serve(self, addresses: impl std::net::ToSocketAddrs) -> std::sync::Arc<impl Checker<M>>
     where M: 'static + Model + Send + Sync,
           M::Action: Debug + Send + Sync,
@@ -116,6 +213,7 @@ impl<M: Model> CheckerBuilder<M> {
     where M: Model + Send + Sync + 'static,
           M::State: Hash + Send + Sync + 'static,
     {
+        self.assert_options_supported_by("spawn_bfs");
         bfs::BfsChecker::spawn(self)
     }
 
@@ -131,18 +229,503 @@ impl<M: Model> CheckerBuilder<M> {
     where M: Model + Send + Sync + 'static,
           M::State: Hash + Send + Sync + 'static,
     {
+        self.assert_options_supported_by("spawn_dfs");
         dfs::DfsChecker::spawn(self)
     }
 
+    /// Spawns an iterative deepening depth-first search model checker, which repeats a
+    /// depth-limited DFS with an increasing depth limit. Memory usage is proportional to the
+    /// depth of the state space rather than the number of reachable states, at the cost of
+    /// revisiting states. Like [`CheckerBuilder::spawn_bfs`], this finds the shortest [`Path`]
+    /// to each discovery.
+    ///
+    /// Previously visited states are not tracked (except along the current path, to avoid
+    /// cycles), so [`Checker::generated_count`] counts paths rather than distinct states, and
+    /// models with many paths to the same state may take considerably longer to check. Checking
+    /// is single threaded.
+    ///
+    /// This call does not block the current thread. Call [`Checker::join`] to block until
+    /// checking completes.
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_iddfs(self) -> impl Checker<M>
+    where M: Model + Send + Sync + 'static,
+          M::State: Hash + Send + Sync + 'static,
+    {
+        self.assert_options_supported_by("spawn_iddfs");
+        iddfs::IddfsChecker::spawn(self)
+    }
+
+    /// Spawns one process of a model checker that runs across a cluster of machines, so that
+    /// models whose generated states would exhaust the memory of one machine can still be
+    /// checked exhaustively. Each process runs the same model with the same list of `peers` (the
+    /// address on which each process listens) and a distinct `index` into that list. Processes
+    /// can be started in any order, as they retry connecting to one another.
+    ///
+    /// Each process owns the states whose fingerprints hash to it, and successor states are sent
+    /// to their owners over TCP, so memory and (for large state spaces) work are spread across the
+    /// cluster. Processes do not steal pending states from one another, so a process with no
+    /// pending states waits for its peers to send it more, which can leave processes idle while
+    /// checking a narrow state space. Discoveries are shared with every process. The first process detects when checking is
+    /// done and notifies the others. [`Checker::generated_count`] only counts the states that
+    /// this process owns.
+    ///
+    /// Pending states carry the fingerprints of the path that reached them, so memory and network
+    /// usage grow with the depth of the state space. Checking is single threaded in each
+    /// process, although multiple processes can run on one machine.
+    /// [`CheckerBuilder::target_generated_count`] is not supported.
+    ///
+    /// Returns an error if unable to listen on this process's address. If a process is later
+    /// unable to reach a peer (retrying for about 30 seconds while peers start) or otherwise
+    /// encounters an I/O error, then every process stops and [`Checker::stop_reason`] indicates
+    /// [`StopReason::Io`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// let peers = vec![
+    ///     "10.0.0.1:3000".parse().unwrap(),
+    ///     "10.0.0.2:3000".parse().unwrap(),
+    ///     "10.0.0.3:3000".parse().unwrap(),
+    /// ];
+    /// let index = std::env::args().nth(1).unwrap().parse().unwrap();
+    /// model.checker().spawn_distributed(peers, index).unwrap().join().assert_properties();
+    /// ```
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_distributed(self, peers: Vec<std::net::SocketAddr>, index: usize)
+        -> std::io::Result<impl Checker<M>>
+    where M: Model + Send + Sync + 'static,
+          M::State: Hash + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + 'static,
+    {
+        self.assert_options_supported_by("spawn_distributed");
+        distributed::DistributedChecker::spawn(self, peers, index)
+    }
+
+    /// Spawns a delay-bounded model checker, which treats the order of [`Model::actions`] as the
+    /// expected schedule and counts each deviation from that order as a delay: taking the `i`th
+    /// action of a state costs `i` delays. States are checked in order of the fewest delays needed
+    /// to reach them, and states requiring more than `max_delays` are not explored. Realistic
+    /// schedules are therefore checked before pathological ones, which tends to surface most bugs
+    /// after visiting a fraction of the state space.
+    ///
+    /// Checking is only exhaustive for the bound, so [`Checker::is_done`] indicates that every
+    /// state within `max_delays` was checked. Checking is single threaded.
+    ///
+    /// The expected schedule is only as realistic as the order of [`Model::actions`]. In
+    /// particular, an [actor system](crate::actor::SystemModel) lists deliveries in the
+    /// iteration order of its network, which follows a hash of each message rather than the order
+    /// in which messages were sent. The zero-delay schedule of an actor system is therefore
+    /// deterministic but arbitrary, so bounding delays still prioritizes schedules that are
+    /// close to some baseline, just not necessarily to the order of sends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker().spawn_delay_bounded(2).join().assert_properties();
+    /// ```
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_delay_bounded(self, max_delays: usize) -> impl Checker<M>
+    where M: Model + Send + Sync + 'static,
+          M::State: Hash + Send + Sync + 'static,
+    {
+        self.assert_options_supported_by("spawn_delay_bounded");
+        bounded::BoundedChecker::spawn(self, bounded::Bound::Delays(max_delays))
+    }
+
+    /// Spawns a context-bounded model checker, which limits the number of preemptions per
+    /// execution, similar to the CHESS tool. A preemption occurs when the previous action's
+    /// context (per [`Model::action_context`], such as an actor) could act again but an action of
+    /// a different context is taken instead. States are checked in order of the fewest
+    /// preemptions needed to reach them, and states requiring more than `max_preemptions` are not
+    /// explored. Most concurrency bugs require few preemptions, so small bounds provide strong
+    /// practical coverage for models whose full state spaces are intractable.
+    ///
+    /// Checking is only exhaustive for the bound, which [`Checker::report`] includes alongside
+    /// the results. Checking is single threaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker().spawn_context_bounded(2).join().assert_properties();
+    /// ```
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_context_bounded(self, max_preemptions: usize) -> impl Checker<M>
+    where M: Model + Send + Sync + 'static,
+          M::State: Hash + Send + Sync + 'static,
+    {
+        self.assert_options_supported_by("spawn_context_bounded");
+        bounded::BoundedChecker::spawn(self, bounded::Bound::Preemptions(max_preemptions))
+    }
+
+    /// Spawns a guided (A* search) model checker, which evaluates states in order of the number
+    /// of steps needed to reach them plus a `heuristic` estimate of the remaining steps to a
+    /// discovery, such as a measure of how far replicas' logs have diverged. Directing the search
+    /// toward a suspected bug can find it after visiting a tiny fraction of a huge state space.
+    ///
+    /// Every reachable state is still checked eventually, so the heuristic only affects the
+    /// order. If the heuristic never overestimates, then the path to each discovery is a shortest
+    /// one. Checking is single threaded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker()
+    ///     .spawn_guided(|_model, _state| 0)
+    ///     .join().assert_properties();
+    /// ```
+    #[must_use = "Checkers run on background threads. \
+                  Consider calling join() or report(...), for example."]
+    pub fn spawn_guided(
+        self, heuristic: impl Fn(&M, &M::State) -> u64 + Send + Sync + 'static)
+        -> impl Checker<M>
+    where M: Model + Send + Sync + 'static,
+          M::State: Hash + Send + Sync + 'static,
+    {
+        self.assert_options_supported_by("spawn_guided");
+        guided::GuidedChecker::spawn(self, Box::new(heuristic))
+    }
+
+    /// Indicates where the checker records generated states, such as [`StateStore::OnDisk`] for
+    /// models whose generated states would otherwise exhaust memory. [`StateStore::Bloom`] is not
+    /// supported by [`CheckerBuilder::spawn_bfs`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// model.checker()
+    ///     .state_store(StateStore::OnDisk {
+    ///         path: "/tmp/stateright".into(),
+    ///         cache_bytes: 1 << 30,
+    ///     })
+    ///     .spawn_bfs().join().assert_properties();
+    /// ```
+    pub fn state_store(self, state_store: StateStore) -> Self {
+        Self { state_store, .. self }
+    }
+
+    /// Indicates how the checker distinguishes states. By default ([`FingerprintMode::Bits64`])
+    /// distinct states with the same fingerprint go undetected, which becomes likely for state
+    /// spaces with billions of states (see [`Checker::fingerprint_collision_probability`]). The
+    /// other modes use more memory to detect such collisions, in which case checking stops and
+    /// [`Checker::stop_reason`] indicates [`StopReason::FingerprintCollision`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker()
+    ///     .fingerprint(FingerprintMode::Bits128)
+    ///     .spawn_bfs().join();
+    /// assert_eq!(checker.stop_reason(), None);
+    /// ```
+    pub fn fingerprint(self, mode: FingerprintMode) -> Self
+    where M::State: Clone + PartialEq,
+    {
+        Self { collision_detector: collision::CollisionDetector::new(mode), .. self }
+    }
+
+    /// Serializes and compresses states awaiting evaluation, decompressing each when it is
+    /// evaluated. The frontier of a breadth-first search often dominates memory usage, and
+    /// states with repetitive contents (such as histories of register or consensus operations)
+    /// commonly compress to a fraction of their size, at the cost of CPU time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker()
+    ///     .compress_states()
+    ///     .spawn_bfs().join().assert_properties();
+    /// ```
+    pub fn compress_states(self) -> Self
+    where M::State: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        Self { state_compressor: Some(compress::StateCompressor::new()), .. self }
+    }
+
+    /// Indicates a function that scores pending states, which the checker then evaluates in order
+    /// of decreasing score (with ties following the usual traversal order). Every reachable state
+    /// is still checked, but a score that favors "interesting" states (e.g. with longer histories
+    /// or fewer messages in flight) can surface discoveries much earlier in large state spaces.
+    /// Breadth-first search then no longer finds the shortest path to each discovery.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker()
+    ///     .frontier_priority(|_model, _state| 0)
+    ///     .spawn_bfs().join().assert_properties();
+    /// ```
+    pub fn frontier_priority(
+        self, frontier_priority: impl Fn(&M, &M::State) -> i64 + Send + Sync + 'static) -> Self
+    {
+        Self { frontier_priority: Some(Box::new(frontier_priority)), .. self }
+    }
+
+    /// Sets the maximum number of steps from an initial state, beyond which successors are not
+    /// explored. Unlike counting steps in the state for [`Model::within_boundary`], this does not
+    /// enlarge the state space. As with the boundary, [`Checker::boundary_limited_properties`]
+    /// names `eventually` properties that only hold because of the bound.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker().max_depth(10).spawn_bfs().join().assert_properties();
+    /// ```
+    pub fn max_depth(self, max_depth: usize) -> Self {
+        Self { limits: ExplorationLimits { max_depth: Some(max_depth), .. self.limits }, .. self }
+    }
+
+    /// Sets the maximum number of actions explored from each state, ignoring those after the
+    /// first `max_actions_per_state` returned by [`Model::actions`]. Useful with models that list
+    /// the most likely actions first.
+    pub fn max_actions_per_state(self, max_actions_per_state: usize) -> Self {
+        Self {
+            limits: ExplorationLimits {
+                max_actions_per_state: Some(max_actions_per_state),
+                .. self.limits
+            },
+            .. self
+        }
+    }
+
+    /// Indicates that the checker should count the transitions taken for each kind of action (per
+    /// [`Model::action_kind`]), which [`Checker::coverage`] returns and [`Checker::report`]
+    /// includes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker().track_coverage().spawn_bfs().join();
+    /// assert!(checker.coverage().never_taken().is_empty());
+    /// ```
+    pub fn track_coverage(self) -> Self
+    where M: 'static,
+          M::Action: Debug,
+    {
+        let action_kind: coverage::ActionKind<M> = Box::new(|model, action| model.action_kind(action));
+        Self { action_kind: Some(action_kind), .. self }
+    }
+
+    /// Streams the explored state graph to a [`StateGraphSink`] while checking, such as
+    /// [`SqlGraphSink`].
+    pub fn graph_sink(self, sink: impl StateGraphSink<M> + Send + Sync + 'static) -> Self
+    where M::Action: Clone,
+    {
+        let graph_exporter = graph_sink::GraphExporter {
+            sink: Box::new(sink),
+            clone_action: M::Action::clone,
+        };
+        Self { graph_exporter: Some(graph_exporter), .. self }
+    }
+
+    /// Registers a predicate that the [Explorer](CheckerBuilder::serve) can search for among the
+    /// states reachable from the current state, such as "leader elected", which saves manually
+    /// stepping through many transitions to reach an interesting configuration. Unlike a
+    /// [`Property`], a named predicate does not affect checking.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// model.checker()
+    ///     .named_predicate("any state", |_, _| true)
+    ///     .serve("localhost:3000");
+    /// ```
+    pub fn named_predicate(mut self, name: &'static str, predicate: fn(&M, &M::State) -> bool) -> Self {
+        self.named_predicates.push((name, predicate));
+        self
+    }
+
+    /// Indicates whether to record the predecessor of each generated state, so that the path to a
+    /// discovery can be reconstructed by re-executing actions from an initial state. Otherwise
+    /// each pending state carries the fingerprints of its path, which can dominate memory usage
+    /// for models with deep state spaces, as the frontier retains many long paths. Recording
+    /// predecessors instead costs more memory per generated state and more CPU whenever a path is
+    /// needed, such as for a discovery or [`CheckerBuilder::visitor`]. Applies to
+    /// [`CheckerBuilder::spawn_dfs`], as [`CheckerBuilder::spawn_bfs`] always records
+    /// predecessors.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// model.checker().record_predecessors(true).spawn_dfs().join().assert_properties();
+    /// ```
+    pub fn record_predecessors(self, record_predecessors: bool) -> Self {
+        Self { record_predecessors, .. self }
+    }
+
+    /// Continues checking from a snapshot written by [`Checker::checkpoint`], rather than from
+    /// the initial states. The model must be the one that was being checked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// let path = "/tmp/stateright.checkpoint";
+    /// let checker = if std::path::Path::new(path).exists() {
+    ///     model.checker().resume_from(path).unwrap().spawn_bfs()
+    /// } else {
+    ///     model.checker().spawn_bfs()
+    /// };
+    /// while !checker.is_done() {
+    ///     std::thread::sleep(std::time::Duration::from_secs(3_600));
+    ///     checker.checkpoint(path).unwrap();
+    /// }
+    /// checker.join().assert_properties();
+    /// ```
+    pub fn resume_from(self, path: impl AsRef<std::path::Path>) -> std::io::Result<Self>
+    where M::State: serde::de::DeserializeOwned,
+    {
+        let checkpoint = checkpoint::Checkpoint::read(&self.model, path.as_ref())?;
+        Ok(Self { checkpoint: Some(checkpoint), .. self })
+    }
+
+    /// Indicates that checking should stop once any property has a discovery, such as the first
+    /// counterexample to a safety property, rather than continuing to resolve the remaining
+    /// properties. Combine with [`CheckerBuilder::target_properties`] to ignore examples of
+    /// `sometimes` properties.
+    pub fn stop_on_first_discovery(self) -> Self {
+        Self { stop_on_first_discovery: true, .. self }
+    }
+
     /// Sets the number of states that the checker should aim to generate. For performance reasons
     /// the checker may exceed this number, but it will never generate fewer states if more exist.
     pub fn target_generated_count(self, target_generated_count: usize) -> Self {
         Self { target_generated_count: NonZeroUsize::new(target_generated_count), .. self }
     }
 
+    /// Stops checking once the estimated memory used to track generated and pending states (see
+    /// [`CheckerStatus::estimated_memory_bytes`]) exceeds a limit, so that a long run ends with
+    /// partial results rather than being killed by the operating system. Checking is then
+    /// inconclusive: [`Checker::is_done`] remains `false` and [`Checker::stop_reason`] indicates
+    /// [`StopReason::MemoryLimit`]. Discoveries found before stopping are retained.
+    ///
+    /// The estimate excludes memory owned by the states themselves (such as the contents of a
+    /// `Vec`) unless [`CheckerBuilder::state_heap_size`] is specified, so leave headroom.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker().max_memory(4 << 30).spawn_bfs().join();
+    /// assert!(!checker.reached_memory_limit());
+    /// ```
+    pub fn max_memory(mut self, max_memory_bytes: usize) -> Self {
+        self.stop_conditions.max_memory_bytes = NonZeroUsize::new(max_memory_bytes);
+        self
+    }
+
+    /// Indicates how many bytes of heap memory a state owns (such as the contents of a `Vec`), so
+    /// that [`CheckerStatus::estimated_memory_bytes`] and therefore [`CheckerBuilder::max_memory`]
+    /// account for it. Each pending state is assumed to own the average of the evaluated states.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*;
+    /// # #[derive(Clone)] struct Queue;
+    /// # impl Model for Queue {
+    /// #     type State = Vec<u64>; type Action = u64;
+    /// #     fn init_states(&self) -> Vec<Self::State> { vec![Vec::new()] }
+    /// #     fn actions(&self, _: &Self::State, _: &mut Vec<Self::Action>) {}
+    /// #     fn next_state(&self, _: &Self::State, _: Self::Action) -> Option<Self::State> { None }
+    /// # }
+    /// let checker = Queue.checker()
+    ///     .state_heap_size(|s| s.capacity() * std::mem::size_of::<u64>())
+    ///     .max_memory(4 << 30)
+    ///     .spawn_bfs().join();
+    /// assert!(!checker.reached_memory_limit());
+    /// ```
+    pub fn state_heap_size(self, state_heap_size: fn(&M::State) -> usize) -> Self {
+        Self { state_heap_size: Some(state_heap_size), .. self }
+    }
+
+    /// Stops checking once it has run for a duration. Checking is then inconclusive:
+    /// [`Checker::is_done`] remains `false` and [`Checker::stop_reason`] indicates
+    /// [`StopReason::TimeLimit`]. Discoveries found before stopping are retained.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// use std::time::Duration;
+    /// let checker = model.checker().timeout(Duration::from_secs(60)).spawn_bfs().join();
+    /// assert_eq!(checker.stop_reason(), None);
+    /// ```
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.stop_conditions.timeout = Some(timeout);
+        self
+    }
+
+    /// Stops checking once a [`CancellationToken`] is cancelled, such as from another thread.
+    /// Checking is then inconclusive: [`Checker::is_done`] remains `false` and
+    /// [`Checker::stop_reason`] indicates [`StopReason::Cancelled`]. Discoveries found before
+    /// stopping are retained.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.stop_conditions.cancellation_token = Some(token);
+        self
+    }
+
+    /// Restricts checking to the named properties, so that a run is not prolonged by properties
+    /// that are irrelevant to it, such as `sometimes` properties that are only resolved late in
+    /// the search. The other properties are ignored, including by [`Checker::assert_properties`].
+    /// Panics if a property does not exist.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*;
+    /// # struct Counter;
+    /// # impl Model for Counter {
+    /// #     type State = u8;
+    /// #     type Action = ();
+    /// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+    /// #     fn actions(&self, _: &u8, actions: &mut Vec<()>) { actions.push(()) }
+    /// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { s.checked_add(1) }
+    /// #     fn properties(&self) -> Vec<Property<Self>> { vec![
+    /// #         Property::always("small", |_, s| *s < 10),
+    /// #         Property::sometimes("large", |_, s| *s == 200),
+    /// #     ] }
+    /// # }
+    /// let checker = Counter.checker()
+    ///     .target_properties(&["small"])
+    ///     .stop_on_first_discovery()
+    ///     .spawn_bfs().join();
+    /// assert_eq!(checker.discovery("small").unwrap().last_state(), &10);
+    /// checker.assert_no_discovery("large");
+    /// ```
+    pub fn target_properties(self, names: &[&'static str]) -> Self {
+        let mut target_properties = Vec::new();
+        for &name in names {
+            self.model.property(name); // panics if missing
+            if !target_properties.contains(&name) { target_properties.push(name); }
+        }
+        Self { target_properties: Some(target_properties), .. self }
+    }
+
     /// Sets the number of threads available for model checking. For maximum performance this
-    /// should match the number of cores.
+    /// should match the number of cores, which is what a `thread_count` of zero indicates.
+    /// Threads that run out of work signal the others, which then share their pending states.
+    /// Applies to [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`].
     pub fn threads(self, thread_count: usize) -> Self {
+        let thread_count = if thread_count == 0 {
+            std::thread::available_parallelism().map_or(1, |n| n.get())
+        } else {
+            thread_count
+        };
         Self { thread_count, .. self }
     }
 
@@ -150,6 +733,50 @@ impl<M: Model> CheckerBuilder<M> {
     pub fn visitor(self, visitor: impl CheckerVisitor<M> + Send + Sync + 'static) -> Self {
         Self { visitor: Some(Box::new(visitor)), .. self }
     }
+
+    /// The properties to check. See [`CheckerBuilder::target_properties`].
+    fn checked_properties(&self) -> Vec<Property<M>> {
+        match &self.target_properties {
+            None => self.model.properties(),
+            Some(names) => names.iter().map(|name| self.model.property(name)).collect(),
+        }
+    }
+
+    /// The number of discoveries after which checking stops, given the number of checked
+    /// properties. See [`CheckerBuilder::stop_on_first_discovery`].
+    fn target_discovery_count(&self, property_count: usize) -> usize {
+        if self.stop_on_first_discovery { property_count.min(1) } else { property_count }
+    }
+
+    /// Panics if an option is set that the checker spawned by the named method does not support,
+    /// rather than letting the checker silently ignore it.
+    fn assert_options_supported_by(&self, spawn_method: &str) {
+        const BFS_AND_DFS: &[&str] = &["spawn_bfs", "spawn_dfs"];
+        let options: [(&str, bool, &[&str]); 14] = [
+            ("state_store", self.state_store != StateStore::InMemory,
+             &["spawn_bfs", "spawn_dfs", "spawn_distributed"]),
+            ("fingerprint", self.collision_detector.is_some(), BFS_AND_DFS),
+            ("compress_states", self.state_compressor.is_some(), &["spawn_bfs"]),
+            ("frontier_priority", self.frontier_priority.is_some(), BFS_AND_DFS),
+            ("track_coverage", self.action_kind.is_some(), BFS_AND_DFS),
+            ("graph_sink", self.graph_exporter.is_some(), BFS_AND_DFS),
+            ("resume_from", self.checkpoint.is_some(), &["spawn_bfs"]),
+            ("max_memory", self.stop_conditions.max_memory_bytes.is_some(), BFS_AND_DFS),
+            ("state_heap_size", self.state_heap_size.is_some(), BFS_AND_DFS),
+            ("timeout", self.stop_conditions.timeout.is_some(), BFS_AND_DFS),
+            ("cancellation_token", self.stop_conditions.cancellation_token.is_some(), BFS_AND_DFS),
+            ("threads", self.thread_count > 1, BFS_AND_DFS),
+            ("target_generated_count", self.target_generated_count.is_some(),
+             &["spawn_bfs", "spawn_dfs", "spawn_iddfs", "spawn_delay_bounded",
+               "spawn_context_bounded", "spawn_guided"]),
+            ("record_predecessors", self.record_predecessors, BFS_AND_DFS),
+        ];
+        for (option, is_set, supported_by) in options.iter() {
+            assert!(!is_set || supported_by.contains(&spawn_method),
+                    "CheckerBuilder::{} is not supported by {}. Supported by: {}.",
+                    option, spawn_method, supported_by.join(", "));
+        }
+    }
 }
 
 /// Implementations perform [`Model`] checking.
@@ -177,11 +804,216 @@ pub trait Checker<M: Model> {
     /// have been visited.
     fn is_done(&self) -> bool;
 
+    /// Summarizes progress, such as the number of pending states and the generation rate. Can be
+    /// polled from another thread while checking runs.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker().spawn_bfs();
+    /// println!("{}", checker.status());
+    /// assert_eq!(checker.join().status().pending_count, 0);
+    /// ```
+    fn status(&self) -> CheckerStatus;
+
+    /// Starts the [Explorer](CheckerBuilder::serve) for a checker that may still be running,
+    /// rather than requiring the run to be structured around the Explorer upfront. Newly
+    /// discovered property violations and stats are streamed to the browser UI as checking
+    /// progresses.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker().spawn_bfs();
+    /// // ... later, once the run looks interesting ...
+    /// checker.serve("localhost:3000");
+    /// ```
+    fn serve(self, addresses: impl std::net::ToSocketAddrs) -> std::sync::Arc<Self>
+    where Self: 'static + Sized + Send + Sync,
+          M: 'static + Send + Sync,
+          M::Action: Debug + Send + Sync,
+          M::State: Debug + Hash + Send + Sync,
+    {
+        explorer::serve_checker(self, addresses)
+    }
+
+    /// Indicates why checking stopped before it was done, if it did, in which case the results
+    /// are inconclusive. See [`CheckerBuilder::max_memory`], [`CheckerBuilder::timeout`],
+    /// [`CheckerBuilder::cancellation_token`], and [`CheckerBuilder::fingerprint`].
+    fn stop_reason(&self) -> Option<StopReason> { None }
+
+    /// Indicates whether checking stopped because the estimated memory exceeded the limit set via
+    /// [`CheckerBuilder::max_memory`], in which case the results are inconclusive.
+    fn reached_memory_limit(&self) -> bool {
+        self.stop_reason() == Some(StopReason::MemoryLimit)
+    }
+
+    /// Snapshots the generated fingerprints, pending states, and discoveries to a file, from which
+    /// [`CheckerBuilder::resume_from`] can continue checking later (for instance after the process
+    /// is interrupted). Checking pauses while the file is written. The previous file at the path
+    /// is only replaced once the snapshot is complete.
+    ///
+    /// Only supported by [`CheckerBuilder::spawn_bfs`] with [`StateStore::InMemory`]. Other
+    /// checkers return an [`std::io::ErrorKind::Unsupported`] error.
+    fn checkpoint(&self, _path: impl AsRef<std::path::Path>) -> std::io::Result<()>
+    where M::State: serde::Serialize,
+    {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Checkpointing is not supported by this checker."))
+    }
+
+    /// Describes the bound beyond which this checker does not explore, if any, such as
+    /// `max_preemptions=2` for [`CheckerBuilder::spawn_context_bounded`].
+    fn exploration_bound(&self) -> Option<String> { None }
+
+    /// Estimates the probability that the next new state would be mistakenly treated as already
+    /// generated and omitted from checking, which is only possible with [`StateStore::Bloom`].
+    /// Otherwise `None`.
+    fn omission_probability(&self) -> Option<f64> { None }
+
+    /// Estimates the probability that distinct states with the same fingerprint went
+    /// undetected, given the number of generated states and the [`FingerprintMode`] set via
+    /// [`CheckerBuilder::fingerprint`].
+    fn fingerprint_collision_probability(&self) -> f64 {
+        FingerprintMode::Bits64.collision_probability(self.generated_count())
+    }
+
+    /// Returns how often each kind of action was taken, if enabled via
+    /// [`CheckerBuilder::track_coverage`]. Otherwise empty.
+    fn coverage(&self) -> Coverage { Coverage::default() }
+
+    /// Summarizes the explored state space: the number of states evaluated at each depth, the
+    /// branching factor, and (if enabled via [`CheckerBuilder::track_coverage`]) how often each
+    /// kind of action was taken. Only tracked by [`CheckerBuilder::spawn_bfs`] and
+    /// [`CheckerBuilder::spawn_dfs`]. Otherwise empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use stateright::*; let model = ();
+    /// let checker = model.checker().spawn_bfs().join();
+    /// println!("{}", checker.stats());
+    /// assert_eq!(checker.stats().states_per_depth, vec![1]);
+    /// ```
+    fn stats(&self) -> CheckerStats { CheckerStats::default() }
+
+    /// Returns paths to the deadlocks found so far: explored states from which no action changes
+    /// the state, excluding intended terminal states per [`Model::is_terminal`] and states whose
+    /// successors were pruned by [`CheckerBuilder::max_depth`]. Only tracked by
+    /// [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`].
+    ///
+    /// The successors of a state are only explored while some property awaits a discovery, so
+    /// deadlocks may go unreported once every property has a discovery. In particular, a model
+    /// without properties is not explored beyond its initial states. To enumerate every
+    /// deadlock, check a property that never has a discovery, such as an `always` property that
+    /// holds for every state.
+    fn deadlocks(&self) -> Vec<Path<M::State, M::Action>> { Vec::new() }
+
+    /// Returns the names of `eventually` properties that lack a counterexample but only hold
+    /// within [`Model::within_boundary`]. That is, the boundary pruned a state from some path on
+    /// which the property was still unmet, so a counterexample may exist beyond the boundary.
+    fn boundary_limited_properties(&self) -> Vec<&'static str> { Vec::new() }
+
+    /// The properties that this checker checks, which are the model's properties unless
+    /// restricted via [`CheckerBuilder::target_properties`].
+    fn checked_properties(&self) -> Vec<Property<M>> { self.model().properties() }
+
     /// Looks up a discovery by property name. Panics if the property does not exist.
     fn discovery(&self, name: &'static str) -> Option<Path<M::State, M::Action>> {
         self.discoveries().remove(name)
     }
 
+    /// Looks up a discovery by property name and then shrinks it via [`Path::minimize`], which
+    /// tends to make long counterexamples considerably easier to understand. Panics if the
+    /// property does not exist.
+    fn minimized_discovery(&self, name: &'static str) -> Option<Path<M::State, M::Action>>
+    where M::State: Clone + Hash + PartialEq,
+          M::Action: Clone + PartialEq,
+    {
+        let property = self.model().property(name);
+        self.discovery(name).map(|path| {
+            path.minimize(self.model(), |path| is_discovery(self.model(), &property, path))
+        })
+    }
+
+    /// Renders the reachable state graph in [DOT](https://graphviz.org/doc/info/lang.html) format,
+    /// including at most `max_states` states in breadth-first order. Nodes are labeled via
+    /// `summarize`, and edges are labeled with actions. The graph is regenerated from the model
+    /// rather than the checker's record of visited states, so this is best suited to small models.
+    fn state_graph_dot(&self, max_states: usize, summarize: impl Fn(&M::State) -> String) -> String
+    where M::State: Hash,
+          M::Action: Debug,
+    {
+        fn escape(label: &str) -> String {
+            label.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+        }
+
+        let model = self.model();
+        let mut ids = HashMap::new();
+        let mut pending = VecDeque::new();
+        let mut dot = "digraph {\n".to_string();
+        let mut add_node = |state: M::State, dot: &mut String, pending: &mut VecDeque<_>| {
+            let fp = fingerprint(&state);
+            if let Some(id) = ids.get(&fp) { return Some(*id) }
+            if ids.len() == max_states { return None }
+            let id = ids.len();
+            ids.insert(fp, id);
+            dot.push_str(&format!("  {} [label=\"{}\"];\n", id, escape(&summarize(&state))));
+            pending.push_back((id, state));
+            Some(id)
+        };
+        for state in model.init_states() {
+            add_node(state, &mut dot, &mut pending);
+        }
+        while let Some((src, state)) = pending.pop_front() {
+            for (action, next_state) in model.next_steps(&state) {
+                if !model.within_boundary(&next_state) { continue }
+                if let Some(dst) = add_node(next_state, &mut dot, &mut pending) {
+                    dot.push_str(&format!(
+                        "  {} -> {} [label=\"{}\"];\n", src, dst, escape(&format!("{:?}", action))));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Summarizes the reachable state space. See [`StateSpaceDigest`].
+    fn state_space_digest(&self) -> StateSpaceDigest
+    where M::State: Hash,
+          M::Action: Debug,
+    {
+        StateSpaceDigest::new(self.model())
+    }
+
+    /// Compares the [`Checker::state_space_digest`] against a snapshot file, panicking with a
+    /// line-by-line diff if they differ. If the file does not exist, then the digest is written
+    /// to it instead, so a snapshot can be updated by deleting the file and rerunning.
+    fn assert_matches_snapshot(&self, path: impl AsRef<std::path::Path>)
+    where M::State: Hash,
+          M::Action: Debug,
+    {
+        let path = path.as_ref();
+        let digest = self.state_space_digest().to_string();
+        match std::fs::read_to_string(path) {
+            Ok(snapshot) => {
+                if snapshot != digest {
+                    panic!("State space does not match snapshot {}. Delete the file to update it.\n{}",
+                           path.display(), crate::util::diff_lines(&snapshot, &digest));
+                }
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                if let Err(err) = std::fs::write(path, digest) {
+                    panic!("Unable to write snapshot {}: {}", path.display(), err);
+                }
+            }
+            Err(err) => panic!("Unable to read snapshot {}: {}", path.display(), err),
+        }
+    }
+
     /// Periodically emits a status message.
     fn report(self, w: &mut impl std::io::Write) -> Self
     where M::Action: Debug,
@@ -190,19 +1022,50 @@ pub trait Checker<M: Model> {
     {
         // Start with the checking status.
         let method_start = Instant::now();
-        while !self.is_done() {
+        while !self.is_done() && self.stop_reason().is_none() {
             let _ = writeln!(w, "Checking. generated={}", self.generated_count());
             std::thread::sleep(std::time::Duration::from_millis(1_000));
         }
-        let _ = writeln!(w, "Done. generated={}, sec={}",
-                 self.generated_count(),
-                 method_start.elapsed().as_secs());
+        if let Some(reason) = self.stop_reason() {
+            let _ = writeln!(w, "Inconclusive: {}. generated={}, sec={}, mem_mb={}",
+                     reason,
+                     self.generated_count(),
+                     method_start.elapsed().as_secs(),
+                     self.status().estimated_memory_bytes / 1_000_000);
+        } else {
+            let _ = writeln!(w, "Done. generated={}, sec={}",
+                     self.generated_count(),
+                     method_start.elapsed().as_secs());
+        }
+        if let Some(bound) = self.exploration_bound() {
+            let _ = writeln!(w, "Bounded by {}. States beyond the bound were not checked.", bound);
+        }
+        if let Some(p) = self.omission_probability() {
+            let _ = writeln!(w, "States may have been omitted due to hash collisions. \
+                                 omission_probability={:.3e}", p);
+        }
+        let p = self.fingerprint_collision_probability();
+        if p > 1e-6 {
+            let _ = writeln!(w, "States may have been omitted due to fingerprint collisions. \
+                                 collision_probability={:.3e}", p);
+        }
+        let coverage = self.coverage();
+        for (kind, count) in &coverage.taken {
+            let _ = writeln!(w, "Action \"{}\" taken={}", kind, count);
+        }
+        for kind in coverage.never_taken() {
+            let _ = writeln!(w, "Action \"{}\" was enabled but never changed the state.", kind);
+        }
 
         // Finish with a discovery summary.
         for (name, path) in self.discoveries() {
             let _ = write!(w, "Discovered \"{}\" {} {}",
                            name, self.discovery_classification(name), path);
         }
+        for name in self.boundary_limited_properties() {
+            let _ = writeln!(w, "Property \"{}\" holds within boundary. \
+                                 The boundary pruned paths on which it was unmet.", name);
+        }
 
         self
     }
@@ -223,7 +1086,7 @@ pub trait Checker<M: Model> {
     where M::Action: Debug,
           M::State: Debug,
     {
-        for p in self.model().properties() {
+        for p in self.checked_properties() {
             match p.expectation {
                 Expectation::Always => self.assert_no_discovery(p.name),
                 Expectation::Eventually => self.assert_no_discovery(p.name),
@@ -257,7 +1120,7 @@ pub trait Checker<M: Model> {
     /// Panics if the specified actions do not result in a discovery for the specified property
     /// name.
     fn assert_discovery(&self, name: &'static str, actions: Vec<M::Action>)
-    where M::State: Debug + PartialEq,
+    where M::State: Debug + Hash + PartialEq,
           M::Action: Debug + PartialEq,
     {
         let mut additional_info: Vec<&'static str> = Vec::new();
@@ -268,9 +1131,10 @@ pub trait Checker<M: Model> {
                 let property = self.model().property(name);
                 match property.expectation {
                     Expectation::Always => {
-                        if !(property.condition)(self.model(), path.last_state()) { return }
+                        if is_discovery(self.model(), &property, &path) { return }
                     }
                     Expectation::Eventually => {
+                        if is_discovery(self.model(), &property, &path) { return }
                         let states = path.into_states();
                         let is_liveness_satisfied = states.iter().any(|s| {
                             (property.condition)(self.model(), s)
@@ -280,7 +1144,6 @@ pub trait Checker<M: Model> {
                             self.model().actions(&states.last().unwrap(), &mut actions);
                             actions.is_empty()
                         };
-                        if !is_liveness_satisfied && is_path_terminal { return }
                         if is_liveness_satisfied {
                             additional_info.push("incorrect counterexample satisfies eventually property");
                         }
@@ -304,6 +1167,58 @@ pub trait Checker<M: Model> {
     }
 }
 
+/// Indicates whether a path is a discovery for a property: an example for a `sometimes` property,
+/// or a counterexample for an `always`/`eventually` property.
+fn is_discovery<M: Model>(model: &M, property: &Property<M>, path: &Path<M::State, M::Action>) -> bool
+where M::State: Hash,
+{
+    match property.expectation {
+        Expectation::Always => {
+            !(property.condition)(model, path.last_state())
+                || matches!(
+                    (property.action_condition, path.last_step()),
+                    (Some(action_condition), Some((last_state, action, next_state)))
+                        if !action_condition(model, last_state, action, next_state))
+        }
+        Expectation::Eventually => {
+            // The path must end in a terminal state or return to an earlier state (a lasso), and
+            // an obligation must remain unmet through the end of the path, including the loop.
+            let states: Vec<_> = path.states().collect();
+            let last_fp = fingerprint(path.last_state());
+            let loop_start = states[..states.len() - 1].iter()
+                .position(|s| fingerprint(s) == last_fp);
+            let mut actions = Vec::new();
+            model.actions(path.last_state(), &mut actions);
+            if !actions.is_empty() && loop_start.is_none() { return false }
+            if let (Some(loop_start), Some(lasso_condition)) = (loop_start, property.lasso_condition) {
+                return !lasso_condition(model, &states[loop_start..]);
+            }
+            let satisfied = states.iter().rposition(|s| (property.condition)(model, s));
+            match (satisfied, loop_start) {
+                (Some(satisfied), Some(loop_start)) if loop_start <= satisfied => false,
+                (None, _) if property.trigger.is_none() => true,
+                _ => {
+                    let unmet_from = satisfied.map_or(0, |i| i + 1);
+                    states[unmet_from..].iter().any(|s| property.is_triggered(model, s))
+                }
+            }
+        }
+        Expectation::Sometimes => (property.condition)(model, path.last_state()),
+    }
+}
+
+/// Checkers record a discovery for an action property at the state from which a violating step is
+/// taken, so the path to that state is extended by the step.
+fn with_violating_step<M: Model>(model: &M, name: &'static str, mut path: Path<M::State, M::Action>)
+    -> Path<M::State, M::Action>
+{
+    let property = model.property(name);
+    if let Some((action, next_state)) = property.violating_step(model, path.last_state()) {
+        path.push(action, next_state);
+    }
+    path
+}
+
 // EventuallyBits tracks one bit per 'eventually' property being checked. Properties are assigned
 // bit-numbers just by counting the 'eventually' properties up from 0 in the properties list. If a
 // bit is present in a bitset, the property has _not_ been found on this path yet. Bits are removed
@@ -313,9 +1228,144 @@ pub trait Checker<M: Model> {
 // a counterexample to the property.
 type EventuallyBits = id_set::IdSet;
 
+/// Bounds on exploration that are independent of [`Model::within_boundary`]. See
+/// [`CheckerBuilder::max_depth`] and [`CheckerBuilder::max_actions_per_state`].
+#[derive(Clone, Copy, Debug, Default)]
+struct ExplorationLimits {
+    max_depth: Option<usize>,
+    max_actions_per_state: Option<usize>,
+}
+
+impl ExplorationLimits {
+    /// Collects the actions to explore from a state at the specified depth, returning `false` if
+    /// the depth bound pruned them, in which case the state is not terminal.
+    ///
+    /// Successors beyond the depth bound are pruned like those outside the boundary, so the
+    /// `eventually` properties indicated by `ebits` are passed to [`record_boundary_pruned`].
+    fn actions<M: Model>(
+        self,
+        model: &M,
+        state: &M::State,
+        depth: usize,
+        actions: &mut Vec<M::Action>,
+        boundary_pruned: &parking_lot::Mutex<EventuallyBits>,
+        ebits: &EventuallyBits)
+        -> bool
+    {
+        model.actions(state, actions);
+        if let Some(max_actions_per_state) = self.max_actions_per_state {
+            actions.truncate(max_actions_per_state);
+        }
+        if self.max_depth.is_some_and(|max_depth| max_depth <= depth) && !actions.is_empty() {
+            actions.clear();
+            record_boundary_pruned(boundary_pruned, ebits);
+            return false
+        }
+        true
+    }
+}
+
+/// The `eventually` properties that are unmet at an initial state, which are those without a
+/// trigger.
+fn initial_ebits<M: Model>(properties: &[Property<M>]) -> EventuallyBits {
+    let mut ebits = EventuallyBits::new();
+    for (i, p) in properties.iter().enumerate() {
+        if let Property { expectation: Expectation::Eventually, trigger: None, .. } = p {
+            ebits.insert(i);
+        }
+    }
+    ebits
+}
+
+/// Evaluates each property at a state unless `skip` indicates otherwise (typically because the
+/// property already has a discovery), calling `discover` with the name of each property for which
+/// the state is a discovery and tracking unmet `eventually` properties in `ebits`. Returns whether
+/// an evaluated property awaits a discovery among the state's successors, which is not the case
+/// for a discovered property or a monotone property that holds.
+fn check_properties<M: Model>(
+    model: &M,
+    properties: &[Property<M>],
+    state: &M::State,
+    ebits: &mut EventuallyBits,
+    skip: impl Fn(&Property<M>) -> bool,
+    mut discover: impl FnMut(&'static str))
+    -> bool
+{
+    let mut is_awaiting_discoveries = false;
+    for (i, property) in properties.iter().enumerate() {
+        if skip(property) { continue }
+        match property {
+            Property { expectation: Expectation::Always, .. } => {
+                if !property.holds(model, state) {
+                    discover(property.name);
+                } else if !property.is_monotone {
+                    is_awaiting_discoveries = true;
+                }
+            },
+            Property { expectation: Expectation::Sometimes, condition: sometimes, .. } => {
+                if sometimes(model, state) {
+                    discover(property.name);
+                } else {
+                    is_awaiting_discoveries = true;
+                }
+            },
+            Property { expectation: Expectation::Eventually, condition: eventually, .. } => {
+                // "Eventually" property discoveries are only identified at terminal states, so
+                // the property awaits a discovery regardless of whether it is now satisfied (i.e.
+                // it might be falsifiable via a different path). The exception is a monotone
+                // property that holds, as it holds for every successor too.
+                if eventually(model, state) {
+                    ebits.remove(i);
+                    if !property.is_monotone { is_awaiting_discoveries = true; }
+                } else {
+                    if property.is_triggered(model, state) { ebits.insert(i); }
+                    is_awaiting_discoveries = true;
+                }
+            }
+        }
+    }
+    is_awaiting_discoveries
+}
+
+/// Calls `discover` with the name of each `eventually` property that is unmet at a terminal
+/// state, unless `skip` indicates otherwise.
+fn check_terminal_state<M: Model>(
+    properties: &[Property<M>],
+    ebits: &EventuallyBits,
+    skip: impl Fn(&Property<M>) -> bool,
+    mut discover: impl FnMut(&'static str))
+{
+    for (i, property) in properties.iter().enumerate() {
+        if ebits.contains(i) && !skip(property) {
+            discover(property.name);
+        }
+    }
+}
+
+/// Records that the boundary pruned a successor of a state for which the `eventually` properties
+/// indicated by `ebits` were still unmet. See [`Checker::boundary_limited_properties`].
+fn record_boundary_pruned(boundary_pruned: &parking_lot::Mutex<EventuallyBits>, ebits: &EventuallyBits) {
+    if ebits.is_empty() { return }
+    boundary_pruned.lock().inplace_union(ebits);
+}
+
+/// Names the properties recorded by [`record_boundary_pruned`] that lack a discovery.
+fn boundary_limited_properties<M: Model, V>(
+    properties: &[Property<M>],
+    boundary_pruned: &parking_lot::Mutex<EventuallyBits>,
+    discoveries: &dashmap::DashMap<&'static str, V>)
+    -> Vec<&'static str>
+{
+    let boundary_pruned = boundary_pruned.lock();
+    properties.iter().enumerate()
+        .filter(|(i, p)| boundary_pruned.contains(*i) && !discoveries.contains_key(p.name))
+        .map(|(_, p)| p.name)
+        .collect()
+}
+
 #[cfg(test)]
 mod test_eventually_property_checker {
-    use crate::{Checker, Property};
+    use crate::{Checker, Model, Property};
     use crate::test_util::dgraph::DGraph;
 
     fn eventually_odd() -> Property<DGraph> {
@@ -364,6 +1414,35 @@ mod test_eventually_property_checker {
             vec![2, 4, 6]);
     }
 
+    #[test]
+    fn flags_properties_that_only_hold_within_boundary() {
+        // The boundary prunes 8, a terminal state that would otherwise be a counterexample.
+        let model = DGraph::with_property(eventually_odd())
+            .with_path(vec![0, 2, 8])
+            .with_path(vec![0, 2, 5]);
+        let checker = model.check();
+        assert!(checker.discovery("odd").is_some());
+        assert!(checker.boundary_limited_properties().is_empty());
+
+        let model = model.with_boundary(7);
+        let checker = model.clone().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.boundary_limited_properties(), vec!["odd"]);
+        let checker = model.clone().checker().spawn_dfs().join();
+        assert_eq!(checker.boundary_limited_properties(), vec!["odd"]);
+        let checker = model.checker().spawn_iddfs().join();
+        assert_eq!(checker.boundary_limited_properties(), vec!["odd"]);
+
+        // Not flagged if the property was already met on the pruned path.
+        let checker = DGraph::with_property(eventually_odd())
+            .with_path(vec![1, 2, 8])
+            .with_path(vec![1, 2, 5])
+            .with_boundary(7)
+            .check();
+        checker.assert_properties();
+        assert!(checker.boundary_limited_properties().is_empty());
+    }
+
     #[test]
     fn fixme_can_miss_counterexample_when_revisiting_a_state() { // i.e. incorrectly verify
         assert_eq!(
@@ -380,6 +1459,169 @@ mod test_eventually_property_checker {
     }
 }
 
+#[cfg(test)]
+mod test_monotone_property {
+    use crate::{Checker, Model, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    #[test]
+    fn skips_successors_once_monotone_properties_hold() {
+        let at_least_two = || Property::always("at least two", |_, s: &u8| *s >= 2);
+        let model = DGraph::with_property(at_least_two().monotone())
+            .with_path(vec![2, 3, 4, 5]);
+        let checker = model.clone().checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 1);
+        let checker = model.checker().spawn_dfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 1);
+
+        // Only states satisfying the property are pruned.
+        let checker = DGraph::with_property(at_least_two().monotone())
+            .with_path(vec![0, 2, 3])
+            .with_path(vec![0, 1])
+            .check();
+        assert_eq!(checker.discovery("at least two").unwrap().into_states(), vec![0]);
+    }
+
+    #[test]
+    fn skips_successors_once_monotone_eventually_properties_hold() {
+        let committed = || Property::eventually("committed", |_, s: &u8| *s >= 2);
+        let checker = DGraph::with_property(committed().monotone())
+            .with_path(vec![0, 2, 3, 4])
+            .check();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 2);
+        let checker = DGraph::with_property(committed())
+            .with_path(vec![0, 2, 3, 4])
+            .check();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 4);
+    }
+}
+
+#[cfg(test)]
+mod test_action_property {
+    use crate::{Checker, Model, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    #[test]
+    fn can_discover_step_violating_action_property() {
+        let increments = || Property::<DGraph>::always_action(
+            "increments", |_, last, _, next| *next == last + 1);
+        DGraph::with_property(increments())
+            .with_path(vec![0, 1, 2, 3])
+            .check().assert_properties();
+
+        // 3 is first generated by an increment, but the jump to it is still discovered.
+        let model = DGraph::with_property(increments())
+            .with_path(vec![0, 1, 2, 3])
+            .with_path(vec![0, 1, 3]);
+        let checker = model.clone().checker().spawn_bfs().join();
+        assert_eq!(checker.discovery("increments").unwrap().into_states(), vec![0, 1, 3]);
+        checker.assert_discovery("increments", vec![1, 3]);
+        let checker = model.checker().spawn_dfs().join();
+        assert_eq!(checker.discovery("increments").unwrap().into_states(), vec![0, 1, 3]);
+        assert_eq!(
+            checker.minimized_discovery("increments").unwrap().into_actions(),
+            vec![1, 3]);
+    }
+}
+
+#[cfg(test)]
+mod test_leads_to {
+    use crate::{Checker, Model, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    fn one_leads_to_three() -> Property<DGraph> {
+        Property::leads_to("one leads to three", |_, s| *s == 1, |_, s| *s == 3)
+    }
+
+    #[test]
+    fn can_discover_terminal_counterexample() {
+        DGraph::with_property(one_leads_to_three())
+            .with_path(vec![0, 1, 2, 3])
+            .with_path(vec![0, 2]) // never triggered
+            .check().assert_properties();
+        assert_eq!(
+            DGraph::with_property(one_leads_to_three())
+                .with_path(vec![0, 1, 2])
+                .with_path(vec![0, 3])
+                .check().discovery("one leads to three").unwrap().into_states(),
+            vec![0, 1, 2]);
+        assert_eq!(
+            DGraph::with_property(one_leads_to_three())
+                .with_path(vec![0, 3, 1]) // triggered after the condition held
+                .check().discovery("one leads to three").unwrap().into_states(),
+            vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn can_discover_lasso_counterexample() {
+        let checker = DGraph::with_property(one_leads_to_three())
+            .with_path(vec![0, 1, 2, 1])
+            .with_path(vec![0, 1, 2, 3])
+            .checker().spawn_dfs().join();
+        assert_eq!(
+            checker.discovery("one leads to three").unwrap().into_states(),
+            vec![0, 1, 2, 1]);
+        checker.assert_discovery("one leads to three", vec![1, 2, 1]);
+
+        // Meeting the obligation within the loop suffices.
+        DGraph::with_property(one_leads_to_three())
+            .with_path(vec![0, 1, 2, 3, 1])
+            .checker().spawn_dfs().join()
+            .assert_properties();
+    }
+}
+
+#[cfg(test)]
+mod test_deadlocks {
+    use crate::{Checker, Model, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    #[test]
+    fn enumerates_deadlocks() {
+        let model = DGraph::with_property(Property::always("unused", |_, _| true))
+            .with_path(vec![0, 1, 2])
+            .with_path(vec![0, 3, 0]) // cycles are not deadlocks
+            .with_path(vec![0, 4, 9]) // nor are states with successors beyond the boundary
+            .with_path(vec![0, 5])
+            .with_path(vec![0, 6, 6]) // whereas self-loops are
+            .with_boundary(8);
+        let bfs = model.clone().checker().spawn_bfs().join();
+        let dfs = model.clone().checker().spawn_dfs().join();
+        for checker in [bfs.deadlocks(), dfs.deadlocks()] {
+            let mut deadlocks: Vec<_> = checker.into_iter().map(|p| p.into_states()).collect();
+            deadlocks.sort();
+            assert_eq!(deadlocks, vec![vec![0, 1, 2], vec![0, 5], vec![0, 6]]);
+        }
+
+        // Nor are states whose successors are beyond the depth bound.
+        let deadlocks = model.checker().max_depth(1).spawn_bfs().join().deadlocks();
+        assert_eq!(deadlocks.into_iter().map(|p| p.into_states()).collect::<Vec<_>>(), vec![vec![0, 5]]);
+    }
+
+    #[test]
+    fn can_check_deadlock_freedom() {
+        DGraph::with_property(Property::deadlock_free())
+            .with_path(vec![0, 1, 0])
+            .check().assert_properties();
+        assert_eq!(
+            DGraph::with_property(Property::deadlock_free())
+                .with_path(vec![0, 1, 2])
+                .check().discovery("deadlock free").unwrap().into_states(),
+            vec![0, 1, 2]);
+
+        // A state whose only successor is itself is a deadlock, as for `Checker::deadlocks`.
+        assert_eq!(
+            DGraph::with_property(Property::deadlock_free())
+                .with_path(vec![0, 1, 2, 2])
+                .check().discovery("deadlock free").unwrap().into_states(),
+            vec![0, 1, 2]);
+    }
+}
+
 #[cfg(test)]
 mod test_path {
     use super::*;
@@ -405,6 +1647,142 @@ mod test_path {
             path.last_state(),
             &Path::final_state(&model, fingerprints).unwrap());
     }
+
+    #[test]
+    fn can_serialize_path_as_tla() {
+        use crate::test_util::linear_equation_solver::Guess::*;
+        let model = LinearEquation { a: 2, b: 10, c: 14 };
+        let path = Path::from_actions(&model, (0, 0), &[IncreaseX, IncreaseY]).unwrap();
+        assert_eq!(
+            path.serialize_tla(|(x, y)| vec![("x", x.to_string()), ("y", y.to_string())]),
+            "<<\n\
+             [x |-> 0, y |-> 0],\n\
+             [x |-> 1, y |-> 0],\n\
+             [x |-> 1, y |-> 1]\n\
+             >>");
+    }
+
+    #[test]
+    fn can_serialize_path_as_json() {
+        use crate::test_util::linear_equation_solver::Guess::*;
+        let model = LinearEquation { a: 2, b: 10, c: 14 };
+        let path = Path::from_actions(&model, (0, 0), &[IncreaseX, IncreaseY]).unwrap();
+        assert_eq!(
+            path.serialize_json().unwrap(),
+            format!("{{\"steps\":[\
+                {{\"seq\":0,\"fingerprint\":\"{}\",\"state\":[0,0]}},\
+                {{\"seq\":1,\"fingerprint\":\"{}\",\"action\":\"IncreaseX\",\"state\":[1,0]}},\
+                {{\"seq\":2,\"fingerprint\":\"{}\",\"action\":\"IncreaseY\",\"state\":[1,1]}}\
+                ]}}",
+                fingerprint(&(0_u8, 0_u8)), fingerprint(&(1_u8, 0_u8)), fingerprint(&(1_u8, 1_u8))));
+    }
+}
+
+#[cfg(test)]
+mod test_minimize {
+    use crate::{Checker, Model, Path, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    fn not_four() -> Property<DGraph> {
+        Property::always("not four", |_, s| *s != 4)
+    }
+
+    #[test]
+    fn removes_unnecessary_actions() {
+        let model = DGraph::with_property(not_four())
+            .with_path(vec![0, 1, 2, 3, 4])
+            .with_path(vec![0, 2, 4]);
+        let path = Path::from_actions(&model, 0, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(
+            path.minimize(&model, |p| *p.last_state() == 4).into_states(),
+            vec![0, 2, 4]);
+
+        // Paths that cannot be shortened are unchanged.
+        let path = Path::from_actions(&model, 0, &[2, 4]).unwrap();
+        assert_eq!(
+            path.minimize(&model, |p| *p.last_state() == 4).into_states(),
+            vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn reorders_actions_to_enable_removal() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum Action { Inc, Double }
+        struct Calculator;
+        impl Model for Calculator {
+            type State = u8;
+            type Action = Action;
+            fn init_states(&self) -> Vec<u8> { vec![0] }
+            fn actions(&self, _: &u8, actions: &mut Vec<Action>) {
+                actions.push(Action::Inc);
+                actions.push(Action::Double);
+            }
+            fn next_state(&self, state: &u8, action: Action) -> Option<u8> {
+                match action {
+                    Action::Inc => Some(state + 1),
+                    Action::Double => Some(state * 2),
+                }
+            }
+        }
+
+        // No subsequence of fewer actions yields 5, but a reordered one does.
+        use Action::*;
+        let path = Path::from_actions(&Calculator, 0, &[Inc, Double, Inc, Inc, Inc]).unwrap();
+        assert_eq!(
+            path.minimize(&Calculator, |p| *p.last_state() == 5).into_actions(),
+            vec![Inc, Inc, Double, Inc]);
+    }
+
+    #[test]
+    fn can_minimize_discovery() {
+        let checker = DGraph::with_property(not_four())
+            .with_path(vec![0, 1, 2, 3, 4])
+            .with_path(vec![0, 2, 4])
+            .checker().spawn_dfs().join();
+        assert_eq!(
+            checker.minimized_discovery("not four").unwrap().into_states(),
+            vec![0, 2, 4]);
+
+        let checker = DGraph::with_property(Property::eventually("odd", |_, s| s % 2 == 1))
+            .with_path(vec![0, 2, 4, 6])
+            .with_path(vec![0, 4])
+            .checker().spawn_dfs().join();
+        assert_eq!(
+            checker.minimized_discovery("odd").unwrap().into_states(),
+            vec![0, 4, 6]);
+    }
+}
+
+#[cfg(test)]
+mod test_state_graph {
+    use crate::{Checker, Model};
+    use crate::test_util::binary_clock::BinaryClock;
+    use crate::test_util::linear_equation_solver::LinearEquation;
+
+    #[test]
+    fn can_render_dot() {
+        let checker = BinaryClock.checker().spawn_bfs().join();
+        assert_eq!(
+            checker.state_graph_dot(10, |s| format!("{}", s)),
+            "digraph {\n  \
+                 0 [label=\"0\"];\n  \
+                 1 [label=\"1\"];\n  \
+                 0 -> 1 [label=\"GoHigh\"];\n  \
+                 1 -> 0 [label=\"GoLow\"];\n\
+             }\n");
+    }
+
+    #[test]
+    fn limits_states_and_escapes_labels() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs().join();
+        assert_eq!(
+            checker.state_graph_dot(2, |s| format!("\"{}\"\n{}", s.0, s.1)),
+            "digraph {\n  \
+                 0 [label=\"\\\"0\\\"\\n0\"];\n  \
+                 1 [label=\"\\\"1\\\"\\n0\"];\n  \
+                 0 -> 1 [label=\"IncreaseX\"];\n\
+             }\n");
+    }
 }
 
 #[cfg(test)]
@@ -477,3 +1855,255 @@ mod test_report {
             "Output did not end as expected (see test). output={:?}`", output);
     }
 }
+
+#[cfg(test)]
+mod test_status {
+    use super::*;
+    use crate::test_util::linear_equation_solver::LinearEquation;
+
+    #[test]
+    fn summarizes_exhaustive_checking() {
+        // No solution, so every state is checked.
+        let unsolvable = || LinearEquation { a: 2, b: 4, c: 7 };
+        for checker in [unsolvable().checker().spawn_bfs().join().status(),
+                        unsolvable().checker().threads(4).spawn_bfs().join().status()].iter() {
+            assert_eq!(checker.generated_count, 256 * 256);
+            assert_eq!(checker.pending_count, 0);
+            assert!(checker.is_done);
+            assert!(checker.estimated_memory_bytes >= 256 * 256 * 8);
+        }
+
+        // Each step increments one variable.
+        let status = unsolvable().checker().spawn_bfs().join().status();
+        assert_eq!(status.max_depth, 255 + 255);
+        let status = unsolvable().checker().spawn_delay_bounded(1).join().status();
+        assert_eq!(status.pending_count, 0);
+        assert!(status.is_done);
+    }
+
+    #[test]
+    fn stops_clock_when_done() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_iddfs().join();
+        let status = checker.status();
+        assert_eq!(status.generated_count, 11);
+        assert_eq!(status.max_depth, 3);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        assert_eq!(checker.status().elapsed, status.elapsed);
+        assert!(format!("{}", status).starts_with("generated=11, pending="));
+    }
+
+    #[test]
+    fn tracks_depth_of_evaluated_states() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_dfs().join();
+        let status = checker.status();
+        assert_eq!(status.generated_count, 55);
+        assert_eq!(status.max_depth, checker.discovery("solvable").unwrap().into_actions().len());
+        assert!(status.pending_count > 0); // stopped early
+    }
+}
+
+#[cfg(test)]
+mod test_limits {
+    use super::*;
+    use crate::test_util::dgraph::DGraph;
+    use crate::test_util::linear_equation_solver::LinearEquation;
+
+    #[test]
+    fn bounds_depth() {
+        // Each step increments one variable, so states within 3 steps have `x + y <= 3`.
+        let unsolvable = || LinearEquation { a: 2, b: 4, c: 7 };
+        assert_eq!(unsolvable().checker().max_depth(3).spawn_bfs().join().generated_count(), 10);
+        assert_eq!(unsolvable().checker().max_depth(3).spawn_dfs().join().generated_count(), 10);
+        let checker = unsolvable().checker().max_depth(3).spawn_guided(|_, _| 0).join();
+        assert_eq!(checker.generated_count(), 10);
+        let checker = unsolvable().checker().max_depth(3).spawn_iddfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.status().max_depth, 3);
+
+        // A solution beyond the bound is not found.
+        let solvable = || LinearEquation { a: 2, b: 10, c: 14 };
+        solvable().checker().max_depth(2).spawn_bfs().join().assert_no_discovery("solvable");
+        solvable().checker().max_depth(3).spawn_bfs().join().assert_any_discovery("solvable");
+    }
+
+    #[test]
+    fn bounds_actions_per_state() {
+        // Only `IncreaseX`.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .max_actions_per_state(1)
+            .spawn_bfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.generated_count(), 256);
+    }
+
+    #[test]
+    fn flags_properties_that_only_hold_within_depth_bound() {
+        // The bound prunes 8, a terminal state that would otherwise be a counterexample.
+        let checker = DGraph::with_property(Property::eventually("odd", |_, s| s % 2 == 1))
+            .with_path(vec![0, 2, 8])
+            .checker().max_depth(1).spawn_bfs().join();
+        checker.assert_no_discovery("odd");
+        assert_eq!(checker.boundary_limited_properties(), vec!["odd"]);
+    }
+}
+
+#[cfg(test)]
+mod test_targets {
+    use super::*;
+
+    /// Counts up from zero.
+    struct Counter;
+    impl Model for Counter {
+        type State = u8;
+        type Action = ();
+        fn init_states(&self) -> Vec<u8> { vec![0] }
+        fn actions(&self, _: &u8, actions: &mut Vec<()>) { actions.push(()) }
+        fn next_state(&self, state: &u8, _: ()) -> Option<u8> { state.checked_add(1) }
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![
+                Property::always("small", |_, s| *s < 10),
+                Property::always("not five", |_, s| *s != 5),
+                Property::sometimes("large", |_, s| *s == 200),
+            ]
+        }
+    }
+
+    #[test]
+    fn can_stop_on_first_discovery() {
+        let checker = Counter.checker().stop_on_first_discovery().spawn_bfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discoveries().keys().collect::<Vec<_>>(), vec![&"not five"]);
+        let checker = Counter.checker().stop_on_first_discovery().spawn_dfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discoveries().keys().collect::<Vec<_>>(), vec![&"not five"]);
+        let checker = Counter.checker().stop_on_first_discovery().spawn_iddfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discoveries().keys().collect::<Vec<_>>(), vec![&"not five"]);
+        let checker = Counter.checker().stop_on_first_discovery().spawn_delay_bounded(0).join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discoveries().keys().collect::<Vec<_>>(), vec![&"not five"]);
+        let checker = Counter.checker().stop_on_first_discovery().spawn_guided(|_, _| 0).join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discoveries().keys().collect::<Vec<_>>(), vec![&"not five"]);
+
+        // Otherwise checking continues until every property is resolved.
+        assert_eq!(Counter.checker().spawn_bfs().join().discoveries().len(), 3);
+    }
+
+    #[test]
+    fn stops_at_memory_limit() {
+        use crate::test_util::linear_equation_solver::LinearEquation;
+        let unsolvable = || LinearEquation { a: 2, b: 4, c: 7 };
+        let checker = unsolvable().checker().max_memory(100_000).spawn_bfs().join();
+        assert!(checker.reached_memory_limit());
+        assert!(!checker.is_done());
+        assert!(checker.generated_count() < 256 * 256);
+        assert!(checker.status().estimated_memory_bytes > 100_000);
+        let checker = unsolvable().checker().threads(2).max_memory(100_000).spawn_dfs().join();
+        assert!(checker.reached_memory_limit());
+        assert!(!checker.is_done());
+        assert!(checker.generated_count() < 256 * 256);
+
+        let mut written: Vec<u8> = Vec::new();
+        unsolvable().checker().max_memory(100_000).spawn_bfs().report(&mut written);
+        let output = String::from_utf8(written).unwrap();
+        assert!(output.contains("Inconclusive: memory limit reached. generated="), "output={:?}", output);
+
+        // Unlimited otherwise.
+        let checker = unsolvable().checker().max_memory(10_000_000).spawn_bfs().join();
+        assert!(!checker.reached_memory_limit());
+        assert!(checker.is_done());
+
+        // Unless pending states own heap memory.
+        let checker = unsolvable().checker().state_heap_size(|_| 100_000)
+            .max_memory(10_000_000).spawn_bfs().join();
+        assert!(checker.reached_memory_limit());
+        let checker = unsolvable().checker().state_heap_size(|_| 100_000)
+            .max_memory(10_000_000).spawn_dfs().join();
+        assert!(checker.reached_memory_limit());
+    }
+
+    #[test]
+    fn stops_at_time_limit_or_when_cancelled() {
+        use crate::test_util::linear_equation_solver::LinearEquation;
+        use std::time::Duration;
+        let unsolvable = || LinearEquation { a: 2, b: 4, c: 7 };
+
+        let checker = unsolvable().checker().threads(2)
+            .timeout(Duration::from_millis(0))
+            .spawn_bfs().join();
+        assert_eq!(checker.stop_reason(), Some(StopReason::TimeLimit));
+        assert!(!checker.is_done());
+        assert!(checker.generated_count() < 256 * 256);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let checker = unsolvable().checker().threads(2)
+            .cancellation_token(token.clone())
+            .spawn_dfs().join();
+        assert_eq!(checker.stop_reason(), Some(StopReason::Cancelled));
+        assert!(!checker.is_done());
+        assert!(checker.generated_count() < 256 * 256);
+        let outcome = CheckOutcome::new("cancelled".to_string(), &checker, Duration::default());
+        assert!(!outcome.is_success());
+        assert!(outcome.to_string().starts_with("[cancelled] Inconclusive: cancelled. generated="),
+                "outcome={}", outcome);
+
+        // Unlimited otherwise.
+        let checker = unsolvable().checker()
+            .timeout(Duration::from_secs(600))
+            .cancellation_token(CancellationToken::new())
+            .spawn_bfs().join();
+        assert_eq!(checker.stop_reason(), None);
+        assert!(checker.is_done());
+    }
+
+    #[test]
+    fn can_check_only_target_properties() {
+        let checker = Counter.checker().target_properties(&["large"]).spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.discoveries().len(), 1);
+        assert_eq!(checker.checked_properties().len(), 1);
+        let checker = Counter.checker().target_properties(&["small", "large"]).spawn_dfs().join();
+        checker.assert_any_discovery("small");
+        checker.assert_any_discovery("large");
+        assert_eq!(checker.discovery("not five"), None);
+        let checker = Counter.checker().target_properties(&["large"]).spawn_iddfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.checked_properties().len(), 1);
+        let checker = Counter.checker().target_properties(&["large"]).spawn_delay_bounded(0).join();
+        checker.assert_properties();
+        assert_eq!(checker.checked_properties().len(), 1);
+        let checker = Counter.checker().target_properties(&["large"]).spawn_guided(|_, _| 0).join();
+        checker.assert_properties();
+        assert_eq!(checker.checked_properties().len(), 1);
+
+        // A target property can be the first discovery.
+        let checker = Counter.checker()
+            .target_properties(&["small", "large"])
+            .stop_on_first_discovery()
+            .spawn_bfs().join();
+        assert_eq!(checker.discovery("small").unwrap().last_state(), &10);
+        assert_eq!(checker.discoveries().len(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown property")]
+    fn rejects_unknown_target_properties() {
+        let _ = Counter.checker().target_properties(&["missing"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "CheckerBuilder::timeout is not supported by spawn_iddfs")]
+    fn rejects_unsupported_options() {
+        let _ = Counter.checker()
+            .timeout(std::time::Duration::from_secs(60))
+            .spawn_iddfs();
+    }
+
+    #[test]
+    #[should_panic(expected = "CheckerBuilder::threads is not supported by spawn_guided")]
+    fn rejects_threads_for_single_threaded_checkers() {
+        let _ = Counter.checker().threads(2).spawn_guided(|_, _| 0);
+    }
+}
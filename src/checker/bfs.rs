@@ -1,89 +1,185 @@
 //! Private module for selective re-export.
 
-use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
-use crate::checker::{Checker, EventuallyBits, Expectation, Path};
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property, StateStore};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, initial_ebits};
+use crate::checker::{ExplorationLimits, FrontierPriority, Path, record_boundary_pruned, with_violating_step};
+use crate::checker::checkpoint::Checkpoint;
+use crate::checker::{CheckerStats, Coverage, coverage::CoverageTracker};
+use crate::checker::collision::{CollisionDetector, FingerprintMode};
+use crate::checker::compress::{SerializeStored, StateCompressor, StoredState};
+use crate::checker::frontier::Frontier;
+use crate::checker::graph_sink::GraphExporter;
+use crate::checker::status::{CheckerStatus, Progress, StopReason};
+use crate::checker::store::Generated;
 use dashmap::DashMap;
-use dashmap::mapref::entry::Entry;
-use nohash_hasher::NoHashHasher;
 use parking_lot::{Condvar, Mutex};
+use serde::Serialize;
 use std::collections::{HashMap, VecDeque};
-use std::hash::{BuildHasherDefault, Hash};
+use std::hash::Hash;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 pub(crate) struct BfsChecker<M: Model> {
-    model: Arc<M>,
+    context: Arc<Context<M>>,
     thread_count: usize,
     handles: Vec<std::thread::JoinHandle<()>>,
     job_market: Arc<Mutex<JobMarket<M::State>>>,
-    generated: Arc<DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>>,
-    discoveries: Arc<DashMap<&'static str, Fingerprint>>,
+    has_new_job: Arc<Condvar>,
+    has_paused: Arc<Condvar>,
 }
-struct JobMarket<State> { wait_count: usize, jobs: Vec<Job<State>> }
-type Job<State> = VecDeque<(State, Fingerprint, EventuallyBits)>;
+/// State shared by the checker's threads for the duration of a run.
+struct Context<M: Model> {
+    model: M,
+    properties: Vec<Property<M>>,
+    target_discovery_count: usize,
+    generated: Generated<Option<Fingerprint>>,
+    discoveries: DashMap<&'static str, Fingerprint>,
+    boundary_pruned: Mutex<EventuallyBits>,
+    deadlocks: Mutex<Vec<Fingerprint>>,
+    progress: Progress,
+    visitor: Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+    frontier_priority: Option<FrontierPriority<M>>,
+    idle_count: AtomicUsize,
+    limits: ExplorationLimits,
+    coverage: Option<CoverageTracker<M>>,
+    graph_exporter: Option<GraphExporter<M>>,
+    collision_detector: Option<CollisionDetector<M::State>>,
+    state_compressor: Option<StateCompressor<M::State>>,
+    state_heap_size: Option<fn(&M::State) -> usize>,
+}
+struct JobMarket<State> { wait_count: usize, jobs: Vec<Job<State>>, is_paused: bool }
+type Job<State> = Frontier<(StoredState<State>, Fingerprint, usize, EventuallyBits)>;
 
 impl<M> BfsChecker<M>
 where M: Model + Send + Sync + 'static,
-      M::State: Hash + Send + 'static,
+      M::State: Hash + Send + Sync + 'static,
 {
     pub(crate) fn spawn(options: CheckerBuilder<M>) -> Self {
-        let model = Arc::new(options.model);
+        let properties = options.checked_properties();
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        // Paths are reconstructed from the store, which a Bloom filter cannot do.
+        assert!(!matches!(options.state_store, StateStore::Bloom { .. }),
+                "StateStore::Bloom is not supported by the BFS checker. Consider spawn_dfs().");
+        let model = options.model;
         let target_generated_count = options.target_generated_count;
+        let stop_conditions = options.stop_conditions;
         let thread_count = options.thread_count;
-        let visitor = Arc::new(options.visitor);
-        let property_count = model.properties().len();
-
-        let generated = Arc::new(DashMap::default());
-        for s in model.init_states() { generated.insert(fingerprint(&s), None); }
-        let ebits = {
-            let mut ebits = EventuallyBits::new();
-            for (i, p) in model.properties().iter().enumerate() {
-                if let Property { expectation: Expectation::Eventually, .. } = p {
-                    ebits.insert(i);
+        let frontier_priority = options.frontier_priority;
+        let state_compressor = options.state_compressor;
+
+        let generated = Generated::new(&options.state_store);
+        let discoveries = DashMap::default();
+        let boundary_pruned = Mutex::new(EventuallyBits::new());
+        let priority = |s: &M::State| frontier_priority.as_ref().map(|priority| priority(&model, s));
+        let mut pending = Frontier::breadth_first(frontier_priority.is_some());
+        match options.checkpoint {
+            Some(checkpoint) => {
+                // Continue where an earlier checker left off. Property names were validated when
+                // the checkpoint was read, and discoveries for untargeted properties are dropped.
+                for (fp, prev_fp) in checkpoint.generated { generated.insert(fp, prev_fp); }
+                for (name, fp) in checkpoint.discoveries {
+                    if let Some(property) = properties.iter().find(|p| p.name == name) {
+                        discoveries.insert(property.name, fp);
+                    }
+                }
+                boundary_pruned.lock().extend(checkpoint.boundary_pruned);
+                for (s, fp, depth, ebits) in checkpoint.pending {
+                    let priority = priority(&s);
+                    let s = StoredState::new(s, state_compressor.as_ref());
+                    pending.push((s, fp, depth, ebits.into_iter().collect()), priority);
                 }
             }
-            ebits
-        };
-        let pending: VecDeque<_> = model.init_states().into_iter()
-            .map(|s| {
-                let fp = fingerprint(&s);
-                (s, fp, ebits.clone())
-            })
-            .collect();
-        let discoveries = Arc::new(DashMap::default());
+            None => {
+                for s in model.init_states() { generated.insert(fingerprint(&s), None); }
+                let ebits = initial_ebits(&properties);
+                for s in model.init_states() {
+                    let fp = fingerprint(&s);
+                    let priority = priority(&s);
+                    let s = StoredState::new(s, state_compressor.as_ref());
+                    pending.push((s, fp, 0, ebits.clone()), priority);
+                }
+            }
+        }
+        let context = Arc::new(Context {
+            model,
+            properties,
+            target_discovery_count,
+            generated,
+            discoveries,
+            boundary_pruned,
+            deadlocks: Mutex::new(Vec::new()),
+            progress: Progress::new(pending.len()),
+            visitor: options.visitor,
+            frontier_priority,
+            idle_count: AtomicUsize::new(0),
+            limits: options.limits,
+            coverage: options.action_kind.map(CoverageTracker::new),
+            graph_exporter: options.graph_exporter,
+            collision_detector: options.collision_detector,
+            state_compressor,
+            state_heap_size: options.state_heap_size,
+        });
+        if let Some(detector) = &context.collision_detector {
+            for (s, fp, _, _) in pending.iter() {
+                if !s.with_state(context.state_compressor.as_ref(), |s| detector.record(*fp, s)) {
+                    context.progress.stop(StopReason::FingerprintCollision);
+                }
+            }
+        }
         let mut handles = Vec::new();
 
         let has_new_job = Arc::new(Condvar::new());
+        let has_paused = Arc::new(Condvar::new());
         let job_market = Arc::new(Mutex::new(JobMarket {
             wait_count: thread_count,
             jobs: vec![pending],
+            is_paused: false,
         }));
         for t in 0..thread_count {
-            let model = Arc::clone(&model);
-            let visitor = Arc::clone(&visitor);
+            let context = Arc::clone(&context);
             let has_new_job = Arc::clone(&has_new_job);
+            let has_paused = Arc::clone(&has_paused);
             let job_market = Arc::clone(&job_market);
-            let generated = Arc::clone(&generated);
-            let discoveries = Arc::clone(&discoveries);
+            let stop_conditions = stop_conditions.clone();
             handles.push(std::thread::spawn(move || {
                 log::debug!("{}: Thread started.", t);
-                let mut pending = VecDeque::new();
+                let Context { generated, discoveries, progress, idle_count, .. } = &*context;
+                let mut pending = Frontier::breadth_first(context.frontier_priority.is_some());
                 loop {
                     // Step 1: Do work.
                     if pending.is_empty() {
                         pending = {
                             let mut job_market = job_market.lock();
+                            if job_market.is_paused {
+                                has_new_job.wait(&mut job_market);
+                                continue
+                            }
+                            // Done if another thread reached the target, in which case remaining
+                            // work is retained for a checkpoint.
+                            let is_target_reached = target_generated_count
+                                .is_some_and(|target| target.get() <= generated.len());
+                            if is_target_reached {
+                                log::debug!("{}: Target generated count reached. Shutting down... gen={}", t, generated.len());
+                                has_new_job.notify_all();
+                                progress.record_finish();
+                                return
+                            }
                             match job_market.jobs.pop() {
                                 None => {
-                                    // Done if all are waiting.
-                                    if job_market.wait_count == thread_count {
+                                    // Done if all are waiting or checking stopped early.
+                                    if job_market.wait_count == thread_count || progress.stop_reason().is_some() {
                                         log::debug!("{}: No more work. Shutting down... gen={}", t, generated.len());
                                         has_new_job.notify_all();
+                                        progress.record_finish();
                                         return
                                     }
 
                                     // Otherwise more work may become available.
                                     log::trace!("{}: No jobs. Awaiting. blocked={}", t, job_market.wait_count);
+                                    idle_count.fetch_add(1, Ordering::Relaxed);
                                     has_new_job.wait(&mut job_market);
+                                    idle_count.fetch_sub(1, Ordering::Relaxed);
                                     continue
                                 }
                                 Some(job) => {
@@ -94,118 +190,153 @@ where M: Model + Send + Sync + 'static,
                             }
                         };
                     }
-                    Self::check_block(&*model, &*generated, &mut pending, &*discoveries, &*visitor, 1500);
-                    if discoveries.len() == property_count {
+                    let pending_count = pending.len();
+                    context.check_block(&mut pending, 1500);
+                    progress.update_pending(pending_count, pending.len());
+                    if discoveries.len() >= context.target_discovery_count {
                         log::debug!("{}: Discovery complete. Shutting down... gen={}", t, generated.len());
                         let mut job_market = job_market.lock();
                         job_market.wait_count += 1;
                         drop(job_market);
                         has_new_job.notify_all();
+                        has_paused.notify_all();
+                        progress.record_finish();
                         return
                     }
                     if let Some(target_generated_count) = target_generated_count {
                         if target_generated_count.get() <= generated.len() {
                             log::debug!("{}: Reached target generated count. Shutting down... gen={}", t, generated.len());
+                            // Remaining work is retained for a checkpoint.
+                            let mut job_market = job_market.lock();
+                            if !pending.is_empty() { job_market.jobs.push(pending); }
+                            job_market.wait_count += 1;
+                            drop(job_market);
+                            has_new_job.notify_all();
+                            has_paused.notify_all();
+                            progress.record_finish();
                             return;
                         }
                     }
-
-                    // Step 2: Share work.
-                    if pending.len() > 1 && thread_count > 1 {
+                    if generated.has_failed() { progress.stop(StopReason::Io); }
+                    if progress.should_stop::<M::State>(&stop_conditions, || context.generated_bytes()) {
+                        log::debug!("{}: Stopping early. reason={:?}, gen={}", t, progress.stop_reason(), generated.len());
+                        // Remaining work is retained for a checkpoint.
                         let mut job_market = job_market.lock();
+                        if !pending.is_empty() { job_market.jobs.push(pending); }
+                        job_market.wait_count += 1;
+                        drop(job_market);
+                        has_new_job.notify_all();
+                        has_paused.notify_all();
+                        progress.record_finish();
+                        return;
+                    }
+
+                    // Step 2: Share work, or set it aside while a checkpoint is written.
+                    let mut job_market = job_market.lock();
+                    if job_market.is_paused {
+                        log::trace!("{}: Pausing. blocked={}", t, job_market.wait_count);
+                        if !pending.is_empty() {
+                            job_market.jobs.push(pending.split_off(pending.len()));
+                        }
+                        job_market.wait_count += 1;
+                        has_paused.notify_all();
+                    } else if pending.len() > 1 && thread_count > 1 {
                         let pieces = 1 + std::cmp::min(job_market.wait_count as usize, pending.len());
                         let size = pending.len() / pieces;
                         for _ in 1..pieces {
                             log::trace!("{}: Sharing work. blocked={}, size={}", t, job_market.wait_count, size);
-                            job_market.jobs.push(pending.split_off(pending.len() - size));
+                            job_market.jobs.push(pending.split_off(size));
                             has_new_job.notify_one();
                         }
                     } else if pending.is_empty() {
-                        let mut job_market = job_market.lock();
                         job_market.wait_count += 1;
                     }
                 }
             }));
         }
         BfsChecker {
-            model,
+            context,
             thread_count,
             handles,
             job_market,
-            generated,
-            discoveries,
+            has_new_job,
+            has_paused,
         }
     }
+}
 
-    fn check_block(
-        model: &M,
-        generated: &DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
-        pending: &mut Job<M::State>,
-        discoveries: &DashMap<&'static str, Fingerprint>,
-        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
-        mut max_count: usize)
-    {
-        let properties = model.properties();
-
+impl<M> Context<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn check_block(&self, pending: &mut Job<M::State>, mut max_count: usize) {
+        let Context {
+            model, properties, target_discovery_count, generated, discoveries, boundary_pruned,
+            deadlocks, progress, visitor, frontier_priority, idle_count, limits, ..
+        } = self;
+        let coverage = self.coverage.as_ref();
+        let graph_exporter = self.graph_exporter.as_ref();
+        let collision_detector = self.collision_detector.as_ref();
+        let state_compressor = self.state_compressor.as_ref();
         let mut actions = Vec::new();
         loop {
             // Done if reached max count.
             if max_count == 0 { return }
             max_count -= 1;
 
+            // Done early if other threads are idle, so that pending states can be shared with
+            // them rather than leaving cores unused until the block is complete.
+            if max_count.is_multiple_of(64) && pending.len() > 1 && idle_count.load(Ordering::Relaxed) > 0 {
+                return
+            }
+
             // Done if none pending.
-            let (state, state_fp, mut ebits) = match pending.pop_back() {
+            let (state, state_fp, depth, mut ebits) = match pending.pop() {
                 None => return,
                 Some(pair) => pair,
             };
+            let state = state.into_state(state_compressor);
+            progress.record_depth(depth);
+            if let Some(state_heap_size) = self.state_heap_size {
+                progress.record_heap_size(state_heap_size(&state));
+            }
             if let Some(visitor) = visitor {
                 visitor.visit(model, reconstruct_path(model, generated, state_fp));
             }
-
-            // Done if discoveries found for all properties.
-            let mut is_awaiting_discoveries = false;
-            for (i, property) in properties.iter().enumerate() {
-                if discoveries.contains_key(property.name) { continue }
-                match property {
-                    Property { expectation: Expectation::Always, condition: always, .. } => {
-                        if !always(model, &state) {
-                            // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
-                        } else {
-                            is_awaiting_discoveries = true;
-                        }
-                    },
-                    Property { expectation: Expectation::Sometimes, condition: sometimes, .. } => {
-                        if sometimes(model, &state) {
-                            // Races other threads, but that's fine.
-                            discoveries.insert(property.name, state_fp);
-                        } else {
-                            is_awaiting_discoveries = true;
-                        }
-                    },
-                    Property { expectation: Expectation::Eventually, condition: eventually, .. } => {
-                        // The checker early exits after finding discoveries for every property,
-                        // and "eventually" property discoveries are only identifid at terminal
-                        // states, so if we are here it means we are still awaiting a corresponding
-                        // discovery regardless of whether the eventually property is now satisfied
-                        // (i.e. it might be falsifiable via a different path).
-                        is_awaiting_discoveries = true;
-                        if eventually(model, &state) {
-                            ebits.remove(i);
-                        }
-                    }
-                }
-
+            if let Some(exporter) = graph_exporter {
+                exporter.sink.on_state(model, state_fp.get(), &state);
             }
-            if !is_awaiting_discoveries { return }
+
+            // Skip successors if discoveries found for all properties or the remaining properties
+            // are monotone and hold.
+            let is_awaiting_discoveries = check_properties(
+                model, properties, &state, &mut ebits,
+                |p| discoveries.contains_key(p.name),
+                // Races other threads, but that's fine.
+                |name| { discoveries.insert(name, state_fp); });
+            if discoveries.len() >= *target_discovery_count { return }
+            if !is_awaiting_discoveries { continue }
 
             // Otherwise enqueue newly generated states (with related metadata).
-            let mut is_terminal = true;
-            model.actions(&state, &mut actions);
-            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
-            for next_state in next_states {
-                // Skip if outside boundary.
-                if !model.within_boundary(&next_state) { continue }
+            let is_depth_bounded = !limits.actions(
+                model, &state, depth, &mut actions, boundary_pruned, &ebits);
+            let mut is_terminal = !is_depth_bounded;
+            let next_states = actions.drain(..).flat_map(|a| {
+                let exported_action = graph_exporter.map(|exporter| (exporter.clone_action)(&a));
+                CoverageTracker::next_state(coverage, model, &state, a)
+                    .map(|next_state| (exported_action, next_state))
+            });
+            let mut successor_count = 0;
+            let mut changes_state = false;
+            for (exported_action, next_state) in next_states {
+                successor_count += 1;
+
+                // Skip if outside boundary, noting unmet `eventually` properties.
+                if !model.within_boundary(&next_state) {
+                    record_boundary_pruned(boundary_pruned, &ebits);
+                    changes_state = true;
+                    continue
+                }
 
                 // Skip if already generated.
                 //
@@ -216,9 +347,18 @@ where M: Model + Send + Sync + 'static,
                 // that it holds in the path leading to the second visit -- another
                 // possible false-negative.
                 let next_fingerprint = fingerprint(&next_state);
-                if let Entry::Vacant(next_entry) = generated.entry(next_fingerprint) {
-                    next_entry.insert(Some(state_fp));
-                } else {
+                if next_fingerprint != state_fp { changes_state = true; }
+                if let (Some(exporter), Some(action)) = (graph_exporter, &exported_action) {
+                    exporter.sink.on_edge(model, state_fp.get(), action, next_fingerprint.get());
+                }
+                if let Some(detector) = collision_detector {
+                    if !detector.record(next_fingerprint, &next_state) {
+                        log::debug!("Fingerprint collision. fp={}", next_fingerprint);
+                        progress.stop(StopReason::FingerprintCollision);
+                        return
+                    }
+                }
+                if !generated.insert(next_fingerprint, Some(state_fp)) {
                     // FIXME: arriving at an already-known state may be a loop (in which case it
                     // could, in a fancier implementation, be considered a terminal state for
                     // purposes of eventually-property checking) but it might also be a join in
@@ -233,35 +373,76 @@ where M: Model + Send + Sync + 'static,
 
                 // Otherwise further checking is applicable.
                 is_terminal = false;
-                pending.push_front((next_state, next_fingerprint, ebits.clone()));
+                let priority = frontier_priority.as_ref()
+                    .map(|frontier_priority| frontier_priority(model, &next_state));
+                let next_state = StoredState::new(next_state, state_compressor);
+                pending.push((next_state, next_fingerprint, depth + 1, ebits.clone()), priority);
+            }
+            if !is_depth_bounded { progress.record_successors(successor_count); }
+            if !changes_state && !is_depth_bounded && !model.is_terminal(&state) {
+                deadlocks.lock().push(state_fp);
             }
             if is_terminal {
-                for (i, property) in properties.iter().enumerate() {
-                    if ebits.contains(i) {
-                        // Races other threads, but that's fine.
-                        discoveries.insert(property.name, state_fp);
-                    }
-                }
+                check_terminal_state(
+                    properties, &ebits,
+                    |p| discoveries.contains_key(p.name),
+                    // Races other threads, but that's fine.
+                    |name| { discoveries.insert(name, state_fp); });
             }
         }
     }
+
+    /// Approximates the bytes of memory used to record generated states.
+    fn generated_bytes(&self) -> usize {
+        self.generated.memory_bytes()
+            + self.collision_detector.as_ref().map_or(0, CollisionDetector::memory_bytes)
+    }
+}
+
+impl<M> BfsChecker<M>
+where M: Model,
+      M::State: Hash + Serialize,
+{
+    /// Writes a checkpoint while every thread is paused.
+    fn write_checkpoint(&self, job_market: &JobMarket<M::State>, path: &std::path::Path)
+        -> std::io::Result<()>
+    {
+        let generated = self.context.generated.to_vec().ok_or_else(|| std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "Checkpointing requires StateStore::InMemory."))?;
+        let checkpoint = Checkpoint {
+            generated,
+            pending: job_market.jobs.iter().flat_map(Frontier::iter)
+                .map(|(s, fp, depth, ebits)| {
+                    let s = SerializeStored(s, self.context.state_compressor.as_ref());
+                    (s, *fp, *depth, ebits.iter().collect())
+                })
+                .collect(),
+            discoveries: self.context.discoveries.iter()
+                .map(|mapref| (mapref.key().to_string(), *mapref.value()))
+                .collect(),
+            boundary_pruned: self.context.boundary_pruned.lock().iter().collect(),
+        };
+        checkpoint.write(path)
+    }
 }
 
 impl<M> Checker<M> for BfsChecker<M>
 where M: Model,
       M::State: Hash,
 {
-    fn model(&self) -> &M { &self.model }
+    fn model(&self) -> &M { &self.context.model }
+
+    fn checked_properties(&self) -> Vec<Property<M>> { self.context.properties.to_vec() }
 
-    fn generated_count(&self) -> usize { self.generated.len() }
+    fn generated_count(&self) -> usize { self.context.generated.len() }
 
     fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
-        self.discoveries.iter()
+        self.context.discoveries.iter()
             .map(|mapref| {
-                (
-                    <&'static str>::clone(mapref.key()),
-                    reconstruct_path(self.model(), &*self.generated, *mapref.value()),
-                )
+                let name = <&'static str>::clone(mapref.key());
+                let path = reconstruct_path(self.model(), &self.context.generated, *mapref.value());
+                (name, with_violating_step(self.model(), name, path))
             })
             .collect()
     }
@@ -276,13 +457,64 @@ where M: Model,
     fn is_done(&self) -> bool {
         let job_market = self.job_market.lock();
         job_market.jobs.is_empty() && job_market.wait_count == self.thread_count
-            || self.discoveries.len() == self.model.properties().len()
+            || self.context.discoveries.len() >= self.context.target_discovery_count
+    }
+
+    fn checkpoint(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()>
+    where M::State: Serialize,
+    {
+        // Wait for every thread to set aside its pending states, so that the generated states
+        // and pending states are consistent.
+        let mut job_market = self.job_market.lock();
+        job_market.is_paused = true;
+        while job_market.wait_count < self.thread_count {
+            self.has_paused.wait(&mut job_market);
+        }
+        let result = self.write_checkpoint(&job_market, path.as_ref());
+        job_market.is_paused = false;
+        drop(job_market);
+        self.has_new_job.notify_all();
+        result
+    }
+    fn status(&self) -> CheckerStatus {
+        self.context.progress.status::<M::State>(
+            self.generated_count(),
+            self.context.generated_bytes(),
+            self.is_done())
+    }
+
+    fn fingerprint_collision_probability(&self) -> f64 {
+        let mode = self.context.collision_detector.as_ref()
+            .map_or(FingerprintMode::Bits64, |detector| detector.mode());
+        mode.collision_probability(self.generated_count())
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        self.context.progress.stop_reason()
+    }
+
+    fn coverage(&self) -> Coverage {
+        self.context.coverage.as_ref().map(|coverage| coverage.coverage()).unwrap_or_default()
+    }
+
+    fn stats(&self) -> CheckerStats {
+        self.context.progress.stats(self.coverage())
+    }
+
+    fn deadlocks(&self) -> Vec<Path<M::State, M::Action>> {
+        self.context.deadlocks.lock().iter()
+            .map(|&fp| reconstruct_path(self.model(), &self.context.generated, fp))
+            .collect()
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(&self.context.properties, &self.context.boundary_pruned, &self.context.discoveries)
     }
 }
 
 fn reconstruct_path<M>(
     model: &M,
-    generated: &DashMap<Fingerprint, Option<Fingerprint>, BuildHasherDefault<NoHashHasher<u64>>>,
+    generated: &Generated<Option<Fingerprint>>,
     fp: Fingerprint)
     -> Path<M::State, M::Action>
     where M: Model,
@@ -295,8 +527,8 @@ fn reconstruct_path<M>(
 
     let mut fingerprints = VecDeque::new();
     let mut next_fp = fp;
-    while let Some(source) = generated.get(&next_fp) {
-        match *source {
+    while let Some(source) = generated.get(next_fp) {
+        match source {
             Some(prev_fingerprint) => {
                 fingerprints.push_front(next_fp);
                 next_fp = prev_fingerprint;
@@ -332,6 +564,54 @@ mod test {
             ]);
     }
 
+    #[test]
+    fn visits_states_in_priority_order() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .frontier_priority(|_, s| s.1 as i64)
+            .visitor(recorder)
+            .spawn_bfs().join();
+        assert_eq!(accessor()[..4], [(0, 0), (0, 1), (0, 2), (0, 3)]);
+        // (10*27) % 256 == 14
+        assert_eq!(checker.discovery("solvable").unwrap().into_actions(), vec![Guess::IncreaseY; 27]);
+
+        // Still exhaustive.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .frontier_priority(|_, s| s.1 as i64)
+            .spawn_bfs().join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+    }
+
+    #[test]
+    fn can_sample_visited_states() {
+        let (sampler, accessor) = StateSampler::new_with_accessor(Sampling::StratifiedByDepth(2));
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .visitor(sampler)
+            .spawn_bfs().join();
+        let samples = accessor();
+        assert_eq!(samples[0], StateSample {
+            depth: 0,
+            fingerprint: format!("{}", fingerprint(&(0_u8, 0_u8))),
+            action: None,
+            state: serde_json::json!([0, 0]),
+        });
+        assert_eq!(
+            samples.iter().map(|s| s.depth).collect::<Vec<_>>(),
+            vec![0, 1, 1, 2, 2, 3, 3]);
+        for sample in samples.iter().skip(1) {
+            let (x, y): (usize, usize) = serde_json::from_value(sample.state.clone()).unwrap();
+            assert_eq!(x + y, sample.depth);
+            assert!(sample.action.is_some());
+        }
+
+        let (sampler, accessor) = StateSampler::new_with_accessor(Sampling::Uniform(3));
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .visitor(sampler)
+            .spawn_bfs().join();
+        assert_eq!(accessor().len(), 3);
+    }
+
     #[test]
     fn can_complete_by_enumerating_all_states() {
         let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker().spawn_bfs().join();
@@ -340,6 +620,20 @@ mod test {
         assert_eq!(checker.generated_count(), 256 * 256);
     }
 
+    #[test]
+    fn can_complete_with_on_disk_state_store() {
+        let path = crate::checker::store::temp_dir("bfs-test");
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .state_store(StateStore::OnDisk { path: path.clone(), cache_bytes: 64 * 1024 })
+            .threads(2)
+            .spawn_bfs().join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+        drop(checker);
+        assert!(!path.exists());
+    }
+
     #[test]
     fn can_complete_by_eliminating_properties() {
         let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs().join();
@@ -361,4 +655,138 @@ mod test {
             // (2*0 + 10*27) % 256 == 14
             vec![Guess::IncreaseY; 27]);
     }
+
+    #[test]
+    fn can_reconstruct_paths_from_on_disk_state_store() {
+        let path = crate::checker::store::temp_dir("bfs-path-test");
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .state_store(StateStore::OnDisk { path: path.clone(), cache_bytes: 0 })
+            .spawn_bfs().join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![
+                Guess::IncreaseX,
+                Guess::IncreaseX,
+                Guess::IncreaseY,
+            ]);
+        drop(checker);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn stops_on_state_store_io_error() {
+        // A directory cannot be created within a file.
+        let file = crate::checker::store::temp_dir("bfs-io-test");
+        std::fs::write(&file, b"").unwrap();
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .state_store(StateStore::OnDisk { path: file.join("store"), cache_bytes: 0 })
+            .spawn_bfs().join();
+        assert!(!checker.is_done());
+        assert_eq!(checker.stop_reason(), Some(StopReason::Io));
+        std::fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn stops_every_thread_at_target_generated_count() {
+        let path = crate::checker::store::temp_dir("bfs-target-test");
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(4)
+            .target_generated_count(1_000)
+            .spawn_bfs().join();
+        assert!(!checker.is_done());
+        assert!(checker.generated_count() >= 1_000);
+        checker.checkpoint(&path).unwrap();
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(4)
+            .resume_from(&path).unwrap()
+            .spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 256 * 256);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn can_resume_from_checkpoint() {
+        let path = crate::checker::store::temp_dir("bfs-checkpoint-test");
+
+        // Stopped early.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .target_generated_count(10_000)
+            .spawn_bfs().join();
+        assert!(!checker.is_done());
+        checker.checkpoint(&path).unwrap();
+        let generated_count = checker.generated_count();
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .resume_from(&path).unwrap()
+            .spawn_bfs();
+        assert!(checker.generated_count() >= generated_count);
+        let checker = checker.join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+
+        // Checkpointed while running.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(4)
+            .spawn_bfs();
+        checker.checkpoint(&path).unwrap();
+        checker.join();
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(4)
+            .resume_from(&path).unwrap()
+            .spawn_bfs().join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+
+        // Discoveries are retained.
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .spawn_bfs().join()
+            .checkpoint(&path).unwrap();
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .resume_from(&path).unwrap()
+            .spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 12);
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![
+                Guess::IncreaseX,
+                Guess::IncreaseX,
+                Guess::IncreaseY,
+            ]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rejects_checkpoint_for_different_model() {
+        let path = crate::checker::store::temp_dir("bfs-checkpoint-mismatch-test");
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .spawn_bfs().join()
+            .checkpoint(&path).unwrap();
+        let err = crate::test_util::dgraph::DGraph::with_property(
+                Property::always("true", |_, _| true))
+            .with_path(vec![0, 1])
+            .checker()
+            .resume_from(&path)
+            .err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn can_share_work_across_threads() {
+        // A single initial state, so every thread but one starts idle.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(8)
+            .spawn_bfs().join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+
+        // One thread per core.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .threads(0)
+            .spawn_bfs().join();
+        assert!(checker.is_done());
+        assert_eq!(checker.generated_count(), 256 * 256);
+    }
 }
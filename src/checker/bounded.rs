@@ -0,0 +1,386 @@
+//! Private module for selective re-export.
+
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, initial_ebits, Path};
+use crate::checker::{ExplorationLimits, record_boundary_pruned, with_violating_step};
+use crate::checker::status::{CheckerStatus, Progress};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Explores states in order of the cost needed to reach them (akin to Dijkstra's algorithm with
+// cost as the distance), so schedules close to the expected one are checked before pathological
+// ones, and states costing more than the bound are not explored at all. The cost depends on the
+// `Bound`:
+//
+// - Delay bounding treats the order of actions returned by `Model::actions` as a deterministic
+//   scheduler's preference: taking the first action is free, while taking the `i`th action
+//   "delays" the `i` preceding actions.
+// - Context bounding (a la CHESS) counts preemptions: switching from the context (per
+//   `Model::action_context`) of the previous action to a different context while the previous
+//   context could still act. As the cost of a step depends on the previous context, states are
+//   revisited if reached with a different context.
+
+pub(crate) struct BoundedChecker<M: Model> {
+    model: Arc<M>,
+    properties: Arc<Vec<Property<M>>>,
+    target_discovery_count: usize,
+    bound: Bound,
+    handles: Vec<std::thread::JoinHandle<()>>,
+    is_exhausted: Arc<AtomicBool>,
+    generated: Arc<DashMap<Fingerprint, (Option<Fingerprint>, usize)>>,
+    discoveries: Arc<DashMap<&'static str, Fingerprint>>,
+    boundary_pruned: Arc<Mutex<EventuallyBits>>,
+    progress: Arc<Progress>,
+}
+type Job<State> = VecDeque<(State, Fingerprint, usize, Option<usize>, EventuallyBits)>;
+
+/// Limits the cost of paths explored by a [`BoundedChecker`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Bound {
+    Delays(usize),
+    Preemptions(usize),
+}
+
+impl Bound {
+    fn max_cost(self) -> usize {
+        match self {
+            Bound::Delays(max) | Bound::Preemptions(max) => max,
+        }
+    }
+}
+
+impl<M> BoundedChecker<M>
+where M: Model + Send + Sync + 'static,
+      M::State: Hash + Send + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>, bound: Bound) -> Self {
+        let properties = Arc::new(options.checked_properties());
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        let model = Arc::new(options.model);
+        let target_generated_count = options.target_generated_count;
+        let visitor = options.visitor;
+        let limits = options.limits;
+
+        let is_exhausted = Arc::new(AtomicBool::new(false));
+        let generated = Arc::new(DashMap::default());
+        let discoveries = Arc::new(DashMap::default());
+        let boundary_pruned = Arc::new(Mutex::new(EventuallyBits::new()));
+        let progress = Arc::new(Progress::new(0));
+
+        let handle = {
+            let model = Arc::clone(&model);
+            let properties = Arc::clone(&properties);
+            let is_exhausted = Arc::clone(&is_exhausted);
+            let generated = Arc::clone(&generated);
+            let discoveries = Arc::clone(&discoveries);
+            let boundary_pruned = Arc::clone(&boundary_pruned);
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || {
+                let is_complete = Self::check(
+                    &model, &properties, target_discovery_count, &generated, &discoveries,
+                    &boundary_pruned, &progress, &visitor, target_generated_count, bound, limits);
+                log::debug!("Shutting down... gen={}", generated.len());
+                is_exhausted.store(is_complete, Ordering::Release);
+                progress.record_finish();
+            })
+        };
+        BoundedChecker {
+            model,
+            properties,
+            target_discovery_count,
+            bound,
+            handles: vec![handle],
+            is_exhausted,
+            generated,
+            discoveries,
+            boundary_pruned,
+            progress,
+        }
+    }
+
+    /// Checks every state reachable within the `bound`, returning whether all were checked
+    /// (versus stopping early).
+    #[allow(clippy::too_many_arguments)]
+    fn check(
+        model: &M,
+        properties: &[Property<M>],
+        target_discovery_count: usize,
+        generated: &DashMap<Fingerprint, (Option<Fingerprint>, usize)>,
+        discoveries: &DashMap<&'static str, Fingerprint>,
+        boundary_pruned: &Mutex<EventuallyBits>,
+        progress: &Progress,
+        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+        target_generated_count: Option<NonZeroUsize>,
+        bound: Bound,
+        limits: ExplorationLimits)
+        -> bool
+    {
+        let ebits = initial_ebits(properties);
+
+        // One queue per cost. The minimum cost of each (state, context) pair is tracked
+        // separately from the minimum cost of each state, which determines the path to it.
+        let max_cost = bound.max_cost();
+        let mut pending: Vec<Job<M::State>> = (0..=max_cost).map(|_| VecDeque::new()).collect();
+        let mut costs = HashMap::new();
+        let mut evaluated = HashSet::new();
+        let mut pending_count = 0;
+        for s in model.init_states() {
+            let fp = fingerprint(&s);
+            if generated.insert(fp, (None, 0)).is_none() {
+                costs.insert((fp, None), 0);
+                pending[0].push_back((s, fp, 0, None, ebits.clone()));
+                pending_count += 1;
+            }
+        }
+
+        let mut actions = Vec::new();
+        let mut contexts = Vec::new();
+        for cost in 0..=max_cost {
+            log::debug!("Checking with cost {}. gen={}", cost, generated.len());
+            while let Some((state, state_fp, depth, context, mut ebits)) = pending[cost].pop_front() {
+                pending_count -= 1;
+                progress.set_pending(pending_count);
+
+                // Skip if reached with a lower cost since enqueued.
+                if costs.get(&(state_fp, context)) != Some(&cost) { continue }
+
+                // A state reached via different contexts is only visited once, although
+                // properties are reevaluated as eventually properties are tracked per path.
+                if evaluated.insert(state_fp) {
+                    progress.record_depth(depth);
+                    if let Some(visitor) = visitor {
+                        visitor.visit(model, reconstruct_path(model, generated, state_fp));
+                    }
+                }
+                let is_awaiting_discoveries = check_properties(
+                    model, properties, &state, &mut ebits,
+                    |p| discoveries.contains_key(p.name),
+                    |name| { discoveries.insert(name, state_fp); });
+                if discoveries.len() >= target_discovery_count { return false }
+                if !is_awaiting_discoveries { continue }
+
+                // Successors that were already reached with as low a cost are skipped.
+                let mut is_terminal = limits.actions(
+                    model, &state, depth, &mut actions, boundary_pruned, &ebits);
+                contexts.clear();
+                contexts.extend(actions.iter().map(|a| model.action_context(a)));
+                let can_continue = context.is_some() && contexts.contains(&context);
+                for (i, action) in actions.drain(..).enumerate() {
+                    let (step_cost, next_context) = match (bound, contexts[i]) {
+                        (Bound::Delays(_), _) => (i, None),
+                        (Bound::Preemptions(_), None) => (0, context),
+                        (Bound::Preemptions(_), next_context) => {
+                            let is_preemption = can_continue && next_context != context;
+                            (is_preemption as usize, next_context)
+                        }
+                    };
+                    let next_state = match model.next_state(&state, action) {
+                        None => continue,
+                        Some(next_state) => next_state,
+                    };
+                    if !model.within_boundary(&next_state) {
+                        record_boundary_pruned(boundary_pruned, &ebits);
+                        continue
+                    }
+                    is_terminal = false;
+                    let next_cost = cost + step_cost;
+                    if next_cost > max_cost { continue }
+                    let next_fingerprint = fingerprint(&next_state);
+                    let is_improved = match costs.get(&(next_fingerprint, next_context)) {
+                        None => true,
+                        Some(&prev_cost) => next_cost < prev_cost,
+                    };
+                    if !is_improved { continue }
+                    costs.insert((next_fingerprint, next_context), next_cost);
+                    let is_path_improved = match generated.get(&next_fingerprint) {
+                        None => true,
+                        Some(entry) => next_cost < entry.1,
+                    };
+                    if is_path_improved {
+                        generated.insert(next_fingerprint, (Some(state_fp), next_cost));
+                    }
+                    pending[next_cost].push_back(
+                        (next_state, next_fingerprint, depth + 1, next_context, ebits.clone()));
+                    pending_count += 1;
+                }
+                if is_terminal {
+                    check_terminal_state(
+                        properties, &ebits,
+                        |p| discoveries.contains_key(p.name),
+                        |name| { discoveries.insert(name, state_fp); });
+                }
+
+                if let Some(target_generated_count) = target_generated_count {
+                    if target_generated_count.get() <= generated.len() { return false }
+                }
+            }
+        }
+        true
+    }
+}
+
+impl<M> Checker<M> for BoundedChecker<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn model(&self) -> &M { &self.model }
+
+    fn checked_properties(&self) -> Vec<Property<M>> { self.properties.to_vec() }
+
+    fn generated_count(&self) -> usize { self.generated.len() }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.discoveries.iter()
+            .map(|mapref| {
+                let name = <&'static str>::clone(mapref.key());
+                let path = reconstruct_path(self.model(), &self.generated, *mapref.value());
+                (name, with_violating_step(self.model(), name, path))
+            })
+            .collect()
+    }
+
+    fn join(mut self) -> Self {
+        for h in self.handles.drain(0..) {
+            h.join().unwrap();
+        }
+        self
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_exhausted.load(Ordering::Acquire)
+            || self.discoveries.len() >= self.target_discovery_count
+    }
+
+    fn status(&self) -> CheckerStatus {
+        let generated_bytes = self.generated.len()
+            * std::mem::size_of::<(Fingerprint, (Option<Fingerprint>, usize))>();
+        self.progress.status::<M::State>(self.generated_count(), generated_bytes, self.is_done())
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(&self.properties, &self.boundary_pruned, &self.discoveries)
+    }
+
+    fn exploration_bound(&self) -> Option<String> {
+        Some(match self.bound {
+            Bound::Delays(max) => format!("max_delays={}", max),
+            Bound::Preemptions(max) => format!("max_preemptions={}", max),
+        })
+    }
+}
+
+fn reconstruct_path<M>(
+    model: &M,
+    generated: &DashMap<Fingerprint, (Option<Fingerprint>, usize)>,
+    fp: Fingerprint)
+    -> Path<M::State, M::Action>
+    where M: Model,
+          M::State: Hash,
+{
+    let mut fingerprints = VecDeque::new();
+    let mut next_fp = Some(fp);
+    while let Some(fp) = next_fp {
+        fingerprints.push_front(fp);
+        next_fp = generated.get(&fp).and_then(|entry| entry.0);
+    }
+    Path::from_fingerprints(model, fingerprints)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use crate::test_util::binary_clock::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn visits_states_in_order_of_delays() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .visitor(recorder)
+            .spawn_delay_bounded(1).join();
+        assert_eq!(
+            accessor()[..8],
+            [
+                // zero delays: always the first action (`IncreaseX`)
+                (0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0),
+            ]);
+    }
+
+    #[test]
+    fn only_explores_states_within_bound() {
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .spawn_delay_bounded(1).join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        // All `x` values with `y == 0` plus a single `IncreaseY` from each of those.
+        assert_eq!(checker.generated_count(), 2 * 256);
+
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .spawn_delay_bounded(0).join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX; 7]);
+    }
+
+    #[test]
+    fn bounds_preemptions() {
+        use crate::actor::{Actor, Id, Out, System, SystemModel, model_timeout};
+        use std::borrow::Cow;
+
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = u8;
+            type Msg = ();
+            fn on_start(&self, _: Id, o: &mut Out<Self>) -> Self::State {
+                o.set_timer(model_timeout());
+                0
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {}
+            fn on_timeout(&self, _: Id, state: &mut Cow<Self::State>, o: &mut Out<Self>) {
+                *state.to_mut() += 1;
+                if **state < 2 { o.set_timer(model_timeout()); }
+            }
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![Property::<SystemModel<Self>>::sometimes("interleaved", |_, state| {
+                    *state.actor_states[0] == 1 && *state.actor_states[1] == 1
+                })]
+            }
+        }
+
+        // Without preemption, an actor only yields once it has no more timeouts.
+        let checker = TestSystem.into_model().checker().spawn_context_bounded(0).join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("interleaved");
+        assert_eq!(checker.generated_count(), 8);
+
+        let mut written: Vec<u8> = Vec::new();
+        let checker = TestSystem.into_model().checker().spawn_context_bounded(1).report(&mut written);
+        checker.assert_any_discovery("interleaved");
+        assert_eq!(checker.generated_count(), 9);
+        let output = String::from_utf8(written).unwrap();
+        assert!(
+            output.contains("Bounded by max_preemptions=1. States beyond the bound were not checked.\n"),
+            "Output did not include bound. output={:?}", output);
+    }
+
+    #[test]
+    fn can_complete_despite_cycles() {
+        let checker = BinaryClock.checker().spawn_delay_bounded(0).join();
+        assert!(checker.is_done());
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 2);
+    }
+}
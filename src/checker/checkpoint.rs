@@ -0,0 +1,58 @@
+//! Private module for selective re-export.
+
+use crate::{Fingerprint, Model};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+/// A snapshot of a checker's progress. Written by [`Checker::checkpoint`] and read by
+/// [`CheckerBuilder::resume_from`].
+///
+/// [`Checker::checkpoint`]: crate::Checker::checkpoint
+/// [`CheckerBuilder::resume_from`]: crate::CheckerBuilder::resume_from
+#[derive(serde::Deserialize, serde::Serialize)]
+pub(crate) struct Checkpoint<State> {
+    /// Each generated fingerprint with the fingerprint of the state that generated it.
+    pub(crate) generated: Vec<(Fingerprint, Option<Fingerprint>)>,
+    /// Each state awaiting evaluation with its fingerprint, depth, and unmet eventually
+    /// properties.
+    pub(crate) pending: Vec<(State, Fingerprint, usize, Vec<usize>)>,
+    /// The fingerprint of each discovery by property name.
+    pub(crate) discoveries: Vec<(String, Fingerprint)>,
+    /// Eventually properties that were unmet on a path pruned by the boundary.
+    pub(crate) boundary_pruned: Vec<usize>,
+}
+
+impl<State> Checkpoint<State> {
+    /// Writes the checkpoint to a temporary file that then replaces the specified path, so that
+    /// an earlier checkpoint survives if writing is interrupted.
+    pub(crate) fn write(&self, path: &Path) -> io::Result<()>
+    where State: Serialize,
+    {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.into_inner().map_err(|err| err.into_error())?.sync_all()?;
+        fs::rename(&tmp_path, path)
+    }
+
+    /// Reads a checkpoint, verifying that each discovery names a property of the model.
+    pub(crate) fn read<M>(model: &M, path: &Path) -> io::Result<Self>
+    where M: Model<State = State>,
+          State: DeserializeOwned,
+    {
+        let checkpoint: Self = serde_json::from_reader(BufReader::new(File::open(path)?))?;
+        let properties = model.properties();
+        for (name, _) in &checkpoint.discoveries {
+            if !properties.iter().any(|p| p.name == name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Checkpoint has a discovery for an unknown property. name={}", name)));
+            }
+        }
+        Ok(checkpoint)
+    }
+}
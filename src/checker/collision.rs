@@ -0,0 +1,204 @@
+//! Private module for selective re-export.
+
+use crate::Fingerprint;
+use dashmap::DashMap;
+use nohash_hasher::NoHashHasher;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+
+/// Indicates how a [`Checker`] distinguishes states, trading memory for the likelihood that two
+/// distinct states are mistaken for one another (in which case the second is not checked, and
+/// checking is not exhaustive). See [`CheckerBuilder::fingerprint`].
+///
+/// [`Checker`]: crate::Checker
+/// [`CheckerBuilder::fingerprint`]: crate::CheckerBuilder::fingerprint
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum FingerprintMode {
+    /// Identifies each state by a 64-bit hash (its fingerprint). This is the default and uses the
+    /// least memory, but colliding fingerprints go undetected. The likelihood of a collision
+    /// becomes significant beyond a few billion states.
+    Bits64,
+    /// Also records an independent 64-bit hash of each state, so that colliding fingerprints are
+    /// detected unless the independent hashes collide as well. Adds roughly 16 bytes per state.
+    Bits128,
+    /// Also retains a copy of each state, so that every collision is detected. Uses the most
+    /// memory.
+    Full,
+}
+
+impl FingerprintMode {
+    /// Estimates the probability that some pair among a number of distinct states collides
+    /// without being detected, per the birthday bound.
+    ///
+    /// ```
+    /// use stateright::FingerprintMode;
+    /// assert!(FingerprintMode::Bits64.collision_probability(1_000_000_000) > 0.02);
+    /// assert!(FingerprintMode::Bits128.collision_probability(1_000_000_000) < 1e-20);
+    /// assert_eq!(FingerprintMode::Full.collision_probability(1_000_000_000), 0.0);
+    /// ```
+    pub fn collision_probability(&self, state_count: usize) -> f64 {
+        let bits = match self {
+            FingerprintMode::Bits64 => 64,
+            FingerprintMode::Bits128 => 128,
+            FingerprintMode::Full => return 0.0,
+        };
+        if state_count < 2 { return 0.0 }
+        let n = state_count as f64;
+        let expected_collisions = n * (n - 1.0) / 2_f64.powi(bits + 1);
+        -(-expected_collisions).exp_m1() // i.e. 1 - e^-expected, accurate for small values
+    }
+}
+
+/// Detects distinct states with the same fingerprint on behalf of a checker's threads.
+pub(crate) enum CollisionDetector<State> {
+    /// Maps each fingerprint to an independent hash of the state.
+    SecondaryHash(DashMap<Fingerprint, u64, BuildHasherDefault<NoHashHasher<u64>>>),
+    /// Maps each fingerprint to the state.
+    FullState {
+        states: DashMap<Fingerprint, State, BuildHasherDefault<NoHashHasher<u64>>>,
+        clone_state: fn(&State) -> State,
+        eq_state: fn(&State, &State) -> bool,
+    },
+}
+
+impl<State: Clone + PartialEq> CollisionDetector<State> {
+    /// Returns `None` if the mode does not detect collisions.
+    pub(crate) fn new(mode: FingerprintMode) -> Option<Self> {
+        match mode {
+            FingerprintMode::Bits64 => None,
+            FingerprintMode::Bits128 => Some(CollisionDetector::SecondaryHash(DashMap::default())),
+            FingerprintMode::Full => Some(CollisionDetector::FullState {
+                states: DashMap::default(),
+                clone_state: State::clone,
+                eq_state: State::eq,
+            }),
+        }
+    }
+}
+
+impl<State: Hash> CollisionDetector<State> {
+    pub(crate) fn mode(&self) -> FingerprintMode {
+        match self {
+            CollisionDetector::SecondaryHash(_) => FingerprintMode::Bits128,
+            CollisionDetector::FullState { .. } => FingerprintMode::Full,
+        }
+    }
+
+    /// Records a generated state, returning `false` if a different state with the same
+    /// fingerprint was recorded earlier.
+    pub(crate) fn record(&self, fingerprint: Fingerprint, state: &State) -> bool {
+        match self {
+            CollisionDetector::SecondaryHash(hashes) => {
+                // A different algorithm than the fingerprint's, so that collisions are unrelated.
+                // Never persisted, so it need not be stable across builds.
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                state.hash(&mut hasher);
+                let hash = hasher.finish();
+                *hashes.entry(fingerprint).or_insert(hash) == hash
+            }
+            CollisionDetector::FullState { states, clone_state, eq_state } => {
+                if let Some(recorded) = states.get(&fingerprint) {
+                    return eq_state(&recorded, state);
+                }
+                let recorded = states.entry(fingerprint).or_insert_with(|| clone_state(state));
+                eq_state(&recorded, state)
+            }
+        }
+    }
+
+    /// Approximates the bytes of memory used to detect collisions, excluding memory owned by
+    /// retained states.
+    pub(crate) fn memory_bytes(&self) -> usize {
+        match self {
+            CollisionDetector::SecondaryHash(hashes) => {
+                hashes.len() * std::mem::size_of::<(Fingerprint, u64)>()
+            }
+            CollisionDetector::FullState { states, .. } => {
+                states.len() * std::mem::size_of::<(Fingerprint, State)>()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    /// A state whose hash ignores a field, so distinct states share fingerprints.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Shadowed { hashed: u8, ignored: u8 }
+
+    impl Hash for Shadowed {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.hashed.hash(state);
+        }
+    }
+
+    struct ShadowedModel;
+
+    impl Model for ShadowedModel {
+        type State = Shadowed;
+        type Action = ();
+
+        fn init_states(&self) -> Vec<Self::State> {
+            vec![Shadowed { hashed: 0, ignored: 0 }]
+        }
+
+        fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+            if state.ignored == 0 { actions.push(()); }
+        }
+
+        fn next_state(&self, state: &Self::State, _: Self::Action) -> Option<Self::State> {
+            Some(Shadowed { hashed: state.hashed, ignored: 1 })
+        }
+
+        fn properties(&self) -> Vec<Property<Self>> {
+            vec![Property::sometimes("ignored", |_, state: &Shadowed| state.ignored == 1)]
+        }
+    }
+
+    #[test]
+    fn detects_collisions() {
+        let fp = Fingerprint::new(1).unwrap();
+        let detector = CollisionDetector::new(FingerprintMode::Bits128).unwrap();
+        assert!(detector.record(fp, &"state"));
+        assert!(detector.record(fp, &"state"));
+        assert!(!detector.record(fp, &"other state"));
+        assert!(CollisionDetector::<u8>::new(FingerprintMode::Bits64).is_none());
+
+        // Without detection, the colliding state is silently skipped.
+        let checker = ShadowedModel.checker().spawn_bfs().join();
+        assert!(checker.is_done());
+        assert!(checker.discovery("ignored").is_none());
+
+        for mode in vec![FingerprintMode::Bits128, FingerprintMode::Full] {
+            let checker = ShadowedModel.checker().fingerprint(mode).spawn_dfs().join();
+            let expected = if mode == FingerprintMode::Full {
+                Some(StopReason::FingerprintCollision)
+            } else {
+                None // hashes of the shadowed states are identical
+            };
+            assert_eq!(checker.stop_reason(), expected);
+        }
+        let checker = ShadowedModel.checker()
+            .fingerprint(FingerprintMode::Full)
+            .spawn_bfs().join();
+        assert_eq!(checker.stop_reason(), Some(StopReason::FingerprintCollision));
+        assert_eq!(checker.fingerprint_collision_probability(), 0.0);
+    }
+
+    #[test]
+    fn does_not_flag_distinct_fingerprints() {
+        for mode in vec![FingerprintMode::Bits128, FingerprintMode::Full] {
+            let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+                .fingerprint(mode)
+                .spawn_bfs().join();
+            assert_eq!(checker.stop_reason(), None);
+            checker.assert_properties();
+            assert!(checker.fingerprint_collision_probability() < 1e-30);
+        }
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs().join();
+        assert!(checker.fingerprint_collision_probability() > 0.0);
+    }
+}
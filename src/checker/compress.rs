@@ -0,0 +1,121 @@
+//! Private module for selective re-export.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Serializes and compresses states awaiting evaluation, and decompresses them on demand. See
+/// [`CheckerBuilder::compress_states`].
+///
+/// [`CheckerBuilder::compress_states`]: crate::CheckerBuilder::compress_states
+pub(crate) struct StateCompressor<State> {
+    compress: fn(&State) -> Box<[u8]>,
+    decompress: fn(&[u8]) -> State,
+}
+
+impl<State: Serialize + DeserializeOwned> StateCompressor<State> {
+    pub(crate) fn new() -> Self {
+        StateCompressor {
+            compress: |state| {
+                let bytes = bincode::serialize(state)
+                    .unwrap_or_else(|err| panic!("Unable to serialize state. err={}", err));
+                lz4_flex::compress_prepend_size(&bytes).into_boxed_slice()
+            },
+            decompress: |compressed| {
+                let bytes = lz4_flex::decompress_size_prepended(compressed)
+                    .unwrap_or_else(|err| panic!("Unable to decompress state. err={}", err));
+                bincode::deserialize(&bytes)
+                    .unwrap_or_else(|err| panic!("Unable to deserialize state. err={}", err))
+            },
+        }
+    }
+}
+
+/// A state that is compressed if a [`StateCompressor`] is in use.
+pub(crate) enum StoredState<State> {
+    Plain(State),
+    Compressed(Box<[u8]>),
+}
+
+impl<State> StoredState<State> {
+    pub(crate) fn new(state: State, compressor: Option<&StateCompressor<State>>) -> Self {
+        match compressor {
+            None => StoredState::Plain(state),
+            Some(compressor) => StoredState::Compressed((compressor.compress)(&state)),
+        }
+    }
+
+    /// Returns the state, decompressing it if necessary.
+    pub(crate) fn into_state(self, compressor: Option<&StateCompressor<State>>) -> State {
+        match self {
+            StoredState::Plain(state) => state,
+            StoredState::Compressed(compressed) => {
+                let compressor = compressor.expect("compressed states require a compressor");
+                (compressor.decompress)(&compressed)
+            }
+        }
+    }
+
+    /// Applies a function to the state, decompressing a copy if necessary.
+    pub(crate) fn with_state<R>(
+        &self, compressor: Option<&StateCompressor<State>>, f: impl FnOnce(&State) -> R) -> R
+    {
+        match self {
+            StoredState::Plain(state) => f(state),
+            StoredState::Compressed(compressed) => {
+                let compressor = compressor.expect("compressed states require a compressor");
+                f(&(compressor.decompress)(compressed))
+            }
+        }
+    }
+}
+
+/// Serializes a [`StoredState`] as the state itself, decompressing it if necessary.
+pub(crate) struct SerializeStored<'a, State>(
+    pub(crate) &'a StoredState<State>,
+    pub(crate) Option<&'a StateCompressor<State>>);
+
+impl<State: Serialize> Serialize for SerializeStored<'_, State> {
+    fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
+        self.0.with_state(self.1, |state| state.serialize(ser))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn round_trips_states() {
+        let compressor = StateCompressor::new();
+        let state = vec![String::from("a register value"); 100];
+        let stored = StoredState::new(state.clone(), Some(&compressor));
+        match &stored {
+            StoredState::Compressed(bytes) => assert!(bytes.len() < 100),
+            StoredState::Plain(_) => panic!("expected a compressed state"),
+        }
+        assert_eq!(stored.with_state(Some(&compressor), Vec::len), 100);
+        assert_eq!(stored.into_state(Some(&compressor)), state);
+
+        let stored = StoredState::new(state.clone(), None);
+        assert_eq!(stored.into_state(None), state);
+    }
+
+    #[test]
+    fn checks_compressed_states() {
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .compress_states()
+            .threads(2)
+            .spawn_bfs().join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .compress_states()
+            .frontier_priority(|_, (x, y)| -((*x as i64) + (*y as i64)))
+            .spawn_bfs().join();
+        checker.assert_properties();
+    }
+}
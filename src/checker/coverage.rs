@@ -0,0 +1,136 @@
+//! Private module for selective re-export.
+
+use crate::Model;
+use dashmap::DashMap;
+use std::collections::BTreeMap;
+
+/// How often each kind of action (per [`Model::action_kind`]) was taken during checking, which
+/// can reveal unreachable code or configuration mistakes such as timers that never fire. See
+/// [`CheckerBuilder::track_coverage`].
+///
+/// [`CheckerBuilder::track_coverage`]: crate::CheckerBuilder::track_coverage
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Coverage {
+    /// The number of transitions taken for each kind of action.
+    pub taken: BTreeMap<String, usize>,
+    /// The number of times each kind of action was enabled but had no effect, meaning
+    /// [`Model::next_state`] returned [`None`]. For an actor system, this counts the callbacks
+    /// that left the actor's state unchanged without sending messages or changing timers.
+    pub no_ops: BTreeMap<String, usize>,
+}
+
+impl Coverage {
+    /// The kinds of action that were enabled but never changed the state.
+    pub fn never_taken(&self) -> Vec<&str> {
+        self.no_ops.keys()
+            .filter(|kind| !self.taken.contains_key(*kind))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Names the kind of an action. See [`Model::action_kind`].
+pub(crate) type ActionKind<M> = Box<dyn Fn(&M, &<M as Model>::Action) -> String + Send + Sync>;
+
+/// Counts transitions by kind of action on behalf of a checker's threads.
+pub(crate) struct CoverageTracker<M: Model> {
+    action_kind: ActionKind<M>,
+    counts: DashMap<String, (usize, usize)>, // (taken, no-ops)
+}
+
+impl<M: Model> CoverageTracker<M> {
+    pub(crate) fn new(action_kind: ActionKind<M>) -> Self {
+        CoverageTracker { action_kind, counts: DashMap::default() }
+    }
+
+    /// Computes the next state like [`Model::next_state`], counting the transition if tracking
+    /// is enabled.
+    pub(crate) fn next_state(
+        tracker: Option<&Self>, model: &M, state: &M::State, action: M::Action)
+        -> Option<M::State>
+    {
+        let tracker = match tracker {
+            None => return model.next_state(state, action),
+            Some(tracker) => tracker,
+        };
+        let kind = (tracker.action_kind)(model, &action);
+        let next_state = model.next_state(state, action);
+        let mut counts = tracker.counts.entry(kind).or_insert((0, 0));
+        if next_state.is_some() { counts.0 += 1 } else { counts.1 += 1 }
+        next_state
+    }
+
+    pub(crate) fn coverage(&self) -> Coverage {
+        let mut coverage = Coverage::default();
+        for mapref in self.counts.iter() {
+            let (taken, no_ops) = *mapref.value();
+            if taken > 0 { coverage.taken.insert(mapref.key().clone(), taken); }
+            if no_ops > 0 { coverage.no_ops.insert(mapref.key().clone(), no_ops); }
+        }
+        coverage
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn counts_actions_by_kind() {
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .track_coverage()
+            .spawn_bfs().join();
+        let coverage = checker.coverage();
+        assert_eq!(coverage.taken.get("IncreaseX"), Some(&(256 * 256)));
+        assert_eq!(coverage.taken.get("IncreaseY"), Some(&(256 * 256)));
+        assert!(coverage.no_ops.is_empty());
+
+        // Not tracked by default.
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_dfs().join();
+        assert_eq!(checker.coverage(), Coverage::default());
+    }
+
+    #[test]
+    fn identifies_actor_callbacks_that_are_no_ops() {
+        use crate::actor::{Actor, Id, Out, System, SystemModel, model_timeout};
+        use std::borrow::Cow;
+
+        struct TestActor;
+        impl Actor for TestActor {
+            type State = ();
+            type Msg = ();
+            fn on_start(&self, id: Id, o: &mut Out<Self>) -> Self::State {
+                if id == Id::from(0) {
+                    o.send(Id::from(1), ());
+                    o.set_timer(model_timeout());
+                }
+            }
+            fn on_msg(&self, _: Id, _: &mut Cow<Self::State>, _: Id, _: Self::Msg, _: &mut Out<Self>) {
+                // Ignores every message.
+            }
+            fn on_timeout(&self, _: Id, _: &mut Cow<Self::State>, _: &mut Out<Self>) {}
+        }
+        struct TestSystem;
+        impl System for TestSystem {
+            type Actor = TestActor;
+            type History = ();
+            fn actors(&self) -> Vec<Self::Actor> { vec![TestActor, TestActor] }
+            fn properties(&self) -> Vec<Property<SystemModel<Self>>> {
+                vec![Property::<SystemModel<Self>>::always("trivial", |_, _| true)]
+            }
+        }
+
+        let mut written: Vec<u8> = Vec::new();
+        let checker = TestSystem.into_model().checker()
+            .track_coverage()
+            .spawn_dfs().report(&mut written);
+        let coverage = checker.coverage();
+        assert_eq!(coverage.taken.keys().collect::<Vec<_>>(), vec!["Timeout at Id(0)"]);
+        assert_eq!(coverage.never_taken(), vec!["Deliver to Id(1)"]);
+        let output = String::from_utf8(written).unwrap();
+        assert!(output.contains("Action \"Timeout at Id(0)\" taken=1\n"), "output={:?}", output);
+        assert!(output.contains("Action \"Deliver to Id(1)\" was enabled but never changed the state.\n"),
+                "output={:?}", output);
+    }
+}
@@ -1,57 +1,182 @@
 //! Private module for selective re-export.
 
-use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
-use crate::checker::{Checker, EventuallyBits, Expectation, Path};
-use dashmap::{DashMap, DashSet};
-use nohash_hasher::NoHashHasher;
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property, StateStore};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, initial_ebits, is_discovery};
+use crate::checker::{ExplorationLimits, FrontierPriority, Path, record_boundary_pruned, with_violating_step};
+use crate::checker::{CheckerStats, Coverage, coverage::CoverageTracker};
+use crate::checker::collision::{CollisionDetector, FingerprintMode};
+use crate::checker::frontier::Frontier;
+use crate::checker::graph_sink::GraphExporter;
+use crate::checker::status::{CheckerStatus, Progress, StopReason};
+use crate::checker::store::Generated;
+use dashmap::DashMap;
 use parking_lot::{Condvar, Mutex};
 use std::collections::{HashMap, VecDeque};
-use std::hash::{BuildHasherDefault, Hash};
+use std::hash::Hash;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 // While this file is currently quite similar to bfs.rs, a refactoring to lift shared
 // behavior is being postponed until DPOR is implemented.
 
 pub(crate) struct DfsChecker<M: Model> {
-    model: Arc<M>,
+    context: Arc<Context<M>>,
     thread_count: usize,
     handles: Vec<std::thread::JoinHandle<()>>,
     job_market: Arc<Mutex<JobMarket<M::State>>>,
-    generated: Arc<DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>>,
-    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+}
+/// State shared by the checker's threads for the duration of a run.
+struct Context<M: Model> {
+    model: M,
+    properties: Vec<Property<M>>,
+    target_discovery_count: usize,
+    generated: DfsGenerated,
+    discoveries: DashMap<&'static str, Vec<Fingerprint>>,
+    boundary_pruned: Mutex<EventuallyBits>,
+    deadlocks: Mutex<Vec<Vec<Fingerprint>>>,
+    progress: Progress,
+    visitor: Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+    frontier_priority: Option<FrontierPriority<M>>,
+    idle_count: AtomicUsize,
+    limits: ExplorationLimits,
+    coverage: Option<CoverageTracker<M>>,
+    graph_exporter: Option<GraphExporter<M>>,
+    collision_detector: Option<CollisionDetector<M::State>>,
+    state_heap_size: Option<fn(&M::State) -> usize>,
 }
 struct JobMarket<State> { wait_count: usize, jobs: Vec<Job<State>> }
-type Job<State> = Vec<(State, Vec<Fingerprint>, EventuallyBits)>;
+type Job<State> = Frontier<(State, Vec<Fingerprint>, usize, EventuallyBits)>;
+
+/// The fingerprints of generated states. Pending states either carry the fingerprints of their
+/// path, or only their own fingerprint if the predecessor of each state is recorded instead.
+enum DfsGenerated {
+    Paths(Generated<()>),
+    Predecessors(Generated<Option<Fingerprint>>),
+}
+
+impl DfsGenerated {
+    fn insert(&self, fingerprint: Fingerprint, predecessor: Option<Fingerprint>) -> bool {
+        match self {
+            DfsGenerated::Paths(generated) => generated.insert(fingerprint, ()),
+            DfsGenerated::Predecessors(generated) => generated.insert(fingerprint, predecessor),
+        }
+    }
+
+    /// The fingerprints that a successor of a pending state carries.
+    fn next_fingerprints(&self, fingerprints: &[Fingerprint], next_fingerprint: Fingerprint)
+        -> Vec<Fingerprint>
+    {
+        match self {
+            DfsGenerated::Paths(_) => {
+                let mut next_fingerprints = Vec::with_capacity(1 + fingerprints.len());
+                for f in fingerprints { next_fingerprints.push(*f); }
+                next_fingerprints.push(next_fingerprint);
+                next_fingerprints
+            },
+            DfsGenerated::Predecessors(_) => vec![next_fingerprint],
+        }
+    }
+
+    /// The fingerprints of the path to a pending state, following recorded predecessors if
+    /// necessary.
+    fn path(&self, fingerprints: &[Fingerprint]) -> Vec<Fingerprint> {
+        match self {
+            DfsGenerated::Paths(_) => fingerprints.to_vec(),
+            DfsGenerated::Predecessors(generated) => {
+                let mut path = fingerprints.to_vec();
+                while let Some(Some(prev)) = generated.get(*path.last().unwrap()) {
+                    path.push(prev);
+                }
+                path.reverse();
+                path
+            },
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            DfsGenerated::Paths(generated) => generated.len(),
+            DfsGenerated::Predecessors(generated) => generated.len(),
+        }
+    }
+
+    fn memory_bytes(&self) -> usize {
+        match self {
+            DfsGenerated::Paths(generated) => generated.memory_bytes(),
+            DfsGenerated::Predecessors(generated) => generated.memory_bytes(),
+        }
+    }
+
+    fn omission_probability(&self) -> Option<f64> {
+        match self {
+            DfsGenerated::Paths(generated) => generated.omission_probability(),
+            DfsGenerated::Predecessors(generated) => generated.omission_probability(),
+        }
+    }
+
+    fn has_failed(&self) -> bool {
+        match self {
+            DfsGenerated::Paths(generated) => generated.has_failed(),
+            DfsGenerated::Predecessors(generated) => generated.has_failed(),
+        }
+    }
+}
 
 impl<M> DfsChecker<M>
 where M: Model + Send + Sync + 'static,
-      M::State: Hash + Send + 'static,
+      M::State: Hash + Send + Sync + 'static,
 {
     pub(crate) fn spawn(options: CheckerBuilder<M>) -> Self {
-        let model = Arc::new(options.model);
+        let properties = options.checked_properties();
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        let model = options.model;
         let target_generated_count = options.target_generated_count;
+        let stop_conditions = options.stop_conditions;
         let thread_count = options.thread_count;
-        let visitor = Arc::new(options.visitor);
-        let property_count = model.properties().len();
-
-        let generated = Arc::new(DashSet::default());
-        for s in model.init_states() { generated.insert(fingerprint(&s)); }
-        let ebits = {
-            let mut ebits = EventuallyBits::new();
-            for (i, p) in model.properties().iter().enumerate() {
-                if let Property { expectation: Expectation::Eventually, .. } = p {
-                    ebits.insert(i);
+        let frontier_priority = options.frontier_priority;
+
+        let generated = if options.record_predecessors {
+            // Predecessors are looked up to build paths, which a Bloom filter cannot do.
+            assert!(!matches!(options.state_store, StateStore::Bloom { .. }),
+                    "StateStore::Bloom cannot record predecessors.");
+            DfsGenerated::Predecessors(Generated::new(&options.state_store))
+        } else {
+            DfsGenerated::Paths(Generated::new(&options.state_store))
+        };
+        for s in model.init_states() { generated.insert(fingerprint(&s), None); }
+        let ebits = initial_ebits(&properties);
+        let mut pending = Frontier::depth_first(frontier_priority.is_some());
+        for s in model.init_states() {
+            let fs = vec![fingerprint(&s)];
+            let priority = frontier_priority.as_ref().map(|priority| priority(&model, &s));
+            pending.push((s, fs, 0, ebits.clone()), priority);
+        }
+        let context = Arc::new(Context {
+            model,
+            properties,
+            target_discovery_count,
+            generated,
+            discoveries: DashMap::default(),
+            boundary_pruned: Mutex::new(EventuallyBits::new()),
+            deadlocks: Mutex::new(Vec::new()),
+            progress: Progress::new(pending.len()),
+            visitor: options.visitor,
+            frontier_priority,
+            idle_count: AtomicUsize::new(0),
+            limits: options.limits,
+            coverage: options.action_kind.map(CoverageTracker::new),
+            graph_exporter: options.graph_exporter,
+            collision_detector: options.collision_detector,
+            state_heap_size: options.state_heap_size,
+        });
+        if let Some(detector) = &context.collision_detector {
+            for (s, fps, _, _) in pending.iter() {
+                if !detector.record(fps[0], s) {
+                    context.progress.stop(StopReason::FingerprintCollision);
                 }
             }
-            ebits
-        };
-        let pending: Vec<_> = model.init_states().into_iter()
-            .map(|s| {
-                let fs = vec![fingerprint(&s)];
-                (s, fs, ebits.clone())
-            })
-            .collect();
-        let discoveries = Arc::new(DashMap::default());
+        }
         let mut handles = Vec::new();
 
         let has_new_job = Arc::new(Condvar::new());
@@ -60,15 +185,14 @@ where M: Model + Send + Sync + 'static,
             jobs: vec![pending],
         }));
         for t in 0..thread_count {
-            let model = Arc::clone(&model);
-            let visitor = Arc::clone(&visitor);
+            let context = Arc::clone(&context);
             let has_new_job = Arc::clone(&has_new_job);
             let job_market = Arc::clone(&job_market);
-            let generated = Arc::clone(&generated);
-            let discoveries = Arc::clone(&discoveries);
+            let stop_conditions = stop_conditions.clone();
             handles.push(std::thread::spawn(move || {
                 log::debug!("{}: Thread started.", t);
-                let mut pending = Vec::new();
+                let Context { generated, discoveries, progress, idle_count, .. } = &*context;
+                let mut pending = Frontier::depth_first(context.frontier_priority.is_some());
                 loop {
                     // Step 1: Do work.
                     if pending.is_empty() {
@@ -76,16 +200,19 @@ where M: Model + Send + Sync + 'static,
                             let mut job_market = job_market.lock();
                             match job_market.jobs.pop() {
                                 None => {
-                                    // Done if all are waiting.
-                                    if job_market.wait_count == thread_count {
+                                    // Done if all are waiting or checking stopped early.
+                                    if job_market.wait_count == thread_count || progress.stop_reason().is_some() {
                                         log::debug!("{}: No more work. Shutting down... gen={}", t, generated.len());
                                         has_new_job.notify_all();
+                                        progress.record_finish();
                                         return
                                     }
 
                                     // Otherwise more work may become available.
                                     log::trace!("{}: No jobs. Awaiting. blocked={}", t, job_market.wait_count);
+                                    idle_count.fetch_add(1, Ordering::Relaxed);
                                     has_new_job.wait(&mut job_market);
+                                    idle_count.fetch_sub(1, Ordering::Relaxed);
                                     continue
                                 }
                                 Some(job) => {
@@ -96,21 +223,32 @@ where M: Model + Send + Sync + 'static,
                             }
                         };
                     }
-                    Self::check_block(&*model, &*generated, &mut pending, &*discoveries, &*visitor, 1500);
-                    if discoveries.len() == property_count {
+                    let pending_count = pending.len();
+                    context.check_block(&mut pending, 1500);
+                    progress.update_pending(pending_count, pending.len());
+                    if discoveries.len() >= context.target_discovery_count {
                         log::debug!("{}: Discovery complete. Shutting down... gen={}", t, generated.len());
                         let mut job_market = job_market.lock();
                         job_market.wait_count += 1;
                         drop(job_market);
                         has_new_job.notify_all();
+                        progress.record_finish();
                         return
                     }
                     if let Some(target_generated_count) = target_generated_count {
                         if target_generated_count.get() <= generated.len() {
                             log::debug!("{}: Reached target generated count. Shutting down... gen={}", t, generated.len());
+                            progress.record_finish();
                             return;
                         }
                     }
+                    if generated.has_failed() { progress.stop(StopReason::Io); }
+                    if progress.should_stop::<M::State>(&stop_conditions, || context.generated_bytes()) {
+                        log::debug!("{}: Stopping early. reason={:?}, gen={}", t, progress.stop_reason(), generated.len());
+                        has_new_job.notify_all();
+                        progress.record_finish();
+                        return;
+                    }
 
                     // Step 2: Share work.
                     if pending.len() > 1 && thread_count > 1 {
@@ -119,7 +257,7 @@ where M: Model + Send + Sync + 'static,
                         let size = pending.len() / pieces;
                         for _ in 1..pieces {
                             log::trace!("{}: Sharing work. blocked={}, size={}", t, job_market.wait_count, size);
-                            job_market.jobs.push(pending.split_off(pending.len() - size));
+                            job_market.jobs.push(pending.split_off(size));
                             has_new_job.notify_one();
                         }
                     } else if pending.is_empty() {
@@ -130,86 +268,87 @@ where M: Model + Send + Sync + 'static,
             }));
         }
         DfsChecker {
-            model,
+            context,
             thread_count,
             handles,
             job_market,
-            generated,
-            discoveries,
         }
     }
+}
 
-    fn check_block(
-        model: &M,
-        generated: &DashSet<Fingerprint, BuildHasherDefault<NoHashHasher<u64>>>,
-        pending: &mut Job<M::State>,
-        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
-        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
-        mut max_count: usize)
-    {
-        let properties = model.properties();
-
+impl<M> Context<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn check_block(&self, pending: &mut Job<M::State>, mut max_count: usize) {
+        let Context {
+            model, properties, target_discovery_count, generated, discoveries, boundary_pruned,
+            deadlocks, progress, visitor, frontier_priority, idle_count, limits, ..
+        } = self;
+        let coverage = self.coverage.as_ref();
+        let graph_exporter = self.graph_exporter.as_ref();
+        let collision_detector = self.collision_detector.as_ref();
         let mut actions = Vec::new();
         loop {
             // Done if reached max count.
             if max_count == 0 { return }
             max_count -= 1;
 
+            // Done early if other threads are idle, so that pending states can be shared with
+            // them rather than leaving cores unused until the block is complete.
+            if max_count.is_multiple_of(64) && pending.len() > 1 && idle_count.load(Ordering::Relaxed) > 0 {
+                return
+            }
+
             // Done if none pending.
-            let (state, fingerprints, mut ebits) = match pending.pop() {
+            let (state, fingerprints, depth, mut ebits) = match pending.pop() {
                 None => return,
                 Some(pair) => pair,
             };
+            progress.record_depth(depth);
+            if let Some(state_heap_size) = self.state_heap_size {
+                progress.record_heap_size(state_heap_size(&state));
+            }
             if let Some(visitor) = visitor {
                 visitor.visit(model, Path::from_fingerprints(
                         model,
-                        VecDeque::from(fingerprints.clone())));
+                        VecDeque::from(generated.path(&fingerprints))));
             }
-
-            // Done if discoveries found for all properties.
-            let mut is_awaiting_discoveries = false;
-            for (i, property) in properties.iter().enumerate() {
-                if discoveries.contains_key(property.name) { continue }
-                match property {
-                    Property { expectation: Expectation::Always, condition: always, .. } => {
-                        if !always(model, &state) {
-                            // Races other threads, but that's fine.
-                            discoveries.insert(property.name, fingerprints.clone());
-                        } else {
-                            is_awaiting_discoveries = true;
-                        }
-                    },
-                    Property { expectation: Expectation::Sometimes, condition: sometimes, .. } => {
-                        if sometimes(model, &state) {
-                            // Races other threads, but that's fine.
-                            discoveries.insert(property.name, fingerprints.clone());
-                        } else {
-                            is_awaiting_discoveries = true;
-                        }
-                    },
-                    Property { expectation: Expectation::Eventually, condition: eventually, .. } => {
-                        // The checker early exits after finding discoveries for every property,
-                        // and "eventually" property discoveries are only identifid at terminal
-                        // states, so if we are here it means we are still awaiting a corresponding
-                        // discovery regardless of whether the eventually property is now satisfied
-                        // (i.e. it might be falsifiable via a different path).
-                        is_awaiting_discoveries = true;
-                        if eventually(model, &state) {
-                            ebits.remove(i);
-                        }
-                    }
-                }
-
+            if let Some(exporter) = graph_exporter {
+                let state_fingerprint = *fingerprints.last().expect("path is nonempty");
+                exporter.sink.on_state(model, state_fingerprint.get(), &state);
             }
-            if !is_awaiting_discoveries { return }
+
+            // Skip successors if discoveries found for all properties or the remaining properties
+            // are monotone and hold.
+            let is_awaiting_discoveries = check_properties(
+                model, properties, &state, &mut ebits,
+                |p| discoveries.contains_key(p.name),
+                // Races other threads, but that's fine.
+                |name| { discoveries.insert(name, generated.path(&fingerprints)); });
+            if discoveries.len() >= *target_discovery_count { return }
+            if !is_awaiting_discoveries { continue }
 
             // Otherwise enqueue newly generated states (with related metadata).
-            let mut is_terminal = true;
-            model.actions(&state, &mut actions);
-            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
-            for next_state in next_states {
-                // Skip if outside boundary.
-                if !model.within_boundary(&next_state) { continue }
+            let is_depth_bounded = !limits.actions(
+                model, &state, depth, &mut actions, boundary_pruned, &ebits);
+            let mut is_terminal = !is_depth_bounded;
+            let next_states = actions.drain(..).flat_map(|a| {
+                let exported_action = graph_exporter.map(|exporter| (exporter.clone_action)(&a));
+                CoverageTracker::next_state(coverage, model, &state, a)
+                    .map(|next_state| (exported_action, next_state))
+            });
+            let mut successor_count = 0;
+            let mut changes_state = false;
+            for (exported_action, next_state) in next_states {
+                successor_count += 1;
+
+                // Skip if outside boundary, noting unmet `eventually` properties.
+                if !model.within_boundary(&next_state) {
+                    record_boundary_pruned(boundary_pruned, &ebits);
+                    changes_state = true;
+                    continue
+                }
 
                 // Skip if already generated.
                 //
@@ -220,7 +359,19 @@ where M: Model + Send + Sync + 'static,
                 // that it holds in the path leading to the second visit -- another
                 // possible false-negative.
                 let next_fingerprint = fingerprint(&next_state);
-                if !generated.insert(next_fingerprint) {
+                let state_fingerprint = *fingerprints.last().expect("path is nonempty");
+                if next_fingerprint != state_fingerprint { changes_state = true; }
+                if let (Some(exporter), Some(action)) = (graph_exporter, &exported_action) {
+                    exporter.sink.on_edge(model, state_fingerprint.get(), action, next_fingerprint.get());
+                }
+                if let Some(detector) = collision_detector {
+                    if !detector.record(next_fingerprint, &next_state) {
+                        log::debug!("Fingerprint collision. fp={}", next_fingerprint);
+                        progress.stop(StopReason::FingerprintCollision);
+                        return
+                    }
+                }
+                if !generated.insert(next_fingerprint, Some(state_fingerprint)) {
                     // FIXME: arriving at an already-known state may be a loop (in which case it
                     // could, in a fancier implementation, be considered a terminal state for
                     // purposes of eventually-property checking) but it might also be a join in
@@ -229,46 +380,79 @@ where M: Model + Send + Sync + 'static,
                     // users they need to explicitly ensure model path-acyclicality when they're
                     // using eventually properties (using a boundary or empty actions or
                     // whatever).
+                    //
+                    // The exception is a `leads_to` property with an unmet obligation, for which
+                    // a loop back to a state on the path is checked as a lasso counterexample.
+                    let is_lasso_candidate = properties.iter().enumerate().any(|(i, p)| {
+                        p.trigger.is_some() && ebits.contains(i) && !discoveries.contains_key(p.name)
+                    });
+                    if is_lasso_candidate {
+                        let mut lasso_fingerprints = generated.path(&fingerprints);
+                        if lasso_fingerprints.contains(&next_fingerprint) {
+                            lasso_fingerprints.push(next_fingerprint);
+                            let lasso = Path::from_fingerprints(
+                                model,
+                                VecDeque::from(lasso_fingerprints.clone()));
+                            for (i, property) in properties.iter().enumerate() {
+                                if property.trigger.is_some() && ebits.contains(i)
+                                    && is_discovery(model, property, &lasso)
+                                {
+                                    // Races other threads, but that's fine.
+                                    discoveries.insert(property.name, lasso_fingerprints.clone());
+                                }
+                            }
+                        }
+                    }
                     is_terminal = false;
                     continue
                 }
 
                 // Otherwise further checking is applicable.
                 is_terminal = false;
-                let mut next_fingerprints = Vec::with_capacity(1 + fingerprints.len());
-                for f in &fingerprints { next_fingerprints.push(*f); }
-                next_fingerprints.push(next_fingerprint);
-                pending.push((next_state, next_fingerprints, ebits.clone()));
+                let next_fingerprints = generated.next_fingerprints(&fingerprints, next_fingerprint);
+                let priority = frontier_priority.as_ref()
+                    .map(|frontier_priority| frontier_priority(model, &next_state));
+                pending.push((next_state, next_fingerprints, depth + 1, ebits.clone()), priority);
+            }
+            if !is_depth_bounded { progress.record_successors(successor_count); }
+            if !changes_state && !is_depth_bounded && !model.is_terminal(&state) {
+                deadlocks.lock().push(generated.path(&fingerprints));
             }
             if is_terminal {
-                for (i, property) in properties.iter().enumerate() {
-                    if ebits.contains(i) {
-                        // Races other threads, but that's fine.
-                        discoveries.insert(property.name, fingerprints.clone());
-                    }
-                }
+                check_terminal_state(
+                    properties, &ebits,
+                    |p| discoveries.contains_key(p.name),
+                    // Races other threads, but that's fine.
+                    |name| { discoveries.insert(name, generated.path(&fingerprints)); });
             }
         }
     }
+
+    /// Approximates the bytes of memory used to record generated states.
+    fn generated_bytes(&self) -> usize {
+        self.generated.memory_bytes()
+            + self.collision_detector.as_ref().map_or(0, CollisionDetector::memory_bytes)
+    }
 }
 
 impl<M> Checker<M> for DfsChecker<M>
 where M: Model,
       M::State: Hash,
 {
-    fn model(&self) -> &M { &self.model }
+    fn model(&self) -> &M { &self.context.model }
 
-    fn generated_count(&self) -> usize { self.generated.len() }
+    fn checked_properties(&self) -> Vec<Property<M>> { self.context.properties.to_vec() }
+
+    fn generated_count(&self) -> usize { self.context.generated.len() }
 
     fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
-        self.discoveries.iter()
+        self.context.discoveries.iter()
             .map(|mapref| {
-                (
-                    <&'static str>::clone(mapref.key()),
-                    Path::from_fingerprints(
-                        self.model(),
-                        VecDeque::from(mapref.value().clone())),
-                )
+                let name = <&'static str>::clone(mapref.key());
+                let path = Path::from_fingerprints(
+                    self.model(),
+                    VecDeque::from(mapref.value().clone()));
+                (name, with_violating_step(self.model(), name, path))
             })
             .collect()
     }
@@ -283,7 +467,46 @@ where M: Model,
     fn is_done(&self) -> bool {
         let job_market = self.job_market.lock();
         job_market.jobs.is_empty() && job_market.wait_count == self.thread_count
-            || self.discoveries.len() == self.model.properties().len()
+            || self.context.discoveries.len() >= self.context.target_discovery_count
+    }
+
+    fn status(&self) -> CheckerStatus {
+        self.context.progress.status::<M::State>(
+            self.generated_count(),
+            self.context.generated_bytes(),
+            self.is_done())
+    }
+
+    fn fingerprint_collision_probability(&self) -> f64 {
+        let mode = self.context.collision_detector.as_ref()
+            .map_or(FingerprintMode::Bits64, |detector| detector.mode());
+        mode.collision_probability(self.generated_count())
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        self.context.progress.stop_reason()
+    }
+
+    fn coverage(&self) -> Coverage {
+        self.context.coverage.as_ref().map(|coverage| coverage.coverage()).unwrap_or_default()
+    }
+
+    fn stats(&self) -> CheckerStats {
+        self.context.progress.stats(self.coverage())
+    }
+
+    fn omission_probability(&self) -> Option<f64> {
+        self.context.generated.omission_probability()
+    }
+
+    fn deadlocks(&self) -> Vec<Path<M::State, M::Action>> {
+        self.context.deadlocks.lock().iter()
+            .map(|fingerprints| Path::from_fingerprints(self.model(), VecDeque::from(fingerprints.clone())))
+            .collect()
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(&self.context.properties, &self.context.boundary_pruned, &self.context.discoveries)
     }
 }
 
@@ -309,6 +532,19 @@ mod test {
             ]);
     }
 
+    #[test]
+    fn visits_states_in_priority_order() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .frontier_priority(|_, s| s.0 as i64)
+            .visitor(recorder)
+            .spawn_dfs().join();
+        assert_eq!(
+            accessor(),
+            vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0)]);
+        assert_eq!(checker.discovery("solvable").unwrap().into_actions(), vec![Guess::IncreaseX; 7]);
+    }
+
     #[cfg(not(debug_assertions))] // too slow for debug build
     #[test]
     fn can_complete_by_enumerating_all_states() {
@@ -318,6 +554,59 @@ mod test {
         assert_eq!(checker.generated_count(), 256 * 256);
     }
 
+    #[test]
+    fn can_complete_with_on_disk_state_store() {
+        let path = crate::checker::store::temp_dir("dfs-test");
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .state_store(StateStore::OnDisk { path: path.clone(), cache_bytes: 64 * 1024 })
+            .threads(2)
+            .spawn_dfs().join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+        drop(checker);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn can_reconstruct_paths_from_predecessors() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .record_predecessors(true)
+            .visitor(recorder)
+            .spawn_dfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 55);
+        assert_eq!(checker.discovery("solvable").unwrap().into_actions(), vec![Guess::IncreaseY; 27]);
+        assert_eq!(accessor()[..3], [(0, 0), (0, 1), (0, 2)]);
+
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .record_predecessors(true)
+            .threads(2)
+            .spawn_dfs().join();
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+        assert!(checker.status().max_depth >= 255 + 255);
+    }
+
+    #[test]
+    fn can_complete_with_bloom_state_store() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .state_store(StateStore::Bloom { bits: 1 << 20 })
+            .spawn_dfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 55);
+        assert!(checker.omission_probability().unwrap() < 1e-9);
+
+        // Too few bits, so most states are omitted.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .state_store(StateStore::Bloom { bits: 1 << 10 })
+            .spawn_dfs().join();
+        assert!(checker.is_done());
+        assert!(checker.generated_count() < 256 * 256);
+        assert!(checker.omission_probability().unwrap() > 0.1);
+    }
+
     #[test]
     fn can_complete_by_eliminating_properties() {
         let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_dfs().join();
@@ -0,0 +1,198 @@
+//! Private module for selective re-export.
+
+use crate::{fingerprint, Model};
+use std::collections::{BTreeSet, HashSet, VecDeque};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
+
+/// The states and transitions reachable in one model but not another, modulo a projection of the
+/// states, which is useful for regression checking a protocol change beyond confirming that its
+/// properties still hold. For example, the models can be two configurations of a
+/// [`SystemModel`] (such as with and without a lossy network) or the same system before and
+/// after changing an actor, and the projection can omit details that are expected to differ.
+///
+/// Each model is traversed in its entirety (within [`Model::within_boundary`]), so this is best
+/// suited to small models. Transitions between states with the same projection are omitted.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Model, StateSpaceDiff};
+/// # struct Counter(u8);
+/// # impl Model for Counter {
+/// #     type State = u8;
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, s: &u8, actions: &mut Vec<()>) { if *s < self.0 { actions.push(()) } }
+/// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { Some(s + 1) }
+/// # }
+/// let diff = StateSpaceDiff::new(&Counter(3), &Counter(5), |s| *s);
+/// assert_eq!(diff.only_in_right.into_iter().collect::<Vec<_>>(), vec![4, 5]);
+/// ```
+///
+/// [`SystemModel`]: crate::actor::SystemModel
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StateSpaceDiff<P: Ord> {
+    /// Projected states that are only reachable in the left model.
+    pub only_in_left: BTreeSet<P>,
+    /// Projected states that are only reachable in the right model.
+    pub only_in_right: BTreeSet<P>,
+    /// Projected transitions that are only taken in the left model.
+    pub transitions_only_in_left: BTreeSet<(P, P)>,
+    /// Projected transitions that are only taken in the right model.
+    pub transitions_only_in_right: BTreeSet<(P, P)>,
+}
+
+impl<P: Clone + Ord> StateSpaceDiff<P> {
+    /// Compares two models with the same type of state.
+    pub fn new<M1, M2>(left: &M1, right: &M2, project: impl Fn(&M1::State) -> P) -> Self
+    where M1: Model,
+          M2: Model<State = M1::State>,
+          M1::State: Hash,
+    {
+        Self::between(left, &project, right, &project)
+    }
+
+    /// Compares two models with different types of state, such as actor systems whose actors
+    /// changed, by projecting both to a common type.
+    pub fn between<M1, M2>(
+        left: &M1, project_left: impl Fn(&M1::State) -> P,
+        right: &M2, project_right: impl Fn(&M2::State) -> P)
+        -> Self
+    where M1: Model,
+          M2: Model,
+          M1::State: Hash,
+          M2::State: Hash,
+    {
+        let (left_states, left_transitions) = reachable(left, project_left);
+        let (right_states, right_transitions) = reachable(right, project_right);
+        StateSpaceDiff {
+            only_in_left: left_states.difference(&right_states).cloned().collect(),
+            only_in_right: right_states.difference(&left_states).cloned().collect(),
+            transitions_only_in_left: left_transitions.difference(&right_transitions).cloned().collect(),
+            transitions_only_in_right: right_transitions.difference(&left_transitions).cloned().collect(),
+        }
+    }
+
+    /// Indicates whether the models have the same projected states and transitions.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_left.is_empty()
+            && self.only_in_right.is_empty()
+            && self.transitions_only_in_left.is_empty()
+            && self.transitions_only_in_right.is_empty()
+    }
+}
+
+impl<P: Debug + Ord> Display for StateSpaceDiff<P> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "States only in left: {}", self.only_in_left.len())?;
+        for state in &self.only_in_left {
+            writeln!(f, "- {:?}", state)?;
+        }
+        writeln!(f, "States only in right: {}", self.only_in_right.len())?;
+        for state in &self.only_in_right {
+            writeln!(f, "- {:?}", state)?;
+        }
+        writeln!(f, "Transitions only in left: {}", self.transitions_only_in_left.len())?;
+        for (src, dst) in &self.transitions_only_in_left {
+            writeln!(f, "- {:?} -> {:?}", src, dst)?;
+        }
+        writeln!(f, "Transitions only in right: {}", self.transitions_only_in_right.len())?;
+        for (src, dst) in &self.transitions_only_in_right {
+            writeln!(f, "- {:?} -> {:?}", src, dst)?;
+        }
+        Ok(())
+    }
+}
+
+/// Collects the projected states and transitions reachable in a model.
+#[allow(clippy::type_complexity)]
+fn reachable<M, P>(model: &M, project: impl Fn(&M::State) -> P) -> (BTreeSet<P>, BTreeSet<(P, P)>)
+where M: Model,
+      M::State: Hash,
+      P: Clone + Ord,
+{
+    let mut generated = HashSet::new();
+    let mut pending = VecDeque::new();
+    let mut states = BTreeSet::new();
+    let mut transitions = BTreeSet::new();
+    for state in model.init_states() {
+        if generated.insert(fingerprint(&state)) {
+            states.insert(project(&state));
+            pending.push_back(state);
+        }
+    }
+    while let Some(state) = pending.pop_front() {
+        let src = project(&state);
+        for next_state in model.next_states(&state) {
+            if !model.within_boundary(&next_state) { continue }
+            let dst = project(&next_state);
+            if src != dst { transitions.insert((src.clone(), dst.clone())); }
+            states.insert(dst);
+            if generated.insert(fingerprint(&next_state)) {
+                pending.push_back(next_state);
+            }
+        }
+    }
+    (states, transitions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::actor::{DuplicatingNetwork, LossyNetwork, System};
+    use crate::actor::actor_test_util::ping_pong::PingPongSystem;
+    use crate::test_util::dgraph::DGraph;
+    use crate::Property;
+
+    #[test]
+    fn reports_states_and_transitions_in_only_one_model() {
+        let graph = || DGraph::with_property(Property::always("true", |_, _| true));
+        let left = graph().with_path(vec![1, 2, 3]);
+        let right = graph().with_path(vec![1, 2, 4]).with_path(vec![1, 3]);
+        let diff = StateSpaceDiff::new(&left, &right, |s| *s);
+        assert_eq!(diff.only_in_left, BTreeSet::new());
+        assert_eq!(diff.only_in_right, vec![4].into_iter().collect());
+        assert_eq!(diff.transitions_only_in_left, vec![(2, 3)].into_iter().collect());
+        assert_eq!(diff.transitions_only_in_right, vec![(1, 3), (2, 4)].into_iter().collect());
+        assert_eq!(
+            format!("{}", diff),
+            "States only in left: 0\n\
+             States only in right: 1\n\
+             - 4\n\
+             Transitions only in left: 1\n\
+             - 2 -> 3\n\
+             Transitions only in right: 2\n\
+             - 1 -> 3\n\
+             - 2 -> 4\n");
+
+        // Differences can be projected away.
+        assert!(StateSpaceDiff::new(&left, &right, |s| *s > 0).is_empty());
+    }
+
+    #[test]
+    fn can_compare_actor_system_configurations() {
+        let system = |lossy| PingPongSystem {
+            max_nat: 2,
+            lossy,
+            duplicating: DuplicatingNetwork::No,
+            maintains_history: false,
+        };
+        let lossy = system(LossyNetwork::Yes).into_model();
+        let reliable = system(LossyNetwork::No).into_model();
+
+        // Dropping a message leaves the counts unchanged but stops the exchange.
+        let diff = StateSpaceDiff::new(&lossy, &reliable, |s| {
+            (s.actor_states.iter().map(|s| s.0).collect::<Vec<_>>(), s.network.len())
+        });
+        assert!(diff.only_in_right.is_empty());
+        assert!(diff.only_in_left.contains(&(vec![1, 1], 0)));
+        assert!(diff.transitions_only_in_right.is_empty());
+
+        // Otherwise the counts are the same.
+        let diff = StateSpaceDiff::new(&lossy, &reliable, |s| {
+            s.actor_states.iter().map(|s| s.0).collect::<Vec<_>>()
+        });
+        assert!(diff.is_empty(), "{}", diff);
+    }
+}
@@ -0,0 +1,136 @@
+//! Private module for selective re-export.
+
+use crate::{fingerprint, Model};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::Hash;
+
+/// A summary of the reachable state space of a model that is stable across runs and platforms,
+/// which is useful for regression checking in CI. Unlike an exact [`Checker::generated_count`],
+/// a mismatch indicates how the state space changed, and the digest does not depend on the
+/// checking strategy or thread count. See [`Checker::assert_matches_snapshot`].
+///
+/// The state space is regenerated from the model (within [`Model::within_boundary`]) rather than
+/// the checker's record of visited states, so this is best suited to small models.
+///
+/// [`Checker::generated_count`]: crate::Checker::generated_count
+/// [`Checker::assert_matches_snapshot`]: crate::Checker::assert_matches_snapshot
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StateSpaceDigest {
+    /// The number of states first reached at each depth, starting with the init states.
+    pub states_per_depth: Vec<usize>,
+    /// The number of transitions taken for each kind of action (per [`Model::action_kind`]).
+    pub actions: BTreeMap<String, usize>,
+    /// A hash of the fingerprints of the reachable states, independent of the order in which
+    /// they were reached.
+    pub fingerprint: u64,
+}
+
+impl StateSpaceDigest {
+    /// Summarizes the state space reachable in a model.
+    pub fn new<M>(model: &M) -> Self
+    where M: Model,
+          M::State: Hash,
+          M::Action: Debug,
+    {
+        let mut digest = StateSpaceDigest::default();
+        let mut generated = HashSet::new();
+        let mut pending = VecDeque::new();
+        for state in model.init_states() {
+            let fp = fingerprint(&state);
+            if generated.insert(fp) {
+                digest.record(0, fp.get());
+                pending.push_back((0, state));
+            }
+        }
+        while let Some((depth, state)) = pending.pop_front() {
+            for (action, next_state) in model.next_steps(&state) {
+                if !model.within_boundary(&next_state) { continue }
+                *digest.actions.entry(model.action_kind(&action)).or_default() += 1;
+                let fp = fingerprint(&next_state);
+                if generated.insert(fp) {
+                    digest.record(depth + 1, fp.get());
+                    pending.push_back((depth + 1, next_state));
+                }
+            }
+        }
+        digest
+    }
+
+    /// The number of reachable states.
+    pub fn state_count(&self) -> usize {
+        self.states_per_depth.iter().sum()
+    }
+
+    fn record(&mut self, depth: usize, fingerprint: u64) {
+        if self.states_per_depth.len() == depth {
+            self.states_per_depth.push(0);
+        }
+        self.states_per_depth[depth] += 1;
+        self.fingerprint = self.fingerprint.wrapping_add(fingerprint);
+    }
+}
+
+/// The snapshot format, which lists one fact per line so that changes are easy to review.
+impl Display for StateSpaceDigest {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "states={}", self.state_count())?;
+        writeln!(f, "fingerprint={:016x}", self.fingerprint)?;
+        for (depth, count) in self.states_per_depth.iter().enumerate() {
+            writeln!(f, "depth {}: {}", depth, count)?;
+        }
+        for (kind, count) in &self.actions {
+            writeln!(f, "action \"{}\": {}", kind, count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Property};
+    use crate::test_util::dgraph::DGraph;
+
+    #[test]
+    fn summarizes_state_space() {
+        let graph = DGraph::with_property(Property::always("true", |_, _| true))
+            .with_path(vec![0, 1, 3])
+            .with_path(vec![0, 2, 3, 0]);
+        let digest = StateSpaceDigest::new(&graph);
+        assert_eq!(digest.states_per_depth, vec![1, 2, 1]);
+        assert_eq!(digest.state_count(), 4);
+        assert_eq!(digest.actions.values().sum::<usize>(), 5);
+        assert_eq!(digest, graph.check().state_space_digest());
+
+        // The fingerprint is independent of the order in which states are reached.
+        let reordered = DGraph::with_property(Property::always("true", |_, _| true))
+            .with_path(vec![0, 3, 1, 2]);
+        assert_eq!(digest.fingerprint, StateSpaceDigest::new(&reordered).fingerprint);
+        assert_ne!(digest.fingerprint, StateSpaceDigest::new(&graph.with_boundary(2)).fingerprint);
+    }
+
+    #[test]
+    fn can_compare_to_snapshot() {
+        let path = crate::checker::store::temp_dir("digest-test");
+        let graph = DGraph::with_property(Property::always("true", |_, _| true))
+            .with_path(vec![0, 1]);
+
+        // The first run records the snapshot, and subsequent runs compare against it.
+        graph.check().assert_matches_snapshot(&path);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("states=2\n\
+                     fingerprint={:016x}\n\
+                     depth 0: 1\n\
+                     depth 1: 1\n\
+                     action \"1\": 1\n", StateSpaceDigest::new(&graph).fingerprint));
+        graph.check().assert_matches_snapshot(&path);
+
+        let changed = std::panic::catch_unwind(|| {
+            graph.with_path(vec![1, 2]).check().assert_matches_snapshot(&path);
+        });
+        let _ = std::fs::remove_file(&path);
+        assert!(changed.is_err());
+    }
+}
@@ -0,0 +1,602 @@
+//! Private module for selective re-export.
+
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, initial_ebits, Path};
+use crate::checker::{ExplorationLimits, record_boundary_pruned, with_violating_step};
+use crate::checker::status::{CheckerStatus, Progress, StopReason};
+use crate::checker::store::Generated;
+use dashmap::DashMap;
+use parking_lot::{Condvar, Mutex};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{BufReader, BufWriter, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+// Each process in the cluster owns the fingerprints that hash to its index, so the set of
+// generated fingerprints is partitioned across processes. A process checks the states that it
+// owns, sending each successor to the successor's owner, which skips it if already generated.
+// States carry the fingerprints of their path (as with the DFS checker) so that any process can
+// reconstruct a discovery without consulting the others. Pending states are never stolen, so an
+// idle process waits until a peer sends it a successor that it owns.
+//
+// The first process coordinates termination using Mattern's "four counter" method: it
+// repeatedly asks every process whether it is idle and how many messages it has sent and
+// received. Checking is done once two consecutive waves find every process idle with identical
+// totals, and sent equal to received (i.e. no messages are in flight). An I/O error would
+// unbalance those totals, so a process that encounters one stops and asks its peers to stop.
+
+/// How many times to try connecting to a peer, 100ms apart, before stopping.
+const CONNECT_ATTEMPTS: usize = if cfg!(test) { 10 } else { 300 };
+
+pub(crate) struct DistributedChecker<M: Model> {
+    node: Arc<Node<M>>,
+    handles: Vec<std::thread::JoinHandle<()>>,
+}
+
+struct Node<M: Model> {
+    model: M,
+    properties: Vec<Property<M>>,
+    target_discovery_count: usize,
+    index: usize,
+    peers: Vec<SocketAddr>,
+    limits: ExplorationLimits,
+    outbound: Vec<Mutex<Option<BufWriter<TcpStream>>>>,
+    queue: Mutex<Queue<M::State>>,
+    has_work: Condvar,
+    generated: Generated<()>,
+    discoveries: DashMap<&'static str, Vec<Fingerprint>>,
+    boundary_pruned: Mutex<EventuallyBits>,
+    sent: AtomicUsize,
+    received: AtomicUsize,
+    is_done: AtomicBool,
+    progress: Progress,
+}
+struct Queue<State> { jobs: VecDeque<(State, Vec<Fingerprint>, EventuallyBits)>, is_busy: bool }
+
+/// Messages exchanged by the processes in a cluster.
+#[derive(serde::Deserialize, serde::Serialize)]
+enum Msg<State> {
+    /// A state for the recipient to check, as the recipient owns its fingerprint.
+    State(State, Vec<Fingerprint>, Vec<usize>),
+    /// A discovery for the named property.
+    Discovery(String, Vec<Fingerprint>),
+    /// Asks the recipient for a `ProbeReply`.
+    Probe(u64),
+    /// Indicates whether the sender is idle and how many states and discoveries it has sent and
+    /// received.
+    ProbeReply { round: u64, is_idle: bool, sent: usize, received: usize },
+    /// Indicates that checking is done.
+    Done,
+    /// Indicates that the sender stopped before checking was done.
+    Abort,
+}
+type ProbeReply = (u64, bool, usize, usize);
+
+impl<M> DistributedChecker<M>
+where M: Model + Send + Sync + 'static,
+      M::State: Hash + Send + Serialize + DeserializeOwned + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>, peers: Vec<SocketAddr>, index: usize)
+        -> std::io::Result<Self>
+    {
+        assert!(index < peers.len(),
+                "Process index out of range. index={}, peer_count={}", index, peers.len());
+        let listener = TcpListener::bind(peers[index])?;
+        listener.set_nonblocking(true)?;
+
+        let properties = options.checked_properties();
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        let model = options.model;
+        let visitor = options.visitor;
+        let ebits = initial_ebits(&properties);
+        let generated = Generated::new(&options.state_store);
+        let mut jobs = VecDeque::new();
+        for s in model.init_states() {
+            let fp = fingerprint(&s);
+            if owner(fp, peers.len()) == index && generated.insert(fp, ()) {
+                jobs.push_back((s, vec![fp], ebits.clone()));
+            }
+        }
+        let node = Arc::new(Node {
+            model,
+            properties,
+            target_discovery_count,
+            index,
+            limits: options.limits,
+            outbound: peers.iter().map(|_| Mutex::new(None)).collect(),
+            peers,
+            progress: Progress::new(jobs.len()),
+            queue: Mutex::new(Queue { jobs, is_busy: false }),
+            has_work: Condvar::new(),
+            generated,
+            discoveries: DashMap::default(),
+            boundary_pruned: Mutex::new(EventuallyBits::new()),
+            sent: AtomicUsize::new(0),
+            received: AtomicUsize::new(0),
+            is_done: AtomicBool::new(false),
+        });
+
+        let (probe_replies, probe_reply_receiver) = channel();
+        let mut handles = Vec::new();
+        handles.push({
+            let node = Arc::clone(&node);
+            std::thread::spawn(move || node.accept(listener, probe_replies))
+        });
+        if index == 0 {
+            let node = Arc::clone(&node);
+            handles.push(std::thread::spawn(move || node.coordinate(probe_reply_receiver)));
+        }
+        handles.push({
+            let node = Arc::clone(&node);
+            std::thread::spawn(move || node.check(&visitor))
+        });
+        Ok(DistributedChecker { node, handles })
+    }
+}
+
+impl<M> Node<M>
+where M: Model + Send + Sync + 'static,
+      M::State: Hash + Send + Serialize + DeserializeOwned + 'static,
+{
+    /// Accepts connections from peers until checking is done.
+    fn accept(self: Arc<Self>, listener: TcpListener, probe_replies: Sender<ProbeReply>) {
+        while !self.is_finished() {
+            match listener.accept() {
+                Ok((stream, addr)) => {
+                    log::debug!("{}: Accepted connection. addr={}", self.index, addr);
+                    if let Err(err) = stream.set_nonblocking(false) {
+                        log::error!("{}: Unable to configure connection. addr={}, err={}",
+                                    self.index, addr, err);
+                        continue
+                    }
+                    let node = Arc::clone(&self);
+                    let probe_replies = probe_replies.clone();
+                    std::thread::spawn(move || node.receive(stream, probe_replies));
+                },
+                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(10));
+                },
+                Err(err) => {
+                    log::error!("{}: Unable to accept connection. err={}", self.index, err);
+                },
+            }
+        }
+    }
+
+    /// Handles messages from a peer until the connection is closed.
+    fn receive(&self, stream: TcpStream, probe_replies: Sender<ProbeReply>) {
+        let msgs = serde_json::Deserializer::from_reader(BufReader::new(stream))
+            .into_iter::<Msg<M::State>>();
+        for msg in msgs {
+            match msg {
+                Err(err) => {
+                    if !err.is_eof() {
+                        log::error!("{}: Unable to read message. err={}", self.index, err);
+                    }
+                    return
+                },
+                Ok(Msg::State(state, fingerprints, ebits)) => {
+                    let fp = *fingerprints.last().expect("path is nonempty");
+                    if self.generated.insert(fp, ()) {
+                        let mut queue = self.queue.lock();
+                        queue.jobs.push_back((state, fingerprints, ebits.into_iter().collect()));
+                        self.progress.set_pending(queue.jobs.len());
+                        self.has_work.notify_one();
+                    }
+                    self.received.fetch_add(1, Ordering::AcqRel);
+                },
+                Ok(Msg::Discovery(name, fingerprints)) => {
+                    match self.properties.iter().find(|p| p.name == name) {
+                        None => {
+                            log::error!("{}: Ignoring discovery for unknown property. name={}",
+                                        self.index, name);
+                        },
+                        Some(property) => {
+                            self.discoveries.entry(property.name).or_insert(fingerprints);
+                        },
+                    }
+                    self.received.fetch_add(1, Ordering::AcqRel);
+                },
+                Ok(Msg::Probe(round)) => {
+                    let (is_idle, sent, received) = self.probe();
+                    self.send(0, &Msg::ProbeReply { round, is_idle, sent, received });
+                    self.flush(0);
+                },
+                Ok(Msg::ProbeReply { round, is_idle, sent, received }) => {
+                    let _ = probe_replies.send((round, is_idle, sent, received));
+                },
+                Ok(Msg::Done) => {
+                    self.finish();
+                },
+                Ok(Msg::Abort) => {
+                    log::debug!("{}: Peer stopped. Stopping.", self.index);
+                    self.stop();
+                },
+            }
+        }
+    }
+
+    /// Repeatedly probes every process until detecting that checking is done.
+    fn coordinate(&self, probe_replies: Receiver<ProbeReply>) {
+        let mut previous_totals = None;
+        for round in 0.. {
+            std::thread::sleep(Duration::from_millis(10));
+            if self.is_finished() { return }
+            for dst in 1..self.peers.len() {
+                self.send(dst, &Msg::Probe(round));
+                self.flush(dst);
+            }
+            let (mut is_idle, mut sent, mut received) = self.probe();
+            let mut reply_count = 0;
+            while reply_count + 1 < self.peers.len() {
+                match probe_replies.recv_timeout(Duration::from_secs(1)) {
+                    Ok((reply_round, reply_is_idle, reply_sent, reply_received)) => {
+                        if reply_round != round { continue } // stale
+                        reply_count += 1;
+                        is_idle &= reply_is_idle;
+                        sent += reply_sent;
+                        received += reply_received;
+                    },
+                    Err(RecvTimeoutError::Timeout) => {
+                        log::debug!("{}: Awaiting probe replies. round={}", self.index, round);
+                        is_idle = false;
+                        break
+                    },
+                    Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+
+            let totals = if is_idle && sent == received { Some((sent, received)) } else { None };
+            if totals.is_some() && totals == previous_totals {
+                log::debug!("{}: Checking is done. Notifying peers.", self.index);
+                for dst in 1..self.peers.len() {
+                    self.send(dst, &Msg::Done);
+                    self.flush(dst);
+                }
+                self.finish();
+                return
+            }
+            previous_totals = totals;
+        }
+    }
+
+    /// Checks states owned by this process until checking is done.
+    fn check(&self, visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>) {
+        let model = &self.model;
+        let properties = &self.properties;
+        let mut actions = Vec::new();
+        loop {
+            if self.generated.has_failed() { self.stop(); } // the error is logged by the store
+            let (state, fingerprints, mut ebits) = {
+                let mut queue = self.queue.lock();
+                loop {
+                    if self.is_finished() {
+                        drop(queue);
+                        self.close();
+                        return
+                    }
+                    if self.discoveries.len() < self.target_discovery_count {
+                        if let Some(job) = queue.jobs.pop_front() {
+                            queue.is_busy = true;
+                            self.progress.set_pending(queue.jobs.len());
+                            break job
+                        }
+                    }
+                    if queue.is_busy {
+                        // Deliver sent messages before idling.
+                        drop(queue);
+                        for dst in 0..self.peers.len() { self.flush(dst); }
+                        queue = self.queue.lock();
+                        queue.is_busy = false;
+                        continue
+                    }
+                    self.has_work.wait_for(&mut queue, Duration::from_millis(100));
+                }
+            };
+            self.progress.record_depth(fingerprints.len() - 1);
+            if let Some(visitor) = visitor {
+                visitor.visit(model, Path::from_fingerprints(
+                        model,
+                        VecDeque::from(fingerprints.clone())));
+            }
+
+            // Skip successors if the remaining properties are monotone and hold.
+            let is_awaiting_discoveries = check_properties(
+                model, properties, &state, &mut ebits,
+                |p| self.discoveries.contains_key(p.name),
+                |name| self.discover(name, &fingerprints));
+            if !is_awaiting_discoveries { continue }
+
+            // Otherwise check owned successors and send the rest to their owners. As with the
+            // other checkers, a previously generated successor does not make the state terminal.
+            let mut is_terminal = self.limits.actions(
+                model, &state, fingerprints.len() - 1, &mut actions, &self.boundary_pruned, &ebits);
+            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
+            for next_state in next_states {
+                if !model.within_boundary(&next_state) {
+                    record_boundary_pruned(&self.boundary_pruned, &ebits);
+                    continue
+                }
+                is_terminal = false;
+                let next_fingerprint = fingerprint(&next_state);
+                let mut next_fingerprints = fingerprints.clone();
+                next_fingerprints.push(next_fingerprint);
+                let dst = owner(next_fingerprint, self.peers.len());
+                if dst != self.index {
+                    self.send(dst, &Msg::State(next_state, next_fingerprints, ebits.iter().collect()));
+                } else if self.generated.insert(next_fingerprint, ()) {
+                    self.queue.lock().jobs.push_back((next_state, next_fingerprints, ebits.clone()));
+                }
+            }
+            if is_terminal {
+                check_terminal_state(
+                    properties, &ebits,
+                    |p| self.discoveries.contains_key(p.name),
+                    |name| self.discover(name, &fingerprints));
+            }
+        }
+    }
+
+    /// Records a discovery and shares it with the other processes.
+    fn discover(&self, name: &'static str, fingerprints: &[Fingerprint]) {
+        self.discoveries.insert(name, fingerprints.to_vec());
+        for dst in 0..self.peers.len() {
+            if dst == self.index { continue }
+            self.send(dst, &Msg::Discovery(name.to_string(), fingerprints.to_vec()));
+        }
+    }
+
+    /// Indicates whether this process is idle and how many messages it has sent and received.
+    fn probe(&self) -> (bool, usize, usize) {
+        let queue = self.queue.lock();
+        let is_idle = !queue.is_busy
+            && (queue.jobs.is_empty()
+                || self.discoveries.len() >= self.target_discovery_count);
+        let received = self.received.load(Ordering::Acquire);
+        let sent = self.sent.load(Ordering::Acquire);
+        (is_idle, sent, received)
+    }
+
+    /// Buffers a message for a peer, connecting first if necessary. Only a message that is
+    /// written counts as sent, but the cluster stops regardless if a write fails.
+    fn send(&self, dst: usize, msg: &Msg<M::State>) {
+        let mut outbound = self.outbound[dst].lock();
+        if outbound.is_none() {
+            match self.connect(dst) {
+                Ok(stream) => *outbound = Some(BufWriter::new(stream)),
+                Err(err) => return self.fail("Unable to connect", dst, err),
+            }
+        }
+        let writer = outbound.as_mut().unwrap();
+        match serde_json::to_writer(writer, msg) {
+            Ok(()) => {
+                if let Msg::State(..) | Msg::Discovery(..) = msg {
+                    self.sent.fetch_add(1, Ordering::AcqRel);
+                }
+            },
+            Err(err) => self.fail("Unable to send message", dst, err),
+        }
+    }
+
+    /// Sends buffered messages to a peer.
+    fn flush(&self, dst: usize) {
+        if let Some(writer) = self.outbound[dst].lock().as_mut() {
+            if let Err(err) = writer.flush() {
+                self.fail("Unable to send messages", dst, err);
+            }
+        }
+    }
+
+    /// Connects to a peer, retrying until the peer is listening or [`CONNECT_ATTEMPTS`] fail.
+    fn connect(&self, dst: usize) -> std::io::Result<TcpStream> {
+        let mut attempt = 0;
+        loop {
+            match TcpStream::connect(self.peers[dst]) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => {
+                    attempt += 1;
+                    if attempt == CONNECT_ATTEMPTS || self.is_finished() { return Err(err) }
+                    if attempt % 50 == 0 {
+                        log::warn!("{}: Unable to connect. Retrying. dst={}, err={}",
+                                   self.index, self.peers[dst], err);
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                },
+            }
+        }
+    }
+
+    /// Closes connections to peers, which stops their corresponding receiving threads. Peers are
+    /// first asked to stop if this process stopped before checking was done.
+    fn close(&self) {
+        let is_stopped = self.progress.stop_reason().is_some();
+        for outbound in &self.outbound {
+            if let Some(mut writer) = outbound.lock().take() {
+                if is_stopped { let _ = serde_json::to_writer(&mut writer, &Msg::<M::State>::Abort); }
+                let _ = writer.flush();
+                let _ = writer.get_ref().shutdown(Shutdown::Write);
+            }
+        }
+    }
+
+    /// Logs an I/O error and stops.
+    fn fail(&self, context: &str, dst: usize, err: impl Display) {
+        log::error!("{}: {}. Stopping. dst={}, err={}", self.index, context, self.peers[dst], err);
+        self.stop();
+    }
+
+    /// Stops before checking is done, as the cluster is unable to detect termination.
+    fn stop(&self) {
+        self.progress.stop(StopReason::Io);
+        self.progress.record_finish();
+        let _queue = self.queue.lock();
+        self.has_work.notify_all();
+    }
+
+    /// Indicates whether checking is done or stopped.
+    fn is_finished(&self) -> bool {
+        self.is_done.load(Ordering::Acquire) || self.progress.stop_reason().is_some()
+    }
+
+    fn finish(&self) {
+        self.is_done.store(true, Ordering::Release);
+        self.progress.record_finish();
+        let _queue = self.queue.lock();
+        self.has_work.notify_all();
+    }
+}
+
+/// The index of the process that owns a fingerprint.
+fn owner(fingerprint: Fingerprint, peer_count: usize) -> usize {
+    // Low bits are used by in-memory hash tables, so partition on high bits.
+    ((fingerprint.get() >> 32) % peer_count as u64) as usize
+}
+
+impl<M> Checker<M> for DistributedChecker<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn model(&self) -> &M { &self.node.model }
+
+    fn checked_properties(&self) -> Vec<Property<M>> { self.node.properties.clone() }
+
+    fn generated_count(&self) -> usize { self.node.generated.len() }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.node.discoveries.iter()
+            .map(|mapref| {
+                let name = <&'static str>::clone(mapref.key());
+                let path = Path::from_fingerprints(
+                    self.model(),
+                    VecDeque::from(mapref.value().clone()));
+                (name, with_violating_step(self.model(), name, path))
+            })
+            .collect()
+    }
+
+    fn join(mut self) -> Self {
+        for h in self.handles.drain(0..) {
+            h.join().unwrap();
+        }
+        self
+    }
+
+    fn is_done(&self) -> bool {
+        self.node.is_done.load(Ordering::Acquire)
+    }
+
+    fn status(&self) -> CheckerStatus {
+        self.node.progress.status::<(M::State, Vec<Fingerprint>)>(
+            self.generated_count(), self.node.generated.memory_bytes(), self.is_done())
+    }
+
+    fn stop_reason(&self) -> Option<StopReason> {
+        self.node.progress.stop_reason()
+    }
+
+    fn omission_probability(&self) -> Option<f64> {
+        self.node.generated.omission_probability()
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(
+            &self.node.properties, &self.node.boundary_pruned, &self.node.discoveries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::dgraph::DGraph;
+    use crate::test_util::linear_equation_solver::*;
+    use std::net::{Ipv4Addr, TcpListener};
+
+    /// Reserves ephemeral ports for a cluster.
+    fn addresses(count: usize) -> Vec<SocketAddr> {
+        let listeners: Vec<_> = (0..count)
+            .map(|_| TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap())
+            .collect();
+        listeners.iter().map(|l| l.local_addr().unwrap()).collect()
+    }
+
+    #[test]
+    fn can_check_exhaustively_across_processes() {
+        let model = DGraph::with_property(Property::eventually("odd", |_, s| s % 2 == 1))
+            .with_path(vec![1])
+            .with_path(vec![2, 3])
+            .with_path(vec![2, 6, 7])
+            .with_path(vec![4, 9, 10]);
+        let peers = addresses(3);
+        let checkers: Vec<_> = (0..3)
+            .map(|i| model.clone().checker().spawn_distributed(peers.clone(), i).unwrap())
+            .collect();
+        let checkers: Vec<_> = checkers.into_iter().map(|c| c.join()).collect();
+        assert_eq!(checkers.iter().map(|c| c.generated_count()).sum::<usize>(), 8);
+        for checker in checkers {
+            assert!(checker.is_done());
+            checker.assert_properties();
+        }
+    }
+
+    #[test]
+    fn stops_if_unable_to_reach_peer() {
+        // The second process never starts.
+        let peers = addresses(2);
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .spawn_distributed(peers, 0).unwrap()
+            .join();
+        assert!(!checker.is_done());
+        assert_eq!(checker.stop_reason(), Some(StopReason::Io));
+    }
+
+    #[test]
+    fn can_check_only_target_properties() {
+        struct Counter;
+        impl Model for Counter {
+            type State = u8;
+            type Action = ();
+            fn init_states(&self) -> Vec<u8> { vec![0] }
+            fn actions(&self, _: &u8, actions: &mut Vec<()>) { actions.push(()) }
+            fn next_state(&self, state: &u8, _: ()) -> Option<u8> { state.checked_add(1) }
+            fn properties(&self) -> Vec<Property<Self>> {
+                vec![
+                    Property::always("small", |_, s| *s < 10),
+                    Property::sometimes("large", |_, s| *s == 200),
+                ]
+            }
+        }
+        let checker = Counter.checker()
+            .target_properties(&["small"])
+            .stop_on_first_discovery()
+            .spawn_distributed(addresses(1), 0).unwrap()
+            .join();
+        assert!(checker.is_done());
+        assert_eq!(checker.discovery("small").unwrap().last_state(), &10);
+        assert_eq!(checker.checked_properties().len(), 1);
+        checker.assert_no_discovery("large");
+    }
+
+    #[test]
+    fn shares_discoveries_across_processes() {
+        let peers = addresses(2);
+        let checkers: Vec<_> = (0..2)
+            .map(|i| {
+                LinearEquation { a: 2, b: 10, c: 14 }.checker()
+                    .spawn_distributed(peers.clone(), i).unwrap()
+            })
+            .collect();
+        for checker in checkers {
+            let checker = checker.join();
+            let actions = checker.discovery("solvable").unwrap().into_actions();
+            checker.assert_discovery("solvable", actions);
+        }
+    }
+}
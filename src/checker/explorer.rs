@@ -1,13 +1,18 @@
+use actix::{Actor, ActorContext, AsyncContext, StreamHandler};
 use actix_web::{*, web::Json};
+use actix_web_actors::ws;
 use crate::*;
+use crate::checker::is_discovery;
 use parking_lot::RwLock;
 use serde::ser::{SerializeStruct, Serializer};
 use serde::Serialize;
 use std::net::ToSocketAddrs;
+use std::marker::PhantomData;
 use std::sync::Arc;
 use std::thread::{sleep, spawn};
 use std::time::Duration;
-use std::collections::{BTreeMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::collections::hash_map::Entry;
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
 struct StatusView {
@@ -16,12 +21,32 @@ struct StatusView {
     generated: usize,
     discoveries: BTreeMap<String, String>, // name+classification -> encoded path
     recent_path: Option<String>,
+    predicates: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+struct LiveUpdateView {
+    done: bool,
+    generated: usize,
+    pending: usize,
+    max_depth: usize,
+    elapsed_secs: u64,
+    new_discoveries: BTreeMap<String, String>, // name+classification -> encoded path
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+struct PropertyView {
+    name: String,
+    expectation: Expectation,
+    discovery: Option<String>, // encoded path
+    satisfied: Option<bool>, // unknown until discovered or done
 }
 
 #[derive(Debug, Eq, PartialEq)]
 struct StateView<State, Action> {
     action: Option<Action>,
     outcome: Option<String>,
+    diff: Option<String>,
     state: State,
     svg: Option<String>,
 }
@@ -41,6 +66,9 @@ where
         if let Some(ref outcome) = self.outcome {
             out.serialize_field("outcome", outcome)?;
         }
+        if let Some(ref diff) = self.diff {
+            out.serialize_field("diff", diff)?;
+        }
         out.serialize_field("state", &format!("{:#?}", self.state))?;
         if let Some(ref svg) = self.svg {
             out.serialize_field("svg", svg)?;
@@ -64,7 +92,7 @@ impl<M: Model> CheckerVisitor<M> for Arc<RwLock<Snapshot<M::Action>>> {
     }
 }
 
-pub(crate) fn serve<M>(checker_builder: CheckerBuilder<M>, addresses: impl ToSocketAddrs) -> Arc<impl Checker<M>>
+pub(crate) fn serve<M>(mut checker_builder: CheckerBuilder<M>, addresses: impl ToSocketAddrs) -> Arc<impl Checker<M>>
 where M: 'static + Model + Send + Sync,
       M::Action: Debug + Send + Sync,
       M::State: Debug + Hash + Send + Sync,
@@ -72,6 +100,7 @@ where M: 'static + Model + Send + Sync,
     let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
     let snapshot_for_visitor = Arc::clone(&snapshot);
     let snapshot_for_server = Arc::clone(&snapshot);
+    let predicates = std::mem::take(&mut checker_builder.named_predicates);
     spawn(move || {
         loop {
             sleep(Duration::from_secs(4));
@@ -81,14 +110,27 @@ where M: 'static + Model + Send + Sync,
     let checker = checker_builder
         .visitor(snapshot_for_visitor)
         .spawn_bfs();
-    serve_checker(checker, snapshot_for_server, addresses)
+    serve_explorer(checker, snapshot_for_server, predicates, addresses)
+}
+
+/// Serves a checker that was spawned without the explorer's visitor, so the status omits the
+/// recent path.
+pub(crate) fn serve_checker<M, C>(checker: C, addresses: impl ToSocketAddrs) -> Arc<C>
+where M: 'static + Model + Send + Sync,
+      M::Action: Debug + Send + Sync,
+      M::State: Debug + Hash + Send + Sync,
+      C: 'static + Checker<M> + Send + Sync,
+{
+    let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
+    serve_explorer(checker, snapshot, Vec::new(), addresses)
 }
 
-fn serve_checker<M, C>(
+fn serve_explorer<M, C>(
     checker: C,
     snapshot: Arc<RwLock<Snapshot<M::Action>>>,
+    predicates: Vec<NamedPredicate<M>>,
     addresses: impl ToSocketAddrs)
-    -> Arc<impl Checker<M>>
+    -> Arc<C>
 where M: 'static + Model + Send + Sync,
       M::Action: Debug + Send + Sync,
       M::State: Debug + Hash + Send + Sync,
@@ -96,7 +138,7 @@ where M: 'static + Model + Send + Sync,
 {
     let checker = Arc::new(checker);
 
-    let data = Arc::new((snapshot, Arc::clone(&checker)));
+    let data = Arc::new((snapshot, Arc::clone(&checker), predicates));
     HttpServer::new(move || {
         macro_rules! get_ui_file {
             ($filename:literal) => {
@@ -115,6 +157,11 @@ where M: 'static + Model + Send + Sync,
             .data(Arc::clone(&data))
             .route("/.status", web::get().to(status::<M, C>))
             .route("/.states{fingerprints:.*}", web::get().to(states::<M, C>))
+            .route("/.live", web::get().to(live::<M, C>))
+            .route("/.properties", web::get().to(properties::<M, C>))
+            .route("/.path{fingerprints:.*}", web::get().to(path::<M, C>))
+            .route("/.snippet{fingerprints:.*}", web::get().to(snippet::<M, C>))
+            .route("/.search{fingerprints:.*}", web::get().to(search::<M, C>))
             .route("/", get_ui_file!("index.htm"))
             .route("/app.css", get_ui_file!("app.css"))
             .route("/app.js", get_ui_file!("app.js"))
@@ -124,9 +171,13 @@ where M: 'static + Model + Send + Sync,
     checker
 }
 
-type Data<Action, Checker> = web::Data<Arc<(Arc<RwLock<Snapshot<Action>>>, Arc<Checker>)>>;
+type Data<M, Checker> = web::Data<Arc<(
+    Arc<RwLock<Snapshot<<M as Model>::Action>>>,
+    Arc<Checker>,
+    Vec<NamedPredicate<M>>,
+)>>;
 
-fn status<M, C>(_: HttpRequest, data: Data<M::Action, C>) -> Result<Json<StatusView>>
+fn status<M, C>(_: HttpRequest, data: Data<M, C>) -> Result<Json<StatusView>>
 where M: Model,
       M::Action: Debug,
       M::State: Hash,
@@ -147,32 +198,96 @@ where M: Model,
             })
             .collect(),
         recent_path: snapshot.read().1.as_ref().map(|p| format!("{:?}", p)),
+        predicates: data.2.iter().map(|(name, _)| name.to_string()).collect(),
     };
     Ok(Json(status))
 }
 
-fn states<M, C>(req: HttpRequest, data: Data<M::Action, C>)
+/// Streams a [`LiveUpdateView`] to a WebSocket each second until checking is done.
+struct LiveUpdates<M, C> {
+    checker: Arc<C>,
+    reported: HashSet<&'static str>,
+    _model: PhantomData<fn() -> M>,
+}
+
+impl<M, C> Actor for LiveUpdates<M, C>
+where M: 'static + Model,
+      M::State: Hash,
+      C: 'static + Checker<M>,
+{
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(Duration::from_secs(1), |live, ctx| {
+            let update = live_update(&*live.checker, &mut live.reported);
+            ctx.text(serde_json::to_string(&update).unwrap());
+            if update.done { ctx.close(None); }
+        });
+    }
+}
+
+impl<M, C> StreamHandler<ws::Message, ws::ProtocolError> for LiveUpdates<M, C>
+where M: 'static + Model,
+      M::State: Hash,
+      C: 'static + Checker<M>,
+{
+    fn handle(&mut self, msg: ws::Message, ctx: &mut Self::Context) {
+        match msg {
+            ws::Message::Ping(msg) => ctx.pong(&msg),
+            ws::Message::Close(_) => ctx.stop(),
+            _ => {}
+        }
+    }
+}
+
+fn live<M, C>(req: HttpRequest, stream: web::Payload, data: Data<M, C>) -> Result<HttpResponse>
+where M: 'static + Model,
+      M::State: Hash,
+      C: 'static + Checker<M>,
+{
+    let live_updates = LiveUpdates {
+        checker: Arc::clone(&data.1),
+        reported: HashSet::new(),
+        _model: PhantomData,
+    };
+    ws::start(live_updates, &req, stream)
+}
+
+/// Summarizes progress along with the discoveries that are not yet `reported`, which are then
+/// recorded as reported.
+fn live_update<M, C>(checker: &C, reported: &mut HashSet<&'static str>) -> LiveUpdateView
+where M: Model,
+      M::State: Hash,
+      C: Checker<M>,
+{
+    // Read the status first so that a status indicating that checking is done is never sent
+    // before the final discoveries.
+    let status = checker.status();
+    let new_discoveries = checker.discoveries().into_iter()
+        .filter(|(name, _)| reported.insert(*name))
+        .map(|(name, path)| {
+            let key = format!("\"{}\" {}", name, checker.discovery_classification(name));
+            (key, path.encode())
+        })
+        .collect();
+    LiveUpdateView {
+        done: status.is_done,
+        generated: status.generated_count,
+        pending: status.pending_count,
+        max_depth: status.max_depth,
+        elapsed_secs: status.elapsed.as_secs(),
+        new_discoveries,
+    }
+}
+
+fn states<M, C>(req: HttpRequest, data: Data<M, C>)
     -> Result<StateViewsJson<M::State, M::Action>>
 where M: Model,
       M::State: Debug + Hash,
       C: Checker<M>,
 {
     let model = &data.1.model();
-
-    // extract fingerprints
-    let mut fingerprints_str = req.match_info().get("fingerprints").expect("missing 'fingerprints' param").to_string();
-    if fingerprints_str.ends_with('/') {
-        let relevant_len = fingerprints_str.len() - 1;
-        fingerprints_str.truncate(relevant_len);
-    }
-    let fingerprints: VecDeque<_> = fingerprints_str.split('/').filter_map(|fp| fp.parse::<Fingerprint>().ok()).collect();
-
-    // ensure all but the first string (which is empty) were parsed
-    if fingerprints.len() + 1 != fingerprints_str.split('/').count() {
-        return Err(
-            actix_web::error::ErrorNotFound(
-                format!("Unable to parse fingerprints {}", fingerprints_str)));
-    }
+    let (fingerprints_str, fingerprints) = parse_fingerprints(&req)?;
 
     // now build up all the subsequent `StateView`s
     let mut results = Vec::new();
@@ -186,6 +301,7 @@ where M: Model,
             results.push(StateView {
                 action: None,
                 outcome: None,
+                diff: None,
                 state,
                 svg,
             });
@@ -203,12 +319,13 @@ where M: Model,
             let outcome = model.display_outcome(&last_state, action2);
             let state = model.next_state(&last_state, action3);
             if let Some(state) = state {
+                let diff = model.display_diff(&last_state, &state);
                 let svg = {
                     let mut fingerprints: VecDeque<_> = fingerprints.clone().into_iter().collect();
                     fingerprints.push_back(fingerprint(&state));
                     model.as_svg(Path::from_fingerprints::<M>(model, fingerprints))
                 };
-                results.push(StateView { action: Some(action), outcome, state, svg });
+                results.push(StateView { action: Some(action), outcome, diff, state, svg });
             }
         }
     } else {
@@ -220,6 +337,178 @@ where M: Model,
     Ok(Json(results))
 }
 
+/// Reports each checked property with its discovery (if any) and whether the property is
+/// satisfied, which is unknown until the property has a discovery or checking is done.
+fn properties<M, C>(_: HttpRequest, data: Data<M, C>) -> Result<Json<Vec<PropertyView>>>
+where M: Model,
+      M::State: Hash,
+      C: Checker<M>,
+{
+    let checker = &data.1;
+    let is_done = checker.is_done();
+    let mut discoveries = checker.discoveries();
+    let properties = checker.checked_properties().into_iter()
+        .map(|p| {
+            let discovery = discoveries.remove(p.name).map(|path| path.encode());
+            let satisfied = match (&p.expectation, discovery.is_some()) {
+                (Expectation::Sometimes, true) => Some(true),
+                (_, true) => Some(false),
+                (_, false) if !is_done => None,
+                (Expectation::Sometimes, false) => Some(false),
+                (_, false) => Some(true),
+            };
+            PropertyView { name: p.name.to_string(), expectation: p.expectation, discovery, satisfied }
+        })
+        .collect();
+    Ok(Json(properties))
+}
+
+/// Expands the path of fingerprints into its states, each with the action that led to it. Unlike
+/// [`states`], which lists the successors of the last state, this returns the path itself.
+fn path<M, C>(req: HttpRequest, data: Data<M, C>) -> Result<StateViewsJson<M::State, M::Action>>
+where M: Model,
+      M::State: Debug + Hash,
+      C: Checker<M>,
+{
+    let model = &data.1.model();
+    let (fingerprints_str, fingerprints) = parse_fingerprints(&req)?;
+    if fingerprints.is_empty() || Path::final_state::<M>(model, fingerprints.clone()).is_none() {
+        return Err(
+            actix_web::error::ErrorNotFound(
+                format!("Unable to find state following fingerprints {}", fingerprints_str)));
+    }
+
+    // Each step of a path pairs a state with the action taken from it, whereas each view pairs
+    // a state with the action that led to it.
+    let mut results: Vec<StateView<M::State, M::Action>> = Vec::new();
+    let mut prev_action = None;
+    let steps = Path::from_fingerprints::<M>(model, fingerprints.clone()).into_vec();
+    for (i, (state, action)) in steps.into_iter().enumerate() {
+        let diff = results.last().and_then(|prev| model.display_diff(&prev.state, &state));
+        let svg = model.as_svg(Path::from_fingerprints::<M>(
+            model, fingerprints.iter().take(i + 1).copied().collect()));
+        results.push(StateView { action: prev_action, outcome: None, diff, state, svg });
+        prev_action = action;
+    }
+    Ok(Json(results))
+}
+
+/// Renders the path of fingerprints as an `assert_discovery` snippet, which can be pasted into a
+/// test. The property is the first for which the path is a discovery, else a placeholder to
+/// replace.
+fn snippet<M, C>(req: HttpRequest, data: Data<M, C>) -> Result<String>
+where M: Model,
+      M::Action: Debug,
+      M::State: Hash,
+      C: Checker<M>,
+{
+    let model = &data.1.model();
+    let (fingerprints_str, fingerprints) = parse_fingerprints(&req)?;
+    if fingerprints.is_empty() || Path::final_state::<M>(model, fingerprints.clone()).is_none() {
+        return Err(
+            actix_web::error::ErrorNotFound(
+                format!("Unable to find state following fingerprints {}", fingerprints_str)));
+    }
+
+    let path = Path::from_fingerprints::<M>(model, fingerprints);
+    let name = model.properties().into_iter()
+        .find(|p| is_discovery(*model, p, &path))
+        .map_or("PROPERTY_NAME", |p| p.name);
+    let mut snippet = format!("checker.assert_discovery({:?}, vec![\n", name);
+    for action in path.into_actions() {
+        snippet.push_str(&format!("    {:?},\n", action));
+    }
+    snippet.push_str("]);\n");
+    Ok(snippet)
+}
+
+/// Searches breadth-first from the last state of the path of fingerprints (or from the initial
+/// states) for a state satisfying the named predicate, returning the fingerprints of the full
+/// path to that state.
+fn search<M, C>(req: HttpRequest, data: Data<M, C>) -> Result<String>
+where M: Model,
+      M::State: Hash,
+      C: Checker<M>,
+{
+    let model = data.1.model();
+    let (fingerprints_str, fingerprints) = parse_fingerprints(&req)?;
+    let query = web::Query::<BTreeMap<String, String>>::from_query(req.query_string())?;
+    let name = query.get("predicate").map(String::as_str).unwrap_or_default();
+    let predicate = match data.2.iter().find(|(n, _)| *n == name) {
+        Some((_, predicate)) => predicate,
+        None => {
+            return Err(
+                actix_web::error::ErrorNotFound(
+                    format!("Unknown predicate {:?}", name)));
+        }
+    };
+
+    let init_states = if fingerprints.is_empty() {
+        model.init_states()
+    } else if let Some(last_state) = Path::final_state::<M>(model, fingerprints.clone()) {
+        vec![last_state]
+    } else {
+        return Err(
+            actix_web::error::ErrorNotFound(
+                format!("Unable to find state following fingerprints {}", fingerprints_str)));
+    };
+
+    // Tracks the predecessor of each generated state for reconstructing the path.
+    let mut generated: HashMap<Fingerprint, Option<Fingerprint>> = HashMap::new();
+    let mut pending = VecDeque::new();
+    for state in init_states {
+        let fp = fingerprint(&state);
+        if generated.insert(fp, None).is_none() {
+            pending.push_back((fp, state));
+        }
+    }
+    while let Some((fp, state)) = pending.pop_front() {
+        if predicate(model, &state) {
+            let mut found = VecDeque::new();
+            let mut next_fp = Some(fp);
+            while let Some(fp) = next_fp {
+                found.push_front(fp);
+                next_fp = generated[&fp];
+            }
+            // The first state of the search is the last state of the specified path.
+            if !fingerprints.is_empty() { found.pop_front(); }
+            let path = fingerprints.iter().chain(&found)
+                .map(|fp| format!("/{}", fp))
+                .collect();
+            return Ok(path);
+        }
+        for next_state in model.next_states(&state) {
+            if !model.within_boundary(&next_state) { continue }
+            let next_fp = fingerprint(&next_state);
+            if let Entry::Vacant(entry) = generated.entry(next_fp) {
+                entry.insert(Some(fp));
+                pending.push_back((next_fp, next_state));
+            }
+        }
+    }
+    Err(
+        actix_web::error::ErrorNotFound(
+            format!("No reachable state satisfies {:?}", name)))
+}
+
+/// Extracts the fingerprints from a request path, also returning the corresponding string.
+fn parse_fingerprints(req: &HttpRequest) -> Result<(String, VecDeque<Fingerprint>)> {
+    let mut fingerprints_str = req.match_info().get("fingerprints").expect("missing 'fingerprints' param").to_string();
+    if fingerprints_str.ends_with('/') {
+        let relevant_len = fingerprints_str.len() - 1;
+        fingerprints_str.truncate(relevant_len);
+    }
+    let fingerprints: VecDeque<_> = fingerprints_str.split('/').filter_map(|fp| fp.parse::<Fingerprint>().ok()).collect();
+
+    // ensure all but the first string (which is empty) were parsed
+    if fingerprints.len() + 1 != fingerprints_str.split('/').count() {
+        return Err(
+            actix_web::error::ErrorNotFound(
+                format!("Unable to parse fingerprints {}", fingerprints_str)));
+    }
+    Ok((fingerprints_str, fingerprints))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -229,8 +518,8 @@ mod test {
     fn can_init() {
         let checker = Arc::new(BinaryClock.checker().spawn_bfs());
         assert_eq!(get_states(Arc::clone(&checker), "/").unwrap(), vec![
-            StateView { action: None, outcome: None, state: 0, svg: None },
-            StateView { action: None, outcome: None, state: 1, svg: None },
+            StateView { action: None, outcome: None, diff: None, state: 0, svg: None },
+            StateView { action: None, outcome: None, diff: None, state: 1, svg: None },
         ]);
     }
 
@@ -245,10 +534,11 @@ mod test {
         // let path_name = format!("/{}/{}", first, second);
         // println!("New path name is: {}", path_name);
         // ```
-        assert_eq!(get_states(Arc::clone(&checker), "/2716592049047647680/9080728272894440685").unwrap(), vec![
+        assert_eq!(get_states(Arc::clone(&checker), "/8649875134658024401/7306484125508665145").unwrap(), vec![
             StateView {
                 action: Some(BinaryClockAction::GoHigh),
                 outcome: Some("1".to_string()),
+                diff: Some("- 0\n+ 1\n".to_string()),
                 state: 1,
                 svg: None,
             },
@@ -264,12 +554,167 @@ mod test {
             "Unable to find state following fingerprints /1/2/3");
     }
 
+    #[test]
+    fn can_export_snippet() {
+        let checker = Arc::new(BinaryClock.checker().spawn_bfs());
+        assert_eq!(get_snippet(Arc::clone(&checker), "/8649875134658024401/7306484125508665145").unwrap(), "\
+            checker.assert_discovery(\"PROPERTY_NAME\", vec![\n    \
+                GoLow,\n\
+            ]);\n");
+        assert_eq!(get_snippet(Arc::clone(&checker), "/8649875134658024401").unwrap(), "\
+            checker.assert_discovery(\"PROPERTY_NAME\", vec![\n\
+            ]);\n");
+        assert_eq!(format!("{}", get_snippet(Arc::clone(&checker), "/").unwrap_err()),
+            "Unable to find state following fingerprints ");
+        assert_eq!(format!("{}", get_snippet(Arc::clone(&checker), "/1/2/3").unwrap_err()),
+            "Unable to find state following fingerprints /1/2/3");
+    }
+
+    #[test]
+    fn names_discovered_property_in_snippet() {
+        use crate::test_util::linear_equation_solver::*;
+
+        let checker = Arc::new(LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs());
+        let path_name = format!(
+            "/{}/{}/{}/{}",
+            fingerprint(&(0_u8, 0_u8)), fingerprint(&(1_u8, 0_u8)),
+            fingerprint(&(2_u8, 0_u8)), fingerprint(&(2_u8, 1_u8)));
+        assert_eq!(get_snippet(Arc::clone(&checker), Box::leak(path_name.into_boxed_str())).unwrap(), "\
+            checker.assert_discovery(\"solvable\", vec![\n    \
+                IncreaseX,\n    \
+                IncreaseX,\n    \
+                IncreaseY,\n\
+            ]);\n");
+    }
+
+    #[test]
+    fn can_search_for_named_predicate() {
+        use crate::test_util::linear_equation_solver::*;
+
+        let checker = Arc::new(LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs());
+        let predicates: Vec<NamedPredicate<LinearEquation>> = vec![
+            ("x is 2", |_, &(x, _)| x == 2),
+            ("y is 2", |_, &(_, y)| y == 2),
+            ("x exceeds 255", |_, &(x, _)| x as u16 > 255),
+        ];
+        let path = |states: &[(u8, u8)]| -> String {
+            states.iter().map(|s| format!("/{}", fingerprint(s))).collect()
+        };
+
+        // From the initial states.
+        assert_eq!(
+            get_search(Arc::clone(&checker), predicates.clone(), "/", "x is 2").unwrap(),
+            path(&[(0, 0), (1, 0), (2, 0)]));
+
+        // From a later state, including that state itself.
+        let from = Box::leak(path(&[(0, 0), (0, 1)]).into_boxed_str());
+        assert_eq!(
+            get_search(Arc::clone(&checker), predicates.clone(), from, "y is 2").unwrap(),
+            path(&[(0, 0), (0, 1), (0, 2)]));
+        let from = Box::leak(path(&[(0, 0), (1, 0), (2, 0)]).into_boxed_str());
+        assert_eq!(
+            get_search(Arc::clone(&checker), predicates.clone(), from, "x is 2").unwrap(),
+            path(&[(0, 0), (1, 0), (2, 0)]));
+
+        // Errors.
+        assert_eq!(
+            format!("{}", get_search(Arc::clone(&checker), predicates.clone(), "/", "x exceeds 255").unwrap_err()),
+            "No reachable state satisfies \"x exceeds 255\"");
+        assert_eq!(
+            format!("{}", get_search(Arc::clone(&checker), predicates.clone(), "/", "unknown").unwrap_err()),
+            "Unknown predicate \"unknown\"");
+        assert_eq!(
+            format!("{}", get_search(Arc::clone(&checker), predicates, "/1/2/3", "x is 2").unwrap_err()),
+            "Unable to find state following fingerprints /1/2/3");
+    }
+
+    #[test]
+    fn can_report_properties() {
+        use crate::test_util::linear_equation_solver::*;
+
+        let checker = Arc::new(BinaryClock.checker().spawn_bfs().join());
+        assert_eq!(get_properties(checker).unwrap(), vec![
+            PropertyView {
+                name: "in [0, 1]".to_string(),
+                expectation: Expectation::Always,
+                discovery: None,
+                satisfied: Some(true),
+            },
+        ]);
+
+        let checker = Arc::new(LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs().join());
+        let properties = get_properties(checker).unwrap();
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].satisfied, Some(true));
+        assert!(properties[0].discovery.is_some());
+
+        let checker = Arc::new(LinearEquation { a: 2, b: 4, c: 7 }.checker().spawn_bfs().join());
+        let properties = get_properties(checker).unwrap();
+        assert_eq!(properties[0].discovery, None);
+        assert_eq!(properties[0].satisfied, Some(false));
+    }
+
+    #[test]
+    fn can_expand_path() {
+        use crate::test_util::linear_equation_solver::*;
+
+        let checker = Arc::new(LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs());
+        let path_name = format!(
+            "/{}/{}/{}",
+            fingerprint(&(0_u8, 0_u8)), fingerprint(&(1_u8, 0_u8)), fingerprint(&(1_u8, 1_u8)));
+        assert_eq!(get_path(Arc::clone(&checker), Box::leak(path_name.into_boxed_str())).unwrap(), vec![
+            StateView { action: None, outcome: None, diff: None, state: (0, 0), svg: None },
+            StateView {
+                action: Some(Guess::IncreaseX),
+                outcome: None,
+                diff: Some("  (\n-     0,\n+     1,\n      0,\n  )\n".to_string()),
+                state: (1, 0),
+                svg: None,
+            },
+            StateView {
+                action: Some(Guess::IncreaseY),
+                outcome: None,
+                diff: Some("  (\n      1,\n-     0,\n+     1,\n  )\n".to_string()),
+                state: (1, 1),
+                svg: None,
+            },
+        ]);
+        assert_eq!(format!("{}", get_path(Arc::clone(&checker), "/").unwrap_err()),
+            "Unable to find state following fingerprints ");
+        assert_eq!(format!("{}", get_path(Arc::clone(&checker), "/1/2/3").unwrap_err()),
+            "Unable to find state following fingerprints /1/2/3");
+    }
+
+    #[test]
+    fn reports_each_discovery_once() {
+        use crate::test_util::linear_equation_solver::*;
+
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_bfs().join();
+        let mut reported = HashSet::new();
+        let update = live_update(&checker, &mut reported);
+        assert_eq!(update.done, true);
+        // Checking stops upon the discovery, leaving some states unchecked.
+        assert_eq!(update.pending, checker.status().pending_count);
+        assert_eq!(update.generated, checker.generated_count());
+        assert_eq!(
+            update.new_discoveries.keys().collect::<Vec<_>>(),
+            vec!["\"solvable\" example"]);
+        assert_eq!(
+            update.new_discoveries["\"solvable\" example"],
+            checker.discovery("solvable").unwrap().encode());
+
+        let update = live_update(&checker, &mut reported);
+        assert_eq!(update.new_discoveries, BTreeMap::new());
+    }
+
     #[test]
     fn smoke_test_states() {
         use crate::actor::{DuplicatingNetwork, Envelope, Id, LossyNetwork, System, SystemState};
         use crate::actor::actor_test_util::ping_pong::{PingPongCount, PingPongMsg::*, PingPongSystem};
         use crate::actor::SystemAction::*;
-        use crate::util::HashableHashSet;
+        use crate::actor::Network;
+        use crate::util::HashableHashMap;
+        use std::collections::BTreeSet;
         use std::iter::FromIterator;
 
         let checker = Arc::new(PingPongSystem {
@@ -284,15 +729,28 @@ mod test {
                 StateView {
                     action: None,
                     outcome: None,
+                    diff: None,
                     state: SystemState {
                         actor_states: vec![Arc::new(PingPongCount(0)), Arc::new(PingPongCount(0))],
                         history: (0, 1),
                         is_timer_set: vec![],
-                        network: HashableHashSet::from_iter(vec![
+                        timer_order: vec![],
+                        config_update_counts: vec![],
+                        send_order: vec![],
+                        overtaken_counts: vec![],
+                        spawned: vec![],
+                        crashed: vec![],
+                        monitors: BTreeSet::new(),
+                        delivery_counts: HashableHashMap::default(),
+                        pending_sends: Vec::new(),
+                        random_choices: Vec::new(),
+                        phase: None,
+                        projected_fingerprint: None,
+                        network: Network::from_iter(vec![
                             Envelope { src: Id::from(0), dst: Id::from(1), msg: Ping(0) },
                         ]),
                     },
-                    svg: Some("<svg version=\'1.1\' baseProfile=\'full\' width=\'500\' height=\'30\' viewbox=\'-20 -20 520 50\' xmlns=\'http://www.w3.org/2000/svg\'><defs><marker class=\'svg-event-shape\' id=\'arrow\' markerWidth=\'12\' markerHeight=\'10\' refX=\'12\' refY=\'5\' orient=\'auto\'><polygon points=\'0 0, 12 5, 0 10\' /></marker></defs><line x1=\'0\' y1=\'0\' x2=\'0\' y2=\'30\' class=\'svg-actor-timeline\' />\n<text x=\'0\' y=\'0\' class=\'svg-actor-label\'>0</text>\n<line x1=\'100\' y1=\'0\' x2=\'100\' y2=\'30\' class=\'svg-actor-timeline\' />\n<text x=\'100\' y=\'0\' class=\'svg-actor-label\'>1</text>\n</svg>\n".to_string()),
+                    svg: Some("<svg version=\'1.1\' baseProfile=\'full\' width=\'500\' height=\'60\' viewbox=\'-20 -20 520 80\' xmlns=\'http://www.w3.org/2000/svg\'><defs><marker class=\'svg-event-shape\' id=\'arrow\' markerWidth=\'12\' markerHeight=\'10\' refX=\'12\' refY=\'5\' orient=\'auto\'><polygon points=\'0 0, 12 5, 0 10\' /></marker></defs><line x1=\'0\' y1=\'0\' x2=\'0\' y2=\'30\' class=\'svg-actor-timeline\' />\n<text x=\'0\' y=\'0\' class=\'svg-actor-label\'>0</text>\n<line x1=\'100\' y1=\'0\' x2=\'100\' y2=\'30\' class=\'svg-actor-timeline\' />\n<text x=\'100\' y=\'0\' class=\'svg-actor-label\'>1</text>\n<a href=\'#/steps/12057438378449086413/15277130054699730385\' class=\'svg-fault-control\'><text x=\'100\' y=\'60\'>Drop Ping(0)</text></a>\n</svg>\n".to_string()),
                 },
             ]);
         // To regenerate the path if the fingerprint changes:
@@ -301,32 +759,69 @@ mod test {
         //     actor_states: vec![Arc::new(PingPongCount(0)), Arc::new(PingPongCount(0))],
         //     history: (0, 1),
         //     is_timer_set: vec![],
-        //     network: HashableHashSet::from_iter(vec![
+        //     config_update_counts: vec![],
+        //     send_order: vec![],
+        //     overtaken_counts: vec![],
+        //     spawned: vec![],
+        //     crashed: vec![],
+        //     monitors: BTreeSet::new(),
+        //     phase: None,
+        //     projected_fingerprint: None,
+        //     network: Network::from_iter(vec![
         //         Envelope { src: Id::from(0), dst: Id::from(1), msg: Ping(0) },
         //     ]),
         // });
         // println!("New path name is: /{}", fp);
         // ```
-        let states = get_states(Arc::clone(&checker), "/2298670378538534683").unwrap();
+        let states = get_states(Arc::clone(&checker), "/12057438378449086413").unwrap();
         assert_eq!(states.len(), 2);
         assert_eq!(
             states[0],
             StateView {
                 action: Some(Drop(Envelope { src: Id::from(0), dst: Id::from(1), msg: Ping(0) })),
                 outcome: Some("DROP: Envelope { src: Id(0), dst: Id(1), msg: Ping(0) }".to_string()),
+                diff: Some("  network:\n-     Envelope { src: Id(0), dst: Id(1), msg: Ping(0) }\n".to_string()),
                 state: SystemState {
                     actor_states: vec![Arc::new(PingPongCount(0)), Arc::new(PingPongCount(0))],
                     history: (0, 1),
                     is_timer_set: vec![],
-                    network: HashableHashSet::new(),
+                    timer_order: vec![],
+                    config_update_counts: vec![],
+                    send_order: vec![],
+                    overtaken_counts: vec![],
+                    spawned: vec![],
+                    crashed: vec![],
+                    monitors: BTreeSet::new(),
+                    delivery_counts: HashableHashMap::default(),
+                    pending_sends: Vec::new(),
+                    random_choices: Vec::new(),
+                    phase: None,
+                    projected_fingerprint: None,
+                    network: Network::new(),
                 },
-                svg: Some("<svg version='1.1' baseProfile='full' width='500' height='60' viewbox='-20 -20 520 80' xmlns='http://www.w3.org/2000/svg'><defs><marker class='svg-event-shape' id='arrow' markerWidth='12' markerHeight='10' refX='12' refY='5' orient='auto'><polygon points='0 0, 12 5, 0 10' /></marker></defs><line x1='0' y1='0' x2='0' y2='60' class='svg-actor-timeline' />\n<text x='0' y='0' class='svg-actor-label'>0</text>\n<line x1='100' y1='0' x2='100' y2='60' class='svg-actor-timeline' />\n<text x='100' y='0' class='svg-actor-label'>1</text>\n</svg>\n".to_string()),
+                svg: Some("<svg version='1.1' baseProfile='full' width='500' height='60' viewbox='-20 -20 520 80' xmlns='http://www.w3.org/2000/svg'><defs><marker class='svg-event-shape' id='arrow' markerWidth='12' markerHeight='10' refX='12' refY='5' orient='auto'><polygon points='0 0, 12 5, 0 10' /></marker></defs><line x1='0' y1='0' x2='0' y2='60' class='svg-actor-timeline' />\n<text x='0' y='0' class='svg-actor-label'>0</text>\n<line x1='100' y1='0' x2='100' y2='60' class='svg-actor-timeline' />\n<text x='100' y='0' class='svg-actor-label'>1</text>\n<line x1='0' x2='50' y1='0' y2='30' class='svg-event-shape svg-dropped-shape' />\n<path d='M 45 25 l 10 10 m 0 -10 l -10 10' class='svg-event-shape' />\n<text x='50' y='30' class='svg-event-label'>Dropped Ping(0)</text>\n</svg>\n".to_string()),
             });
         assert_eq!(
             states[1],
             StateView {
                 action: Some(Deliver { src: Id::from(0), dst: Id::from(1), msg: Ping(0) }),
                 outcome: Some("OUT: [Send(Id(0), Pong(0))]\n\nNEXT_STATE: PingPongCount(\n    1,\n)\n\nPREV_STATE: PingPongCount(\n    0,\n)\n".to_string()),
+                diff: Some(concat!(
+                    "  actor_states[1]:\n",
+                    "      PingPongCount(\n",
+                    "-         0,\n",
+                    "+         1,\n",
+                    "      )\n",
+                    "  history:\n",
+                    "      (\n",
+                    "-         0,\n",
+                    "          1,\n",
+                    "+         2,\n",
+                    "      )\n",
+                    "  network:\n",
+                    "-     Envelope { src: Id(0), dst: Id(1), msg: Ping(0) }\n",
+                    "+     Envelope { src: Id(1), dst: Id(0), msg: Pong(0) }\n",
+                ).to_string()),
                 state: SystemState {
                     actor_states: vec![
                         Arc::new(PingPongCount(0)),
@@ -334,11 +829,23 @@ mod test {
                     ],
                     history: (1, 2),
                     is_timer_set: vec![],
-                    network: HashableHashSet::from_iter(vec![
+                    timer_order: vec![],
+                    config_update_counts: vec![],
+                    send_order: vec![],
+                    overtaken_counts: vec![],
+                    spawned: vec![],
+                    crashed: vec![],
+                    monitors: BTreeSet::new(),
+                    delivery_counts: HashableHashMap::default(),
+                    pending_sends: Vec::new(),
+                    random_choices: Vec::new(),
+                    phase: None,
+                    projected_fingerprint: None,
+                    network: Network::from_iter(vec![
                         Envelope { src: Id::from(1), dst: Id::from(0), msg: Pong(0) },
                     ]),
                 },
-                svg: Some("<svg version='1.1' baseProfile='full' width='500' height='60' viewbox='-20 -20 520 80' xmlns='http://www.w3.org/2000/svg'><defs><marker class='svg-event-shape' id='arrow' markerWidth='12' markerHeight='10' refX='12' refY='5' orient='auto'><polygon points='0 0, 12 5, 0 10' /></marker></defs><line x1='0' y1='0' x2='0' y2='60' class='svg-actor-timeline' />\n<text x='0' y='0' class='svg-actor-label'>0</text>\n<line x1='100' y1='0' x2='100' y2='60' class='svg-actor-timeline' />\n<text x='100' y='0' class='svg-actor-label'>1</text>\n<line x1='0' x2='100' y1='0' y2='30' marker-end='url(#arrow)' class='svg-event-shape' />\n<text x='100' y='30' class='svg-event-label'>Ping(0)</text>\n</svg>\n".to_string()),
+                svg: Some("<svg version='1.1' baseProfile='full' width='500' height='90' viewbox='-20 -20 520 110' xmlns='http://www.w3.org/2000/svg'><defs><marker class='svg-event-shape' id='arrow' markerWidth='12' markerHeight='10' refX='12' refY='5' orient='auto'><polygon points='0 0, 12 5, 0 10' /></marker></defs><line x1='0' y1='0' x2='0' y2='60' class='svg-actor-timeline' />\n<text x='0' y='0' class='svg-actor-label'>0</text>\n<line x1='100' y1='0' x2='100' y2='60' class='svg-actor-timeline' />\n<text x='100' y='0' class='svg-actor-label'>1</text>\n<line x1='0' x2='100' y1='0' y2='30' marker-end='url(#arrow)' class='svg-event-shape' />\n<text x='100' y='30' class='svg-event-label'>Ping(0)</text>\n<a href='#/steps/12057438378449086413/3779279936160933357/9711978330645156486' class='svg-fault-control'><text x='0' y='90'>Drop Pong(0)</text></a>\n</svg>\n".to_string()),
             });
     }
 
@@ -379,13 +886,74 @@ mod test {
             .param("fingerprints", &path_name)
             .to_http_request();
         let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
-        let data = web::Data::new(Arc::new((snapshot, checker)));
+        let data = web::Data::new(Arc::new((snapshot, checker, Vec::new())));
         match states(req, data) {
             Ok(Json(view)) => Ok(view),
             Err(err) => Err(err),
         }
     }
 
+    fn get_path<M, C>(checker: Arc<C>, path_name: &'static str)
+                -> Result<Vec<StateView<M::State, M::Action>>>
+    where M: Model,
+          M::State: Debug + Hash,
+          C: Checker<M>,
+    {
+        let req = actix_web::test::TestRequest::get()
+            .param("fingerprints", path_name)
+            .to_http_request();
+        let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
+        let data = web::Data::new(Arc::new((snapshot, checker, Vec::new())));
+        match path(req, data) {
+            Ok(Json(view)) => Ok(view),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn get_properties<M, C>(checker: Arc<C>) -> Result<Vec<PropertyView>>
+    where M: Model,
+          M::State: Hash,
+          C: Checker<M>,
+    {
+        let req = actix_web::test::TestRequest::get().to_http_request();
+        let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
+        let data = web::Data::new(Arc::new((snapshot, checker, Vec::new())));
+        match properties(req, data) {
+            Ok(Json(view)) => Ok(view),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn get_snippet<M, C>(checker: Arc<C>, path_name: &'static str) -> Result<String>
+    where M: Model,
+          M::Action: Debug,
+          M::State: Hash,
+          C: Checker<M>,
+    {
+        let req = actix_web::test::TestRequest::get()
+            .param("fingerprints", path_name)
+            .to_http_request();
+        let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
+        let data = web::Data::new(Arc::new((snapshot, checker, Vec::new())));
+        snippet(req, data)
+    }
+
+    fn get_search<M, C>(
+        checker: Arc<C>, predicates: Vec<NamedPredicate<M>>, path_name: &'static str, predicate: &str)
+        -> Result<String>
+    where M: Model,
+          M::State: Hash,
+          C: Checker<M>,
+    {
+        let req = actix_web::test::TestRequest::with_uri(
+                &format!("/.search{}?predicate={}", path_name, predicate.replace(' ', "%20")))
+            .param("fingerprints", path_name)
+            .to_http_request();
+        let snapshot = Arc::new(RwLock::new(Snapshot(true, None)));
+        let data = web::Data::new(Arc::new((snapshot, checker, predicates)));
+        search(req, data)
+    }
+
     fn get_status<M, C>(checker: Arc<C>, snapshot: Arc<RwLock<Snapshot<M::Action>>>)
     -> Result<StatusView>
     where M: Model,
@@ -394,7 +962,7 @@ mod test {
           C: Checker<M>,
     {
         let req = actix_web::test::TestRequest::get().to_http_request();
-        let data = web::Data::new(Arc::new((snapshot, checker)));
+        let data = web::Data::new(Arc::new((snapshot, checker, Vec::new())));
         match status(req, data) {
             Ok(Json(view)) => Ok(view),
             Err(err) => Err(err),
@@ -0,0 +1,164 @@
+//! Private module for selective re-export.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// States awaiting evaluation. Without a [`CheckerBuilder::frontier_priority`], states are
+/// evaluated in traversal order: first in, first out for breadth-first search, and last in,
+/// first out for depth-first search. Otherwise states are evaluated in order of decreasing
+/// priority, with ties in traversal order, and each priority is stored with its state so that
+/// it is only computed once.
+///
+/// [`CheckerBuilder::frontier_priority`]: crate::CheckerBuilder::frontier_priority
+pub(crate) enum Frontier<T> {
+    FirstInFirstOut(VecDeque<T>),
+    LastInFirstOut(Vec<T>),
+    Prioritized { entries: BinaryHeap<Entry<T>>, is_fifo: bool, next_seq: u64 },
+}
+
+/// A prioritized item. Entries are ordered by priority and then by `rank`, which is derived from
+/// insertion order.
+pub(crate) struct Entry<T> { priority: i64, rank: u64, item: T }
+
+impl<T> Frontier<T> {
+    /// An empty frontier for breadth-first search.
+    pub(crate) fn breadth_first(is_prioritized: bool) -> Self {
+        if is_prioritized {
+            Frontier::Prioritized { entries: BinaryHeap::new(), is_fifo: true, next_seq: 0 }
+        } else {
+            Frontier::FirstInFirstOut(VecDeque::new())
+        }
+    }
+
+    /// An empty frontier for depth-first search.
+    pub(crate) fn depth_first(is_prioritized: bool) -> Self {
+        if is_prioritized {
+            Frontier::Prioritized { entries: BinaryHeap::new(), is_fifo: false, next_seq: 0 }
+        } else {
+            Frontier::LastInFirstOut(Vec::new())
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Frontier::FirstInFirstOut(items) => items.len(),
+            Frontier::LastInFirstOut(items) => items.len(),
+            Frontier::Prioritized { entries, .. } => entries.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Adds an item. The `priority` is ignored unless the frontier is prioritized.
+    pub(crate) fn push(&mut self, item: T, priority: Option<i64>) {
+        match self {
+            Frontier::FirstInFirstOut(items) => items.push_back(item),
+            Frontier::LastInFirstOut(items) => items.push(item),
+            Frontier::Prioritized { entries, is_fifo, next_seq } => {
+                // The max-heap pops the highest rank first, so earlier items rank higher when
+                // ties are first in, first out.
+                let rank = if *is_fifo { u64::MAX - *next_seq } else { *next_seq };
+                *next_seq += 1;
+                entries.push(Entry { priority: priority.unwrap_or_default(), rank, item });
+            }
+        }
+    }
+
+    /// Removes the next item to evaluate.
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        match self {
+            Frontier::FirstInFirstOut(items) => items.pop_front(),
+            Frontier::LastInFirstOut(items) => items.pop(),
+            Frontier::Prioritized { entries, .. } => entries.pop().map(|entry| entry.item),
+        }
+    }
+
+    /// Removes `count` items, such as to share them with another thread, returning them as a
+    /// frontier with the same ordering.
+    pub(crate) fn split_off(&mut self, count: usize) -> Self {
+        let at = self.len() - count;
+        match self {
+            Frontier::FirstInFirstOut(items) => Frontier::FirstInFirstOut(items.split_off(at)),
+            Frontier::LastInFirstOut(items) => Frontier::LastInFirstOut(items.split_off(at)),
+            Frontier::Prioritized { entries, is_fifo, next_seq } => {
+                let mut kept = std::mem::take(entries).into_vec();
+                let removed = kept.split_off(at);
+                *entries = BinaryHeap::from(kept);
+                Frontier::Prioritized {
+                    entries: BinaryHeap::from(removed),
+                    is_fifo: *is_fifo,
+                    next_seq: *next_seq,
+                }
+            }
+        }
+    }
+
+    /// Iterates over the items in an arbitrary order.
+    pub(crate) fn iter(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self {
+            Frontier::FirstInFirstOut(items) => Box::new(items.iter()),
+            Frontier::LastInFirstOut(items) => Box::new(items.iter()),
+            Frontier::Prioritized { entries, .. } => Box::new(entries.iter().map(|entry| &entry.item)),
+        }
+    }
+}
+
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.priority, self.rank).cmp(&(other.priority, other.rank))
+    }
+}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl<T> Eq for Entry<T> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn drain<T>(mut frontier: Frontier<T>) -> Vec<T> {
+        std::iter::from_fn(|| frontier.pop()).collect()
+    }
+
+    #[test]
+    fn orders_by_traversal_without_priorities() {
+        let mut frontier = Frontier::breadth_first(false);
+        for i in 0..4 { frontier.push(i, None); }
+        assert_eq!(drain(frontier), vec![0, 1, 2, 3]);
+
+        let mut frontier = Frontier::depth_first(false);
+        for i in 0..4 { frontier.push(i, None); }
+        assert_eq!(drain(frontier), vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn orders_by_priority_then_traversal() {
+        let mut frontier = Frontier::breadth_first(true);
+        for (i, priority) in [0, 1, 1, 0].iter().enumerate() { frontier.push(i, Some(*priority)); }
+        assert_eq!(drain(frontier), vec![1, 2, 0, 3]);
+
+        let mut frontier = Frontier::depth_first(true);
+        for (i, priority) in [0, 1, 1, 0].iter().enumerate() { frontier.push(i, Some(*priority)); }
+        assert_eq!(drain(frontier), vec![2, 1, 3, 0]);
+    }
+
+    #[test]
+    fn can_split_off_items() {
+        let mut frontier = Frontier::breadth_first(true);
+        for i in 0..10 { frontier.push(i, Some(i)); }
+        let removed = frontier.split_off(4);
+        assert_eq!(frontier.len(), 6);
+        assert_eq!(removed.len(), 4);
+        let mut all = drain(frontier);
+        all.extend(drain(removed));
+        all.sort_unstable();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+    }
+}
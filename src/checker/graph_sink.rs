@@ -0,0 +1,155 @@
+//! Private module for selective re-export.
+
+use crate::Model;
+use parking_lot::Mutex;
+use std::fmt::Debug;
+use std::io::Write;
+
+/// Receives the state graph as the checker explores it, so the graph can be analyzed with
+/// external tools (such as a graph database query for "all states where two leaders coexist")
+/// without rebuilding it afterwards. See [`CheckerBuilder::graph_sink`].
+///
+/// States are identified by fingerprint. Each evaluated state is reported once, and each
+/// transition to a state within [`Model::within_boundary`] is reported once per evaluated source
+/// state, including transitions to states that were already generated. Calls arrive from every
+/// checker thread, so implementations must synchronize internally.
+///
+/// See [`SqlGraphSink`] for an implementation.
+///
+/// [`CheckerBuilder::graph_sink`]: crate::CheckerBuilder::graph_sink
+pub trait StateGraphSink<M: Model> {
+    /// Called for each evaluated state, including initial states.
+    fn on_state(&self, _model: &M, _fingerprint: u64, _state: &M::State) {}
+
+    /// Called for each transition from an evaluated state.
+    fn on_edge(&self, model: &M, src: u64, action: &M::Action, dst: u64);
+}
+
+/// A [`StateGraphSink`] that writes SQL statements to populate `states` and `edges` tables, for
+/// instance to be piped into `sqlite3`. States and actions are recorded via their [`Debug`]
+/// representations, and fingerprints are recorded as text because SQL integers are signed.
+///
+/// # Example
+///
+/// Run the following via `cargo run | sqlite3 graph.db`, then query the database.
+///
+/// ```
+/// use stateright::{Checker, Model, SqlGraphSink};
+/// # let model = ();
+///
+/// model.checker()
+///     .graph_sink(SqlGraphSink::new(std::io::stdout()))
+///     .spawn_bfs().join();
+/// ```
+pub struct SqlGraphSink<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> SqlGraphSink<W> {
+    /// Writes statements that create the tables (unless they exist), after which the sink writes
+    /// one statement per state and per transition.
+    pub fn new(mut writer: W) -> Self {
+        let _ = writeln!(writer,
+            "CREATE TABLE IF NOT EXISTS states (fingerprint TEXT PRIMARY KEY, state TEXT);");
+        let _ = writeln!(writer,
+            "CREATE TABLE IF NOT EXISTS edges (src TEXT, action TEXT, dst TEXT);");
+        SqlGraphSink { writer: Mutex::new(writer) }
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+}
+
+impl<M, W> StateGraphSink<M> for SqlGraphSink<W>
+where M: Model,
+      M::State: Debug,
+      M::Action: Debug,
+      W: Write,
+{
+    fn on_state(&self, _: &M, fingerprint: u64, state: &M::State) {
+        let _ = writeln!(self.writer.lock(),
+            "INSERT OR IGNORE INTO states VALUES ('{}', {});",
+            fingerprint, sql_string(&format!("{:?}", state)));
+    }
+
+    fn on_edge(&self, _: &M, src: u64, action: &M::Action, dst: u64) {
+        let _ = writeln!(self.writer.lock(),
+            "INSERT INTO edges VALUES ('{}', {}, '{}');",
+            src, sql_string(&format!("{:?}", action)), dst);
+    }
+}
+
+/// Quotes text as a SQL string literal.
+fn sql_string(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Reports transitions to a [`StateGraphSink`] on behalf of a checker's threads.
+pub(crate) struct GraphExporter<M: Model> {
+    pub(crate) sink: Box<dyn StateGraphSink<M> + Send + Sync>,
+    /// Retains a copy of each action, which computing the next state consumes.
+    pub(crate) clone_action: fn(&M::Action) -> M::Action,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::test_util::dgraph::DGraph;
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct EdgeRecorder(Arc<Mutex<(BTreeSet<u8>, BTreeSet<(u64, u8, u64)>)>>);
+
+    impl StateGraphSink<DGraph> for EdgeRecorder {
+        fn on_state(&self, _: &DGraph, _: u64, state: &u8) {
+            self.0.lock().unwrap().0.insert(*state);
+        }
+        fn on_edge(&self, _: &DGraph, src: u64, action: &u8, dst: u64) {
+            self.0.lock().unwrap().1.insert((src, *action, dst));
+        }
+    }
+
+    #[test]
+    fn reports_state_graph() {
+        let model = || DGraph::with_property(Property::always("true", |_, _| true))
+            .with_path(vec![1, 2, 3])
+            .with_path(vec![1, 3, 1]);
+        let fp = |state: u8| fingerprint(&state).get();
+        let assert_graph = |recorder: EdgeRecorder| {
+            let (states, edges) = recorder.0.lock().unwrap().clone();
+            assert_eq!(states, vec![1, 2, 3].into_iter().collect());
+            assert_eq!(edges, vec![
+                (fp(1), 2, fp(2)),
+                (fp(1), 3, fp(3)),
+                (fp(2), 3, fp(3)),
+                (fp(3), 1, fp(1)),
+            ].into_iter().collect());
+        };
+
+        let recorder = EdgeRecorder::default();
+        model().checker().graph_sink(recorder.clone()).spawn_bfs().join();
+        assert_graph(recorder);
+        let recorder = EdgeRecorder::default();
+        model().checker().graph_sink(recorder.clone()).spawn_dfs().join();
+        assert_graph(recorder);
+    }
+
+    #[test]
+    fn writes_sql() {
+        let model = DGraph::with_property(Property::always("true", |_, _| true))
+            .with_path(vec![1, 2]);
+        let sink = SqlGraphSink::new(Vec::new());
+        StateGraphSink::on_state(&sink, &model, 7, &1);
+        StateGraphSink::on_edge(&sink, &model, 7, &2, 8);
+        assert_eq!(String::from_utf8(sink.into_inner()).unwrap(), "\
+CREATE TABLE IF NOT EXISTS states (fingerprint TEXT PRIMARY KEY, state TEXT);
+CREATE TABLE IF NOT EXISTS edges (src TEXT, action TEXT, dst TEXT);
+INSERT OR IGNORE INTO states VALUES ('7', '1');
+INSERT INTO edges VALUES ('7', '2', '8');
+");
+        assert_eq!(super::sql_string("it's"), "'it''s'");
+    }
+}
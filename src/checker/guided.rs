@@ -0,0 +1,307 @@
+//! Private module for selective re-export.
+
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, initial_ebits, Path};
+use crate::checker::{ExplorationLimits, record_boundary_pruned, with_violating_step};
+use crate::checker::status::{CheckerStatus, Progress};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{self, AtomicBool};
+
+// Explores states in the order of A* search: by the number of steps taken to reach a state plus
+// a user-supplied estimate of the remaining steps to a discovery. Every reachable state is still
+// checked eventually, so the heuristic only affects how quickly discoveries are found. If the
+// heuristic never overestimates, then the path to each discovery is also a shortest one, as a
+// state reached via a shorter path after being generated is enqueued again.
+
+pub(crate) struct GuidedChecker<M: Model> {
+    model: Arc<M>,
+    properties: Arc<Vec<Property<M>>>,
+    target_discovery_count: usize,
+    handles: Vec<std::thread::JoinHandle<()>>,
+    is_exhausted: Arc<AtomicBool>,
+    generated: Arc<DashMap<Fingerprint, (Option<Fingerprint>, usize)>>,
+    discoveries: Arc<DashMap<&'static str, Fingerprint>>,
+    boundary_pruned: Arc<Mutex<EventuallyBits>>,
+    progress: Arc<Progress>,
+}
+
+/// Estimates the number of steps from a state to a discovery. See
+/// [`CheckerBuilder::spawn_guided`].
+pub(crate) type Heuristic<M> = Box<dyn Fn(&M, &<M as Model>::State) -> u64 + Send + Sync>;
+
+/// A pending state, ordered so that a [`BinaryHeap`] pops the lowest estimated total cost first,
+/// with ties following the order in which states were generated.
+struct Job<State> {
+    estimate: u64,
+    sequence: u64,
+    state: State,
+    fingerprint: Fingerprint,
+    depth: usize,
+    ebits: EventuallyBits,
+}
+
+impl<State> Ord for Job<State> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Reverse((self.estimate, self.sequence)).cmp(&Reverse((other.estimate, other.sequence)))
+    }
+}
+
+impl<State> PartialOrd for Job<State> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl<State> PartialEq for Job<State> {
+    fn eq(&self, other: &Self) -> bool { self.cmp(other) == Ordering::Equal }
+}
+
+impl<State> Eq for Job<State> {}
+
+impl<M> GuidedChecker<M>
+where M: Model + Send + Sync + 'static,
+      M::State: Hash + Send + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>, heuristic: Heuristic<M>) -> Self {
+        let properties = Arc::new(options.checked_properties());
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        let model = Arc::new(options.model);
+        let target_generated_count = options.target_generated_count;
+        let visitor = options.visitor;
+        let limits = options.limits;
+
+        let is_exhausted = Arc::new(AtomicBool::new(false));
+        let generated = Arc::new(DashMap::default());
+        let discoveries = Arc::new(DashMap::default());
+        let boundary_pruned = Arc::new(Mutex::new(EventuallyBits::new()));
+        let progress = Arc::new(Progress::new(0));
+
+        let handle = {
+            let model = Arc::clone(&model);
+            let properties = Arc::clone(&properties);
+            let is_exhausted = Arc::clone(&is_exhausted);
+            let generated = Arc::clone(&generated);
+            let discoveries = Arc::clone(&discoveries);
+            let boundary_pruned = Arc::clone(&boundary_pruned);
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || {
+                let is_complete = Self::check(
+                    &model, &properties, target_discovery_count, &generated, &discoveries,
+                    &boundary_pruned, &progress, &visitor, target_generated_count, &heuristic, limits);
+                log::debug!("Shutting down... gen={}", generated.len());
+                is_exhausted.store(is_complete, atomic::Ordering::Release);
+                progress.record_finish();
+            })
+        };
+        GuidedChecker {
+            model,
+            properties,
+            target_discovery_count,
+            handles: vec![handle],
+            is_exhausted,
+            generated,
+            discoveries,
+            boundary_pruned,
+            progress,
+        }
+    }
+
+    /// Checks every reachable state, returning whether all were checked (versus stopping early).
+    #[allow(clippy::too_many_arguments)]
+    fn check(
+        model: &M,
+        properties: &[Property<M>],
+        target_discovery_count: usize,
+        generated: &DashMap<Fingerprint, (Option<Fingerprint>, usize)>,
+        discoveries: &DashMap<&'static str, Fingerprint>,
+        boundary_pruned: &Mutex<EventuallyBits>,
+        progress: &Progress,
+        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+        target_generated_count: Option<NonZeroUsize>,
+        heuristic: &Heuristic<M>,
+        limits: ExplorationLimits)
+        -> bool
+    {
+        let ebits = initial_ebits(properties);
+
+        let mut pending = BinaryHeap::new();
+        let mut sequence = 0;
+        for s in model.init_states() {
+            let fp = fingerprint(&s);
+            if generated.insert(fp, (None, 0)).is_none() {
+                pending.push(Job {
+                    estimate: heuristic(model, &s),
+                    sequence,
+                    state: s,
+                    fingerprint: fp,
+                    depth: 0,
+                    ebits: ebits.clone(),
+                });
+                sequence += 1;
+            }
+        }
+
+        let mut actions = Vec::new();
+        while let Some(job) = pending.pop() {
+            progress.set_pending(pending.len());
+            let Job { state, fingerprint: state_fp, depth, mut ebits, .. } = job;
+
+            // Skip if reached via a shorter path since enqueued.
+            if generated.get(&state_fp).map(|entry| entry.1) != Some(depth) { continue }
+
+            progress.record_depth(depth);
+            if let Some(visitor) = visitor {
+                visitor.visit(model, reconstruct_path(model, generated, state_fp));
+            }
+            let is_awaiting_discoveries = check_properties(
+                model, properties, &state, &mut ebits,
+                |p| discoveries.contains_key(p.name),
+                |name| { discoveries.insert(name, state_fp); });
+            if discoveries.len() >= target_discovery_count { return false }
+            if !is_awaiting_discoveries { continue }
+
+            // Successors that were already reached via as short a path are skipped.
+            let mut is_terminal = limits.actions(
+                model, &state, depth, &mut actions, boundary_pruned, &ebits);
+            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
+            for next_state in next_states {
+                if !model.within_boundary(&next_state) {
+                    record_boundary_pruned(boundary_pruned, &ebits);
+                    continue
+                }
+                is_terminal = false;
+                let next_depth = depth + 1;
+                let next_fingerprint = fingerprint(&next_state);
+                let is_improved = match generated.get(&next_fingerprint) {
+                    None => true,
+                    Some(entry) => next_depth < entry.1,
+                };
+                if !is_improved { continue }
+                generated.insert(next_fingerprint, (Some(state_fp), next_depth));
+                pending.push(Job {
+                    estimate: next_depth as u64 + heuristic(model, &next_state),
+                    sequence,
+                    state: next_state,
+                    fingerprint: next_fingerprint,
+                    depth: next_depth,
+                    ebits: ebits.clone(),
+                });
+                sequence += 1;
+            }
+            if is_terminal {
+                check_terminal_state(
+                    properties, &ebits,
+                    |p| discoveries.contains_key(p.name),
+                    |name| { discoveries.insert(name, state_fp); });
+            }
+
+            if let Some(target_generated_count) = target_generated_count {
+                if target_generated_count.get() <= generated.len() { return false }
+            }
+        }
+        true
+    }
+}
+
+impl<M> Checker<M> for GuidedChecker<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn model(&self) -> &M { &self.model }
+
+    fn checked_properties(&self) -> Vec<Property<M>> { self.properties.to_vec() }
+
+    fn generated_count(&self) -> usize { self.generated.len() }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.discoveries.iter()
+            .map(|mapref| {
+                let name = <&'static str>::clone(mapref.key());
+                let path = reconstruct_path(self.model(), &self.generated, *mapref.value());
+                (name, with_violating_step(self.model(), name, path))
+            })
+            .collect()
+    }
+
+    fn join(mut self) -> Self {
+        for h in self.handles.drain(0..) {
+            h.join().unwrap();
+        }
+        self
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_exhausted.load(atomic::Ordering::Acquire)
+            || self.discoveries.len() >= self.target_discovery_count
+    }
+
+    fn status(&self) -> CheckerStatus {
+        let generated_bytes = self.generated.len()
+            * std::mem::size_of::<(Fingerprint, (Option<Fingerprint>, usize))>();
+        self.progress.status::<M::State>(self.generated_count(), generated_bytes, self.is_done())
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(&self.properties, &self.boundary_pruned, &self.discoveries)
+    }
+}
+
+fn reconstruct_path<M>(
+    model: &M,
+    generated: &DashMap<Fingerprint, (Option<Fingerprint>, usize)>,
+    fp: Fingerprint)
+    -> Path<M::State, M::Action>
+    where M: Model,
+          M::State: Hash,
+{
+    let mut fingerprints = VecDeque::new();
+    let mut next_fp = Some(fp);
+    while let Some(fp) = next_fp {
+        fingerprints.push_front(fp);
+        next_fp = generated.get(&fp).and_then(|entry| entry.0);
+    }
+    Path::from_fingerprints(model, fingerprints)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn expands_states_that_minimize_the_heuristic() {
+        // Solutions require `x == 200`, and the heuristic is exact, so only states with `y == 0`
+        // are evaluated, whereas breadth-first search would visit over 20,000 states.
+        let checker = LinearEquation { a: 1, b: 0, c: 200 }.checker()
+            .spawn_guided(|_, &(x, _)| 200_u64.saturating_sub(x as u64))
+            .join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX; 200]);
+        assert_eq!(checker.generated_count(), 2 * 200 + 1);
+    }
+
+    #[test]
+    fn finds_shortest_path_if_heuristic_is_admissible() {
+        // The solution `(2, 1)` is closer than `(7, 0)`.
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .spawn_guided(|_, _| 0)
+            .join();
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![Guess::IncreaseX, Guess::IncreaseX, Guess::IncreaseY]);
+
+        // Misleading heuristics do not prevent checking the full state space.
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .spawn_guided(|_, &(x, y)| (x as u64) * (y as u64))
+            .join();
+        assert!(checker.is_done());
+        checker.assert_no_discovery("solvable");
+        assert_eq!(checker.generated_count(), 256 * 256);
+    }
+}
@@ -0,0 +1,294 @@
+//! Private module for selective re-export.
+
+use crate::{CheckerBuilder, CheckerVisitor, Fingerprint, fingerprint, Model, Property};
+use crate::checker::{boundary_limited_properties, check_properties, check_terminal_state, Checker};
+use crate::checker::{EventuallyBits, Expectation, initial_ebits, Path};
+use crate::checker::{ExplorationLimits, record_boundary_pruned, with_violating_step};
+use crate::checker::status::{CheckerStatus, Progress};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+// Unlike the BFS and DFS checkers, this checker does not track previously generated states.
+// Instead it repeats a depth-limited DFS with an increasing limit, only retaining the current
+// path (and pending siblings), so memory is proportional to depth rather than the number of
+// reachable states. Cycles are detected by checking the current path.
+
+pub(crate) struct IddfsChecker<M: Model> {
+    model: Arc<M>,
+    properties: Arc<Vec<Property<M>>>,
+    target_discovery_count: usize,
+    handles: Vec<std::thread::JoinHandle<()>>,
+    is_exhausted: Arc<AtomicBool>,
+    generated: Arc<AtomicUsize>,
+    discoveries: Arc<DashMap<&'static str, Vec<Fingerprint>>>,
+    boundary_pruned: Arc<Mutex<EventuallyBits>>,
+    progress: Arc<Progress>,
+}
+type Job<State> = Vec<(State, Fingerprint, usize, EventuallyBits)>;
+
+impl<M> IddfsChecker<M>
+where M: Model + Send + Sync + 'static,
+      M::State: Hash + Send + 'static,
+{
+    pub(crate) fn spawn(options: CheckerBuilder<M>) -> Self {
+        let properties = Arc::new(options.checked_properties());
+        let target_discovery_count = options.target_discovery_count(properties.len());
+        let model = Arc::new(options.model);
+        let target_generated_count = options.target_generated_count;
+        let visitor = options.visitor;
+        let limits = options.limits;
+
+        let is_exhausted = Arc::new(AtomicBool::new(false));
+        let generated = Arc::new(AtomicUsize::new(0));
+        let discoveries = Arc::new(DashMap::default());
+        let boundary_pruned = Arc::new(Mutex::new(EventuallyBits::new()));
+        let progress = Arc::new(Progress::new(0));
+
+        let handle = {
+            let model = Arc::clone(&model);
+            let properties = Arc::clone(&properties);
+            let is_exhausted = Arc::clone(&is_exhausted);
+            let generated = Arc::clone(&generated);
+            let discoveries = Arc::clone(&discoveries);
+            let boundary_pruned = Arc::clone(&boundary_pruned);
+            let progress = Arc::clone(&progress);
+            std::thread::spawn(move || {
+                for max_depth in 0.. {
+                    log::debug!("Checking to depth {}. gen={}", max_depth, generated.load(Ordering::Relaxed));
+                    let is_cut_off = Self::check_to_depth(
+                        &model, &properties, target_discovery_count, &generated, &discoveries,
+                        &boundary_pruned, &progress, &visitor, target_generated_count, max_depth,
+                        limits);
+                    if !is_cut_off {
+                        log::debug!("No more work. Shutting down... gen={}", generated.load(Ordering::Relaxed));
+                        is_exhausted.store(true, Ordering::Release);
+                        progress.record_finish();
+                        return
+                    }
+                    if discoveries.len() >= target_discovery_count {
+                        log::debug!("Discovery complete. Shutting down... gen={}", generated.load(Ordering::Relaxed));
+                        progress.record_finish();
+                        return
+                    }
+                    if let Some(target_generated_count) = target_generated_count {
+                        if target_generated_count.get() <= generated.load(Ordering::Relaxed) {
+                            log::debug!("Reached target generated count. Shutting down... gen={}", generated.load(Ordering::Relaxed));
+                            progress.record_finish();
+                            return
+                        }
+                    }
+                }
+            })
+        };
+        IddfsChecker {
+            model,
+            properties,
+            target_discovery_count,
+            handles: vec![handle],
+            is_exhausted,
+            generated,
+            discoveries,
+            boundary_pruned,
+            progress,
+        }
+    }
+
+    /// Checks states at exactly `max_depth`, returning whether any states exist beyond that
+    /// depth (i.e. whether the search was cut off). Shallower states were checked by earlier
+    /// iterations, so they are only traversed.
+    #[allow(clippy::too_many_arguments)]
+    fn check_to_depth(
+        model: &M,
+        properties: &[Property<M>],
+        target_discovery_count: usize,
+        generated: &AtomicUsize,
+        discoveries: &DashMap<&'static str, Vec<Fingerprint>>,
+        boundary_pruned: &Mutex<EventuallyBits>,
+        progress: &Progress,
+        visitor: &Option<Box<dyn CheckerVisitor<M> + Send + Sync>>,
+        target_generated_count: Option<NonZeroUsize>,
+        max_depth: usize,
+        limits: ExplorationLimits)
+        -> bool
+    {
+        let ebits = initial_ebits(properties);
+        let mut pending: Job<M::State> = model.init_states().into_iter()
+            .map(|s| {
+                let fp = fingerprint(&s);
+                (s, fp, 0, ebits.clone())
+            })
+            .collect();
+
+        let mut is_cut_off = false;
+        let mut path = Vec::new();
+        let mut actions = Vec::new();
+        while let Some((state, state_fp, depth, mut ebits)) = pending.pop() {
+            progress.set_pending(pending.len());
+            path.truncate(depth);
+            path.push(state_fp);
+
+            // Eventually properties are tracked along the entire path, whereas other properties
+            // are only checked at the frontier, as earlier iterations checked shallower states.
+            let is_frontier = depth == max_depth;
+            if is_frontier {
+                generated.fetch_add(1, Ordering::Relaxed);
+                progress.record_depth(depth);
+                if let Some(visitor) = visitor {
+                    visitor.visit(model, Path::from_fingerprints(model, VecDeque::from(path.clone())));
+                }
+            }
+            check_properties(
+                model, properties, &state, &mut ebits,
+                |p| {
+                    discoveries.contains_key(p.name)
+                        || !is_frontier && p.expectation != Expectation::Eventually
+                },
+                |name| { discoveries.insert(name, path.clone()); });
+
+            // Traverse further unless at the frontier, where it suffices to know whether the
+            // state is terminal.
+            let mut is_terminal = limits.actions(
+                model, &state, depth, &mut actions, boundary_pruned, &ebits);
+            let next_states = actions.drain(..).flat_map(|a| model.next_state(&state, a));
+            for next_state in next_states {
+                // Skip if outside boundary, noting unmet `eventually` properties.
+                if !model.within_boundary(&next_state) {
+                    record_boundary_pruned(boundary_pruned, &ebits);
+                    continue
+                }
+
+                // Skip if a cycle. As with the other checkers, a cycle does not make the
+                // state terminal for the purposes of eventually-property checking.
+                is_terminal = false;
+                let next_fingerprint = fingerprint(&next_state);
+                if path.contains(&next_fingerprint) { continue }
+
+                if is_frontier {
+                    is_cut_off = true;
+                } else {
+                    pending.push((next_state, next_fingerprint, depth + 1, ebits.clone()));
+                }
+            }
+            if is_frontier && is_terminal {
+                check_terminal_state(
+                    properties, &ebits,
+                    |p| discoveries.contains_key(p.name),
+                    |name| { discoveries.insert(name, path.clone()); });
+            }
+
+            // Done if discoveries found for all properties or reached target generated count.
+            if discoveries.len() >= target_discovery_count { return true }
+            if let Some(target_generated_count) = target_generated_count {
+                if target_generated_count.get() <= generated.load(Ordering::Relaxed) { return true }
+            }
+        }
+        is_cut_off
+    }
+}
+
+impl<M> Checker<M> for IddfsChecker<M>
+where M: Model,
+      M::State: Hash,
+{
+    fn model(&self) -> &M { &self.model }
+
+    fn checked_properties(&self) -> Vec<Property<M>> { self.properties.to_vec() }
+
+    fn generated_count(&self) -> usize { self.generated.load(Ordering::Relaxed) }
+
+    fn discoveries(&self) -> HashMap<&'static str, Path<M::State, M::Action>> {
+        self.discoveries.iter()
+            .map(|mapref| {
+                let name = <&'static str>::clone(mapref.key());
+                let path = Path::from_fingerprints(
+                    self.model(),
+                    VecDeque::from(mapref.value().clone()));
+                (name, with_violating_step(self.model(), name, path))
+            })
+            .collect()
+    }
+
+    fn join(mut self) -> Self {
+        for h in self.handles.drain(0..) {
+            h.join().unwrap();
+        }
+        self
+    }
+
+    fn is_done(&self) -> bool {
+        self.is_exhausted.load(Ordering::Acquire)
+            || self.discoveries.len() >= self.target_discovery_count
+    }
+
+    fn status(&self) -> CheckerStatus {
+        self.progress.status::<M::State>(self.generated_count(), 0, self.is_done())
+    }
+
+    fn boundary_limited_properties(&self) -> Vec<&'static str> {
+        boundary_limited_properties(&self.properties, &self.boundary_pruned, &self.discoveries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::*;
+    use crate::test_util::binary_clock::*;
+    use crate::test_util::dgraph::DGraph;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn visits_states_in_bfs_order() {
+        let (recorder, accessor) = StateRecorder::new_with_accessor();
+        LinearEquation { a: 2, b: 10, c: 14 }.checker()
+            .visitor(recorder)
+            .spawn_iddfs().join();
+        assert_eq!(
+            accessor(),
+            vec![
+                (0, 0),                         // distance == 0
+                (0, 1), (1, 0),                 // distance == 1
+                (0, 2), (1, 1), (1, 1), (2, 0), // distance == 2 (paths, not states)
+                (0, 3), (1, 2), (1, 2), (2, 1), // distance == 3
+            ]);
+    }
+
+    #[test]
+    fn can_complete_by_eliminating_properties() {
+        let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_iddfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 11);
+
+        // Like BFS, finds a shortest example.
+        assert_eq!(
+            checker.discovery("solvable").unwrap().into_actions(),
+            vec![
+                Guess::IncreaseY,
+                Guess::IncreaseX,
+                Guess::IncreaseX,
+            ]);
+    }
+
+    #[test]
+    fn can_complete_despite_cycles() {
+        let checker = BinaryClock.checker().spawn_iddfs().join();
+        assert!(checker.is_done());
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 4); // two paths per init state
+    }
+
+    #[test]
+    fn can_discover_eventually_counterexample() {
+        let checker = DGraph::with_property(Property::eventually("odd", |_, s| s % 2 == 1))
+            .with_path(vec![0, 1])
+            .with_path(vec![0, 2, 4, 6])
+            .with_path(vec![0, 2, 3])
+            .clone().checker().spawn_iddfs().join();
+        assert_eq!(checker.discovery("odd").unwrap().into_states(), vec![0, 2, 4, 6]);
+    }
+}
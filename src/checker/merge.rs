@@ -0,0 +1,266 @@
+//! Private module for selective re-export.
+
+use crate::{CheckOutcome, SessionReport};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// Consolidates the results of several independent partial runs, such as runs with different
+/// bounds, different [`CheckerBuilder::target_generated_count`] limits, or different frontier
+/// priorities. Discoveries are combined, statistics are aggregated, and property verdicts are
+/// reconciled, with contradictory verdicts reported as [`PropertyVerdict::Conflicting`].
+///
+/// A discovery is always conclusive, whereas the lack of a discovery is only conclusive for runs
+/// that visited every reachable state without an [exploration bound] (and for `eventually`
+/// properties, without [boundary limitations]). A conflict therefore
+/// indicates that the runs did not check the same model (or that the model is nondeterministic).
+///
+/// # Example
+///
+/// ```
+/// use stateright::{CheckOutcome, Checker, FingerprintRecorder, MergedReport, Model};
+/// use std::time::Duration;
+/// # #[derive(Clone)] struct Counter(u8);
+/// # impl Model for Counter {
+/// #     type State = u8;
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, s: &u8, actions: &mut Vec<()>) { if *s < self.0 { actions.push(()) } }
+/// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { Some(s + 1) }
+/// # }
+/// let mut report = MergedReport::new();
+/// for target in &[5, 10] {
+///     let (recorder, accessor) = FingerprintRecorder::new_with_accessor();
+///     let checker = Counter(20).checker()
+///         .target_generated_count(*target)
+///         .visitor(recorder)
+///         .spawn_bfs().join();
+///     report
+///         .merge(CheckOutcome::new(format!("target={}", target), &checker, Duration::default()))
+///         .merge_visited(accessor());
+/// }
+/// println!("{}", report);
+/// assert!(report.conflicts().is_empty());
+/// ```
+///
+/// [`CheckerBuilder::target_generated_count`]: crate::CheckerBuilder::target_generated_count
+/// [boundary limitations]: crate::Checker::boundary_limited_properties
+/// [exploration bound]: crate::Checker::exploration_bound
+#[derive(Clone, Debug, Default)]
+pub struct MergedReport {
+    outcomes: Vec<CheckOutcome>,
+    visited: Option<HashSet<u64>>,
+}
+
+/// The consolidated verdict for a property across the runs of a [`MergedReport`].
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum PropertyVerdict {
+    /// Some run found an example for a `sometimes` property, or some complete and unbounded run
+    /// found no counterexample for an `always`/`eventually` property (that is not
+    /// [boundary limited]).
+    ///
+    /// [boundary limited]: crate::Checker::boundary_limited_properties
+    Satisfied,
+    /// Some run found a counterexample for an `always`/`eventually` property, or some complete
+    /// and unbounded run found no example for a `sometimes` property.
+    Violated,
+    /// No run was conclusive.
+    Unknown,
+    /// Runs reached contradictory conclusions.
+    Conflicting,
+}
+
+impl MergedReport {
+    /// Instantiates an empty report.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the outcome of a partial run.
+    pub fn merge(&mut self, outcome: CheckOutcome) -> &mut Self {
+        self.outcomes.push(outcome);
+        self
+    }
+
+    /// Adds the fingerprints of states visited by a partial run, typically collected with a
+    /// [`FingerprintRecorder`]. See [`MergedReport::visited_count`].
+    ///
+    /// [`FingerprintRecorder`]: crate::FingerprintRecorder
+    pub fn merge_visited(&mut self, visited: impl IntoIterator<Item = u64>) -> &mut Self {
+        self.visited.get_or_insert_with(HashSet::new).extend(visited);
+        self
+    }
+
+    /// The outcome of each partial run in the order merged.
+    pub fn outcomes(&self) -> &[CheckOutcome] {
+        &self.outcomes
+    }
+
+    /// Indicates the total number of states generated across all runs, including states
+    /// generated by more than one run.
+    pub fn generated_count(&self) -> usize {
+        self.outcomes.iter().map(|o| o.generated_count).sum()
+    }
+
+    /// Indicates the number of distinct states visited across all runs, or `None` if no visited
+    /// states were merged.
+    pub fn visited_count(&self) -> Option<usize> {
+        self.visited.as_ref().map(HashSet::len)
+    }
+
+    /// Returns the consolidated verdict for each property by name.
+    pub fn verdicts(&self) -> BTreeMap<&'static str, PropertyVerdict> {
+        let mut verdicts = BTreeMap::new();
+        for outcome in &self.outcomes {
+            let is_conclusive = outcome.is_done && outcome.exploration_bound.is_none();
+            let mut record = |name, verdict| {
+                let merged = verdicts.entry(name).or_insert(PropertyVerdict::Unknown);
+                *merged = match (*merged, verdict) {
+                    (merged, PropertyVerdict::Unknown) => merged,
+                    (PropertyVerdict::Unknown, verdict) => verdict,
+                    (merged, verdict) if merged == verdict => merged,
+                    _ => PropertyVerdict::Conflicting,
+                };
+            };
+            let inconclusive_unless = |is_conclusive: bool, verdict| {
+                if is_conclusive { verdict } else { PropertyVerdict::Unknown }
+            };
+            for (name, _) in &outcome.counterexamples {
+                record(*name, PropertyVerdict::Violated);
+            }
+            for (name, _) in &outcome.examples {
+                record(*name, PropertyVerdict::Satisfied);
+            }
+            for name in &outcome.missing_examples {
+                record(*name, inconclusive_unless(is_conclusive, PropertyVerdict::Violated));
+            }
+            for name in &outcome.unrefuted {
+                let is_conclusive = is_conclusive && !outcome.boundary_limited.contains(name);
+                record(*name, inconclusive_unless(is_conclusive, PropertyVerdict::Satisfied));
+            }
+        }
+        verdicts
+    }
+
+    /// Returns the names of properties for which runs reached contradictory conclusions.
+    pub fn conflicts(&self) -> Vec<&'static str> {
+        self.verdicts().into_iter()
+            .filter(|(_, v)| *v == PropertyVerdict::Conflicting)
+            .map(|(name, _)| name)
+            .collect()
+    }
+
+    /// Returns the first discovery for each property by name, along with the name of the run
+    /// that found it.
+    pub fn discoveries(&self) -> BTreeMap<&'static str, (&str, &str)> {
+        let mut discoveries = BTreeMap::new();
+        for outcome in &self.outcomes {
+            for (name, path) in outcome.counterexamples.iter().chain(&outcome.examples) {
+                discoveries.entry(*name).or_insert((outcome.name.as_str(), path.as_str()));
+            }
+        }
+        discoveries
+    }
+
+    /// Indicates whether every property is conclusively satisfied.
+    pub fn is_success(&self) -> bool {
+        self.verdicts().values().all(|v| *v == PropertyVerdict::Satisfied)
+    }
+
+    /// Panics unless [`MergedReport::is_success`], listing the runs and verdicts.
+    pub fn assert_properties(&self) {
+        assert!(self.is_success(), "Merged check failed.\n{}", self);
+    }
+}
+
+impl From<SessionReport> for MergedReport {
+    fn from(report: SessionReport) -> Self {
+        MergedReport { outcomes: report.outcomes, visited: None }
+    }
+}
+
+impl Display for MergedReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for outcome in &self.outcomes {
+            write!(f, "{}", outcome)?;
+        }
+        for (name, verdict) in self.verdicts() {
+            writeln!(f, "Verdict for \"{}\": {:?}", name, verdict)?;
+        }
+        write!(f, "Merged {}. runs={}, generated={}",
+               if self.is_success() { "passed" } else { "failed" },
+               self.outcomes.len(), self.generated_count())?;
+        if let Some(visited_count) = self.visited_count() {
+            write!(f, ", visited={}", visited_count)?;
+        }
+        writeln!(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, FingerprintRecorder, Model};
+    use crate::test_util::linear_equation_solver::*;
+    use std::time::Duration;
+
+    fn run(name: &str, model: LinearEquation, target: usize) -> (CheckOutcome, HashSet<u64>) {
+        let (recorder, accessor) = FingerprintRecorder::new_with_accessor();
+        let checker = model.checker()
+            .target_generated_count(target)
+            .visitor(recorder)
+            .spawn_bfs().join();
+        (CheckOutcome::new(name.to_string(), &checker, Duration::default()), accessor())
+    }
+
+    #[test]
+    fn merges_statistics() {
+        let (small, small_visited) = run("small", LinearEquation { a: 2, b: 4, c: 7 }, 10);
+        let (large, large_visited) = run("large", LinearEquation { a: 2, b: 4, c: 7 }, 100);
+        let generated_count = small.generated_count + large.generated_count;
+        let visited_count = large_visited.len();
+        assert!(small_visited.is_subset(&large_visited));
+
+        let mut report = MergedReport::new();
+        report
+            .merge(small).merge_visited(small_visited)
+            .merge(large).merge_visited(large_visited);
+        assert_eq!(report.outcomes().len(), 2);
+        assert_eq!(report.generated_count(), generated_count);
+        assert_eq!(report.visited_count(), Some(visited_count));
+        assert!(report.to_string().ends_with(
+            &format!("Merged failed. runs=2, generated={}, visited={}\n",
+                     generated_count, visited_count)));
+    }
+
+    #[test]
+    fn merges_discoveries() {
+        let mut report = MergedReport::new();
+        report
+            .merge(run("unsolvable", LinearEquation { a: 2, b: 4, c: 7 }, 100).0)
+            .merge(run("first", LinearEquation { a: 2, b: 10, c: 14 }, 1_000_000).0)
+            .merge(run("second", LinearEquation { a: 2, b: 10, c: 14 }, 1_000_000).0);
+        assert_eq!(report.verdicts().get("solvable"), Some(&PropertyVerdict::Satisfied));
+        assert_eq!(report.discoveries().get("solvable").map(|(run, _)| *run), Some("first"));
+        assert!(report.is_success());
+        assert!(report.to_string().contains("Verdict for \"solvable\": Satisfied"));
+    }
+
+    #[test]
+    fn partial_runs_are_inconclusive() {
+        let mut report = MergedReport::new();
+        report.merge(run("partial", LinearEquation { a: 2, b: 4, c: 7 }, 100).0);
+        assert_eq!(report.verdicts().get("solvable"), Some(&PropertyVerdict::Unknown));
+        assert!(!report.is_success());
+        assert_eq!(report.visited_count(), None);
+    }
+
+    #[test]
+    fn detects_conflicting_verdicts() {
+        let mut report = MergedReport::new();
+        report
+            .merge(run("unsolvable", LinearEquation { a: 2, b: 4, c: 7 }, 1_000_000).0)
+            .merge(run("solvable", LinearEquation { a: 2, b: 10, c: 14 }, 1_000_000).0);
+        assert_eq!(report.conflicts(), vec!["solvable"]);
+        assert!(!report.is_success());
+    }
+}
@@ -54,6 +54,11 @@ impl<State, Action> Path<State, Action> {
         Path(output)
     }
 
+    /// Constructs a path from states and the actions between them, which are assumed to be valid.
+    pub(crate) fn from_vec(steps: Vec<(State, Option<Action>)>) -> Self {
+        Path(steps)
+    }
+
     /// Constructs a path from a model, initial state, and a sequence of actions. Panics for inputs
     /// unreachable via the model.
     pub fn from_actions<'a, M>(
@@ -80,6 +85,61 @@ impl<State, Action> Path<State, Action> {
         Some(Path(output))
     }
 
+    /// Shrinks the path by removing actions (and reordering adjacent actions when doing so enables
+    /// further removal) for as long as the resulting path is valid per the model and satisfies
+    /// `is_discovery`. The result is not necessarily the shortest such path, but no single action
+    /// can be removed from it. See [`Checker::minimized_discovery`].
+    ///
+    /// [`Checker::minimized_discovery`]: crate::Checker::minimized_discovery
+    pub fn minimize<M>(self, model: &M, is_discovery: impl Fn(&Self) -> bool) -> Self
+    where M: Model<State = State, Action = Action>,
+          State: Clone + PartialEq,
+          Action: Clone + PartialEq,
+    {
+        let init_state = self.0[0].0.clone();
+        let replay = |actions: &[Action]| {
+            Path::from_actions(model, init_state.clone(), actions).filter(|p| is_discovery(p))
+        };
+        let remove_chunks = |mut path: Self, mut actions: Vec<Action>| {
+            // Tries the largest chunks first, so long irrelevant stretches are cheap to drop.
+            let mut chunk = std::cmp::max(actions.len() / 2, 1);
+            while chunk > 0 {
+                let mut start = 0;
+                let mut removed = false;
+                while start + chunk <= actions.len() {
+                    let mut candidate = actions.clone();
+                    candidate.drain(start..start + chunk);
+                    if let Some(shorter) = replay(&candidate) {
+                        path = shorter;
+                        actions = candidate;
+                        removed = true;
+                    } else {
+                        start += chunk;
+                    }
+                }
+                chunk = if removed { std::cmp::min(chunk, actions.len()) } else { chunk / 2 };
+            }
+            (path, actions)
+        };
+
+        let actions = self.clone().into_actions();
+        let (mut path, mut actions) = remove_chunks(self, actions);
+        'reorder: loop {
+            for i in 1..actions.len() {
+                let mut swapped = actions.clone();
+                swapped.swap(i - 1, i);
+                if Path::from_actions(model, init_state.clone(), &swapped).is_none() { continue }
+                let (shorter, shorter_actions) = remove_chunks(path.clone(), swapped);
+                if shorter_actions.len() < actions.len() {
+                    path = shorter;
+                    actions = shorter_actions;
+                    continue 'reorder;
+                }
+            }
+            return path;
+        }
+    }
+
     /// Determines the final state associated with a particular fingerprint path.
     pub(crate) fn final_state<M>(model: &M, mut fingerprints: VecDeque<Fingerprint>) -> Option<M::State>
     where M: Model<State = State, Action = Action>,
@@ -109,6 +169,41 @@ impl<State, Action> Path<State, Action> {
         &self.0.last().unwrap().0
     }
 
+    /// The number of actions taken to reach the last state.
+    pub fn depth(&self) -> usize {
+        self.0.len() - 1
+    }
+
+    /// The action that led to the last state, or `None` for a path to an initial state.
+    pub fn last_action(&self) -> Option<&Action> {
+        self.0.iter().rev().nth(1).and_then(|(_s, a)| a.as_ref())
+    }
+
+    /// The last step (last state, action, and next state), or `None` for a path to an initial
+    /// state.
+    pub(crate) fn last_step(&self) -> Option<(&State, &Action, &State)> {
+        let n = self.0.len();
+        if n < 2 { return None }
+        let (last_state, action) = &self.0[n - 2];
+        Some((last_state, action.as_ref()?, &self.0[n - 1].0))
+    }
+
+    /// Appends a step, which is assumed to be valid.
+    pub(crate) fn push(&mut self, action: Action, next_state: State) {
+        self.0.last_mut().unwrap().1 = Some(action);
+        self.0.push((next_state, None));
+    }
+
+    /// Iterates over the states.
+    pub(crate) fn states(&self) -> impl Iterator<Item = &State> {
+        self.0.iter().map(|(s, _a)| s)
+    }
+
+    /// Iterates over the actions.
+    pub(crate) fn actions(&self) -> impl Iterator<Item = &Action> {
+        self.0.iter().filter_map(|(_s, a)| a.as_ref())
+    }
+
     /// Extracts the states.
     pub fn into_states(self) -> Vec<State> {
         self.0.into_iter().map(|(s, _a)| s).collect()
@@ -124,6 +219,60 @@ impl<State, Action> Path<State, Action> {
         self.into()
     }
 
+    /// Serializes the path as a JSON trace for consumption by external tools. The trace is an
+    /// object whose `steps` field lists each state along with its sequence number, fingerprint,
+    /// and the action that led to it (absent for the initial state).
+    ///
+    /// ```json
+    /// {"steps":[{"seq":0,"fingerprint":"...","state":...},{"seq":1,"fingerprint":"...","action":...,"state":...}]}
+    /// ```
+    pub fn serialize_json(&self) -> serde_json::Result<String>
+    where State: Hash + serde::Serialize,
+          Action: serde::Serialize,
+    {
+        #[derive(serde::Serialize)]
+        struct Trace<'a, State, Action> { steps: Vec<TraceStep<'a, State, Action>> }
+        #[derive(serde::Serialize)]
+        struct TraceStep<'a, State, Action> {
+            seq: usize,
+            fingerprint: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            action: Option<&'a Action>,
+            state: &'a State,
+        }
+
+        let mut prev_action = None;
+        let steps = self.0.iter().enumerate()
+            .map(|(seq, (state, action))| {
+                let step = TraceStep {
+                    seq,
+                    fingerprint: format!("{}", fingerprint(state)),
+                    action: prev_action,
+                    state,
+                };
+                prev_action = action.as_ref();
+                step
+            })
+            .collect();
+        serde_json::to_string(&Trace { steps })
+    }
+
+    /// Serializes the path as a TLA+ behavior: a sequence of records, one per state, such as
+    /// `<<[x |-> 0, y |-> 0], [x |-> 1, y |-> 0]>>`. `to_fields` maps each state to TLA+ field
+    /// names and corresponding TLA+ expressions, which allows a path to be validated against a
+    /// parallel TLA+ specification (e.g. via TLC's trace checking).
+    pub fn serialize_tla(&self, to_fields: impl Fn(&State) -> Vec<(&'static str, String)>) -> String {
+        let records = self.0.iter()
+            .map(|(state, _action)| {
+                let fields = to_fields(state).into_iter()
+                    .map(|(name, value)| format!("{} |-> {}", name, value))
+                    .collect::<Vec<_>>();
+                format!("[{}]", fields.join(", "))
+            })
+            .collect::<Vec<_>>();
+        format!("<<\n{}\n>>", records.join(",\n"))
+    }
+
     /// Encodes the path as a sequence of opaque "fingerprints" delimited by forward
     /// slash (`/`) characters.
     pub fn encode(&self) -> String where State: Hash {
@@ -0,0 +1,345 @@
+//! Private module for selective re-export.
+
+use crate::{Checker, CheckerBuilder, Expectation, Model, StopReason};
+use parking_lot::{Condvar, Mutex};
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Checks several models concurrently while sharing a thread budget and an optional budget of
+/// generated states, then reports on all of them together. Useful for checking the same protocol
+/// with different configurations.
+///
+/// Each model runs with the thread count requested via [`CheckerBuilder::threads`] (capped at the
+/// session's thread count), and models start in the order added once enough threads are free. A
+/// model never starts before an earlier model, even if enough threads are free for it, so adding
+/// the largest configuration first generally minimizes wall-clock time.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{CheckerBuilder, CheckerSession, Model};
+/// # #[derive(Clone)] struct Counter(u8);
+/// # impl Model for Counter {
+/// #     type State = u8;
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, s: &u8, actions: &mut Vec<()>) { if *s < self.0 { actions.push(()) } }
+/// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { Some(s + 1) }
+/// # }
+/// let report = CheckerSession::new()
+///     .threads(4)
+///     .add("max=20", Counter(20).checker().threads(2), CheckerBuilder::spawn_bfs)
+///     .add("max=10", Counter(10).checker().threads(2), CheckerBuilder::spawn_dfs)
+///     .run();
+/// println!("{}", report);
+/// report.assert_properties();
+/// ```
+pub struct CheckerSession {
+    thread_count: usize,
+    max_generated_count: Option<NonZeroUsize>,
+    entries: Vec<Entry>,
+}
+
+type Entry = (String, usize, Box<dyn FnOnce(usize, Option<NonZeroUsize>) -> CheckOutcome + Send>);
+
+impl CheckerSession {
+    /// Instantiates an empty session that uses one thread.
+    pub fn new() -> Self {
+        CheckerSession {
+            thread_count: 1,
+            max_generated_count: None,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets the number of threads shared by all models in the session.
+    pub fn threads(self, thread_count: usize) -> Self {
+        Self { thread_count: std::cmp::max(1, thread_count), .. self }
+    }
+
+    /// Limits the total number of states generated across the session, which bounds memory
+    /// usage. The budget is split evenly, with each model receiving a corresponding
+    /// [`CheckerBuilder::target_generated_count`] (or retaining its own if lower).
+    pub fn max_generated_count(self, max_generated_count: usize) -> Self {
+        Self { max_generated_count: NonZeroUsize::new(max_generated_count), .. self }
+    }
+
+    /// Adds a model to the session. `spawn` indicates the checking strategy, such as
+    /// [`CheckerBuilder::spawn_bfs`].
+    pub fn add<M, C>(
+        mut self,
+        name: impl Into<String>,
+        builder: CheckerBuilder<M>,
+        spawn: fn(CheckerBuilder<M>) -> C)
+        -> Self
+    where M: Model + Send + Sync + 'static,
+          M::State: Debug + Hash + Send + Sync + 'static,
+          M::Action: Debug,
+          C: Checker<M> + 'static,
+    {
+        let name = name.into();
+        let thread_count = builder.thread_count;
+        let outcome_name = name.clone();
+        self.entries.push((name, thread_count, Box::new(move |thread_count, target_generated_count| {
+            let target_generated_count = match (builder.target_generated_count, target_generated_count) {
+                (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+                (a, b) => a.or(b),
+            };
+            let builder = CheckerBuilder { thread_count, target_generated_count, .. builder };
+            let start = Instant::now();
+            let checker = spawn(builder).join();
+            CheckOutcome::new(outcome_name, &checker, start.elapsed())
+        })));
+        self
+    }
+
+    /// Checks every model, blocking until all complete.
+    pub fn run(self) -> SessionReport {
+        let budget = self.thread_count;
+        let target_generated_count = self.max_generated_count.and_then(|max| {
+            NonZeroUsize::new(max.get() / std::cmp::max(1, self.entries.len()))
+        });
+        let admission = Arc::new((Mutex::new(Admission { available: budget, next: 0 }), Condvar::new()));
+        let handles: Vec<_> = self.entries.into_iter().enumerate()
+            .map(|(position, (name, thread_count, check))| {
+                let thread_count = std::cmp::min(std::cmp::max(1, thread_count), budget);
+                let admission = Arc::clone(&admission);
+                std::thread::spawn(move || {
+                    {
+                        // Models are admitted in the order added, so a model waits for every
+                        // earlier model to start even if enough threads are free for it.
+                        let (lock, changed) = &*admission;
+                        let mut admission = lock.lock();
+                        while admission.next != position || admission.available < thread_count {
+                            changed.wait(&mut admission);
+                        }
+                        admission.available -= thread_count;
+                        admission.next += 1;
+                        changed.notify_all();
+                    }
+                    log::debug!("Session check started. name={}, threads={}", name, thread_count);
+                    let outcome = check(thread_count, target_generated_count);
+                    let (lock, changed) = &*admission;
+                    lock.lock().available += thread_count;
+                    changed.notify_all();
+                    outcome
+                })
+            })
+            .collect();
+        SessionReport {
+            outcomes: handles.into_iter().map(|h| h.join().unwrap()).collect(),
+        }
+    }
+}
+
+/// Tracks the threads that are free for a [`CheckerSession`] and which model starts next.
+struct Admission {
+    available: usize,
+    next: usize,
+}
+
+impl Default for CheckerSession {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The results of a [`CheckerSession`], one [`CheckOutcome`] per model in the order added.
+#[derive(Clone, Debug)]
+pub struct SessionReport {
+    /// The outcome for each model.
+    pub outcomes: Vec<CheckOutcome>,
+}
+
+impl SessionReport {
+    /// Indicates whether every model completed without counterexamples and with examples for all
+    /// `sometimes` properties.
+    pub fn is_success(&self) -> bool {
+        self.outcomes.iter().all(CheckOutcome::is_success)
+    }
+
+    /// Panics unless [`SessionReport::is_success`], listing the failing models.
+    pub fn assert_properties(&self) {
+        assert!(self.is_success(), "Session check failed.\n{}", self);
+    }
+}
+
+impl Display for SessionReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for outcome in &self.outcomes {
+            write!(f, "{}", outcome)?;
+        }
+        let total: usize = self.outcomes.iter().map(|o| o.generated_count).sum();
+        writeln!(f, "Session {}. models={}, generated={}",
+                 if self.is_success() { "passed" } else { "failed" },
+                 self.outcomes.len(), total)
+    }
+}
+
+/// The result of checking one model within a [`CheckerSession`]. Paths are rendered as text so
+/// that outcomes for different model types can be reported together.
+#[derive(Clone, Debug)]
+pub struct CheckOutcome {
+    /// The name with which the model was added.
+    pub name: String,
+    /// See [`Checker::generated_count`].
+    pub generated_count: usize,
+    /// See [`Checker::is_done`]. `false` if checking stopped at a generated count target.
+    pub is_done: bool,
+    /// How long checking took.
+    pub elapsed: Duration,
+    /// See [`Checker::stop_reason`].
+    pub stop_reason: Option<StopReason>,
+    /// See [`Checker::exploration_bound`].
+    pub exploration_bound: Option<String>,
+    /// Property names and paths for `always`/`eventually` counterexamples.
+    pub counterexamples: Vec<(&'static str, String)>,
+    /// Property names and paths for `sometimes` examples.
+    pub examples: Vec<(&'static str, String)>,
+    /// `sometimes` properties lacking an example.
+    pub missing_examples: Vec<&'static str>,
+    /// `always`/`eventually` properties lacking a counterexample.
+    pub unrefuted: Vec<&'static str>,
+    /// See [`Checker::boundary_limited_properties`].
+    pub boundary_limited: Vec<&'static str>,
+}
+
+impl CheckOutcome {
+    /// Summarizes a checker that has been run outside of a [`CheckerSession`], such as one of
+    /// several partial runs to be combined via [`MergedReport`].
+    ///
+    /// [`MergedReport`]: crate::MergedReport
+    pub fn new<M, C>(name: String, checker: &C, elapsed: Duration) -> Self
+    where M: Model,
+          M::State: Debug,
+          M::Action: Debug,
+          C: Checker<M>,
+    {
+        let mut discoveries = checker.discoveries();
+        let mut outcome = CheckOutcome {
+            name,
+            generated_count: checker.generated_count(),
+            is_done: checker.is_done(),
+            elapsed,
+            stop_reason: checker.stop_reason(),
+            exploration_bound: checker.exploration_bound(),
+            counterexamples: Vec::new(),
+            examples: Vec::new(),
+            missing_examples: Vec::new(),
+            unrefuted: Vec::new(),
+            boundary_limited: checker.boundary_limited_properties(),
+        };
+        for property in checker.checked_properties() {
+            let path = discoveries.remove(property.name).map(|path| path.to_string());
+            match (property.expectation, path) {
+                (Expectation::Sometimes, Some(path)) =>
+                    outcome.examples.push((property.name, path)),
+                (Expectation::Sometimes, None) =>
+                    outcome.missing_examples.push(property.name),
+                (_, Some(path)) =>
+                    outcome.counterexamples.push((property.name, path)),
+                (_, None) =>
+                    outcome.unrefuted.push(property.name),
+            }
+        }
+        outcome
+    }
+
+    /// Indicates whether checking completed without counterexamples and with examples for all
+    /// `sometimes` properties.
+    pub fn is_success(&self) -> bool {
+        self.is_done && self.counterexamples.is_empty() && self.missing_examples.is_empty()
+    }
+}
+
+impl Display for CheckOutcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.stop_reason {
+            None => write!(f, "[{}] {}.", self.name, if self.is_done { "Done" } else { "Incomplete" })?,
+            Some(reason) => write!(f, "[{}] Inconclusive: {}.", self.name, reason)?,
+        }
+        writeln!(f, " generated={}, sec={}", self.generated_count, self.elapsed.as_secs())?;
+        for (name, path) in &self.counterexamples {
+            write!(f, "[{}] Discovered \"{}\" counterexample {}", self.name, name, path)?;
+        }
+        for (name, path) in &self.examples {
+            write!(f, "[{}] Discovered \"{}\" example {}", self.name, name, path)?;
+        }
+        for name in &self.missing_examples {
+            writeln!(f, "[{}] Missing \"{}\" example", self.name, name)?;
+        }
+        for name in &self.boundary_limited {
+            writeln!(f, "[{}] Property \"{}\" holds within boundary", self.name, name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn checks_models_concurrently_within_thread_budget() {
+        let report = CheckerSession::new()
+            .threads(2)
+            .add("unsolvable", LinearEquation { a: 2, b: 4, c: 7 }.checker().threads(2),
+                 CheckerBuilder::spawn_bfs)
+            .add("solvable", LinearEquation { a: 2, b: 10, c: 14 }.checker(),
+                 CheckerBuilder::spawn_dfs)
+            .add("greedy", LinearEquation { a: 2, b: 10, c: 14 }.checker().threads(8),
+                 CheckerBuilder::spawn_bfs)
+            .run();
+        let names: Vec<_> = report.outcomes.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, vec!["unsolvable", "solvable", "greedy"]);
+
+        let unsolvable = &report.outcomes[0];
+        assert!(unsolvable.is_done);
+        assert_eq!(unsolvable.generated_count, 256 * 256);
+        assert_eq!(unsolvable.missing_examples, vec!["solvable"]);
+        assert!(!unsolvable.is_success());
+
+        assert!(report.outcomes[1].is_success());
+        assert_eq!(report.outcomes[1].examples.len(), 1);
+        assert!(report.outcomes[2].is_success());
+        assert!(!report.is_success());
+
+        let text = report.to_string();
+        assert!(text.contains("[unsolvable] Missing \"solvable\" example"));
+        assert!(text.contains("Session failed. models=3"));
+    }
+
+    #[test]
+    fn starts_models_in_the_order_added() {
+        let started = Arc::new(Mutex::new(Vec::new()));
+        let mut session = CheckerSession::new().threads(2);
+        for (name, thread_count) in [("a", 1), ("b", 2), ("c", 2), ("d", 1)] {
+            let started = Arc::clone(&started);
+            session.entries.push((name.to_string(), thread_count, Box::new(move |_, _| {
+                started.lock().push(name);
+                std::thread::sleep(Duration::from_millis(10));
+                let checker = LinearEquation { a: 2, b: 10, c: 14 }.checker().spawn_dfs().join();
+                CheckOutcome::new(name.to_string(), &checker, Duration::default())
+            })));
+        }
+        assert!(session.run().is_success());
+        assert_eq!(*started.lock(), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn splits_generated_count_budget() {
+        let report = CheckerSession::new()
+            .max_generated_count(2_000)
+            .add("a", LinearEquation { a: 2, b: 4, c: 7 }.checker(), CheckerBuilder::spawn_bfs)
+            .add("b", LinearEquation { a: 2, b: 4, c: 7 }.checker(), CheckerBuilder::spawn_bfs)
+            .run();
+        for outcome in &report.outcomes {
+            assert!(!outcome.is_done);
+            assert!(outcome.generated_count < 256 * 256);
+        }
+    }
+}
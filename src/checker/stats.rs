@@ -0,0 +1,106 @@
+//! Private module for selective re-export.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+/// Describes the shape of the state space explored by a [`Checker`], which helps explain why a
+/// model's state space is large and where to aim reductions such as symmetry or a tighter
+/// [`Model::within_boundary`]. See [`Checker::stats`].
+///
+/// The [`Display`] implementation renders the statistics as tables.
+///
+/// [`Checker`]: crate::Checker
+/// [`Checker::stats`]: crate::Checker::stats
+/// [`Model::within_boundary`]: crate::Model::within_boundary
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CheckerStats {
+    /// The number of evaluated states at each depth (steps from an initial state), indexed by
+    /// depth.
+    pub states_per_depth: Vec<usize>,
+    /// The number of evaluated states whose successors were computed. Successors are skipped
+    /// once no property awaits a discovery.
+    pub expanded_count: usize,
+    /// The number of successors computed across every expanded state, including those outside
+    /// the boundary and those that were already generated.
+    pub successor_count: usize,
+    /// The greatest number of successors of any expanded state.
+    pub max_branching_factor: usize,
+    /// The number of transitions taken for each kind of action, if enabled via
+    /// [`CheckerBuilder::track_coverage`]. Otherwise empty. See [`Checker::coverage`].
+    ///
+    /// [`Checker::coverage`]: crate::Checker::coverage
+    /// [`CheckerBuilder::track_coverage`]: crate::CheckerBuilder::track_coverage
+    pub actions_taken: BTreeMap<String, usize>,
+}
+
+impl CheckerStats {
+    /// The average number of successors of an expanded state.
+    pub fn avg_branching_factor(&self) -> f64 {
+        if self.expanded_count == 0 { return 0.0 }
+        self.successor_count as f64 / self.expanded_count as f64
+    }
+}
+
+impl Display for CheckerStats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "{:>8} {:>12}", "depth", "states")?;
+        for (depth, count) in self.states_per_depth.iter().enumerate() {
+            writeln!(f, "{:>8} {:>12}", depth, count)?;
+        }
+        writeln!(f, "branching factor: avg={:.2}, max={}, expanded={}, successors={}",
+                 self.avg_branching_factor(),
+                 self.max_branching_factor,
+                 self.expanded_count,
+                 self.successor_count)?;
+        if !self.actions_taken.is_empty() {
+            let total: usize = self.actions_taken.values().sum();
+            let width = self.actions_taken.keys().map(String::len).max().unwrap_or(0);
+            writeln!(f, "{:<width$} {:>12} {:>7}", "action", "taken", "share", width = width)?;
+            for (kind, count) in &self.actions_taken {
+                writeln!(f, "{:<width$} {:>12} {:>6.1}%",
+                         kind, count, 100.0 * *count as f64 / total as f64, width = width)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+    use crate::test_util::linear_equation_solver::*;
+
+    #[test]
+    fn describes_state_space() {
+        let checker = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .max_depth(3)
+            .track_coverage()
+            .spawn_bfs().join();
+        let stats = checker.stats();
+        assert_eq!(stats.states_per_depth, vec![1, 2, 3, 4]);
+        assert_eq!(stats.expanded_count, 1 + 2 + 3);
+        assert_eq!(stats.successor_count, 2 * (1 + 2 + 3));
+        assert_eq!(stats.max_branching_factor, 2);
+        assert_eq!(stats.avg_branching_factor(), 2.0);
+        assert_eq!(stats.actions_taken.get("IncreaseX"), Some(&6));
+        assert_eq!(format!("{}", stats), "\
+\x20  depth       states
+\x20      0            1
+\x20      1            2
+\x20      2            3
+\x20      3            4
+branching factor: avg=2.00, max=2, expanded=6, successors=12
+action           taken   share
+IncreaseX            6   50.0%
+IncreaseY            6   50.0%
+");
+
+        // DFS tracks the same statistics, but actions only when tracking coverage.
+        let stats = LinearEquation { a: 2, b: 4, c: 7 }.checker()
+            .max_depth(3)
+            .spawn_dfs().join().stats();
+        assert_eq!(stats.states_per_depth, vec![1, 2, 3, 4]);
+        assert_eq!(stats.max_branching_factor, 2);
+        assert!(stats.actions_taken.is_empty());
+    }
+}
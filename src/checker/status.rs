@@ -0,0 +1,303 @@
+//! Private module for selective re-export.
+
+use crate::checker::{CheckerStats, Coverage};
+use parking_lot::{Mutex, RwLock};
+use std::fmt::{self, Display, Formatter};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// A snapshot of a [`Checker`]'s progress, which can be polled via [`Checker::status`] while
+/// checking runs.
+///
+/// [`Checker`]: crate::Checker
+/// [`Checker::status`]: crate::Checker::status
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckerStatus {
+    /// How many states have been generated. See [`Checker::generated_count`].
+    ///
+    /// [`Checker::generated_count`]: crate::Checker::generated_count
+    pub generated_count: usize,
+    /// How many generated states are awaiting evaluation.
+    pub pending_count: usize,
+    /// The greatest number of steps from an initial state to an evaluated state.
+    pub max_depth: usize,
+    /// How long the checker has been running. Stops advancing once checking is done.
+    pub elapsed: Duration,
+    /// A rough lower bound on the memory used to track generated and pending states. Memory
+    /// owned by the states themselves (such as the contents of a `Vec`) is not included unless
+    /// [`CheckerBuilder::state_heap_size`] is specified.
+    ///
+    /// [`CheckerBuilder::state_heap_size`]: crate::CheckerBuilder::state_heap_size
+    pub estimated_memory_bytes: usize,
+    /// Indicates whether checking is done. See [`Checker::is_done`].
+    ///
+    /// [`Checker::is_done`]: crate::Checker::is_done
+    pub is_done: bool,
+}
+
+impl CheckerStatus {
+    /// The average number of states generated per second.
+    pub fn generated_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 { return 0.0 }
+        self.generated_count as f64 / secs
+    }
+}
+
+impl Display for CheckerStatus {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "generated={}, pending={}, max_depth={}, sec={}, rate={:.0}/sec, mem_mb={}",
+               self.generated_count,
+               self.pending_count,
+               self.max_depth,
+               self.elapsed.as_secs(),
+               self.generated_per_sec(),
+               self.estimated_memory_bytes / 1_000_000)
+    }
+}
+
+/// Indicates why a [`Checker`] stopped before checking was done, in which case the results are
+/// inconclusive. See [`Checker::stop_reason`].
+///
+/// [`Checker`]: crate::Checker
+/// [`Checker::stop_reason`]: crate::Checker::stop_reason
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StopReason {
+    /// The estimated memory exceeded [`CheckerBuilder::max_memory`].
+    ///
+    /// [`CheckerBuilder::max_memory`]: crate::CheckerBuilder::max_memory
+    MemoryLimit,
+    /// Checking ran longer than [`CheckerBuilder::timeout`].
+    ///
+    /// [`CheckerBuilder::timeout`]: crate::CheckerBuilder::timeout
+    TimeLimit,
+    /// A [`CancellationToken`] was cancelled.
+    Cancelled,
+    /// Distinct states had the same fingerprint, as detected per [`CheckerBuilder::fingerprint`].
+    ///
+    /// [`CheckerBuilder::fingerprint`]: crate::CheckerBuilder::fingerprint
+    FingerprintCollision,
+    /// An I/O operation failed, such as sending a message to a peer of
+    /// [`CheckerBuilder::spawn_distributed`] or spilling fingerprints to a
+    /// [`StateStore::OnDisk`]. The error is logged.
+    ///
+    /// [`CheckerBuilder::spawn_distributed`]: crate::CheckerBuilder::spawn_distributed
+    /// [`StateStore::OnDisk`]: crate::StateStore::OnDisk
+    Io,
+}
+
+impl Display for StopReason {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            StopReason::MemoryLimit => "memory limit reached",
+            StopReason::TimeLimit => "time limit reached",
+            StopReason::Cancelled => "cancelled",
+            StopReason::FingerprintCollision => "fingerprint collision detected",
+            StopReason::Io => "I/O error",
+        })
+    }
+}
+
+/// Requests that a [`Checker`] stop, typically from another thread, such as when a user presses
+/// a "stop" button. Clones share the same request. See [`CheckerBuilder::cancellation_token`].
+///
+/// ```
+/// use stateright::{CancellationToken, Checker, Model, StopReason};
+/// # let model = ();
+///
+/// let token = CancellationToken::new();
+/// let checker = model.checker().cancellation_token(token.clone()).spawn_bfs();
+/// token.cancel();
+/// let checker = checker.join();
+/// // Unless checking finished first.
+/// assert!(checker.is_done() || checker.stop_reason() == Some(StopReason::Cancelled));
+/// ```
+///
+/// [`Checker`]: crate::Checker
+/// [`CheckerBuilder::cancellation_token`]: crate::CheckerBuilder::cancellation_token
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Constructs a token that has not been cancelled.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Requests that checkers using this token stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Indicates whether [`CancellationToken::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Conditions under which a checker stops before checking is done. See [`StopReason`].
+#[derive(Clone, Default)]
+pub(crate) struct StopConditions {
+    pub(crate) max_memory_bytes: Option<NonZeroUsize>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) cancellation_token: Option<CancellationToken>,
+}
+
+/// Progress counters shared by a checker's threads, from which a [`CheckerStatus`] is derived.
+pub(crate) struct Progress {
+    started: Instant,
+    finished_nanos: AtomicU64, // zero until a thread finishes
+    pending: AtomicUsize,
+    max_depth: AtomicUsize,
+    depth_counts: RwLock<Vec<AtomicUsize>>,
+    expanded: AtomicUsize,
+    successors: AtomicUsize,
+    max_successors: AtomicUsize,
+    heap_bytes: AtomicUsize,
+    heap_samples: AtomicUsize,
+    stop_reason: Mutex<Option<StopReason>>,
+}
+
+impl Progress {
+    pub(crate) fn new(pending: usize) -> Self {
+        Progress {
+            started: Instant::now(),
+            finished_nanos: AtomicU64::new(0),
+            pending: AtomicUsize::new(pending),
+            max_depth: AtomicUsize::new(0),
+            depth_counts: RwLock::new(Vec::new()),
+            expanded: AtomicUsize::new(0),
+            successors: AtomicUsize::new(0),
+            max_successors: AtomicUsize::new(0),
+            heap_bytes: AtomicUsize::new(0),
+            heap_samples: AtomicUsize::new(0),
+            stop_reason: Mutex::new(None),
+        }
+    }
+
+    /// Adjusts the pending count after a thread's pending states changed from `before` to
+    /// `after`.
+    pub(crate) fn update_pending(&self, before: usize, after: usize) {
+        if after > before {
+            self.pending.fetch_add(after - before, Ordering::Relaxed);
+        } else {
+            self.pending.fetch_sub(before - after, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn set_pending(&self, pending: usize) {
+        self.pending.store(pending, Ordering::Relaxed);
+    }
+
+    /// Notes that a state at the specified depth was evaluated.
+    pub(crate) fn record_depth(&self, depth: usize) {
+        self.max_depth.fetch_max(depth, Ordering::Relaxed);
+        if let Some(count) = self.depth_counts.read().get(depth) {
+            count.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let mut depth_counts = self.depth_counts.write();
+        if depth_counts.len() <= depth {
+            depth_counts.resize_with(depth + 1, Default::default);
+        }
+        depth_counts[depth].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Notes that the successors of an evaluated state were computed.
+    pub(crate) fn record_successors(&self, count: usize) {
+        self.expanded.fetch_add(1, Ordering::Relaxed);
+        self.successors.fetch_add(count, Ordering::Relaxed);
+        self.max_successors.fetch_max(count, Ordering::Relaxed);
+    }
+
+    /// Notes the heap memory owned by an evaluated state, from which the heap memory owned by
+    /// pending states is estimated. See [`CheckerBuilder::state_heap_size`].
+    ///
+    /// [`CheckerBuilder::state_heap_size`]: crate::CheckerBuilder::state_heap_size
+    pub(crate) fn record_heap_size(&self, bytes: usize) {
+        self.heap_bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.heap_samples.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Indicates whether checking should stop before it is done, noting the first reason. Once
+    /// stopped, checking remains stopped for every thread.
+    pub(crate) fn should_stop<State>(
+        &self, conditions: &StopConditions, generated_bytes: impl FnOnce() -> usize) -> bool
+    {
+        let mut stop_reason = self.stop_reason.lock();
+        if stop_reason.is_some() { return true }
+        let is_cancelled = matches!(&conditions.cancellation_token, Some(token) if token.is_cancelled());
+        let is_late = matches!(conditions.timeout, Some(timeout) if self.started.elapsed() > timeout);
+        let is_full = matches!(conditions.max_memory_bytes, Some(max_memory_bytes)
+            if self.estimated_memory_bytes::<State>(generated_bytes()) > max_memory_bytes.get());
+        *stop_reason = if is_cancelled {
+            Some(StopReason::Cancelled)
+        } else if is_late {
+            Some(StopReason::TimeLimit)
+        } else if is_full {
+            Some(StopReason::MemoryLimit)
+        } else {
+            None
+        };
+        stop_reason.is_some()
+    }
+
+    /// Stops checking for a reason other than the [`StopConditions`], unless already stopped.
+    pub(crate) fn stop(&self, reason: StopReason) {
+        self.stop_reason.lock().get_or_insert(reason);
+    }
+
+    pub(crate) fn stop_reason(&self) -> Option<StopReason> {
+        *self.stop_reason.lock()
+    }
+
+    fn estimated_memory_bytes<State>(&self, generated_bytes: usize) -> usize {
+        let heap_bytes = self.heap_bytes.load(Ordering::Relaxed)
+            .checked_div(self.heap_samples.load(Ordering::Relaxed))
+            .unwrap_or(0);
+        generated_bytes
+            + self.pending.load(Ordering::Relaxed) * (std::mem::size_of::<State>() + heap_bytes)
+    }
+
+    /// Notes that a checker thread finished, so the elapsed time stops advancing once all have.
+    pub(crate) fn record_finish(&self) {
+        let nanos = std::cmp::max(1, self.started.elapsed().as_nanos() as u64);
+        self.finished_nanos.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    /// Summarizes the explored state space, given coverage that only the checker tracks.
+    pub(crate) fn stats(&self, coverage: Coverage) -> CheckerStats {
+        CheckerStats {
+            states_per_depth: self.depth_counts.read().iter()
+                .map(|count| count.load(Ordering::Relaxed))
+                .collect(),
+            expanded_count: self.expanded.load(Ordering::Relaxed),
+            successor_count: self.successors.load(Ordering::Relaxed),
+            max_branching_factor: self.max_successors.load(Ordering::Relaxed),
+            actions_taken: coverage.taken,
+        }
+    }
+
+    /// Summarizes progress, given state that only the checker tracks.
+    pub(crate) fn status<State>(
+        &self, generated_count: usize, generated_bytes: usize, is_done: bool) -> CheckerStatus
+    {
+        let finished_nanos = self.finished_nanos.load(Ordering::Relaxed);
+        let elapsed = if is_done && finished_nanos != 0 {
+            Duration::from_nanos(finished_nanos)
+        } else {
+            self.started.elapsed()
+        };
+        let pending_count = self.pending.load(Ordering::Relaxed);
+        CheckerStatus {
+            generated_count,
+            pending_count,
+            max_depth: self.max_depth.load(Ordering::Relaxed),
+            elapsed,
+            estimated_memory_bytes: self.estimated_memory_bytes::<State>(generated_bytes),
+            is_done,
+        }
+    }
+}
@@ -0,0 +1,587 @@
+//! Private module for selective re-export.
+
+use crate::Fingerprint;
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use nohash_hasher::NoHashHasher;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::hash::BuildHasherDefault;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+
+/// Indicates where a [`Checker`] records the fingerprints of generated states. Configured via
+/// [`CheckerBuilder::state_store`].
+///
+/// [`Checker`]: crate::Checker
+/// [`CheckerBuilder::state_store`]: crate::CheckerBuilder::state_store
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateStore {
+    /// Retains every fingerprint in memory. This is the default and fastest option.
+    InMemory,
+    /// Retains recently generated fingerprints in memory, spilling the rest to sorted files.
+    ///
+    /// Only fingerprints are spilled. States awaiting evaluation remain in memory, so this is most
+    /// effective for models whose reachable state count dwarfs the size of the search frontier.
+    ///
+    /// If reading or writing a file fails, then the error is logged, and checking stops with
+    /// [`StopReason::Io`].
+    ///
+    /// [`StopReason::Io`]: crate::StopReason::Io
+    OnDisk {
+        /// The directory for spilled fingerprints, which is created if necessary. Files are
+        /// removed when the checker is dropped, as is the directory if it is then empty.
+        path: PathBuf,
+        /// Roughly how many bytes of fingerprints to retain in memory before spilling to disk.
+        cache_bytes: usize,
+    },
+    /// Records fingerprints in a fixed number of bits (a Bloom filter), akin to the "bitstate
+    /// hashing" mode of the SPIN model checker. Memory usage does not grow with the number of
+    /// generated states, but a new state whose bits were all set by earlier states is mistakenly
+    /// treated as already generated and is not checked. Checking is therefore not exhaustive,
+    /// although the likelihood of an omission is negligible while few bits are set. See
+    /// [`Checker::omission_probability`].
+    ///
+    /// Paths to states are not recorded, so this is only supported by checkers that track each
+    /// pending state's path, such as [`CheckerBuilder::spawn_dfs`].
+    ///
+    /// [`Checker::omission_probability`]: crate::Checker::omission_probability
+    /// [`CheckerBuilder::spawn_dfs`]: crate::CheckerBuilder::spawn_dfs
+    Bloom {
+        /// The number of bits, which is rounded up to a multiple of 64.
+        bits: usize,
+    },
+}
+
+/// A value associated with each generated fingerprint, encodable as a `u64`.
+pub(crate) trait StoreValue: Copy {
+    fn encode(self) -> u64;
+    fn decode(bits: u64) -> Self;
+}
+
+impl StoreValue for () {
+    fn encode(self) -> u64 { 0 }
+    fn decode(_bits: u64) -> Self {}
+}
+
+impl StoreValue for Option<Fingerprint> {
+    fn encode(self) -> u64 { self.map(Fingerprint::get).unwrap_or(0) }
+    fn decode(bits: u64) -> Self { Fingerprint::new(bits) }
+}
+
+/// The set of generated fingerprints (each with an associated value) shared by checker threads.
+pub(crate) enum Generated<V: StoreValue> {
+    InMemory(DashMap<Fingerprint, V, BuildHasherDefault<NoHashHasher<u64>>>),
+    OnDisk(DiskStore<V>),
+    Bloom(BloomFilter),
+}
+
+impl<V: StoreValue> Generated<V> {
+    pub(crate) fn new(state_store: &StateStore) -> Self {
+        match state_store {
+            StateStore::InMemory => Generated::InMemory(DashMap::default()),
+            StateStore::OnDisk { path, cache_bytes } => Generated::OnDisk(
+                DiskStore::new(path.clone(), *cache_bytes)),
+            StateStore::Bloom { bits } => Generated::Bloom(BloomFilter::new(*bits)),
+        }
+    }
+
+    /// Records a fingerprint, returning `false` if it was already present (in which case the
+    /// associated value is unchanged).
+    pub(crate) fn insert(&self, fingerprint: Fingerprint, value: V) -> bool {
+        match self {
+            Generated::InMemory(map) => {
+                if let Entry::Vacant(entry) = map.entry(fingerprint) {
+                    entry.insert(value);
+                    true
+                } else {
+                    false
+                }
+            },
+            Generated::OnDisk(store) => store.insert(fingerprint, value),
+            Generated::Bloom(filter) => filter.insert(fingerprint.get()),
+        }
+    }
+
+    /// Returns the value associated with a fingerprint if present. Always `None` for a Bloom
+    /// filter, which does not retain values.
+    pub(crate) fn get(&self, fingerprint: Fingerprint) -> Option<V> {
+        match self {
+            Generated::InMemory(map) => map.get(&fingerprint).map(|r| *r),
+            Generated::OnDisk(store) => store.get(fingerprint),
+            Generated::Bloom(_) => None,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Generated::InMemory(map) => map.len(),
+            Generated::OnDisk(store) => store.len.load(Ordering::Relaxed),
+            Generated::Bloom(filter) => filter.len.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns every fingerprint and associated value, or `None` if fingerprints are spilled to
+    /// disk or not retained.
+    pub(crate) fn to_vec(&self) -> Option<Vec<(Fingerprint, V)>> {
+        match self {
+            Generated::InMemory(map) => Some(map.iter().map(|r| (*r.key(), *r.value())).collect()),
+            Generated::OnDisk(_) | Generated::Bloom(_) => None,
+        }
+    }
+
+    /// Approximates the bytes of memory used to retain fingerprints.
+    pub(crate) fn memory_bytes(&self) -> usize {
+        match self {
+            Generated::InMemory(map) => map.len() * std::mem::size_of::<(Fingerprint, V)>(),
+            Generated::OnDisk(store) => store.shards.iter()
+                .map(|shard| shard.lock().memory_bytes())
+                .sum(),
+            Generated::Bloom(filter) => filter.words.len() * std::mem::size_of::<u64>(),
+        }
+    }
+
+    /// Estimates the probability that a newly generated state would be mistakenly treated as
+    /// previously generated, or `None` if that is not possible.
+    pub(crate) fn omission_probability(&self) -> Option<f64> {
+        match self {
+            Generated::InMemory(_) | Generated::OnDisk(_) => None,
+            Generated::Bloom(filter) => Some(filter.omission_probability()),
+        }
+    }
+
+    /// Indicates whether an I/O error occurred, in which case checking should stop, as states
+    /// are no longer spilled to disk.
+    pub(crate) fn has_failed(&self) -> bool {
+        match self {
+            Generated::InMemory(_) | Generated::Bloom(_) => false,
+            Generated::OnDisk(store) => store.has_failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A concurrent Bloom filter of fingerprints. Each fingerprint sets `BLOOM_HASH_COUNT` bits,
+/// whose positions are derived via double hashing.
+pub(crate) struct BloomFilter {
+    words: Vec<AtomicU64>,
+    len: AtomicUsize,
+}
+
+const BLOOM_HASH_COUNT: u64 = 3;
+
+impl BloomFilter {
+    fn new(bits: usize) -> Self {
+        let word_count = std::cmp::max(1, bits.div_ceil(64));
+        BloomFilter {
+            words: (0..word_count).map(|_| AtomicU64::new(0)).collect(),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Sets the bits for a fingerprint, returning `false` if they were already set.
+    fn insert(&self, key: u64) -> bool {
+        let mut is_new = false;
+        for (word, mask) in self.positions(key) {
+            let prev = self.words[word].fetch_or(mask, Ordering::Relaxed);
+            is_new |= prev & mask == 0;
+        }
+        if is_new { self.len.fetch_add(1, Ordering::Relaxed); }
+        is_new
+    }
+
+    /// Indicates whether the bits for a fingerprint are set.
+    fn contains(&self, key: u64) -> bool {
+        self.positions(key).all(|(word, mask)| self.words[word].load(Ordering::Relaxed) & mask != 0)
+    }
+
+    /// The word index and mask of each bit for a fingerprint.
+    fn positions(&self, h1: u64) -> impl Iterator<Item = (usize, u64)> {
+        let bit_count = self.words.len() as u64 * 64;
+        let h2 = h1.wrapping_mul(0x9E37_79B9_7F4A_7C15).rotate_left(31) | 1;
+        (0..BLOOM_HASH_COUNT).map(move |i| {
+            let bit = h1.wrapping_add(i.wrapping_mul(h2)) % bit_count;
+            ((bit / 64) as usize, 1 << (bit % 64))
+        })
+    }
+
+    /// The likelihood that every bit for a new fingerprint is already set, given the number of
+    /// fingerprints inserted so far.
+    fn omission_probability(&self) -> f64 {
+        let bit_count = self.words.len() as f64 * 64.0;
+        let len = self.len.load(Ordering::Relaxed) as f64;
+        let k = BLOOM_HASH_COUNT as f64;
+        (1.0 - (-k * len / bit_count).exp()).powf(k)
+    }
+}
+
+// The on-disk store is a simple log-structured merge tree. Fingerprints are partitioned across
+// shards (to reduce lock contention), and each shard buffers insertions in memory until its cache
+// fills, at which point the cache is written to an immutable sorted "run" file. Lookups consult
+// the cache and then each run. Every run has an in-memory Bloom filter, so that most lookups skip
+// runs lacking the fingerprint without reading them, and an index of the first fingerprint in
+// every block, so that other lookups read at most one block per run. Runs are merged in tiers:
+// once `TIER_RUN_COUNT` runs of the same level accumulate, they are merged into one run of the
+// next level. Each fingerprint is therefore rewritten a logarithmic number of times, and the
+// number of runs grows logarithmically with the number of fingerprints.
+
+const SHARD_COUNT: usize = 16;
+const RECORD_BYTES: usize = 16;
+const BLOCK_RECORDS: usize = 256;
+const TIER_RUN_COUNT: usize = 4;
+const RUN_BLOOM_BITS_PER_RECORD: usize = 10;
+
+pub(crate) struct DiskStore<V> {
+    dir: PathBuf,
+    shards: Vec<Mutex<Shard>>,
+    len: AtomicUsize,
+    has_failed: AtomicBool,
+    _value: PhantomData<V>,
+}
+
+struct Shard {
+    dir: PathBuf,
+    id: usize,
+    cache: HashMap<u64, u64, BuildHasherDefault<NoHashHasher<u64>>>,
+    cache_capacity: usize,
+    runs: Vec<Run>,
+    next_run_id: usize,
+}
+
+struct Run {
+    path: PathBuf,
+    file: File,
+    level: usize,
+    record_count: usize,
+    block_starts: Vec<u64>,
+    filter: BloomFilter,
+}
+
+impl<V: StoreValue> DiskStore<V> {
+    fn new(dir: PathBuf, cache_bytes: usize) -> Self {
+        let cache_capacity = std::cmp::max(1, cache_bytes / SHARD_COUNT / RECORD_BYTES);
+        let store = DiskStore {
+            dir: dir.clone(),
+            shards: (0..SHARD_COUNT)
+                .map(|id| Mutex::new(Shard {
+                    dir: dir.clone(),
+                    id,
+                    cache: HashMap::default(),
+                    cache_capacity,
+                    runs: Vec::new(),
+                    next_run_id: 0,
+                }))
+                .collect(),
+            len: AtomicUsize::new(0),
+            has_failed: AtomicBool::new(false),
+            _value: PhantomData,
+        };
+        if let Err(err) = fs::create_dir_all(&dir) {
+            store.fail("Unable to create state store", err);
+        }
+        store
+    }
+
+    fn shard(&self, fingerprint: Fingerprint) -> &Mutex<Shard> {
+        // Low bits are used by in-memory hash tables, so partition on high bits.
+        &self.shards[(fingerprint.get() >> 60) as usize % SHARD_COUNT]
+    }
+
+    fn insert(&self, fingerprint: Fingerprint, value: V) -> bool {
+        let mut shard = self.shard(fingerprint).lock();
+        match shard.get(fingerprint.get()) {
+            Ok(None) => {},
+            Ok(Some(_)) => return false,
+            Err(err) => self.fail("Unable to read state store", err),
+        }
+        shard.cache.insert(fingerprint.get(), value.encode());
+        // After a failure, fingerprints are retained in memory until checking stops.
+        if shard.cache.len() >= shard.cache_capacity && !self.has_failed.load(Ordering::Relaxed) {
+            if let Err(err) = shard.flush() {
+                self.fail("Unable to write state store", err);
+            }
+        }
+        self.len.fetch_add(1, Ordering::Relaxed);
+        true
+    }
+
+    fn get(&self, fingerprint: Fingerprint) -> Option<V> {
+        match self.shard(fingerprint).lock().get(fingerprint.get()) {
+            Ok(value) => value.map(V::decode),
+            Err(err) => {
+                self.fail("Unable to read state store", err);
+                None
+            },
+        }
+    }
+
+    /// Logs the first I/O error, after which checking stops.
+    fn fail(&self, context: &str, err: std::io::Error) {
+        if !self.has_failed.swap(true, Ordering::Relaxed) {
+            log::error!("{}. Stopping. dir={:?}, err={}", context, self.dir, err);
+        }
+    }
+}
+
+impl<V> Drop for DiskStore<V> {
+    fn drop(&mut self) {
+        for shard in &self.shards {
+            shard.lock().runs.clear(); // removes the files
+        }
+        // Fails if the directory contains other files, which are left alone.
+        let _ = fs::remove_dir(&self.dir);
+    }
+}
+
+impl Shard {
+    fn get(&mut self, key: u64) -> std::io::Result<Option<u64>> {
+        if let Some(value) = self.cache.get(&key) { return Ok(Some(*value)) }
+        // Newer runs are more likely to contain recently generated states.
+        for run in self.runs.iter_mut().rev() {
+            let found = run.get(key)?;
+            if found.is_some() { return Ok(found) }
+        }
+        Ok(None)
+    }
+
+    /// Writes the cache to a new run. The cache is retained if writing fails.
+    fn flush(&mut self) -> std::io::Result<()> {
+        let mut records: Vec<(u64, u64)> = self.cache.iter().map(|(k, v)| (*k, *v)).collect();
+        records.sort_unstable_by_key(|(key, _)| *key);
+        let path = self.next_run_path();
+        let run = Run::write(path, 0, records.len(), records.into_iter().map(Ok))?;
+        self.cache.clear();
+        self.runs.push(run);
+        while self.is_tier_full() {
+            self.merge_tier()?;
+        }
+        Ok(())
+    }
+
+    /// Indicates whether the newest `TIER_RUN_COUNT` runs have the same level. Levels never
+    /// increase from older to newer runs, so these are all the runs of that level.
+    fn is_tier_full(&self) -> bool {
+        self.runs.len() >= TIER_RUN_COUNT && {
+            let tier = &self.runs[self.runs.len() - TIER_RUN_COUNT..];
+            tier.iter().all(|run| run.level == tier[0].level)
+        }
+    }
+
+    /// Merges the newest `TIER_RUN_COUNT` runs into one run of the next level. The runs are
+    /// retained if merging fails.
+    fn merge_tier(&mut self) -> std::io::Result<()> {
+        let tier_start = self.runs.len() - TIER_RUN_COUNT;
+        let level = self.runs[tier_start].level + 1;
+        let record_count = self.runs[tier_start..].iter().map(|run| run.record_count).sum();
+        let mut readers = Vec::with_capacity(TIER_RUN_COUNT);
+        for run in &self.runs[tier_start..] {
+            let mut reader = BufReader::new(File::open(&run.path)?);
+            let head = read_record(&mut reader)?;
+            readers.push((reader, head));
+        }
+        let merged = std::iter::from_fn(|| {
+            let (i, record) = readers.iter()
+                .enumerate()
+                .filter_map(|(i, (_, head))| head.map(|record| (i, record)))
+                .min_by_key(|(_, (key, _))| *key)?;
+            let (reader, head) = &mut readers[i];
+            Some(read_record(reader).map(|next| {
+                *head = next;
+                record
+            }))
+        });
+        let path = self.next_run_path();
+        let run = Run::write(path, level, record_count, merged)?;
+        self.runs.truncate(tier_start); // removes the merged files
+        self.runs.push(run);
+        Ok(())
+    }
+
+    fn next_run_path(&mut self) -> PathBuf {
+        let path = self.dir.join(format!("shard-{:02}-run-{}.bin", self.id, self.next_run_id));
+        self.next_run_id += 1;
+        path
+    }
+
+    /// Approximates the bytes of memory used by the cache and the runs' indexes.
+    fn memory_bytes(&self) -> usize {
+        self.cache.len() * RECORD_BYTES
+            + self.runs.iter()
+                .map(|run| {
+                    run.block_starts.len() * std::mem::size_of::<u64>()
+                        + run.filter.words.len() * std::mem::size_of::<u64>()
+                })
+                .sum::<usize>()
+    }
+}
+
+impl Run {
+    /// Writes sorted records, removing the file if writing fails. `record_count` sizes the Bloom
+    /// filter.
+    fn write(
+        path: PathBuf, level: usize, record_count: usize,
+        records: impl Iterator<Item = std::io::Result<(u64, u64)>>)
+        -> std::io::Result<Self>
+    {
+        let mut run = Run {
+            file: File::create(&path)?,
+            path,
+            level,
+            record_count: 0,
+            block_starts: Vec::new(),
+            filter: BloomFilter::new(record_count * RUN_BLOOM_BITS_PER_RECORD),
+        };
+        // The file is removed if the run is dropped due to an error.
+        let mut writer = BufWriter::new(&run.file);
+        for record in records {
+            let (key, value) = record?;
+            if run.record_count.is_multiple_of(BLOCK_RECORDS) { run.block_starts.push(key); }
+            run.filter.insert(key);
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&value.to_le_bytes())?;
+            run.record_count += 1;
+        }
+        writer.flush()?;
+        drop(writer);
+        run.file = File::open(&run.path)?;
+        Ok(run)
+    }
+
+    fn get(&mut self, key: u64) -> std::io::Result<Option<u64>> {
+        if !self.filter.contains(key) { return Ok(None) }
+        let block = match self.block_starts.binary_search(&key) {
+            Ok(block) => block,
+            Err(0) => return Ok(None),
+            Err(next_block) => next_block - 1,
+        };
+        let first = block * BLOCK_RECORDS;
+        let count = std::cmp::min(BLOCK_RECORDS, self.record_count - first);
+        let mut buf = vec![0; count * RECORD_BYTES];
+        self.file.seek(SeekFrom::Start((first * RECORD_BYTES) as u64))?;
+        self.file.read_exact(&mut buf)?;
+        let records: Vec<_> = buf.chunks_exact(RECORD_BYTES).map(decode_record).collect();
+        Ok(records.binary_search_by_key(&key, |(key, _)| *key).ok().map(|i| records[i].1))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn decode_record(bytes: &[u8]) -> (u64, u64) {
+    let mut key = [0; 8];
+    let mut value = [0; 8];
+    key.copy_from_slice(&bytes[..8]);
+    value.copy_from_slice(&bytes[8..RECORD_BYTES]);
+    (u64::from_le_bytes(key), u64::from_le_bytes(value))
+}
+
+fn read_record(reader: &mut impl Read) -> std::io::Result<Option<(u64, u64)>> {
+    let mut buf = [0; RECORD_BYTES];
+    match reader.read_exact(&mut buf) {
+        Ok(()) => Ok(Some(decode_record(&buf))),
+        Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("stateright-{}-{}", name, std::process::id()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fp(n: u64) -> Fingerprint {
+        // Spread keys across shards and blocks.
+        Fingerprint::new(n.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1).unwrap()
+    }
+
+    #[test]
+    fn on_disk_store_matches_in_memory_store() {
+        let dir = temp_dir("store-test");
+        let state_store = StateStore::OnDisk { path: dir.clone(), cache_bytes: 4096 };
+        let on_disk = Generated::<Option<Fingerprint>>::new(&state_store);
+        let in_memory = Generated::<Option<Fingerprint>>::new(&StateStore::InMemory);
+        for n in 0..20_000 {
+            let parent = if n == 0 { None } else { Some(fp(n - 1)) };
+            assert!(on_disk.insert(fp(n), parent));
+            assert!(in_memory.insert(fp(n), parent));
+        }
+        for n in 0..20_000 {
+            assert!(!on_disk.insert(fp(n), None));
+            assert_eq!(on_disk.get(fp(n)), in_memory.get(fp(n)));
+        }
+        assert_eq!(on_disk.get(fp(20_000)), None);
+        assert_eq!(on_disk.len(), 20_000);
+
+        // Runs were merged, and files are removed on drop. Each shard flushed fewer than 4^4
+        // runs, leaving at most 3 runs for each of 4 levels.
+        assert!(fs::read_dir(&dir).unwrap().count() <= SHARD_COUNT * (TIER_RUN_COUNT - 1) * 4);
+        drop(on_disk);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn merges_runs_in_tiers() {
+        let dir = temp_dir("store-tier-test");
+        let store = DiskStore::<()>::new(dir.clone(), 0); // flushes every insertion
+        let levels = |store: &DiskStore<()>| -> Vec<usize> {
+            store.shards[0].lock().runs.iter().map(|run| run.level).collect()
+        };
+        // Small fingerprints are all in the first shard.
+        for n in 1..=16 { assert!(store.insert(Fingerprint::new(n).unwrap(), ())); }
+        assert_eq!(levels(&store), vec![2]);
+        for n in 17..=22 { assert!(store.insert(Fingerprint::new(n).unwrap(), ())); }
+        assert_eq!(levels(&store), vec![2, 1, 0, 0]);
+        for n in 1..=22 {
+            assert_eq!(store.get(Fingerprint::new(n).unwrap()), Some(()));
+            assert!(!store.insert(Fingerprint::new(n).unwrap(), ()));
+        }
+        assert_eq!(store.get(Fingerprint::new(23).unwrap()), None);
+        drop(store);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn retains_fingerprints_in_memory_after_io_error() {
+        // A directory cannot be created within a file.
+        let file = temp_dir("store-failure-test");
+        fs::write(&file, b"").unwrap();
+        let state_store = StateStore::OnDisk { path: file.join("store"), cache_bytes: 0 };
+        let on_disk = Generated::<Option<Fingerprint>>::new(&state_store);
+        assert!(on_disk.has_failed());
+        for n in 0..100 {
+            assert!(on_disk.insert(fp(n), None));
+        }
+        for n in 0..100 {
+            assert!(!on_disk.insert(fp(n), None));
+        }
+        assert_eq!(on_disk.len(), 100);
+        drop(on_disk);
+        fs::remove_file(&file).unwrap();
+    }
+
+    #[test]
+    fn bloom_filter_omits_few_states_while_sparse() {
+        let bloom = Generated::<()>::new(&StateStore::Bloom { bits: 1 << 20 });
+        assert_eq!(bloom.omission_probability(), Some(0.0));
+        let inserted = (0..20_000).filter(|n| bloom.insert(fp(*n), ())).count();
+        assert!(inserted > 19_990, "inserted={}", inserted);
+        assert_eq!(bloom.len(), inserted);
+        for n in 0..20_000 {
+            assert!(!bloom.insert(fp(n), ()));
+        }
+        assert_eq!(bloom.memory_bytes(), 1 << 17);
+
+        // (1 - e^(-3 * 20,000 / 2^20))^3
+        let p = bloom.omission_probability().unwrap();
+        assert!(0.000_16 < p && p < 0.000_18, "p={}", p);
+    }
+}
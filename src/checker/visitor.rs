@@ -1,4 +1,7 @@
-use crate::{Model, Path};
+use crate::{fingerprint, Model, Path};
+use rand::Rng;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::Hash;
 use std::sync::{Arc, Mutex};
 
 /// A visitor to apply to every [`Path`] of the checked [`Model`].
@@ -59,3 +62,133 @@ where M: Model,
         (recorder, accessor)
     }
 }
+
+/// A [`CheckerVisitor`] that records the fingerprints of states evaluated by the model checker,
+/// which is considerably cheaper than recording the states themselves. Useful for measuring the
+/// combined coverage of several partial runs via [`MergedReport::merge_visited`].
+///
+/// # Example
+///
+/// ```
+/// # use stateright::*; let model = ();
+/// let (recorder, accessor) = FingerprintRecorder::new_with_accessor();
+/// model.checker().visitor(recorder).spawn_bfs().join();
+/// assert_eq!(accessor().len(), 1);
+/// ```
+///
+/// [`MergedReport::merge_visited`]: crate::MergedReport::merge_visited
+pub struct FingerprintRecorder(Arc<Mutex<HashSet<u64>>>);
+impl<M> CheckerVisitor<M> for FingerprintRecorder
+where M: Model,
+      M::State: Hash,
+{
+    fn visit(&self, _: &M, path: Path<M::State, M::Action>) {
+        self.0.lock().unwrap().insert(fingerprint(path.last_state()).get());
+    }
+}
+impl FingerprintRecorder {
+    /// Instantiates a ([`FingerprintRecorder`], accessor) pair.
+    pub fn new_with_accessor() -> (Self, impl Fn() -> HashSet<u64>) {
+        let recorder = Self(Arc::new(Mutex::new(HashSet::new())));
+        let accessor = { let r = Arc::clone(&recorder.0); move || r.lock().unwrap().clone() };
+        (recorder, accessor)
+    }
+}
+
+/// A state sampled by a [`StateSampler`], including the context in which it was visited.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct StateSample {
+    /// The number of actions taken to reach the state.
+    pub depth: usize,
+    /// The fingerprint of the state, as a string since JSON numbers cannot represent it
+    /// precisely.
+    pub fingerprint: String,
+    /// The action that led to the state, serialized as JSON. `None` for an initial state.
+    pub action: Option<serde_json::Value>,
+    /// The state, serialized as JSON.
+    pub state: serde_json::Value,
+}
+
+/// How a [`StateSampler`] selects states.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sampling {
+    /// Retains up to the specified number of states chosen uniformly at random from all
+    /// visited states.
+    Uniform(usize),
+    /// Retains up to the specified number of states chosen uniformly at random from the visited
+    /// states at each depth, so that deep (often sparse) regions of the state space are not
+    /// drowned out by shallow ones.
+    StratifiedByDepth(usize),
+}
+
+/// A [`CheckerVisitor`] that retains a random sample of states evaluated by the model checker,
+/// along with their depth and the action that led to them, for offline analysis. Uses reservoir
+/// sampling, so memory is bounded by the sample size rather than the size of the state space.
+///
+/// # Example
+///
+/// ```
+/// # use stateright::*; let model = ();
+/// let (sampler, accessor) = StateSampler::new_with_accessor(Sampling::Uniform(100));
+/// model.checker().visitor(sampler).spawn_bfs().join();
+/// for sample in accessor() {
+///     println!("{}", serde_json::to_string(&sample).unwrap());
+/// }
+/// ```
+pub struct StateSampler(Sampling, Arc<Mutex<BTreeMap<usize, Reservoir>>>);
+
+#[derive(Default)]
+struct Reservoir { seen: usize, samples: Vec<StateSample> }
+
+impl<M> CheckerVisitor<M> for StateSampler
+where M: Model,
+      M::State: Hash + serde::Serialize,
+      M::Action: serde::Serialize,
+{
+    fn visit(&self, _: &M, path: Path<M::State, M::Action>) {
+        let (stratum, capacity) = match self.0 {
+            Sampling::Uniform(capacity) => (0, capacity),
+            Sampling::StratifiedByDepth(capacity) => (path.depth(), capacity),
+        };
+        let mut reservoirs = self.1.lock().unwrap();
+        let reservoir = reservoirs.entry(stratum).or_default();
+        reservoir.seen += 1;
+        let index = if reservoir.samples.len() < capacity {
+            reservoir.samples.len()
+        } else {
+            let index = rand::thread_rng().gen_range(0, reservoir.seen);
+            if index >= capacity { return }
+            index
+        };
+        let sample = StateSample {
+            depth: path.depth(),
+            fingerprint: format!("{}", fingerprint(path.last_state())),
+            action: path.last_action().map(|a| serde_json::to_value(a).expect("serializable action")),
+            state: serde_json::to_value(path.last_state()).expect("serializable state"),
+        };
+        if index == reservoir.samples.len() {
+            reservoir.samples.push(sample);
+        } else {
+            reservoir.samples[index] = sample;
+        }
+    }
+}
+
+impl StateSampler {
+    /// Instantiates a ([`StateSampler`], accessor) pair. The accessor returns the samples
+    /// ordered by depth.
+    pub fn new_with_accessor(sampling: Sampling) -> (Self, impl Fn() -> Vec<StateSample>) {
+        let sampler = Self(sampling, Arc::new(Mutex::new(BTreeMap::new())));
+        let accessor = {
+            let r = Arc::clone(&sampler.1);
+            move || {
+                let mut samples: Vec<_> = r.lock().unwrap().values()
+                    .flat_map(|r| r.samples.iter().cloned())
+                    .collect();
+                samples.sort_by_key(|s| s.depth);
+                samples
+            }
+        };
+        (sampler, accessor)
+    }
+}
@@ -133,6 +133,8 @@
 #[warn(missing_docs)]
 
 mod checker;
+mod ltl;
+mod product;
 use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
 #[cfg(test)]
@@ -140,6 +142,8 @@ mod test_util;
 
 pub mod actor;
 pub use checker::*;
+pub use ltl::*;
+pub use product::*;
 pub mod semantics;
 pub mod util;
 
@@ -174,6 +178,30 @@ pub trait Model: Sized {
             .map(|next_state| format!("{:?}", next_state))
     }
 
+    /// Summarizes how a step changed the state, such as for highlighting in the
+    /// [Explorer](CheckerBuilder::serve). Each line is prefixed by `"- "` if removed, `"+ "` if
+    /// added, or `"  "` if unchanged. Defaults to a diff of the pretty-printed states.
+    fn display_diff(&self, last_state: &Self::State, next_state: &Self::State) -> Option<String>
+    where Self::State: Debug
+    {
+        Some(util::diff_lines(&format!("{:#?}", last_state), &format!("{:#?}", next_state)))
+    }
+
+    /// Identifies the thread of control (such as an actor) that takes an action, which
+    /// [`CheckerBuilder::spawn_context_bounded`] uses to count preemptions. [`None`] indicates an
+    /// action of the environment, which never counts as a preemption.
+    fn action_context(&self, _action: &Self::Action) -> Option<usize> { None }
+
+    /// Names the kind of an action (such as an enum variant) for [`Checker::coverage`]. Defaults
+    /// to the [`Debug`] representation of the action up to its first field.
+    fn action_kind(&self, action: &Self::Action) -> String
+    where Self::Action: Debug,
+    {
+        let mut kind = format!("{:?}", action);
+        if let Some(i) = kind.find(['(', '{', ' ']) { kind.truncate(i); }
+        kind
+    }
+
     /// Returns an [SVG](https://developer.mozilla.org/en-US/docs/Web/SVG) representation of a
     /// [`Path`] for this model.
     fn as_svg(&self, _path: Path<Self::State, Self::Action>) -> Option<String> { None }
@@ -217,6 +245,11 @@ pub trait Model: Sized {
     /// Indicates whether a state is within the state space that should be model checked.
     fn within_boundary(&self, _state: &Self::State) -> bool { true }
 
+    /// Indicates whether a state is an intended terminal state, such as one in which every
+    /// request has been served, in which case it is not a deadlock even though no action changes
+    /// the state. See [`Checker::deadlocks`] and [`Property::deadlock_free`].
+    fn is_terminal(&self, _state: &Self::State) -> bool { false }
+
     /// Instantiates a [`CheckerBuilder`] for this model.
     fn checker(self) -> CheckerBuilder<Self>
     where Self: Send + Sync + 'static,
@@ -231,10 +264,28 @@ pub trait Model: Sized {
 /// model checker would find a counterexample) or "a proposal is *eventually* accepted" (for
 /// which the model checker would find a counterexample path leading from the initial state
 /// through to a terminal state).
+///
+/// Construct properties via functions such as [`Property::always`], as the struct has private
+/// fields.
 pub struct Property<M: Model> {
     pub expectation: Expectation,
     pub name: &'static str,
     pub condition: fn(&M, &M::State) -> bool,
+    /// A condition over each step (last state, action, and next state), which must also hold for
+    /// an `always` property. See [`Property::always_action`].
+    #[allow(clippy::type_complexity)]
+    pub(crate) action_condition: Option<fn(&M, &M::State, &M::Action, &M::State) -> bool>,
+    /// For an `eventually` property, a condition that obliges a later state to satisfy
+    /// `condition`, or `None` if the initial state does. See [`Property::leads_to`].
+    pub(crate) trigger: Option<fn(&M, &M::State) -> bool>,
+    /// For an `eventually` property, whether the property holds along the behavior that repeats
+    /// a loop forever, given the states from the start of the loop through its return to that
+    /// state. Defaults to requiring that `condition` meet each obligation within the loop. See
+    /// [`LtlModel`].
+    #[allow(clippy::type_complexity)]
+    pub(crate) lasso_condition: Option<fn(&M, &[&M::State]) -> bool>,
+    /// Whether the condition is known to be monotone. See [`Property::monotone`].
+    pub(crate) is_monotone: bool,
 }
 impl<M: Model> Property<M> {
     /// An invariant that defines a [safety
@@ -242,7 +293,15 @@ impl<M: Model> Property<M> {
     /// discover a counterexample.
     pub fn always(name: &'static str, condition: fn(&M, &M::State) -> bool)
                   -> Property<M> {
-        Property { expectation: Expectation::Always, name, condition }
+        Property {
+            expectation: Expectation::Always,
+            name,
+            condition,
+            action_condition: None,
+            trigger: None,
+            lasso_condition: None,
+            is_monotone: false,
+        }
     }
 
     /// An invariant that defines a [liveness
@@ -257,14 +316,169 @@ impl<M: Model> Property<M> {
     /// by the cycle-closing edge will ignored -- a false negative.
     pub fn eventually(name: &'static str, condition: fn(&M, &M::State) -> bool)
                       -> Property<M> {
-        Property { expectation: Expectation::Eventually, name, condition }
+        Property {
+            expectation: Expectation::Eventually,
+            name,
+            condition,
+            action_condition: None,
+            trigger: None,
+            lasso_condition: None,
+            is_monotone: false,
+        }
+    }
+
+    /// An invariant over steps rather than states, such as "a node never votes twice in a term",
+    /// which would otherwise require recording past actions in the state. The condition is
+    /// evaluated for each (last state, action, next state) triple, and a counterexample ends with
+    /// the step that violates it.
+    ///
+    /// Checkers evaluate the condition for the steps from a state while visiting that state, so
+    /// each such step is generated an extra time.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stateright::{Checker, Model, Property};
+    /// # struct Counter;
+    /// # impl Model for Counter {
+    /// #     type State = u8;
+    /// #     type Action = u8;
+    /// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+    /// #     fn actions(&self, s: &u8, actions: &mut Vec<u8>) { if *s < 5 { actions.extend([1, 2]) } }
+    /// #     fn next_state(&self, s: &u8, a: u8) -> Option<u8> { Some(s + a) }
+    /// #     fn properties(&self) -> Vec<Property<Self>> { vec![
+    /// Property::<Counter>::always_action("increases by one", |_, last, _, next| *next == last + 1)
+    /// #     ] }
+    /// # }
+    /// # let checker = Counter.checker().spawn_bfs().join();
+    /// # checker.assert_discovery("increases by one", vec![2]);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn always_action(
+        name: &'static str,
+        condition: fn(&M, &M::State, &M::Action, &M::State) -> bool)
+        -> Property<M>
+    {
+        Property {
+            expectation: Expectation::Always,
+            name,
+            condition: |_, _| true,
+            action_condition: Some(condition),
+            trigger: None,
+            lasso_condition: None,
+            is_monotone: false,
+        }
+    }
+
+    /// A [liveness property](https://en.wikipedia.org/wiki/Liveness) requiring every state that
+    /// satisfies `trigger` to be followed (or itself satisfied) by a state that satisfies
+    /// `condition`, such as "a request gets a response". An `eventually` property is the special
+    /// case where only the initial state triggers the obligation.
+    ///
+    /// As for `eventually` properties, a counterexample can end in a terminal state, and
+    /// [`Checker::boundary_limited_properties`] flags a property whose obligation was unmet where
+    /// the boundary pruned a path. [`CheckerBuilder::spawn_dfs`] also finds a counterexample that
+    /// ends by returning to an earlier state (a "lasso"), such that the obligation is never met
+    /// while repeating the loop.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use stateright::{Checker, Model, Property};
+    /// # struct Server;
+    /// # impl Model for Server {
+    /// #     type State = bool; // is requested
+    /// #     type Action = &'static str;
+    /// #     fn init_states(&self) -> Vec<bool> { vec![false] }
+    /// #     fn actions(&self, s: &bool, actions: &mut Vec<Self::Action>) {
+    /// #         if *s { actions.extend(["respond", "defer"]) } else { actions.push("request") }
+    /// #     }
+    /// #     fn next_state(&self, _: &bool, a: Self::Action) -> Option<bool> {
+    /// #         Some(a != "respond")
+    /// #     }
+    /// #     fn properties(&self) -> Vec<Property<Self>> { vec![
+    /// Property::<Server>::leads_to("request gets response",
+    ///     |_, is_requested| *is_requested,
+    ///     |_, is_requested| !is_requested)
+    /// #     ] }
+    /// # }
+    /// // The server can defer a request forever.
+    /// let checker = Server.checker().spawn_dfs().join();
+    /// checker.assert_discovery("request gets response", vec!["request", "defer"]);
+    /// ```
+    pub fn leads_to(
+        name: &'static str,
+        trigger: fn(&M, &M::State) -> bool,
+        condition: fn(&M, &M::State) -> bool)
+        -> Property<M>
+    {
+        Property {
+            expectation: Expectation::Eventually,
+            name,
+            condition,
+            action_condition: None,
+            trigger: Some(trigger),
+            lasso_condition: None,
+            is_monotone: false,
+        }
     }
 
     /// Something that should be possible in the model. The model checker will try to discover an
     /// example.
     pub fn sometimes(name: &'static str, condition: fn(&M, &M::State) -> bool)
                      -> Property<M> {
-        Property { expectation: Expectation::Sometimes, name, condition }
+        Property {
+            expectation: Expectation::Sometimes,
+            name,
+            condition,
+            action_condition: None,
+            trigger: None,
+            lasso_condition: None,
+            is_monotone: false,
+        }
+    }
+
+    /// A built-in property named `"deadlock free"`, which requires that every state either has
+    /// a successor that differs from it (including one beyond [`Model::within_boundary`]) or is
+    /// an intended terminal state per [`Model::is_terminal`]. A state whose only successor is
+    /// itself is therefore a deadlock, as for [`Checker::deadlocks`], which enumerates deadlocks
+    /// rather than reporting one.
+    pub fn deadlock_free() -> Property<M>
+    where M::State: Hash,
+    {
+        Property::always("deadlock free", |model, state| {
+            model.is_terminal(state) || model.next_states(state).iter().any(|next_state| {
+                !model.within_boundary(next_state) || fingerprint(next_state) != fingerprint(state)
+            })
+        })
+    }
+
+    /// Declares that once the condition holds for a state, it also holds for every state reachable
+    /// from that state (e.g. "once committed, always committed"). When every property either has
+    /// a discovery or is monotone and holds, no successor can affect the outcome, so the
+    /// [`CheckerBuilder::spawn_bfs`] and [`CheckerBuilder::spawn_dfs`] checkers skip the
+    /// successors. Declaring a property that is not actually monotone can hide discoveries.
+    pub fn monotone(self) -> Property<M> {
+        Property { is_monotone: true, .. self }
+    }
+
+    /// Indicates whether the condition holds for a state and, for an action property, each step
+    /// from that state.
+    pub(crate) fn holds(&self, model: &M, state: &M::State) -> bool {
+        (self.condition)(model, state) && self.violating_step(model, state).is_none()
+    }
+
+    /// Indicates whether a state obliges a later state to satisfy the condition of an
+    /// `eventually` property. Without a trigger, only the initial state does.
+    pub(crate) fn is_triggered(&self, model: &M, state: &M::State) -> bool {
+        self.trigger.is_some_and(|trigger| trigger(model, state))
+    }
+
+    /// Returns the first step from a state that violates the action condition, if any.
+    pub(crate) fn violating_step(&self, model: &M, state: &M::State) -> Option<(M::Action, M::State)> {
+        let action_condition = self.action_condition?;
+        model.next_steps(state).into_iter()
+            .find(|(action, next_state)| !action_condition(model, state, action, next_state))
     }
 }
 impl<M: Model> Clone for Property<M> {
@@ -273,12 +487,17 @@ impl<M: Model> Clone for Property<M> {
             expectation: self.expectation.clone(),
             name: self.name,
             condition: self.condition,
+            action_condition: self.action_condition,
+            trigger: self.trigger,
+            lasso_condition: self.lasso_condition,
+            is_monotone: self.is_monotone,
         }
     }
 }
 
 /// Indicates whether a property is always, eventually, or sometimes true.
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(serde::Serialize)]
 pub enum Expectation {
     /// The property is true for all reachable states.
     Always,
@@ -316,18 +535,127 @@ impl Model for () {
     }
 }
 
-// Helpers for stable hashing, wherein hashes should not vary across builds.
+// Helpers for stable hashing, wherein hashes should not vary across builds or platforms, so that
+// fingerprints, discovery paths, and explorer URLs can be shared between machines.
 mod stable {
-    use ahash::{AHasher, RandomState};
+    use std::convert::TryInto;
+    use std::hash::{BuildHasher, Hasher};
 
     const KEY1: u64 = 123_456_789_987_654_321;
     const KEY2: u64 = 98_765_432_123_456_789;
+    const MULTIPLE: u64 = 6_364_136_223_846_793_005;
+
+    pub(crate) fn hasher() -> StableHasher {
+        StableHasher { buffer: KEY1, pad: KEY2 }
+    }
+
+    /// Builds [`StableHasher`]s, for collections whose iteration order should be consistent
+    /// across platforms.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct BuildStableHasher;
+
+    impl BuildHasher for BuildStableHasher {
+        type Hasher = StableHasher;
+
+        fn build_hasher(&self) -> StableHasher {
+            hasher()
+        }
+    }
+
+    /// A multiply-fold hasher whose output depends only on the hashed data. Unlike `AHasher`,
+    /// the algorithm does not vary with CPU features (such as AES instructions), endianness, or
+    /// pointer width.
+    #[derive(Clone, Debug)]
+    pub struct StableHasher {
+        buffer: u64,
+        pad: u64,
+    }
+
+    impl StableHasher {
+        #[inline(always)]
+        fn update(&mut self, data: u64) {
+            self.buffer = folded_multiply(data ^ self.buffer, MULTIPLE);
+        }
+    }
 
-    pub(crate) fn hasher() -> AHasher {
-        AHasher::new_with_keys(KEY1, KEY2)
+    impl Hasher for StableHasher {
+        #[inline]
+        fn write(&mut self, bytes: &[u8]) {
+            self.update(bytes.len() as u64);
+            let mut chunks = bytes.chunks_exact(8);
+            for chunk in &mut chunks {
+                self.update(u64::from_le_bytes(chunk.try_into().unwrap()));
+            }
+            let remainder = chunks.remainder();
+            if !remainder.is_empty() {
+                let mut last = [0; 8];
+                last[..remainder.len()].copy_from_slice(remainder);
+                self.update(u64::from_le_bytes(last));
+            }
+        }
+
+        #[inline]
+        fn write_u8(&mut self, i: u8) { self.update(i as u64); }
+
+        #[inline]
+        fn write_u16(&mut self, i: u16) { self.update(i as u64); }
+
+        #[inline]
+        fn write_u32(&mut self, i: u32) { self.update(i as u64); }
+
+        #[inline]
+        fn write_u64(&mut self, i: u64) { self.update(i); }
+
+        #[inline]
+        fn write_u128(&mut self, i: u128) {
+            self.update(i as u64);
+            self.update((i >> 64) as u64);
+        }
+
+        #[inline]
+        fn write_usize(&mut self, i: usize) { self.update(i as u64); }
+
+        #[inline]
+        fn finish(&self) -> u64 {
+            let rot = (self.pad & 63) as u32;
+            folded_multiply(self.buffer, self.pad).rotate_left(rot)
+        }
+    }
+
+    /// Multiplies as 128-bit integers, then folds the high and low halves together.
+    #[inline(always)]
+    fn folded_multiply(x: u64, y: u64) -> u64 {
+        let product = (x as u128).wrapping_mul(y as u128);
+        (product as u64) ^ ((product >> 64) as u64)
     }
 
-    pub(crate) fn build_hasher() -> RandomState {
-        RandomState::with_seeds(KEY1, KEY2)
+    #[cfg(test)]
+    mod test {
+        use std::hash::{Hash, Hasher};
+
+        fn hash<T: Hash>(value: T) -> u64 {
+            let mut hasher = super::hasher();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        #[test]
+        fn hashes_are_stable() {
+            // Hard-coded so that any change to the algorithm (which would invalidate
+            // previously shared fingerprints) is deliberate.
+            assert_eq!(hash(()), 15347456163014516016);
+            assert_eq!(hash(1_u8), 8649875134658024401);
+            assert_eq!(hash(1_usize), hash(1_u64));
+            assert_eq!(hash("stateright"), 5580193231889236704);
+            assert_eq!(hash(vec![(1_u32, 'a'), (2, 'b')]), 17756105403094084567);
+        }
+
+        #[test]
+        fn distinguishes_values() {
+            assert_ne!(hash(1_u8), hash(2_u8));
+            assert_ne!(hash("ab"), hash("ab\0"));
+            assert_ne!(hash(("a", "bc")), hash(("ab", "c")));
+            assert_ne!(hash(vec![1_u64, 2]), hash(vec![2_u64, 1]));
+        }
     }
 }
@@ -0,0 +1,460 @@
+//! Private module for selective re-export.
+
+use crate::{Expectation, Model, Property};
+use std::collections::BTreeSet;
+
+/// A formula in a stuttering-insensitive subset of [linear temporal
+/// logic](https://en.wikipedia.org/wiki/Linear_temporal_logic) over the states of a [`Model`],
+/// which [`LtlModel`] checks. Formulas are built from named atoms with [`Ltl::always`],
+/// [`Ltl::eventually`], [`Ltl::until`], and boolean connectives. There is no "next" operator, so
+/// a formula cannot distinguish a behavior from one that repeats (stutters on) some of its
+/// states.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Ltl, Model};
+/// # struct Handoff;
+/// # impl Model for Handoff {
+/// #     type State = u8;
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, _: &u8, _: &mut Vec<()>) {}
+/// #     fn next_state(&self, _: &u8, _: ()) -> Option<u8> { None }
+/// # }
+/// let sender_owns = Ltl::<Handoff>::atom("sender owns", |_, s| *s == 0);
+/// let receiver_owns = Ltl::atom("receiver owns", |_, s| *s == 2);
+/// // The sender retains ownership until the receiver takes it, which it then keeps.
+/// let handoff = sender_owns.until(Ltl::always(receiver_owns));
+/// ```
+pub struct Ltl<M: Model>(Formula<M>);
+
+enum Formula<M: Model> {
+    Atom(&'static str, fn(&M, &M::State) -> bool),
+    Not(Box<Formula<M>>),
+    And(Box<Formula<M>>, Box<Formula<M>>),
+    Or(Box<Formula<M>>, Box<Formula<M>>),
+    Always(Box<Formula<M>>),
+    Eventually(Box<Formula<M>>),
+    Until(Box<Formula<M>>, Box<Formula<M>>),
+}
+
+impl<M: Model> Ltl<M> {
+    /// A named condition on a single state.
+    pub fn atom(name: &'static str, condition: fn(&M, &M::State) -> bool) -> Self {
+        Ltl(Formula::Atom(name, condition))
+    }
+
+    /// Holds if the formula holds for every suffix of the behavior.
+    pub fn always(formula: Self) -> Self {
+        Ltl(Formula::Always(Box::new(formula.0)))
+    }
+
+    /// Holds if the formula holds for some suffix of the behavior.
+    pub fn eventually(formula: Self) -> Self {
+        Ltl(Formula::Eventually(Box::new(formula.0)))
+    }
+
+    /// Holds if `other` holds for some suffix of the behavior and this formula holds for every
+    /// longer suffix.
+    pub fn until(self, other: Self) -> Self {
+        Ltl(Formula::Until(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// Holds if both formulas hold.
+    pub fn and(self, other: Self) -> Self {
+        Ltl(Formula::And(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// Holds if either formula holds.
+    pub fn or(self, other: Self) -> Self {
+        Ltl(Formula::Or(Box::new(self.0), Box::new(other.0)))
+    }
+
+    /// Holds if `other` holds whenever this formula holds.
+    pub fn implies(self, other: Self) -> Self {
+        self.not().or(other)
+    }
+
+    /// Holds if this formula does not.
+    #[allow(clippy::should_implement_trait)]
+    pub fn not(self) -> Self {
+        Ltl(Formula::Not(Box::new(self.0)))
+    }
+}
+
+impl<M: Model> std::fmt::Display for Ltl<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fn write<M: Model>(formula: &Formula<M>, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match formula {
+                Formula::Atom(name, _) => write!(f, "{}", name),
+                Formula::Not(a) => { write!(f, "!")?; write(a, f) }
+                Formula::And(a, b) => {
+                    write!(f, "(")?; write(a, f)?; write!(f, " && ")?; write(b, f)?; write!(f, ")")
+                }
+                Formula::Or(a, b) => {
+                    write!(f, "(")?; write(a, f)?; write!(f, " || ")?; write(b, f)?; write!(f, ")")
+                }
+                Formula::Always(a) => { write!(f, "[]")?; write(a, f) }
+                Formula::Eventually(a) => { write!(f, "<>")?; write(a, f) }
+                Formula::Until(a, b) => {
+                    write!(f, "(")?; write(a, f)?; write!(f, " U ")?; write(b, f)?; write!(f, ")")
+                }
+            }
+        }
+        write(&self.0, f)
+    }
+}
+
+/// A node of a compiled formula, in negation normal form. Children precede their parents.
+enum Node<M: Model> {
+    Literal(fn(&M, &M::State) -> bool, bool),
+    And(usize, usize),
+    Or(usize, usize),
+    Always(usize),
+    Eventually(usize),
+    Until(usize, usize),
+    Release(usize, usize),
+}
+
+/// The state of the monitor automaton for an [`LtlModel`]: the obligations remaining for the rest
+/// of the behavior, as a disjunction of conjunctions of formula nodes. No disjunct means that the
+/// formula is violated, while an empty disjunct means that it is satisfied.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct LtlMonitor(BTreeSet<BTreeSet<usize>>);
+
+impl LtlMonitor {
+    fn satisfied() -> Self {
+        LtlMonitor(std::iter::once(BTreeSet::new()).collect())
+    }
+
+    fn violated() -> Self {
+        LtlMonitor(BTreeSet::new())
+    }
+
+    fn obligation(node: usize) -> Self {
+        LtlMonitor(std::iter::once(std::iter::once(node).collect()).collect())
+    }
+
+    /// Indicates that the formula holds regardless of the rest of the behavior.
+    pub fn is_satisfied(&self) -> bool {
+        self.0.contains(&BTreeSet::new())
+    }
+
+    /// Indicates that the formula fails regardless of the rest of the behavior.
+    pub fn is_violated(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn and(&self, other: &Self) -> Self {
+        let mut disjuncts = BTreeSet::new();
+        for a in &self.0 {
+            for b in &other.0 {
+                disjuncts.insert(a.union(b).copied().collect());
+            }
+        }
+        LtlMonitor::absorbed(disjuncts)
+    }
+
+    fn or(&self, other: &Self) -> Self {
+        LtlMonitor::absorbed(self.0.union(&other.0).cloned().collect())
+    }
+
+    /// Drops each disjunct that is implied by another, keeping the monitor states canonical.
+    fn absorbed(disjuncts: BTreeSet<BTreeSet<usize>>) -> Self {
+        LtlMonitor(disjuncts.iter()
+            .filter(|d| !disjuncts.iter().any(|other| other != *d && other.is_subset(d)))
+            .cloned()
+            .collect())
+    }
+}
+
+/// Checks that the behaviors of a model satisfy an [`Ltl`] formula, by pairing each state with the
+/// state of a monitor automaton compiled from the formula. The resulting model has a single
+/// `eventually`-style property, for which:
+///
+/// - A path to a state after which no continuation can satisfy the formula is a counterexample.
+/// - A path ending in a terminal state is a counterexample if the formula fails when that state
+///   repeats forever.
+/// - [`CheckerBuilder::spawn_dfs`] also finds a path ending in a loop (a "lasso") if the formula
+///   fails when the loop repeats forever.
+///
+/// Other properties of the original model are not checked.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Checker, Ltl, LtlModel, Model};
+/// # struct Handoff;
+/// # impl Model for Handoff {
+/// #     type State = u8; // 0: sender owns, 1: in transit, 2: receiver owns
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, s: &u8, actions: &mut Vec<()>) { if *s < 2 { actions.push(()) } }
+/// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { Some(s + 1) }
+/// # }
+/// let sender_owns = Ltl::<Handoff>::atom("sender owns", |_, s| *s == 0);
+/// let receiver_owns = Ltl::atom("receiver owns", |_, s| *s == 2);
+/// let model = LtlModel::new(Handoff, "handoff", sender_owns.until(Ltl::always(receiver_owns)));
+/// let checker = model.checker().spawn_dfs().join();
+/// // The ownership is in transit in between.
+/// checker.assert_discovery("handoff", vec![()]);
+/// ```
+///
+/// [`CheckerBuilder::spawn_dfs`]: crate::CheckerBuilder::spawn_dfs
+pub struct LtlModel<M: Model> {
+    /// The model whose behaviors are checked.
+    pub model: M,
+    name: &'static str,
+    nodes: Vec<Node<M>>,
+}
+
+impl<M: Model> LtlModel<M> {
+    /// Pairs a model with a monitor for a formula, which is checked as a property with the
+    /// specified name.
+    pub fn new(model: M, name: &'static str, formula: Ltl<M>) -> Self {
+        let mut ltl_model = LtlModel { model, name, nodes: Vec::new() };
+        ltl_model.compile(&formula.0, false);
+        ltl_model
+    }
+
+    /// Appends the nodes of a formula (negated if requested), returning the index of its root.
+    fn compile(&mut self, formula: &Formula<M>, is_negated: bool) -> usize {
+        let node = match (formula, is_negated) {
+            (Formula::Atom(_, condition), _) => Node::Literal(*condition, !is_negated),
+            (Formula::Not(f), _) => return self.compile(f, !is_negated),
+            (Formula::And(a, b), false) | (Formula::Or(a, b), true) => {
+                Node::And(self.compile(a, is_negated), self.compile(b, is_negated))
+            }
+            (Formula::Or(a, b), false) | (Formula::And(a, b), true) => {
+                Node::Or(self.compile(a, is_negated), self.compile(b, is_negated))
+            }
+            (Formula::Always(f), false) | (Formula::Eventually(f), true) => {
+                Node::Always(self.compile(f, is_negated))
+            }
+            (Formula::Eventually(f), false) | (Formula::Always(f), true) => {
+                Node::Eventually(self.compile(f, is_negated))
+            }
+            (Formula::Until(a, b), false) => {
+                Node::Until(self.compile(a, false), self.compile(b, false))
+            }
+            // Not (a until b) means that not b holds up to and including a state where neither
+            // a nor b holds, if any.
+            (Formula::Until(a, b), true) => {
+                Node::Release(self.compile(a, true), self.compile(b, true))
+            }
+        };
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn root(&self) -> usize {
+        self.nodes.len() - 1
+    }
+
+    /// Advances the monitor past a state, leaving the obligations for the rest of the behavior.
+    fn progress(&self, monitor: &LtlMonitor, state: &M::State) -> LtlMonitor {
+        let mut next = LtlMonitor::violated();
+        for conjunction in &monitor.0 {
+            let mut progressed = LtlMonitor::satisfied();
+            for &node in conjunction {
+                progressed = progressed.and(&self.progress_node(node, state));
+            }
+            next = next.or(&progressed);
+        }
+        next
+    }
+
+    fn progress_node(&self, node: usize, state: &M::State) -> LtlMonitor {
+        match self.nodes[node] {
+            Node::Literal(condition, expected) => {
+                if condition(&self.model, state) == expected {
+                    LtlMonitor::satisfied()
+                } else {
+                    LtlMonitor::violated()
+                }
+            }
+            Node::And(a, b) => self.progress_node(a, state).and(&self.progress_node(b, state)),
+            Node::Or(a, b) => self.progress_node(a, state).or(&self.progress_node(b, state)),
+            Node::Always(f) => {
+                self.progress_node(f, state).and(&LtlMonitor::obligation(node))
+            }
+            Node::Eventually(f) => {
+                self.progress_node(f, state).or(&LtlMonitor::obligation(node))
+            }
+            Node::Until(a, b) => {
+                let awaiting = self.progress_node(a, state).and(&LtlMonitor::obligation(node));
+                self.progress_node(b, state).or(&awaiting)
+            }
+            Node::Release(a, b) => {
+                let released = self.progress_node(a, state).or(&LtlMonitor::obligation(node));
+                self.progress_node(b, state).and(&released)
+            }
+        }
+    }
+
+    /// Indicates whether the monitor's obligations hold for the behavior that repeats the states
+    /// of a loop forever.
+    fn holds_on_loop(&self, monitor: &LtlMonitor, states: &[&M::State]) -> bool {
+        monitor.0.iter().any(|conjunction| {
+            conjunction.iter().all(|&node| self.node_holds_on_loop(node, states, 0))
+        })
+    }
+
+    fn node_holds_on_loop(&self, node: usize, states: &[&M::State], i: usize) -> bool {
+        let holds = |node, j| self.node_holds_on_loop(node, states, j);
+        let ahead = (0..states.len()).map(|offset| (i + offset) % states.len());
+        match self.nodes[node] {
+            Node::Literal(condition, expected) => condition(&self.model, states[i]) == expected,
+            Node::And(a, b) => holds(a, i) && holds(b, i),
+            Node::Or(a, b) => holds(a, i) || holds(b, i),
+            Node::Always(f) => (0..states.len()).all(|j| holds(f, j)),
+            Node::Eventually(f) => (0..states.len()).any(|j| holds(f, j)),
+            Node::Until(a, b) => {
+                for j in ahead {
+                    if holds(b, j) { return true }
+                    if !holds(a, j) { return false }
+                }
+                false
+            }
+            Node::Release(a, b) => {
+                for j in ahead {
+                    if !holds(b, j) { return false }
+                    if holds(a, j) { return true }
+                }
+                true
+            }
+        }
+    }
+
+    /// Indicates that the formula holds for every continuation, or that the state is terminal
+    /// and the formula holds when it repeats forever.
+    fn is_satisfied(&self, state: &(M::State, LtlMonitor)) -> bool {
+        let (state, monitor) = state;
+        if monitor.is_satisfied() { return true }
+        if monitor.is_violated() || !self.model.next_states(state).is_empty() { return false }
+        self.holds_on_loop(monitor, &[state])
+    }
+}
+
+impl<M: Model> Model for LtlModel<M>
+where M::State: Clone,
+{
+    type State = (M::State, LtlMonitor);
+    type Action = M::Action;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let monitor = LtlMonitor::obligation(self.root());
+        self.model.init_states().into_iter()
+            .map(|state| {
+                let monitor = self.progress(&monitor, &state);
+                (state, monitor)
+            })
+            .collect()
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        // No continuation can satisfy a violated formula.
+        if state.1.is_violated() { return }
+        self.model.actions(&state.0, actions);
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let (last_state, monitor) = last_state;
+        self.model.next_state(last_state, action).map(|state| {
+            let monitor = self.progress(monitor, &state);
+            (state, monitor)
+        })
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        vec![Property {
+            expectation: Expectation::Eventually,
+            name: self.name,
+            condition: |model, state| model.is_satisfied(state),
+            action_condition: None,
+            trigger: Some(|model, state| !model.is_satisfied(state)),
+            lasso_condition: Some(|model, states| {
+                // The monitor at the start of the loop tracks the obligations for the states
+                // that follow.
+                let loop_states: Vec<_> = states[1..].iter().map(|(s, _)| s).collect();
+                model.holds_on_loop(&states[0].1, &loop_states)
+            }),
+            is_monotone: false,
+        }]
+    }
+
+    fn within_boundary(&self, state: &Self::State) -> bool {
+        self.model.within_boundary(&state.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Checker;
+    use crate::test_util::dgraph::DGraph;
+
+    fn graph() -> DGraph {
+        DGraph::with_property(Property::always("true", |_, _| true))
+    }
+
+    fn is(n: u8) -> Ltl<DGraph> {
+        match n {
+            0 => Ltl::atom("is 0", |_, s| *s == 0),
+            1 => Ltl::atom("is 1", |_, s| *s == 1),
+            2 => Ltl::atom("is 2", |_, s| *s == 2),
+            _ => Ltl::atom("is 3", |_, s| *s == 3),
+        }
+    }
+
+    fn states(checker: &impl Checker<LtlModel<DGraph>>) -> Vec<u8> {
+        checker.discovery("formula").unwrap().into_states().into_iter().map(|(s, _)| s).collect()
+    }
+
+    #[test]
+    fn can_check_until() {
+        let formula = || is(0).or(is(1)).until(is(2));
+        assert_eq!(formula().to_string(), "((is 0 || is 1) U is 2)");
+        LtlModel::new(graph().with_path(vec![0, 1, 2, 3]), "formula", formula())
+            .checker().spawn_bfs().join()
+            .assert_properties();
+
+        // Violated once 3 precedes 2.
+        let checker = LtlModel::new(graph().with_path(vec![0, 1, 3, 2]), "formula", formula())
+            .checker().spawn_bfs().join();
+        assert_eq!(states(&checker), vec![0, 1, 3]);
+
+        // Violated if 2 never occurs, with the last state repeating forever.
+        let checker = LtlModel::new(graph().with_path(vec![0, 1]), "formula", formula())
+            .checker().spawn_bfs().join();
+        assert_eq!(states(&checker), vec![0, 1]);
+
+        // Negation.
+        LtlModel::new(graph().with_path(vec![0, 1, 3, 2]), "formula", formula().not())
+            .checker().spawn_bfs().join()
+            .assert_properties();
+    }
+
+    #[test]
+    fn can_check_nested_operators_on_loops() {
+        // Each of 0 and 1 recurs.
+        let formula = || Ltl::always(Ltl::eventually(is(0))).and(Ltl::always(Ltl::eventually(is(1))));
+        LtlModel::new(graph().with_path(vec![0, 1, 0]), "formula", formula())
+            .checker().spawn_dfs().join()
+            .assert_properties();
+        let checker = LtlModel::new(graph().with_path(vec![0, 1, 2, 1]), "formula", formula())
+            .checker().spawn_dfs().join();
+        assert_eq!(states(&checker), vec![0, 1, 2, 1]);
+
+        // Every 1 is followed by a 3.
+        let formula = || Ltl::always(is(1).implies(Ltl::eventually(is(3))));
+        LtlModel::new(graph().with_path(vec![0, 1, 2, 3, 0]), "formula", formula())
+            .checker().spawn_dfs().join()
+            .assert_properties();
+        let checker = LtlModel::new(graph().with_path(vec![0, 1, 2, 0]), "formula", formula())
+            .checker().spawn_dfs().join();
+        checker.assert_discovery("formula", vec![1, 2, 0, 1]);
+    }
+}
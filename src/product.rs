@@ -0,0 +1,208 @@
+//! Private module for selective re-export.
+
+use crate::{Model, Property};
+
+/// Composes two models so that they can be checked in one run against properties that relate
+/// both, such as that a concrete [`SystemModel`] refines an abstract specification. The state
+/// is the pair of the subsystem states.
+///
+/// - [`ProductModel::interleaved`] takes the action of either model at each step.
+/// - [`ProductModel::synchronized`] lets the right model drive while the left model follows:
+///   each right action is paired with every enabled left action that it synchronizes with, or
+///   leaves the left state unchanged if none do. A property relating the two states (such as
+///   that the right state maps to the left state) then detects a right step that the left
+///   model cannot match.
+///
+/// # Example
+///
+/// ```
+/// use stateright::{Checker, Model, ProductModel, Property};
+/// # struct Counter(u8);
+/// # impl Model for Counter {
+/// #     type State = u8;
+/// #     type Action = ();
+/// #     fn init_states(&self) -> Vec<u8> { vec![0] }
+/// #     fn actions(&self, s: &u8, actions: &mut Vec<()>) { if *s < self.0 { actions.push(()) } }
+/// #     fn next_state(&self, s: &u8, _: ()) -> Option<u8> { Some(s + 1) }
+/// # }
+/// let model = ProductModel::synchronized(Counter(3), Counter(3), |_, _| true)
+///     .property(Property::always("in sync", |_, (left, right)| left == right));
+/// model.checker().spawn_bfs().join().assert_properties();
+/// ```
+///
+/// [`SystemModel`]: crate::actor::SystemModel
+pub struct ProductModel<A, B>
+where A: Model,
+      B: Model,
+      A::State: Clone,
+      B::State: Clone,
+      A::Action: Clone,
+      B::Action: Clone,
+{
+    /// The left model.
+    pub left: A,
+    /// The right model.
+    pub right: B,
+    #[allow(clippy::type_complexity)]
+    sync: Option<fn(&A::Action, &B::Action) -> bool>,
+    properties: Vec<Property<ProductModel<A, B>>>,
+}
+
+/// An action of a [`ProductModel`].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum ProductAction<L, R> {
+    /// An action of the left model alone.
+    Left(L),
+    /// An action of the right model alone.
+    Right(R),
+    /// Synchronized actions of both models.
+    Both(L, R),
+}
+
+impl<A, B> ProductModel<A, B>
+where A: Model,
+      B: Model,
+      A::State: Clone,
+      B::State: Clone,
+      A::Action: Clone,
+      B::Action: Clone,
+{
+    /// Composes two models such that either can take a step at any time.
+    pub fn interleaved(left: A, right: B) -> Self {
+        ProductModel { left, right, sync: None, properties: Vec::new() }
+    }
+
+    /// Composes two models such that the left model only takes a step alongside a right action
+    /// for which `sync` holds. See [`ProductModel`].
+    pub fn synchronized(left: A, right: B, sync: fn(&A::Action, &B::Action) -> bool) -> Self {
+        ProductModel { left, right, sync: Some(sync), properties: Vec::new() }
+    }
+
+    /// Adds a property, which can relate the states of both models.
+    pub fn property(mut self, property: Property<Self>) -> Self {
+        self.properties.push(property);
+        self
+    }
+}
+
+impl<A, B> Model for ProductModel<A, B>
+where A: Model,
+      B: Model,
+      A::State: Clone,
+      B::State: Clone,
+      A::Action: Clone,
+      B::Action: Clone,
+{
+    type State = (A::State, B::State);
+    type Action = ProductAction<A::Action, B::Action>;
+
+    fn init_states(&self) -> Vec<Self::State> {
+        let right_inits = self.right.init_states();
+        let mut inits = Vec::new();
+        for left in self.left.init_states() {
+            for right in &right_inits {
+                inits.push((left.clone(), right.clone()));
+            }
+        }
+        inits
+    }
+
+    fn actions(&self, state: &Self::State, actions: &mut Vec<Self::Action>) {
+        let mut left_actions = Vec::new();
+        let mut right_actions = Vec::new();
+        self.left.actions(&state.0, &mut left_actions);
+        self.right.actions(&state.1, &mut right_actions);
+        match self.sync {
+            None => {
+                actions.extend(left_actions.into_iter().map(ProductAction::Left));
+                actions.extend(right_actions.into_iter().map(ProductAction::Right));
+            }
+            Some(sync) => {
+                for right_action in right_actions {
+                    let len = actions.len();
+                    for left_action in &left_actions {
+                        if sync(left_action, &right_action) {
+                            actions.push(ProductAction::Both(left_action.clone(), right_action.clone()));
+                        }
+                    }
+                    if actions.len() == len {
+                        actions.push(ProductAction::Right(right_action));
+                    }
+                }
+            }
+        }
+    }
+
+    fn next_state(&self, last_state: &Self::State, action: Self::Action) -> Option<Self::State> {
+        let (left, right) = last_state;
+        match action {
+            ProductAction::Left(action) => {
+                self.left.next_state(left, action).map(|left| (left, right.clone()))
+            }
+            ProductAction::Right(action) => {
+                self.right.next_state(right, action).map(|right| (left.clone(), right))
+            }
+            ProductAction::Both(left_action, right_action) => {
+                let next_left = self.left.next_state(left, left_action);
+                let next_right = self.right.next_state(right, right_action);
+                if next_left.is_none() && next_right.is_none() { return None }
+                Some((
+                    next_left.unwrap_or_else(|| left.clone()),
+                    next_right.unwrap_or_else(|| right.clone()),
+                ))
+            }
+        }
+    }
+
+    fn properties(&self) -> Vec<Property<Self>> {
+        self.properties.clone()
+    }
+
+    fn within_boundary(&self, state: &Self::State) -> bool {
+        self.left.within_boundary(&state.0) && self.right.within_boundary(&state.1)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Checker, Expectation};
+    use crate::test_util::dgraph::DGraph;
+
+    #[test]
+    fn interleaves_actions() {
+        let graph = || DGraph::with_property(Property::always("true", |_, _| true));
+        let model = ProductModel::interleaved(graph().with_path(vec![0, 1]), graph().with_path(vec![5, 6]))
+            .property(Property::sometimes("both advanced", |_, s| *s == (1, 6)));
+        let mut actions = Vec::new();
+        model.actions(&(0, 5), &mut actions);
+        assert_eq!(actions, vec![ProductAction::Left(1), ProductAction::Right(6)]);
+        let checker = model.checker().spawn_bfs().join();
+        assert_eq!(checker.generated_count(), 4);
+        checker.assert_discovery("both advanced", vec![ProductAction::Left(1), ProductAction::Right(6)]);
+    }
+
+    #[test]
+    fn detects_step_that_left_model_cannot_match() {
+        // The right model can also skip a state, which the left model cannot match.
+        let graph = || DGraph::with_property(Property::always("true", |_, _| true));
+        let spec = graph().with_path(vec![0, 1, 2]);
+        let implementation = graph().with_path(vec![0, 1, 2]).with_path(vec![0, 2]);
+        let model = ProductModel::synchronized(spec, implementation, |left, right| left == right)
+            .property(Property::always("refines", |_, (left, right)| left == right));
+        assert_eq!(model.properties()[0].expectation, Expectation::Always);
+        let checker = model.checker().spawn_bfs().join();
+        checker.assert_discovery("refines", vec![ProductAction::Right(2)]);
+
+        // Matching steps are synchronized.
+        let checker = ProductModel::synchronized(
+                graph().with_path(vec![0, 1, 2]),
+                graph().with_path(vec![0, 1, 2]),
+                |left, right| left == right)
+            .property(Property::always("refines", |_, (left, right)| left == right))
+            .checker().spawn_bfs().join();
+        checker.assert_properties();
+        assert_eq!(checker.generated_count(), 3);
+    }
+}
@@ -5,7 +5,7 @@
 //! # Defining Correctness
 //!
 //! [`SequentialSpec`] is a trait for defining correctness, and Stateright
-//! includes reusable implementations such as [`register`] and [`vec`]. More
+//! includes reusable implementations such as [`register`], [`vec`], [`map`], and [`log`]. More
 //! implementations will be added to cover common data types emulated by
 //! distributed systems.
 //!
@@ -25,6 +25,11 @@
 //! and actors need not sequence their operations to a concurrent
 //! system outside of that use case.
 //!
+//! Systems that group operations into transactions can instead be verified
+//! with a [`SerializabilityTester`] or [`SnapshotIsolationTester`], which
+//! identify each transaction by a distinct transaction ID rather than a thread
+//! ID.
+//!
 //! # Additional Reading
 //!
 //! For more background on specifying the semantics of concurrent systems, see
@@ -40,15 +45,23 @@
 //!   by Pierce et al.
 //!
 //! [`actor::Id`]: crate::actor::Id
+//! [`log`]: self::log
+//! [`map`]: self::map
 //! [consistency model]: https://en.wikipedia.org/wiki/Consistency_model
 //! [`vec`]: self::vec
 
 mod linearizability;
 mod sequential_consistency;
+mod serializability;
+mod snapshot_isolation;
 
+pub mod log;
+pub mod map;
 pub mod register;
 pub use linearizability::LinearizabilityTester;
 pub use sequential_consistency::SequentialConsistencyTester;
+pub use serializability::SerializabilityTester;
+pub use snapshot_isolation::SnapshotIsolationTester;
 pub mod vec;
 
 /// An implementation of this trait can serve as a sequential "reference object"
@@ -61,6 +74,11 @@ pub mod vec;
 ///
 /// - [`LinearizabilityTester`]
 /// - [`SequentialConsistencyTester`]
+/// - [`SerializabilityTester`]
+/// - [`SnapshotIsolationTester`] (requires [`TransactionalSpec`])
+///
+/// Implement [`JepsenSpec`] as well to export histories for verification by
+/// other tools.
 ///
 /// [consistency model]: https://en.wikipedia.org/wiki/Consistency_model
 /// [operational semantics]: https://en.wikipedia.org/wiki/Operational_semantics
@@ -91,3 +109,76 @@ pub trait SequentialSpec: Sized {
         ops.into_iter().all(|(op, ret)| self.is_valid_step(&op, &ret))
     }
 }
+
+/// A tester that validates a potentially concurrent history of operations,
+/// invoked by thread-like callers, against an expected [consistency model].
+/// Implemented by [`LinearizabilityTester`] and [`SequentialConsistencyTester`],
+/// so that a system recording a history (such as
+/// [`RegisterTestSystem`](crate::actor::register::RegisterTestSystem)) can be
+/// verified against a different consistency model by swapping the tester type.
+///
+/// [consistency model]: https://en.wikipedia.org/wiki/Consistency_model
+pub trait ConsistencyTester<ThreadId, Op, Ret> {
+    /// Names the consistency model, such as `"linearizable"`. Useful for naming
+    /// a [`Property`](crate::Property).
+    fn consistency_model() -> &'static str;
+
+    /// Enables memoization of consistency checks across clones of this tester,
+    /// if supported. See [`LinearizabilityTester::memoized`].
+    fn memoized(self) -> Self where Self: Sized + std::hash::Hash {
+        self
+    }
+
+    /// Indicates that a thread invoked an operation. Returns `Ok(...)` if the
+    /// history is valid, even if it is not consistent.
+    fn on_invoke(&mut self, thread_id: ThreadId, op: Op) -> Result<&mut Self, String>;
+
+    /// Indicates that a thread's earlier operation invocation returned.
+    /// Returns `Ok(...)` if the history is valid, even if it is not consistent.
+    fn on_return(&mut self, thread_id: ThreadId, ret: Ret) -> Result<&mut Self, String>;
+
+    /// Indicates that a thread gave up waiting for its in-flight operation to
+    /// return, so the operation may or may not take effect. Returns `Ok(...)`
+    /// if the history is valid, even if it is not consistent.
+    fn on_timeout(&mut self, thread_id: ThreadId) -> Result<&mut Self, String>;
+
+    /// Indicates whether a thread has an operation in flight.
+    fn is_in_flight(&self, thread_id: ThreadId) -> bool;
+
+    /// Indicates why the history is invalid (for example, because a thread
+    /// returned without invoking an operation), or `None` if it is valid. An
+    /// invalid history is never consistent.
+    fn invalid_history(&self) -> Option<&str>;
+
+    /// Indicates whether the recorded history is valid and adheres to the
+    /// consistency model.
+    fn is_consistent(&self) -> bool;
+}
+
+/// A [`SequentialSpec`] whose operations can be exported to a history in the
+/// schema used by [Jepsen], so that histories can be independently verified by
+/// tools such as Knossos and Elle. See
+/// [`LinearizabilityTester::jepsen_history`].
+///
+/// [Jepsen]: https://jepsen.io
+pub trait JepsenSpec: SequentialSpec {
+    /// Indicates the function (Jepsen's `f`) and value of an invocation.
+    fn jepsen_invocation(op: &Self::Op) -> (&'static str, serde_json::Value);
+
+    /// Indicates the type (`"ok"`, `"fail"`, or `"info"` if unknown) and value
+    /// of a completion.
+    fn jepsen_completion(op: &Self::Op, ret: &Self::Ret) -> (&'static str, serde_json::Value);
+}
+
+/// A [`SequentialSpec`] whose state is partitioned into keys, such that an
+/// operation that writes a key only observes and updates that key. Required by
+/// testers that reason about write conflicts, such as
+/// [`SnapshotIsolationTester`].
+pub trait TransactionalSpec: SequentialSpec {
+    /// The type of keys. `()` if the reference object is a single value.
+    type Key: Clone + Ord;
+
+    /// Indicates the key written by an operation that returned a specified
+    /// value, if any.
+    fn written_key(op: &Self::Op, ret: &Self::Ret) -> Option<Self::Key>;
+}
@@ -1,8 +1,13 @@
 //! Private module for selective re-export. See [`LinearizabilityTester`].
 
-use crate::semantics::SequentialSpec;
-use std::collections::{btree_map, BTreeMap, VecDeque};
-use std::fmt::Debug;
+use crate::{fingerprint, Fingerprint};
+use crate::semantics::{ConsistencyTester, JepsenSpec, SequentialSpec};
+use parking_lot::Mutex;
+use std::cmp::Reverse;
+use std::collections::{btree_map, BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::fmt::{Debug, Formatter};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
 
 // This implementation is based on `SequentialConsistencyTester` and will be
 // easier to follow if you are already familiar with that code. The key
@@ -10,6 +15,18 @@ use std::fmt::Debug;
 // the index of the last operation completed by every other thread, and those
 // same indices are also preserved if/when the operation completes. That data
 // allows the tester to reject histories that violate "real time" ordering.
+//
+// Operations that time out are moved out of `in_flight_by_thread` (freeing the
+// thread to invoke further operations) and into `indeterminate`. Their
+// recorded indices additionally include the invoking thread's own last
+// completed operation, as the operation must still follow those.
+//
+// A memoized tester shares a cache of serialization results with its clones.
+// Model checking clones the history for every successor state, and many states
+// share a history (as it only changes when clients send or receive), so the
+// cache avoids repeating the search for histories already checked. The cache is
+// keyed by the whole history, so a history that extends a checked one is still
+// searched from scratch.
 
 /// This tester captures a potentially concurrent history of operations and
 /// validates that it adheres to a [`SequentialSpec`] based on the
@@ -58,13 +75,49 @@ pub struct LinearizabilityTester<ThreadId, RefObj: SequentialSpec> {
     init_ref_obj: RefObj,
     history_by_thread: BTreeMap<ThreadId, VecDeque<Complete<ThreadId, RefObj::Op, RefObj::Ret>>>,
     in_flight_by_thread: BTreeMap<ThreadId, InFlight<ThreadId, RefObj::Op>>,
-    is_valid_history: bool,
+    indeterminate: Vec<InFlight<ThreadId, RefObj::Op>>,
+    invalid_history: Option<String>,
+    memo: Memo<Self, RefObj::Op, RefObj::Ret>,
 }
 
 type LastCompletedOpMap<ThreadId> = BTreeMap<ThreadId, usize>;
 type Complete<ThreadId, Op, Ret> = (LastCompletedOpMap<ThreadId>, Op, Ret);
 type InFlight<ThreadId, Op> = (LastCompletedOpMap<ThreadId>, Op);
 
+/// Serialization results by history fingerprint, if memoized, along with the function that
+/// fingerprints a history, which is captured by [`LinearizabilityTester::memoized`] so that only
+/// memoized testers require hashable histories. Ignored by comparisons and hashing, as it does
+/// not affect the outcome of checking.
+#[allow(clippy::type_complexity)]
+struct Memo<Tester, Op, Ret>(Option<(fn(&Tester) -> Fingerprint, Arc<Mutex<HashMap<Fingerprint, Option<Vec<(Op, Ret)>>>>>)>);
+
+impl<Tester, Op, Ret> Clone for Memo<Tester, Op, Ret> {
+    fn clone(&self) -> Self {
+        Memo(self.0.clone())
+    }
+}
+
+impl<Tester, Op, Ret> Debug for Memo<Tester, Op, Ret> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            None => write!(f, "None"),
+            Some((_, memo)) => write!(f, "Memo({})", memo.lock().len()),
+        }
+    }
+}
+
+impl<Tester, Op, Ret> Eq for Memo<Tester, Op, Ret> {}
+
+impl<Tester, Op, Ret> Hash for Memo<Tester, Op, Ret> {
+    fn hash<H: Hasher>(&self, _state: &mut H) {}
+}
+
+impl<Tester, Op, Ret> PartialEq for Memo<Tester, Op, Ret> {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
 #[allow(clippy::len_without_is_empty)] // no use case for an emptiness check
 impl<T: Ord, RefObj: SequentialSpec> LinearizabilityTester<T, RefObj> {
     /// Constructs a [`LinearizabilityTester`].
@@ -73,14 +126,29 @@ impl<T: Ord, RefObj: SequentialSpec> LinearizabilityTester<T, RefObj> {
             init_ref_obj,
             history_by_thread: Default::default(),
             in_flight_by_thread: Default::default(),
-            is_valid_history: true,
+            indeterminate: Default::default(),
+            invalid_history: None,
+            memo: Memo(None),
         }
     }
 
-    /// Indicates the aggregate number of operations completed or in flight
-    /// across all threads.
+    /// Enables a mode in which [`LinearizabilityTester::serialized_history`] memoizes its result
+    /// for each distinct history, sharing the cache with every clone of this tester (such as the
+    /// histories of successor states during model checking). Checking a history that was already
+    /// checked then skips the search, which helps when many states share a history, at the cost
+    /// of memory proportional to the number of distinct histories. A history that extends a
+    /// checked history is still searched in full.
+    pub fn memoized(mut self) -> Self
+    where Self: Hash,
+    {
+        self.memo = Memo(Some((fingerprint::<Self>, Default::default())));
+        self
+    }
+
+    /// Indicates the aggregate number of operations completed, in flight, or
+    /// indeterminate across all threads.
     pub fn len(&self) -> usize {
-        let mut len = self.in_flight_by_thread.len();
+        let mut len = self.in_flight_by_thread.len() + self.indeterminate.len();
         for history in self.history_by_thread.values() {
             len += history.len();
         }
@@ -100,16 +168,17 @@ where
     ///
     /// See [`LinearizabilityTester::serialized_history`].
     pub fn on_invoke(&mut self, thread_id: T, op: RefObj::Op) -> Result<&mut Self, String> {
-        if !self.is_valid_history {
+        if self.invalid_history.is_some() {
             return Err("Earlier history was invalid.".to_string());
         }
         let in_flight_elem = self.in_flight_by_thread.entry(thread_id);
         if let btree_map::Entry::Occupied(occupied_op_entry) = in_flight_elem {
-            self.is_valid_history = false;
             let (_, op) = occupied_op_entry.get();
-            return Err(format!(
+            let reason = format!(
                     "Thread already has an operation in flight. thread_id={:?}, op={:?}, history_by_thread={:?}",
-                    thread_id, op, self.history_by_thread));
+                    thread_id, op, self.history_by_thread);
+            self.invalid_history = Some(reason.clone());
+            return Err(reason);
         };
         let last_completed = self.history_by_thread.iter().filter_map(|(id, cs)| {
             // collect last completed op index for every other thread
@@ -129,16 +198,17 @@ where
     ///
     /// See [`LinearizabilityTester::serialized_history`].
     pub fn on_return(&mut self, thread_id: T, ret: RefObj::Ret) -> Result<&mut Self, String> {
-        if !self.is_valid_history {
+        if self.invalid_history.is_some() {
             return Err("Earlier history was invalid.".to_string());
         }
         let (completed, op) = match self.in_flight_by_thread.remove(&thread_id) {
             None => {
-                self.is_valid_history = false;
-                return Err(format!(
+                let reason = format!(
                     "There is no in-flight invocation for this thread ID. \
                      thread_id={:?}, unexpected_return={:?}, history={:?}",
-                    thread_id, ret, self.history_by_thread.entry(thread_id).or_insert_with(VecDeque::new)));
+                    thread_id, ret, self.history_by_thread.entry(thread_id).or_default());
+                self.invalid_history = Some(reason.clone());
+                return Err(reason);
             }
             Some(x) => x,
         };
@@ -146,6 +216,52 @@ where
         Ok(self)
     }
 
+    /// Indicates that a thread gave up waiting for its in-flight operation to
+    /// return, such as a client that times out. The operation becomes
+    /// indeterminate: it may or may not take effect, and if it does, it can do
+    /// so at any point after it was invoked (even after subsequent operations
+    /// by the same thread). The thread is then free to invoke another
+    /// operation. Returns `Ok(...)` if the history is valid, even if it is not
+    /// linearizable.
+    ///
+    /// See [`LinearizabilityTester::serialized_history`].
+    pub fn on_timeout(&mut self, thread_id: T) -> Result<&mut Self, String> {
+        if self.invalid_history.is_some() {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        let (mut completed, op) = match self.in_flight_by_thread.remove(&thread_id) {
+            None => {
+                let reason = format!(
+                    "There is no in-flight invocation for this thread ID. \
+                     thread_id={:?}, history={:?}",
+                    thread_id, self.history_by_thread.entry(thread_id).or_default());
+                self.invalid_history = Some(reason.clone());
+                return Err(reason);
+            }
+            Some(x) => x,
+        };
+        if let Some(history) = self.history_by_thread.get(&thread_id) {
+            if !history.is_empty() {
+                completed.insert(thread_id, history.len() - 1);
+            }
+        }
+        self.indeterminate.push((completed, op));
+        Ok(self)
+    }
+
+    /// Indicates whether a thread has an operation in flight (as opposed to
+    /// completed or indeterminate).
+    pub fn is_in_flight(&self, thread_id: T) -> bool {
+        self.in_flight_by_thread.contains_key(&thread_id)
+    }
+
+    /// Indicates why the history is invalid, such as a return without a
+    /// corresponding invocation, or `None` if the history is valid. An invalid
+    /// history is never linearizable, and the tester rejects further operations.
+    pub fn invalid_history(&self) -> Option<&str> {
+        self.invalid_history.as_deref()
+    }
+
     /// A helper that indicates both an operation and corresponding return
     /// value for a thread. Returns `Ok(...)` if the history is valid, even if
     /// it is not lineariable.
@@ -159,21 +275,153 @@ where
     /// Attempts to serialize the recorded partially ordered operation history
     /// into a total order that is consistent with a reference object's
     /// operational semantics.
+    ///
+    /// See also [`LinearizabilityTester::memoized`].
     pub fn serialized_history(&self) -> Option<Vec<(RefObj::Op, RefObj::Ret)>>
     where
         RefObj: Clone,
         RefObj::Op: Clone,
         RefObj::Ret: Clone,
     {
-        if !self.is_valid_history { return None }
+        if self.invalid_history.is_some() { return None }
+        let memo = self.memo.0.as_ref().map(|(fingerprint, memo)| (memo, fingerprint(self)));
+        if let Some((memo, key)) = memo {
+            if let Some(serialized) = memo.lock().get(&key) {
+                return serialized.clone();
+            }
+        }
         let history_by_thread = self.history_by_thread.iter().map(|(t, cs)| {
             (*t, cs.clone().into_iter().enumerate().collect())
         }).collect();
-        Self::serialize(
+        let serialized = Self::serialize(
             Vec::new(),
             &self.init_ref_obj,
             &history_by_thread,
-            &self.in_flight_by_thread)
+            &self.in_flight_by_thread,
+            &self.indeterminate);
+        if let Some((memo, key)) = memo {
+            memo.lock().insert(key, serialized.clone());
+        }
+        serialized
+    }
+
+    /// Exports the recorded history as a sequence of operations in the schema used by [Jepsen],
+    /// such that it can be independently verified by tools such as Knossos (for
+    /// linearizability) or Elle. Each thread is assigned a process number, and each
+    /// indeterminate operation is assigned a process of its own, following Jepsen's convention
+    /// of retiring a process after an `"info"` completion. Operations that are indeterminate or
+    /// still in flight complete with `"info"` at the end of the history.
+    ///
+    /// The tester does not record a total order of events, so invocations and completions are
+    /// ordered to preserve exactly the recorded "real time" ordering: an operation completes
+    /// before another is invoked if and only if the tester required the former to precede the
+    /// latter.
+    ///
+    /// ```
+    /// use stateright::semantics::LinearizabilityTester;
+    /// use stateright::semantics::register::*;
+    /// let mut tester = LinearizabilityTester::new(Register(0));
+    /// tester.on_invret(99, RegisterOp::Write(1), RegisterRet::WriteOk).unwrap();
+    /// let history = tester.jepsen_history();
+    /// assert_eq!(
+    ///     serde_json::to_string(&history).unwrap(),
+    ///     r#"[{"f":"write","index":0,"process":0,"type":"invoke","value":1},{"f":"write","index":1,"process":0,"type":"ok","value":1}]"#);
+    /// ```
+    ///
+    /// [Jepsen]: https://jepsen.io
+    pub fn jepsen_history(&self) -> Vec<serde_json::Value>
+    where RefObj: JepsenSpec,
+    {
+        // Every operation along with its process, its thread and index within that thread's
+        // history (unless indeterminate), the indices of prerequisite operations, and its return
+        // value (if it completed).
+        struct Entry<'a, T, Op, Ret> {
+            process: usize,
+            position: Option<(T, usize)>,
+            completed: &'a LastCompletedOpMap<T>,
+            op: &'a Op,
+            ret: Option<&'a Ret>,
+        }
+        let mut entries = Vec::new();
+        for (process, (thread_id, history)) in self.history_by_thread.iter().enumerate() {
+            for (index, (completed, op, ret)) in history.iter().enumerate() {
+                entries.push(Entry { process, position: Some((*thread_id, index)), completed, op, ret: Some(ret) });
+            }
+            if let Some((completed, op)) = self.in_flight_by_thread.get(thread_id) {
+                entries.push(Entry { process, position: Some((*thread_id, history.len())), completed, op, ret: None });
+            }
+        }
+        for (i, (completed, op)) in self.indeterminate.iter().enumerate() {
+            let process = self.history_by_thread.len() + i;
+            entries.push(Entry { process, position: None, completed, op, ret: None });
+        }
+
+        // Event `2 * i` invokes entry `i`, and event `2 * i + 1` completes it. Completions
+        // without a return value are deferred to the end.
+        let invocation = |i: usize| 2 * i;
+        let completion = |i: usize| 2 * i + 1;
+        let mut successors = vec![Vec::new(); 2 * entries.len()];
+        for (i, entry) in entries.iter().enumerate() {
+            if entry.ret.is_some() {
+                successors[invocation(i)].push(completion(i));
+            }
+            if let Some((_, index)) = entry.position {
+                if index > 0 {
+                    // The thread's previous operation is the previous entry.
+                    successors[completion(i - 1)].push(invocation(i));
+                }
+            }
+            for (j, prior) in entries.iter().enumerate() {
+                let (prior_thread_id, prior_index) = match (prior.position, prior.ret) {
+                    (Some(position), Some(_)) => position,
+                    _ => continue,
+                };
+                if entry.position.map(|(t, _)| t) == Some(prior_thread_id) { continue }
+                if matches!(entry.completed.get(&prior_thread_id), Some(i) if *i >= prior_index) {
+                    successors[completion(j)].push(invocation(i));
+                } else {
+                    successors[invocation(i)].push(completion(j));
+                }
+            }
+        }
+        let mut predecessor_counts = vec![0; successors.len()];
+        for successor in successors.iter().flatten() {
+            predecessor_counts[*successor] += 1;
+        }
+        let mut ready: BinaryHeap<_> = (0..entries.len())
+            .flat_map(|i| vec![invocation(i), completion(i)])
+            .filter(|event| predecessor_counts[*event] == 0)
+            .filter(|event| event % 2 == 0 || entries[event / 2].ret.is_some())
+            .map(Reverse)
+            .collect();
+        let mut events = Vec::with_capacity(successors.len());
+        while let Some(Reverse(event)) = ready.pop() {
+            events.push(event);
+            for successor in &successors[event] {
+                predecessor_counts[*successor] -= 1;
+                if predecessor_counts[*successor] == 0 {
+                    ready.push(Reverse(*successor));
+                }
+            }
+        }
+        events.extend((0..entries.len()).filter(|i| entries[*i].ret.is_none()).map(completion));
+
+        events.into_iter().enumerate().map(|(index, event)| {
+            let entry = &entries[event / 2];
+            let (f, invocation_value) = RefObj::jepsen_invocation(entry.op);
+            let (r#type, value) = match (event % 2, entry.ret) {
+                (0, _) => ("invoke", invocation_value),
+                (_, Some(ret)) => RefObj::jepsen_completion(entry.op, ret),
+                (_, None) => ("info", invocation_value),
+            };
+            serde_json::json!({
+                "type": r#type,
+                "f": f,
+                "value": value,
+                "process": entry.process,
+                "index": index,
+            })
+        }).collect()
     }
 
     #[allow(clippy::type_complexity)]
@@ -181,7 +429,8 @@ where
         valid_history: Vec<(RefObj::Op, RefObj::Ret)>, // total order
         ref_obj: &RefObj,
         remaining_history_by_thread: &BTreeMap<T, VecDeque<(usize, Complete<T, RefObj::Op, RefObj::Ret>)>>, // partial order
-        in_flight_by_thread: &BTreeMap<T, InFlight<T, RefObj::Op>>) // potential extension of partial order
+        in_flight_by_thread: &BTreeMap<T, InFlight<T, RefObj::Op>>, // potential extension of partial order
+        indeterminate: &[InFlight<T, RefObj::Op>]) // another potential extension of partial order
         -> Option<Vec<(RefObj::Op, RefObj::Ret)>>
     where
         RefObj: Clone,
@@ -238,7 +487,33 @@ where
                 (ref_obj, valid_history)
             };
             if let Some(valid_history) = Self::serialize(
-                        valid_history, &ref_obj, &remaining_history_by_thread, &in_flight_by_thread) {
+                        valid_history, &ref_obj, &remaining_history_by_thread, &in_flight_by_thread,
+                        indeterminate) {
+                return Some(valid_history)
+            }
+        }
+
+        // Case 3: An indeterminate operation takes effect now.
+        for (i, (cs, op)) in indeterminate.iter().enumerate() {
+            let violation = cs.iter().any(|(peer_id, min_peer_time)| {
+                // Ensure all pre-req operations were completed by peers (and this thread)
+                if let Some(ops) = remaining_history_by_thread.get(peer_id) {
+                    if let Some((next_peer_time, _)) = ops.iter().next() {
+                        if next_peer_time <= min_peer_time { return true }
+                    }
+                }
+                false
+            });
+            if violation { continue }
+            let mut ref_obj = ref_obj.clone();
+            let ret = ref_obj.invoke(op);
+            let mut valid_history = valid_history.clone();
+            valid_history.push((op.clone(), ret));
+            let mut indeterminate = indeterminate.to_vec();
+            indeterminate.remove(i);
+            if let Some(valid_history) = Self::serialize(
+                        valid_history, &ref_obj, remaining_history_by_thread, in_flight_by_thread,
+                        &indeterminate) {
                 return Some(valid_history)
             }
         }
@@ -246,6 +521,44 @@ where
     }
 }
 
+impl<T, RefObj> ConsistencyTester<T, RefObj::Op, RefObj::Ret> for LinearizabilityTester<T, RefObj>
+where
+    T: Copy + Debug + Ord,
+    RefObj: Clone + SequentialSpec,
+    RefObj::Op: Clone + Debug,
+    RefObj::Ret: Clone + Debug + PartialEq,
+{
+    fn consistency_model() -> &'static str { "linearizable" }
+
+    fn memoized(self) -> Self where Self: Hash {
+        LinearizabilityTester::memoized(self)
+    }
+
+    fn on_invoke(&mut self, thread_id: T, op: RefObj::Op) -> Result<&mut Self, String> {
+        LinearizabilityTester::on_invoke(self, thread_id, op)
+    }
+
+    fn on_return(&mut self, thread_id: T, ret: RefObj::Ret) -> Result<&mut Self, String> {
+        LinearizabilityTester::on_return(self, thread_id, ret)
+    }
+
+    fn on_timeout(&mut self, thread_id: T) -> Result<&mut Self, String> {
+        LinearizabilityTester::on_timeout(self, thread_id)
+    }
+
+    fn is_in_flight(&self, thread_id: T) -> bool {
+        LinearizabilityTester::is_in_flight(self, thread_id)
+    }
+
+    fn invalid_history(&self) -> Option<&str> {
+        LinearizabilityTester::invalid_history(self)
+    }
+
+    fn is_consistent(&self) -> bool {
+        self.serialized_history().is_some()
+    }
+}
+
 impl<T: Ord, RefObj> Default for LinearizabilityTester<T, RefObj>
 where RefObj: Default + SequentialSpec
 {
@@ -262,11 +575,12 @@ where RefObj: serde::Serialize + SequentialSpec,
 {
     fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
         use serde::ser::SerializeStruct;
-        let mut out = ser.serialize_struct("LinearizabilityTester", 4)?;
+        let mut out = ser.serialize_struct("LinearizabilityTester", 5)?;
         out.serialize_field("init_ref_obj", &self.init_ref_obj)?;
         out.serialize_field("history_by_thread", &self.history_by_thread)?;
         out.serialize_field("in_flight_by_thread", &self.in_flight_by_thread)?;
-        out.serialize_field("is_valid_history", &self.is_valid_history)?;
+        out.serialize_field("indeterminate", &self.indeterminate)?;
+        out.serialize_field("invalid_history", &self.invalid_history)?;
         out.end()
     }
 }
@@ -296,6 +610,22 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn retains_reason_for_invalid_history() {
+        let mut tester = LinearizabilityTester::new(Register('A'));
+        assert!(tester.on_return(99, RegisterRet::WriteOk).is_err());
+        assert_eq!(
+            tester.invalid_history(),
+            Some("There is no in-flight invocation for this thread ID. \
+                  thread_id=99, unexpected_return=WriteOk, history=[]"));
+        assert_eq!(
+            tester.on_invoke(99, RegisterOp::Read).map(|_| ()),
+            Err("Earlier history was invalid.".to_string()));
+        assert_eq!(tester.serialized_history(), None);
+        assert!(!tester.is_consistent());
+        assert!(tester.invalid_history().is_some());
+    }
+
     #[test]
     fn identifies_linearizable_register_history() -> Result<(), String> {
         assert_eq!(
@@ -335,6 +665,149 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn exports_jepsen_history() -> Result<(), String> {
+        let history = LinearizabilityTester::new(Register('A'))
+            .on_invoke(0, RegisterOp::Write('B'))?
+            .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+            .on_return(0, RegisterRet::WriteOk)?
+            .on_invret(1, RegisterOp::Cas('A', 'C'), RegisterRet::CasOk(false))?
+            .on_invoke(0, RegisterOp::Write('D'))?
+            .on_timeout(0)?
+            .on_invoke(0, RegisterOp::Read)?
+            .jepsen_history();
+        let history: Vec<_> = history.iter()
+            .map(|op| format!("{} {} {} {}", op["process"], op["type"].as_str().unwrap(), op["f"], op["value"]))
+            .collect();
+        assert_eq!(history, vec![
+            "0 invoke \"write\" \"B\"",
+            "1 invoke \"read\" null",
+            "0 ok \"write\" \"B\"",
+            "1 ok \"read\" \"A\"",
+            "1 invoke \"cas\" [\"A\",\"C\"]",
+            "1 fail \"cas\" [\"A\",\"C\"]",
+            "0 invoke \"read\" null",
+            "2 invoke \"write\" \"D\"",
+            "0 info \"read\" null",
+            "2 info \"write\" \"D\"",
+        ]);
+        Ok(())
+    }
+
+    #[test]
+    fn serializes_without_hashable_spec() -> Result<(), String> {
+        #[derive(Clone)]
+        struct Counter(u8);
+        impl SequentialSpec for Counter {
+            type Op = ();
+            type Ret = u8;
+            fn invoke(&mut self, _op: &()) -> u8 {
+                self.0 += 1;
+                self.0
+            }
+        }
+
+        let mut tester = LinearizabilityTester::new(Counter(0));
+        tester.on_invoke(0, ())?.on_invret(1, (), 1)?.on_return(0, 2)?;
+        assert_eq!(tester.serialized_history(), Some(vec![((), 1), ((), 2)]));
+        Ok(())
+    }
+
+    #[test]
+    fn memoizes_across_clones() -> Result<(), String> {
+        let memo_len = |tester: &LinearizabilityTester<_, _>| tester.memo.0.as_ref().unwrap().1.lock().len();
+        let mut tester = LinearizabilityTester::new(Register('A')).memoized();
+        tester.on_invret(0, RegisterOp::Write('B'), RegisterRet::WriteOk)?;
+        let mut linearizable = tester.clone();
+        linearizable.on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?;
+        let mut unlinearizable = tester.clone();
+        unlinearizable.on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('A'))?;
+
+        assert_eq!(
+            linearizable.serialized_history(),
+            Some(vec![
+                (RegisterOp::Write('B'), RegisterRet::WriteOk),
+                (RegisterOp::Read, RegisterRet::ReadOk('B')),
+            ]));
+        assert_eq!(unlinearizable.serialized_history(), None);
+        assert_eq!(memo_len(&tester), 2);
+
+        // Results are reused for equivalent histories.
+        assert_eq!(linearizable.clone().serialized_history(), linearizable.serialized_history());
+        assert_eq!(unlinearizable.clone().serialized_history(), None);
+        assert_eq!(memo_len(&tester), 2);
+
+        // Memoization does not affect equality.
+        let mut unmemoized = LinearizabilityTester::new(Register('A'));
+        unmemoized.on_invret(0, RegisterOp::Write('B'), RegisterRet::WriteOk)?;
+        assert_eq!(tester, unmemoized);
+        assert_eq!(crate::fingerprint(&tester), crate::fingerprint(&unmemoized));
+        Ok(())
+    }
+
+    #[test]
+    fn treats_timed_out_operations_as_indeterminate() -> Result<(), String> {
+        // The write may take effect...
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(0, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .serialized_history(),
+            Some(vec![
+                (RegisterOp::Write('B'), RegisterRet::WriteOk),
+                (RegisterOp::Read, RegisterRet::ReadOk('B')),
+            ]));
+        // ... or not ...
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(0, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .serialized_history(),
+            Some(vec![
+                (RegisterOp::Read, RegisterRet::ReadOk('A')),
+            ]));
+        // ... including after later operations by the same thread ...
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(0, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .serialized_history(),
+            Some(vec![
+                (RegisterOp::Read, RegisterRet::ReadOk('A')),
+                (RegisterOp::Write('B'), RegisterRet::WriteOk),
+                (RegisterOp::Read, RegisterRet::ReadOk('B')),
+            ]));
+        // ... but it takes effect at most once and not before it was invoked.
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_invret(1, RegisterOp::Write('C'), RegisterRet::WriteOk)?
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('C'))?
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .serialized_history(),
+            None);
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .serialized_history(),
+            None);
+        assert_eq!(
+            LinearizabilityTester::new(Register('A'))
+                .on_timeout(0)
+                .map(|_| ()),
+            Err("There is no in-flight invocation for this thread ID. \
+                 thread_id=0, history=[]".to_string()));
+        Ok(())
+    }
+
     #[test]
     fn identifies_linearizable_vec_history() -> Result<(), String> {
         assert_eq!(
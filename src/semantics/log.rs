@@ -0,0 +1,230 @@
+//! Implements [`SequentialSpec`] for a replicated [`Log`] of state machine operations, including
+//! snapshot/compaction.
+
+use crate::semantics::SequentialSpec;
+
+/// A log of operations for a state machine defined by a [`SequentialSpec`]. A prefix of the log can
+/// be compacted into a snapshot of the state machine, as is common in consensus implementations.
+///
+/// Compaction must not change the state machine semantics, so a compacted log should always
+/// [refine] the corresponding uncompacted log. Models can verify this with an `always` property.
+///
+/// [refine]: Log::refines
+#[derive(Clone, Debug, Hash, PartialEq)]
+pub struct Log<StateMachine: SequentialSpec> {
+    snapshot: StateMachine,
+    snapshot_len: usize,
+    entries: Vec<StateMachine::Op>,
+}
+
+/// An operation that can be invoked upon a [`Log`], resulting in a [`LogRet`].
+#[derive(Clone, Debug, Hash, PartialEq)]
+pub enum LogOp<Op> {
+    /// Appends an operation to the log.
+    Append(Op),
+    /// Compacts the entries before the specified index, leaving the entry at that index
+    /// uncompacted. Indices beyond the length of the log are clamped.
+    Compact(usize),
+    /// Retrieves the length of the log, including compacted entries.
+    Len,
+}
+
+/// A return value for a [`LogOp`] invoked upon a [`Log`].
+#[derive(Clone, Debug, Hash, PartialEq)]
+pub enum LogRet { AppendOk(usize), CompactOk, LenOk(usize) }
+
+impl<StateMachine: SequentialSpec> Log<StateMachine> {
+    /// Constructs an empty [`Log`] for a state machine in its initial state.
+    pub fn new(init_state_machine: StateMachine) -> Self {
+        Log {
+            snapshot: init_state_machine,
+            snapshot_len: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Indicates the number of entries, including compacted entries.
+    pub fn len(&self) -> usize {
+        self.snapshot_len + self.entries.len()
+    }
+
+    /// Indicates whether the log has no entries, including compacted entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Indicates the number of entries that have been compacted into the snapshot.
+    pub fn snapshot_len(&self) -> usize {
+        self.snapshot_len
+    }
+
+    /// Returns the state machine snapshot, which reflects every compacted entry.
+    pub fn snapshot(&self) -> &StateMachine {
+        &self.snapshot
+    }
+
+    /// Returns the entries that have not been compacted.
+    pub fn entries(&self) -> &[StateMachine::Op] {
+        &self.entries
+    }
+
+    /// Looks up an entry by index. Returns `None` if the entry does not exist or was compacted.
+    pub fn get(&self, index: usize) -> Option<&StateMachine::Op> {
+        index.checked_sub(self.snapshot_len).and_then(|i| self.entries.get(i))
+    }
+
+    /// Appends an operation, returning its index.
+    pub fn append(&mut self, op: StateMachine::Op) -> usize {
+        self.entries.push(op);
+        self.len() - 1
+    }
+
+    /// Applies entries before the specified index to the snapshot and discards them, so the entry
+    /// at that index is the first that remains. Indices at or before the current snapshot are
+    /// ignored, and indices beyond the log length are clamped.
+    pub fn compact(&mut self, index: usize) {
+        let index = std::cmp::min(index, self.len());
+        if index <= self.snapshot_len { return }
+        for op in self.entries.drain(..index - self.snapshot_len) {
+            self.snapshot.invoke(&op);
+        }
+        self.snapshot_len = index;
+    }
+
+    /// Computes the state machine after applying entries before the specified index. Returns
+    /// `None` if that index was compacted or is beyond the log length.
+    pub fn state_machine_at(&self, index: usize) -> Option<StateMachine>
+    where StateMachine: Clone,
+    {
+        if index < self.snapshot_len || self.len() < index { return None }
+        let mut state_machine = self.snapshot.clone();
+        for op in &self.entries[..index - self.snapshot_len] {
+            state_machine.invoke(op);
+        }
+        Some(state_machine)
+    }
+
+    /// Computes the state machine after applying every entry.
+    pub fn state_machine(&self) -> StateMachine
+    where StateMachine: Clone,
+    {
+        self.state_machine_at(self.len()).unwrap() // `len()` is never compacted
+    }
+
+    /// Indicates whether this log is a valid compaction of another log: both have the same
+    /// entries wherever neither is compacted, and this log's snapshot matches the state machine
+    /// that results from applying the other log's corresponding entries.
+    pub fn refines(&self, uncompacted: &Self) -> bool
+    where StateMachine: Clone + PartialEq,
+          StateMachine::Op: PartialEq,
+    {
+        if self.len() != uncompacted.len() { return false }
+        if self.snapshot_len < uncompacted.snapshot_len { return false }
+        let offset = self.snapshot_len - uncompacted.snapshot_len;
+        uncompacted.entries[offset..] == self.entries[..]
+            && uncompacted.state_machine_at(self.snapshot_len).as_ref() == Some(&self.snapshot)
+    }
+}
+
+impl<StateMachine> SequentialSpec for Log<StateMachine>
+where StateMachine: SequentialSpec,
+      StateMachine::Op: Clone,
+{
+    type Op = LogOp<StateMachine::Op>;
+    type Ret = LogRet;
+    fn invoke(&mut self, op: &Self::Op) -> Self::Ret {
+        match op {
+            LogOp::Append(op) => LogRet::AppendOk(self.append(op.clone())),
+            LogOp::Compact(index) => {
+                self.compact(*index);
+                LogRet::CompactOk
+            }
+            LogOp::Len => LogRet::LenOk(self.len()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::semantics::register::*;
+
+    #[test]
+    fn models_expected_semantics() {
+        let mut log = Log::new(Register('A'));
+        assert_eq!(log.invoke(&LogOp::Append(RegisterOp::Write('B'))), LogRet::AppendOk(0));
+        assert_eq!(log.invoke(&LogOp::Append(RegisterOp::Write('C'))), LogRet::AppendOk(1));
+        assert_eq!(log.invoke(&LogOp::Compact(1)),                      LogRet::CompactOk);
+        assert_eq!(log.invoke(&LogOp::Len),                             LogRet::LenOk(2));
+        assert_eq!(log.invoke(&LogOp::Compact(99)),                     LogRet::CompactOk);
+        assert_eq!(log.invoke(&LogOp::Len),                             LogRet::LenOk(2));
+        assert_eq!(log.snapshot(), &Register('C'));
+    }
+
+    #[test]
+    fn compaction_preserves_state_machine() {
+        let mut uncompacted = Log::new(Register('A'));
+        uncompacted.append(RegisterOp::Write('B'));
+        uncompacted.append(RegisterOp::Read);
+        uncompacted.append(RegisterOp::Write('C'));
+
+        let mut compacted = uncompacted.clone();
+        compacted.compact(2);
+        assert_eq!(compacted.snapshot_len(), 2);
+        assert_eq!(compacted.get(1), None);
+        assert_eq!(compacted.get(2), Some(&RegisterOp::Write('C')));
+        assert_eq!(compacted.state_machine(), uncompacted.state_machine());
+        assert_eq!(compacted.state_machine_at(1), None);
+        assert_eq!(uncompacted.state_machine_at(1), Some(Register('B')));
+        assert!(compacted.refines(&uncompacted));
+        assert!(uncompacted.refines(&uncompacted));
+        assert!(!uncompacted.refines(&compacted)); // compaction is irreversible
+    }
+
+    #[test]
+    fn compacts_entries_before_index() {
+        let mut log = Log::new(Register('A'));
+        log.append(RegisterOp::Write('B'));
+        log.append(RegisterOp::Write('C'));
+
+        log.compact(1);
+        assert_eq!(log.snapshot_len(), 1);
+        assert_eq!(log.snapshot(), &Register('B'));
+        assert_eq!(log.get(0), None);
+        assert_eq!(log.get(1), Some(&RegisterOp::Write('C')));
+
+        // Compacting at the snapshot boundary is a no-op.
+        log.compact(1);
+        assert_eq!(log.snapshot_len(), 1);
+        assert_eq!(log.get(1), Some(&RegisterOp::Write('C')));
+
+        log.compact(2);
+        assert_eq!(log.snapshot_len(), 2);
+        assert_eq!(log.snapshot(), &Register('C'));
+        assert_eq!(log.entries(), &[]);
+    }
+
+    #[test]
+    fn detects_invalid_compaction() {
+        let mut uncompacted = Log::new(Register('A'));
+        uncompacted.append(RegisterOp::Write('B'));
+        uncompacted.append(RegisterOp::Write('C'));
+
+        // Wrong snapshot.
+        let mut compacted = uncompacted.clone();
+        compacted.compact(1);
+        compacted.snapshot = Register('Z');
+        assert!(!compacted.refines(&uncompacted));
+
+        // Divergent entries.
+        let mut compacted = uncompacted.clone();
+        compacted.compact(1);
+        compacted.entries[0] = RegisterOp::Write('Z');
+        assert!(!compacted.refines(&uncompacted));
+
+        // Different length.
+        let mut compacted = uncompacted.clone();
+        compacted.append(RegisterOp::Read);
+        assert!(!compacted.refines(&uncompacted));
+    }
+}
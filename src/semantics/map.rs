@@ -0,0 +1,97 @@
+//! Implements [`SequentialSpec`] for [`BTreeMap`] operational semantics.
+
+use crate::semantics::{SequentialSpec, TransactionalSpec};
+use std::collections::BTreeMap;
+
+/// An operation that can be invoked upon a [`BTreeMap`], resulting in a
+/// [`MapRet`].
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[derive(serde::Serialize)]
+pub enum MapOp<K, V> { Put(K, V), Get(K) }
+
+/// A return value for a [`MapOp`] invoked upon a [`BTreeMap`].
+#[derive(Clone, Debug, Hash, PartialEq)]
+#[derive(serde::Serialize)]
+pub enum MapRet<V> { PutOk, GetOk(Option<V>) }
+
+impl<K, V> SequentialSpec for BTreeMap<K, V>
+    where K: Clone + Ord,
+          V: Clone + PartialEq,
+{
+    type Op = MapOp<K, V>;
+    type Ret = MapRet<V>;
+    fn invoke(&mut self, op: &Self::Op) -> Self::Ret {
+        match op {
+            MapOp::Put(k, v) => {
+                self.insert(k.clone(), v.clone());
+                MapRet::PutOk
+            }
+            MapOp::Get(k) => MapRet::GetOk(self.get(k).cloned()),
+        }
+    }
+    fn is_valid_step(&mut self, op: &Self::Op, ret: &Self::Ret) -> bool {
+        // Override to avoid unnecessary `clone` on `Get`.
+        match (op, ret) {
+            (MapOp::Put(k, v), MapRet::PutOk) => {
+                self.insert(k.clone(), v.clone());
+                true
+            }
+            (MapOp::Get(k), MapRet::GetOk(v)) => {
+                self.get(k) == v.as_ref()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<K, V> TransactionalSpec for BTreeMap<K, V>
+    where K: Clone + Ord,
+          V: Clone + PartialEq,
+{
+    type Key = K;
+    fn written_key(op: &Self::Op, _ret: &Self::Ret) -> Option<Self::Key> {
+        match op {
+            MapOp::Put(k, _) => Some(k.clone()),
+            MapOp::Get(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn models_expected_semantics() {
+        let mut m = BTreeMap::new();
+        assert_eq!(m.invoke(&MapOp::Get(1)),      MapRet::GetOk(None));
+        assert_eq!(m.invoke(&MapOp::Put(1, 'A')), MapRet::PutOk);
+        assert_eq!(m.invoke(&MapOp::Get(1)),      MapRet::GetOk(Some('A')));
+        assert_eq!(m.invoke(&MapOp::Get(2)),      MapRet::GetOk(None));
+        assert_eq!(m.invoke(&MapOp::Put(1, 'B')), MapRet::PutOk);
+        assert_eq!(m.invoke(&MapOp::Get(1)),      MapRet::GetOk(Some('B')));
+    }
+
+    #[test]
+    fn accepts_valid_histories() {
+        assert!(BTreeMap::<u8, char>::new().is_valid_history(vec![]));
+        assert!(BTreeMap::new().is_valid_history(vec![
+            (MapOp::Put(1, 'A'), MapRet::PutOk),
+            (MapOp::Get(2),      MapRet::GetOk(None)),
+            (MapOp::Put(2, 'B'), MapRet::PutOk),
+            (MapOp::Get(1),      MapRet::GetOk(Some('A'))),
+            (MapOp::Get(2),      MapRet::GetOk(Some('B'))),
+        ]));
+    }
+
+    #[test]
+    fn rejects_invalid_histories() {
+        assert!(!BTreeMap::new().is_valid_history(vec![
+            (MapOp::Put(1, 'A'), MapRet::PutOk),
+            (MapOp::Get(2),      MapRet::GetOk(Some('A'))),
+        ]));
+        assert!(!BTreeMap::<u8, char>::new().is_valid_history(vec![
+            (MapOp::Get(1),      MapRet::GetOk(Some('A'))),
+        ]));
+    }
+}
@@ -1,7 +1,8 @@
 //! Implements [`SequentialSpec`] for [`Register`] operational semantics.
 
+use serde_json::{json, Value};
 use std::fmt::Debug;
-use super::SequentialSpec;
+use super::{JepsenSpec, SequentialSpec, TransactionalSpec};
 
 /// A simple register used to define reference operational semantics via
 /// [`SequentialSpec`].
@@ -10,15 +11,17 @@ use super::SequentialSpec;
 pub struct Register<T>(pub T);
 
 /// An operation that can be invoked upon a [`Register`], resulting in a
-/// [`RegisterRet`]
+/// [`RegisterRet`]. `Cas(expected, new)` writes `new` only if the register
+/// holds `expected`.
 #[derive(Clone, Debug, Hash, PartialEq)]
 #[derive(serde::Serialize)]
-pub enum RegisterOp<T> { Write(T), Read }
+pub enum RegisterOp<T> { Write(T), Read, Cas(T, T) }
 
-/// A return value for a [`RegisterOp`] invoked upon a [`Register`].
+/// A return value for a [`RegisterOp`] invoked upon a [`Register`]. `CasOk`
+/// indicates whether the compare-and-swap succeeded.
 #[derive(Clone, Debug, Hash, PartialEq)]
 #[derive(serde::Serialize)]
-pub enum RegisterRet<T> { WriteOk, ReadOk(T) }
+pub enum RegisterRet<T> { WriteOk, ReadOk(T), CasOk(bool) }
 
 impl<T: Clone + Debug + PartialEq> SequentialSpec for Register<T> {
     type Op = RegisterOp<T>;
@@ -30,6 +33,14 @@ impl<T: Clone + Debug + PartialEq> SequentialSpec for Register<T> {
                 RegisterRet::WriteOk
             }
             RegisterOp::Read => RegisterRet::ReadOk(self.0.clone()),
+            RegisterOp::Cas(expected, new) => {
+                if &self.0 == expected {
+                    self.0 = new.clone();
+                    RegisterRet::CasOk(true)
+                } else {
+                    RegisterRet::CasOk(false)
+                }
+            }
         }
     }
     fn is_valid_step(&mut self, op: &Self::Op, ret: &Self::Ret) -> bool {
@@ -42,11 +53,49 @@ impl<T: Clone + Debug + PartialEq> SequentialSpec for Register<T> {
             (RegisterOp::Read, RegisterRet::ReadOk(v)) => {
                 &self.0 == v
             }
+            (RegisterOp::Cas(expected, new), RegisterRet::CasOk(swapped)) => {
+                if &self.0 != expected { return !swapped }
+                if *swapped { self.0 = new.clone(); }
+                *swapped
+            }
             _ => false,
         }
     }
 }
 
+impl<T: Clone + Debug + PartialEq> TransactionalSpec for Register<T> {
+    type Key = ();
+    fn written_key(op: &Self::Op, ret: &Self::Ret) -> Option<Self::Key> {
+        match (op, ret) {
+            (RegisterOp::Write(_), _) => Some(()),
+            (RegisterOp::Cas(_, _), RegisterRet::CasOk(true)) => Some(()),
+            _ => None,
+        }
+    }
+}
+
+/// Follows the conventions of Jepsen's register workloads: `Cas(expected, new)`
+/// has the value `[expected, new]`, and fails if the swap does not occur.
+impl<T: Clone + Debug + PartialEq + serde::Serialize> JepsenSpec for Register<T> {
+    fn jepsen_invocation(op: &Self::Op) -> (&'static str, Value) {
+        match op {
+            RegisterOp::Write(v) => ("write", json!(v)),
+            RegisterOp::Read => ("read", Value::Null),
+            RegisterOp::Cas(expected, new) => ("cas", json!([expected, new])),
+        }
+    }
+    fn jepsen_completion(op: &Self::Op, ret: &Self::Ret) -> (&'static str, Value) {
+        match (op, ret) {
+            (RegisterOp::Write(v), RegisterRet::WriteOk) => ("ok", json!(v)),
+            (RegisterOp::Read, RegisterRet::ReadOk(v)) => ("ok", json!(v)),
+            (RegisterOp::Cas(expected, new), RegisterRet::CasOk(swapped)) => {
+                (if *swapped { "ok" } else { "fail" }, json!([expected, new]))
+            }
+            _ => ("info", Value::Null),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -57,6 +106,10 @@ mod test {
         assert_eq!(r.invoke(&RegisterOp::Read),       RegisterRet::ReadOk('A'));
         assert_eq!(r.invoke(&RegisterOp::Write('B')), RegisterRet::WriteOk);
         assert_eq!(r.invoke(&RegisterOp::Read),       RegisterRet::ReadOk('B'));
+        assert_eq!(r.invoke(&RegisterOp::Cas('A', 'C')), RegisterRet::CasOk(false));
+        assert_eq!(r.invoke(&RegisterOp::Read),          RegisterRet::ReadOk('B'));
+        assert_eq!(r.invoke(&RegisterOp::Cas('B', 'C')), RegisterRet::CasOk(true));
+        assert_eq!(r.invoke(&RegisterOp::Read),          RegisterRet::ReadOk('C'));
     }
 
     #[test]
@@ -69,6 +122,11 @@ mod test {
             (RegisterOp::Write('C'), RegisterRet::WriteOk),
             (RegisterOp::Read,       RegisterRet::ReadOk('C')),
         ]));
+        assert!(Register('A').is_valid_history(vec![
+            (RegisterOp::Cas('B', 'C'), RegisterRet::CasOk(false)),
+            (RegisterOp::Cas('A', 'C'), RegisterRet::CasOk(true)),
+            (RegisterOp::Read,          RegisterRet::ReadOk('C')),
+        ]));
     }
 
     #[test]
@@ -81,5 +139,15 @@ mod test {
             (RegisterOp::Write('B'), RegisterRet::WriteOk),
             (RegisterOp::Read,       RegisterRet::ReadOk('A')),
         ]));
+        assert!(!Register('A').is_valid_history(vec![
+            (RegisterOp::Cas('A', 'B'), RegisterRet::CasOk(false)),
+        ]));
+        assert!(!Register('A').is_valid_history(vec![
+            (RegisterOp::Cas('B', 'C'), RegisterRet::CasOk(true)),
+        ]));
+        assert!(!Register('A').is_valid_history(vec![
+            (RegisterOp::Cas('A', 'B'), RegisterRet::CasOk(true)),
+            (RegisterOp::Read,          RegisterRet::ReadOk('A')),
+        ]));
     }
 }
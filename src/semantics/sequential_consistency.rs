@@ -1,6 +1,6 @@
 //! Private module for selective re-export. See [`SequentialConsistencyTester`].
 
-use crate::semantics::SequentialSpec;
+use crate::semantics::{ConsistencyTester, SequentialSpec};
 use std::collections::{btree_map, BTreeMap, VecDeque};
 use std::fmt::Debug;
 
@@ -56,9 +56,13 @@ pub struct SequentialConsistencyTester<ThreadId, RefObj: SequentialSpec> {
     init_ref_obj: RefObj,
     history_by_thread: BTreeMap<ThreadId, VecDeque<(RefObj::Op, RefObj::Ret)>>,
     in_flight_by_thread: BTreeMap<ThreadId, RefObj::Op>,
-    is_valid_history: bool,
+    indeterminate: Vec<Indeterminate<ThreadId, RefObj::Op>>,
+    invalid_history: Option<String>,
 }
 
+/// A timed out operation, along with the number of operations its thread had completed.
+type Indeterminate<ThreadId, Op> = (ThreadId, usize, Op);
+
 #[allow(clippy::len_without_is_empty)] // no use case for an emptiness check
 impl<T: Ord, RefObj: SequentialSpec> SequentialConsistencyTester<T, RefObj> {
     /// Constructs a [`SequentialConsistencyTester`].
@@ -67,14 +71,15 @@ impl<T: Ord, RefObj: SequentialSpec> SequentialConsistencyTester<T, RefObj> {
             init_ref_obj,
             history_by_thread: Default::default(),
             in_flight_by_thread: Default::default(),
-            is_valid_history: true,
+            indeterminate: Default::default(),
+            invalid_history: None,
         }
     }
 
-    /// Indicates the aggregate number of operations completed or in flight
-    /// across all threads.
+    /// Indicates the aggregate number of operations completed, in flight, or
+    /// indeterminate across all threads.
     pub fn len(&self) -> usize {
-        let mut len = self.in_flight_by_thread.len();
+        let mut len = self.in_flight_by_thread.len() + self.indeterminate.len();
         for history in self.history_by_thread.values() {
             len += history.len();
         }
@@ -94,15 +99,16 @@ where
     ///
     /// See [`SequentialConsistencyTester::serialized_history`].
     pub fn on_invoke(&mut self, thread_id: T, op: RefObj::Op) -> Result<&mut Self, String> {
-        if !self.is_valid_history {
+        if self.invalid_history.is_some() {
             return Err("Earlier history was invalid.".to_string());
         }
         let in_flight_elem = self.in_flight_by_thread.entry(thread_id);
         if let btree_map::Entry::Occupied(occupied_op_entry) = in_flight_elem {
-            self.is_valid_history = false;
-            return Err(format!(
+            let reason = format!(
                     "Thread already has an operation in flight. thread_id={:?}, op={:?}, history_by_thread={:?}",
-                    thread_id, occupied_op_entry.get(), self.history_by_thread));
+                    thread_id, occupied_op_entry.get(), self.history_by_thread);
+            self.invalid_history = Some(reason.clone());
+            return Err(reason);
         };
         in_flight_elem.or_insert(op);
         self.history_by_thread.entry(thread_id).or_insert_with(VecDeque::new); // `serialize` requires entry
@@ -115,16 +121,17 @@ where
     ///
     /// See [`SequentialConsistencyTester::serialized_history`].
     pub fn on_return(&mut self, thread_id: T, ret: RefObj::Ret) -> Result<&mut Self, String> {
-        if !self.is_valid_history {
+        if self.invalid_history.is_some() {
             return Err("Earlier history was invalid.".to_string());
         }
         let op = match self.in_flight_by_thread.remove(&thread_id) {
             None => {
-                self.is_valid_history = false;
-                return Err(format!(
+                let reason = format!(
                     "There is no in-flight invocation for this thread ID. \
                      thread_id={:?}, unexpected_return={:?}, history={:?}",
-                    thread_id, ret, self.history_by_thread.entry(thread_id).or_insert_with(VecDeque::new)));
+                    thread_id, ret, self.history_by_thread.entry(thread_id).or_default());
+                self.invalid_history = Some(reason.clone());
+                return Err(reason);
             }
             Some(op) => op,
         };
@@ -132,6 +139,48 @@ where
         Ok(self)
     }
 
+    /// Indicates that a thread gave up waiting for its in-flight operation to
+    /// return, such as a client that times out. The operation becomes
+    /// indeterminate: it may or may not take effect, and if it does, it can do
+    /// so at any point after the thread's earlier operations (even after
+    /// subsequent operations by the same thread). The thread is then free to
+    /// invoke another operation. Returns `Ok(...)` if the history is valid,
+    /// even if it is not sequentially consistent.
+    ///
+    /// See [`SequentialConsistencyTester::serialized_history`].
+    pub fn on_timeout(&mut self, thread_id: T) -> Result<&mut Self, String> {
+        if self.invalid_history.is_some() {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        let op = match self.in_flight_by_thread.remove(&thread_id) {
+            None => {
+                let reason = format!(
+                    "There is no in-flight invocation for this thread ID. \
+                     thread_id={:?}, history={:?}",
+                    thread_id, self.history_by_thread.entry(thread_id).or_default());
+                self.invalid_history = Some(reason.clone());
+                return Err(reason);
+            }
+            Some(op) => op,
+        };
+        let completed_count = self.history_by_thread.get(&thread_id).map_or(0, VecDeque::len);
+        self.indeterminate.push((thread_id, completed_count, op));
+        Ok(self)
+    }
+
+    /// Indicates whether a thread has an operation in flight (as opposed to
+    /// completed or indeterminate).
+    pub fn is_in_flight(&self, thread_id: T) -> bool {
+        self.in_flight_by_thread.contains_key(&thread_id)
+    }
+
+    /// Indicates why the history is invalid, such as a return without a
+    /// corresponding invocation, or `None` if the history is valid. An invalid
+    /// history is never sequentially consistent, and the tester rejects further operations.
+    pub fn invalid_history(&self) -> Option<&str> {
+        self.invalid_history.as_deref()
+    }
+
     /// A helper that indicates both an operation and corresponding return
     /// value for a thread. Returns `Ok(...)` if the history is valid, even if
     /// it is not sequentially consistent.
@@ -151,20 +200,24 @@ where
         RefObj::Op: Clone,
         RefObj::Ret: Clone,
     {
-        if !self.is_valid_history { return None }
+        if self.invalid_history.is_some() { return None }
         Self::serialize(
             Vec::new(),
             &self.init_ref_obj,
             &self.history_by_thread,
-            &self.in_flight_by_thread)
+            &self.history_by_thread,
+            &self.in_flight_by_thread,
+            &self.indeterminate)
     }
 
     #[allow(clippy::type_complexity)]
     fn serialize(
          valid_history: Vec<(RefObj::Op, RefObj::Ret)>, // total order
          ref_obj: &RefObj,
+         history_by_thread: &BTreeMap<T, VecDeque<(RefObj::Op, RefObj::Ret)>>, // for indeterminate
          remaining_history_by_thread: &BTreeMap<T, VecDeque<(RefObj::Op, RefObj::Ret)>>, // partial order
-         in_flight_by_thread: &BTreeMap<T, RefObj::Op>) // potential extension of partial order
+         in_flight_by_thread: &BTreeMap<T, RefObj::Op>, // potential extension of partial order
+         indeterminate: &[Indeterminate<T, RefObj::Op>]) // another potential extension
         -> Option<Vec<(RefObj::Op, RefObj::Ret)>>
     where
         RefObj: Clone,
@@ -201,7 +254,27 @@ where
                 (ref_obj, valid_history)
             };
             if let Some(valid_history) = Self::serialize(
-                        valid_history, &ref_obj, &remaining_history_by_thread, &in_flight_by_thread) {
+                        valid_history, &ref_obj, history_by_thread, &remaining_history_by_thread,
+                        &in_flight_by_thread, indeterminate) {
+                return Some(valid_history)
+            }
+        }
+
+        // Case 3: An indeterminate operation takes effect now, provided its thread's earlier
+        // operations already have.
+        for (i, (thread_id, completed_count, op)) in indeterminate.iter().enumerate() {
+            let total_count = history_by_thread.get(thread_id).map_or(0, VecDeque::len);
+            let remaining_count = remaining_history_by_thread.get(thread_id).map_or(0, VecDeque::len);
+            if total_count - remaining_count < *completed_count { continue }
+            let mut ref_obj = ref_obj.clone();
+            let ret = ref_obj.invoke(op);
+            let mut valid_history = valid_history.clone();
+            valid_history.push((op.clone(), ret));
+            let mut indeterminate = indeterminate.to_vec();
+            indeterminate.remove(i);
+            if let Some(valid_history) = Self::serialize(
+                        valid_history, &ref_obj, history_by_thread, remaining_history_by_thread,
+                        in_flight_by_thread, &indeterminate) {
                 return Some(valid_history)
             }
         }
@@ -209,6 +282,40 @@ where
     }
 }
 
+impl<T, RefObj> ConsistencyTester<T, RefObj::Op, RefObj::Ret> for SequentialConsistencyTester<T, RefObj>
+where
+    T: Copy + Debug + Ord,
+    RefObj: Clone + SequentialSpec,
+    RefObj::Op: Clone + Debug,
+    RefObj::Ret: Clone + Debug + PartialEq,
+{
+    fn consistency_model() -> &'static str { "sequentially consistent" }
+
+    fn on_invoke(&mut self, thread_id: T, op: RefObj::Op) -> Result<&mut Self, String> {
+        SequentialConsistencyTester::on_invoke(self, thread_id, op)
+    }
+
+    fn on_return(&mut self, thread_id: T, ret: RefObj::Ret) -> Result<&mut Self, String> {
+        SequentialConsistencyTester::on_return(self, thread_id, ret)
+    }
+
+    fn on_timeout(&mut self, thread_id: T) -> Result<&mut Self, String> {
+        SequentialConsistencyTester::on_timeout(self, thread_id)
+    }
+
+    fn is_in_flight(&self, thread_id: T) -> bool {
+        SequentialConsistencyTester::is_in_flight(self, thread_id)
+    }
+
+    fn invalid_history(&self) -> Option<&str> {
+        SequentialConsistencyTester::invalid_history(self)
+    }
+
+    fn is_consistent(&self) -> bool {
+        self.serialized_history().is_some()
+    }
+}
+
 impl<T: Ord, RefObj> Default for SequentialConsistencyTester<T, RefObj>
 where RefObj: Default + SequentialSpec
 {
@@ -225,11 +332,12 @@ where RefObj: serde::Serialize + SequentialSpec,
 {
     fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
         use serde::ser::SerializeStruct;
-        let mut out = ser.serialize_struct("SequentialConsistencyTester", 4)?;
+        let mut out = ser.serialize_struct("SequentialConsistencyTester", 5)?;
         out.serialize_field("init_ref_obj", &self.init_ref_obj)?;
         out.serialize_field("history_by_thread", &self.history_by_thread)?;
         out.serialize_field("in_flight_by_thread", &self.in_flight_by_thread)?;
-        out.serialize_field("is_valid_history", &self.is_valid_history)?;
+        out.serialize_field("indeterminate", &self.indeterminate)?;
+        out.serialize_field("invalid_history", &self.invalid_history)?;
         out.end()
     }
 }
@@ -291,6 +399,46 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn treats_timed_out_operations_as_indeterminate() -> Result<(), String> {
+        // The write may take effect...
+        assert_eq!(
+            SequentialConsistencyTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .serialized_history(),
+            Some(vec![
+                (RegisterOp::Write('B'), RegisterRet::WriteOk),
+                (RegisterOp::Read, RegisterRet::ReadOk('B')),
+            ]));
+        // ... or not ...
+        assert_eq!(
+            SequentialConsistencyTester::new(Register('A'))
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .on_invret(0, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .serialized_history(),
+            Some(vec![
+                (RegisterOp::Read, RegisterRet::ReadOk('A')),
+            ]));
+        // ... but not before the thread's earlier operations.
+        assert_eq!(
+            SequentialConsistencyTester::new(Register('A'))
+                .on_invret(0, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_invoke(0, RegisterOp::Write('B'))?
+                .on_timeout(0)?
+                .serialized_history(),
+            None);
+        assert_eq!(
+            SequentialConsistencyTester::new(Register('A'))
+                .on_timeout(0)
+                .map(|_| ()),
+            Err("There is no in-flight invocation for this thread ID. \
+                 thread_id=0, history=[]".to_string()));
+        Ok(())
+    }
+
     #[test]
     fn identifies_serializable_vec_history() -> Result<(), String> {
         assert_eq!(
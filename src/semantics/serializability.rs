@@ -0,0 +1,355 @@
+//! Private module for selective re-export. See [`SerializabilityTester`].
+
+use crate::semantics::SequentialSpec;
+use std::collections::{btree_map, BTreeMap};
+use std::fmt::Debug;
+
+/// This tester captures a potentially concurrent history of transactions, each consisting of
+/// multiple operations, and validates that it adheres to a [`SequentialSpec`] based on the
+/// [serializability] model. This model requires that the committed transactions have the same
+/// effect as if they had been executed one at a time in some total order, with the operations of
+/// each transaction applied in the order they were performed.
+///
+/// # Serializability
+///
+/// Like [sequential consistency], there is no intrinsic order of transactions, even if they are
+/// fully sequenced in "real-time." Unlike sequential consistency, there is also no order among
+/// transactions issued by the same client, so each transaction is identified by a distinct
+/// transaction ID rather than a thread ID.
+///
+/// Only committed transactions are serialized. Aborted transactions must have had no effect, and
+/// transactions that are still in progress cannot have had an effect yet. Operations within
+/// aborted transactions are not validated, so this tester does not verify stronger properties
+/// such as [opacity].
+///
+/// # Example
+///
+/// ```
+/// use stateright::semantics::*;
+/// use stateright::semantics::register::*;
+///
+/// let mut tester = SerializabilityTester::new(Register('A'));
+/// tester.on_begin(1)?.on_begin(2)?
+///     .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+///     .on_op(2, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+///     .on_op(1, RegisterOp::Write('C'), RegisterRet::WriteOk)?
+///     .on_commit(2)?
+///     .on_commit(1)?;
+///
+/// // Transaction 1 must precede transaction 2, as it read the initial value.
+/// assert_eq!(
+///     tester.serialized_history(),
+///     Some(vec![
+///         vec![(RegisterOp::Read, RegisterRet::ReadOk('A')),
+///              (RegisterOp::Write('C'), RegisterRet::WriteOk)],
+///         vec![(RegisterOp::Write('B'), RegisterRet::WriteOk)],
+///     ]));
+/// # Ok::<(), String>(())
+/// ```
+///
+/// See also: [`SequentialConsistencyTester`].
+///
+/// [opacity]: https://doi.org/10.1145/1345206.1345233
+/// [sequential consistency]: https://en.wikipedia.org/wiki/Sequential_consistency
+/// [serializability]: https://en.wikipedia.org/wiki/Serializability
+/// [`SequentialConsistencyTester`]: crate::semantics::SequentialConsistencyTester
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[allow(clippy::type_complexity)]
+pub struct SerializabilityTester<TxnId, RefObj: SequentialSpec> {
+    init_ref_obj: RefObj,
+    in_progress: BTreeMap<TxnId, Vec<(RefObj::Op, RefObj::Ret)>>,
+    committed: BTreeMap<TxnId, Vec<(RefObj::Op, RefObj::Ret)>>,
+    aborted_count: usize,
+    is_valid_history: bool,
+}
+
+#[allow(clippy::len_without_is_empty)] // no use case for an emptiness check
+impl<T: Ord, RefObj: SequentialSpec> SerializabilityTester<T, RefObj> {
+    /// Constructs a [`SerializabilityTester`].
+    pub fn new(init_ref_obj: RefObj) -> Self {
+        Self {
+            init_ref_obj,
+            in_progress: Default::default(),
+            committed: Default::default(),
+            aborted_count: 0,
+            is_valid_history: true,
+        }
+    }
+
+    /// Indicates the aggregate number of transactions committed, aborted, or in progress.
+    pub fn len(&self) -> usize {
+        self.in_progress.len() + self.committed.len() + self.aborted_count
+    }
+}
+
+impl<T, RefObj> SerializabilityTester<T, RefObj>
+where
+    T: Copy + Debug + Ord,
+    RefObj: SequentialSpec,
+    RefObj::Op: Debug,
+    RefObj::Ret: Debug + PartialEq,
+{
+    /// Indicates that a transaction began. Returns `Ok(...)` if the history is valid, even if it
+    /// is not serializable.
+    ///
+    /// See [`SerializabilityTester::serialized_history`].
+    pub fn on_begin(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        if self.committed.contains_key(&txn_id) {
+            self.is_valid_history = false;
+            return Err(format!("Transaction already committed. txn_id={:?}", txn_id));
+        }
+        match self.in_progress.entry(txn_id) {
+            btree_map::Entry::Occupied(_) => {
+                self.is_valid_history = false;
+                Err(format!("Transaction already in progress. txn_id={:?}", txn_id))
+            }
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(Vec::new());
+                Ok(self)
+            }
+        }
+    }
+
+    /// Indicates that an operation within a transaction returned. Returns `Ok(...)` if the
+    /// history is valid, even if it is not serializable.
+    ///
+    /// See [`SerializabilityTester::serialized_history`].
+    pub fn on_op(&mut self, txn_id: T, op: RefObj::Op, ret: RefObj::Ret) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        match self.in_progress.get_mut(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                Err(format!(
+                    "There is no transaction in progress for this transaction ID. \
+                     txn_id={:?}, unexpected_op={:?}, unexpected_ret={:?}",
+                    txn_id, op, ret))
+            }
+            Some(ops) => {
+                ops.push((op, ret));
+                Ok(self)
+            }
+        }
+    }
+
+    /// Indicates that a transaction committed. Returns `Ok(...)` if the history is valid, even if
+    /// it is not serializable.
+    ///
+    /// See [`SerializabilityTester::serialized_history`].
+    pub fn on_commit(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        match self.in_progress.remove(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                Err(format!(
+                    "There is no transaction in progress to commit. txn_id={:?}", txn_id))
+            }
+            Some(ops) => {
+                self.committed.insert(txn_id, ops);
+                Ok(self)
+            }
+        }
+    }
+
+    /// Indicates that a transaction aborted, so its operations must have had no effect. Returns
+    /// `Ok(...)` if the history is valid, even if it is not serializable.
+    ///
+    /// See [`SerializabilityTester::serialized_history`].
+    pub fn on_abort(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        match self.in_progress.remove(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                Err(format!(
+                    "There is no transaction in progress to abort. txn_id={:?}", txn_id))
+            }
+            Some(_) => {
+                self.aborted_count += 1;
+                Ok(self)
+            }
+        }
+    }
+
+    /// Attempts to serialize the committed transactions into a total order that is consistent
+    /// with a reference object's operational semantics, returning the operations of each
+    /// transaction in that order.
+    #[allow(clippy::type_complexity)]
+    pub fn serialized_history(&self) -> Option<Vec<Vec<(RefObj::Op, RefObj::Ret)>>>
+    where
+        RefObj: Clone,
+        RefObj::Op: Clone,
+        RefObj::Ret: Clone,
+    {
+        if !self.is_valid_history { return None }
+        let remaining: Vec<_> = self.committed.values().collect();
+        let mut order = Vec::with_capacity(remaining.len());
+        if !Self::serialize(&mut order, &self.init_ref_obj, &mut vec![false; remaining.len()], &remaining) {
+            return None;
+        }
+        Some(order.into_iter().map(|i| remaining[i].clone()).collect())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn serialize(
+        order: &mut Vec<usize>, // indices of serialized transactions
+        ref_obj: &RefObj,
+        is_serialized: &mut Vec<bool>,
+        txns: &[&Vec<(RefObj::Op, RefObj::Ret)>])
+        -> bool
+    where
+        RefObj: Clone,
+    {
+        if order.len() == txns.len() { return true }
+
+        // Try each remaining transaction next, applying its operations atomically.
+        for (i, txn) in txns.iter().enumerate() {
+            if is_serialized[i] { continue }
+            let mut ref_obj = ref_obj.clone();
+            if !txn.iter().all(|(op, ret)| ref_obj.is_valid_step(op, ret)) { continue }
+            is_serialized[i] = true;
+            order.push(i);
+            if Self::serialize(order, &ref_obj, is_serialized, txns) { return true }
+            order.pop();
+            is_serialized[i] = false;
+        }
+        false
+    }
+}
+
+impl<T: Ord, RefObj> Default for SerializabilityTester<T, RefObj>
+where RefObj: Default + SequentialSpec
+{
+    fn default() -> Self {
+        Self::new(RefObj::default())
+    }
+}
+
+impl<T, RefObj> serde::Serialize for SerializabilityTester<T, RefObj>
+where RefObj: serde::Serialize + SequentialSpec,
+      RefObj::Op: serde::Serialize,
+      RefObj::Ret: serde::Serialize,
+      T: Ord + serde::Serialize,
+{
+    fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
+        use serde::ser::SerializeStruct;
+        let mut out = ser.serialize_struct("SerializabilityTester", 5)?;
+        out.serialize_field("init_ref_obj", &self.init_ref_obj)?;
+        out.serialize_field("in_progress", &self.in_progress)?;
+        out.serialize_field("committed", &self.committed)?;
+        out.serialize_field("aborted_count", &self.aborted_count)?;
+        out.serialize_field("is_valid_history", &self.is_valid_history)?;
+        out.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::semantics::map::*;
+    use crate::semantics::register::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn rejects_invalid_history() -> Result<(), String> {
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_begin(1)?
+                .on_begin(1),
+            Err("Transaction already in progress. txn_id=1".to_string()));
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('A')),
+            Err("There is no transaction in progress for this transaction ID. \
+                 txn_id=1, unexpected_op=Read, unexpected_ret=ReadOk('A')".to_string()));
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_begin(1)?
+                .on_commit(1)?
+                .on_abort(1),
+            Err("There is no transaction in progress to abort. txn_id=1".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn identifies_serializable_history() -> Result<(), String> {
+        // Write skew is impossible if both transactions observe the other's write.
+        assert_eq!(
+            SerializabilityTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Get('x'), MapRet::GetOk(None))?
+                .on_op(2, MapOp::Get('x'), MapRet::GetOk(Some(1)))?
+                .on_op(1, MapOp::Put('x', 1), MapRet::PutOk)?
+                .on_op(2, MapOp::Put('y', 2), MapRet::PutOk)?
+                .on_commit(2)?
+                .on_commit(1)?
+                .serialized_history(),
+            Some(vec![
+                vec![(MapOp::Get('x'), MapRet::GetOk(None)), (MapOp::Put('x', 1), MapRet::PutOk)],
+                vec![(MapOp::Get('x'), MapRet::GetOk(Some(1))), (MapOp::Put('y', 2), MapRet::PutOk)],
+            ]));
+
+        // Aborted and in-progress transactions have no effect.
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_begin(1)?.on_begin(2)?.on_begin(3)?
+                .on_op(1, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_op(2, RegisterOp::Write('C'), RegisterRet::WriteOk)?
+                .on_abort(1)?
+                .on_op(3, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .on_commit(3)?
+                .serialized_history(),
+            Some(vec![
+                vec![(RegisterOp::Read, RegisterRet::ReadOk('A'))],
+            ]));
+        Ok(())
+    }
+
+    #[test]
+    fn identifies_unserializable_history() -> Result<(), String> {
+        // Write skew: each transaction reads a key that the other writes, but neither observes
+        // the other's write.
+        assert_eq!(
+            SerializabilityTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Get('x'), MapRet::GetOk(None))?
+                .on_op(2, MapOp::Get('y'), MapRet::GetOk(None))?
+                .on_op(1, MapOp::Put('y', 1), MapRet::PutOk)?
+                .on_op(2, MapOp::Put('x', 2), MapRet::PutOk)?
+                .on_commit(1)?
+                .on_commit(2)?
+                .serialized_history(),
+            None);
+
+        // Reads from an aborted transaction.
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_op(2, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_abort(1)?
+                .on_commit(2)?
+                .serialized_history(),
+            None);
+
+        // Transactions are atomic: no interleaving within a transaction.
+        assert_eq!(
+            SerializabilityTester::new(Register('A'))
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .on_op(2, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_commit(1)?
+                .on_commit(2)?
+                .serialized_history(),
+            None);
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+//! Private module for selective re-export. See [`SnapshotIsolationTester`].
+
+use crate::semantics::TransactionalSpec;
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::fmt::Debug;
+
+/// This tester captures a potentially concurrent history of transactions, each consisting of
+/// multiple operations, and validates that it adheres to a [`TransactionalSpec`] based on the
+/// [snapshot isolation] model. The tester accepts the same events as a [`SerializabilityTester`],
+/// so a single recording can be checked against both models.
+///
+/// # Snapshot Isolation
+///
+/// Each transaction operates on a snapshot consisting of the transactions that committed before
+/// the snapshot was taken, along with the transaction's own earlier writes. The snapshot is taken
+/// at some point between the transaction beginning and its first operation returning. Committed
+/// transactions then take effect in the order in which they commit.
+///
+/// Two concurrent transactions (where neither committed before the other's snapshot) cannot both
+/// commit if they write the same key, a rule known as "first committer wins." Transactions that
+/// write disjoint keys can both commit, so anomalies such as "write skew" are allowed even though
+/// they are not [serializable].
+///
+/// Timestamps are implied by the order in which events are recorded, so events must be recorded
+/// in the order the system under test observes them (for example when messages enter/leave the
+/// system rather than when they enter/leave the clients).
+///
+/// # Example
+///
+/// ```
+/// use stateright::semantics::*;
+/// use stateright::semantics::map::*;
+/// use std::collections::BTreeMap;
+///
+/// // Write skew: each transaction reads a key that the other writes.
+/// let mut serializability = SerializabilityTester::new(BTreeMap::new());
+/// let mut snapshot_isolation = SnapshotIsolationTester::new(BTreeMap::new());
+/// for txn_id in &[1, 2] {
+///     serializability.on_begin(*txn_id)?;
+///     snapshot_isolation.on_begin(*txn_id)?;
+/// }
+/// for (txn_id, read, write) in &[(1, 'x', 'y'), (2, 'y', 'x')] {
+///     for (op, ret) in vec![(MapOp::Get(*read), MapRet::GetOk(None)),
+///                           (MapOp::Put(*write, *txn_id), MapRet::PutOk)] {
+///         serializability.on_op(*txn_id, op.clone(), ret.clone())?;
+///         snapshot_isolation.on_op(*txn_id, op, ret)?;
+///     }
+///     serializability.on_commit(*txn_id)?;
+///     snapshot_isolation.on_commit(*txn_id)?;
+/// }
+/// assert_eq!(serializability.serialized_history(), None);
+/// assert!(snapshot_isolation.is_snapshot_isolated());
+/// # Ok::<(), String>(())
+/// ```
+///
+/// [serializable]: crate::semantics::SerializabilityTester
+/// [snapshot isolation]: https://en.wikipedia.org/wiki/Snapshot_isolation
+/// [`SerializabilityTester`]: crate::semantics::SerializabilityTester
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[allow(clippy::type_complexity)]
+pub struct SnapshotIsolationTester<TxnId, RefObj: TransactionalSpec> {
+    states: Vec<RefObj>, // state after each commit that wrote a key
+    writes: Vec<BTreeSet<RefObj::Key>>, // keys written by each of those commits
+    in_progress: BTreeMap<TxnId, Transaction<RefObj::Op, RefObj::Ret>>,
+    committed: BTreeSet<TxnId>,
+    aborted_count: usize,
+    is_isolated: bool,
+    is_valid_history: bool,
+}
+
+/// An in-progress transaction.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, serde::Serialize)]
+struct Transaction<Op, Ret> {
+    /// Index into `states` of the earliest viable snapshot.
+    begin_index: usize,
+    /// Index into `states` of the latest viable snapshot, if an operation has returned.
+    first_op_index: Option<usize>,
+    ops: Vec<(Op, Ret)>,
+}
+
+#[allow(clippy::len_without_is_empty)] // no use case for an emptiness check
+impl<T: Ord, RefObj: TransactionalSpec> SnapshotIsolationTester<T, RefObj> {
+    /// Constructs a [`SnapshotIsolationTester`].
+    pub fn new(init_ref_obj: RefObj) -> Self {
+        Self {
+            states: vec![init_ref_obj],
+            writes: Vec::new(),
+            in_progress: Default::default(),
+            committed: Default::default(),
+            aborted_count: 0,
+            is_isolated: true,
+            is_valid_history: true,
+        }
+    }
+
+    /// Indicates the aggregate number of transactions committed, aborted, or in progress.
+    pub fn len(&self) -> usize {
+        self.in_progress.len() + self.committed.len() + self.aborted_count
+    }
+
+    /// Indicates whether the recorded history is valid and adheres to snapshot isolation.
+    pub fn is_snapshot_isolated(&self) -> bool {
+        self.is_valid_history && self.is_isolated
+    }
+}
+
+impl<T, RefObj> SnapshotIsolationTester<T, RefObj>
+where
+    T: Copy + Debug + Ord,
+    RefObj: Clone + TransactionalSpec,
+    RefObj::Op: Debug,
+    RefObj::Ret: Debug + PartialEq,
+{
+    /// Indicates that a transaction began. Returns `Ok(...)` if the history is valid, even if it
+    /// does not adhere to snapshot isolation.
+    ///
+    /// See [`SnapshotIsolationTester::is_snapshot_isolated`].
+    pub fn on_begin(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        if self.committed.contains(&txn_id) {
+            self.is_valid_history = false;
+            return Err(format!("Transaction already committed. txn_id={:?}", txn_id));
+        }
+        let begin_index = self.states.len() - 1;
+        match self.in_progress.entry(txn_id) {
+            btree_map::Entry::Occupied(_) => {
+                self.is_valid_history = false;
+                Err(format!("Transaction already in progress. txn_id={:?}", txn_id))
+            }
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(Transaction { begin_index, first_op_index: None, ops: Vec::new() });
+                Ok(self)
+            }
+        }
+    }
+
+    /// Indicates that an operation within a transaction returned. Returns `Ok(...)` if the
+    /// history is valid, even if it does not adhere to snapshot isolation.
+    ///
+    /// See [`SnapshotIsolationTester::is_snapshot_isolated`].
+    pub fn on_op(&mut self, txn_id: T, op: RefObj::Op, ret: RefObj::Ret) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        let latest_index = self.states.len() - 1;
+        match self.in_progress.get_mut(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                Err(format!(
+                    "There is no transaction in progress for this transaction ID. \
+                     txn_id={:?}, unexpected_op={:?}, unexpected_ret={:?}",
+                    txn_id, op, ret))
+            }
+            Some(txn) => {
+                txn.first_op_index.get_or_insert(latest_index);
+                txn.ops.push((op, ret));
+                Ok(self)
+            }
+        }
+    }
+
+    /// Indicates that a transaction committed. Returns `Ok(...)` if the history is valid, even if
+    /// it does not adhere to snapshot isolation.
+    ///
+    /// See [`SnapshotIsolationTester::is_snapshot_isolated`].
+    pub fn on_commit(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        let txn = match self.in_progress.remove(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                return Err(format!(
+                    "There is no transaction in progress to commit. txn_id={:?}", txn_id));
+            }
+            Some(txn) => txn,
+        };
+        self.committed.insert(txn_id);
+
+        // Find a viable snapshot: one against which the operations are valid, and after which no
+        // other transaction committed a write to a key that this transaction also writes.
+        let written: BTreeSet<_> = txn.ops.iter()
+            .filter_map(|(op, ret)| RefObj::written_key(op, ret))
+            .collect();
+        let latest_index = txn.first_op_index.unwrap_or(self.states.len() - 1);
+        let has_snapshot = (txn.begin_index..=latest_index).any(|index| {
+            let first_committer_wins = self.writes[index..].iter()
+                .all(|later_writes| later_writes.is_disjoint(&written));
+            first_committer_wins && {
+                let mut snapshot = self.states[index].clone();
+                txn.ops.iter().all(|(op, ret)| snapshot.is_valid_step(op, ret))
+            }
+        });
+        if !has_snapshot {
+            self.is_isolated = false;
+        }
+
+        // Then apply the writes to the latest state. Read-only transactions have no effect.
+        if !written.is_empty() {
+            let mut state = self.states.last().unwrap().clone(); // never empty
+            for (op, ret) in &txn.ops {
+                if RefObj::written_key(op, ret).is_some() {
+                    state.invoke(op);
+                }
+            }
+            self.states.push(state);
+            self.writes.push(written);
+        }
+        Ok(self)
+    }
+
+    /// Indicates that a transaction aborted, so its operations must have had no effect. Returns
+    /// `Ok(...)` if the history is valid, even if it does not adhere to snapshot isolation.
+    ///
+    /// See [`SnapshotIsolationTester::is_snapshot_isolated`].
+    pub fn on_abort(&mut self, txn_id: T) -> Result<&mut Self, String> {
+        if !self.is_valid_history {
+            return Err("Earlier history was invalid.".to_string());
+        }
+        match self.in_progress.remove(&txn_id) {
+            None => {
+                self.is_valid_history = false;
+                Err(format!(
+                    "There is no transaction in progress to abort. txn_id={:?}", txn_id))
+            }
+            Some(_) => {
+                self.aborted_count += 1;
+                Ok(self)
+            }
+        }
+    }
+}
+
+impl<T: Ord, RefObj> Default for SnapshotIsolationTester<T, RefObj>
+where RefObj: Default + TransactionalSpec
+{
+    fn default() -> Self {
+        Self::new(RefObj::default())
+    }
+}
+
+impl<T, RefObj> serde::Serialize for SnapshotIsolationTester<T, RefObj>
+where RefObj: serde::Serialize + TransactionalSpec,
+      RefObj::Key: serde::Serialize,
+      RefObj::Op: serde::Serialize,
+      RefObj::Ret: serde::Serialize,
+      T: Ord + serde::Serialize,
+{
+    fn serialize<Ser: serde::Serializer>(&self, ser: Ser) -> Result<Ser::Ok, Ser::Error> {
+        use serde::ser::SerializeStruct;
+        let mut out = ser.serialize_struct("SnapshotIsolationTester", 7)?;
+        out.serialize_field("states", &self.states)?;
+        out.serialize_field("writes", &self.writes)?;
+        out.serialize_field("in_progress", &self.in_progress)?;
+        out.serialize_field("committed", &self.committed)?;
+        out.serialize_field("aborted_count", &self.aborted_count)?;
+        out.serialize_field("is_isolated", &self.is_isolated)?;
+        out.serialize_field("is_valid_history", &self.is_valid_history)?;
+        out.end()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::semantics::map::*;
+    use crate::semantics::register::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn rejects_invalid_history() -> Result<(), String> {
+        assert_eq!(
+            SnapshotIsolationTester::new(Register('A'))
+                .on_begin(1)?
+                .on_commit(1)?
+                .on_begin(1),
+            Err("Transaction already committed. txn_id=1".to_string()));
+        assert_eq!(
+            SnapshotIsolationTester::new(Register('A'))
+                .on_commit(1),
+            Err("There is no transaction in progress to commit. txn_id=1".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn allows_write_skew() -> Result<(), String> {
+        assert!(
+            SnapshotIsolationTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Get('x'), MapRet::GetOk(None))?
+                .on_op(2, MapOp::Get('y'), MapRet::GetOk(None))?
+                .on_op(1, MapOp::Put('y', 1), MapRet::PutOk)?
+                .on_op(2, MapOp::Put('x', 2), MapRet::PutOk)?
+                .on_commit(1)?
+                .on_commit(2)?
+                .is_snapshot_isolated());
+        Ok(())
+    }
+
+    #[test]
+    fn requires_first_committer_wins() -> Result<(), String> {
+        // Lost update: both transactions increment the same key from the same snapshot.
+        assert!(
+            !SnapshotIsolationTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Get('x'), MapRet::GetOk(None))?
+                .on_op(2, MapOp::Get('x'), MapRet::GetOk(None))?
+                .on_op(1, MapOp::Put('x', 1), MapRet::PutOk)?
+                .on_op(2, MapOp::Put('x', 1), MapRet::PutOk)?
+                .on_commit(1)?
+                .on_commit(2)?
+                .is_snapshot_isolated());
+
+        // Fine if the second aborts.
+        assert!(
+            SnapshotIsolationTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Put('x', 1), MapRet::PutOk)?
+                .on_op(2, MapOp::Put('x', 2), MapRet::PutOk)?
+                .on_commit(1)?
+                .on_abort(2)?
+                .is_snapshot_isolated());
+
+        // Also fine if the second takes its snapshot after the first commits.
+        assert!(
+            SnapshotIsolationTester::new(BTreeMap::new())
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, MapOp::Put('x', 1), MapRet::PutOk)?
+                .on_commit(1)?
+                .on_op(2, MapOp::Get('x'), MapRet::GetOk(Some(1)))?
+                .on_op(2, MapOp::Put('x', 2), MapRet::PutOk)?
+                .on_commit(2)?
+                .is_snapshot_isolated());
+        Ok(())
+    }
+
+    #[test]
+    fn requires_reads_from_snapshot() -> Result<(), String> {
+        // Reads own writes.
+        assert!(
+            SnapshotIsolationTester::new(Register('A'))
+                .on_begin(1)?
+                .on_op(1, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_commit(1)?
+                .is_snapshot_isolated());
+
+        // Cannot observe a write committed after the snapshot.
+        assert!(
+            !SnapshotIsolationTester::new(Register('A'))
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('A'))?
+                .on_op(2, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_commit(2)?
+                .on_op(1, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_commit(1)?
+                .is_snapshot_isolated());
+
+        // Cannot observe an uncommitted write.
+        assert!(
+            !SnapshotIsolationTester::new(Register('A'))
+                .on_begin(1)?.on_begin(2)?
+                .on_op(1, RegisterOp::Write('B'), RegisterRet::WriteOk)?
+                .on_op(2, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_commit(2)?
+                .on_commit(1)?
+                .is_snapshot_isolated());
+
+        // Later transactions observe committed writes.
+        assert!(
+            SnapshotIsolationTester::new(Register('A'))
+                .on_begin(1)?
+                .on_op(1, RegisterOp::Cas('A', 'B'), RegisterRet::CasOk(true))?
+                .on_commit(1)?
+                .on_begin(2)?
+                .on_op(2, RegisterOp::Read, RegisterRet::ReadOk('B'))?
+                .on_commit(2)?
+                .is_snapshot_isolated());
+        Ok(())
+    }
+}
@@ -58,6 +58,7 @@ pub mod dgraph {
         inits: BTreeSet<u8>,
         edges: BTreeMap<u8, BTreeSet<u8>>,
         property: Property<DGraph>,
+        max_state: u8,
     }
 
     impl DGraph {
@@ -66,6 +67,7 @@ pub mod dgraph {
                 inits: Default::default(),
                 edges: Default::default(),
                 property,
+                max_state: u8::MAX,
             }
         }
 
@@ -86,9 +88,14 @@ pub mod dgraph {
                     edges
                 },
                 property: self.property.clone(),
+                max_state: self.max_state,
             }
         }
 
+        pub fn with_boundary(&self, max_state: u8) -> Self {
+            DGraph { max_state, .. self.clone() }
+        }
+
         pub fn check(&self) -> impl Checker<Self> {
             self.clone().checker().spawn_bfs().join()
         }
@@ -115,6 +122,10 @@ pub mod dgraph {
         fn properties(&self) -> Vec<Property<Self>> {
             vec![self.property.clone()]
         }
+
+        fn within_boundary(&self, state: &Self::State) -> bool {
+            *state <= self.max_state
+        }
     }
 }
 
@@ -127,6 +138,7 @@ pub mod linear_equation_solver {
     pub struct LinearEquation { pub a: u8, pub b: u8, pub c: u8 }
 
     #[derive(Clone, Debug, Eq, PartialEq)]
+    #[derive(serde::Serialize)]
     pub enum Guess { IncreaseX, IncreaseY }
 
     impl Model for LinearEquation {
@@ -1,4 +1,5 @@
-//! Utilities such as [`HashableHashSet`] and [`HashableHashMap`]. Those two in particular are useful
+//! Utilities such as [`HashableHashSet`], [`HashableHashMap`], [`VectorClock`], and the
+//! [`choose!`](crate::choose) helpers for enumerating nondeterministic choices. The first two in particular are useful
 //! because the corresponding [`HashSet`] and [`HashMap`] do not implement [`Hash`], meaning they cannot
 //! be used directly in models.
 //!
@@ -51,8 +52,13 @@
 //! # let checker = MyModel.checker().spawn_bfs().join();
 //! ```
 
+mod choose;
+pub use choose::*;
+pub use crate::stable::{BuildStableHasher, StableHasher};
+
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher, BuildHasher};
 use std::ops::{Deref, DerefMut};
@@ -117,22 +123,31 @@ impl<V: Eq + Hash, S: BuildHasher + Default> FromIterator<V> for HashableHashSet
 
 impl<V: Hash, S> Hash for HashableHashSet<V, S> {
     fn hash<H: Hasher>(&self, hasher: &mut H) {
-        BUFFER.with(|buffer| {
-            let mut buffer = buffer.borrow_mut();
-            buffer.clear();
-            buffer.extend(self.0.iter().map(|v| {
-                let mut inner_hasher = crate::stable::hasher();
-                v.hash(&mut inner_hasher);
-                inner_hasher.finish()
-            }));
-            buffer.sort_unstable();
-            for v in &*buffer {
-                hasher.write_u64(*v);
-            }
-        });
+        hash_unordered(self.0.iter(), hasher);
     }
 }
 
+/// Hashes items independently of their order by sorting pre-hashed items and feeding those back
+/// into the passed-in [`Hasher`].
+pub(crate) fn hash_unordered<'a, V: Hash + 'a, H: Hasher>(
+    items: impl Iterator<Item = &'a V>,
+    hasher: &mut H)
+{
+    BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.clear();
+        buffer.extend(items.map(|v| {
+            let mut inner_hasher = crate::stable::hasher();
+            v.hash(&mut inner_hasher);
+            inner_hasher.finish()
+        }));
+        buffer.sort_unstable();
+        for v in &*buffer {
+            hasher.write_u64(*v);
+        }
+    });
+}
+
 impl<'a, V, S> IntoIterator for &'a HashableHashSet<V, S> {
     type Item = &'a V;
     type IntoIter = std::collections::hash_set::Iter<'a, V>;
@@ -347,3 +362,181 @@ mod hashable_hash_map_test {
         assert_eq!(fp1, fp2);
     }
 }
+
+/// Renders a line-by-line diff between two (typically pretty-printed [`Debug`]) strings. Each
+/// line is prefixed by `"- "` if removed, `"+ "` if added, or `"  "` if unchanged.
+pub(crate) fn diff_lines(before: &str, after: &str) -> String {
+    let before: Vec<_> = before.lines().collect();
+    let after: Vec<_> = after.lines().collect();
+
+    // Only the lines between the common prefix and suffix need a longest common subsequence.
+    let prefix = before.iter().zip(&after).take_while(|(b, a)| b == a).count();
+    let suffix = before[prefix..].iter().rev().zip(after[prefix..].iter().rev())
+        .take_while(|(b, a)| b == a).count();
+    let removed = &before[prefix..before.len() - suffix];
+    let added = &after[prefix..after.len() - suffix];
+
+    // `lcs[i][j]` is the length of the longest common subsequence of `removed[i..]` and
+    // `added[j..]`.
+    let mut lcs = vec![vec![0; added.len() + 1]; removed.len() + 1];
+    for i in (0..removed.len()).rev() {
+        for j in (0..added.len()).rev() {
+            lcs[i][j] = if removed[i] == added[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let mut push = |marker: &str, line: &str| {
+        diff.push_str(marker);
+        diff.push_str(line);
+        diff.push('\n');
+    };
+    for line in &before[..prefix] { push("  ", line); }
+    let (mut i, mut j) = (0, 0);
+    while i < removed.len() || j < added.len() {
+        if i < removed.len() && j < added.len() && removed[i] == added[j] {
+            push("  ", removed[i]);
+            i += 1;
+            j += 1;
+        } else if j == added.len() || (i < removed.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            push("- ", removed[i]);
+            i += 1;
+        } else {
+            push("+ ", added[j]);
+            j += 1;
+        }
+    }
+    for line in &before[before.len() - suffix..] { push("  ", line); }
+    diff
+}
+
+#[cfg(test)]
+mod diff_lines_test {
+    use crate::util::diff_lines;
+
+    #[test]
+    fn marks_removed_and_added_lines() {
+        assert_eq!(diff_lines("a\nb\nc\n", "a\nb\nc\n"), "  a\n  b\n  c\n");
+        assert_eq!(
+            diff_lines("a\nb\nc\nd\n", "a\nx\nc\nd\ne\n"),
+            "  a\n- b\n+ x\n  c\n  d\n+ e\n");
+        assert_eq!(diff_lines("", "a\n"), "+ a\n");
+        assert_eq!(diff_lines("a\n", ""), "- a\n");
+    }
+}
+
+/// A vector clock, which counts events per participant (such as an actor [`Id`]) such that one
+/// clock precedes another (per [`PartialOrd`]) exactly when the events it reflects causally
+/// precede those of the other. Clocks that are neither equal nor ordered are concurrent.
+/// Participants without events are omitted, so clocks need not be initialized with every
+/// participant.
+///
+/// [`Id`]: crate::actor::Id
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct VectorClock<K: Ord>(BTreeMap<K, u64>);
+
+impl<K: Ord> VectorClock<K> {
+    /// Constructs a clock that reflects no events.
+    pub fn new() -> Self {
+        VectorClock(BTreeMap::new())
+    }
+
+    /// The number of events of a participant that the clock reflects.
+    pub fn get(&self, participant: &K) -> u64 {
+        self.0.get(participant).copied().unwrap_or(0)
+    }
+
+    /// Records an event of a participant.
+    pub fn increment(&mut self, participant: K) {
+        *self.0.entry(participant).or_insert(0) += 1;
+    }
+
+    /// Incorporates the events of another clock, taking the maximum count for each participant.
+    pub fn merge(&mut self, other: &Self)
+    where K: Clone
+    {
+        for (participant, &count) in &other.0 {
+            let entry = self.0.entry(participant.clone()).or_insert(0);
+            *entry = (*entry).max(count);
+        }
+    }
+
+    /// Indicates whether neither clock precedes the other.
+    pub fn is_concurrent(&self, other: &Self) -> bool {
+        self.partial_cmp(other).is_none()
+    }
+
+    /// Indicates whether a message from `sender` that is tagged with `clock` can be delivered to
+    /// a recipient whose clock is `self` while preserving causal order: the message must be the
+    /// next from the sender, and every event that it depends on must already be reflected.
+    pub fn is_next_from(&self, sender: &K, clock: &Self) -> bool {
+        clock.get(sender) == self.get(sender) + 1
+            && clock.0.iter().all(|(participant, &count)| participant == sender || count <= self.get(participant))
+    }
+}
+
+impl<K: Ord> Default for VectorClock<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord> PartialOrd for VectorClock<K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let is_le = self.0.iter().all(|(participant, &count)| count <= other.get(participant));
+        let is_ge = other.0.iter().all(|(participant, &count)| count <= self.get(participant));
+        match (is_le, is_ge) {
+            (true, true) => Some(Ordering::Equal),
+            (true, false) => Some(Ordering::Less),
+            (false, true) => Some(Ordering::Greater),
+            (false, false) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod vector_clock_test {
+    use crate::util::VectorClock;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn orders_by_causality() {
+        let mut a = VectorClock::new();
+        a.increment('a');
+        let mut b = a.clone();
+        b.increment('b');
+        assert_eq!(a.partial_cmp(&b), Some(Ordering::Less));
+        assert_eq!(b.partial_cmp(&a), Some(Ordering::Greater));
+        assert_eq!(a.partial_cmp(&a.clone()), Some(Ordering::Equal));
+
+        let mut c = a.clone();
+        c.increment('c');
+        assert!(b.is_concurrent(&c));
+        c.merge(&b);
+        assert_eq!(c.get(&'a'), 1);
+        assert_eq!(c.get(&'b'), 1);
+        assert!(b < c);
+    }
+
+    #[test]
+    fn identifies_next_message_from_sender() {
+        let mut sender = VectorClock::new();
+        sender.increment('s');
+        let mut recipient = VectorClock::new();
+        assert!(recipient.is_next_from(&'s', &sender));
+
+        // A message that depends on an undelivered message from another participant waits.
+        sender.increment('s');
+        sender.merge(&{ let mut other = VectorClock::new(); other.increment('o'); other });
+        assert!(!recipient.is_next_from(&'s', &sender));
+        recipient.increment('s');
+        assert!(!recipient.is_next_from(&'s', &sender));
+        recipient.increment('o');
+        assert!(recipient.is_next_from(&'s', &sender));
+    }
+}
@@ -0,0 +1,139 @@
+//! Private module for selective re-export.
+
+use std::collections::BTreeMap;
+
+/// Pushes an action for each combination of values drawn from the specified domains, which is
+/// useful for implementing [`Model::actions`] similarly to TLA+'s `\E x \in S: ...` without
+/// writing nested loops. Later domains can depend on earlier values, and an optional guard
+/// filters combinations. See also [`cartesian_product`], [`subsets`], and [`functions`] for
+/// building domains.
+///
+/// # Example
+///
+/// ```
+/// use stateright::choose;
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Action { Transfer { from: u8, to: u8, amount: u8 } }
+///
+/// let accounts = [0, 1, 2];
+/// let mut actions = Vec::new();
+/// choose!(actions,
+///         from in accounts,
+///         to in accounts,
+///         amount in 1..=from,
+///         if from != to
+///         => Action::Transfer { from, to, amount });
+/// assert_eq!(actions, vec![
+///     Action::Transfer { from: 1, to: 0, amount: 1 },
+///     Action::Transfer { from: 1, to: 2, amount: 1 },
+///     Action::Transfer { from: 2, to: 0, amount: 1 },
+///     Action::Transfer { from: 2, to: 0, amount: 2 },
+///     Action::Transfer { from: 2, to: 1, amount: 1 },
+///     Action::Transfer { from: 2, to: 1, amount: 2 },
+/// ]);
+/// ```
+///
+/// [`Model::actions`]: crate::Model::actions
+#[macro_export]
+macro_rules! choose {
+    ($actions:expr, $var:pat in $domain:expr $(, if $guard:expr)? => $action:expr) => {
+        for $var in $domain {
+            $(if !$guard { continue })?
+            $actions.push($action);
+        }
+    };
+    ($actions:expr, $var:pat in $domain:expr, $($rest:tt)+) => {
+        for $var in $domain {
+            $crate::choose!($actions, $($rest)+);
+        }
+    };
+}
+
+/// Every way to choose one value from each domain, like TLA+'s `S \X T` generalized to any
+/// number of domains.
+///
+/// ```
+/// # use stateright::util::cartesian_product;
+/// assert_eq!(
+///     cartesian_product(&[vec!['a', 'b'], vec!['x', 'y']]),
+///     vec![vec!['a', 'x'], vec!['a', 'y'], vec!['b', 'x'], vec!['b', 'y']]);
+/// ```
+pub fn cartesian_product<T: Clone>(domains: &[Vec<T>]) -> Vec<Vec<T>> {
+    let mut products = vec![Vec::new()];
+    for domain in domains {
+        products = products.into_iter()
+            .flat_map(|product| domain.iter().map(move |value| {
+                let mut product = product.clone();
+                product.push(value.clone());
+                product
+            }))
+            .collect();
+    }
+    products
+}
+
+/// Every subset of the items, like TLA+'s `SUBSET S`. Items within each subset retain their
+/// order.
+///
+/// ```
+/// # use stateright::util::subsets;
+/// assert_eq!(
+///     subsets(&[1, 2, 3]),
+///     vec![vec![], vec![1], vec![2], vec![1, 2], vec![3], vec![1, 3], vec![2, 3], vec![1, 2, 3]]);
+/// ```
+pub fn subsets<T: Clone>(items: &[T]) -> Vec<Vec<T>> {
+    assert!(items.len() < usize::BITS as usize, "too many items for subsets");
+    (0..1_usize << items.len())
+        .map(|mask| {
+            items.iter().enumerate()
+                .filter(|(i, _)| mask & (1 << i) != 0)
+                .map(|(_, item)| item.clone())
+                .collect()
+        })
+        .collect()
+}
+
+/// Every function from the domain to the codomain, like TLA+'s `[S -> T]`.
+///
+/// ```
+/// # use stateright::util::functions;
+/// let fs = functions(&["x", "y"], &[false, true]);
+/// assert_eq!(fs.len(), 4);
+/// assert_eq!(fs[1].get("x"), Some(&false));
+/// assert_eq!(fs[1].get("y"), Some(&true));
+/// ```
+pub fn functions<K, V>(domain: &[K], codomain: &[V]) -> Vec<BTreeMap<K, V>>
+where K: Clone + Ord,
+      V: Clone,
+{
+    let codomains = vec![codomain.to_vec(); domain.len()];
+    cartesian_product(&codomains).into_iter()
+        .map(|values| domain.iter().cloned().zip(values).collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn handles_empty_domains() {
+        assert_eq!(cartesian_product::<u8>(&[]), vec![Vec::<u8>::new()]);
+        assert_eq!(cartesian_product(&[vec![1], vec![]]), Vec::<Vec<u8>>::new());
+        assert_eq!(subsets::<u8>(&[]), vec![Vec::<u8>::new()]);
+        assert_eq!(functions::<u8, u8>(&[], &[1, 2]), vec![BTreeMap::new()]);
+        assert_eq!(functions::<u8, u8>(&[1, 2], &[]), Vec::<BTreeMap<u8, u8>>::new());
+    }
+
+    #[test]
+    fn can_choose_actions() {
+        let mut actions = Vec::new();
+        choose!(actions, x in 0..2, (y, z) in vec![(x, 'a'), (x + 10, 'b')] => (x, y, z));
+        assert_eq!(actions, vec![(0, 0, 'a'), (0, 10, 'b'), (1, 1, 'a'), (1, 11, 'b')]);
+
+        let mut actions = Vec::new();
+        choose!(actions, subset in subsets(&[1, 2, 3]), if subset.len() == 2 => subset);
+        assert_eq!(actions, vec![vec![1, 2], vec![1, 3], vec![2, 3]]);
+    }
+}
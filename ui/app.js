@@ -1,16 +1,19 @@
-/// Represents the checker status. Reloads periodically until checking completes.
-function Status({discoveries, done, generated, model, recent_path}) {
+/// Represents the checker status. Reloads as live updates arrive until checking completes.
+function Status({discoveries, done, generated, model, predicates, recent_path}) {
     let status = this;
 
     status.discoveries = discoveries;
     status.generated = generated;
+    status.predicates = predicates;
     status.model = model
         .replace('stateright::actor::register::', '')
         .replace('stateright::actor::system::', '')
         .replace('stateright::actor::', '')
         .replace('stateright::', '');
     status.progress = 'Done';
-    if (!done) {
+    if (!done && !recent_path) {
+        status.progress = 'Checking';
+    } else if (!done) {
         status.progress = recent_path.length < 100
             ? recent_path
             : recent_path.substring(0, 99 - 3) + '...';
@@ -23,15 +26,21 @@ Status.LOADING = new Status({
     done: 'loading...',
     generated: 'loading...',
     model: 'loading...',
+    predicates: [],
     recent_path: 'loading...',
 });
 
 /// Represents a model step. Only loads next steps on demand.
-function Step({action, outcome, state, fingerprint, prevStep, svg}) {
+function Step({action, outcome, diff, state, fingerprint, prevStep, svg}) {
     let step = this;
 
     step.action = action || `Init ${i}`;
     step.outcome = outcome;
+    step.diff = diff;
+    step.diffLines = (diff || '').split('\n').filter(line => line).map(line => ({
+        text: line,
+        css: {'is-added': line.startsWith('+'), 'is-removed': line.startsWith('-')},
+    }));
     step.state = state;
     step.svg = svg;
     step.fingerprint = fingerprint;
@@ -56,6 +65,7 @@ function Step({action, outcome, state, fingerprint, prevStep, svg}) {
                 return nextSteps.map((nextStep, i) => new Step({
                     action: nextStep.action || `Init ${i}`,
                     outcome: nextStep.outcome,
+                    diff: nextStep.diff,
                     state: nextStep.state,
                     svg: nextStep.svg,
                     fingerprint: nextStep.fingerprint,
@@ -86,12 +96,29 @@ function App() {
     app.selectedStep = ko.observable(Step.PRE_INIT);
     app.isCompact = ko.observable(false);
     app.isCompleteState = ko.observable(false);
+    app.isDiff = ko.observable(true);
     app.isStepNoOp = (step) => step.state == app.selectedStep().state;
     app.status = ko.observable(Status.LOADING);
+    app.freshDiscoveries = ko.observableArray([]);
+    app.searchPredicate = ko.observable();
+    app.searchError = ko.observable('');
+    app.search = async () => {
+        let predicate = encodeURIComponent(app.searchPredicate());
+        let response = await fetch(`/.search${app.selectedStep().path}?predicate=${predicate}`);
+        let text = await response.text();
+        if (!response.ok) { return app.searchError(text); }
+        app.searchError('');
+        window.location.hash = '#/steps' + text;
+    };
+    app.copyPermalink = () => navigator.clipboard.writeText(window.location.href);
+    app.copySnippet = async () => {
+        let response = await fetch(`/.snippet${app.selectedStep().path}`);
+        await navigator.clipboard.writeText(await response.text());
+    };
 
     window.onhashchange = prepareView;
     window.onhashchange();
-    refreshStatus();
+    refreshStatus().then(done => done || subscribeToUpdates());
 
     async function refreshStatus() {
         console.log('Refreshing status.');
@@ -99,10 +126,42 @@ function App() {
         let json = await response.json();
         console.log(json);
         app.status(new Status(json));
-        if (!json.done) {
-            setTimeout(refreshStatus, 5000);
+        return json.done;
+    }
+    async function pollStatus() {
+        if (!await refreshStatus()) {
+            setTimeout(pollStatus, 5000);
         }
     }
+    function subscribeToUpdates() {
+        let protocol = window.location.protocol == 'https:' ? 'wss:' : 'ws:';
+        let socket = new WebSocket(`${protocol}//${window.location.host}/.live`);
+        let isDone = false;
+        socket.onmessage = (event) => {
+            let update = JSON.parse(event.data);
+            let names = Object.keys(update.new_discoveries);
+            if (names.length) {
+                console.log('New discoveries.', update.new_discoveries);
+                app.freshDiscoveries.unshift(...names.map(name => ({
+                    name,
+                    path: update.new_discoveries[name],
+                })));
+            }
+            isDone = update.done;
+            if (names.length || isDone) {
+                refreshStatus();
+            } else {
+                app.status().generated = update.generated;
+                app.status.valueHasMutated();
+            }
+        };
+        socket.onclose = () => {
+            if (!isDone) {
+                console.log('Live updates unavailable. Polling status.');
+                pollStatus();
+            }
+        };
+    }
     async function prepareView() {
         let hash = window.location.hash;
         console.log('Hash changed. Preparing view.', {hash});
@@ -121,7 +180,12 @@ function App() {
                     let nextSteps = await step.fetchNextSteps();
                     let nextFingerprint = components.shift();
                     if (!nextFingerprint) { return app.selectedStep(step); }
-                    step = nextSteps.find(step => step.fingerprint == nextFingerprint);
+                    let nextStep = nextSteps.find(step => step.fingerprint == nextFingerprint);
+                    if (!nextStep) {
+                        console.log('Unknown fingerprint. Stopping at last known step.', {nextFingerprint});
+                        return app.selectedStep(step);
+                    }
+                    step = nextStep;
                 }
             default: throw new Error(`Invalid view: '${view}'`);
         }